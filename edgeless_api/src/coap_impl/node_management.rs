@@ -57,4 +57,19 @@ impl crate::node_management::NodeManagementAPI for super::CoapClient {
             Err(err) => Err(anyhow::anyhow!(String::from_utf8(err).unwrap())),
         }
     }
+    async fn sync_state(&mut self, _instance_id: crate::function_instance::InstanceId) -> anyhow::Result<()> {
+        anyhow::bail!("state management not supported with CoAP");
+    }
+    async fn hand_over(&mut self, _request: crate::node_management::HandOverRequest) -> anyhow::Result<()> {
+        anyhow::bail!("state management not supported with CoAP");
+    }
+    async fn get_state(
+        &mut self,
+        _state_specification: crate::function_instance::StateSpecification,
+    ) -> anyhow::Result<Option<crate::node_management::SerializedState>> {
+        anyhow::bail!("state management not supported with CoAP");
+    }
+    async fn set_state(&mut self, _request: crate::node_management::SetStateRequest) -> anyhow::Result<()> {
+        anyhow::bail!("state management not supported with CoAP");
+    }
 }
@@ -20,6 +20,18 @@ pub enum StartComponentResponse<InstanceIdType> {
 pub struct PatchRequest {
     pub function_id: ComponentId,
     pub output_mapping: std::collections::HashMap<String, InstanceId>,
+    /// Output channels whose events are split among multiple targets,
+    /// e.g., two versions of the same function during a canary upgrade.
+    /// The target in `output_mapping` is used for the channels not found here.
+    pub output_splits: std::collections::HashMap<String, Vec<WeightedTarget>>,
+}
+
+/// Target of an output channel that receives a fraction of its events
+/// proportional to the weight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedTarget {
+    pub instance_id: InstanceId,
+    pub weight: u32,
 }
 
 impl std::fmt::Display for ResponseError {
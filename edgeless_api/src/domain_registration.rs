@@ -81,6 +81,10 @@ pub struct UpdateDomainRequest {
     // allows the domain register to detect service restarts.
     // It is drawn randomly when the node starts and never changes.
     pub nonce: u64,
+    // Logical identifiers of the functions active in the domain.
+    pub active_functions: std::collections::HashSet<uuid::Uuid>,
+    // Logical identifiers of the resources active in the domain.
+    pub active_resources: std::collections::HashSet<uuid::Uuid>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -31,6 +31,44 @@ impl Default for StateSpecification {
     }
 }
 
+impl StateSpecification {
+    /// Build the state specification of a function from its annotations:
+    ///
+    /// - `state_policy`: one of `transient`, `node-local` (default), `global`;
+    /// - `state_id`: UUID of the state, only used with the global policy.
+    ///
+    /// With the global policy, if the `state_id` is not specified then it
+    /// is derived from the workflow identifier and the function name, so
+    /// that all the instances of the same function share the same state.
+    pub fn from_annotations(
+        workflow_id: &uuid::Uuid,
+        function_name: &str,
+        annotations: &std::collections::HashMap<String, String>,
+    ) -> anyhow::Result<Self> {
+        let state_policy = match annotations.get("state_policy").map(|x| x.as_str()) {
+            None | Some("node-local") => StatePolicy::NodeLocal,
+            Some("transient") => StatePolicy::Transient,
+            Some("global") => StatePolicy::Global,
+            Some(val) => anyhow::bail!("invalid state_policy annotation: {}", val),
+        };
+        let state_id = match state_policy {
+            StatePolicy::Global => match annotations.get("state_id") {
+                Some(val) => uuid::Uuid::parse_str(val)?,
+                None => {
+                    // 64-bit FNV-1a hash of the function name.
+                    let hash = function_name
+                        .as_bytes()
+                        .iter()
+                        .fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3));
+                    uuid::Uuid::from_u128(workflow_id.as_u128() ^ (((hash as u128) << 64) | hash as u128))
+                }
+            },
+            _ => uuid::Uuid::new_v4(),
+        };
+        Ok(Self { state_id, state_policy })
+    }
+}
+
 impl FunctionClassSpecification {
     pub fn to_short_string(&self) -> String {
         format!(
@@ -103,3 +141,40 @@ impl Clone for Box<dyn FunctionInstanceAPI<crate::function_instance::DomainManag
         self.clone_box()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_state_specification_from_annotations() {
+        let wf_id = uuid::Uuid::new_v4();
+        let make = |name: &str, annotations: &[(&str, &str)]| {
+            StateSpecification::from_annotations(
+                &wf_id,
+                name,
+                &annotations.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            )
+        };
+
+        assert_eq!(StatePolicy::NodeLocal, make("f1", &[]).unwrap().state_policy);
+        assert_eq!(StatePolicy::NodeLocal, make("f1", &[("state_policy", "node-local")]).unwrap().state_policy);
+        assert_eq!(StatePolicy::Transient, make("f1", &[("state_policy", "transient")]).unwrap().state_policy);
+        assert!(make("f1", &[("state_policy", "unknown")]).is_err());
+
+        // Global state identifiers are stable for the same function.
+        let f1_global = make("f1", &[("state_policy", "global")]).unwrap();
+        assert_eq!(StatePolicy::Global, f1_global.state_policy);
+        assert_eq!(f1_global, make("f1", &[("state_policy", "global")]).unwrap());
+        assert_ne!(f1_global, make("f2", &[("state_policy", "global")]).unwrap());
+
+        let state_id = uuid::Uuid::new_v4();
+        assert_eq!(
+            state_id,
+            make("f1", &[("state_policy", "global"), ("state_id", state_id.to_string().as_str())])
+                .unwrap()
+                .state_id
+        );
+        assert!(make("f1", &[("state_policy", "global"), ("state_id", "not-a-uuid")]).is_err());
+    }
+}
@@ -113,6 +113,26 @@ impl CommonConverters {
                     Err(_) => None,
                 })
                 .collect(),
+            output_splits: api_update
+                .output_splits
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        key.clone(),
+                        value
+                            .targets
+                            .iter()
+                            .filter_map(|target| match target.instance_id.as_ref().map(CommonConverters::parse_instance_id) {
+                                Some(Ok(instance_id)) => Some(crate::common::WeightedTarget {
+                                    instance_id,
+                                    weight: target.weight,
+                                }),
+                                _ => None,
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
         })
     }
 
@@ -168,6 +188,24 @@ impl CommonConverters {
                 .iter()
                 .map(|(key, value)| (key.clone(), CommonConverters::serialize_instance_id(value)))
                 .collect(),
+            output_splits: crate_update
+                .output_splits
+                .iter()
+                .map(|(key, value)| {
+                    (
+                        key.clone(),
+                        crate::grpc_impl::api::WeightedTargets {
+                            targets: value
+                                .iter()
+                                .map(|target| crate::grpc_impl::api::WeightedTarget {
+                                    instance_id: Some(CommonConverters::serialize_instance_id(&target.instance_id)),
+                                    weight: target.weight,
+                                })
+                                .collect(),
+                        },
+                    )
+                })
+                .collect(),
         }
     }
 }
@@ -178,6 +216,7 @@ impl From<&edgeless_api_core::event_metadata::EventMetadata> for crate::grpc_imp
         Self {
             trace_id: _words.to_vec(),
             span_id: value.span_id().to_bytes().to_vec(),
+            deadline: value.deadline().map(|deadline| CommonConverters::serialize_event_timestamp(&deadline)),
         }
     }
 }
@@ -188,7 +227,11 @@ impl TryFrom<&crate::grpc_impl::api::EventSerializedMetadata> for edgeless_api_c
     fn try_from(value: &crate::grpc_impl::api::EventSerializedMetadata) -> Result<Self, Self::Error> {
         let trace_id: [u8; 16] = value.clone().trace_id.try_into().map_err(|_| anyhow::anyhow!("Mismatched length"))?;
         let span_id: [u8; 8] = value.clone().span_id.try_into().map_err(|_| anyhow::anyhow!("Mismatched length"))?;
-        Ok(Self::from_bytes(trace_id, span_id))
+        let deadline = match &value.deadline {
+            Some(deadline) => Some(CommonConverters::parse_event_timestamp(deadline)?),
+            None => None,
+        };
+        Ok(Self::from_bytes(trace_id, span_id).with_deadline(deadline))
     }
 }
 
@@ -198,7 +241,7 @@ mod tests {
     use edgeless_api_core::instance_id::InstanceId;
 
     use super::*;
-    use crate::common::PatchRequest;
+    use crate::common::{PatchRequest, WeightedTarget};
     use crate::grpc_impl::api::EventSerializedMetadata;
 
     #[test]
@@ -222,6 +265,7 @@ mod tests {
                         },
                     ),
                 ]),
+                output_splits: std::collections::HashMap::new(),
             },
             PatchRequest {
                 function_id: uuid::Uuid::new_v4(),
@@ -241,6 +285,36 @@ mod tests {
                         },
                     ),
                 ]),
+                output_splits: std::collections::HashMap::new(),
+            },
+            PatchRequest {
+                function_id: uuid::Uuid::new_v4(),
+                output_mapping: std::collections::HashMap::from([(
+                    "out".to_string(),
+                    InstanceId {
+                        node_id: uuid::Uuid::new_v4(),
+                        function_id: uuid::Uuid::new_v4(),
+                    },
+                )]),
+                output_splits: std::collections::HashMap::from([(
+                    "out".to_string(),
+                    vec![
+                        WeightedTarget {
+                            instance_id: InstanceId {
+                                node_id: uuid::Uuid::new_v4(),
+                                function_id: uuid::Uuid::new_v4(),
+                            },
+                            weight: 90,
+                        },
+                        WeightedTarget {
+                            instance_id: InstanceId {
+                                node_id: uuid::Uuid::new_v4(),
+                                function_id: uuid::Uuid::new_v4(),
+                            },
+                            weight: 10,
+                        },
+                    ],
+                )]),
             },
         ];
         for msg in messages {
@@ -260,6 +334,7 @@ mod tests {
             EventMetadata::from_uints(1, 2),
             EventMetadata::from_uints(0x42a42bdecaf00005u128, 0x42a42bdecaf00006u64),
             EventMetadata::from_uints(u128::MAX, u64::MAX),
+            EventMetadata::from_uints(1, 2).with_deadline(Some(crate::function_instance::EventTimestamp { secs: 1000, nsecs: 42 })),
         ];
         for some_i in inputs {
             let ser = EventSerializedMetadata::from(&some_i);
@@ -156,6 +156,16 @@ fn parse_update_domain_request(
         refresh_deadline: std::time::UNIX_EPOCH + std::time::Duration::from_secs(api_instance.refresh_deadline),
         counter: api_instance.counter,
         nonce: api_instance.nonce,
+        active_functions: api_instance
+            .active_functions
+            .iter()
+            .map(|lid| uuid::Uuid::parse_str(lid))
+            .collect::<Result<_, _>>()?,
+        active_resources: api_instance
+            .active_resources
+            .iter()
+            .map(|lid| uuid::Uuid::parse_str(lid))
+            .collect::<Result<_, _>>()?,
     })
 }
 
@@ -204,6 +214,8 @@ fn serialize_update_domain_request(req: &crate::domain_registration::UpdateDomai
         refresh_deadline: req.refresh_deadline.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs(),
         counter: req.counter,
         nonce: req.nonce,
+        active_functions: req.active_functions.iter().map(|lid| lid.to_string()).collect(),
+        active_resources: req.active_resources.iter().map(|lid| lid.to_string()).collect(),
     }
 }
 
@@ -227,6 +239,8 @@ mod test {
                 refresh_deadline: std::time::UNIX_EPOCH + std::time::Duration::from_secs(313714800),
                 counter: 1,
                 nonce: 2,
+                active_functions: std::collections::HashSet::new(),
+                active_resources: std::collections::HashSet::new(),
             },
             UpdateDomainRequest {
                 domain_id: "my-domain".to_string(),
@@ -249,6 +263,8 @@ mod test {
                 refresh_deadline: std::time::UNIX_EPOCH + std::time::Duration::from_secs(313714800),
                 counter: 42,
                 nonce: u64::MAX,
+                active_functions: std::collections::HashSet::from([uuid::Uuid::new_v4(), uuid::Uuid::new_v4()]),
+                active_resources: std::collections::HashSet::from([uuid::Uuid::new_v4()]),
             },
         ];
         for msg in messages {
@@ -187,6 +187,7 @@ pub fn parse_call_return(api_instance: &crate::grpc_impl::api::CallReturn) -> an
             Ok(crate::guest_api_function::CallReturn::Reply(api_instance.msg.clone()))
         }
         x if x == crate::grpc_impl::api::CallRetType::CallRetErr as i32 => Ok(crate::guest_api_function::CallReturn::Err),
+        x if x == crate::grpc_impl::api::CallRetType::CallRetTimeout as i32 => Ok(crate::guest_api_function::CallReturn::Timeout),
         x => Err(anyhow::anyhow!("Ill-formed CallReturn message: unknown type {}", x)),
     }
 }
@@ -226,6 +227,10 @@ pub fn serialize_call_return(ret: &crate::guest_api_function::CallReturn) -> cra
             r#type: crate::grpc_impl::api::CallRetType::CallRetErr as i32,
             msg: vec![],
         },
+        crate::guest_api_function::CallReturn::Timeout => crate::grpc_impl::api::CallReturn {
+            r#type: crate::grpc_impl::api::CallRetType::CallRetTimeout as i32,
+            msg: vec![],
+        },
     }
 }
 
@@ -305,6 +310,7 @@ mod test {
             CallReturn::Reply(vec![]),
             CallReturn::Reply(vec![0, 42, 0, 42, 99]),
             CallReturn::Err,
+            CallReturn::Timeout,
         ];
         for msg in messages {
             match parse_call_return(&serialize_call_return(&msg)) {
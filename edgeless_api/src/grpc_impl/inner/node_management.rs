@@ -93,6 +93,99 @@ impl crate::node_management::NodeManagementAPI for NodeManagementClient {
             }
         }
     }
+    async fn sync_state(&mut self, instance_id: crate::function_instance::InstanceId) -> anyhow::Result<()> {
+        match self.try_connect().await {
+            Ok(_) => {
+                if let Some(client) = &mut self.client {
+                    match client
+                        .sync_state(tonic::Request::new(crate::grpc_impl::common::CommonConverters::serialize_instance_id(
+                            &instance_id,
+                        )))
+                        .await
+                    {
+                        Err(err) => {
+                            self.disconnect();
+                            anyhow::bail!("Error when synchronizing state at {}: {}", self.server_addr, err);
+                        }
+                        _ => Ok(()),
+                    }
+                } else {
+                    panic!("The impossible happened");
+                }
+            }
+            Err(err) => {
+                anyhow::bail!("Error when connecting to {}: {}", self.server_addr, err);
+            }
+        }
+    }
+    async fn hand_over(&mut self, request: crate::node_management::HandOverRequest) -> anyhow::Result<()> {
+        match self.try_connect().await {
+            Ok(_) => {
+                if let Some(client) = &mut self.client {
+                    match client.hand_over(tonic::Request::new(serialize_hand_over_request(&request))).await {
+                        Err(err) => {
+                            self.disconnect();
+                            anyhow::bail!("Error when handing over events at {}: {}", self.server_addr, err);
+                        }
+                        _ => Ok(()),
+                    }
+                } else {
+                    panic!("The impossible happened");
+                }
+            }
+            Err(err) => {
+                anyhow::bail!("Error when connecting to {}: {}", self.server_addr, err);
+            }
+        }
+    }
+    async fn get_state(
+        &mut self,
+        state_specification: crate::function_instance::StateSpecification,
+    ) -> anyhow::Result<Option<crate::node_management::SerializedState>> {
+        match self.try_connect().await {
+            Ok(_) => {
+                if let Some(client) = &mut self.client {
+                    match client
+                        .get_state(tonic::Request::new(super::function_instance::serialize_state_specification(
+                            &state_specification,
+                        )))
+                        .await
+                    {
+                        Err(err) => {
+                            self.disconnect();
+                            anyhow::bail!("Error when getting state at {}: {}", self.server_addr, err);
+                        }
+                        Ok(res) => Ok(parse_serialized_state(&res.into_inner())),
+                    }
+                } else {
+                    panic!("The impossible happened");
+                }
+            }
+            Err(err) => {
+                anyhow::bail!("Error when connecting to {}: {}", self.server_addr, err);
+            }
+        }
+    }
+    async fn set_state(&mut self, request: crate::node_management::SetStateRequest) -> anyhow::Result<()> {
+        match self.try_connect().await {
+            Ok(_) => {
+                if let Some(client) = &mut self.client {
+                    match client.set_state(tonic::Request::new(serialize_set_state_request(&request))).await {
+                        Err(err) => {
+                            self.disconnect();
+                            anyhow::bail!("Error when setting state at {}: {}", self.server_addr, err);
+                        }
+                        _ => Ok(()),
+                    }
+                } else {
+                    panic!("The impossible happened");
+                }
+            }
+            Err(err) => {
+                anyhow::bail!("Error when connecting to {}: {}", self.server_addr, err);
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -119,6 +212,73 @@ impl crate::grpc_impl::api::node_management_server::NodeManagement for NodeManag
             Err(err) => Err(tonic::Status::internal(format!("Error when resetting: {}", err))),
         }
     }
+    async fn sync_state(&self, request: tonic::Request<crate::grpc_impl::api::InstanceId>) -> Result<tonic::Response<()>, tonic::Status> {
+        let parsed_request = match crate::grpc_impl::common::CommonConverters::parse_instance_id(&request.into_inner()) {
+            Ok(parsed_request) => parsed_request,
+            Err(err) => {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "Error when parsing an InstanceId message: {}",
+                    err
+                )));
+            }
+        };
+        // The lock is not held while the function instance synchronizes.
+        let mut node_management_api = self.node_management_api.lock().await.clone();
+        match node_management_api.sync_state(parsed_request).await {
+            Ok(_) => Ok(tonic::Response::new(())),
+            Err(err) => Err(tonic::Status::internal(format!("Error when synchronizing state: {}", err))),
+        }
+    }
+    async fn hand_over(&self, request: tonic::Request<crate::grpc_impl::api::HandOverRequest>) -> Result<tonic::Response<()>, tonic::Status> {
+        let parsed_request = match parse_hand_over_request(&request.into_inner()) {
+            Ok(parsed_request) => parsed_request,
+            Err(err) => {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "Error when parsing a HandOverRequest message: {}",
+                    err
+                )));
+            }
+        };
+        // The lock is not held while the function instance hands over the events.
+        let mut node_management_api = self.node_management_api.lock().await.clone();
+        match node_management_api.hand_over(parsed_request).await {
+            Ok(_) => Ok(tonic::Response::new(())),
+            Err(err) => Err(tonic::Status::internal(format!("Error when handing over events: {}", err))),
+        }
+    }
+    async fn get_state(
+        &self,
+        request: tonic::Request<crate::grpc_impl::api::StateSpecification>,
+    ) -> Result<tonic::Response<crate::grpc_impl::api::SerializedState>, tonic::Status> {
+        let parsed_request = match super::function_instance::parse_state_specification(&request.into_inner()) {
+            Ok(parsed_request) => parsed_request,
+            Err(err) => {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "Error when parsing a StateSpecification message: {}",
+                    err
+                )));
+            }
+        };
+        match self.node_management_api.lock().await.get_state(parsed_request).await {
+            Ok(serialized_state) => Ok(tonic::Response::new(serialize_serialized_state(&serialized_state))),
+            Err(err) => Err(tonic::Status::internal(format!("Error when getting state: {}", err))),
+        }
+    }
+    async fn set_state(&self, request: tonic::Request<crate::grpc_impl::api::SetStateRequest>) -> Result<tonic::Response<()>, tonic::Status> {
+        let parsed_request = match parse_set_state_request(&request.into_inner()) {
+            Ok(parsed_request) => parsed_request,
+            Err(err) => {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "Error when parsing a SetStateRequest message: {}",
+                    err
+                )));
+            }
+        };
+        match self.node_management_api.lock().await.set_state(parsed_request).await {
+            Ok(_) => Ok(tonic::Response::new(())),
+            Err(err) => Err(tonic::Status::internal(format!("Error when setting state: {}", err))),
+        }
+    }
 }
 
 fn parse_update_peers_request(
@@ -176,9 +336,70 @@ fn serialize_update_peers_request(req: &crate::node_management::UpdatePeersReque
     }
 }
 
+fn parse_serialized_state(api_instance: &crate::grpc_impl::api::SerializedState) -> Option<crate::node_management::SerializedState> {
+    api_instance
+        .serialized_state
+        .as_ref()
+        .map(|serialized_state| crate::node_management::SerializedState {
+            serialized_state: serialized_state.clone(),
+            version: api_instance.version,
+        })
+}
+
+fn serialize_serialized_state(req: &Option<crate::node_management::SerializedState>) -> crate::grpc_impl::api::SerializedState {
+    match req {
+        Some(state) => crate::grpc_impl::api::SerializedState {
+            serialized_state: Some(state.serialized_state.clone()),
+            version: state.version,
+        },
+        None => crate::grpc_impl::api::SerializedState {
+            serialized_state: None,
+            version: 0,
+        },
+    }
+}
+
+fn parse_set_state_request(api_instance: &crate::grpc_impl::api::SetStateRequest) -> anyhow::Result<crate::node_management::SetStateRequest> {
+    match &api_instance.state_specification {
+        Some(state_specification) => Ok(crate::node_management::SetStateRequest {
+            state_specification: super::function_instance::parse_state_specification(state_specification)?,
+            serialized_state: api_instance.serialized_state.clone(),
+            expected_version: api_instance.expected_version,
+        }),
+        None => Err(anyhow::anyhow!("Ill-formed SetStateRequest message: state_specification not specified")),
+    }
+}
+
+fn serialize_set_state_request(req: &crate::node_management::SetStateRequest) -> crate::grpc_impl::api::SetStateRequest {
+    crate::grpc_impl::api::SetStateRequest {
+        state_specification: Some(super::function_instance::serialize_state_specification(&req.state_specification)),
+        serialized_state: req.serialized_state.clone(),
+        expected_version: req.expected_version,
+    }
+}
+
+fn parse_hand_over_request(api_instance: &crate::grpc_impl::api::HandOverRequest) -> anyhow::Result<crate::node_management::HandOverRequest> {
+    match (&api_instance.origin, &api_instance.target) {
+        (Some(origin), Some(target)) => Ok(crate::node_management::HandOverRequest {
+            origin: crate::grpc_impl::common::CommonConverters::parse_instance_id(origin)?,
+            target: crate::grpc_impl::common::CommonConverters::parse_instance_id(target)?,
+        }),
+        _ => Err(anyhow::anyhow!("Ill-formed HandOverRequest message: origin or target not specified")),
+    }
+}
+
+fn serialize_hand_over_request(req: &crate::node_management::HandOverRequest) -> crate::grpc_impl::api::HandOverRequest {
+    crate::grpc_impl::api::HandOverRequest {
+        origin: Some(crate::grpc_impl::common::CommonConverters::serialize_instance_id(&req.origin)),
+        target: Some(crate::grpc_impl::common::CommonConverters::serialize_instance_id(&req.target)),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::node_management::HandOverRequest;
+    use crate::node_management::SetStateRequest;
     use crate::node_management::UpdatePeersRequest;
 
     #[test]
@@ -195,4 +416,58 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn serialize_deserialize_set_state_request() {
+        let messages = vec![
+            SetStateRequest {
+                state_specification: crate::function_instance::StateSpecification {
+                    state_id: uuid::Uuid::new_v4(),
+                    state_policy: crate::function_instance::StatePolicy::NodeLocal,
+                },
+                serialized_state: "my-state".to_string(),
+                expected_version: 0,
+            },
+            SetStateRequest {
+                state_specification: crate::function_instance::StateSpecification {
+                    state_id: uuid::Uuid::new_v4(),
+                    state_policy: crate::function_instance::StatePolicy::Global,
+                },
+                serialized_state: String::default(),
+                expected_version: 42,
+            },
+        ];
+        for msg in messages {
+            match parse_set_state_request(&serialize_set_state_request(&msg)) {
+                Ok(val) => assert_eq!(msg, val),
+                Err(err) => panic!("{}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_deserialize_hand_over_request() {
+        let msg = HandOverRequest {
+            origin: crate::function_instance::InstanceId::new(uuid::Uuid::new_v4()),
+            target: crate::function_instance::InstanceId::new(uuid::Uuid::new_v4()),
+        };
+        match parse_hand_over_request(&serialize_hand_over_request(&msg)) {
+            Ok(val) => assert_eq!(msg, val),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[test]
+    fn serialize_deserialize_serialized_state() {
+        let messages = vec![
+            None,
+            Some(crate::node_management::SerializedState {
+                serialized_state: "my-state".to_string(),
+                version: 42,
+            }),
+        ];
+        for msg in messages {
+            assert_eq!(msg, parse_serialized_state(&serialize_serialized_state(&msg)));
+        }
+    }
 }
@@ -352,6 +352,14 @@ fn parse_node_performance_samples(api_instance: &crate::grpc_impl::api::NodePerf
                 _ => None,
             })
             .collect(),
+        function_queue_depths: api_instance
+            .function_queue_depths
+            .iter()
+            .filter_map(|x| match uuid::Uuid::from_str(&x.id) {
+                Ok(val) => Some((val, x.samples.iter().map(parse_sample).collect())),
+                _ => None,
+            })
+            .collect(),
         function_log_entries: api_instance
             .function_log_entries
             .iter()
@@ -422,6 +430,14 @@ fn serialize_node_performance_samples(req: &crate::node_registration::NodePerfor
                 samples: samples.iter().map(serialize_sample).collect(),
             })
             .collect(),
+        function_queue_depths: req
+            .function_queue_depths
+            .iter()
+            .map(|(id, samples)| crate::grpc_impl::api::Samples {
+                id: id.to_string(),
+                samples: samples.iter().map(serialize_sample).collect(),
+            })
+            .collect(),
         function_log_entries: req
             .function_log_entries
             .iter()
@@ -543,6 +559,10 @@ mod test {
                     (uuid::Uuid::new_v4(), vec![]),
                     (uuid::Uuid::new_v4(), vec![new_sample(0.1), new_sample(0.2), new_sample(999.0)]),
                 ]),
+                function_queue_depths: std::collections::HashMap::from([
+                    (uuid::Uuid::new_v4(), vec![new_sample(601.0), new_sample(2.0), new_sample(0.0)]),
+                    (uuid::Uuid::new_v4(), vec![]),
+                ]),
                 function_log_entries: std::collections::HashMap::from([(uuid::Uuid::new_v4(), vec![new_log(100.0), new_log(200.1)])]),
             },
         }];
@@ -122,6 +122,19 @@ impl crate::workflow_instance::WorkflowInstanceAPI for WorkflowInstanceAPIClient
             Err(err) => Err(anyhow::anyhow!("Communication error while migrating a workflow: {}", err)),
         }
     }
+    async fn update(
+        &mut self,
+        request: crate::workflow_instance::UpdateWorkflowRequest,
+    ) -> anyhow::Result<crate::workflow_instance::SpawnWorkflowResponse> {
+        let ret = self
+            .client
+            .update(tonic::Request::new(super::workflow_instance::serialize_update_workflow_request(&request)))
+            .await;
+        match ret {
+            Ok(ret) => return super::workflow_instance::parse_workflow_spawn_response(&ret.into_inner()),
+            Err(err) => Err(anyhow::anyhow!("Communication error while updating a workflow: {}", err)),
+        }
+    }
 }
 
 pub struct WorkflowInstanceAPIServer {
@@ -238,6 +251,37 @@ impl crate::grpc_impl::api::workflow_instance_server::WorkflowInstance for Workf
             })),
         }
     }
+
+    async fn update(
+        &self,
+        request: tonic::Request<crate::grpc_impl::api::UpdateWorkflowRequest>,
+    ) -> Result<tonic::Response<crate::grpc_impl::api::SpawnWorkflowResponse>, tonic::Status> {
+        let request = match super::workflow_instance::parse_update_workflow_request(&request.into_inner()) {
+            Ok(val) => val,
+            Err(err) => {
+                return Ok(tonic::Response::new(crate::grpc_impl::api::SpawnWorkflowResponse {
+                    response_error: Some(crate::grpc_impl::api::ResponseError {
+                        summary: "Invalid request".to_string(),
+                        detail: Some(err.to_string()),
+                    }),
+                    workflow_status: None,
+                }));
+            }
+        };
+        let ret = self.root_api.lock().await.update(request).await;
+        match ret {
+            Ok(response) => Ok(tonic::Response::new(super::workflow_instance::serialize_workflow_spawn_response(
+                &response,
+            ))),
+            Err(err) => Ok(tonic::Response::new(crate::grpc_impl::api::SpawnWorkflowResponse {
+                response_error: Some(crate::grpc_impl::api::ResponseError {
+                    summary: "Request rejected".to_string(),
+                    detail: Some(err.to_string()),
+                }),
+                workflow_status: None,
+            })),
+        }
+    }
 }
 
 fn parse_workflow_id(api_id: &crate::grpc_impl::api::WorkflowId) -> anyhow::Result<crate::workflow_instance::WorkflowId> {
@@ -364,6 +408,19 @@ fn parse_migrate_workflow_request(
     }
 }
 
+fn parse_update_workflow_request(
+    api_request: &crate::grpc_impl::api::UpdateWorkflowRequest,
+) -> anyhow::Result<crate::workflow_instance::UpdateWorkflowRequest> {
+    if let (Some(workflow_id), Some(workflow)) = (&api_request.workflow_id, &api_request.workflow) {
+        Ok(crate::workflow_instance::UpdateWorkflowRequest {
+            workflow_id: parse_workflow_id(workflow_id)?,
+            workflow: parse_workflow_spawn_request(workflow)?,
+        })
+    } else {
+        anyhow::bail!("missing workflow_id or workflow in UpdateWorkflowRequest");
+    }
+}
+
 fn serialize_workflow_id(crate_id: &crate::workflow_instance::WorkflowId) -> crate::grpc_impl::api::WorkflowId {
     crate::grpc_impl::api::WorkflowId {
         workflow_id: crate_id.workflow_id.to_string(),
@@ -456,6 +513,15 @@ fn serialize_migrate_workflow_request(
     }
 }
 
+fn serialize_update_workflow_request(
+    crate_request: &crate::workflow_instance::UpdateWorkflowRequest,
+) -> crate::grpc_impl::api::UpdateWorkflowRequest {
+    crate::grpc_impl::api::UpdateWorkflowRequest {
+        workflow_id: Some(serialize_workflow_id(&crate_request.workflow_id)),
+        workflow: Some(serialize_workflow_spawn_request(&crate_request.workflow)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -464,6 +530,7 @@ mod tests {
     use crate::function_instance::FunctionClassSpecification;
     use crate::workflow_instance::SpawnWorkflowRequest;
     use crate::workflow_instance::SpawnWorkflowResponse;
+    use crate::workflow_instance::UpdateWorkflowRequest;
     use crate::workflow_instance::WorkflowFunction;
     use crate::workflow_instance::WorkflowFunctionMapping;
     use crate::workflow_instance::WorkflowId;
@@ -558,6 +625,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn serialize_deserialize_update_workflow_request() {
+        let messages = vec![UpdateWorkflowRequest {
+            workflow_id: WorkflowId {
+                workflow_id: uuid::Uuid::new_v4(),
+            },
+            workflow: SpawnWorkflowRequest {
+                functions: vec![WorkflowFunction {
+                    name: "f1".to_string(),
+                    class_specification: FunctionClassSpecification {
+                        id: "my_fun_class".to_string(),
+                        function_type: "my_fun_class_type".to_string(),
+                        version: "0.0.2".to_string(),
+                        binary: Some("byte-code".to_string().as_bytes().to_vec()),
+                        code: None,
+                        outputs: vec!["out1".to_string()],
+                    },
+                    output_mapping: HashMap::from([("out1".to_string(), "res1".to_string())]),
+                    annotations: HashMap::from([("ann1".to_string(), "val1".to_string())]),
+                }],
+                annotations: HashMap::new(),
+                resources: vec![WorkflowResource {
+                    name: "res1".to_string(),
+                    class_type: "my_res_class_type".to_string(),
+                    output_mapping: HashMap::new(),
+                    configurations: HashMap::from([("conf1".to_string(), "val1".to_string())]),
+                }],
+            },
+        }];
+
+        for msg in messages {
+            match parse_update_workflow_request(&serialize_update_workflow_request(&msg)) {
+                Ok(val) => assert_eq!(msg, val),
+                Err(err) => panic!("{}", err),
+            }
+        }
+    }
+
     #[test]
     fn serialize_deserialize_workflow_function_mapping() {
         let messages = vec![WorkflowFunctionMapping {
@@ -4,7 +4,7 @@
 // SPDX-License-Identifier: MIT
 use crate::grpc_impl::common::CommonConverters;
 
-struct InvocationConverters {}
+pub(super) struct InvocationConverters {}
 
 const TYPE_CALL: i32 = crate::grpc_impl::api::EventType::Call as i32;
 const TYPE_CAST: i32 = crate::grpc_impl::api::EventType::Cast as i32;
@@ -12,7 +12,7 @@ const TYPE_CALL_RET: i32 = crate::grpc_impl::api::EventType::CallRet as i32;
 const TYPE_CALL_NO_RET: i32 = crate::grpc_impl::api::EventType::CallNoRet as i32;
 
 impl InvocationConverters {
-    fn parse_api_event(api_event: &crate::grpc_impl::api::Event) -> anyhow::Result<crate::invocation::Event> {
+    pub(super) fn parse_api_event(api_event: &crate::grpc_impl::api::Event) -> anyhow::Result<crate::invocation::Event> {
         Ok(crate::invocation::Event {
             target: CommonConverters::parse_instance_id(api_event.target.as_ref().unwrap())?,
             source: CommonConverters::parse_instance_id(api_event.source.as_ref().unwrap())?,
@@ -24,20 +24,21 @@ impl InvocationConverters {
                 .as_ref()
                 .ok_or(anyhow::anyhow!("the serialized metadata field is missing"))
                 .and_then(|x| x.try_into())?,
+            event_id: api_event.event_id,
         })
     }
 
     fn parse_api_event_data(api_event_data: &crate::grpc_impl::api::EventData) -> anyhow::Result<crate::invocation::EventData> {
         match api_event_data.event_type {
-            TYPE_CALL => Ok(crate::invocation::EventData::Call(api_event_data.payload.to_string())),
-            TYPE_CAST => Ok(crate::invocation::EventData::Cast(api_event_data.payload.to_string())),
-            TYPE_CALL_RET => Ok(crate::invocation::EventData::CallRet(api_event_data.payload.to_string())),
+            TYPE_CALL => Ok(crate::invocation::EventData::Call(api_event_data.payload.clone())),
+            TYPE_CAST => Ok(crate::invocation::EventData::Cast(api_event_data.payload.clone())),
+            TYPE_CALL_RET => Ok(crate::invocation::EventData::CallRet(api_event_data.payload.clone())),
             TYPE_CALL_NO_RET => Ok(crate::invocation::EventData::CallNoRet),
             _ => Ok(crate::invocation::EventData::Err),
         }
     }
 
-    fn encode_crate_event(crate_event: &crate::invocation::Event) -> crate::grpc_impl::api::Event {
+    pub(super) fn encode_crate_event(crate_event: &crate::invocation::Event) -> crate::grpc_impl::api::Event {
         crate::grpc_impl::api::Event {
             target: Some(CommonConverters::serialize_instance_id(&crate_event.target)),
             source: Some(CommonConverters::serialize_instance_id(&crate_event.source)),
@@ -45,22 +46,23 @@ impl InvocationConverters {
             msg: Some(Self::encode_crate_event_data(&crate_event.data)),
             created: Some(CommonConverters::serialize_event_timestamp(&crate_event.created)),
             metadata: Some(crate::grpc_impl::api::EventSerializedMetadata::from(&crate_event.metadata)),
+            event_id: crate_event.event_id,
         }
     }
 
     fn encode_crate_event_data(crate_event: &crate::invocation::EventData) -> crate::grpc_impl::api::EventData {
-        let mut payload_buffer = "".to_string();
+        let mut payload_buffer = Vec::new();
         let event = match crate_event {
             crate::invocation::EventData::Call(payload) => {
-                payload_buffer = payload.to_string();
+                payload_buffer = payload.clone();
                 crate::grpc_impl::api::EventType::Call
             }
             crate::invocation::EventData::Cast(payload) => {
-                payload_buffer = payload.to_string();
+                payload_buffer = payload.clone();
                 crate::grpc_impl::api::EventType::Cast
             }
             crate::invocation::EventData::CallRet(payload) => {
-                payload_buffer = payload.to_string();
+                payload_buffer = payload.clone();
                 crate::grpc_impl::api::EventType::CallRet
             }
             crate::invocation::EventData::CallNoRet => crate::grpc_impl::api::EventType::CallNoRet,
@@ -79,64 +81,71 @@ pub struct InvocationAPIClient {
 
 impl InvocationAPIClient {
     pub async fn new(server_addr: &str) -> Self {
-        let server_addr = server_addr.to_string();
-        let tls_config = crate::grpc_impl::tls_config::TlsConfig::global_client();
-
-        loop {
-            if tls_config.is_tpm_enabled() {
-                log::info!("Created InvocationAPI client with TPM integration");
-                match tls_config.create_channel_with_tpm(&server_addr).await {
-                    Ok(channel) => {
-                        let client = crate::grpc_impl::api::function_invocation_client::FunctionInvocationClient::new(channel)
-                            .max_decoding_message_size(usize::MAX);
-                        return Self { client };
-                    }
-                    Err(err) => {
-                        log::warn!("Failed to create TPM-backed TLS channel for InvocationAPI at {}: {}", server_addr, err);
-                    }
-                }
-            } else {
-                log::info!("Created InvocationAPI client");
-                let client_tls_config = match tls_config.create_client_tls_config() {
-                    Ok(cfg) => cfg,
-                    Err(err) => {
-                        log::error!("Failed to create client TLS configuration for InvocationAPI at {}: {}", server_addr, err);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                        continue;
-                    }
-                };
-
-                let endpoint = match tonic::transport::Endpoint::from_shared(server_addr.clone()) {
-                    Ok(ep) => ep,
-                    Err(err) => {
-                        log::error!("Failed to create endpoint for InvocationAPI at {}: {}", server_addr, err);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                        continue;
-                    }
-                };
-
-                let endpoint = match endpoint.tls_config(client_tls_config) {
-                    Ok(ep) => ep,
-                    Err(err) => {
-                        log::error!("Failed to configure TLS for InvocationAPI endpoint at {}: {}", server_addr, err);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                        continue;
-                    }
-                };
+        Self {
+            client: connect(server_addr).await,
+        }
+    }
+}
 
-                match crate::grpc_impl::api::function_invocation_client::FunctionInvocationClient::connect(endpoint.clone()).await {
-                    Ok(client) => {
-                        let client = client.max_decoding_message_size(usize::MAX);
-                        return Self { client };
-                    }
-                    Err(err) => {
-                        log::debug!("Waiting for InvocationAPI at {}: {}", server_addr, err);
-                    }
+/// Connect to the InvocationAPI server of a node, retrying until successful.
+pub(super) async fn connect(
+    server_addr: &str,
+) -> crate::grpc_impl::api::function_invocation_client::FunctionInvocationClient<tonic::transport::Channel> {
+    let server_addr = server_addr.to_string();
+    let tls_config = crate::grpc_impl::tls_config::TlsConfig::global_client();
+
+    loop {
+        if tls_config.is_tpm_enabled() {
+            log::info!("Created InvocationAPI client with TPM integration");
+            match tls_config.create_channel_with_tpm(&server_addr).await {
+                Ok(channel) => {
+                    return crate::grpc_impl::api::function_invocation_client::FunctionInvocationClient::new(channel)
+                        .max_decoding_message_size(usize::MAX);
+                }
+                Err(err) => {
+                    log::warn!("Failed to create TPM-backed TLS channel for InvocationAPI at {}: {}", server_addr, err);
                 }
             }
+        } else {
+            log::info!("Created InvocationAPI client");
+            let client_tls_config = match tls_config.create_client_tls_config() {
+                Ok(cfg) => cfg,
+                Err(err) => {
+                    log::error!("Failed to create client TLS configuration for InvocationAPI at {}: {}", server_addr, err);
+                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
 
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            let endpoint = match tonic::transport::Endpoint::from_shared(server_addr.clone()) {
+                Ok(ep) => ep,
+                Err(err) => {
+                    log::error!("Failed to create endpoint for InvocationAPI at {}: {}", server_addr, err);
+                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+
+            let endpoint = match endpoint.tls_config(client_tls_config) {
+                Ok(ep) => ep,
+                Err(err) => {
+                    log::error!("Failed to configure TLS for InvocationAPI endpoint at {}: {}", server_addr, err);
+                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+
+            match crate::grpc_impl::api::function_invocation_client::FunctionInvocationClient::connect(endpoint.clone()).await {
+                Ok(client) => {
+                    return client.max_decoding_message_size(usize::MAX);
+                }
+                Err(err) => {
+                    log::debug!("Waiting for InvocationAPI at {}: {}", server_addr, err);
+                }
+            }
         }
+
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
     }
 }
 
@@ -153,7 +162,35 @@ impl crate::invocation::InvocationAPI for InvocationAPIClient {
 }
 
 pub struct InvocationAPIServerHandler {
-    pub root_api: tokio::sync::Mutex<Box<dyn crate::invocation::InvocationAPI>>,
+    // Shared by all the requests, which are thus handled one at a time: the
+    // root API should not wait, e.g., for room in the queue of the target,
+    // but reject the events that it cannot accept right away.
+    pub root_api: std::sync::Arc<tokio::sync::Mutex<Box<dyn crate::invocation::InvocationAPI>>>,
+}
+
+impl InvocationAPIServerHandler {
+    // Handle the events of a batch in order, returning for each of them
+    // whether it has been handled successfully.
+    async fn handle_batch(
+        root_api: &tokio::sync::Mutex<Box<dyn crate::invocation::InvocationAPI>>,
+        batch: crate::grpc_impl::api::EventBatch,
+    ) -> crate::grpc_impl::api::EventBatchAck {
+        let mut handled = Vec::with_capacity(batch.events.len());
+        for event in &batch.events {
+            let res = match InvocationConverters::parse_api_event(event) {
+                Ok(parsed_event) => root_api.lock().await.handle(parsed_event).await.is_ok(),
+                Err(err) => {
+                    log::error!("Parse Request Failed: {}", err);
+                    false
+                }
+            };
+            handled.push(res);
+        }
+        crate::grpc_impl::api::EventBatchAck {
+            batch_id: batch.batch_id,
+            handled,
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -174,6 +211,25 @@ impl crate::grpc_impl::api::function_invocation_server::FunctionInvocation for I
             Err(_) => Err(tonic::Status::internal("Server Error")),
         }
     }
+
+    type HandleStreamStream =
+        std::pin::Pin<Box<dyn futures::Stream<Item = Result<crate::grpc_impl::api::EventBatchAck, tonic::Status>> + Send + 'static>>;
+
+    async fn handle_stream(
+        &self,
+        request: tonic::Request<tonic::Streaming<crate::grpc_impl::api::EventBatch>>,
+    ) -> Result<tonic::Response<Self::HandleStreamStream>, tonic::Status> {
+        use futures::StreamExt;
+
+        // The batches are handled as the acknowledgements are consumed, so
+        // that a slow receiver slows down the sender.
+        let root_api = self.root_api.clone();
+        let acks = request.into_inner().then(move |batch| {
+            let root_api = root_api.clone();
+            async move { Ok::<_, tonic::Status>(Self::handle_batch(&root_api, batch?).await) }
+        });
+        Ok(tonic::Response::new(Box::pin(acks)))
+    }
 }
 
 pub struct InvocationAPIServer {}
@@ -186,7 +242,7 @@ impl InvocationAPIServer {
     ) -> futures::future::BoxFuture<'static, ()> {
         let data_plane = data_plane;
         let function_api = super::invocation::InvocationAPIServerHandler {
-            root_api: tokio::sync::Mutex::new(data_plane),
+            root_api: std::sync::Arc::new(tokio::sync::Mutex::new(data_plane)),
         };
         Box::pin(async move {
             let function_api = function_api;
@@ -240,3 +296,48 @@ impl InvocationAPIServer {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use prost::Message;
+
+    #[test]
+    fn serialize_deserialize_event_data() {
+        let messages = vec![
+            crate::invocation::EventData::Call(b"call".to_vec()),
+            crate::invocation::EventData::Cast(vec![0x00_u8, 0xff, 0xfe, 0x80]),
+            crate::invocation::EventData::CallRet(vec![]),
+            crate::invocation::EventData::CallNoRet,
+            crate::invocation::EventData::Err,
+        ];
+        for msg in messages {
+            match InvocationConverters::parse_api_event_data(&InvocationConverters::encode_crate_event_data(&msg)) {
+                Ok(val) => assert_eq!(msg, val),
+                Err(err) => panic!("{}", err),
+            }
+        }
+    }
+
+    /// Layout of EventData before payloads became bytes.
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct LegacyEventData {
+        #[prost(int32, tag = "1")]
+        event_type: i32,
+        #[prost(string, tag = "2")]
+        payload: String,
+    }
+
+    #[test]
+    fn deserialize_legacy_string_event_data() {
+        let legacy = LegacyEventData {
+            event_type: TYPE_CAST,
+            payload: "hello, wörld".to_string(),
+        };
+        let api_event_data = crate::grpc_impl::api::EventData::decode(legacy.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(
+            crate::invocation::EventData::Cast("hello, wörld".as_bytes().to_vec()),
+            InvocationConverters::parse_api_event_data(&api_event_data).unwrap()
+        );
+    }
+}
@@ -0,0 +1,215 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+use super::invocation::InvocationConverters;
+
+/// Default maximum number of events sent in a batch.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 64;
+
+/// Maximum number of events waiting to be sent, after which the senders
+/// wait for room.
+const QUEUE_SIZE: usize = 4096;
+
+/// Maximum number of batches sent and not yet acknowledged.
+const MAX_IN_FLIGHT: usize = 16;
+
+/// Sink of the events rejected by the peer, or whose batch has not been
+/// acknowledged before the stream was closed, with the reason why.
+pub type RejectedSender = tokio::sync::mpsc::UnboundedSender<(crate::invocation::Event, String)>;
+
+/// What to do once the peer has acknowledged an event, or failed to.
+enum Tracking {
+    /// Nothing: the event is not tracked.
+    None,
+    /// Notify the sender, who is waiting, whether it has been handled.
+    Wait(tokio::sync::oneshot::Sender<bool>),
+    /// Forward the event to the rejected events if it has not been handled,
+    /// without the sender waiting.
+    Forward(Box<crate::invocation::Event>),
+}
+
+/// Event waiting to be sent, with how its result is tracked.
+struct PendingEvent {
+    event: crate::grpc_impl::api::Event,
+    tracking: Tracking,
+}
+
+/// Client of the InvocationAPI that sends the events over a persistent
+/// bidirectional gRPC stream, rather than one request per event.
+///
+/// The events queued while the previous batches are being sent are batched
+/// together, up to a maximum size. The events delivered at least once
+/// (i.e., with a non-zero `event_id`) are only acknowledged to the caller
+/// when handled by the peer. The casts and calls delivered at most once
+/// are acknowledged as soon as they are queued, if there is a sink of the
+/// rejected events, to which they are forwarded if not handled by the peer,
+/// including those whose batch has not been acknowledged before the stream
+/// was closed. Otherwise, the callers of the calls wait for them to be
+/// handled, while the other events are not tracked.
+pub struct InvocationAPIStreamClient {
+    sender: tokio::sync::mpsc::Sender<PendingEvent>,
+    forward_rejected: bool,
+}
+
+impl InvocationAPIStreamClient {
+    pub async fn new(server_addr: &str, max_batch_size: usize, rejected: Option<RejectedSender>) -> Self {
+        let client = super::invocation::connect(server_addr).await;
+        let (sender, receiver) = tokio::sync::mpsc::channel(QUEUE_SIZE);
+        let server_addr = server_addr.to_string();
+        let max_batch_size = std::cmp::max(1, max_batch_size);
+        let forward_rejected = rejected.is_some();
+        tokio::spawn(async move {
+            let mut client = client;
+            let mut receiver = receiver;
+            loop {
+                if Self::run_stream(&mut client, &mut receiver, max_batch_size, &rejected).await {
+                    break;
+                }
+                log::warn!("InvocationAPI stream to {} closed, reconnecting", server_addr);
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                if receiver.is_closed() && receiver.is_empty() {
+                    break;
+                }
+            }
+            log::debug!("InvocationAPI stream to {} terminated", server_addr);
+        });
+        Self { sender, forward_rejected }
+    }
+
+    // Send the events queued over a new stream until the latter is closed.
+    // Return true if the client has been dropped.
+    async fn run_stream(
+        client: &mut crate::grpc_impl::api::function_invocation_client::FunctionInvocationClient<tonic::transport::Channel>,
+        receiver: &mut tokio::sync::mpsc::Receiver<PendingEvent>,
+        max_batch_size: usize,
+        rejected: &Option<RejectedSender>,
+    ) -> bool {
+        // The capacity ensures that sending a batch never waits, since
+        // there cannot be more than MAX_IN_FLIGHT batches not acknowledged.
+        let (mut batch_sender, batch_receiver) = futures::channel::mpsc::channel::<crate::grpc_impl::api::EventBatch>(MAX_IN_FLIGHT);
+        let mut acks = match client.handle_stream(tonic::Request::new(batch_receiver)).await {
+            Ok(response) => response.into_inner(),
+            Err(err) => {
+                log::warn!("Could not open the InvocationAPI stream: {}", err);
+                return false;
+            }
+        };
+
+        // Batches sent and not yet acknowledged, in order.
+        let mut in_flight = std::collections::VecDeque::<(u64, Vec<Tracking>)>::new();
+        let mut next_batch_id = 1;
+        let closed = loop {
+            tokio::select! {
+                pending_event = receiver.recv(), if in_flight.len() < MAX_IN_FLIGHT => {
+                    let pending_event = match pending_event {
+                        Some(pending_event) => pending_event,
+                        None => break true,
+                    };
+                    let mut events = vec![pending_event.event];
+                    let mut tracking = vec![pending_event.tracking];
+                    while events.len() < max_batch_size {
+                        match receiver.try_recv() {
+                            Ok(pending_event) => {
+                                events.push(pending_event.event);
+                                tracking.push(pending_event.tracking);
+                            }
+                            Err(_) => break,
+                        }
+                    }
+                    let batch_id = next_batch_id;
+                    next_batch_id += 1;
+                    if batch_sender.try_send(crate::grpc_impl::api::EventBatch { batch_id, events }).is_err() {
+                        Self::notify(tracking, &[], rejected);
+                        break false;
+                    }
+                    in_flight.push_back((batch_id, tracking));
+                }
+                ack = acks.message() => {
+                    let ack = match ack {
+                        Ok(Some(ack)) => ack,
+                        Ok(None) => break false,
+                        Err(err) => {
+                            log::warn!("Error in the InvocationAPI stream: {}", err);
+                            break false;
+                        }
+                    };
+                    match in_flight.pop_front() {
+                        Some((batch_id, tracking)) if batch_id == ack.batch_id => Self::notify(tracking, &ack.handled, rejected),
+                        _ => {
+                            log::error!("Unexpected acknowledgement of batch {} in the InvocationAPI stream", ack.batch_id);
+                            break false;
+                        }
+                    }
+                }
+            }
+        };
+
+        // The events of the batches not acknowledged are considered failed.
+        for (_, tracking) in in_flight {
+            Self::notify(tracking, &[], rejected);
+        }
+        closed
+    }
+
+    // Notify the results of the events of a batch, which are assumed not
+    // handled if not acknowledged.
+    fn notify(tracking: Vec<Tracking>, acks: &[bool], rejected: &Option<RejectedSender>) {
+        for (i, tracking) in tracking.into_iter().enumerate() {
+            let handled = acks.get(i).copied();
+            match tracking {
+                Tracking::None => {}
+                Tracking::Wait(sender) => {
+                    let _ = sender.send(handled.unwrap_or(false));
+                }
+                Tracking::Forward(event) => {
+                    let reason = match handled {
+                        Some(true) => continue,
+                        Some(false) => "rejected by the remote node",
+                        None => "not acknowledged by the remote node",
+                    };
+                    if let Some(rejected) = rejected {
+                        let _ = rejected.send((*event, reason.to_string()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::invocation::InvocationAPI for InvocationAPIStreamClient {
+    async fn handle(&mut self, event: crate::invocation::Event) -> anyhow::Result<crate::invocation::LinkProcessingResult> {
+        let serialized_event = InvocationConverters::encode_crate_event(&event);
+        let is_cast_or_call = matches!(
+            event.data,
+            crate::invocation::EventData::Cast(_) | crate::invocation::EventData::Call(_)
+        );
+        let is_call = matches!(event.data, crate::invocation::EventData::Call(_));
+        let (tracking, handled_receiver) = if event.event_id == 0 && is_cast_or_call && self.forward_rejected {
+            (Tracking::Forward(Box::new(event)), None)
+        } else if event.event_id != 0 || is_call {
+            let (handled_sender, handled_receiver) = tokio::sync::oneshot::channel();
+            (Tracking::Wait(handled_sender), Some(handled_receiver))
+        } else {
+            (Tracking::None, None)
+        };
+
+        if self
+            .sender
+            .send(PendingEvent {
+                event: serialized_event,
+                tracking,
+            })
+            .await
+            .is_err()
+        {
+            return Err(anyhow::anyhow!("Remote Event Stream Closed"));
+        }
+        match handled_receiver {
+            None => Ok(crate::invocation::LinkProcessingResult::PROCESSED),
+            Some(handled_receiver) => match handled_receiver.await {
+                Ok(true) => Ok(crate::invocation::LinkProcessingResult::PROCESSED),
+                _ => Err(anyhow::anyhow!("Remote Event Request Failed")),
+            },
+        }
+    }
+}
@@ -9,5 +9,6 @@ pub mod container_runtime;
 pub mod controller;
 pub mod domain_register;
 pub mod invocation;
+pub mod invocation_stream;
 pub mod node_register;
 pub mod orc;
@@ -3,11 +3,12 @@
 // SPDX-FileCopyrightText: © 2023 Siemens AG
 // SPDX-License-Identifier: MIT
 
-#[derive(Clone)]
+/// Payload of an event, carried as raw bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum EventData {
-    Call(String),
-    Cast(String),
-    CallRet(String),
+    Call(Vec<u8>),
+    Cast(Vec<u8>),
+    CallRet(Vec<u8>),
     CallNoRet,
     Err,
 }
@@ -32,6 +33,9 @@ pub struct Event {
     pub data: EventData,
     pub created: crate::function_instance::EventTimestamp,
     pub metadata: crate::function_instance::EventMetadata,
+    /// Identifier of an event delivered at least once, used by the receiver
+    /// to discard duplicates. Zero if the event is delivered at most once.
+    pub event_id: u64,
 }
 
 impl std::fmt::Display for Event {
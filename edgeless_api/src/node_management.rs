@@ -10,10 +10,36 @@ pub enum UpdatePeersRequest {
     Clear,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerializedState {
+    pub serialized_state: String,
+    pub version: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetStateRequest {
+    pub state_specification: crate::function_instance::StateSpecification,
+    pub serialized_state: String,
+    // The state is only saved if its current version is this one.
+    pub expected_version: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandOverRequest {
+    // Function instance that has synchronized its state.
+    pub origin: crate::function_instance::InstanceId,
+    // Function instance replacing it, to which the events are forwarded.
+    pub target: crate::function_instance::InstanceId,
+}
+
 #[async_trait::async_trait]
 pub trait NodeManagementAPI: NodeManagementAPIClone + Sync + Send {
     async fn update_peers(&mut self, request: UpdatePeersRequest) -> anyhow::Result<()>;
     async fn reset(&mut self) -> anyhow::Result<()>;
+    async fn sync_state(&mut self, instance_id: crate::function_instance::InstanceId) -> anyhow::Result<()>;
+    async fn hand_over(&mut self, request: HandOverRequest) -> anyhow::Result<()>;
+    async fn get_state(&mut self, state_specification: crate::function_instance::StateSpecification) -> anyhow::Result<Option<SerializedState>>;
+    async fn set_state(&mut self, request: SetStateRequest) -> anyhow::Result<()>;
 }
 
 // https://stackoverflow.com/a/30353928
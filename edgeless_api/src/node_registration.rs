@@ -229,6 +229,7 @@ pub struct NodePerformanceSamples {
     pub function_execution_times: FunctionTimes,
     pub function_stop_times: FunctionTimes,
     pub function_transfer_times: FunctionTimes,
+    pub function_queue_depths: FunctionTimes,
     pub function_log_entries: std::collections::HashMap<crate::function_instance::ComponentId, Vec<FunctionLogEntry>>,
 }
 
@@ -8,6 +8,18 @@ use std::str::FromStr;
 
 include!("workflow_instance_structs.rs");
 
+/// Name of the workflow annotation with the function/resource to which the
+/// events that cannot be delivered or processed are forwarded.
+pub const DEAD_LETTER_ANNOTATION: &str = "dead_letter";
+
+/// Name of the output channel through which the components of a workflow
+/// forward the events that cannot be delivered or processed.
+pub const DEAD_LETTER_CHANNEL: &str = "dead_letter";
+
+/// Name of the function annotation with the percentage of events directed
+/// to the new version of a function during a canary upgrade.
+pub const CANARY_WEIGHT: &str = "canary_weight";
+
 const WORKFLOW_ID_NONE: uuid::Uuid = uuid::uuid!("00000000-0000-0000-0000-ffff00000000");
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, PartialOrd, Ord)]
@@ -92,6 +104,15 @@ impl WorkflowFunction {
                 .any(|(channel, component)| channel.is_empty() || component.is_empty()),
             "empty channel or component in output_mapping of a function"
         );
+        if let Some(canary_weight) = self.annotations.get(CANARY_WEIGHT) {
+            anyhow::ensure!(
+                canary_weight.parse::<u32>().is_ok_and(|weight| weight <= 100),
+                "invalid {} annotation in function {}: {}",
+                CANARY_WEIGHT,
+                self.name,
+                canary_weight
+            );
+        }
         Ok(())
     }
 }
@@ -145,6 +166,40 @@ impl SpawnWorkflowRequest {
         }
     }
 
+    /// Return the function/resource to which the events that cannot be
+    /// delivered or processed are forwarded, if any.
+    pub fn dead_letter(&self) -> Option<&String> {
+        self.annotations.get(DEAD_LETTER_ANNOTATION)
+    }
+
+    /// Add the dead-letter channel to the output mappings of all the
+    /// functions/resources, except the dead-letter component itself and
+    /// those that already map the channel explicitly.
+    ///
+    /// Do nothing if the workflow does not have a dead-letter annotation.
+    pub fn expand_dead_letter(&mut self) {
+        let dead_letter = match self.dead_letter() {
+            Some(dead_letter) => dead_letter.clone(),
+            None => return,
+        };
+        let output_mappings = self
+            .functions
+            .iter_mut()
+            .filter(|function| function.name != dead_letter)
+            .map(|function| &mut function.output_mapping)
+            .chain(
+                self.resources
+                    .iter_mut()
+                    .filter(|resource| resource.name != dead_letter)
+                    .map(|resource| &mut resource.output_mapping),
+            );
+        for output_mapping in output_mappings {
+            output_mapping
+                .entry(DEAD_LETTER_CHANNEL.to_string())
+                .or_insert_with(|| dead_letter.clone());
+        }
+    }
+
     /// Return the output mappings of all the components, both functions and
     /// resources.
     pub fn output_mappings(&self) -> std::collections::HashMap<String, std::collections::HashMap<String, String>> {
@@ -176,6 +231,13 @@ impl SpawnWorkflowRequest {
                 .collect::<Vec<&String>>()
                 .is_empty()
         );
+        if let Some(dead_letter) = self.dead_letter() {
+            anyhow::ensure!(
+                self.source_components().contains(dead_letter),
+                "dead-letter component not found in the workflow: {}",
+                dead_letter
+            );
+        }
 
         // self.workflow_functions.
         Ok(())
@@ -201,6 +263,12 @@ pub struct MigrateWorkflowRequest {
     pub component: String,
 }
 
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct UpdateWorkflowRequest {
+    pub workflow_id: WorkflowId,
+    pub workflow: SpawnWorkflowRequest,
+}
+
 #[async_trait::async_trait]
 pub trait WorkflowInstanceAPI: WorkflowInstanceAPIClone + Send + Sync {
     async fn start(&mut self, request: SpawnWorkflowRequest) -> anyhow::Result<SpawnWorkflowResponse>;
@@ -212,6 +280,7 @@ pub trait WorkflowInstanceAPI: WorkflowInstanceAPIClone + Send + Sync {
         domain_id: String,
     ) -> anyhow::Result<std::collections::HashMap<String, crate::domain_registration::DomainCapabilities>>;
     async fn migrate(&mut self, request: MigrateWorkflowRequest) -> anyhow::Result<SpawnWorkflowResponse>;
+    async fn update(&mut self, request: UpdateWorkflowRequest) -> anyhow::Result<SpawnWorkflowResponse>;
 }
 
 // https://stackoverflow.com/a/30353928
@@ -348,4 +417,65 @@ mod test {
             spec.output_mappings()
         );
     }
+
+    #[test]
+    fn test_spawn_workflow_request_dead_letter() {
+        let function = |name: &str, output_mapping: Vec<(&str, &str)>| WorkflowFunction {
+            name: String::from(name),
+            class_specification: FunctionClassSpecification {
+                id: String::from("function-class-id"),
+                function_type: String::from("function-class-type"),
+                version: String::from("function-class-version"),
+                binary: None,
+                code: None,
+                outputs: vec![],
+            },
+            output_mapping: output_mapping
+                .into_iter()
+                .map(|(channel, target)| (String::from(channel), String::from(target)))
+                .collect(),
+            annotations: std::collections::HashMap::new(),
+        };
+        let mut spec = SpawnWorkflowRequest {
+            functions: vec![
+                function("f1", vec![("out", "f2")]),
+                function("f2", vec![(DEAD_LETTER_CHANNEL, "f1")]),
+                function("dlq", vec![]),
+            ],
+            resources: vec![WorkflowResource {
+                name: String::from("r1"),
+                class_type: String::from("resource-class"),
+                output_mapping: std::collections::HashMap::from([(String::from("out"), String::from("f1"))]),
+                configurations: std::collections::HashMap::new(),
+            }],
+            annotations: std::collections::HashMap::from([(String::from(DEAD_LETTER_ANNOTATION), String::from("dlq"))]),
+        };
+        assert!(spec.is_valid().is_ok());
+        assert_eq!(Some(&String::from("dlq")), spec.dead_letter());
+
+        spec.expand_dead_letter();
+        let output_mappings = spec.output_mappings();
+        assert_eq!(
+            std::collections::HashMap::from([
+                (String::from("out"), String::from("f2")),
+                (String::from(DEAD_LETTER_CHANNEL), String::from("dlq"))
+            ]),
+            output_mappings["f1"]
+        );
+        assert_eq!(
+            std::collections::HashMap::from([(String::from(DEAD_LETTER_CHANNEL), String::from("f1"))]),
+            output_mappings["f2"]
+        );
+        assert!(output_mappings["dlq"].is_empty());
+        assert_eq!(
+            std::collections::HashMap::from([
+                (String::from("out"), String::from("f1")),
+                (String::from(DEAD_LETTER_CHANNEL), String::from("dlq"))
+            ]),
+            output_mappings["r1"]
+        );
+
+        spec.annotations.insert(String::from(DEAD_LETTER_ANNOTATION), String::from("unknown"));
+        assert!(spec.is_valid().is_err());
+    }
 }
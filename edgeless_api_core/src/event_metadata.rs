@@ -4,8 +4,16 @@
 use opentelemetry::trace::{SpanContext, TraceFlags, TraceState};
 use opentelemetry::{SpanId, TraceId};
 
+use crate::event_timestamp::EventTimestamp;
+
+/// Metadata of an event, which contain the tracing context and, for the
+/// events generated while serving a call, the deadline by which the call
+/// must be completed.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct EventMetadata(SpanContext);
+pub struct EventMetadata {
+    span_context: SpanContext,
+    deadline: Option<EventTimestamp>,
+}
 
 impl EventMetadata {
     pub fn from_uints(trace_id: u128, span_id: u64) -> EventMetadata {
@@ -13,7 +21,10 @@ impl EventMetadata {
     }
 
     pub fn from(trace_id: TraceId, span_id: SpanId) -> EventMetadata {
-        EventMetadata(SpanContext::new(trace_id, span_id, TraceFlags::SAMPLED, true, TraceState::NONE))
+        EventMetadata {
+            span_context: SpanContext::new(trace_id, span_id, TraceFlags::SAMPLED, true, TraceState::NONE),
+            deadline: None,
+        }
     }
 
     pub fn from_bytes(trace_id: [u8; 16], span_id: [u8; 8]) -> EventMetadata {
@@ -21,26 +32,38 @@ impl EventMetadata {
     }
 
     pub fn from_event(e: &EventMetadata) -> EventMetadata {
-        EventMetadata::from(e.trace_id(), e.span_id())
+        EventMetadata::from(e.trace_id(), e.span_id()).with_deadline(e.deadline())
+    }
+
+    /// Return the same metadata with the given deadline, in UTC.
+    pub fn with_deadline(mut self, deadline: Option<EventTimestamp>) -> EventMetadata {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Return the deadline by which the call being served must be completed,
+    /// in UTC, if any.
+    pub fn deadline(&self) -> Option<EventTimestamp> {
+        self.deadline
     }
 
     pub fn to_bytes(&self) -> [u8; 16 + 8] {
         let mut tmp = [0u8; 24];
-        tmp[..16].copy_from_slice(&self.0.trace_id().to_bytes());
-        tmp[16..].copy_from_slice(&self.0.span_id().to_bytes());
+        tmp[..16].copy_from_slice(&self.span_context.trace_id().to_bytes());
+        tmp[16..].copy_from_slice(&self.span_context.span_id().to_bytes());
         tmp
     }
 
     pub fn trace_id(&self) -> TraceId {
-        self.0.trace_id()
+        self.span_context.trace_id()
     }
 
     pub fn span_id(&self) -> SpanId {
-        self.0.span_id()
+        self.span_context.span_id()
     }
 
     pub fn span_context(&self) -> &SpanContext {
-        &self.0
+        &self.span_context
     }
 
     pub fn empty_new_root() -> Self {
@@ -64,8 +87,9 @@ impl<'b, C> minicbor::Decode<'b, C> for EventMetadata {
 impl<C> minicbor::Encode<C> for EventMetadata {
     fn encode<W: minicbor::encode::Write>(&self, e: &mut minicbor::Encoder<W>, _: &mut C) -> Result<(), minicbor::encode::Error<W::Error>> {
         // See https://github.com/twittner/minicbor/blob/develop/minicbor/src/encode.rs#L876
-        e.bytes(&self.0.trace_id().to_bytes())
-            .and_then(|e| e.bytes(&self.0.span_id().to_bytes()))?
+        // The deadline is not encoded, i.e., it is not propagated via CoAP.
+        e.bytes(&self.span_context.trace_id().to_bytes())
+            .and_then(|e| e.bytes(&self.span_context.span_id().to_bytes()))?
             .ok()
     }
 }
@@ -110,4 +134,23 @@ mod test {
         let em_2 = EventMetadata::from_bytes(x, y);
         assert_eq!(em_1, em_2)
     }
+
+    #[test]
+    fn test_event_metadata_deadline() {
+        let deadline = crate::event_timestamp::EventTimestamp { secs: 1000, nsecs: 42 };
+        let em_1 = EventMetadata::from_uints(0x42a42bdecaf00050u128, 0x42a42bdecaf00051u64);
+        assert_eq!(None, em_1.deadline());
+
+        let em_2 = em_1.clone().with_deadline(Some(deadline));
+        assert_eq!(Some(deadline), em_2.deadline());
+        assert_ne!(em_1, em_2);
+        assert_eq!(em_2, EventMetadata::from_event(&em_2));
+        assert_eq!(em_1, em_2.clone().with_deadline(None));
+
+        // The deadline is not encoded in CBOR.
+        let mut buffer = [0u8; 8 + 16 + 2];
+        minicbor::encode(&em_2, &mut buffer[..]).unwrap();
+        let decoded: EventMetadata = minicbor::decode(&buffer[..]).unwrap();
+        assert_eq!(em_1, decoded);
+    }
 }
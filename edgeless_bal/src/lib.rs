@@ -100,6 +100,7 @@ pub async fn edgeless_bal_main(settings: EdgelessBalSettings) {
         )]),
         settings.local.node_id,
         local_data_plane.clone(),
+        None,
     );
     let local_agent_api_server = edgeless_api::grpc_impl::outer::agent::AgentAPIServer::run(
         local_agent.get_api_client(),
@@ -118,6 +119,7 @@ pub async fn edgeless_bal_main(settings: EdgelessBalSettings) {
         )]),
         settings.portal.node_id,
         portal_data_plane.clone(),
+        None,
     );
     let portal_agent_api_server = edgeless_api::grpc_impl::outer::agent::AgentAPIServer::run(
         portal_agent.get_api_client(),
@@ -175,6 +177,11 @@ pub fn edgeless_bal_default_conf() -> String {
             invocation_url_announced_coap: None,
             node_register_url: String::from("http://127.0.0.1:7104"),
             subscription_refresh_interval_sec: 2,
+            call_timeout_ms: None,
+            inbound_queue_capacity: 0,
+            inbound_queue_overflow: edgeless_dataplane::inbound_queue::OverflowPolicy::default(),
+            invocation_transport: edgeless_dataplane::core::InvocationTransport::default(),
+            invocation_max_batch_size: None,
         },
         telemetry: edgeless_node::EdgelessNodeTelemetrySettings::default(),
     };
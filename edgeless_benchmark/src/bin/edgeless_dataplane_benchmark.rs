@@ -0,0 +1,149 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use clap::Parser;
+use edgeless_dataplane::core::InvocationTransport;
+
+#[derive(Debug, clap::Parser)]
+#[command(long_about = None)]
+struct Args {
+    /// Transport between the nodes, one of {unary, stream, all}
+    #[arg(short, long, default_value_t = String::from("all"))]
+    transport: String,
+    /// Number of casts sent to measure the throughput
+    #[arg(short, long, default_value_t = 10000)]
+    casts: usize,
+    /// Number of sequential calls made to measure the round-trip time
+    #[arg(long, default_value_t = 1000)]
+    calls: usize,
+    /// Size of the payload of the events, in bytes
+    #[arg(short, long, default_value_t = 64)]
+    payload_size: usize,
+    /// Maximum number of events per batch with the stream transport
+    #[arg(short, long, default_value_t = edgeless_api::grpc_impl::outer::invocation_stream::DEFAULT_MAX_BATCH_SIZE)]
+    max_batch_size: usize,
+    /// First of the local ports used by the data planes of the nodes, two per transport
+    #[arg(long, default_value_t = 7200)]
+    base_port: u16,
+}
+
+/// Performance of a transport, as measured between two nodes.
+struct Results {
+    /// Average time between the casts received, in s.
+    cast_time: f64,
+    /// Average round-trip time of the calls, in s.
+    call_time: f64,
+}
+
+impl std::fmt::Display for Results {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.0} casts/s ({:.1} us per cast), call round-trip time {:.1} us",
+            1.0 / self.cast_time,
+            self.cast_time * 1e6,
+            self.call_time * 1e6
+        )
+    }
+}
+
+/// Measure the performance of a transport between two nodes on localhost,
+/// each hosting one function instance.
+async fn run(args: &Args, transport: InvocationTransport, port: u16) -> anyhow::Result<Results> {
+    let node_id_1 = uuid::Uuid::new_v4();
+    let node_id_2 = uuid::Uuid::new_v4();
+    let url_1 = format!("http://127.0.0.1:{}", port);
+    let url_2 = format!("http://127.0.0.1:{}", port + 1);
+    let mut provider_1 = edgeless_dataplane::handle::DataplaneProvider::new(node_id_1, url_1.clone(), None)
+        .await
+        .with_invocation_transport(transport, args.max_batch_size);
+    let mut provider_2 = edgeless_dataplane::handle::DataplaneProvider::new(node_id_2, url_2.clone(), None)
+        .await
+        .with_invocation_transport(transport, args.max_batch_size);
+    provider_1
+        .add_peer(edgeless_dataplane::core::EdgelessDataplanePeerSettings {
+            node_id: node_id_2,
+            invocation_url: url_2,
+        })
+        .await;
+    provider_2
+        .add_peer(edgeless_dataplane::core::EdgelessDataplanePeerSettings {
+            node_id: node_id_1,
+            invocation_url: url_1,
+        })
+        .await;
+
+    let fid_1 = edgeless_api::function_instance::InstanceId::new(node_id_1);
+    let fid_2 = edgeless_api::function_instance::InstanceId::new(node_id_2);
+    let mut handle_1 = provider_1.get_handle_for(fid_1).await;
+    let mut handle_2 = provider_2.get_handle_for(fid_2).await;
+    let metadata = edgeless_api::function_instance::EventMetadata::empty_new_root();
+    let payload = vec![0x42_u8; args.payload_size];
+
+    // The second function instance counts the casts received, then replies
+    // to the calls.
+    let (casts, calls) = (args.casts, args.calls);
+    let receiver = tokio::spawn(async move {
+        for _ in 0..casts {
+            handle_2.receive_next().await;
+        }
+        let cast_end = std::time::Instant::now();
+        for _ in 0..calls {
+            let event = handle_2.receive_next().await;
+            handle_2
+                .reply(
+                    event.source_id,
+                    event.channel_id,
+                    edgeless_dataplane::core::CallRet::NoReply,
+                    &event.metadata,
+                )
+                .await;
+        }
+        cast_end
+    });
+
+    let cast_start = std::time::Instant::now();
+    for _ in 0..args.casts {
+        handle_1.send(fid_2, payload.clone(), &metadata).await;
+    }
+
+    let mut call_time = std::time::Duration::ZERO;
+    for _ in 0..args.calls {
+        let call_start = std::time::Instant::now();
+        let ret = handle_1.call(fid_2, payload.clone(), &metadata).await;
+        anyhow::ensure!(
+            ret == edgeless_dataplane::core::CallRet::NoReply,
+            "unexpected return from a call: {:?}",
+            ret
+        );
+        call_time += call_start.elapsed();
+    }
+    let cast_end = receiver.await?;
+
+    Ok(Results {
+        cast_time: (cast_end - cast_start).as_secs_f64() / std::cmp::max(1, args.casts) as f64,
+        call_time: call_time.as_secs_f64() / std::cmp::max(1, args.calls) as f64,
+    })
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    edgeless_api::grpc_impl::init_crypto();
+
+    let args = Args::parse();
+
+    let transports = match args.transport.as_str() {
+        "unary" => vec![InvocationTransport::Unary],
+        "stream" => vec![InvocationTransport::Stream],
+        "all" => vec![InvocationTransport::Unary, InvocationTransport::Stream],
+        _ => anyhow::bail!("invalid transport: {}", args.transport),
+    };
+
+    for (i, transport) in transports.into_iter().enumerate() {
+        let results = run(&args, transport, args.base_port + 2 * i as u16).await?;
+        println!("{:?}: {}", transport, results);
+    }
+
+    Ok(())
+}
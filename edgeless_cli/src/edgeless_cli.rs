@@ -24,6 +24,7 @@ enum WorkflowCommands {
     Stop { id: String },
     Migrate { id: String, domain: String },
     MigrateComponent { id: String, domain: String, component: String },
+    Update { id: String, spec_file: String },
     List {},
     Inspect { id: String },
 }
@@ -279,6 +280,29 @@ async fn main() -> anyhow::Result<()> {
                             SpawnWorkflowResponse::WorkflowInstance(_workflow_instance) => println!("migration of {} to {} successful", id, domain),
                         }
                     }
+                    WorkflowCommands::Update { id, spec_file } => {
+                        let workflow_spec: edgeless_api::workflow_instance::SpawnWorkflowRequest =
+                            serde_json::from_str(&std::fs::read_to_string(spec_file.clone())?)?;
+                        let parent_path = std::path::Path::new(&spec_file)
+                            .parent()
+                            .expect("cannot find the workflow spec's parent path");
+                        let workflow = edgeless_cli::workflow_spec_to_request(workflow_spec, parent_path)?;
+                        match wf_client
+                            .update(edgeless_api::workflow_instance::UpdateWorkflowRequest {
+                                workflow_id: edgeless_api::workflow_instance::WorkflowId::new(&id)?,
+                                workflow,
+                            })
+                            .await?
+                        {
+                            SpawnWorkflowResponse::ResponseError(response_error) => println!(
+                                "update of {} failed: {} ({})",
+                                id,
+                                response_error.summary,
+                                response_error.detail.unwrap_or_default()
+                            ),
+                            SpawnWorkflowResponse::WorkflowInstance(_workflow_instance) => println!("update of {} successful", id),
+                        }
+                    }
                     WorkflowCommands::List {} => {
                         for wf_id in wf_client.list().await? {
                             println!("{}", wf_id);
@@ -415,11 +439,12 @@ async fn main() -> anyhow::Result<()> {
                         source: edgeless_api::function_instance::InstanceId::none(),
                         stream_id: 0,
                         data: match event_type.as_str() {
-                            "cast" => edgeless_api::invocation::EventData::Cast(payload),
+                            "cast" => edgeless_api::invocation::EventData::Cast(payload.into_bytes()),
                             _ => return Err(anyhow::anyhow!("invalid event type: {}", event_type)),
                         },
                         created: edgeless_api::function_instance::EventTimestamp::default(),
                         metadata: edgeless_api::function_instance::EventMetadata::empty_new_root(),
+                        event_id: 0,
                     };
                     match edgeless_api::invocation::InvocationAPI::handle(&mut client, event).await {
                         Ok(_) => println!("event casted"),
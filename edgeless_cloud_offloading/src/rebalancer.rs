@@ -124,6 +124,7 @@ impl Rebalancer {
                         node_id,
                         &node_desc.capabilities,
                         &node_desc.resource_providers,
+                        &edgeless_orc::deployment_requirements::PlacementConstraints::default(),
                     )
                 })
                 .map(|(node_id, _)| *node_id)
@@ -174,6 +175,7 @@ impl Rebalancer {
                             target_node_id,
                             &target_node_desc.capabilities,
                             &target_node_desc.resource_providers,
+                            &edgeless_orc::deployment_requirements::PlacementConstraints::default(),
                         )
                     {
                         migrations.push(edgeless_orc::deploy_intent::DeployIntent::Migrate(*lid, vec![*target_node_id]));
@@ -260,6 +262,7 @@ impl Rebalancer {
                         target_node_id,
                         &target_node_desc.capabilities,
                         &target_node_desc.resource_providers,
+                        &edgeless_orc::deployment_requirements::PlacementConstraints::default(),
                     )
                 {
                     migrations.push(edgeless_orc::deploy_intent::DeployIntent::Migrate(*lid, vec![*target_node_id]));
@@ -9,6 +9,7 @@ pub mod client;
 pub mod controller_task;
 mod deployment_state;
 pub mod domain_register_client;
+mod persistence;
 #[cfg(test)]
 pub mod test;
 
@@ -43,6 +44,11 @@ pub(crate) enum ControllerRequest {
         // Reply Channel
         tokio::sync::oneshot::Sender<anyhow::Result<edgeless_api::workflow_instance::SpawnWorkflowResponse>>,
     ),
+    Update(
+        edgeless_api::workflow_instance::UpdateWorkflowRequest,
+        // Reply Channel
+        tokio::sync::oneshot::Sender<anyhow::Result<edgeless_api::workflow_instance::SpawnWorkflowResponse>>,
+    ),
 }
 
 pub(crate) enum DomainRegisterRequest {
@@ -60,7 +66,7 @@ pub(crate) enum InternalRequest {
     ),
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 enum ComponentType {
     Function,
     Resource,
@@ -76,11 +82,12 @@ impl Controller {
 
         let main_task = Box::pin(async move {
             let mut controller_task = controller_task::ControllerTask::new(
-                persistence_filename,
+                persistence::store_from_filename(&persistence_filename),
                 workflow_instance_receiver,
                 domain_register_receiver,
                 internal_receiver,
-            );
+            )
+            .await;
             controller_task.run().await;
         });
 
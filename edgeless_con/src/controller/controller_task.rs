@@ -5,7 +5,6 @@
 
 use futures::StreamExt;
 use rand::{SeedableRng, seq::SliceRandom};
-use std::{io::Write, str::FromStr};
 
 use crate::controller::deployment_state::ActiveWorkflow;
 
@@ -27,7 +26,7 @@ pub struct PortalDesc {
 }
 
 pub struct ControllerTask {
-    persistence_filename: String,
+    store: Box<dyn super::persistence::ControllerStore>,
     workflow_instance_receiver: futures::channel::mpsc::UnboundedReceiver<super::ControllerRequest>,
     domain_registration_receiver: futures::channel::mpsc::UnboundedReceiver<super::DomainRegisterRequest>,
     internal_receiver: futures::channel::mpsc::UnboundedReceiver<super::InternalRequest>,
@@ -35,37 +34,51 @@ pub struct ControllerTask {
     portal_desc: Option<PortalDesc>,
     active_workflows: std::collections::HashMap<edgeless_api::workflow_instance::WorkflowId, super::deployment_state::ActiveWorkflow>,
     orphan_workflows: std::collections::BTreeMap<edgeless_api::workflow_instance::WorkflowId, edgeless_api::workflow_instance::SpawnWorkflowRequest>,
+    /// Stable version of the functions subject to a canary upgrade of the
+    /// workflows that are not active, by function name, so that the split
+    /// of the events between the two versions is rebuilt on relocation.
+    canary_stables: std::collections::HashMap<
+        edgeless_api::workflow_instance::WorkflowId,
+        std::collections::HashMap<String, edgeless_api::workflow_instance::WorkflowFunction>,
+    >,
     rng: rand::rngs::StdRng,
     last_portal_resource_id: u64,
+    /// Orchestration domains that were known before the controller restarted
+    /// and have not registered again yet, with their last nonce.
+    recovered_domains: std::collections::HashMap<String, u64>,
+    /// Time until which the recovered domains are waited for.
+    recovery_deadline: std::time::SystemTime,
 }
 
-type PersistedWorkflows = Vec<(String, edgeless_api::workflow_instance::SpawnWorkflowRequest)>;
-
-#[derive(Default, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
-struct PersistedState {
-    workflows: PersistedWorkflows,
-}
+/// Time allowed to the orchestration domains known before a restart of the
+/// controller to register again, after which the functions/resources of the
+/// recovered workflows that they hosted are considered lost.
+const RECOVERY_PERIOD: std::time::Duration = std::time::Duration::from_secs(30);
 
 impl ControllerTask {
-    pub fn new(
-        persistence_filename: String,
+    pub async fn new(
+        store: Box<dyn super::persistence::ControllerStore>,
         workflow_instance_receiver: futures::channel::mpsc::UnboundedReceiver<super::ControllerRequest>,
         domain_registration_receiver: futures::channel::mpsc::UnboundedReceiver<super::DomainRegisterRequest>,
         internal_receiver: futures::channel::mpsc::UnboundedReceiver<super::InternalRequest>,
     ) -> Self {
-        let orphan_workflows = ControllerTask::load_persistence(&persistence_filename);
-        Self {
-            persistence_filename,
+        let mut task = Self {
+            store,
             workflow_instance_receiver,
             domain_registration_receiver,
             internal_receiver,
             orchestrators: std::collections::HashMap::new(),
             portal_desc: None,
             active_workflows: std::collections::HashMap::new(),
-            orphan_workflows,
+            orphan_workflows: std::collections::BTreeMap::new(),
+            canary_stables: std::collections::HashMap::new(),
             rng: rand::rngs::StdRng::from_entropy(),
             last_portal_resource_id: 0,
-        }
+            recovered_domains: std::collections::HashMap::new(),
+            recovery_deadline: std::time::SystemTime::now() + RECOVERY_PERIOD,
+        };
+        task.recover().await;
+        task
     }
 
     #[cfg(test)]
@@ -76,7 +89,7 @@ impl ControllerTask {
         orchestrators: std::collections::HashMap<String, OrchestratorDesc>,
     ) -> Self {
         Self {
-            persistence_filename: String::default(),
+            store: Box::new(super::persistence::VolatileStore {}),
             workflow_instance_receiver,
             domain_registration_receiver,
             internal_receiver,
@@ -84,88 +97,82 @@ impl ControllerTask {
             portal_desc: None,
             active_workflows: std::collections::HashMap::new(),
             orphan_workflows: std::collections::BTreeMap::new(),
+            canary_stables: std::collections::HashMap::new(),
             rng: rand::rngs::StdRng::from_entropy(),
             last_portal_resource_id: 0,
+            recovered_domains: std::collections::HashMap::new(),
+            recovery_deadline: std::time::SystemTime::now(),
         }
     }
 
-    fn load_persistence(
-        filename: &str,
-    ) -> std::collections::BTreeMap<edgeless_api::workflow_instance::WorkflowId, edgeless_api::workflow_instance::SpawnWorkflowRequest> {
-        let mut ret = std::collections::BTreeMap::new();
-
-        if filename.is_empty() {
-            return ret;
-        }
-
-        let file = match std::fs::File::open(filename) {
-            Ok(file) => file,
-            Err(err) => {
-                log::warn!("could not load from persistence file '{}': {}", filename, err);
-                return ret;
-            }
-        };
-        let reader = std::io::BufReader::new(file);
-        let data: PersistedState = match serde_json::from_reader(reader) {
-            Ok(data) => data,
+    /// Restore the workflows from the store.
+    ///
+    /// The workflows that were active keep their mapping to the orchestration
+    /// domains, which is confirmed when the domains register again with the
+    /// same nonce, see `update_domain()`.
+    async fn recover(&mut self) {
+        let state = match self.store.load().await {
+            Ok(state) => state,
             Err(err) => {
-                log::warn!("invalid content found in persistence file '{}': {}", filename, err);
-                return ret;
+                log::error!("could not load the persisted state, starting with no workflows: {}", err);
+                return;
             }
         };
 
-        for (uuid, request) in data.workflows {
-            let workflow_id = match uuid::Uuid::from_str(&uuid) {
-                Ok(uuid) => uuid,
-                Err(err) => {
-                    log::warn!("invalid workflow UUID found in persistence file '{}': {}", filename, err);
-                    return ret;
+        for (workflow_id, workflow) in state.workflows {
+            let wf_id = edgeless_api::workflow_instance::WorkflowId { workflow_id };
+            match workflow {
+                super::persistence::PersistedWorkflow::Active(active_workflow) => {
+                    self.active_workflows.insert(wf_id, active_workflow);
                 }
-            };
-            ret.insert(edgeless_api::workflow_instance::WorkflowId { workflow_id }, request);
+                super::persistence::PersistedWorkflow::Orphan(request) => {
+                    self.orphan_workflows.insert(wf_id, request);
+                }
+                super::persistence::PersistedWorkflow::OrphanCanary(request, stables) => {
+                    self.orphan_workflows.insert(wf_id.clone(), request);
+                    self.canary_stables.insert(wf_id, stables);
+                }
+            }
         }
-
-        ret
+        self.recovered_domains = state.domains.into_iter().collect();
+        self.last_portal_resource_id = state.last_portal_resource_id;
+
+        log::info!(
+            "recovered {} active and {} orphan workflows, waiting for {} domains to register again",
+            self.active_workflows.len(),
+            self.orphan_workflows.len(),
+            self.recovered_domains.len()
+        );
     }
 
-    /// Save the currently active/orphan workflows to a file.
-    /// Do nothing if the name of the persistence file is empty.
-    fn persist(&self) {
-        if self.persistence_filename.is_empty() {
-            return;
-        }
-
-        let mut persistence = match std::fs::OpenOptions::new()
-            .write(true)
-            .append(false)
-            .create(true)
-            .truncate(true)
-            .open(&self.persistence_filename)
-        {
-            Ok(file) => file,
-            Err(err) => {
-                log::warn!("could not open the persistence file '{}': {}", self.persistence_filename, err);
-                return;
-            }
+    /// Save the current state of a workflow: active, orphan, or removed.
+    async fn persist_workflow(&mut self, wf_id: &edgeless_api::workflow_instance::WorkflowId) {
+        let res = if let Some(active_workflow) = self.active_workflows.get(wf_id) {
+            self.store
+                .put_workflow(&wf_id.workflow_id, super::persistence::PersistedWorkflow::Active(active_workflow.clone()))
+                .await
+        } else if let Some(request) = self.orphan_workflows.get(wf_id) {
+            let workflow = match self.canary_stables.get(wf_id) {
+                Some(stables) => super::persistence::PersistedWorkflow::OrphanCanary(request.clone(), stables.clone()),
+                None => super::persistence::PersistedWorkflow::Orphan(request.clone()),
+            };
+            self.store.put_workflow(&wf_id.workflow_id, workflow).await
+        } else {
+            self.store.remove_workflow(&wf_id.workflow_id).await
         };
-
-        // Copy all the workflow information into the data structure to be
-        // serialized.
-        let mut persisted_state = PersistedState::default();
-        for (wid, active_workflow) in &self.orphan_workflows {
-            persisted_state.workflows.push((wid.to_string(), active_workflow.clone()));
-        }
-        for (wid, active_workflow) in &self.active_workflows {
-            persisted_state.workflows.push((wid.to_string(), active_workflow.desired_state.clone()));
+        if let Err(err) = res {
+            log::warn!("error saving the persistence state of workflow {}: {}", wf_id, err);
         }
+    }
 
-        match serde_json::to_string(&persisted_state) {
-            Ok(serialized) => {
-                if let Err(err) = write!(&mut persistence, "{}", serialized) {
-                    log::warn!("error saving the persistence state to '{}': {}", self.persistence_filename, err)
-                }
-            }
-            Err(err) => log::warn!("error serializing the persistence state: {}", err),
+    /// Save the nonce of a domain, or remove it if `None`.
+    async fn persist_domain(&mut self, domain_id: &str, nonce: Option<u64>) {
+        let res = match nonce {
+            Some(nonce) => self.store.put_domain(domain_id, nonce).await,
+            None => self.store.remove_domain(domain_id).await,
+        };
+        if let Err(err) = res {
+            log::warn!("error saving the persistence state of domain '{}': {}", domain_id, err);
         }
     }
 
@@ -195,8 +202,8 @@ impl ControllerTask {
                                 Ok(val) => Ok(val),
                                 Err(spawn_req) => Err(anyhow::anyhow!("could not start workflow: {:?}", spawn_req))
                             };
-                            if let Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::WorkflowInstance(_)) = &reply {
-                                self.persist();
+                            if let Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::WorkflowInstance(instance)) = &reply {
+                                self.persist_workflow(&instance.workflow_id).await;
                             }
                             if let Err(err) = reply_sender.send(reply) {
                                 log::error!("Unhandled: {:?}", err);
@@ -204,7 +211,8 @@ impl ControllerTask {
                         }
                         super::ControllerRequest::Stop(wf_id) => {
                             if self.stop_workflow(&wf_id).await.is_some() {
-                                self.persist();
+                                self.canary_stables.remove(&wf_id);
+                                self.persist_workflow(&wf_id).await;
                             }
                         }
                         super::ControllerRequest::List(reply_sender) => {
@@ -230,6 +238,14 @@ impl ControllerTask {
                                 Ok(val) => Ok(val),
                                 Err(spawn_req) => Err(anyhow::anyhow!("could not migrate workflow: {:?}", spawn_req))
                             };
+                            self.persist_workflow(&request.workflow_id).await;
+                            if let Err(err) = reply_sender.send(reply) {
+                                log::error!("Unhandled: {:?}", err);
+                            }
+                        }
+                        super::ControllerRequest::Update(request, reply_sender) => {
+                            let reply = self.update_workflow(&request).await;
+                            self.persist_workflow(&request.workflow_id).await;
                             if let Err(err) = reply_sender.send(reply) {
                                 log::error!("Unhandled: {:?}", err);
                             }
@@ -252,11 +268,16 @@ impl ControllerTask {
         &mut self,
         spawn_workflow_request: edgeless_api::workflow_instance::SpawnWorkflowRequest,
     ) -> anyhow::Result<edgeless_api::workflow_instance::SpawnWorkflowResponse, edgeless_api::workflow_instance::SpawnWorkflowRequest> {
-        if !spawn_workflow_request.annotations.is_empty() {
-            log::warn!(
-                "Workflow annotations ({}) are currently ignored",
-                spawn_workflow_request.annotations.len()
-            );
+        let num_ignored_annotations = spawn_workflow_request
+            .annotations
+            .keys()
+            .filter(|name| {
+                !super::deployment_state::INHERITED_ANNOTATIONS.contains(&name.as_str())
+                    && name.as_str() != edgeless_api::workflow_instance::DEAD_LETTER_ANNOTATION
+            })
+            .count();
+        if num_ignored_annotations > 0 {
+            log::warn!("Workflow annotations ({}) are currently ignored", num_ignored_annotations);
         }
 
         // Optimistically identify a new identifier for the workflow that
@@ -265,31 +286,32 @@ impl ControllerTask {
             workflow_id: uuid::Uuid::new_v4(),
         };
 
-        // Find a domain that can host all the workflow's functions and
-        // resources.
-        let candidate_domains = Self::workflow_compatible_domains(&self.orchestrators, &spawn_workflow_request);
+        let domain_assignments = self.domain_assignments(&spawn_workflow_request);
+        if domain_assignments.is_empty() {
+            Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::ResponseError(
+                edgeless_api::common::ResponseError {
+                    summary: "Workflow creation failed".to_string(),
+                    detail: None,
+                },
+            ))
+        } else {
+            self.relocate_workflow(&wf_id, spawn_workflow_request, domain_assignments).await
+        }
+    }
 
+    /// Return a candidate assignment of all the functions/resources of a
+    /// workflow to domains, or an empty map if this is not possible.
+    ///
+    /// A domain that can host the whole workflow is preferred, otherwise
+    /// multiple domains attached to the portal, if any, are used.
+    fn domain_assignments(
+        &mut self,
+        spawn_workflow_request: &edgeless_api::workflow_instance::SpawnWorkflowRequest,
+    ) -> std::collections::HashMap<String, String> {
+        let candidate_domains = Self::workflow_compatible_domains(&self.orchestrators, spawn_workflow_request);
         match candidate_domains.choose(&mut self.rng) {
-            Some(target_domain) => {
-                let domain_assignments = Self::fill_domains(&spawn_workflow_request, target_domain);
-                self.relocate_workflow(&wf_id, spawn_workflow_request, domain_assignments).await
-            }
-            None => {
-                // No single domain was able to host the workflow.
-                // Try again with multiple domains attached to the portal, if any.
-                let domain_assignments = self.domain_assignments_portal(&spawn_workflow_request);
-
-                if domain_assignments.is_empty() {
-                    Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::ResponseError(
-                        edgeless_api::common::ResponseError {
-                            summary: "Workflow creation failed".to_string(),
-                            detail: None,
-                        },
-                    ))
-                } else {
-                    self.relocate_workflow(&wf_id, spawn_workflow_request, domain_assignments).await
-                }
-            }
+            Some(target_domain) => Self::fill_domains(spawn_workflow_request, target_domain),
+            None => self.domain_assignments_portal(spawn_workflow_request),
         }
     }
 
@@ -362,6 +384,7 @@ impl ControllerTask {
         }
         let mut update_output_mappings = vec![];
         let mut augmented_spec = spawn_workflow_request.clone();
+        augmented_spec.expand_dead_letter();
         for (component, output_mappings) in augmented_spec.output_mappings() {
             let origin_domain = domain_assignments.get(&component).unwrap();
             for (channel, target_component_name) in output_mappings {
@@ -438,6 +461,14 @@ impl ControllerTask {
             }
         }
 
+        // Make sure that the portal resource identifiers are not reused
+        // after a restart of the controller.
+        if !new_resources.is_empty()
+            && let Err(err) = self.store.set_last_portal_resource_id(self.last_portal_resource_id).await
+        {
+            log::warn!("error saving the last portal resource identifier: {}", err);
+        }
+
         // Add the new resources to the augmented workflow and update the
         // domain mapping.
         let mut domain_assignments = domain_assignments;
@@ -470,16 +501,23 @@ impl ControllerTask {
         // First pass: create instances for all the functions and resources.
         //
 
-        // Start the functions on the orchestration domain.
+        // Start the functions on the orchestration domain, together with
+        // the stable version of those subject to a canary upgrade.
+        let canary_stables = self.canary_stables.get(wf_id).cloned().unwrap_or_default();
         for function in &augmented_spec.functions {
             if res.is_err() {
                 log::error!("Could not start a function {}", res.clone().unwrap_err());
                 break;
             }
 
-            res = self
-                .start_workflow_function_in_domain(wf_id, &mut workflow, function, domain_assignments.get(&function.name).unwrap())
-                .await;
+            let domain = domain_assignments.get(&function.name).unwrap();
+            res = match (super::deployment_state::canary_weight(function), canary_stables.get(&function.name)) {
+                (Some(weight), Some(stable)) => {
+                    self.start_canary_function_in_domain(wf_id, &mut workflow, function, stable, weight, domain)
+                        .await
+                }
+                _ => self.start_workflow_function_in_domain(wf_id, &mut workflow, function, domain).await,
+            };
         }
 
         // Start the resources on the orchestration domain.
@@ -509,8 +547,9 @@ impl ControllerTask {
             // Loop on all the identifiers for this function/resource
             // (once for each orchestration domain to which the
             // function/resource was allocated).
-            for origin_fid in workflow.mapped_fids(&component_name).unwrap() {
+            for origin_fid in workflow.domain_mapping.get(&component_name).unwrap().lids() {
                 let physical_mapping = workflow.physical_mapping(augmented_spec.output_mappings().get(&component_name).unwrap());
+                let physical_splits = workflow.physical_splits(augmented_spec.output_mappings().get(&component_name).unwrap());
 
                 if physical_mapping.is_empty() {
                     continue;
@@ -537,7 +576,14 @@ impl ControllerTask {
                 }
 
                 res = self
-                    .patch_outputs(origin_domain, origin_fid, component_type, physical_mapping, &component_name)
+                    .patch_outputs(
+                        origin_domain,
+                        origin_fid,
+                        component_type,
+                        physical_mapping,
+                        physical_splits,
+                        &component_name,
+                    )
                     .await;
             }
         }
@@ -568,6 +614,8 @@ impl ControllerTask {
                 ))
             }
         } else {
+            // The stable versions are now part of the active workflow.
+            self.canary_stables.remove(wf_id);
             Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::WorkflowInstance(
                 edgeless_api::workflow_instance::WorkflowInstance {
                     workflow_id: wf_id.clone(),
@@ -590,40 +638,71 @@ impl ControllerTask {
         };
 
         // Stop all the functions/resources.
-        for component in workflow.domain_mapping.values() {
-            log::debug!("stopping function/resource of workflow {}: {}", wf_id, &component);
-            let orc_api = match self.orchestrators.get_mut(&component.domain_id) {
-                None => {
-                    log::warn!(
-                        "Orchestration domain '{}' for workflow '{}' component '{}' disappeared",
-                        &component.domain_id,
-                        wf_id,
-                        &component.name,
-                    );
-                    continue;
-                }
-                Some(val) => val,
-            };
-            let mut fn_client = orc_api.client.function_instance_api();
-            let mut resource_client = orc_api.client.resource_configuration_api();
-            match component.component_type {
-                super::ComponentType::Function => {
-                    if let Err(err) = fn_client.stop(component.lid).await {
-                        log::error!("Unhandled error when stopping wf '{}' function '{}': {}", wf_id, component.name, err);
-                    }
-                }
-                super::ComponentType::Resource => {
-                    if let Err(err) = resource_client.stop(component.lid).await {
-                        log::error!("Unhandled error when stopping wf '{}' resource '{}': {}", wf_id, component.name, err);
-                    }
-                }
-            }
+        let components = workflow.domain_mapping.values().cloned().collect::<Vec<_>>();
+        for component in components {
+            self.stop_component(wf_id, &component).await;
         }
 
-        // Remove the workflow from the active set.
+        // Remove the workflow from the active set, keeping the stable version
+        // of the functions subject to a canary upgrade in case the workflow
+        // is started again.
         let remove_res = self.active_workflows.remove(wf_id);
         assert!(remove_res.is_some());
-        Some(remove_res.unwrap().desired_state)
+        let workflow = remove_res.unwrap();
+        self.save_canary_stables(wf_id, &workflow);
+        Some(workflow.desired_state)
+    }
+
+    /// Save the stable version of the functions of a workflow that are
+    /// subject to a canary upgrade, if any.
+    fn save_canary_stables(&mut self, wf_id: &edgeless_api::workflow_instance::WorkflowId, workflow: &super::deployment_state::ActiveWorkflow) {
+        let stables = workflow.canary_stables();
+        if !stables.is_empty() {
+            self.canary_stables.entry(wf_id.clone()).or_default().extend(stables);
+        }
+    }
+
+    /// Stop a single function/resource of a workflow, including its canary
+    /// version, if any.
+    async fn stop_component(&mut self, wf_id: &edgeless_api::workflow_instance::WorkflowId, component: &super::deployment_state::ActiveComponent) {
+        for lid in component.lids() {
+            self.stop_component_instance(wf_id, component, lid).await;
+        }
+    }
+
+    /// Stop one of the instances of a function/resource of a workflow,
+    /// identified by its logical identifier.
+    async fn stop_component_instance(
+        &mut self,
+        wf_id: &edgeless_api::workflow_instance::WorkflowId,
+        component: &super::deployment_state::ActiveComponent,
+        lid: edgeless_api::function_instance::ComponentId,
+    ) {
+        log::debug!("stopping function/resource of workflow {}: {}, lid {}", wf_id, &component, lid);
+        let orc_api = match self.orchestrators.get_mut(&component.domain_id) {
+            None => {
+                log::warn!(
+                    "Orchestration domain '{}' for workflow '{}' component '{}' disappeared",
+                    &component.domain_id,
+                    wf_id,
+                    &component.name,
+                );
+                return;
+            }
+            Some(val) => val,
+        };
+        match component.component_type {
+            super::ComponentType::Function => {
+                if let Err(err) = orc_api.client.function_instance_api().stop(lid).await {
+                    log::error!("Unhandled error when stopping wf '{}' function '{}': {}", wf_id, component.name, err);
+                }
+            }
+            super::ComponentType::Resource => {
+                if let Err(err) = orc_api.client.resource_configuration_api().stop(lid).await {
+                    log::error!("Unhandled error when stopping wf '{}' resource '{}': {}", wf_id, component.name, err);
+                }
+            }
+        }
     }
 
     fn list(&self) -> Vec<edgeless_api::workflow_instance::WorkflowId> {
@@ -718,9 +797,28 @@ impl ControllerTask {
                     },
                 );
 
-                // It is a new orchestration domain. Therefore, we ask the
-                // orchestrator to reset to a clean state.
-                (Ok(edgeless_api::domain_registration::UpdateDomainResponse::Reset), true)
+                // If the domain was known before the controller restarted
+                // and it has the same nonce, then the orchestrator still
+                // hosts the functions/resources of the recovered workflows,
+                // which are reconciled with those that it reports as active.
+                // Otherwise, it is a new orchestration domain, or the
+                // orchestrator has restarted too and its functions/resources
+                // are lost: we ask the orchestrator to reset to a clean state.
+                let response = match self.recovered_domains.remove(&update_domain_request.domain_id) {
+                    Some(nonce) if nonce == update_domain_request.nonce => {
+                        log::info!("Domain '{}' reconciled after the controller restart", update_domain_request.domain_id);
+                        self.reconcile_domain(update_domain_request).await;
+                        edgeless_api::domain_registration::UpdateDomainResponse::Accepted
+                    }
+                    Some(_) => {
+                        self.invalidate_domain(&update_domain_request.domain_id).await;
+                        edgeless_api::domain_registration::UpdateDomainResponse::Reset
+                    }
+                    None => edgeless_api::domain_registration::UpdateDomainResponse::Reset,
+                };
+                self.persist_domain(&update_domain_request.domain_id, Some(update_domain_request.nonce))
+                    .await;
+                (Ok(response), true)
             }
             Some(desc) => {
                 // If the nonce is different: this is a new instance of an
@@ -760,6 +858,14 @@ impl ControllerTask {
                     }
                 };
                 desc.refresh_deadline = update_domain_request.refresh_deadline;
+
+                // The orchestrator has restarted: the functions/resources
+                // that it hosted are lost.
+                if response == edgeless_api::domain_registration::UpdateDomainResponse::Reset {
+                    self.invalidate_domain(&update_domain_request.domain_id).await;
+                    self.persist_domain(&update_domain_request.domain_id, Some(update_domain_request.nonce))
+                        .await;
+                }
                 (Ok(response), update_portal_domain)
             }
         };
@@ -926,6 +1032,307 @@ impl ControllerTask {
         }
     }
 
+    /// Update a workflow to a new specification, keeping its identifier.
+    ///
+    /// If the workflow is deployed on a single domain that is compatible with
+    /// the new specification, then only the functions/resources added or
+    /// changed are started, the output mappings affected are patched, and
+    /// the functions/resources removed or changed are stopped.
+    /// Otherwise, the workflow is relocated with the new specification,
+    /// including the canary upgrades, see `redeploy_workflow()`.
+    /// An orphan workflow has only its specification replaced.
+    async fn update_workflow(
+        &mut self,
+        request: &edgeless_api::workflow_instance::UpdateWorkflowRequest,
+    ) -> anyhow::Result<edgeless_api::workflow_instance::SpawnWorkflowResponse> {
+        let wf_id = &request.workflow_id;
+        let new_spec = &request.workflow;
+
+        if let Err(err) = new_spec.is_valid() {
+            return Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::ResponseError(
+                edgeless_api::common::ResponseError {
+                    summary: String::from("Invalid workflow update request"),
+                    detail: Some(err.to_string()),
+                },
+            ));
+        }
+
+        if let Some(orphan_workflow) = self.orphan_workflows.get_mut(wf_id) {
+            *orphan_workflow = new_spec.clone();
+            // The stable versions are kept only for the functions that are
+            // still subject to a canary upgrade.
+            if let Some(stables) = self.canary_stables.get_mut(wf_id) {
+                stables.retain(|name, _| {
+                    new_spec
+                        .get_function(name)
+                        .is_some_and(|function| super::deployment_state::canary_weight(function).is_some())
+                });
+                if stables.is_empty() {
+                    self.canary_stables.remove(wf_id);
+                }
+            }
+            return Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::WorkflowInstance(
+                edgeless_api::workflow_instance::WorkflowInstance {
+                    workflow_id: wf_id.clone(),
+                    domain_mapping: vec![],
+                },
+            ));
+        }
+
+        let workflow = match self.active_workflows.get(wf_id) {
+            Some(workflow) => workflow.clone(),
+            None => {
+                return Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::ResponseError(
+                    edgeless_api::common::ResponseError {
+                        summary: String::from("Unknown workflow id"),
+                        detail: Some(wf_id.to_string()),
+                    },
+                ));
+            }
+        };
+
+        // Update the workflow in place only if it is entirely hosted by a
+        // domain that can host also the new functions/resources.
+        let domains = workflow
+            .domain_mapping
+            .values()
+            .map(|component| component.domain_id.clone())
+            .collect::<std::collections::HashSet<String>>();
+        let target_domain = match domains.into_iter().collect::<Vec<String>>().as_slice() {
+            [domain]
+                if self
+                    .orchestrators
+                    .get(domain)
+                    .is_some_and(|desc| Self::is_workflow_compatible(desc, new_spec)) =>
+            {
+                domain.clone()
+            }
+            _ => return Ok(self.redeploy_workflow(wf_id, new_spec.clone()).await),
+        };
+
+        let old_spec = &workflow.desired_state;
+        let diff = super::deployment_state::WorkflowDiff::new(old_spec, new_spec);
+        log::info!(
+            "updating workflow {}: added {:?}, changed {:?}, removed {:?}, canary {:?}",
+            wf_id,
+            diff.added,
+            diff.changed,
+            diff.removed,
+            diff.canary
+        );
+
+        // The output mappings include the dead-letter channel, if any.
+        let mut augmented_spec = new_spec.clone();
+        augmented_spec.expand_dead_letter();
+        let mut old_augmented_spec = old_spec.clone();
+        old_augmented_spec.expand_dead_letter();
+
+        let mut new_workflow = ActiveWorkflow {
+            desired_state: new_spec.clone(),
+            augmented_spec: Some(augmented_spec.clone()),
+            domain_mapping: workflow.domain_mapping.clone(),
+        };
+        new_workflow.domain_mapping.retain(|name, _| !diff.removed.contains(name));
+
+        // Keep the last error.
+        let mut res: Result<(), String> = Ok(());
+
+        // Start the functions/resources added or changed, and the new
+        // versions of the functions subject to a canary upgrade.
+        for function in &new_spec.functions {
+            if res.is_err() {
+                break;
+            }
+            if diff.is_started(&function.name) {
+                res = self
+                    .start_workflow_function_in_domain(wf_id, &mut new_workflow, function, &target_domain)
+                    .await;
+            } else if diff.canary.contains(&function.name) {
+                res = self
+                    .update_canary(
+                        wf_id,
+                        &mut new_workflow,
+                        &old_spec.annotations,
+                        old_spec.get_function(&function.name).unwrap(),
+                        function,
+                        &target_domain,
+                    )
+                    .await;
+            }
+        }
+        for resource in &new_spec.resources {
+            if res.is_err() {
+                break;
+            }
+            if diff.is_started(&resource.name) {
+                res = self
+                    .start_workflow_resource_in_domain(wf_id, &mut new_workflow, resource, &target_domain)
+                    .await;
+            }
+        }
+
+        // If a function/resource could not be started, stop those that have
+        // been started, while the workflow keeps running as before.
+        let old_lids = workflow.lids();
+        if let Err(err) = res {
+            log::error!("Update of workflow {} failed: {}", wf_id, err);
+            for component in new_workflow.domain_mapping.values() {
+                for lid in component.lids() {
+                    if !old_lids.contains(&lid) {
+                        self.stop_component_instance(wf_id, component, lid).await;
+                    }
+                }
+            }
+            return Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::ResponseError(
+                edgeless_api::common::ResponseError {
+                    summary: String::from("Workflow update failed"),
+                    detail: Some(err),
+                },
+            ));
+        }
+
+        // Patch the functions/resources started, those whose output mapping
+        // has changed, and those that map to a function/resource started or
+        // whose canary version has changed.
+        let old_output_mappings = old_augmented_spec.output_mappings();
+        for (component_name, output_mapping) in augmented_spec.output_mappings() {
+            if res.is_err() {
+                break;
+            }
+            let patch_all = !diff.is_started(&component_name)
+                && (old_output_mappings.get(&component_name) != Some(&output_mapping)
+                    || output_mapping.values().any(|target| diff.is_retargeted(target)));
+
+            let component = new_workflow.domain_mapping.get(&component_name).unwrap().clone();
+            let physical_mapping = new_workflow.physical_mapping(&output_mapping);
+            let physical_splits = new_workflow.physical_splits(&output_mapping);
+            for lid in component.lids() {
+                if patch_all || (!old_lids.contains(&lid) && !output_mapping.is_empty()) {
+                    res = self
+                        .patch_outputs(
+                            &target_domain,
+                            lid,
+                            component.component_type.clone(),
+                            physical_mapping.clone(),
+                            physical_splits.clone(),
+                            &component_name,
+                        )
+                        .await;
+                    if res.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Stop the instances of the functions/resources that are not part
+        // of the workflow anymore, i.e., those removed, the previous
+        // instances of those changed, and the versions of the functions
+        // replaced by a canary upgrade, promotion or rollback.
+        let new_lids = new_workflow.lids();
+        for component in workflow.domain_mapping.values() {
+            for lid in component.lids() {
+                if !new_lids.contains(&lid) {
+                    self.stop_component_instance(wf_id, component, lid).await;
+                }
+            }
+        }
+
+        self.active_workflows.insert(wf_id.clone(), new_workflow);
+
+        // If the workflow could not be patched, it is left in an
+        // inconsistent state: we stop it and try to start it again later.
+        if let Err(err) = res {
+            log::error!("Update of workflow {} failed, restarting it: {}", wf_id, err);
+            if let Some(spec) = self.stop_workflow(wf_id).await {
+                self.orphan_workflows.insert(wf_id.clone(), spec);
+            }
+            return Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::ResponseError(
+                edgeless_api::common::ResponseError {
+                    summary: String::from("Workflow update failed, the workflow will be restarted"),
+                    detail: Some(err),
+                },
+            ));
+        }
+
+        log::info!("workflow '{}' successfully updated", wf_id);
+        Ok(edgeless_api::workflow_instance::SpawnWorkflowResponse::WorkflowInstance(
+            edgeless_api::workflow_instance::WorkflowInstance {
+                workflow_id: wf_id.clone(),
+                domain_mapping: self.active_workflows.get(wf_id).unwrap().domain_mapping(),
+            },
+        ))
+    }
+
+    /// Stop a workflow and start it again with a new specification on the
+    /// domains that can host it, keeping its identifier.
+    ///
+    /// The functions subject to a canary upgrade are started with both their
+    /// stable and canary versions, as with an update in place.
+    async fn redeploy_workflow(
+        &mut self,
+        wf_id: &edgeless_api::workflow_instance::WorkflowId,
+        new_spec: edgeless_api::workflow_instance::SpawnWorkflowRequest,
+    ) -> edgeless_api::workflow_instance::SpawnWorkflowResponse {
+        let domain_assignments = self.domain_assignments(&new_spec);
+        if domain_assignments.is_empty() {
+            return edgeless_api::workflow_instance::SpawnWorkflowResponse::ResponseError(edgeless_api::common::ResponseError {
+                summary: String::from("Workflow update cannot be satisfied"),
+                detail: Some(wf_id.to_string()),
+            });
+        }
+
+        // The functions for which a canary upgrade starts keep their current
+        // version as the stable one, while those already subject to a canary
+        // upgrade keep their stable version when the workflow is stopped.
+        let mut new_stables = std::collections::HashMap::new();
+        if let Some(workflow) = self.active_workflows.get(wf_id) {
+            let old_spec = &workflow.desired_state;
+            for function in &new_spec.functions {
+                if super::deployment_state::canary_weight(function).is_none()
+                    || workflow
+                        .domain_mapping
+                        .get(&function.name)
+                        .is_some_and(|component| component.canary.is_some())
+                {
+                    continue;
+                }
+                if let Some(old_function) = old_spec.get_function(&function.name)
+                    && !super::deployment_state::same_version(&old_spec.annotations, old_function, &new_spec.annotations, function)
+                {
+                    new_stables.insert(
+                        function.name.clone(),
+                        edgeless_api::workflow_instance::WorkflowFunction {
+                            annotations: super::deployment_state::function_annotations(&old_spec.annotations, old_function),
+                            ..old_function.clone()
+                        },
+                    );
+                }
+            }
+        }
+
+        log::info!("updating workflow {} by redeploying it", wf_id);
+        if self.stop_workflow(wf_id).await.is_none() {
+            return edgeless_api::workflow_instance::SpawnWorkflowResponse::ResponseError(edgeless_api::common::ResponseError {
+                summary: String::from("Error when terminating the workflow during update"),
+                detail: Some(wf_id.to_string()),
+            });
+        }
+        if !new_stables.is_empty() {
+            self.canary_stables.entry(wf_id.clone()).or_default().extend(new_stables);
+        }
+        match self.relocate_workflow(wf_id, new_spec, domain_assignments).await {
+            Ok(response) => response,
+            Err(spec) => {
+                self.orphan_workflows.insert(wf_id.clone(), spec);
+                edgeless_api::workflow_instance::SpawnWorkflowResponse::ResponseError(edgeless_api::common::ResponseError {
+                    summary: String::from("Error when updating the workflow"),
+                    detail: Some(wf_id.to_string()),
+                })
+            }
+        }
+    }
+
     async fn refresh(&mut self) {
         log::debug!("Checking domains");
 
@@ -938,26 +1345,28 @@ impl ControllerTask {
             }
         }
 
+        // Domains known before the restart of the controller that have not
+        // registered again in time are stale, too.
+        if !self.recovered_domains.is_empty() && std::time::SystemTime::now() > self.recovery_deadline {
+            for (domain_id, _nonce) in std::mem::take(&mut self.recovered_domains) {
+                log::info!("Domain '{}' did not register again after the controller restart", domain_id);
+                stale_domains.push(domain_id);
+            }
+        }
+
         // Delete all stale domains, also invalidating all mapping of functions
         // and resources of active flows.
         let domains_removed = !stale_domains.is_empty();
         for stale_domain in stale_domains {
             log::info!("Removing domain '{}' because it is stale", stale_domain);
             self.orchestrators.remove(&stale_domain);
-
-            for workflow in &mut self.active_workflows.values_mut() {
-                for component in workflow.domain_mapping.values_mut() {
-                    if component.domain_id == stale_domain {
-                        component.domain_id.clear();
-                    }
-                }
-            }
+            self.persist_domain(&stale_domain, None).await;
+            self.invalidate_domain(&stale_domain).await;
         }
 
-        // If some domains were removed there might be new orphans, and the
-        // portal domain status might have changed.
+        // If some domains were removed the portal domain status might have
+        // changed.
         if domains_removed {
-            self.find_new_orphans().await;
             self.update_portal_domain().await;
         }
 
@@ -1109,6 +1518,77 @@ impl ControllerTask {
             .collect()
     }
 
+    /// Invalidate the mapping of all the functions and resources of active
+    /// workflows to the given domain, which then become orphans.
+    async fn invalidate_domain(&mut self, domain_id: &str) {
+        for workflow in &mut self.active_workflows.values_mut() {
+            for component in workflow.domain_mapping.values_mut() {
+                if component.domain_id == domain_id {
+                    component.domain_id.clear();
+                }
+            }
+        }
+        self.find_new_orphans().await;
+    }
+
+    /// Reconcile the recovered workflows with the functions/resources that
+    /// the orchestrator of a domain reports as active:
+    /// - the workflows with functions/resources in the domain that are not
+    ///   active are stopped and become orphans, to be deployed again;
+    /// - the functions/resources that are active but do not belong to any
+    ///   workflow are stopped.
+    async fn reconcile_domain(&mut self, update_domain_request: &edgeless_api::domain_registration::UpdateDomainRequest) {
+        let domain_id = &update_domain_request.domain_id;
+        let is_active = |component: &super::deployment_state::ActiveComponent, lid: &uuid::Uuid| match component.component_type {
+            super::ComponentType::Function => update_domain_request.active_functions.contains(lid),
+            super::ComponentType::Resource => update_domain_request.active_resources.contains(lid),
+        };
+
+        let mut known_lids = std::collections::HashSet::new();
+        let mut lost_workflows = vec![];
+        for (wf_id, workflow) in &self.active_workflows {
+            let mut lost = false;
+            for component in workflow.domain_mapping.values().filter(|component| &component.domain_id == domain_id) {
+                for lid in component.lids() {
+                    lost |= !is_active(component, &lid);
+                    known_lids.insert(lid);
+                }
+            }
+            if lost {
+                lost_workflows.push(wf_id.clone());
+            }
+        }
+
+        for wf_id in lost_workflows {
+            log::info!(
+                "Workflow {} lost some functions/resources in domain '{}' while the controller was down",
+                wf_id,
+                domain_id
+            );
+            if let Some(request) = self.stop_workflow(&wf_id).await {
+                self.orphan_workflows.insert(wf_id.clone(), request);
+                self.persist_workflow(&wf_id).await;
+            }
+        }
+
+        for lid in update_domain_request.active_functions.difference(&known_lids) {
+            log::info!("Stopping unknown function in domain '{}', lid {}", domain_id, lid);
+            if let Some(mut fn_client) = self.fn_client(domain_id)
+                && let Err(err) = fn_client.stop(*lid).await
+            {
+                log::error!("Unhandled error when stopping function in domain '{}', lid {}: {}", domain_id, lid, err);
+            }
+        }
+        for lid in update_domain_request.active_resources.difference(&known_lids) {
+            log::info!("Stopping unknown resource in domain '{}', lid {}", domain_id, lid);
+            if let Some(mut resource_client) = self.resource_client(domain_id)
+                && let Err(err) = resource_client.stop(*lid).await
+            {
+                log::error!("Unhandled error when stopping resource in domain '{}', lid {}: {}", domain_id, lid, err);
+            }
+        }
+    }
+
     /// Check all active workflows.
     /// If a workflow has at least one resource or function that is not assigned
     /// to a domain, then it is marked as orphan.
@@ -1124,8 +1604,10 @@ impl ControllerTask {
                 .active_workflows
                 .remove(&wf_id)
                 .expect("Could not find a workflow that must be there");
-            let res = self.orphan_workflows.insert(wf_id, active_workflow.desired_state);
+            self.save_canary_stables(&wf_id, &active_workflow);
+            let res = self.orphan_workflows.insert(wf_id.clone(), active_workflow.desired_state);
             assert!(res.is_none(), "Trying to mark as orphan a workflow that already so");
+            self.persist_workflow(&wf_id).await;
         }
     }
 
@@ -1187,6 +1669,7 @@ impl ControllerTask {
                     if let edgeless_api::workflow_instance::SpawnWorkflowResponse::WorkflowInstance(_) = response {
                         log::info!("orphan workflow {} relocated ", wf_id);
                     }
+                    self.persist_workflow(&wf_id).await;
                 }
                 Err(workflow_request) => {
                     self.orphan_workflows.insert(wf_id, workflow_request);
@@ -1202,20 +1685,93 @@ impl ControllerTask {
         function: &edgeless_api::workflow_instance::WorkflowFunction,
         domain: &str,
     ) -> Result<(), String> {
-        // [TODO] Issue#95
-        // The state_specification configuration should be
-        // read from the function annotations.
-        log::debug!("state specifications currently forced to NodeLocal");
+        let lid = self
+            .start_function_in_domain(wf_id, &workflow.desired_state.annotations, function, domain)
+            .await?;
+        workflow.domain_mapping.insert(
+            function.name.clone(),
+            super::deployment_state::ActiveComponent {
+                component_type: super::ComponentType::Function,
+                name: function.name.clone(),
+                domain_id: domain.to_string(),
+                lid,
+                canary: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Start both the stable and the canary version of a function subject to
+    /// a canary upgrade, which receives the given percentage of the events.
+    async fn start_canary_function_in_domain(
+        &mut self,
+        wf_id: &edgeless_api::workflow_instance::WorkflowId,
+        workflow: &mut ActiveWorkflow,
+        function: &edgeless_api::workflow_instance::WorkflowFunction,
+        stable: &edgeless_api::workflow_instance::WorkflowFunction,
+        weight: u32,
+        domain: &str,
+    ) -> Result<(), String> {
+        let stable_function = edgeless_api::workflow_instance::WorkflowFunction {
+            class_specification: stable.class_specification.clone(),
+            annotations: stable.annotations.clone(),
+            ..function.clone()
+        };
+        let lid = self
+            .start_function_in_domain(wf_id, &workflow.desired_state.annotations, &stable_function, domain)
+            .await?;
+        // The stable version is part of the workflow even if the canary
+        // version cannot be started, so that it is stopped with it.
+        workflow.domain_mapping.insert(
+            function.name.clone(),
+            super::deployment_state::ActiveComponent {
+                component_type: super::ComponentType::Function,
+                name: function.name.clone(),
+                domain_id: domain.to_string(),
+                lid,
+                canary: None,
+            },
+        );
+        let canary_lid = self
+            .start_function_in_domain(wf_id, &workflow.desired_state.annotations, function, domain)
+            .await?;
+        log::info!(
+            "workflow {} function {} canary version {} started with weight {}",
+            wf_id,
+            function.name,
+            function.class_specification.version,
+            weight
+        );
+        if let Some(component) = workflow.domain_mapping.get_mut(&function.name) {
+            component.canary = Some(super::deployment_state::CanaryComponent {
+                lid: canary_lid,
+                weight,
+                stable: stable.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Start a function instance in a domain and return its logical
+    /// identifier. The function inherits the annotations of the workflow
+    /// in `INHERITED_ANNOTATIONS` that it does not override.
+    async fn start_function_in_domain(
+        &mut self,
+        wf_id: &edgeless_api::workflow_instance::WorkflowId,
+        workflow_annotations: &std::collections::HashMap<String, String>,
+        function: &edgeless_api::workflow_instance::WorkflowFunction,
+        domain: &str,
+    ) -> Result<edgeless_api::function_instance::ComponentId, String> {
+        let state_specification =
+            edgeless_api::function_instance::StateSpecification::from_annotations(&wf_id.workflow_id, &function.name, &function.annotations)
+                .map_err(|err| format!("invalid state specification of function {}: {}", function.name, err))?;
         let response = self
             .fn_client(domain)
             .ok_or(format!("No function client for domain: {}", domain))?
             .start(edgeless_api::function_instance::SpawnFunctionRequest {
                 spec: function.class_specification.clone(),
-                annotations: function.annotations.clone(),
-                state_specification: edgeless_api::function_instance::StateSpecification {
-                    state_id: uuid::Uuid::new_v4(),
-                    state_policy: edgeless_api::function_instance::StatePolicy::NodeLocal,
-                },
+                annotations: super::deployment_state::function_annotations(workflow_annotations, function),
+                state_specification,
                 workflow_id: wf_id.workflow_id.to_string(),
             })
             .await;
@@ -1229,22 +1785,92 @@ impl ControllerTask {
                 edgeless_api::common::StartComponentResponse::InstanceId(id) => {
                     log::info!("workflow {} function {} started with fid {}", wf_id, function.name, &id);
                     // id.node_id is unused
-                    workflow.domain_mapping.insert(
-                        function.name.clone(),
-                        super::deployment_state::ActiveComponent {
-                            component_type: super::ComponentType::Function,
-                            name: function.name.clone(),
-                            domain_id: domain.to_string(),
-                            lid: id,
-                        },
-                    );
-                    Ok(())
+                    Ok(id)
                 }
             },
             Err(err) => Err(format!("failed interaction when creating a function instance: {}", err)),
         }
     }
 
+    /// Update a function that is subject to a canary upgrade.
+    ///
+    /// - If the new specification has a canary weight annotated:
+    ///   - if it is the same version as the current canary, then only the
+    ///     weight is changed;
+    ///   - otherwise, the new version is started next to the current one,
+    ///     replacing the previous canary version, if any.
+    /// - If the new specification has no canary weight annotated:
+    ///   - if it is the same version as the current canary, then the canary
+    ///     version is promoted to the current one;
+    ///   - if it is the same version as the current one, then the canary
+    ///     version is rolled back;
+    ///   - otherwise, the function is started again with the new
+    ///     specification.
+    ///
+    /// The instances that are not needed anymore are stopped by the caller.
+    async fn update_canary(
+        &mut self,
+        wf_id: &edgeless_api::workflow_instance::WorkflowId,
+        workflow: &mut ActiveWorkflow,
+        old_workflow_annotations: &std::collections::HashMap<String, String>,
+        old_function: &edgeless_api::workflow_instance::WorkflowFunction,
+        function: &edgeless_api::workflow_instance::WorkflowFunction,
+        domain: &str,
+    ) -> Result<(), String> {
+        let mut component = workflow.domain_mapping.get(&function.name).unwrap().clone();
+        let workflow_annotations = &workflow.desired_state.annotations;
+        let same_version_as_old = super::deployment_state::same_version(old_workflow_annotations, old_function, workflow_annotations, function);
+        match (super::deployment_state::canary_weight(function), component.canary.take()) {
+            (Some(weight), Some(mut canary)) if same_version_as_old => {
+                log::info!(
+                    "workflow {} function {} canary weight {} -> {}",
+                    wf_id,
+                    function.name,
+                    canary.weight,
+                    weight
+                );
+                canary.weight = weight;
+                component.canary = Some(canary);
+            }
+            (Some(weight), canary) => {
+                let lid = self
+                    .start_function_in_domain(wf_id, &workflow.desired_state.annotations, function, domain)
+                    .await?;
+                log::info!(
+                    "workflow {} function {} canary version {} started with weight {}",
+                    wf_id,
+                    function.name,
+                    function.class_specification.version,
+                    weight
+                );
+                let stable = match canary {
+                    Some(canary) => canary.stable,
+                    None => edgeless_api::workflow_instance::WorkflowFunction {
+                        annotations: super::deployment_state::function_annotations(old_workflow_annotations, old_function),
+                        ..old_function.clone()
+                    },
+                };
+                component.canary = Some(super::deployment_state::CanaryComponent { lid, weight, stable });
+            }
+            (None, Some(canary)) if same_version_as_old => {
+                log::info!("workflow {} function {} canary version promoted", wf_id, function.name);
+                component.lid = canary.lid;
+            }
+            (None, Some(canary))
+                if super::deployment_state::same_version(&std::collections::HashMap::new(), &canary.stable, workflow_annotations, function) =>
+            {
+                log::info!("workflow {} function {} canary version rolled back", wf_id, function.name);
+            }
+            (None, _) => {
+                component.lid = self
+                    .start_function_in_domain(wf_id, &workflow.desired_state.annotations, function, domain)
+                    .await?;
+            }
+        }
+        workflow.domain_mapping.insert(function.name.clone(), component);
+        Ok(())
+    }
+
     async fn start_workflow_resource_in_domain(
         &mut self,
         wf_id: &edgeless_api::workflow_instance::WorkflowId,
@@ -1278,6 +1904,7 @@ impl ControllerTask {
                             name: resource.name.clone(),
                             domain_id: domain.to_string(),
                             lid: id,
+                            canary: None,
                         },
                     );
                     Ok(())
@@ -1293,6 +1920,7 @@ impl ControllerTask {
         origin_id: uuid::Uuid,
         origin_type: super::ComponentType,
         output_mapping: std::collections::HashMap<String, edgeless_api::function_instance::InstanceId>,
+        output_splits: std::collections::HashMap<String, Vec<edgeless_api::common::WeightedTarget>>,
         name_in_workflow: &str,
     ) -> Result<(), String> {
         match origin_type {
@@ -1303,6 +1931,7 @@ impl ControllerTask {
                     .patch(edgeless_api::common::PatchRequest {
                         function_id: origin_id,
                         output_mapping,
+                        output_splits,
                     })
                     .await
                 {
@@ -1317,6 +1946,7 @@ impl ControllerTask {
                     .patch(edgeless_api::common::PatchRequest {
                         function_id: origin_id,
                         output_mapping,
+                        output_splits,
                     })
                     .await
                 {
@@ -1335,52 +1965,3 @@ impl ControllerTask {
         Some(self.orchestrators.get_mut(domain)?.client.resource_configuration_api())
     }
 }
-
-#[cfg(test)]
-mod tests {
-    use edgeless_api::workflow_instance::SpawnWorkflowRequest;
-
-    use super::*;
-
-    #[tokio::test]
-    async fn test_serialize_deserialize_controller_task_state() {
-        let mut expected_state = PersistedState { workflows: vec![] };
-
-        let serialized = serde_json::to_string(&expected_state).unwrap();
-        let actual_state: PersistedState = serde_json::from_str(&serialized).unwrap();
-        assert_eq!(expected_state, actual_state);
-
-        for i in 0..10 {
-            let functions = vec![edgeless_api::workflow_instance::WorkflowFunction {
-                name: format!("f{}", i),
-                class_specification: edgeless_api::function_instance::FunctionClassSpecification {
-                    id: "test".to_string(),
-                    function_type: "RUST_WASM".to_string(),
-                    version: "0.1".to_string(),
-                    binary: Some(include_bytes!("../../../functions/system_test/system_test.wasm").to_vec()),
-                    code: None,
-                    outputs: vec!["out1".to_string(), "out2".to_string(), "err".to_string(), "log".to_string()],
-                },
-                output_mapping: std::collections::HashMap::new(),
-                annotations: std::collections::HashMap::new(),
-            }];
-            let resources = vec![edgeless_api::workflow_instance::WorkflowResource {
-                name: "log".to_string(),
-                class_type: "file-log".to_string(),
-                output_mapping: std::collections::HashMap::new(),
-                configurations: std::collections::HashMap::from([("filename".to_string(), "example.log".to_string())]),
-            }];
-            let annotations = std::collections::HashMap::from([("ann1".to_string(), "val1".to_string())]);
-            let request = SpawnWorkflowRequest {
-                functions,
-                resources,
-                annotations,
-            };
-            expected_state.workflows.push((uuid::Uuid::new_v4().to_string(), request));
-        }
-
-        let serialized = serde_json::to_string(&expected_state).unwrap();
-        let actual_state: PersistedState = serde_json::from_str(&serialized).unwrap();
-        assert_eq!(expected_state, actual_state);
-    }
-}
@@ -2,7 +2,7 @@
 // SPDX-FileCopyrightText: © 2024 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
 // SPDX-License-Identifier: MIT
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ActiveWorkflow {
     // Workflow as it was requested by the client.
     pub desired_state: edgeless_api::workflow_instance::SpawnWorkflowRequest,
@@ -15,7 +15,7 @@ pub struct ActiveWorkflow {
     pub domain_mapping: std::collections::HashMap<String, ActiveComponent>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ActiveComponent {
     // Function or resource.
     pub component_type: super::ComponentType,
@@ -34,6 +34,70 @@ pub struct ActiveComponent {
 
     // Logical identifier of the function/resource.
     pub lid: edgeless_api::function_instance::ComponentId,
+
+    // New version of the function deployed next to the current one, which
+    // receives only a fraction of the events.
+    #[serde(default)]
+    pub canary: Option<CanaryComponent>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CanaryComponent {
+    // Logical identifier of the new version of the function.
+    pub lid: edgeless_api::function_instance::ComponentId,
+
+    // Percentage of the events directed to the new version.
+    pub weight: u32,
+
+    // Specification of the current version of the function, which is
+    // restored if the new version is rolled back, including the annotations
+    // inherited from the workflow.
+    pub stable: edgeless_api::workflow_instance::WorkflowFunction,
+}
+
+/// Return the canary weight annotated in a function, if any.
+pub fn canary_weight(function: &edgeless_api::workflow_instance::WorkflowFunction) -> Option<u32> {
+    function
+        .annotations
+        .get(edgeless_api::workflow_instance::CANARY_WEIGHT)
+        .and_then(|weight| weight.parse::<u32>().ok())
+}
+
+/// Names of the workflow annotations that apply to all its functions, unless
+/// overridden by the functions' own annotations.
+pub const INHERITED_ANNOTATIONS: [&str; 1] = ["placement"];
+
+/// Return the annotations of a function, including those inherited from the
+/// workflow.
+pub fn function_annotations(
+    workflow_annotations: &std::collections::HashMap<String, String>,
+    function: &edgeless_api::workflow_instance::WorkflowFunction,
+) -> std::collections::HashMap<String, String> {
+    let mut annotations = function.annotations.clone();
+    for name in INHERITED_ANNOTATIONS {
+        if let Some(value) = workflow_annotations.get(name) {
+            annotations.entry(name.to_string()).or_insert(value.clone());
+        }
+    }
+    annotations
+}
+
+/// Return true if two specifications refer to the same version of a
+/// function, i.e., they differ at most by the output mapping or the canary
+/// weight, also considering the annotations inherited from their workflows.
+pub fn same_version(
+    a_workflow_annotations: &std::collections::HashMap<String, String>,
+    a: &edgeless_api::workflow_instance::WorkflowFunction,
+    b_workflow_annotations: &std::collections::HashMap<String, String>,
+    b: &edgeless_api::workflow_instance::WorkflowFunction,
+) -> bool {
+    let annotations = |workflow_annotations: &std::collections::HashMap<String, String>,
+                       function: &edgeless_api::workflow_instance::WorkflowFunction| {
+        let mut annotations = function_annotations(workflow_annotations, function);
+        annotations.remove(edgeless_api::workflow_instance::CANARY_WEIGHT);
+        annotations
+    };
+    a.class_specification == b.class_specification && annotations(a_workflow_annotations, a) == annotations(b_workflow_annotations, b)
 }
 
 impl ActiveWorkflow {
@@ -46,6 +110,21 @@ impl ActiveWorkflow {
         false
     }
 
+    /// Return the logical identifiers of all the instances of all the
+    /// functions/resources, including the canary versions.
+    pub fn lids(&self) -> std::collections::HashSet<edgeless_api::function_instance::ComponentId> {
+        self.domain_mapping.values().flat_map(|component| component.lids()).collect()
+    }
+
+    /// Return the specification of the stable version of the functions
+    /// subject to a canary upgrade, by function name.
+    pub fn canary_stables(&self) -> std::collections::HashMap<String, edgeless_api::workflow_instance::WorkflowFunction> {
+        self.domain_mapping
+            .iter()
+            .filter_map(|(name, component)| Some((name.clone(), component.canary.as_ref()?.stable.clone())))
+            .collect()
+    }
+
     pub fn mapped_fids(&self, component_name: &str) -> Option<Vec<edgeless_api::function_instance::ComponentId>> {
         let comp = self.domain_mapping.get(component_name)?;
         Some(vec![comp.lid])
@@ -112,6 +191,49 @@ impl ActiveWorkflow {
 
         ret
     }
+
+    /// Return the output channels of a logical output_mapping whose events
+    /// are split between the current and the canary version of the target.
+    ///
+    /// Returned map:
+    /// - key: channel name
+    /// - value: PIDs with their weights
+    pub fn physical_splits(
+        &self,
+        logical_mapping: &std::collections::HashMap<String, String>,
+    ) -> std::collections::HashMap<String, Vec<edgeless_api::common::WeightedTarget>> {
+        let weighted_target = |function_id, weight| edgeless_api::common::WeightedTarget {
+            instance_id: edgeless_api::function_instance::InstanceId {
+                node_id: uuid::Uuid::nil(),
+                function_id,
+            },
+            weight,
+        };
+        logical_mapping
+            .iter()
+            .filter_map(|(from_channel, to_name)| {
+                let component = self.domain_mapping.get(to_name)?;
+                let canary = component.canary.as_ref()?;
+                Some((
+                    from_channel.clone(),
+                    vec![
+                        weighted_target(component.lid, 100 - canary.weight.min(100)),
+                        weighted_target(canary.lid, canary.weight.min(100)),
+                    ],
+                ))
+            })
+            .collect()
+    }
+}
+
+impl ActiveComponent {
+    /// Return the logical identifiers of the component, including that of
+    /// the canary version, if any.
+    pub fn lids(&self) -> Vec<edgeless_api::function_instance::ComponentId> {
+        let mut lids = vec![self.lid];
+        lids.extend(self.canary.as_ref().map(|canary| canary.lid));
+        lids
+    }
 }
 
 impl std::fmt::Display for ActiveComponent {
@@ -122,3 +244,73 @@ impl std::fmt::Display for ActiveComponent {
         }
     }
 }
+
+/// Difference between two specifications of the same workflow.
+///
+/// A component is changed if its function class, annotations (including those
+/// inherited from the workflow), resource class or configuration are
+/// different, in which case it has to be started again.
+/// A change of the output mapping alone only requires a patch.
+/// A function with a canary weight annotated, in either specification, is
+/// not changed but it is subject to a canary upgrade, promotion or rollback.
+#[derive(Debug, Default, PartialEq)]
+pub struct WorkflowDiff {
+    /// Components only found in the new specification.
+    pub added: std::collections::HashSet<String>,
+    /// Components found in both specifications, which must be restarted.
+    pub changed: std::collections::HashSet<String>,
+    /// Components only found in the old specification.
+    pub removed: std::collections::HashSet<String>,
+    /// Functions found in both specifications, whose canary version changes.
+    pub canary: std::collections::HashSet<String>,
+}
+
+impl WorkflowDiff {
+    pub fn new(old: &edgeless_api::workflow_instance::SpawnWorkflowRequest, new: &edgeless_api::workflow_instance::SpawnWorkflowRequest) -> Self {
+        let old_components = old.source_components();
+        let new_components = new.source_components();
+
+        let (canary, changed) = old_components
+            .intersection(&new_components)
+            .filter(|name| match (old.get_function(name), new.get_function(name)) {
+                (Some(old_function), Some(new_function)) => {
+                    old_function.class_specification != new_function.class_specification
+                        || function_annotations(&old.annotations, old_function) != function_annotations(&new.annotations, new_function)
+                }
+                (None, None) => {
+                    let old_resource = old.get_resource(name).unwrap();
+                    let new_resource = new.get_resource(name).unwrap();
+                    old_resource.class_type != new_resource.class_type || old_resource.configurations != new_resource.configurations
+                }
+                // A function replaced by a resource, or vice versa.
+                _ => true,
+            })
+            .cloned()
+            .partition(|name| match (old.get_function(name), new.get_function(name)) {
+                (Some(old_function), Some(new_function)) => {
+                    old_function.annotations.contains_key(edgeless_api::workflow_instance::CANARY_WEIGHT)
+                        || new_function.annotations.contains_key(edgeless_api::workflow_instance::CANARY_WEIGHT)
+                }
+                _ => false,
+            });
+
+        Self {
+            added: new_components.difference(&old_components).cloned().collect(),
+            changed,
+            removed: old_components.difference(&new_components).cloned().collect(),
+            canary,
+        }
+    }
+
+    /// Return true if the component must be started with the new
+    /// specification.
+    pub fn is_started(&self, component_name: &str) -> bool {
+        self.added.contains(component_name) || self.changed.contains(component_name)
+    }
+
+    /// Return true if the instances of the component may change, hence the
+    /// components that map to it must be patched.
+    pub fn is_retargeted(&self, component_name: &str) -> bool {
+        self.is_started(component_name) || self.canary.contains(component_name)
+    }
+}
@@ -0,0 +1,879 @@
+// SPDX-FileCopyrightText: © 2024 Technical University of Munich, Chair of Connected Mobility
+// SPDX-FileCopyrightText: © 2024 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-FileCopyrightText: © 2024 Siemens AG
+// SPDX-License-Identifier: MIT
+
+use std::io::Write;
+
+/// Workflow as saved by the controller.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum PersistedWorkflow {
+    /// Workflow deployed, with the mapping of its functions/resources to the
+    /// orchestration domains and their identifiers.
+    Active(super::deployment_state::ActiveWorkflow),
+    /// Workflow waiting to be assigned to orchestration domains.
+    Orphan(edgeless_api::workflow_instance::SpawnWorkflowRequest),
+    /// Orphan workflow with functions subject to a canary upgrade, with the
+    /// specification of their stable version by function name.
+    OrphanCanary(
+        edgeless_api::workflow_instance::SpawnWorkflowRequest,
+        std::collections::HashMap<String, edgeless_api::workflow_instance::WorkflowFunction>,
+    ),
+}
+
+/// State of the controller that survives a restart.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ControllerState {
+    /// Active and orphan workflows.
+    pub workflows: std::collections::BTreeMap<uuid::Uuid, PersistedWorkflow>,
+    /// Orchestration domains known, with the nonce they last advertised.
+    pub domains: std::collections::BTreeMap<String, u64>,
+    /// Last identifier used for the portal resources.
+    pub last_portal_resource_id: u64,
+}
+
+/// Durable storage of the controller state.
+///
+/// The controller loads the state once when starting, before calling any
+/// other method, then it notifies every change as soon as it happens.
+#[async_trait::async_trait]
+pub trait ControllerStore: Send {
+    async fn load(&mut self) -> anyhow::Result<ControllerState>;
+    async fn put_workflow(&mut self, workflow_id: &uuid::Uuid, workflow: PersistedWorkflow) -> anyhow::Result<()>;
+    async fn remove_workflow(&mut self, workflow_id: &uuid::Uuid) -> anyhow::Result<()>;
+    async fn put_domain(&mut self, domain_id: &str, nonce: u64) -> anyhow::Result<()>;
+    async fn remove_domain(&mut self, domain_id: &str) -> anyhow::Result<()>;
+    async fn set_last_portal_resource_id(&mut self, id: u64) -> anyhow::Result<()>;
+}
+
+/// Create the store for the given persistence file name.
+///
+/// If the name is empty, then the state is not persisted at all.
+pub fn store_from_filename(filename: &str) -> Box<dyn ControllerStore> {
+    if filename.is_empty() {
+        Box::new(VolatileStore {})
+    } else {
+        Box::new(JournalStore::new(filename))
+    }
+}
+
+/// Store that does not save anything.
+pub struct VolatileStore {}
+
+#[async_trait::async_trait]
+impl ControllerStore for VolatileStore {
+    async fn load(&mut self) -> anyhow::Result<ControllerState> {
+        Ok(ControllerState::default())
+    }
+    async fn put_workflow(&mut self, _workflow_id: &uuid::Uuid, _workflow: PersistedWorkflow) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn remove_workflow(&mut self, _workflow_id: &uuid::Uuid) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn put_domain(&mut self, _domain_id: &str, _nonce: u64) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn remove_domain(&mut self, _domain_id: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
+    async fn set_last_portal_resource_id(&mut self, _id: u64) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Single change of the controller state, saved as a line of the journal.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalRecord {
+    /// Binary of a function, saved once and then referenced by its SHA-256
+    /// digest in the workflows saved after it.
+    PutBinary {
+        sha256: String,
+        binary: Vec<u8>,
+    },
+    /// Workflow, with the function binaries replaced by their digest, see
+    /// `encode_workflow()`.
+    PutWorkflow {
+        workflow_id: uuid::Uuid,
+        workflow: serde_json::Value,
+    },
+    RemoveWorkflow {
+        workflow_id: uuid::Uuid,
+    },
+    PutDomain {
+        domain_id: String,
+        nonce: u64,
+    },
+    RemoveDomain {
+        domain_id: String,
+    },
+    LastPortalResourceId {
+        id: u64,
+    },
+}
+
+/// Field that replaces the binary of a function in the workflows saved in
+/// the journal, with the SHA-256 digest of the binary.
+const BINARY_SHA256: &str = "binary_sha256";
+
+/// Serialize a workflow replacing the function binaries with their SHA-256
+/// digest, and add the binaries to `binaries`, by digest.
+fn encode_workflow(workflow: &PersistedWorkflow, binaries: &mut std::collections::BTreeMap<String, Vec<u8>>) -> anyhow::Result<serde_json::Value> {
+    let mut value = serde_json::to_value(workflow)?;
+    extract_binaries(&mut value, binaries)?;
+    Ok(value)
+}
+
+fn extract_binaries(value: &mut serde_json::Value, binaries: &mut std::collections::BTreeMap<String, Vec<u8>>) -> anyhow::Result<()> {
+    match value {
+        serde_json::Value::Object(fields) => {
+            // Function class specification with an inline binary.
+            if fields.contains_key("function_type")
+                && let Some(binary) = fields.get_mut("binary")
+                && binary.is_array()
+            {
+                let binary = serde_json::from_value::<Vec<u8>>(binary.take())?;
+                let sha256 = format!("{:x}", <sha2::Sha256 as sha2::Digest>::digest(&binary));
+                fields.insert(BINARY_SHA256.to_string(), serde_json::Value::String(sha256.clone()));
+                binaries.insert(sha256, binary);
+            }
+            for field in fields.values_mut() {
+                extract_binaries(field, binaries)?;
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                extract_binaries(item, binaries)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Deserialize a workflow saved with `encode_workflow()`, restoring the
+/// function binaries from their SHA-256 digest.
+fn decode_workflow(mut value: serde_json::Value, binaries: &std::collections::HashMap<String, Vec<u8>>) -> anyhow::Result<PersistedWorkflow> {
+    restore_binaries(&mut value, binaries)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+fn restore_binaries(value: &mut serde_json::Value, binaries: &std::collections::HashMap<String, Vec<u8>>) -> anyhow::Result<()> {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for field in fields.values_mut() {
+                restore_binaries(field, binaries)?;
+            }
+            if let Some(sha256) = fields.remove(BINARY_SHA256) {
+                let binary = match sha256.as_str().and_then(|sha256| binaries.get(sha256)) {
+                    Some(binary) => binary,
+                    None => anyhow::bail!("unknown function binary {}", sha256),
+                };
+                fields.insert("binary".to_string(), serde_json::to_value(binary)?);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                restore_binaries(item, binaries)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Content of the persistence file before the introduction of the journal,
+/// which only contained the workflow requests.
+#[derive(Default, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct LegacyPersistedState {
+    workflows: Vec<(String, edgeless_api::workflow_instance::SpawnWorkflowRequest)>,
+}
+
+/// The journal is compacted when it contains at least this number of records
+/// and more than `COMPACTION_RATIO` times the records needed to represent the
+/// current state.
+const COMPACTION_MIN_RECORDS: usize = 256;
+const COMPACTION_RATIO: usize = 4;
+
+/// Store saving the changes to a `Journal`.
+///
+/// The file operations are blocking, hence they are executed on the thread
+/// pool of tokio dedicated to blocking tasks, so as not to stall the
+/// controller task while waiting for the data to be flushed to disk.
+pub struct JournalStore {
+    journal: std::sync::Arc<std::sync::Mutex<Journal>>,
+}
+
+impl JournalStore {
+    pub fn new(filename: &str) -> Self {
+        Self {
+            journal: std::sync::Arc::new(std::sync::Mutex::new(Journal::new(filename))),
+        }
+    }
+
+    /// Execute an operation on the journal in a blocking task.
+    async fn execute<T: Send + 'static>(&self, op: impl FnOnce(&mut Journal) -> anyhow::Result<T> + Send + 'static) -> anyhow::Result<T> {
+        let journal = self.journal.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut journal = journal.lock().map_err(|_| anyhow::anyhow!("persistence journal poisoned"))?;
+            op(&mut journal)
+        })
+        .await?
+    }
+}
+
+#[async_trait::async_trait]
+impl ControllerStore for JournalStore {
+    async fn load(&mut self) -> anyhow::Result<ControllerState> {
+        self.execute(|journal| journal.load()).await
+    }
+
+    async fn put_workflow(&mut self, workflow_id: &uuid::Uuid, workflow: PersistedWorkflow) -> anyhow::Result<()> {
+        let workflow_id = *workflow_id;
+        self.execute(move |journal| journal.put_workflow(workflow_id, workflow)).await
+    }
+
+    async fn remove_workflow(&mut self, workflow_id: &uuid::Uuid) -> anyhow::Result<()> {
+        let workflow_id = *workflow_id;
+        self.execute(move |journal| journal.remove_workflow(workflow_id)).await
+    }
+
+    async fn put_domain(&mut self, domain_id: &str, nonce: u64) -> anyhow::Result<()> {
+        let domain_id = domain_id.to_string();
+        self.execute(move |journal| journal.put_domain(&domain_id, nonce)).await
+    }
+
+    async fn remove_domain(&mut self, domain_id: &str) -> anyhow::Result<()> {
+        let domain_id = domain_id.to_string();
+        self.execute(move |journal| journal.remove_domain(&domain_id)).await
+    }
+
+    async fn set_last_portal_resource_id(&mut self, id: u64) -> anyhow::Result<()> {
+        self.execute(move |journal| journal.set_last_portal_resource_id(id)).await
+    }
+}
+
+/// Append-only file with the changes of the controller state, one JSON record
+/// per line.
+///
+/// Every record is flushed to disk before returning, and the file is
+/// periodically compacted by writing a snapshot of the state to a temporary
+/// file, which then atomically replaces the journal. If writing a record
+/// fails, then the journal is truncated back to the last complete record.
+/// If the controller crashes while appending, the last record may be
+/// incomplete: it is discarded when loading.
+///
+/// The function binaries are saved only once in the journal, then the
+/// workflows refer to them by their SHA-256 digest.
+struct Journal {
+    filename: String,
+    file: Option<std::fs::File>,
+    state: ControllerState,
+    /// Digests of the function binaries saved in the journal.
+    binaries: std::collections::HashSet<String>,
+    num_records: usize,
+    /// Size of the journal up to the last record written successfully.
+    offset: u64,
+}
+
+impl Journal {
+    fn new(filename: &str) -> Self {
+        Self {
+            filename: filename.to_string(),
+            file: None,
+            state: ControllerState::default(),
+            binaries: std::collections::HashSet::new(),
+            num_records: 0,
+            offset: 0,
+        }
+    }
+
+    /// Apply a record read from the journal to the state, where `binaries`
+    /// are the function binaries read so far, by digest.
+    fn apply(state: &mut ControllerState, binaries: &mut std::collections::HashMap<String, Vec<u8>>, record: JournalRecord) -> anyhow::Result<()> {
+        match record {
+            JournalRecord::PutBinary { sha256, binary } => {
+                binaries.insert(sha256, binary);
+            }
+            JournalRecord::PutWorkflow { workflow_id, workflow } => {
+                state.workflows.insert(workflow_id, decode_workflow(workflow, binaries)?);
+            }
+            JournalRecord::RemoveWorkflow { workflow_id } => {
+                state.workflows.remove(&workflow_id);
+            }
+            JournalRecord::PutDomain { domain_id, nonce } => {
+                state.domains.insert(domain_id, nonce);
+            }
+            JournalRecord::RemoveDomain { domain_id } => {
+                state.domains.remove(&domain_id);
+            }
+            JournalRecord::LastPortalResourceId { id } => {
+                state.last_portal_resource_id = id;
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the records that are needed to rebuild the current state.
+    fn snapshot(state: &ControllerState) -> anyhow::Result<Vec<JournalRecord>> {
+        let mut records = vec![JournalRecord::LastPortalResourceId {
+            id: state.last_portal_resource_id,
+        }];
+        for (domain_id, nonce) in &state.domains {
+            records.push(JournalRecord::PutDomain {
+                domain_id: domain_id.clone(),
+                nonce: *nonce,
+            });
+        }
+        let mut binaries = std::collections::BTreeMap::new();
+        let mut workflows = vec![];
+        for (workflow_id, workflow) in &state.workflows {
+            workflows.push(JournalRecord::PutWorkflow {
+                workflow_id: *workflow_id,
+                workflow: encode_workflow(workflow, &mut binaries)?,
+            });
+        }
+        records.extend(binaries.into_iter().map(|(sha256, binary)| JournalRecord::PutBinary { sha256, binary }));
+        records.extend(workflows);
+        Ok(records)
+    }
+
+    /// Read the state from the journal, or from a file with the legacy format.
+    ///
+    /// Only the records before the first invalid one are used: if the latter
+    /// is not the last record of the journal, then a copy of the file is kept
+    /// for inspection with the `.corrupted` suffix.
+    ///
+    /// Return `Ok(None)` if the file does not exist.
+    fn read(&self) -> anyhow::Result<Option<ControllerState>> {
+        let content = match std::fs::read(&self.filename) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => anyhow::bail!("could not read '{}': {}", self.filename, err),
+        };
+
+        if let Ok(legacy) = serde_json::from_slice::<LegacyPersistedState>(&content) {
+            log::info!("importing the workflows from persistence file '{}' with legacy format", self.filename);
+            let mut state = ControllerState::default();
+            for (workflow_id, request) in legacy.workflows {
+                match uuid::Uuid::parse_str(&workflow_id) {
+                    Ok(workflow_id) => {
+                        state.workflows.insert(workflow_id, PersistedWorkflow::Orphan(request));
+                    }
+                    Err(err) => log::warn!("invalid workflow UUID found in persistence file '{}': {}", self.filename, err),
+                }
+            }
+            return Ok(Some(state));
+        }
+
+        let mut state = ControllerState::default();
+        let mut binaries = std::collections::HashMap::new();
+        let content = String::from_utf8_lossy(&content);
+        let lines = content.lines().collect::<Vec<&str>>();
+        let num_lines = lines.len();
+        for (i, line) in lines.into_iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<JournalRecord>(line)
+                .map_err(anyhow::Error::from)
+                .and_then(|record| Self::apply(&mut state, &mut binaries, record))
+            {
+                Ok(()) => {}
+                Err(err) if i + 1 == num_lines => {
+                    log::warn!("discarding incomplete last record of persistence file '{}': {}", self.filename, err);
+                }
+                Err(err) => {
+                    let corrupted_filename = format!("{}.corrupted", self.filename);
+                    if let Err(err) = std::fs::copy(&self.filename, &corrupted_filename) {
+                        log::warn!("could not copy persistence file '{}' to '{}': {}", self.filename, corrupted_filename, err);
+                    }
+                    log::error!(
+                        "invalid record at line {} of persistence file '{}', discarding the next {} lines (original file copied to '{}'): {}",
+                        i + 1,
+                        self.filename,
+                        num_lines - i,
+                        corrupted_filename,
+                        err
+                    );
+                    break;
+                }
+            }
+        }
+        Ok(Some(state))
+    }
+
+    /// Replace the journal with a snapshot of the given state, then reopen
+    /// it for appending new records.
+    fn compact(&mut self, state: &ControllerState) -> anyhow::Result<()> {
+        self.file = None;
+
+        let records = Self::snapshot(state)?;
+        let tmp_filename = format!("{}.tmp", self.filename);
+        {
+            let mut tmp_file = std::fs::File::create(&tmp_filename)?;
+            for record in &records {
+                writeln!(tmp_file, "{}", serde_json::to_string(record)?)?;
+            }
+            tmp_file.sync_all()?;
+        }
+        std::fs::rename(&tmp_filename, &self.filename)?;
+
+        // Make the rename durable, as well.
+        let dir = match std::path::Path::new(&self.filename).parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => std::path::PathBuf::from("."),
+        };
+        if let Ok(dir) = std::fs::File::open(dir) {
+            let _ = dir.sync_all();
+        }
+
+        let file = std::fs::OpenOptions::new().append(true).open(&self.filename)?;
+        self.offset = file.metadata()?.len();
+        self.num_records = records.len();
+        self.binaries = records
+            .into_iter()
+            .filter_map(|record| match record {
+                JournalRecord::PutBinary { sha256, .. } => Some(sha256),
+                _ => None,
+            })
+            .collect();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Save records to the journal, then apply the corresponding change to
+    /// the current state.
+    ///
+    /// The state is not changed if the records cannot be saved.
+    fn append(&mut self, records: Vec<JournalRecord>, change: impl FnOnce(&mut ControllerState)) -> anyhow::Result<()> {
+        let live_records = 1 + self.state.domains.len() + self.state.workflows.len() + self.binaries.len();
+        if self.file.is_none() || (self.num_records >= COMPACTION_MIN_RECORDS && self.num_records > COMPACTION_RATIO * live_records) {
+            let mut state = self.state.clone();
+            change(&mut state);
+            self.compact(&state)?;
+            self.state = state;
+            return Ok(());
+        }
+
+        let mut lines = String::new();
+        for record in &records {
+            lines += &serde_json::to_string(record)?;
+            lines.push('\n');
+        }
+
+        let file = self.file.as_mut().expect("journal not open");
+        if let Err(err) = file.write_all(lines.as_bytes()).and_then(|_| file.sync_data()) {
+            // Do not leave a partial record, which would make the records
+            // appended later unreadable.
+            if let Err(truncate_err) = file.set_len(self.offset) {
+                log::error!("could not truncate persistence file '{}': {}", self.filename, truncate_err);
+                // Rewrite the whole journal at the next change.
+                self.file = None;
+            }
+            anyhow::bail!("could not write to persistence file '{}': {}", self.filename, err);
+        }
+        self.offset += lines.len() as u64;
+        self.num_records += records.len();
+        for record in records {
+            if let JournalRecord::PutBinary { sha256, .. } = record {
+                self.binaries.insert(sha256);
+            }
+        }
+        change(&mut self.state);
+        Ok(())
+    }
+
+    fn load(&mut self) -> anyhow::Result<ControllerState> {
+        let state = self.read()?.unwrap_or_default();
+
+        // Start from a clean journal, which also drops the invalid records
+        // at the end of the file, if any.
+        self.compact(&state)?;
+        self.state = state;
+        Ok(self.state.clone())
+    }
+
+    fn put_workflow(&mut self, workflow_id: uuid::Uuid, workflow: PersistedWorkflow) -> anyhow::Result<()> {
+        // Only save the binaries that are not already in the journal.
+        let mut binaries = std::collections::BTreeMap::new();
+        let encoded = encode_workflow(&workflow, &mut binaries)?;
+        let mut records = binaries
+            .into_iter()
+            .filter(|(sha256, _binary)| !self.binaries.contains(sha256))
+            .map(|(sha256, binary)| JournalRecord::PutBinary { sha256, binary })
+            .collect::<Vec<_>>();
+        records.push(JournalRecord::PutWorkflow {
+            workflow_id,
+            workflow: encoded,
+        });
+        self.append(records, |state| {
+            state.workflows.insert(workflow_id, workflow);
+        })
+    }
+
+    fn remove_workflow(&mut self, workflow_id: uuid::Uuid) -> anyhow::Result<()> {
+        self.append(vec![JournalRecord::RemoveWorkflow { workflow_id }], |state| {
+            state.workflows.remove(&workflow_id);
+        })
+    }
+
+    fn put_domain(&mut self, domain_id: &str, nonce: u64) -> anyhow::Result<()> {
+        self.append(
+            vec![JournalRecord::PutDomain {
+                domain_id: domain_id.to_string(),
+                nonce,
+            }],
+            |state| {
+                state.domains.insert(domain_id.to_string(), nonce);
+            },
+        )
+    }
+
+    fn remove_domain(&mut self, domain_id: &str) -> anyhow::Result<()> {
+        self.append(
+            vec![JournalRecord::RemoveDomain {
+                domain_id: domain_id.to_string(),
+            }],
+            |state| {
+                state.domains.remove(domain_id);
+            },
+        )
+    }
+
+    fn set_last_portal_resource_id(&mut self, id: u64) -> anyhow::Result<()> {
+        self.append(vec![JournalRecord::LastPortalResourceId { id }], |state| {
+            state.last_portal_resource_id = id;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_filename() -> String {
+        std::env::temp_dir()
+            .join(format!("edgeless-con-{}.save", uuid::Uuid::new_v4()))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn workflow_request(name: &str) -> edgeless_api::workflow_instance::SpawnWorkflowRequest {
+        edgeless_api::workflow_instance::SpawnWorkflowRequest {
+            functions: vec![edgeless_api::workflow_instance::WorkflowFunction {
+                name: name.to_string(),
+                class_specification: edgeless_api::function_instance::FunctionClassSpecification {
+                    id: "test".to_string(),
+                    function_type: "RUST_WASM".to_string(),
+                    version: "0.1".to_string(),
+                    binary: Some(vec![0, 1, 2, 3]),
+                    code: None,
+                    outputs: vec!["out".to_string()],
+                },
+                output_mapping: std::collections::HashMap::new(),
+                annotations: std::collections::HashMap::new(),
+            }],
+            resources: vec![],
+            annotations: std::collections::HashMap::new(),
+        }
+    }
+
+    fn active_workflow(name: &str, domain_id: &str) -> super::super::deployment_state::ActiveWorkflow {
+        let request = workflow_request(name);
+        super::super::deployment_state::ActiveWorkflow {
+            desired_state: request.clone(),
+            augmented_spec: Some(request),
+            domain_mapping: std::collections::HashMap::from([(
+                name.to_string(),
+                super::super::deployment_state::ActiveComponent {
+                    component_type: super::super::ComponentType::Function,
+                    name: name.to_string(),
+                    domain_id: domain_id.to_string(),
+                    lid: uuid::Uuid::new_v4(),
+                    canary: None,
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_serialize_deserialize_journal_records() {
+        let mut expected_records = vec![];
+        let mut binaries = std::collections::BTreeMap::new();
+        for i in 0..10 {
+            let functions = vec![edgeless_api::workflow_instance::WorkflowFunction {
+                name: format!("f{}", i),
+                class_specification: edgeless_api::function_instance::FunctionClassSpecification {
+                    id: "test".to_string(),
+                    function_type: "RUST_WASM".to_string(),
+                    version: "0.1".to_string(),
+                    binary: Some(include_bytes!("../../../functions/system_test/system_test.wasm").to_vec()),
+                    code: None,
+                    outputs: vec!["out1".to_string(), "out2".to_string(), "err".to_string(), "log".to_string()],
+                },
+                output_mapping: std::collections::HashMap::new(),
+                annotations: std::collections::HashMap::new(),
+            }];
+            let resources = vec![edgeless_api::workflow_instance::WorkflowResource {
+                name: "log".to_string(),
+                class_type: "file-log".to_string(),
+                output_mapping: std::collections::HashMap::new(),
+                configurations: std::collections::HashMap::from([("filename".to_string(), "example.log".to_string())]),
+            }];
+            let annotations = std::collections::HashMap::from([("ann1".to_string(), "val1".to_string())]);
+            let request = edgeless_api::workflow_instance::SpawnWorkflowRequest {
+                functions,
+                resources,
+                annotations,
+            };
+            expected_records.push(JournalRecord::PutWorkflow {
+                workflow_id: uuid::Uuid::new_v4(),
+                workflow: encode_workflow(&PersistedWorkflow::Orphan(request), &mut binaries).unwrap(),
+            });
+            expected_records.push(JournalRecord::PutWorkflow {
+                workflow_id: uuid::Uuid::new_v4(),
+                workflow: encode_workflow(&PersistedWorkflow::Active(active_workflow(&format!("f{}", i), "domain-1")), &mut binaries).unwrap(),
+            });
+        }
+        assert_eq!(2, binaries.len());
+        for (sha256, binary) in binaries {
+            expected_records.push(JournalRecord::PutBinary { sha256, binary });
+        }
+        expected_records.push(JournalRecord::RemoveWorkflow {
+            workflow_id: uuid::Uuid::new_v4(),
+        });
+        expected_records.push(JournalRecord::PutDomain {
+            domain_id: "domain-1".to_string(),
+            nonce: u64::MAX,
+        });
+        expected_records.push(JournalRecord::RemoveDomain {
+            domain_id: "domain-1".to_string(),
+        });
+        expected_records.push(JournalRecord::LastPortalResourceId { id: 42 });
+
+        for expected_record in expected_records {
+            let serialized = serde_json::to_string(&expected_record).unwrap();
+            assert!(!serialized.contains('\n'));
+            let actual_record: JournalRecord = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(expected_record, actual_record);
+        }
+    }
+
+    #[test]
+    fn test_journal_store_recover() {
+        let filename = temp_filename();
+
+        let wf1 = uuid::Uuid::new_v4();
+        let wf2 = uuid::Uuid::new_v4();
+        let wf3 = uuid::Uuid::new_v4();
+        let active = PersistedWorkflow::Active(active_workflow("f1", "domain-1"));
+        let mut expected = ControllerState::default();
+        {
+            let mut store = Journal::new(&filename);
+            assert_eq!(ControllerState::default(), store.load().unwrap());
+
+            store.put_domain("domain-1", 42).unwrap();
+            store.put_domain("domain-2", 43).unwrap();
+            store.remove_domain("domain-2").unwrap();
+            store.set_last_portal_resource_id(4).unwrap();
+            store.put_workflow(wf1, active.clone()).unwrap();
+            store.put_workflow(wf2, PersistedWorkflow::Orphan(workflow_request("f2"))).unwrap();
+            store.put_workflow(wf3, PersistedWorkflow::Orphan(workflow_request("f3"))).unwrap();
+            store.remove_workflow(wf3).unwrap();
+
+            expected.domains.insert("domain-1".to_string(), 42);
+            expected.last_portal_resource_id = 4;
+            expected.workflows.insert(wf1, active);
+            expected.workflows.insert(wf2, PersistedWorkflow::Orphan(workflow_request("f2")));
+        }
+
+        let mut store = Journal::new(&filename);
+        assert_eq!(expected, store.load().unwrap());
+
+        let _ = std::fs::remove_file(&filename);
+    }
+
+    #[test]
+    fn test_journal_store_incomplete_last_record() {
+        let filename = temp_filename();
+
+        let wf1 = uuid::Uuid::new_v4();
+        {
+            let mut store = Journal::new(&filename);
+            store.load().unwrap();
+            store.put_workflow(wf1, PersistedWorkflow::Orphan(workflow_request("f1"))).unwrap();
+        }
+
+        // Simulate a crash while appending a record.
+        let line = serde_json::to_string(&JournalRecord::PutWorkflow {
+            workflow_id: uuid::Uuid::new_v4(),
+            workflow: serde_json::to_value(PersistedWorkflow::Orphan(workflow_request("f2"))).unwrap(),
+        })
+        .unwrap();
+        let mut file = std::fs::OpenOptions::new().append(true).open(&filename).unwrap();
+        write!(file, "{}", &line[..line.len() / 2]).unwrap();
+        drop(file);
+
+        let mut store = Journal::new(&filename);
+        let state = store.load().unwrap();
+        assert_eq!(1, state.workflows.len());
+        assert!(state.workflows.contains_key(&wf1));
+
+        // The incomplete record has been removed from the journal.
+        store.remove_workflow(wf1).unwrap();
+        let mut store = Journal::new(&filename);
+        assert!(store.load().unwrap().workflows.is_empty());
+
+        let _ = std::fs::remove_file(&filename);
+    }
+
+    #[test]
+    fn test_journal_store_invalid_record() {
+        let filename = temp_filename();
+        std::fs::write(
+            &filename,
+            "{\"op\":\"put_domain\",\"domain_id\":\"d\",\"nonce\":1}\ngarbage\n{\"op\":\"remove_domain\",\"domain_id\":\"d\"}\n",
+        )
+        .unwrap();
+
+        // The records before the invalid one are kept.
+        let expected = ControllerState {
+            domains: std::collections::BTreeMap::from([("d".to_string(), 1)]),
+            ..Default::default()
+        };
+        let mut store = Journal::new(&filename);
+        assert_eq!(expected, store.load().unwrap());
+        assert!(std::path::Path::new(&format!("{}.corrupted", filename)).exists());
+
+        let mut store = Journal::new(&filename);
+        assert_eq!(expected, store.load().unwrap());
+
+        let _ = std::fs::remove_file(&filename);
+        let _ = std::fs::remove_file(format!("{}.corrupted", filename));
+    }
+
+    #[test]
+    fn test_journal_store_write_error() {
+        let filename = temp_filename();
+
+        let mut store = Journal::new(&filename);
+        store.load().unwrap();
+        store.put_domain("domain-1", 1).unwrap();
+
+        // Simulate a failure when writing to the journal.
+        store.file = Some(std::fs::File::open(&filename).unwrap());
+        assert!(store.put_domain("domain-2", 2).is_err());
+        assert_eq!(None, store.state.domains.get("domain-2"));
+
+        // The journal can be written again afterwards.
+        store.put_domain("domain-3", 3).unwrap();
+        let mut store = Journal::new(&filename);
+        let state = store.load().unwrap();
+        assert_eq!(
+            std::collections::BTreeMap::from([("domain-1".to_string(), 1), ("domain-3".to_string(), 3)]),
+            state.domains
+        );
+
+        let _ = std::fs::remove_file(&filename);
+    }
+
+    #[test]
+    fn test_journal_store_compaction() {
+        let filename = temp_filename();
+
+        let wf1 = uuid::Uuid::new_v4();
+        let mut store = Journal::new(&filename);
+        store.load().unwrap();
+        for i in 0..(2 * COMPACTION_MIN_RECORDS) {
+            store.put_domain("domain-1", i as u64).unwrap();
+        }
+        store.put_workflow(wf1, PersistedWorkflow::Orphan(workflow_request("f1"))).unwrap();
+
+        let num_lines = std::fs::read_to_string(&filename).unwrap().lines().count();
+        assert!(num_lines <= COMPACTION_MIN_RECORDS, "journal not compacted: {} lines", num_lines);
+
+        let mut store = Journal::new(&filename);
+        let state = store.load().unwrap();
+        assert_eq!(Some(&(2 * COMPACTION_MIN_RECORDS as u64 - 1)), state.domains.get("domain-1"));
+        assert!(state.workflows.contains_key(&wf1));
+
+        let _ = std::fs::remove_file(&filename);
+    }
+
+    #[test]
+    fn test_journal_store_binaries() {
+        let filename = temp_filename();
+
+        let wf1 = uuid::Uuid::new_v4();
+        let wf2 = uuid::Uuid::new_v4();
+        let active = PersistedWorkflow::Active(active_workflow("f1", "domain-1"));
+        let mut store = Journal::new(&filename);
+        store.load().unwrap();
+        store.put_workflow(wf1, PersistedWorkflow::Orphan(workflow_request("f1"))).unwrap();
+        store.put_workflow(wf1, active.clone()).unwrap();
+        store.put_workflow(wf2, PersistedWorkflow::Orphan(workflow_request("f2"))).unwrap();
+
+        // The binary shared by all the functions is saved only once.
+        let content = std::fs::read_to_string(&filename).unwrap();
+        assert_eq!(1, content.lines().filter(|line| line.contains("\"put_binary\"")).count());
+
+        let mut store = Journal::new(&filename);
+        let state = store.load().unwrap();
+        assert_eq!(Some(&active), state.workflows.get(&wf1));
+        assert_eq!(Some(&PersistedWorkflow::Orphan(workflow_request("f2"))), state.workflows.get(&wf2));
+
+        // A workflow referring to an unknown binary is invalid.
+        let mut binaries = std::collections::BTreeMap::new();
+        let encoded = encode_workflow(&active, &mut binaries).unwrap();
+        assert!(decode_workflow(encoded.clone(), &std::collections::HashMap::new()).is_err());
+        assert_eq!(active, decode_workflow(encoded, &binaries.into_iter().collect()).unwrap());
+
+        let _ = std::fs::remove_file(&filename);
+    }
+
+    #[tokio::test]
+    async fn test_journal_store_async() {
+        let filename = temp_filename();
+
+        let wf1 = uuid::Uuid::new_v4();
+        {
+            let mut store = JournalStore::new(&filename);
+            assert_eq!(ControllerState::default(), store.load().await.unwrap());
+            store.put_domain("domain-1", 42).await.unwrap();
+            store.put_workflow(&wf1, PersistedWorkflow::Orphan(workflow_request("f1"))).await.unwrap();
+            store.set_last_portal_resource_id(7).await.unwrap();
+        }
+
+        let mut store = JournalStore::new(&filename);
+        let state = store.load().await.unwrap();
+        assert_eq!(Some(&42), state.domains.get("domain-1"));
+        assert_eq!(Some(&PersistedWorkflow::Orphan(workflow_request("f1"))), state.workflows.get(&wf1));
+        assert_eq!(7, state.last_portal_resource_id);
+
+        let _ = std::fs::remove_file(&filename);
+    }
+
+    #[test]
+    fn test_journal_store_legacy_format() {
+        let filename = temp_filename();
+
+        let wf1 = uuid::Uuid::new_v4();
+        let legacy = LegacyPersistedState {
+            workflows: vec![(wf1.to_string(), workflow_request("f1"))],
+        };
+        std::fs::write(&filename, serde_json::to_string(&legacy).unwrap()).unwrap();
+
+        let mut store = Journal::new(&filename);
+        let state = store.load().unwrap();
+        assert_eq!(Some(&PersistedWorkflow::Orphan(workflow_request("f1"))), state.workflows.get(&wf1));
+
+        // The file has been converted to the journal format.
+        let mut store = Journal::new(&filename);
+        assert_eq!(state, store.load().unwrap());
+
+        let _ = std::fs::remove_file(&filename);
+    }
+}
@@ -433,3 +433,737 @@ async fn function_link_loop_start_stop() {
     }
     assert!(fids.is_empty());
 }
+
+#[tokio::test]
+async fn function_update() {
+    let (mut wf_client, mut mock_orc_receiver) = test_setup().await;
+
+    assert!(mock_orc_receiver.try_next().is_err());
+
+    let function = |name: &str, version: &str, output_mapping: Vec<(&str, &str)>| edgeless_api::workflow_instance::WorkflowFunction {
+        name: name.to_string(),
+        class_specification: edgeless_api::function_instance::FunctionClassSpecification {
+            id: format!("fc-{}", name),
+            function_type: "RUST_WASM".to_string(),
+            version: version.to_string(),
+            binary: None,
+            code: None,
+            outputs: vec!["output".to_string()],
+        },
+        output_mapping: output_mapping
+            .into_iter()
+            .map(|(channel, target)| (channel.to_string(), target.to_string()))
+            .collect(),
+        annotations: std::collections::HashMap::new(),
+    };
+
+    // Start a workflow f1 -> f2.
+    let response = wf_client
+        .start(edgeless_api::workflow_instance::SpawnWorkflowRequest {
+            functions: vec![function("f1", "0.1", vec![("output", "f2")]), function("f2", "0.1", vec![])],
+            resources: vec![],
+            annotations: std::collections::HashMap::new(),
+        })
+        .await
+        .unwrap();
+    let instance = match &response {
+        SpawnWorkflowResponse::ResponseError(err) => panic!("{}", err),
+        SpawnWorkflowResponse::WorkflowInstance(val) => val.clone(),
+    };
+    let fid = |instance: &edgeless_api::workflow_instance::WorkflowInstance, name: &str| {
+        instance.domain_mapping.iter().find(|mapping| mapping.name == name).unwrap().function_id
+    };
+    let f1_id = fid(&instance, "f1");
+    let old_f2_id = fid(&instance, "f2");
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    while mock_orc_receiver.try_next().is_ok() {}
+
+    // Update to a new version of f2, which also maps to a new function f3.
+    let response = wf_client
+        .update(edgeless_api::workflow_instance::UpdateWorkflowRequest {
+            workflow_id: instance.workflow_id.clone(),
+            workflow: edgeless_api::workflow_instance::SpawnWorkflowRequest {
+                functions: vec![
+                    function("f1", "0.1", vec![("output", "f2")]),
+                    function("f2", "0.2", vec![("output", "f3")]),
+                    function("f3", "0.1", vec![]),
+                ],
+                resources: vec![],
+                annotations: std::collections::HashMap::new(),
+            },
+        })
+        .await
+        .unwrap();
+    let updated_instance = match &response {
+        SpawnWorkflowResponse::ResponseError(err) => panic!("{}", err),
+        SpawnWorkflowResponse::WorkflowInstance(val) => val.clone(),
+    };
+    assert_eq!(instance.workflow_id, updated_instance.workflow_id);
+    assert_eq!(3, updated_instance.domain_mapping.len());
+    assert_eq!(f1_id, fid(&updated_instance, "f1"));
+    let new_f2_id = fid(&updated_instance, "f2");
+    let f3_id = fid(&updated_instance, "f3");
+    assert_ne!(old_f2_id, new_f2_id);
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    // Only f2 and f3 are started, f1 and the new f2 are patched, then the
+    // previous instance of f2 is stopped.
+    let mut started = vec![];
+    let mut patched = std::collections::HashMap::new();
+    let mut stopped = vec![];
+    while let Ok(Some(event)) = mock_orc_receiver.try_next() {
+        match event {
+            MockFunctionInstanceEvent::StartFunction((id, spawn_req)) => {
+                assert!(patched.is_empty() && stopped.is_empty());
+                started.push((id, spawn_req.spec.version));
+            }
+            MockFunctionInstanceEvent::Patch(patch_req) => {
+                assert!(stopped.is_empty());
+                patched.insert(patch_req.function_id, patch_req.output_mapping);
+            }
+            MockFunctionInstanceEvent::StopFunction(id) => stopped.push(id),
+            _ => panic!("unexpected event"),
+        }
+    }
+    assert_eq!(vec![(new_f2_id, "0.2".to_string()), (f3_id, "0.1".to_string())], started);
+    assert_eq!(2, patched.len());
+    assert_eq!(new_f2_id, patched.get(&f1_id).unwrap().get("output").unwrap().function_id);
+    assert_eq!(f3_id, patched.get(&new_f2_id).unwrap().get("output").unwrap().function_id);
+    assert_eq!(vec![old_f2_id], stopped);
+
+    // Remove f3.
+    let response = wf_client
+        .update(edgeless_api::workflow_instance::UpdateWorkflowRequest {
+            workflow_id: instance.workflow_id.clone(),
+            workflow: edgeless_api::workflow_instance::SpawnWorkflowRequest {
+                functions: vec![function("f1", "0.1", vec![("output", "f2")]), function("f2", "0.2", vec![])],
+                resources: vec![],
+                annotations: std::collections::HashMap::new(),
+            },
+        })
+        .await
+        .unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::WorkflowInstance(_)));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    if let MockFunctionInstanceEvent::Patch(patch_req) = mock_orc_receiver.try_next().unwrap().unwrap() {
+        assert_eq!(new_f2_id, patch_req.function_id);
+        assert!(patch_req.output_mapping.is_empty());
+    } else {
+        panic!();
+    }
+    if let MockFunctionInstanceEvent::StopFunction(id) = mock_orc_receiver.try_next().unwrap().unwrap() {
+        assert_eq!(f3_id, id);
+    } else {
+        panic!();
+    }
+    assert!(mock_orc_receiver.try_next().is_err());
+
+    // A change of an annotation inherited from the workflow restarts all
+    // the functions.
+    let response = wf_client
+        .update(edgeless_api::workflow_instance::UpdateWorkflowRequest {
+            workflow_id: instance.workflow_id.clone(),
+            workflow: edgeless_api::workflow_instance::SpawnWorkflowRequest {
+                functions: vec![function("f1", "0.1", vec![("output", "f2")]), function("f2", "0.2", vec![])],
+                resources: vec![],
+                annotations: std::collections::HashMap::from([("placement".to_string(), "locality".to_string())]),
+            },
+        })
+        .await
+        .unwrap();
+    let updated_instance = match &response {
+        SpawnWorkflowResponse::ResponseError(err) => panic!("{}", err),
+        SpawnWorkflowResponse::WorkflowInstance(val) => val.clone(),
+    };
+    assert_ne!(f1_id, fid(&updated_instance, "f1"));
+    assert_ne!(new_f2_id, fid(&updated_instance, "f2"));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let mut started = 0;
+    while let Ok(Some(event)) = mock_orc_receiver.try_next() {
+        if let MockFunctionInstanceEvent::StartFunction((_id, spawn_req)) = event {
+            assert_eq!(Some(&"locality".to_string()), spawn_req.annotations.get("placement"));
+            started += 1;
+        }
+    }
+    assert_eq!(2, started);
+
+    // Update an unknown workflow.
+    let response = wf_client
+        .update(edgeless_api::workflow_instance::UpdateWorkflowRequest {
+            workflow_id: edgeless_api::workflow_instance::WorkflowId {
+                workflow_id: uuid::Uuid::new_v4(),
+            },
+            workflow: edgeless_api::workflow_instance::SpawnWorkflowRequest {
+                functions: vec![function("f1", "0.1", vec![])],
+                resources: vec![],
+                annotations: std::collections::HashMap::new(),
+            },
+        })
+        .await
+        .unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::ResponseError(_)));
+}
+
+#[tokio::test]
+async fn function_canary() {
+    let (mut wf_client, mut mock_orc_receiver) = test_setup().await;
+
+    assert!(mock_orc_receiver.try_next().is_err());
+
+    let function = |name: &str, version: &str, output_mapping: Vec<(&str, &str)>, canary_weight: Option<&str>| {
+        edgeless_api::workflow_instance::WorkflowFunction {
+            name: name.to_string(),
+            class_specification: edgeless_api::function_instance::FunctionClassSpecification {
+                id: format!("fc-{}", name),
+                function_type: "RUST_WASM".to_string(),
+                version: version.to_string(),
+                binary: None,
+                code: None,
+                outputs: vec!["output".to_string()],
+            },
+            output_mapping: output_mapping
+                .into_iter()
+                .map(|(channel, target)| (channel.to_string(), target.to_string()))
+                .collect(),
+            annotations: canary_weight
+                .map(|weight| std::collections::HashMap::from([(edgeless_api::workflow_instance::CANARY_WEIGHT.to_string(), weight.to_string())]))
+                .unwrap_or_default(),
+        }
+    };
+    let workflow = |f2_version: &str, canary_weight: Option<&str>| edgeless_api::workflow_instance::SpawnWorkflowRequest {
+        functions: vec![
+            function("f1", "0.1", vec![("output", "f2")], None),
+            function("f2", f2_version, vec![], canary_weight),
+        ],
+        resources: vec![],
+        annotations: std::collections::HashMap::new(),
+    };
+
+    // Start a workflow f1 -> f2.
+    let response = wf_client.start(workflow("0.1", None)).await.unwrap();
+    let instance = match &response {
+        SpawnWorkflowResponse::ResponseError(err) => panic!("{}", err),
+        SpawnWorkflowResponse::WorkflowInstance(val) => val.clone(),
+    };
+    let fid = |instance: &edgeless_api::workflow_instance::WorkflowInstance, name: &str| {
+        instance.domain_mapping.iter().find(|mapping| mapping.name == name).unwrap().function_id
+    };
+    let f1_id = fid(&instance, "f1");
+    let stable_f2_id = fid(&instance, "f2");
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    while mock_orc_receiver.try_next().is_ok() {}
+
+    let update = |workflow| edgeless_api::workflow_instance::UpdateWorkflowRequest {
+        workflow_id: instance.workflow_id.clone(),
+        workflow,
+    };
+
+    // Check that f1 is patched and return its output splits, if any.
+    let expect_patch_f1 = |mock_orc_receiver: &mut futures::channel::mpsc::UnboundedReceiver<MockFunctionInstanceEvent>| {
+        if let MockFunctionInstanceEvent::Patch(patch_req) = mock_orc_receiver.try_next().unwrap().unwrap() {
+            assert_eq!(f1_id, patch_req.function_id);
+            assert_eq!(stable_f2_id, patch_req.output_mapping.get("output").unwrap().function_id);
+            patch_req.output_splits.get("output").map(|targets| {
+                targets
+                    .iter()
+                    .map(|target| (target.instance_id.function_id, target.weight))
+                    .collect::<Vec<_>>()
+            })
+        } else {
+            panic!();
+        }
+    };
+
+    // Invalid canary weight.
+    let response = wf_client.update(update(workflow("0.2", Some("101")))).await.unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::ResponseError(_)));
+
+    // Start the canary version of f2, which receives 10% of the events.
+    let response = wf_client.update(update(workflow("0.2", Some("10")))).await.unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::WorkflowInstance(_)));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let canary_f2_id = if let MockFunctionInstanceEvent::StartFunction((id, spawn_req)) = mock_orc_receiver.try_next().unwrap().unwrap() {
+        assert_eq!("0.2", spawn_req.spec.version);
+        id
+    } else {
+        panic!();
+    };
+    assert_eq!(
+        Some(vec![(stable_f2_id, 90), (canary_f2_id, 10)]),
+        expect_patch_f1(&mut mock_orc_receiver)
+    );
+    assert!(mock_orc_receiver.try_next().is_err());
+
+    // Change the weight of the canary version.
+    let response = wf_client.update(update(workflow("0.2", Some("50")))).await.unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::WorkflowInstance(_)));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    assert_eq!(
+        Some(vec![(stable_f2_id, 50), (canary_f2_id, 50)]),
+        expect_patch_f1(&mut mock_orc_receiver)
+    );
+    assert!(mock_orc_receiver.try_next().is_err());
+
+    // Roll back the canary version.
+    let response = wf_client.update(update(workflow("0.1", None))).await.unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::WorkflowInstance(_)));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    assert_eq!(None, expect_patch_f1(&mut mock_orc_receiver));
+    if let MockFunctionInstanceEvent::StopFunction(id) = mock_orc_receiver.try_next().unwrap().unwrap() {
+        assert_eq!(canary_f2_id, id);
+    } else {
+        panic!();
+    }
+    assert!(mock_orc_receiver.try_next().is_err());
+
+    // Start a canary version again, then promote it.
+    let response = wf_client.update(update(workflow("0.3", Some("20")))).await.unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::WorkflowInstance(_)));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let canary_f2_id = if let MockFunctionInstanceEvent::StartFunction((id, spawn_req)) = mock_orc_receiver.try_next().unwrap().unwrap() {
+        assert_eq!("0.3", spawn_req.spec.version);
+        id
+    } else {
+        panic!();
+    };
+    assert_eq!(
+        Some(vec![(stable_f2_id, 80), (canary_f2_id, 20)]),
+        expect_patch_f1(&mut mock_orc_receiver)
+    );
+    assert!(mock_orc_receiver.try_next().is_err());
+
+    let response = wf_client.update(update(workflow("0.3", None))).await.unwrap();
+    let promoted_instance = match &response {
+        SpawnWorkflowResponse::ResponseError(err) => panic!("{}", err),
+        SpawnWorkflowResponse::WorkflowInstance(val) => val.clone(),
+    };
+    assert_eq!(canary_f2_id, fid(&promoted_instance, "f2"));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    if let MockFunctionInstanceEvent::Patch(patch_req) = mock_orc_receiver.try_next().unwrap().unwrap() {
+        assert_eq!(f1_id, patch_req.function_id);
+        assert_eq!(canary_f2_id, patch_req.output_mapping.get("output").unwrap().function_id);
+        assert!(patch_req.output_splits.is_empty());
+    } else {
+        panic!();
+    }
+    if let MockFunctionInstanceEvent::StopFunction(id) = mock_orc_receiver.try_next().unwrap().unwrap() {
+        assert_eq!(stable_f2_id, id);
+    } else {
+        panic!();
+    }
+    assert!(mock_orc_receiver.try_next().is_err());
+}
+
+#[tokio::test]
+async fn function_canary_migrate() {
+    let (mut wf_client, mut mock_orc_receiver) = test_setup().await;
+
+    let function = |name: &str, version: &str, output_mapping: Vec<(&str, &str)>, canary_weight: Option<&str>| {
+        edgeless_api::workflow_instance::WorkflowFunction {
+            name: name.to_string(),
+            class_specification: edgeless_api::function_instance::FunctionClassSpecification {
+                id: format!("fc-{}", name),
+                function_type: "RUST_WASM".to_string(),
+                version: version.to_string(),
+                binary: None,
+                code: None,
+                outputs: vec!["output".to_string()],
+            },
+            output_mapping: output_mapping
+                .into_iter()
+                .map(|(channel, target)| (channel.to_string(), target.to_string()))
+                .collect(),
+            annotations: canary_weight
+                .map(|weight| std::collections::HashMap::from([(edgeless_api::workflow_instance::CANARY_WEIGHT.to_string(), weight.to_string())]))
+                .unwrap_or_default(),
+        }
+    };
+    let workflow = |f2_version: &str, canary_weight: Option<&str>| edgeless_api::workflow_instance::SpawnWorkflowRequest {
+        functions: vec![
+            function("f1", "0.1", vec![("output", "f2")], None),
+            function("f2", f2_version, vec![], canary_weight),
+        ],
+        resources: vec![],
+        annotations: std::collections::HashMap::new(),
+    };
+
+    // Start a workflow f1 -> f2, then a canary version of f2.
+    let response = wf_client.start(workflow("0.1", None)).await.unwrap();
+    let instance = match &response {
+        SpawnWorkflowResponse::ResponseError(err) => panic!("{}", err),
+        SpawnWorkflowResponse::WorkflowInstance(val) => val.clone(),
+    };
+    let response = wf_client
+        .update(edgeless_api::workflow_instance::UpdateWorkflowRequest {
+            workflow_id: instance.workflow_id.clone(),
+            workflow: workflow("0.2", Some("30")),
+        })
+        .await
+        .unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::WorkflowInstance(_)));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    while mock_orc_receiver.try_next().is_ok() {}
+
+    // Migrate the workflow: both versions of f2 are started again, and the
+    // events of f1 are split between them as before.
+    let response = wf_client
+        .migrate(edgeless_api::workflow_instance::MigrateWorkflowRequest {
+            workflow_id: instance.workflow_id.clone(),
+            domain_id: String::from("domain-1"),
+            component: String::default(),
+        })
+        .await
+        .unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::WorkflowInstance(_)));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let mut started = std::collections::HashMap::new();
+    let mut splits = None;
+    let mut num_stopped = 0;
+    while let Ok(Some(event)) = mock_orc_receiver.try_next() {
+        match event {
+            MockFunctionInstanceEvent::StartFunction((id, spawn_req)) => {
+                started.insert(id, spawn_req.spec.version);
+            }
+            MockFunctionInstanceEvent::StopFunction(_) => num_stopped += 1,
+            MockFunctionInstanceEvent::Patch(patch_req) => {
+                splits = patch_req.output_splits.get("output").map(|targets| {
+                    targets
+                        .iter()
+                        .map(|target| (started.get(&target.instance_id.function_id).unwrap().clone(), target.weight))
+                        .collect::<Vec<_>>()
+                });
+            }
+            _ => panic!(),
+        }
+    }
+    assert_eq!(3, num_stopped);
+    assert_eq!(3, started.len());
+    assert_eq!(Some(vec![(String::from("0.1"), 70), (String::from("0.2"), 30)]), splits);
+}
+
+#[tokio::test]
+async fn workflow_placement_annotation() {
+    let (mut wf_client, mut mock_orc_receiver) = test_setup().await;
+
+    let function = |name: &str, annotations: Vec<(&str, &str)>| edgeless_api::workflow_instance::WorkflowFunction {
+        name: name.to_string(),
+        class_specification: edgeless_api::function_instance::FunctionClassSpecification {
+            id: format!("fc-{}", name),
+            function_type: "RUST_WASM".to_string(),
+            version: "0.1".to_string(),
+            binary: None,
+            code: None,
+            outputs: vec![],
+        },
+        output_mapping: std::collections::HashMap::new(),
+        annotations: annotations.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+    };
+
+    // The placement annotated in the workflow is inherited by the functions
+    // that do not override it, while the other annotations are ignored.
+    let response = wf_client
+        .start(edgeless_api::workflow_instance::SpawnWorkflowRequest {
+            functions: vec![
+                function("f1", vec![("max_instances", "1")]),
+                function("f2", vec![("placement", "default")]),
+            ],
+            resources: vec![],
+            annotations: std::collections::HashMap::from([
+                ("placement".to_string(), "locality".to_string()),
+                ("other".to_string(), "value".to_string()),
+            ]),
+        })
+        .await
+        .unwrap();
+    if let SpawnWorkflowResponse::ResponseError(err) = response {
+        panic!("{}", err);
+    }
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let mut annotations = std::collections::HashMap::new();
+    for _ in 0..2 {
+        if let MockFunctionInstanceEvent::StartFunction((_id, spawn_req)) = mock_orc_receiver.try_next().unwrap().unwrap() {
+            annotations.insert(spawn_req.spec.id.clone(), spawn_req.annotations);
+        } else {
+            panic!();
+        }
+    }
+    assert_eq!(
+        std::collections::HashMap::from([
+            ("max_instances".to_string(), "1".to_string()),
+            ("placement".to_string(), "locality".to_string())
+        ]),
+        annotations["fc-f1"]
+    );
+    assert_eq!(
+        std::collections::HashMap::from([("placement".to_string(), "default".to_string())]),
+        annotations["fc-f2"]
+    );
+}
+
+#[tokio::test]
+async fn workflow_dead_letter() {
+    let (mut wf_client, mut mock_orc_receiver) = test_setup().await;
+
+    let function = |name: &str, output_mapping: Vec<(&str, &str)>| edgeless_api::workflow_instance::WorkflowFunction {
+        name: name.to_string(),
+        class_specification: edgeless_api::function_instance::FunctionClassSpecification {
+            id: format!("fc-{}", name),
+            function_type: "RUST_WASM".to_string(),
+            version: "0.1".to_string(),
+            binary: None,
+            code: None,
+            outputs: vec!["output".to_string()],
+        },
+        output_mapping: output_mapping
+            .into_iter()
+            .map(|(channel, target)| (channel.to_string(), target.to_string()))
+            .collect(),
+        annotations: std::collections::HashMap::new(),
+    };
+    let workflow = |annotations: Vec<(&str, &str)>| edgeless_api::workflow_instance::SpawnWorkflowRequest {
+        functions: vec![function("f1", vec![("output", "f2")]), function("f2", vec![]), function("dlq", vec![])],
+        resources: vec![],
+        annotations: annotations.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+    };
+    let patched = |mock_orc_receiver: &mut futures::channel::mpsc::UnboundedReceiver<MockFunctionInstanceEvent>| {
+        let mut patched = std::collections::HashMap::new();
+        while let Ok(Some(event)) = mock_orc_receiver.try_next() {
+            if let MockFunctionInstanceEvent::Patch(patch_req) = event {
+                patched.insert(patch_req.function_id, patch_req.output_mapping);
+            }
+        }
+        patched
+    };
+
+    // Start a workflow f1 -> f2 with a dead-letter function: all the other
+    // functions are patched to forward the undeliverable events to it.
+    let response = wf_client
+        .start(workflow(vec![(edgeless_api::workflow_instance::DEAD_LETTER_ANNOTATION, "dlq")]))
+        .await
+        .unwrap();
+    let instance = match &response {
+        SpawnWorkflowResponse::ResponseError(err) => panic!("{}", err),
+        SpawnWorkflowResponse::WorkflowInstance(val) => val.clone(),
+    };
+    let fid = |name: &str| instance.domain_mapping.iter().find(|mapping| mapping.name == name).unwrap().function_id;
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let patched_start = patched(&mut mock_orc_receiver);
+    assert_eq!(2, patched_start.len());
+    let f1_mapping = patched_start.get(&fid("f1")).unwrap();
+    assert_eq!(2, f1_mapping.len());
+    assert_eq!(fid("f2"), f1_mapping.get("output").unwrap().function_id);
+    assert_eq!(
+        fid("dlq"),
+        f1_mapping.get(edgeless_api::workflow_instance::DEAD_LETTER_CHANNEL).unwrap().function_id
+    );
+    let f2_mapping = patched_start.get(&fid("f2")).unwrap();
+    assert_eq!(1, f2_mapping.len());
+    assert_eq!(
+        fid("dlq"),
+        f2_mapping.get(edgeless_api::workflow_instance::DEAD_LETTER_CHANNEL).unwrap().function_id
+    );
+
+    // Removing the dead-letter annotation patches the functions again.
+    let response = wf_client
+        .update(edgeless_api::workflow_instance::UpdateWorkflowRequest {
+            workflow_id: instance.workflow_id.clone(),
+            workflow: workflow(vec![]),
+        })
+        .await
+        .unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::WorkflowInstance(_)));
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let patched_update = patched(&mut mock_orc_receiver);
+    assert_eq!(2, patched_update.len());
+    assert_eq!(1, patched_update.get(&fid("f1")).unwrap().len());
+    assert!(patched_update.get(&fid("f2")).unwrap().is_empty());
+
+    // A dead-letter annotation must refer to a component of the workflow.
+    let response = wf_client
+        .start(workflow(vec![(edgeless_api::workflow_instance::DEAD_LETTER_ANNOTATION, "unknown")]))
+        .await
+        .unwrap();
+    assert!(matches!(response, SpawnWorkflowResponse::ResponseError(_)));
+}
+
+#[tokio::test]
+async fn recover_reconcile_domain() {
+    let filename = std::env::temp_dir()
+        .join(format!("edgeless-con-{}.save", uuid::Uuid::new_v4()))
+        .to_str()
+        .unwrap()
+        .to_string();
+    let orchestrator_url = format!(
+        "http://127.0.0.1:{}",
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    );
+
+    let function_class_specification = edgeless_api::function_instance::FunctionClassSpecification {
+        id: "fc1".to_string(),
+        function_type: "RUST_WASM".to_string(),
+        version: "0.1".to_string(),
+        binary: None,
+        code: None,
+        outputs: vec![],
+    };
+    let active_workflow = |name: &str, lid: uuid::Uuid| {
+        let request = edgeless_api::workflow_instance::SpawnWorkflowRequest {
+            functions: vec![edgeless_api::workflow_instance::WorkflowFunction {
+                name: name.to_string(),
+                class_specification: function_class_specification.clone(),
+                output_mapping: std::collections::HashMap::new(),
+                annotations: std::collections::HashMap::new(),
+            }],
+            resources: vec![],
+            annotations: std::collections::HashMap::new(),
+        };
+        persistence::PersistedWorkflow::Active(deployment_state::ActiveWorkflow {
+            desired_state: request.clone(),
+            augmented_spec: Some(request),
+            domain_mapping: std::collections::HashMap::from([(
+                name.to_string(),
+                deployment_state::ActiveComponent {
+                    component_type: ComponentType::Function,
+                    name: name.to_string(),
+                    domain_id: "domain-1".to_string(),
+                    lid,
+                    canary: None,
+                },
+            )]),
+        })
+    };
+
+    // State of the controller before the restart: two workflows with one
+    // function each in the same domain.
+    let wf_1 = edgeless_api::workflow_instance::WorkflowId {
+        workflow_id: uuid::Uuid::new_v4(),
+    };
+    let wf_2 = edgeless_api::workflow_instance::WorkflowId {
+        workflow_id: uuid::Uuid::new_v4(),
+    };
+    let lid_1 = uuid::Uuid::new_v4();
+    let lid_2 = uuid::Uuid::new_v4();
+    let lid_unknown = uuid::Uuid::new_v4();
+    {
+        use persistence::ControllerStore;
+        let mut store = persistence::JournalStore::new(&filename);
+        store.load().await.unwrap();
+        store.put_domain("domain-1", 42).await.unwrap();
+        store.put_workflow(&wf_1.workflow_id, active_workflow("f1", lid_1)).await.unwrap();
+        store.put_workflow(&wf_2.workflow_id, active_workflow("f2", lid_2)).await.unwrap();
+    }
+
+    let (mock_orc_sender, mut mock_orc_receiver) = futures::channel::mpsc::unbounded::<MockFunctionInstanceEvent>();
+    tokio::spawn(edgeless_api::grpc_impl::outer::orc::OrchestratorAPIServer::run(
+        Box::new(MockOrchestrator { sender: mock_orc_sender }),
+        orchestrator_url.clone(),
+        None,
+    ));
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let (workflow_instance_sender, workflow_instance_receiver) = futures::channel::mpsc::unbounded();
+    let (mut domain_registration_sender, domain_registration_receiver) = futures::channel::mpsc::unbounded();
+    let (mut internal_sender, internal_receiver) = futures::channel::mpsc::unbounded();
+    let store = Box::new(persistence::JournalStore::new(&filename));
+    tokio::spawn(async move {
+        let mut controller_task =
+            controller_task::ControllerTask::new(store, workflow_instance_receiver, domain_registration_receiver, internal_receiver).await;
+        controller_task.run().await;
+    });
+    let mut wf_client = client::ControllerClient::new(workflow_instance_sender).workflow_instance_api();
+
+    // The domain registers again with the same nonce, but the function of
+    // the second workflow was lost meanwhile, while another function that
+    // the controller does not know is active.
+    let mut capabilities = DomainCapabilities::default();
+    capabilities.runtimes.insert(String::from("RUST_WASM"));
+    let (reply_sender, reply_receiver) = tokio::sync::oneshot::channel();
+    domain_registration_sender
+        .send(DomainRegisterRequest::Update(
+            edgeless_api::domain_registration::UpdateDomainRequest {
+                domain_id: "domain-1".to_string(),
+                orchestrator_url,
+                capabilities,
+                refresh_deadline: std::time::SystemTime::now() + std::time::Duration::from_secs(60),
+                counter: 1,
+                nonce: 42,
+                active_functions: std::collections::HashSet::from([lid_1, lid_unknown]),
+                active_resources: std::collections::HashSet::new(),
+            },
+            reply_sender,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        edgeless_api::domain_registration::UpdateDomainResponse::Accepted,
+        reply_receiver.await.unwrap().unwrap()
+    );
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    // The second workflow is torn down and the unknown function is stopped.
+    let mut stopped = std::collections::HashSet::new();
+    while let Ok(Some(event)) = mock_orc_receiver.try_next() {
+        match event {
+            MockFunctionInstanceEvent::StopFunction(id) => {
+                stopped.insert(id);
+            }
+            _ => panic!("unexpected event"),
+        }
+    }
+    assert_eq!(std::collections::HashSet::from([lid_2, lid_unknown]), stopped);
+
+    // The first workflow is confirmed as it was.
+    let info = wf_client.inspect(wf_1.clone()).await.unwrap();
+    assert_eq!(1, info.status.domain_mapping.len());
+    assert_eq!(lid_1, info.status.domain_mapping[0].function_id);
+
+    // The second workflow is orphan, until it is deployed again.
+    assert!(wf_client.inspect(wf_2.clone()).await.unwrap().status.domain_mapping.is_empty());
+
+    let (reply_sender, reply_receiver) = tokio::sync::oneshot::channel();
+    internal_sender.send(InternalRequest::Refresh(reply_sender)).await.unwrap();
+    reply_receiver.await.unwrap();
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+    let mut new_lid = None;
+    while let Ok(Some(event)) = mock_orc_receiver.try_next() {
+        if let MockFunctionInstanceEvent::StartFunction((id, spawn_req)) = event {
+            assert!(new_lid.is_none());
+            assert_eq!(function_class_specification, spawn_req.spec);
+            new_lid = Some(id);
+        }
+    }
+    let info = wf_client.inspect(wf_2).await.unwrap();
+    assert_eq!(1, info.status.domain_mapping.len());
+    assert_eq!(new_lid.unwrap(), info.status.domain_mapping[0].function_id);
+
+    let _ = std::fs::remove_file(&filename);
+}
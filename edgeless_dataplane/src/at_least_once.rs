@@ -0,0 +1,427 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Name of the function annotation containing the comma-separated list of
+/// the output channels whose casts are delivered at least once.
+pub const AT_LEAST_ONCE_ANNOTATION: &str = "at_least_once";
+
+/// Number of identifiers of the events delivered at least once that are
+/// remembered by the receiver to discard duplicates.
+pub const DEDUP_WINDOW: usize = 100_000;
+
+/// Telemetry handle shared by the tasks delivering events at least once.
+pub type SharedTelemetryHandle = std::sync::Arc<std::sync::Mutex<Option<Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>>>>;
+
+/// Parameters of the retransmissions of the events delivered at least once.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts to deliver an event, after which it is
+    /// dropped.
+    pub max_attempts: u32,
+    /// Delay before the first retransmission, doubled at every attempt.
+    pub initial_backoff: std::time::Duration,
+    /// Maximum delay between two consecutive attempts.
+    pub max_backoff: std::time::Duration,
+    /// Maximum number of events buffered for a peer node, beyond which the
+    /// new events are dropped.
+    pub max_buffered: usize,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            initial_backoff: std::time::Duration::from_millis(100),
+            max_backoff: std::time::Duration::from_secs(5),
+            max_buffered: 10_000,
+        }
+    }
+}
+
+/// Return the output channels selected by the at-least-once annotation.
+pub fn parse_annotation(annotations: &std::collections::HashMap<String, String>) -> std::collections::HashSet<String> {
+    annotations
+        .get(AT_LEAST_ONCE_ANNOTATION)
+        .map(|channels| {
+            channels
+                .split(',')
+                .map(|channel| channel.trim())
+                .filter(|channel| !channel.is_empty())
+                .map(|channel| channel.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Return a new identifier for an event delivered at least once.
+pub fn new_event_id() -> u64 {
+    loop {
+        let (_, event_id) = uuid::Uuid::new_v4().as_u64_pair();
+        if event_id != 0 {
+            return event_id;
+        }
+    }
+}
+
+/// Output channels of the components hosted by a node whose casts are
+/// delivered at least once.
+#[derive(Default)]
+pub struct AtLeastOnceOutputs {
+    // key:   component identifier of the sender
+    // value: output channels, as found in the annotation
+    channels: std::collections::HashMap<edgeless_api::function_instance::ComponentId, std::collections::HashSet<String>>,
+}
+
+impl AtLeastOnceOutputs {
+    /// Set the output channels of a component delivered at least once.
+    pub fn set_channels(&mut self, component_id: edgeless_api::function_instance::ComponentId, channels: std::collections::HashSet<String>) {
+        if channels.is_empty() {
+            self.channels.remove(&component_id);
+        } else {
+            self.channels.insert(component_id, channels);
+        }
+    }
+
+    /// Remove the output channels of a component.
+    pub fn remove(&mut self, component_id: &edgeless_api::function_instance::ComponentId) {
+        self.channels.remove(component_id);
+    }
+
+    /// Remove the output channels of all the components.
+    pub fn clear(&mut self) {
+        self.channels.clear();
+    }
+
+    /// Return true if the casts from a component on an output channel are
+    /// delivered at least once.
+    pub fn is_at_least_once(&self, source: &edgeless_api::function_instance::ComponentId, channel: &str) -> bool {
+        self.channels.get(source).is_some_and(|channels| channels.contains(channel))
+    }
+}
+
+/// Identifiers of the events delivered at least once most recently received,
+/// used to discard the duplicates due to retransmissions.
+pub struct DeliveredEvents {
+    ids: std::collections::HashSet<(edgeless_api::function_instance::InstanceId, u64)>,
+    order: std::collections::VecDeque<(edgeless_api::function_instance::InstanceId, u64)>,
+    capacity: usize,
+}
+
+impl DeliveredEvents {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            ids: std::collections::HashSet::new(),
+            order: std::collections::VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Return true if the event from the given source has been delivered.
+    pub fn contains(&self, source: &edgeless_api::function_instance::InstanceId, event_id: u64) -> bool {
+        self.ids.contains(&(*source, event_id))
+    }
+
+    /// Remember an event delivered, forgetting the oldest one if needed.
+    pub fn insert(&mut self, source: edgeless_api::function_instance::InstanceId, event_id: u64) {
+        if self.ids.insert((source, event_id)) {
+            self.order.push_back((source, event_id));
+            while self.order.len() > self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.ids.remove(&oldest);
+                }
+            }
+        }
+    }
+}
+
+/// Creates the client used to deliver the events at least once to a peer
+/// node, which is only called when the first such event is sent.
+pub type PeerConnector = Box<dyn FnOnce() -> futures::future::BoxFuture<'static, Box<dyn edgeless_api::invocation::InvocationAPI>> + Send>;
+
+/// Command for the task delivering the events at least once to a peer node.
+#[allow(clippy::large_enum_variant)]
+enum OutboxCommand {
+    /// Deliver an event.
+    Event(edgeless_api::invocation::Event),
+    /// Deliver the events with a new client, e.g., because the peer node has
+    /// been added or its invocation URL has changed.
+    Connect(PeerConnector),
+}
+
+/// Event pending delivery at least once.
+struct PendingEvent {
+    event: edgeless_api::invocation::Event,
+    /// Number of attempts to deliver the event so far.
+    attempts: u32,
+    /// Delay before the next retransmission, if the next attempt fails.
+    backoff: std::time::Duration,
+    /// Time of the next attempt.
+    next_attempt: tokio::time::Instant,
+}
+
+// key:   target of the events
+// value: events pending delivery to the target, in order
+type PendingQueues = std::collections::HashMap<edgeless_api::function_instance::InstanceId, std::collections::VecDeque<PendingEvent>>;
+
+/// Events delivered at least once to a peer node.
+///
+/// The events are delivered by a dedicated task, with a client not shared
+/// with the events delivered at most once, which retries with an
+/// exponential backoff until the peer acknowledges the event or the maximum
+/// number of attempts is reached. The events towards a given target are
+/// delivered in order, while those towards other targets are not delayed by
+/// the retransmissions. The events are buffered until a client is
+/// available, i.e., the peer node is added, which is created by the task
+/// itself, so that no client is created for the peers towards which no
+/// event is delivered at least once.
+/// The failed attempts and the events dropped, including those still
+/// pending when the outbox is dropped, are reported through telemetry.
+/// The events dropped are also passed to `undelivered`, with the reason why.
+pub struct PeerOutbox {
+    sender: tokio::sync::mpsc::UnboundedSender<OutboxCommand>,
+    // Number of events pushed and not yet delivered or dropped.
+    buffered: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    max_buffered: usize,
+    telemetry: SharedTelemetryHandle,
+    undelivered: crate::remote_node::UndeliveredSender,
+}
+
+impl PeerOutbox {
+    pub fn new(policy: RetryPolicy, telemetry: SharedTelemetryHandle, undelivered: crate::remote_node::UndeliveredSender) -> Self {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let buffered = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let max_buffered = policy.max_buffered;
+        tokio::spawn(Self::delivery_task(
+            receiver,
+            policy,
+            telemetry.clone(),
+            undelivered.clone(),
+            buffered.clone(),
+        ));
+        Self {
+            sender,
+            buffered,
+            max_buffered,
+            telemetry,
+            undelivered,
+        }
+    }
+
+    /// Set how to create the client used to deliver the events to the peer
+    /// node.
+    pub fn connect(&self, connector: PeerConnector) {
+        let _ = self.sender.send(OutboxCommand::Connect(connector));
+    }
+
+    /// Enqueue an event for delivery, which is dropped if the buffer is full.
+    pub fn push(&self, event: edgeless_api::invocation::Event) {
+        if self.buffered.fetch_add(1, std::sync::atomic::Ordering::SeqCst) >= self.max_buffered {
+            self.buffered.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+            log::warn!("Dropped event sent at least once, buffer full: {}", event);
+            drop_event(&self.telemetry, &self.undelivered, event, 0, "at-least-once buffer full");
+            return;
+        }
+        if let Err(err) = self.sender.send(OutboxCommand::Event(event)) {
+            self.buffered.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+            if let OutboxCommand::Event(event) = err.0 {
+                log::warn!("Dropped event sent at least once, outbox closed: {}", event);
+                drop_event(&self.telemetry, &self.undelivered, event, 0, "peer node removed");
+            }
+        }
+    }
+
+    fn apply(command: OutboxCommand, policy: &RetryPolicy, connector: &mut Option<PeerConnector>, queues: &mut PendingQueues) {
+        match command {
+            OutboxCommand::Event(event) => queues.entry(event.target).or_default().push_back(PendingEvent {
+                event,
+                attempts: 0,
+                backoff: policy.initial_backoff,
+                next_attempt: tokio::time::Instant::now(),
+            }),
+            OutboxCommand::Connect(new_connector) => *connector = Some(new_connector),
+        }
+    }
+
+    async fn delivery_task(
+        mut receiver: tokio::sync::mpsc::UnboundedReceiver<OutboxCommand>,
+        policy: RetryPolicy,
+        telemetry: SharedTelemetryHandle,
+        undelivered: crate::remote_node::UndeliveredSender,
+        buffered: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    ) {
+        let mut client: Option<Box<dyn edgeless_api::invocation::InvocationAPI>> = None;
+        let mut connector: Option<PeerConnector> = None;
+        let mut queues = PendingQueues::new();
+
+        'outer: loop {
+            // Take the commands already received, without waiting.
+            loop {
+                match receiver.try_recv() {
+                    Ok(command) => Self::apply(command, &policy, &mut connector, &mut queues),
+                    Err(tokio::sync::mpsc::error::TryRecvError::Empty) => break,
+                    Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => break 'outer,
+                }
+            }
+
+            // Create the client to the peer node, if it has been added or
+            // replaced since the last iteration.
+            if let Some(connector) = connector.take() {
+                client = Some(connector().await);
+            }
+
+            // Attempt to deliver the first event of the target that has been
+            // due for the longest time, if any.
+            let next_attempt = queues
+                .iter()
+                .filter_map(|(target, queue)| queue.front().map(|pending| (pending.next_attempt, *target)))
+                .min_by_key(|(next_attempt, _)| *next_attempt);
+            if let (Some(node_client), Some((next_attempt, target))) = (client.as_mut(), next_attempt)
+                && next_attempt <= tokio::time::Instant::now()
+            {
+                let queue = queues.get_mut(&target).expect("queue of a target with pending events");
+                let pending = queue.front_mut().expect("pending event of a target");
+                pending.attempts += 1;
+                match node_client.handle(pending.event.clone()).await {
+                    Ok(_) => {
+                        queue.pop_front();
+                        buffered.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                    }
+                    Err(err) if pending.attempts >= policy.max_attempts => {
+                        log::warn!(
+                            "Dropped event sent at least once after {} attempts: {}: {}",
+                            pending.attempts,
+                            pending.event,
+                            err
+                        );
+                        if let Some(pending) = queue.pop_front() {
+                            drop_event(
+                                &telemetry,
+                                &undelivered,
+                                pending.event,
+                                pending.attempts,
+                                "maximum delivery attempts reached",
+                            );
+                        }
+                        buffered.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                    }
+                    Err(err) => {
+                        log::debug!("Retrying event sent at least once in {:?}: {}: {}", pending.backoff, pending.event, err);
+                        report(
+                            &telemetry,
+                            &pending.event,
+                            edgeless_telemetry::telemetry_events::TelemetryEvent::DeliveryRetry(pending.attempts),
+                        );
+                        pending.next_attempt = tokio::time::Instant::now() + pending.backoff;
+                        pending.backoff = std::cmp::min(pending.backoff * 2, policy.max_backoff);
+                    }
+                }
+                if queue.is_empty() {
+                    queues.remove(&target);
+                }
+                continue;
+            }
+
+            // Wait for a new command or for the next attempt.
+            let command = match (client.is_some(), next_attempt) {
+                (true, Some((next_attempt, _))) => tokio::select! {
+                    command = receiver.recv() => command,
+                    _ = tokio::time::sleep_until(next_attempt) => continue,
+                },
+                _ => receiver.recv().await,
+            };
+            match command {
+                Some(command) => Self::apply(command, &policy, &mut connector, &mut queues),
+                None => break,
+            }
+        }
+
+        // The outbox has been dropped, e.g., because the peer node has been
+        // removed: the events still pending are lost.
+        for pending in queues.into_values().flatten() {
+            log::warn!("Dropped event sent at least once, peer removed: {}", pending.event);
+            drop_event(&telemetry, &undelivered, pending.event, pending.attempts, "peer node removed");
+        }
+    }
+}
+
+// Report an event dropped after the given number of attempts and pass it to
+// the sink of the undelivered events.
+fn drop_event(
+    telemetry: &SharedTelemetryHandle,
+    undelivered: &crate::remote_node::UndeliveredSender,
+    event: edgeless_api::invocation::Event,
+    attempts: u32,
+    reason: &str,
+) {
+    report(
+        telemetry,
+        &event,
+        edgeless_telemetry::telemetry_events::TelemetryEvent::DeliveryFailure(attempts),
+    );
+    let _ = undelivered.send((event, reason.to_string()));
+}
+
+fn report(
+    telemetry: &SharedTelemetryHandle,
+    event: &edgeless_api::invocation::Event,
+    telemetry_event: edgeless_telemetry::telemetry_events::TelemetryEvent,
+) {
+    if let Some(handle) = telemetry.lock().unwrap().as_mut() {
+        handle.observe(
+            telemetry_event,
+            std::collections::BTreeMap::from([
+                ("FUNCTION_ID".to_string(), event.source.function_id.to_string()),
+                ("TARGET_NODE_ID".to_string(), event.target.node_id.to_string()),
+                ("TARGET_FUNCTION_ID".to_string(), event.target.function_id.to_string()),
+            ]),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_annotation() {
+        assert!(parse_annotation(&std::collections::HashMap::new()).is_empty());
+        assert_eq!(
+            std::collections::HashSet::from(["out".to_string(), "err".to_string()]),
+            parse_annotation(&std::collections::HashMap::from([(
+                AT_LEAST_ONCE_ANNOTATION.to_string(),
+                "out, err,,".to_string()
+            )]))
+        );
+    }
+
+    #[test]
+    fn test_at_least_once_outputs() {
+        let source = uuid::Uuid::new_v4();
+
+        let mut outputs = AtLeastOnceOutputs::default();
+        assert!(!outputs.is_at_least_once(&source, "out"));
+
+        outputs.set_channels(source, std::collections::HashSet::from(["out".to_string()]));
+        assert!(outputs.is_at_least_once(&source, "out"));
+        assert!(!outputs.is_at_least_once(&source, "log"));
+        assert!(!outputs.is_at_least_once(&uuid::Uuid::new_v4(), "out"));
+
+        outputs.remove(&source);
+        assert!(!outputs.is_at_least_once(&source, "out"));
+    }
+
+    #[test]
+    fn test_delivered_events() {
+        let source = edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4());
+        let mut delivered = DeliveredEvents::new(2);
+        delivered.insert(source, 1);
+        delivered.insert(source, 2);
+        delivered.insert(source, 2);
+        assert!(delivered.contains(&source, 1));
+        assert!(delivered.contains(&source, 2));
+        delivered.insert(source, 3);
+        assert!(!delivered.contains(&source, 1));
+        assert!(delivered.contains(&source, 2));
+        assert!(delivered.contains(&source, 3));
+    }
+}
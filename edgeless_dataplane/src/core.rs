@@ -8,6 +8,7 @@ pub use edgeless_api::invocation::LinkProcessingResult;
 /// Link instances are commonly created by a LinkProvider (which is not a trait yet).
 #[async_trait::async_trait]
 pub trait DataPlaneLink: Send + Sync {
+    #[allow(clippy::too_many_arguments)]
     async fn handle_send(
         &mut self,
         target: &edgeless_api::function_instance::InstanceId,
@@ -16,21 +17,32 @@ pub trait DataPlaneLink: Send + Sync {
         created: &edgeless_api::function_instance::EventTimestamp,
         channel_id: u64,
         metadata: &edgeless_api::function_instance::EventMetadata,
+        delivery: Delivery,
     ) -> LinkProcessingResult;
 }
 
+/// Delivery guarantee of an event towards another node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    AtMostOnce,
+    /// Only honoured for casts.
+    AtLeastOnce,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CallRet {
     NoReply,
-    Reply(String),
+    Reply(Vec<u8>),
     Err,
+    /// No return event received before the deadline of the call.
+    Timeout,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Message {
-    Cast(String),
-    Call(String),
-    CallRet(String),
+    Cast(Vec<u8>),
+    Call(Vec<u8>),
+    CallRet(Vec<u8>),
     CallNoRet,
     Err,
 }
@@ -44,6 +56,17 @@ pub struct DataplaneEvent {
     pub metadata: edgeless_api::function_instance::EventMetadata,
 }
 
+/// Transport used to send the events to the gRPC peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvocationTransport {
+    /// One unary request per event.
+    #[default]
+    Unary,
+    /// Batches of events over a persistent bidirectional stream per peer.
+    Stream,
+}
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct EdgelessDataplanePeerSettings {
     pub node_id: uuid::Uuid,
@@ -0,0 +1,89 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+use base64::Engine;
+
+/// Dead-letter targets of the components hosted by a node.
+///
+/// key:   component identifier of the sender
+/// value: instance to which the events that cannot be delivered or
+///        processed are forwarded, as found in the last patch
+pub type DeadLetterTargets = std::collections::HashMap<edgeless_api::function_instance::ComponentId, edgeless_api::function_instance::InstanceId>;
+
+/// Function/resource instance mentioned in a dead letter.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeadLetterInstance {
+    pub node_id: uuid::Uuid,
+    pub function_id: uuid::Uuid,
+}
+
+impl From<&edgeless_api::function_instance::InstanceId> for DeadLetterInstance {
+    fn from(instance_id: &edgeless_api::function_instance::InstanceId) -> Self {
+        Self {
+            node_id: instance_id.node_id,
+            function_id: instance_id.function_id,
+        }
+    }
+}
+
+/// Event that could not be delivered or processed, forwarded as a JSON
+/// cast to the dead-letter function/resource of the workflow.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeadLetter {
+    /// Human-readable reason why the event has been forwarded.
+    pub reason: String,
+    /// Sender of the original event.
+    pub source: DeadLetterInstance,
+    /// Intended receiver of the original event.
+    pub target: DeadLetterInstance,
+    /// Payload of the original event, base64-encoded.
+    pub payload: String,
+}
+
+impl DeadLetter {
+    pub fn new(
+        reason: &str,
+        source: &edgeless_api::function_instance::InstanceId,
+        target: &edgeless_api::function_instance::InstanceId,
+        payload: &[u8],
+    ) -> Self {
+        Self {
+            reason: reason.to_string(),
+            source: source.into(),
+            target: target.into(),
+            payload: base64::engine::general_purpose::STANDARD.encode(payload),
+        }
+    }
+
+    /// Return the payload of the original event.
+    pub fn payload(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(base64::engine::general_purpose::STANDARD.decode(&self.payload)?)
+    }
+}
+
+/// Return the dead-letter target of a function/resource instance, if its
+/// output mapping contains the dead-letter channel.
+pub fn dead_letter_target(update: &edgeless_api::common::PatchRequest) -> Option<edgeless_api::function_instance::InstanceId> {
+    update.output_mapping.get(edgeless_api::workflow_instance::DEAD_LETTER_CHANNEL).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dead_letter_serialize_deserialize() {
+        let source = edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4());
+        let target = edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4());
+        let payload = vec![0_u8, 1, 2, 255];
+
+        let dead_letter = DeadLetter::new("target not found", &source, &target, &payload);
+        let serialized = serde_json::to_string(&dead_letter).unwrap();
+        let deserialized: DeadLetter = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(dead_letter, deserialized);
+        assert_eq!("target not found", deserialized.reason);
+        assert_eq!(source.function_id, deserialized.source.function_id);
+        assert_eq!(target.node_id, deserialized.target.node_id);
+        assert_eq!(payload, deserialized.payload().unwrap());
+    }
+}
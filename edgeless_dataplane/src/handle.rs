@@ -2,17 +2,45 @@
 // SPDX-FileCopyrightText: © 2023 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
 // SPDX-FileCopyrightText: © 2023 Siemens AG
 // SPDX-License-Identifier: MIT
-use futures::{SinkExt, StreamExt};
+use futures::StreamExt;
 
 use crate::core::*;
+use crate::inbound_queue::*;
 use crate::node_local::*;
+use crate::output_split::*;
 use crate::remote_node::*;
 
+/// Default timeout of the calls, if the event metadata do not contain a
+/// deadline.
+pub const DEFAULT_CALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
 fn timestamp_utc() -> edgeless_api::function_instance::EventTimestamp {
-    let now = chrono::Utc::now();
+    timestamp_from_utc(chrono::Utc::now())
+}
+
+fn timestamp_from_utc(time: chrono::DateTime<chrono::Utc>) -> edgeless_api::function_instance::EventTimestamp {
     edgeless_api::function_instance::EventTimestamp {
-        secs: now.timestamp(),
-        nsecs: now.timestamp_subsec_nanos(),
+        secs: time.timestamp(),
+        nsecs: time.timestamp_subsec_nanos(),
+    }
+}
+
+/// Return the time remaining until a deadline, zero if already expired.
+pub fn remaining_time(deadline: &edgeless_api::function_instance::EventTimestamp) -> std::time::Duration {
+    let deadline = chrono::DateTime::from_timestamp(deadline.secs, deadline.nsecs).unwrap_or(chrono::DateTime::UNIX_EPOCH);
+    (deadline - chrono::Utc::now()).to_std().unwrap_or(std::time::Duration::ZERO)
+}
+
+/// Return the deadline of a call with the given timeout, which cannot be
+/// after the deadline in the event metadata, if any.
+fn call_deadline(
+    metadata: &edgeless_api::function_instance::EventMetadata,
+    timeout: std::time::Duration,
+) -> edgeless_api::function_instance::EventTimestamp {
+    let deadline = timestamp_from_utc(chrono::Utc::now() + timeout);
+    match metadata.deadline() {
+        Some(inherited) if (inherited.secs, inherited.nsecs) < (deadline.secs, deadline.nsecs) => inherited,
+        _ => deadline,
     }
 }
 
@@ -21,134 +49,196 @@ fn timestamp_utc() -> edgeless_api::function_instance::EventTimestamp {
 #[derive(Clone)]
 pub struct DataplaneHandle {
     slf: edgeless_api::function_instance::InstanceId,
-    receiver: std::sync::Arc<tokio::sync::Mutex<futures::channel::mpsc::UnboundedReceiver<DataplaneEvent>>>,
+    inbound: std::sync::Arc<InboundReceiver>,
     output_chain: std::sync::Arc<tokio::sync::Mutex<Vec<Box<dyn DataPlaneLink>>>>,
-    receiver_overwrites: std::sync::Arc<tokio::sync::Mutex<TemporaryReceivers>>,
+    output_splits: std::sync::Arc<tokio::sync::Mutex<OutputSplits>>,
+    at_least_once: std::sync::Arc<tokio::sync::Mutex<crate::at_least_once::AtLeastOnceOutputs>>,
+    dead_letters: std::sync::Arc<tokio::sync::Mutex<crate::dead_letter::DeadLetterTargets>>,
+    call_timeout: std::time::Duration,
     next_id: u64,
 }
 
 impl DataplaneHandle {
-    async fn new(
+    fn new(
         receiver_id: edgeless_api::function_instance::InstanceId,
         output_chain: Vec<Box<dyn DataPlaneLink>>,
-        receiver: futures::channel::mpsc::UnboundedReceiver<DataplaneEvent>,
+        inbound: InboundReceiver,
+        output_splits: std::sync::Arc<tokio::sync::Mutex<OutputSplits>>,
+        at_least_once: std::sync::Arc<tokio::sync::Mutex<crate::at_least_once::AtLeastOnceOutputs>>,
+        dead_letters: std::sync::Arc<tokio::sync::Mutex<crate::dead_letter::DeadLetterTargets>>,
+        call_timeout: std::time::Duration,
     ) -> Self {
-        let (main_sender, main_receiver) = futures::channel::mpsc::unbounded::<DataplaneEvent>();
-        let receiver_overwrites = std::sync::Arc::new(tokio::sync::Mutex::new(TemporaryReceivers {
-            temporary_receivers: std::collections::HashMap::new(),
-        }));
-
-        let clone_overwrites = receiver_overwrites.clone();
-        // This task intercepts the messages received and routes responses towards temporary receivers while routing other events towards the main receiver used in `receive_next`.
-        tokio::spawn(async move {
-            let mut receiver = receiver;
-            let mut main_sender = main_sender;
-            loop {
-                if let Some(DataplaneEvent {
-                    source_id,
-                    channel_id,
-                    message,
-                    created,
-                    metadata,
-                }) = receiver.next().await
-                {
-                    if let Some(sender) = clone_overwrites.lock().await.temporary_receivers.remove(&channel_id) {
-                        match sender.send((source_id, message.clone())) {
-                            Ok(_) => {
-                                continue;
-                            }
-                            Err(_) => {
-                                log::error!("Tried to use expired overwrite send handle.");
-                            }
-                        }
-                    }
-                    match main_sender
-                        .send(DataplaneEvent {
-                            source_id,
-                            channel_id,
-                            message,
-                            created,
-                            metadata,
-                        })
-                        .await
-                    {
-                        Ok(_) => {}
-                        Err(_) => {
-                            break;
-                        }
-                    }
-                }
-            }
-        });
-
         DataplaneHandle {
             slf: receiver_id,
-            receiver: std::sync::Arc::new(tokio::sync::Mutex::new(main_receiver)),
+            inbound: std::sync::Arc::new(inbound),
             output_chain: std::sync::Arc::new(tokio::sync::Mutex::new(output_chain)),
-            receiver_overwrites,
+            output_splits,
+            at_least_once,
+            dead_letters,
+            call_timeout,
             next_id: 1,
         }
     }
 
     /// Main receive function for receiving the next cast or call event.
-    /// This is NOT used for processing replies to return values.
+    /// This is NOT used for processing replies to return values, which are
+    /// passed directly by the inbound queue to the calls waiting for them.
+    /// The call events received after their deadline are discarded, since
+    /// the caller is not waiting for them anymore.
     pub async fn receive_next(&mut self) -> DataplaneEvent {
         loop {
-            if let Some(DataplaneEvent {
-                source_id,
-                channel_id,
-                message,
-                created,
-                metadata,
-            }) = self.receiver.lock().await.next().await
+            let event = self.inbound.recv().await;
+            if let Message::Call(_) = &event.message
+                && event.metadata.deadline().is_some_and(|deadline| remaining_time(&deadline).is_zero())
             {
-                if std::mem::discriminant(&message) == std::mem::discriminant(&Message::Cast("".to_string()))
-                    || std::mem::discriminant(&message) == std::mem::discriminant(&Message::Call("".to_string()))
-                {
-                    return DataplaneEvent {
-                        source_id,
-                        channel_id,
-                        message,
-                        created,
-                        metadata,
-                    };
-                }
-                log::error!("Unprocesses other message");
+                log::warn!("Discarded call from {:?} to {:?} received after its deadline", event.source_id, self.slf);
+                continue;
+            }
+            return event;
+        }
+    }
+
+    /// Return the next cast or call event waiting in the inbound queue, if
+    /// any, without waiting, e.g., to drain the queue of an instance that
+    /// is being stopped. The call events past their deadline are discarded.
+    pub fn try_receive_next(&mut self) -> Option<DataplaneEvent> {
+        while let Some(event) = self.inbound.try_recv() {
+            if let Message::Call(_) = &event.message
+                && event.metadata.deadline().is_some_and(|deadline| remaining_time(&deadline).is_zero())
+            {
+                log::warn!("Discarded call from {:?} to {:?} received after its deadline", event.source_id, self.slf);
+                continue;
             }
+            return Some(event);
         }
+        None
+    }
+
+    /// Return the number of cast and call events waiting in the inbound
+    /// queue of this instance.
+    pub fn queue_depth(&self) -> usize {
+        self.inbound.len()
     }
 
     /// Send a `cast` event.
+    /// The payload is carried as raw bytes; UTF-8 strings can be passed directly.
     pub async fn send(
         &mut self,
         target: edgeless_api::function_instance::InstanceId,
-        msg: String,
+        msg: impl Into<Vec<u8>>,
         metadata: &edgeless_api::function_instance::EventMetadata,
     ) {
-        self.send_inner(target, Message::Cast(msg), timestamp_utc(), 0, metadata).await;
+        self.send_inner(target, Message::Cast(msg.into()), timestamp_utc(), 0, metadata, Delivery::AtMostOnce)
+            .await;
     }
 
-    // Send a `call` event and wait for the return event.
-    // Internally, this sets up a receiver override to handle the message before it would be sent to the `receive_next` function.
+    /// Send a `cast` event on an output channel, whose `target` is the one
+    /// in the output mapping, unless the events on the channel are split
+    /// among multiple instances.
+    /// The event is delivered at least once to other nodes if the channel
+    /// is selected by the at-least-once annotation of this instance.
+    pub async fn send_output(
+        &mut self,
+        channel: &str,
+        target: edgeless_api::function_instance::InstanceId,
+        msg: impl Into<Vec<u8>>,
+        metadata: &edgeless_api::function_instance::EventMetadata,
+    ) {
+        let target = self.split_target(channel, target).await;
+        let delivery = if self.at_least_once.lock().await.is_at_least_once(&self.slf.function_id, channel) {
+            Delivery::AtLeastOnce
+        } else {
+            Delivery::AtMostOnce
+        };
+        self.send_inner(target, Message::Cast(msg.into()), timestamp_utc(), 0, metadata, delivery)
+            .await;
+    }
+
+    /// Select the instance receiving the next event on an output channel,
+    /// which differs from `target` only if the events on the channel are
+    /// split among multiple instances.
+    /// Together with `send`, this allows a sequence of events, e.g., the
+    /// chunks of a stream, to be delivered to the same instance.
+    pub async fn select_target(
+        &mut self,
+        channel: &str,
+        target: edgeless_api::function_instance::InstanceId,
+    ) -> edgeless_api::function_instance::InstanceId {
+        self.split_target(channel, target).await
+    }
+
+    // Send a `call` event and wait for the return event, at most until the
+    // deadline in the metadata or for the default call timeout.
     pub async fn call(
         &mut self,
         target: edgeless_api::function_instance::InstanceId,
-        msg: String,
+        msg: impl Into<Vec<u8>>,
         metadata: &edgeless_api::function_instance::EventMetadata,
     ) -> CallRet {
+        let call_timeout = self.call_timeout;
+        self.call_with_timeout(target, msg, metadata, call_timeout).await
+    }
+
+    /// Send a `call` event on an output channel, like `send_output`, and
+    /// wait for the return event, like `call`.
+    /// Calls are always delivered at most once, since the caller is
+    /// notified of failures by the return event.
+    pub async fn call_output(
+        &mut self,
+        channel: &str,
+        target: edgeless_api::function_instance::InstanceId,
+        msg: impl Into<Vec<u8>>,
+        metadata: &edgeless_api::function_instance::EventMetadata,
+    ) -> CallRet {
+        let target = self.split_target(channel, target).await;
+        self.call(target, msg, metadata).await
+    }
+
+    // Send a `call` event and wait for the return event, at most until the
+    // deadline in the metadata or for the given timeout, whichever comes
+    // first, after which `CallRet::Timeout` is returned.
+    // The deadline is carried in the metadata of the event, so that the
+    // callee can honour the remaining budget in its own calls.
+    // Internally, this sets up a temporary receiver in the inbound queue to handle the message before it would be sent to the `receive_next` function.
+    pub async fn call_with_timeout(
+        &mut self,
+        target: edgeless_api::function_instance::InstanceId,
+        msg: impl Into<Vec<u8>>,
+        metadata: &edgeless_api::function_instance::EventMetadata,
+        timeout: std::time::Duration,
+    ) -> CallRet {
+        let deadline = call_deadline(metadata, timeout);
+        let remaining = remaining_time(&deadline);
+        if remaining.is_zero() {
+            return CallRet::Timeout;
+        }
+        let metadata = metadata.clone().with_deadline(Some(deadline));
+
         let (sender, receiver) = futures::channel::oneshot::channel::<(edgeless_api::function_instance::InstanceId, Message)>();
         let channel_id = self.next_id;
         self.next_id += 1;
-        // Potential Leak: This is only received if a message is received (or the handle is dropped)
-        self.receiver_overwrites.lock().await.temporary_receivers.insert(channel_id, sender);
-        self.send_inner(target, Message::Call(msg), timestamp_utc(), channel_id, metadata).await;
-        match receiver.await {
-            Ok((_src, msg)) => match msg {
+        self.inbound.add_temporary_receiver(channel_id, sender);
+        self.send_inner(
+            target,
+            Message::Call(msg.into()),
+            timestamp_utc(),
+            channel_id,
+            &metadata,
+            Delivery::AtMostOnce,
+        )
+        .await;
+        match tokio::time::timeout(remaining, receiver).await {
+            Ok(Ok((_src, msg))) => match msg {
                 Message::CallRet(ret) => CallRet::Reply(ret),
                 Message::CallNoRet => CallRet::NoReply,
                 _ => CallRet::Err,
             },
-            Err(_) => CallRet::Err,
+            Ok(Err(_)) => CallRet::Err,
+            Err(_) => {
+                log::warn!("Call from {:?} to {:?} timed out", self.slf, target);
+                self.inbound.remove_temporary_receiver(channel_id);
+                CallRet::Timeout
+            }
         }
     }
 
@@ -165,15 +255,69 @@ impl DataplaneHandle {
             match msg {
                 CallRet::Reply(msg) => Message::CallRet(msg),
                 CallRet::NoReply => Message::CallNoRet,
-                CallRet::Err => Message::Err,
+                CallRet::Err | CallRet::Timeout => Message::Err,
             },
             edgeless_api::function_instance::EventTimestamp::default(),
             channel_id,
             metadata,
+            Delivery::AtMostOnce,
         )
         .await;
     }
 
+    /// Pass a cast or call event received by this instance to another one,
+    /// e.g., the instance replacing it after a migration, on behalf of the
+    /// original sender, which receives the return event of a call directly.
+    /// The event is forwarded to the dead-letter function/resource, if any,
+    /// if the target is not found.
+    pub async fn redirect(&mut self, target: edgeless_api::function_instance::InstanceId, event: DataplaneEvent) {
+        let DataplaneEvent {
+            source_id,
+            channel_id,
+            message,
+            created,
+            metadata,
+        } = event;
+        if self
+            .forward_from(
+                &source_id,
+                &target,
+                message.clone(),
+                &created,
+                channel_id,
+                &metadata,
+                Delivery::AtMostOnce,
+            )
+            .await
+        {
+            return;
+        }
+        log::info!("Unprocessed redirected message: {:?}->{:?}", source_id, target);
+        if let Message::Cast(payload) | Message::Call(payload) = &message {
+            self.send_dead_letter("target not found", &source_id, &target, payload, &metadata).await;
+        }
+    }
+
+    // Select the actual target of a cast or call event on an output channel,
+    // which differs from the one given only if the events on the channel
+    // are split among multiple instances.
+    async fn split_target(
+        &mut self,
+        channel: &str,
+        target: edgeless_api::function_instance::InstanceId,
+    ) -> edgeless_api::function_instance::InstanceId {
+        match self
+            .output_splits
+            .lock()
+            .await
+            .get_mut(&self.slf.function_id)
+            .and_then(|splits| splits.get_mut(channel))
+        {
+            Some(split) => split.next_target(),
+            None => target,
+        }
+    }
+
     async fn send_inner(
         &mut self,
         target: edgeless_api::function_instance::InstanceId,
@@ -181,25 +325,146 @@ impl DataplaneHandle {
         created: edgeless_api::function_instance::EventTimestamp,
         channel_id: u64,
         metadata: &edgeless_api::function_instance::EventMetadata,
+        delivery: Delivery,
     ) {
+        // Only the call events carry the deadline, which is meaningless for
+        // the others, e.g., the casts generated while serving a call.
+        let without_deadline;
+        let metadata = match (&msg, metadata.deadline()) {
+            (Message::Call(_), _) | (_, None) => metadata,
+            _ => {
+                without_deadline = metadata.clone().with_deadline(None);
+                &without_deadline
+            }
+        };
+
+        if self.forward(&target, msg.clone(), &created, channel_id, metadata, delivery).await {
+            return;
+        }
+        log::info!("Unprocessed Message: {:?}->{:?}", self.slf, target);
+        if let Message::Cast(payload) | Message::Call(payload) = &msg {
+            let slf = self.slf;
+            self.send_dead_letter("target not found", &slf, &target, payload, metadata).await;
+        }
+    }
+
+    // Pass an event through the output chain, returning true if a link
+    // has processed it.
+    async fn forward(
+        &mut self,
+        target: &edgeless_api::function_instance::InstanceId,
+        msg: Message,
+        created: &edgeless_api::function_instance::EventTimestamp,
+        channel_id: u64,
+        metadata: &edgeless_api::function_instance::EventMetadata,
+        delivery: Delivery,
+    ) -> bool {
+        let slf = self.slf;
+        self.forward_from(&slf, target, msg, created, channel_id, metadata, delivery).await
+    }
+
+    // Pass an event through the output chain on behalf of a given source,
+    // returning true if a link has processed it.
+    #[allow(clippy::too_many_arguments)]
+    async fn forward_from(
+        &mut self,
+        source: &edgeless_api::function_instance::InstanceId,
+        target: &edgeless_api::function_instance::InstanceId,
+        msg: Message,
+        created: &edgeless_api::function_instance::EventTimestamp,
+        channel_id: u64,
+        metadata: &edgeless_api::function_instance::EventMetadata,
+        delivery: Delivery,
+    ) -> bool {
         let mut lck = self.output_chain.lock().await;
         for link in &mut lck.iter_mut() {
-            if link.handle_send(&target, msg.clone(), &self.slf, &created, channel_id, metadata).await == LinkProcessingResult::FINAL {
-                return;
+            if link
+                .handle_send(target, msg.clone(), source, created, channel_id, metadata, delivery)
+                .await
+                == LinkProcessingResult::FINAL
+            {
+                return true;
             }
         }
-        log::info!("Unprocessed Message: {:?}->{:?}", self.slf, target);
+        false
+    }
+
+    /// Forward an event that could not be delivered or processed, with
+    /// the reason why, to the dead-letter function/resource of this
+    /// instance, if any.
+    /// The dead letter is a JSON-serialized `DeadLetter` sent as a cast,
+    /// which is discarded if it cannot be delivered in turn.
+    pub async fn send_dead_letter(
+        &mut self,
+        reason: &str,
+        source: &edgeless_api::function_instance::InstanceId,
+        target: &edgeless_api::function_instance::InstanceId,
+        payload: &[u8],
+        metadata: &edgeless_api::function_instance::EventMetadata,
+    ) {
+        let component_id = self.slf.function_id;
+        self.send_dead_letter_of(&component_id, reason, source, target, payload, metadata).await;
     }
-}
 
-struct TemporaryReceivers {
-    temporary_receivers: std::collections::HashMap<u64, futures::channel::oneshot::Sender<(edgeless_api::function_instance::InstanceId, Message)>>,
+    // Forward an event to the dead-letter function/resource of a given
+    // component, which is the sender of the event unless it overflowed the
+    // inbound queue of the receiver.
+    async fn send_dead_letter_of(
+        &mut self,
+        component_id: &edgeless_api::function_instance::ComponentId,
+        reason: &str,
+        source: &edgeless_api::function_instance::InstanceId,
+        target: &edgeless_api::function_instance::InstanceId,
+        payload: &[u8],
+        metadata: &edgeless_api::function_instance::EventMetadata,
+    ) {
+        let dead_letter_target = match self.dead_letters.lock().await.get(component_id) {
+            Some(dead_letter_target) => *dead_letter_target,
+            None => return,
+        };
+        if *target == dead_letter_target {
+            log::warn!("Discarded dead letter from {:?} undeliverable to {:?}: {}", source, target, reason);
+            return;
+        }
+        let msg = match serde_json::to_vec(&crate::dead_letter::DeadLetter::new(reason, source, target, payload)) {
+            Ok(msg) => msg,
+            Err(err) => {
+                log::error!("Could not serialize a dead letter: {}", err);
+                return;
+            }
+        };
+        let dead_letter_target = self
+            .split_target(edgeless_api::workflow_instance::DEAD_LETTER_CHANNEL, dead_letter_target)
+            .await;
+        let metadata = metadata.clone().with_deadline(None);
+        if !self
+            .forward(
+                &dead_letter_target,
+                Message::Cast(msg),
+                &timestamp_utc(),
+                0,
+                &metadata,
+                Delivery::AtMostOnce,
+            )
+            .await
+        {
+            log::warn!("Discarded dead letter from {:?} to {:?}: target not found", source, dead_letter_target);
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct DataplaneProvider {
     local_provider: std::sync::Arc<tokio::sync::Mutex<NodeLocalLinkProvider>>,
     remote_provider: std::sync::Arc<tokio::sync::Mutex<RemoteLinkProvider>>,
+    output_splits: std::sync::Arc<tokio::sync::Mutex<OutputSplits>>,
+    at_least_once: std::sync::Arc<tokio::sync::Mutex<crate::at_least_once::AtLeastOnceOutputs>>,
+    dead_letters: std::sync::Arc<tokio::sync::Mutex<crate::dead_letter::DeadLetterTargets>>,
+    call_timeout: std::time::Duration,
+    inbound_queue: InboundQueueSettings,
+    diverter: Diverter,
+    transport: InvocationTransport,
+    max_batch_size: usize,
 }
 
 impl DataplaneProvider {
@@ -225,27 +490,246 @@ impl DataplaneProvider {
             ));
         }
 
-        Self {
+        let mut undelivered = remote_provider.lock().await.take_undelivered().expect("undelivered events not taken yet");
+
+        let (diverter, mut diverted) = futures::channel::mpsc::unbounded();
+        let mut provider = Self {
             local_provider: std::sync::Arc::new(tokio::sync::Mutex::new(NodeLocalLinkProvider::new())),
             remote_provider,
-        }
+            output_splits: std::sync::Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
+            at_least_once: std::sync::Arc::new(tokio::sync::Mutex::new(crate::at_least_once::AtLeastOnceOutputs::default())),
+            dead_letters: std::sync::Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
+            call_timeout: DEFAULT_CALL_TIMEOUT,
+            inbound_queue: InboundQueueSettings::default(),
+            diverter,
+            transport: InvocationTransport::default(),
+            max_batch_size: edgeless_api::grpc_impl::outer::invocation_stream::DEFAULT_MAX_BATCH_SIZE,
+        };
+
+        let mut forwarder = provider.get_handle_for(edgeless_api::function_instance::InstanceId::new(node_id)).await;
+        let mut undelivered_forwarder = forwarder.clone();
+
+        // This task forwards the events that overflow the inbound queues with
+        // the dead-letter policy, or that find no room within the maximum
+        // blocking time with the block-local policy, to the dead-letter
+        // functions/resources of their receivers, and replies with an error to
+        // the calls dropped because of any policy.
+        tokio::spawn(async move {
+            while let Some((receiver_id, event, overflow)) = diverted.next().await {
+                let (payload, is_call) = match event.message {
+                    Message::Cast(payload) => (payload, false),
+                    Message::Call(payload) => (payload, true),
+                    _ => continue,
+                };
+                if matches!(overflow, OverflowPolicy::DeadLetter | OverflowPolicy::BlockLocal) {
+                    forwarder
+                        .send_dead_letter_of(
+                            &receiver_id.function_id,
+                            "inbound queue full",
+                            &event.source_id,
+                            &receiver_id,
+                            &payload,
+                            &event.metadata,
+                        )
+                        .await;
+                }
+                if is_call {
+                    forwarder.reply(event.source_id, event.channel_id, CallRet::Err, &event.metadata).await;
+                }
+            }
+        });
+
+        // This task forwards the events that could not be delivered to the
+        // peers to the dead-letter functions/resources of their senders, and
+        // replies with an error to the calls.
+        tokio::spawn(async move {
+            while let Some((event, reason)) = undelivered.recv().await {
+                let (payload, is_call) = match event.data {
+                    edgeless_api::invocation::EventData::Cast(payload) => (payload, false),
+                    edgeless_api::invocation::EventData::Call(payload) => (payload, true),
+                    _ => continue,
+                };
+                undelivered_forwarder
+                    .send_dead_letter_of(
+                        &event.source.function_id,
+                        &reason,
+                        &event.source,
+                        &event.target,
+                        &payload,
+                        &event.metadata,
+                    )
+                    .await;
+                if is_call {
+                    undelivered_forwarder
+                        .reply(event.source, event.stream_id, CallRet::Err, &event.metadata)
+                        .await;
+                }
+            }
+        });
+
+        provider
+    }
+
+    /// Set the timeout of the calls made via the handles created afterwards,
+    /// if the event metadata do not contain an earlier deadline.
+    pub fn with_call_timeout(mut self, call_timeout: std::time::Duration) -> Self {
+        self.call_timeout = call_timeout;
+        self
+    }
+
+    /// Set the default settings of the inbound queues of the handles
+    /// created afterwards.
+    pub fn with_inbound_queue(mut self, inbound_queue: InboundQueueSettings) -> Self {
+        self.inbound_queue = inbound_queue;
+        self
+    }
+
+    /// Set the transport used to send the events to the peers added
+    /// afterwards, with the maximum number of events per batch, which only
+    /// applies to `InvocationTransport::Stream`.
+    /// CoAP peers are not affected.
+    pub fn with_invocation_transport(mut self, transport: InvocationTransport, max_batch_size: usize) -> Self {
+        self.transport = transport;
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
+    /// Return the default settings of the inbound queues.
+    pub fn inbound_queue_settings(&self) -> InboundQueueSettings {
+        self.inbound_queue
     }
 
     pub async fn get_handle_for(&mut self, target: edgeless_api::function_instance::InstanceId) -> DataplaneHandle {
-        let (sender, receiver) = futures::channel::mpsc::unbounded::<DataplaneEvent>();
+        let inbound_queue = self.inbound_queue;
+        self.get_handle_with_inbound_queue(target, inbound_queue).await
+    }
+
+    /// Create the handle of an instance whose inbound queue has the given
+    /// settings, e.g., as overridden by the annotations of a function.
+    pub async fn get_handle_with_inbound_queue(
+        &mut self,
+        target: edgeless_api::function_instance::InstanceId,
+        inbound_queue: InboundQueueSettings,
+    ) -> DataplaneHandle {
+        let (sender, receiver) = crate::inbound_queue::inbound_queue(target, inbound_queue, Some(self.diverter.clone()));
         let output_chain = vec![
             self.local_provider.lock().await.new_link(target, sender.clone()).await,
-            self.remote_provider.lock().await.new_link(target, sender.clone()).await,
+            self.remote_provider.lock().await.new_link(target, sender).await,
         ];
-        DataplaneHandle::new(target, output_chain, receiver).await
+        DataplaneHandle::new(
+            target,
+            output_chain,
+            receiver,
+            self.output_splits.clone(),
+            self.at_least_once.clone(),
+            self.dead_letters.clone(),
+            self.call_timeout,
+        )
+    }
+
+    /// Set the output splits of a function/resource instance, as given in
+    /// the patch request, replacing the previous ones.
+    pub async fn patch_output_splits(&mut self, update: &edgeless_api::common::PatchRequest) {
+        let splits = update
+            .output_splits
+            .iter()
+            .filter_map(
+                |(channel, targets)| match (update.output_mapping.get(channel), OutputSplit::new(targets)) {
+                    (Some(_), Some(split)) => Some((channel.clone(), split)),
+                    (None, _) => {
+                        log::warn!(
+                            "Ignoring split of output '{}' not found in the output mapping of {}",
+                            channel,
+                            update.function_id
+                        );
+                        None
+                    }
+                    _ => None,
+                },
+            )
+            .collect::<std::collections::HashMap<String, OutputSplit>>();
+        let mut output_splits = self.output_splits.lock().await;
+        if splits.is_empty() {
+            output_splits.remove(&update.function_id);
+        } else {
+            output_splits.insert(update.function_id, splits);
+        }
+    }
+
+    /// Remove the output splits of a function/resource instance.
+    pub async fn remove_output_splits(&mut self, component_id: &edgeless_api::function_instance::ComponentId) {
+        self.output_splits.lock().await.remove(component_id);
+    }
+
+    /// Remove the output splits of all the function/resource instances.
+    pub async fn clear_output_splits(&mut self) {
+        self.output_splits.lock().await.clear();
+    }
+
+    /// Set the output channels of a function instance whose casts to other
+    /// nodes are delivered at least once, as selected by its annotations.
+    pub async fn set_at_least_once_outputs(
+        &mut self,
+        component_id: edgeless_api::function_instance::ComponentId,
+        annotations: &std::collections::HashMap<String, String>,
+    ) {
+        self.at_least_once
+            .lock()
+            .await
+            .set_channels(component_id, crate::at_least_once::parse_annotation(annotations));
+    }
+
+    /// Remove the output channels delivered at least once of a function instance.
+    pub async fn remove_at_least_once_outputs(&mut self, component_id: &edgeless_api::function_instance::ComponentId) {
+        self.at_least_once.lock().await.remove(component_id);
+    }
+
+    /// Remove the output channels delivered at least once of all the
+    /// function instances.
+    pub async fn clear_at_least_once_outputs(&mut self) {
+        self.at_least_once.lock().await.clear();
+    }
+
+    /// Set the dead-letter target of a function/resource instance, as given
+    /// in the patch request, or remove it if the output mapping does not
+    /// contain the dead-letter channel.
+    pub async fn patch_dead_letter(&mut self, update: &edgeless_api::common::PatchRequest) {
+        let mut dead_letters = self.dead_letters.lock().await;
+        match crate::dead_letter::dead_letter_target(update) {
+            Some(dead_letter_target) => dead_letters.insert(update.function_id, dead_letter_target),
+            None => dead_letters.remove(&update.function_id),
+        };
+    }
+
+    /// Remove the dead-letter target of a function/resource instance.
+    pub async fn remove_dead_letter(&mut self, component_id: &edgeless_api::function_instance::ComponentId) {
+        self.dead_letters.lock().await.remove(component_id);
+    }
+
+    /// Remove the dead-letter targets of all the function/resource instances.
+    pub async fn clear_dead_letters(&mut self) {
+        self.dead_letters.lock().await.clear();
+    }
+
+    /// Set the telemetry handle used to report the failures to deliver the
+    /// events at least once.
+    pub async fn set_telemetry_handle(&mut self, telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>) {
+        self.remote_provider.lock().await.set_telemetry_handle(telemetry_handle).await;
     }
 
     pub async fn add_peer(&mut self, peer: EdgelessDataplanePeerSettings) {
         log::debug!("add_peer {:?}", peer);
+        let undelivered = self.remote_provider.lock().await.undelivered_sender().await;
+        let peer_api = Self::connect_peer(self.transport, self.max_batch_size, &peer, Some(undelivered)).await;
+        // The client dedicated to the events delivered at least once is only
+        // created if any such event is sent to the peer.
+        let (transport, max_batch_size, node_id) = (self.transport, self.max_batch_size, peer.node_id);
+        let at_least_once_connector: crate::at_least_once::PeerConnector =
+            Box::new(move || Box::pin(async move { Self::connect_peer(transport, max_batch_size, &peer, None).await }));
         self.remote_provider
             .lock()
             .await
-            .add_peer(peer.node_id, Self::connect_peer(&peer).await)
+            .add_peer(node_id, peer_api, at_least_once_connector)
             .await;
     }
 
@@ -254,13 +738,32 @@ impl DataplaneProvider {
         self.remote_provider.lock().await.del_peer(node_id).await;
     }
 
-    async fn connect_peer(target: &EdgelessDataplanePeerSettings) -> Box<dyn edgeless_api::invocation::InvocationAPI> {
+    // Connect to a peer. With the stream transport, the casts and calls
+    // rejected by the peer are forwarded to `undelivered`, if set.
+    async fn connect_peer(
+        transport: InvocationTransport,
+        max_batch_size: usize,
+        target: &EdgelessDataplanePeerSettings,
+        undelivered: Option<crate::remote_node::UndeliveredSender>,
+    ) -> Box<dyn edgeless_api::invocation::InvocationAPI> {
         let (proto, url, port) = edgeless_api::util::parse_http_host(&target.invocation_url).unwrap();
         match proto {
             edgeless_api::util::Proto::COAP => {
                 Box::new(edgeless_api::coap_impl::CoapClient::new(std::net::SocketAddrV4::new(url.parse().unwrap(), port)).await)
             }
-            _ => Box::new(edgeless_api::grpc_impl::outer::invocation::InvocationAPIClient::new(&target.invocation_url).await),
+            _ => match transport {
+                InvocationTransport::Unary => {
+                    Box::new(edgeless_api::grpc_impl::outer::invocation::InvocationAPIClient::new(&target.invocation_url).await)
+                }
+                InvocationTransport::Stream => Box::new(
+                    edgeless_api::grpc_impl::outer::invocation_stream::InvocationAPIStreamClient::new(
+                        &target.invocation_url,
+                        max_batch_size,
+                        undelivered,
+                    )
+                    .await,
+                ),
+            },
         }
     }
 }
@@ -286,7 +789,7 @@ mod test {
         let res = handle_2.receive_next().await;
         assert_eq!(
             std::mem::discriminant(&res.message),
-            std::mem::discriminant(&crate::core::Message::Cast("".to_string()))
+            std::mem::discriminant(&crate::core::Message::Cast(Vec::new()))
         );
 
         assert_eq!(
@@ -295,6 +798,26 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn local_binary_payload() {
+        let node_id = uuid::Uuid::new_v4();
+        let fid_1 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_2 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00022u128, 0x42a42bdecaf00023u64);
+
+        let mut provider = DataplaneProvider::new(node_id, "http://127.0.0.1:7095".to_string(), None).await;
+
+        let mut handle_1 = provider.get_handle_for(fid_1).await;
+        let mut handle_2 = provider.get_handle_for(fid_2).await;
+
+        // Not valid UTF-8: must be delivered unchanged.
+        let payload = vec![0x00_u8, 0xff, 0xfe, 0x80, 0x42];
+        handle_1.send(fid_2, payload.clone(), &metad_1).await;
+
+        let res = handle_2.receive_next().await;
+        assert_eq!(res.message, crate::core::Message::Cast(payload));
+    }
+
     #[tokio::test]
     async fn local_call_with_return() {
         let node_id = uuid::Uuid::new_v4();
@@ -315,12 +838,14 @@ mod test {
         let req = handle_2.receive_next().await;
         assert_eq!(
             std::mem::discriminant(&req.message),
-            std::mem::discriminant(&crate::core::Message::Call("".to_string()))
+            std::mem::discriminant(&crate::core::Message::Call(Vec::new()))
         );
         assert_eq!(
-            &req.metadata, &metad_1,
+            &req.metadata.clone().with_deadline(None),
+            &metad_1,
             "Handle 2 must receive the same metadata given by its parent through handle 1"
         );
+        assert!(req.metadata.deadline().is_some(), "Calls must carry their deadline");
 
         handle_2.reply(req.source_id, req.channel_id, CallRet::NoReply, &req.metadata).await;
 
@@ -328,9 +853,17 @@ mod test {
         assert_eq!(std::mem::discriminant(&CallRet::NoReply), std::mem::discriminant(&repl));
     }
 
+    static INIT: std::sync::Once = std::sync::Once::new();
+
+    fn init_crypto() {
+        INIT.call_once(|| {
+            edgeless_api::grpc_impl::init_crypto();
+        });
+    }
+
     #[tokio::test]
     async fn grpc_impl_e2e() {
-        edgeless_api::grpc_impl::init_crypto();
+        init_crypto();
         let node_id = uuid::Uuid::new_v4();
         let node_id_2 = uuid::Uuid::new_v4();
         let fid_1 = edgeless_api::function_instance::InstanceId::new(node_id);
@@ -376,7 +909,7 @@ mod test {
         let cast_req = handle_2.receive_next().await;
         assert_eq!(
             std::mem::discriminant(&cast_req.message),
-            std::mem::discriminant(&crate::core::Message::Cast("".to_string()))
+            std::mem::discriminant(&crate::core::Message::Cast(Vec::new()))
         );
         assert_eq!(&cast_req.metadata, &metad_1);
 
@@ -391,9 +924,10 @@ mod test {
         let call_req = handle_1.receive_next().await;
         assert_eq!(
             std::mem::discriminant(&call_req.message),
-            std::mem::discriminant(&crate::core::Message::Call("".to_string()))
+            std::mem::discriminant(&crate::core::Message::Call(Vec::new()))
         );
-        assert_eq!(&call_req.metadata, &metad_2);
+        assert_eq!(&call_req.metadata.clone().with_deadline(None), &metad_2);
+        assert!(call_req.metadata.deadline().is_some());
         handle_1
             .reply(call_req.source_id, call_req.channel_id, CallRet::NoReply, &call_req.metadata)
             .await;
@@ -401,4 +935,409 @@ mod test {
         let repl = return_handle.await.unwrap();
         assert_eq!(std::mem::discriminant(&CallRet::NoReply), std::mem::discriminant(&repl));
     }
+
+    #[tokio::test]
+    async fn grpc_stream_e2e() {
+        init_crypto();
+        let node_id = uuid::Uuid::new_v4();
+        let node_id_2 = uuid::Uuid::new_v4();
+        let fid_1 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_2 = edgeless_api::function_instance::InstanceId::new(node_id_2);
+        let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00030u128, 0x42a42bdecaf00031u64);
+
+        let provider1_f = tokio::spawn(async move {
+            let mut dataplane = DataplaneProvider::new(node_id, "http://127.0.0.1:7109".to_string(), None)
+                .await
+                .with_invocation_transport(InvocationTransport::Stream, 8);
+            dataplane
+                .add_peer(EdgelessDataplanePeerSettings {
+                    node_id: node_id_2,
+                    invocation_url: "http://127.0.0.1:7110".to_string(),
+                })
+                .await;
+            dataplane
+        });
+
+        let provider2_f = tokio::spawn(async move {
+            let mut dataplane = DataplaneProvider::new(node_id_2, "http://127.0.0.1:7110".to_string(), None)
+                .await
+                .with_invocation_transport(InvocationTransport::Stream, 8);
+            dataplane
+                .add_peer(EdgelessDataplanePeerSettings {
+                    node_id,
+                    invocation_url: "http://127.0.0.1:7109".to_string(),
+                })
+                .await;
+            dataplane
+        });
+
+        let (provider_1_r, provider_2_r) = futures::join!(
+            tokio::time::timeout(tokio::time::Duration::from_secs(5), provider1_f),
+            tokio::time::timeout(tokio::time::Duration::from_secs(5), provider2_f)
+        );
+        let mut provider_1 = provider_1_r.unwrap().unwrap();
+        let mut provider_2 = provider_2_r.unwrap().unwrap();
+
+        let mut handle_1 = provider_1.get_handle_for(fid_1).await;
+        let mut handle_2 = provider_2.get_handle_for(fid_2).await;
+
+        // The casts are received in order, even if batched.
+        for i in 0..100 {
+            handle_1.send(fid_2, format!("Test{}", i), &metad_1).await;
+        }
+        for i in 0..100 {
+            let cast_req = tokio::time::timeout(tokio::time::Duration::from_secs(5), handle_2.receive_next())
+                .await
+                .unwrap();
+            assert_eq!(crate::core::Message::Cast(format!("Test{}", i).into_bytes()), cast_req.message);
+            assert_eq!(&cast_req.metadata, &metad_1);
+        }
+
+        let mut cloned_handle_2 = handle_2.clone();
+        let return_handle = {
+            let cloned_metad_1 = metad_1.clone();
+            tokio::spawn(async move { cloned_handle_2.call(fid_1, "Test".to_string(), &cloned_metad_1).await })
+        };
+        let call_req = handle_1.receive_next().await;
+        handle_1
+            .reply(
+                call_req.source_id,
+                call_req.channel_id,
+                CallRet::Reply("Done".to_string().into_bytes()),
+                &call_req.metadata,
+            )
+            .await;
+        assert_eq!(CallRet::Reply("Done".to_string().into_bytes()), return_handle.await.unwrap());
+
+        // A call rejected by the peer, since the inbound queue of the target
+        // is full, is replied to with an error without waiting for the timeout.
+        let fid_3 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let _handle_3 = provider_1
+            .get_handle_with_inbound_queue(
+                fid_3,
+                InboundQueueSettings {
+                    capacity: 1,
+                    overflow: OverflowPolicy::BlockLocal,
+                },
+            )
+            .await;
+        handle_2.send(fid_3, "Fill".to_string(), &metad_1).await;
+        let res = tokio::time::timeout(tokio::time::Duration::from_secs(5), handle_2.call(fid_3, "Test".to_string(), &metad_1))
+            .await
+            .unwrap();
+        assert_eq!(CallRet::Err, res);
+    }
+
+    #[tokio::test]
+    async fn local_output_split() {
+        let node_id = uuid::Uuid::new_v4();
+        let fid_1 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_2 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_3 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00024u128, 0x42a42bdecaf00025u64);
+
+        let mut provider = DataplaneProvider::new(node_id, "http://127.0.0.1:7094".to_string(), None).await;
+
+        let mut handle_1 = provider.get_handle_for(fid_1).await;
+        let mut handle_2 = provider.get_handle_for(fid_2).await;
+        let mut handle_3 = provider.get_handle_for(fid_3).await;
+
+        // Split the events sent by 1 on "out" so that 1 out of 4 goes to 3,
+        // while those on "other", mapped to the same target, are not split.
+        provider
+            .patch_output_splits(&edgeless_api::common::PatchRequest {
+                function_id: fid_1.function_id,
+                output_mapping: std::collections::HashMap::from([("out".to_string(), fid_2), ("other".to_string(), fid_2)]),
+                output_splits: std::collections::HashMap::from([(
+                    "out".to_string(),
+                    vec![
+                        edgeless_api::common::WeightedTarget {
+                            instance_id: fid_2,
+                            weight: 3,
+                        },
+                        edgeless_api::common::WeightedTarget {
+                            instance_id: fid_3,
+                            weight: 1,
+                        },
+                    ],
+                )]),
+            })
+            .await;
+
+        for _ in 0..4 {
+            handle_1.send_output("out", fid_2, "Test".to_string(), &metad_1).await;
+        }
+        for _ in 0..3 {
+            assert_eq!(fid_1, handle_2.receive_next().await.source_id);
+        }
+        assert_eq!(fid_1, handle_3.receive_next().await.source_id);
+        for _ in 0..4 {
+            handle_1.send_output("other", fid_2, "Test".to_string(), &metad_1).await;
+            handle_1.send(fid_2, "Test".to_string(), &metad_1).await;
+        }
+        for _ in 0..8 {
+            assert_eq!(fid_1, handle_2.receive_next().await.source_id);
+        }
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), handle_3.receive_next())
+                .await
+                .is_err()
+        );
+
+        // Without splits all the events go to the target given.
+        provider.remove_output_splits(&fid_1.function_id).await;
+        handle_1.send_output("out", fid_2, "Test".to_string(), &metad_1).await;
+        assert_eq!(fid_1, handle_2.receive_next().await.source_id);
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), handle_3.receive_next())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn local_call_timeout() {
+        let node_id = uuid::Uuid::new_v4();
+        let fid_1 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_2 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00026u128, 0x42a42bdecaf00027u64);
+
+        let mut provider = DataplaneProvider::new(node_id, "http://127.0.0.1:7102".to_string(), None)
+            .await
+            .with_call_timeout(std::time::Duration::from_millis(100));
+
+        let mut handle_1 = provider.get_handle_for(fid_1).await;
+        let mut handle_2 = provider.get_handle_for(fid_2).await;
+
+        // Nobody replies to the call.
+        let repl = handle_1.call(fid_2, "Test".to_string(), &metad_1).await;
+        assert_eq!(CallRet::Timeout, repl);
+        assert_eq!(0, handle_1.inbound.num_temporary_receivers());
+
+        // The late call is discarded by the callee.
+        handle_1.send(fid_2, "Cast".to_string(), &metad_1).await;
+        let req = handle_2.receive_next().await;
+        assert_eq!(crate::core::Message::Cast("Cast".to_string().into_bytes()), req.message);
+
+        // A deadline in the metadata earlier than the timeout is honoured.
+        let expired = timestamp_from_utc(chrono::Utc::now() - chrono::TimeDelta::seconds(1));
+        let repl = handle_1
+            .call_with_timeout(
+                fid_2,
+                "Test".to_string(),
+                &metad_1.clone().with_deadline(Some(expired)),
+                std::time::Duration::from_secs(60),
+            )
+            .await;
+        assert_eq!(CallRet::Timeout, repl);
+    }
+
+    #[tokio::test]
+    async fn local_call_deadline_propagation() {
+        let node_id = uuid::Uuid::new_v4();
+        let fid_1 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_2 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_3 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00028u128, 0x42a42bdecaf00029u64);
+
+        let mut provider = DataplaneProvider::new(node_id, "http://127.0.0.1:7103".to_string(), None).await;
+
+        let mut handle_1 = provider.get_handle_for(fid_1).await;
+        let mut handle_2 = provider.get_handle_for(fid_2).await;
+        let mut handle_3 = provider.get_handle_for(fid_3).await;
+
+        let return_handle = tokio::spawn(async move {
+            handle_1
+                .call_with_timeout(fid_2, "Test".to_string(), &metad_1, std::time::Duration::from_secs(10))
+                .await
+        });
+
+        let req = handle_2.receive_next().await;
+        let deadline = req.metadata.deadline().unwrap();
+        assert!(remaining_time(&deadline) <= std::time::Duration::from_secs(10));
+
+        // Nested calls inherit the deadline, even with a longer timeout.
+        let nested_handle = {
+            let mut handle_2 = handle_2.clone();
+            let metadata = req.metadata.clone();
+            tokio::spawn(async move {
+                handle_2
+                    .call_with_timeout(fid_3, "Nested".to_string(), &metadata, std::time::Duration::from_secs(60))
+                    .await
+            })
+        };
+        let nested_req = handle_3.receive_next().await;
+        assert_eq!(Some(deadline), nested_req.metadata.deadline());
+
+        // Casts do not carry the deadline.
+        handle_3.send(fid_2, "Cast".to_string(), &nested_req.metadata).await;
+        let cast_req = handle_2.receive_next().await;
+        assert_eq!(None, cast_req.metadata.deadline());
+
+        handle_3
+            .reply(nested_req.source_id, nested_req.channel_id, CallRet::NoReply, &nested_req.metadata)
+            .await;
+        assert_eq!(CallRet::NoReply, nested_handle.await.unwrap());
+        handle_2
+            .reply(
+                req.source_id,
+                req.channel_id,
+                CallRet::Reply("Done".to_string().into_bytes()),
+                &req.metadata,
+            )
+            .await;
+        assert_eq!(CallRet::Reply("Done".to_string().into_bytes()), return_handle.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn local_dead_letter() {
+        let node_id = uuid::Uuid::new_v4();
+        let fid_1 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_dlq = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_gone = edgeless_api::function_instance::InstanceId::new(node_id);
+        let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf0002au128, 0x42a42bdecaf0002bu64);
+
+        let mut provider = DataplaneProvider::new(node_id, "http://127.0.0.1:7107".to_string(), None).await;
+
+        let mut handle_1 = provider.get_handle_for(fid_1).await;
+        let mut handle_dlq = provider.get_handle_for(fid_dlq).await;
+
+        provider
+            .patch_dead_letter(&edgeless_api::common::PatchRequest {
+                function_id: fid_1.function_id,
+                output_mapping: std::collections::HashMap::from([
+                    ("out".to_string(), fid_gone),
+                    (edgeless_api::workflow_instance::DEAD_LETTER_CHANNEL.to_string(), fid_dlq),
+                ]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await;
+
+        // The cast to a target that does not exist is forwarded to the
+        // dead-letter target.
+        handle_1.send(fid_gone, vec![0_u8, 1, 2], &metad_1).await;
+        let dead_letter_event = handle_dlq.receive_next().await;
+        assert_eq!(fid_1, dead_letter_event.source_id);
+        assert_eq!(metad_1, dead_letter_event.metadata);
+        let dead_letter = match dead_letter_event.message {
+            crate::core::Message::Cast(payload) => serde_json::from_slice::<crate::dead_letter::DeadLetter>(&payload).unwrap(),
+            _ => panic!("wrong message type"),
+        };
+        assert_eq!("target not found", dead_letter.reason);
+        assert_eq!(crate::dead_letter::DeadLetterInstance::from(&fid_1), dead_letter.source);
+        assert_eq!(crate::dead_letter::DeadLetterInstance::from(&fid_gone), dead_letter.target);
+        assert_eq!(vec![0_u8, 1, 2], dead_letter.payload().unwrap());
+
+        // Without a dead-letter target the event is dropped.
+        provider.remove_dead_letter(&fid_1.function_id).await;
+        handle_1.send(fid_gone, "Test".to_string(), &metad_1).await;
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), handle_dlq.receive_next())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn local_inbound_queue_overflow() {
+        let node_id = uuid::Uuid::new_v4();
+        let fid_1 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_2 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_dlq = edgeless_api::function_instance::InstanceId::new(node_id);
+        let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf0002eu128, 0x42a42bdecaf0002fu64);
+
+        let mut provider = DataplaneProvider::new(node_id, "http://127.0.0.1:7108".to_string(), None).await;
+
+        let mut handle_1 = provider.get_handle_for(fid_1).await;
+        let mut handle_2 = provider
+            .get_handle_with_inbound_queue(
+                fid_2,
+                InboundQueueSettings {
+                    capacity: 1,
+                    overflow: OverflowPolicy::DeadLetter,
+                },
+            )
+            .await;
+        let mut handle_dlq = provider.get_handle_for(fid_dlq).await;
+
+        provider
+            .patch_dead_letter(&edgeless_api::common::PatchRequest {
+                function_id: fid_2.function_id,
+                output_mapping: std::collections::HashMap::from([(edgeless_api::workflow_instance::DEAD_LETTER_CHANNEL.to_string(), fid_dlq)]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await;
+
+        // The cast that overflows the inbound queue of the receiver is
+        // forwarded to the dead-letter target of the latter.
+        handle_1.send(fid_2, "1".to_string(), &metad_1).await;
+        handle_1.send(fid_2, "2".to_string(), &metad_1).await;
+        assert_eq!(1, handle_2.queue_depth());
+        let dead_letter = match handle_dlq.receive_next().await.message {
+            crate::core::Message::Cast(payload) => serde_json::from_slice::<crate::dead_letter::DeadLetter>(&payload).unwrap(),
+            _ => panic!("wrong message type"),
+        };
+        assert_eq!("inbound queue full", dead_letter.reason);
+        assert_eq!(crate::dead_letter::DeadLetterInstance::from(&fid_1), dead_letter.source);
+        assert_eq!(crate::dead_letter::DeadLetterInstance::from(&fid_2), dead_letter.target);
+        assert_eq!("2".as_bytes().to_vec(), dead_letter.payload().unwrap());
+
+        // The call that overflows the inbound queue fails immediately.
+        assert_eq!(CallRet::Err, handle_1.call(fid_2, "3".to_string(), &metad_1).await);
+        let dead_letter = match handle_dlq.receive_next().await.message {
+            crate::core::Message::Cast(payload) => serde_json::from_slice::<crate::dead_letter::DeadLetter>(&payload).unwrap(),
+            _ => panic!("wrong message type"),
+        };
+        assert_eq!("3".as_bytes().to_vec(), dead_letter.payload().unwrap());
+
+        assert_eq!(crate::core::Message::Cast("1".as_bytes().to_vec()), handle_2.receive_next().await.message);
+        assert_eq!(0, handle_2.queue_depth());
+
+        // The call dropped because the inbound queue is full fails
+        // immediately with the other policies, too, without dead letters.
+        let fid_3 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let mut handle_3 = provider
+            .get_handle_with_inbound_queue(
+                fid_3,
+                InboundQueueSettings {
+                    capacity: 1,
+                    overflow: OverflowPolicy::DropNewest,
+                },
+            )
+            .await;
+        handle_1.send(fid_3, "4".to_string(), &metad_1).await;
+        assert_eq!(CallRet::Err, handle_1.call(fid_3, "5".to_string(), &metad_1).await);
+        assert_eq!(crate::core::Message::Cast("4".as_bytes().to_vec()), handle_3.receive_next().await.message);
+        assert_eq!(0, handle_dlq.queue_depth());
+
+        // The cast that finds no room within the maximum blocking time is
+        // forwarded to the dead-letter target with the block-local policy.
+        let fid_4 = edgeless_api::function_instance::InstanceId::new(node_id);
+        let mut handle_4 = provider
+            .get_handle_with_inbound_queue(
+                fid_4,
+                InboundQueueSettings {
+                    capacity: 1,
+                    overflow: OverflowPolicy::BlockLocal,
+                },
+            )
+            .await;
+        provider
+            .patch_dead_letter(&edgeless_api::common::PatchRequest {
+                function_id: fid_4.function_id,
+                output_mapping: std::collections::HashMap::from([(edgeless_api::workflow_instance::DEAD_LETTER_CHANNEL.to_string(), fid_dlq)]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await;
+        handle_1.send(fid_4, "6".to_string(), &metad_1).await;
+        handle_1.send(fid_4, "7".to_string(), &metad_1).await;
+        let dead_letter = match handle_dlq.receive_next().await.message {
+            crate::core::Message::Cast(payload) => serde_json::from_slice::<crate::dead_letter::DeadLetter>(&payload).unwrap(),
+            _ => panic!("wrong message type"),
+        };
+        assert_eq!("inbound queue full", dead_letter.reason);
+        assert_eq!(crate::dead_letter::DeadLetterInstance::from(&fid_4), dead_letter.target);
+        assert_eq!("7".as_bytes().to_vec(), dead_letter.payload().unwrap());
+        assert_eq!(crate::core::Message::Cast("6".as_bytes().to_vec()), handle_4.receive_next().await.message);
+    }
 }
@@ -0,0 +1,570 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+use crate::core::*;
+
+/// Name of the function annotation with the capacity of its inbound queue,
+/// which overrides the one in the node configuration.
+pub const CAPACITY_ANNOTATION: &str = "inbound_queue_capacity";
+
+/// Name of the function annotation with the overflow policy of its inbound
+/// queue, which overrides the one in the node configuration.
+pub const OVERFLOW_ANNOTATION: &str = "inbound_queue_overflow";
+
+/// Default capacity of the inbound queues configured in the nodes.
+pub const DEFAULT_CAPACITY: usize = 10000;
+
+/// Maximum time a local sender waits for room in a full inbound queue with
+/// the `BlockLocal` policy, after which the event is diverted to the
+/// dead-letter function/resource of the receiver, if any.
+pub const MAX_BLOCKING_TIME: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// What to do with a cast or call event received when the inbound queue of
+/// the target function/resource instance is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Wait until there is room in the queue, which slows down the sender,
+    /// for at most `MAX_BLOCKING_TIME`, then forward the new event to the
+    /// dead-letter function/resource of the target, if any, otherwise drop it.
+    /// Only the senders on the same node are blocked: the events sent by
+    /// the instance to itself are forwarded without waiting, and those
+    /// received from other nodes are rejected, so that the ones delivered
+    /// at least once are retransmitted later.
+    #[default]
+    BlockLocal,
+    /// Drop the oldest event in the queue to make room for the new one.
+    DropOldest,
+    /// Drop the new event.
+    DropNewest,
+    /// Forward the new event to the dead-letter function/resource of the
+    /// target, if any, otherwise drop it.
+    DeadLetter,
+}
+
+impl std::str::FromStr for OverflowPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "block-local" => Ok(Self::BlockLocal),
+            "drop-oldest" => Ok(Self::DropOldest),
+            "drop-newest" => Ok(Self::DropNewest),
+            "dead-letter" => Ok(Self::DeadLetter),
+            _ => anyhow::bail!("invalid inbound queue overflow policy: {}", s),
+        }
+    }
+}
+
+/// Configuration of the inbound queue of a function/resource instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InboundQueueSettings {
+    /// Maximum number of cast and call events queued, unbounded if 0.
+    pub capacity: usize,
+    /// What to do with the events received when the queue is full.
+    pub overflow: OverflowPolicy,
+}
+
+impl InboundQueueSettings {
+    /// Return the settings overridden by the annotations of a function, if
+    /// valid.
+    pub fn with_annotations(self, annotations: &std::collections::HashMap<String, String>) -> Self {
+        let mut settings = self;
+        if let Some(capacity) = annotations.get(CAPACITY_ANNOTATION) {
+            match capacity.parse::<usize>() {
+                Ok(capacity) => settings.capacity = capacity,
+                Err(err) => log::warn!("Ignoring invalid {} annotation '{}': {}", CAPACITY_ANNOTATION, capacity, err),
+            }
+        }
+        if let Some(overflow) = annotations.get(OVERFLOW_ANNOTATION) {
+            match overflow.parse::<OverflowPolicy>() {
+                Ok(overflow) => settings.overflow = overflow,
+                Err(err) => log::warn!("Ignoring invalid {} annotation: {}", OVERFLOW_ANNOTATION, err),
+            }
+        }
+        settings
+    }
+}
+
+/// Channel through which the events that overflow the inbound queues with
+/// the `DeadLetter` and `BlockLocal` policies, and the calls dropped with the
+/// other policies, are diverted, together with the identifier of the instance that should
+/// have received them and the overflow policy of its queue.
+pub type Diverter = futures::channel::mpsc::UnboundedSender<(edgeless_api::function_instance::InstanceId, DataplaneEvent, OverflowPolicy)>;
+
+/// Sender of the return event of a pending call.
+pub type TemporaryReceiver = futures::channel::oneshot::Sender<(edgeless_api::function_instance::InstanceId, Message)>;
+
+struct QueueState {
+    events: std::collections::VecDeque<DataplaneEvent>,
+    closed: bool,
+}
+
+struct Shared {
+    receiver_id: edgeless_api::function_instance::InstanceId,
+    settings: InboundQueueSettings,
+    diverter: Option<Diverter>,
+    state: std::sync::Mutex<QueueState>,
+    // Senders of the return events of the calls pending, by channel.
+    temporary_receivers: std::sync::Mutex<std::collections::HashMap<u64, TemporaryReceiver>>,
+    readable: tokio::sync::Notify,
+    writable: tokio::sync::Notify,
+}
+
+/// Create the inbound queue of a function/resource instance.
+///
+/// The capacity only applies to cast and call events, while the return
+/// events are passed immediately to the calls waiting for them, if any,
+/// and dropped otherwise.
+pub fn inbound_queue(
+    receiver_id: edgeless_api::function_instance::InstanceId,
+    settings: InboundQueueSettings,
+    diverter: Option<Diverter>,
+) -> (InboundSender, InboundReceiver) {
+    let shared = std::sync::Arc::new(Shared {
+        receiver_id,
+        settings,
+        diverter,
+        state: std::sync::Mutex::new(QueueState {
+            events: std::collections::VecDeque::new(),
+            closed: false,
+        }),
+        temporary_receivers: std::sync::Mutex::new(std::collections::HashMap::new()),
+        readable: tokio::sync::Notify::new(),
+        writable: tokio::sync::Notify::new(),
+    });
+    (InboundSender { shared: shared.clone() }, InboundReceiver { shared })
+}
+
+/// Error returned by `InboundSender::try_send`, with the event not enqueued.
+#[derive(Debug)]
+pub enum TrySendError {
+    /// The queue is full and its overflow policy is `BlockLocal`.
+    Full(DataplaneEvent),
+    /// The receiving half has been dropped.
+    Closed(DataplaneEvent),
+}
+
+/// Sending half of an inbound queue, held by the dataplane links.
+#[derive(Clone)]
+pub struct InboundSender {
+    shared: std::sync::Arc<Shared>,
+}
+
+impl InboundSender {
+    /// Enqueue an event, according to the overflow policy if the queue is
+    /// full, or pass it to the call waiting for it, if it is a return event.
+    ///
+    /// With the `BlockLocal` policy, the event is diverted if there is no
+    /// room after `MAX_BLOCKING_TIME`, or immediately if sent by the
+    /// receiving instance itself, which would otherwise wait forever, as
+    /// would the instances sending events to one another.
+    ///
+    /// Return the event if the receiving half has been dropped.
+    pub async fn send(&self, mut event: DataplaneEvent) -> Result<(), DataplaneEvent> {
+        let deadline = tokio::time::Instant::now() + MAX_BLOCKING_TIME;
+        loop {
+            // Created before checking the queue so that a dequeue in between
+            // is not missed.
+            let writable = self.shared.writable.notified();
+            match self.try_send(event) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Full(full)) => event = full,
+                Err(TrySendError::Closed(closed)) => return Err(closed),
+            }
+            if event.source_id == self.shared.receiver_id || tokio::time::timeout_at(deadline, writable).await.is_err() {
+                log::warn!(
+                    "Inbound queue of {:?} full, diverted an event from {:?} without room",
+                    self.shared.receiver_id,
+                    event.source_id
+                );
+                self.divert(event);
+                return Ok(());
+            }
+        }
+    }
+
+    /// Enqueue an event like `send`, but without waiting for room in the
+    /// queue with the `BlockLocal` overflow policy.
+    #[allow(clippy::result_large_err)]
+    pub fn try_send(&self, event: DataplaneEvent) -> Result<(), TrySendError> {
+        if !matches!(event.message, Message::Cast(_) | Message::Call(_)) {
+            return self.send_return(event).map_err(TrySendError::Closed);
+        }
+
+        let mut state = self.shared.state.lock().unwrap();
+        if state.closed {
+            return Err(TrySendError::Closed(event));
+        }
+        let capacity = self.shared.settings.capacity;
+        if capacity == 0 || state.events.len() < capacity {
+            state.events.push_back(event);
+            drop(state);
+            self.shared.readable.notify_one();
+            return Ok(());
+        }
+        match self.shared.settings.overflow {
+            OverflowPolicy::BlockLocal => Err(TrySendError::Full(event)),
+            OverflowPolicy::DropOldest => {
+                let dropped = state.events.pop_front();
+                state.events.push_back(event);
+                drop(state);
+                log::warn!("Inbound queue of {:?} full, dropped the oldest event", self.shared.receiver_id);
+                self.shared.readable.notify_one();
+                if let Some(dropped) = dropped {
+                    self.drop_event(dropped);
+                }
+                Ok(())
+            }
+            OverflowPolicy::DropNewest => {
+                drop(state);
+                log::warn!(
+                    "Inbound queue of {:?} full, dropped an event from {:?}",
+                    self.shared.receiver_id,
+                    event.source_id
+                );
+                self.drop_event(event);
+                Ok(())
+            }
+            OverflowPolicy::DeadLetter => {
+                drop(state);
+                self.divert(event);
+                Ok(())
+            }
+        }
+    }
+
+    /// Return true if the receiving half has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().unwrap().closed
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn send_return(&self, event: DataplaneEvent) -> Result<(), DataplaneEvent> {
+        if self.is_closed() {
+            return Err(event);
+        }
+        match self.shared.temporary_receivers.lock().unwrap().remove(&event.channel_id) {
+            Some(sender) => {
+                if sender.send((event.source_id, event.message)).is_err() {
+                    log::debug!("Return event for an abandoned call of {:?}", self.shared.receiver_id);
+                }
+            }
+            None => log::error!("Unprocessed return event from {:?} to {:?}", event.source_id, self.shared.receiver_id),
+        }
+        Ok(())
+    }
+
+    // Divert a call dropped, so that the caller is replied to with an error
+    // instead of waiting until the timeout.
+    fn drop_event(&self, event: DataplaneEvent) {
+        if matches!(event.message, Message::Call(_)) {
+            self.divert(event);
+        }
+    }
+
+    fn divert(&self, event: DataplaneEvent) {
+        let diverted = match &self.shared.diverter {
+            Some(diverter) => diverter
+                .unbounded_send((self.shared.receiver_id, event, self.shared.settings.overflow))
+                .is_ok(),
+            None => false,
+        };
+        if !diverted {
+            log::warn!(
+                "Inbound queue of {:?} full, dropped an event that could not be diverted",
+                self.shared.receiver_id
+            );
+        }
+    }
+}
+
+/// Receiving half of an inbound queue, held by the dataplane handle.
+/// The queue is closed when this is dropped.
+pub struct InboundReceiver {
+    shared: std::sync::Arc<Shared>,
+}
+
+impl InboundReceiver {
+    /// Wait for the next cast or call event.
+    pub async fn recv(&self) -> DataplaneEvent {
+        loop {
+            let readable = self.shared.readable.notified();
+            if let Some(event) = self.shared.state.lock().unwrap().events.pop_front() {
+                self.shared.writable.notify_one();
+                return event;
+            }
+            readable.await;
+        }
+    }
+
+    /// Return the next cast or call event, if any, without waiting.
+    pub fn try_recv(&self) -> Option<DataplaneEvent> {
+        let event = self.shared.state.lock().unwrap().events.pop_front()?;
+        self.shared.writable.notify_one();
+        Some(event)
+    }
+
+    /// Return the number of cast and call events in the queue.
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().unwrap().events.len()
+    }
+
+    /// Return true if there are no cast or call events in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Pass the return event received on a given channel to `sender`.
+    pub fn add_temporary_receiver(&self, channel_id: u64, sender: TemporaryReceiver) {
+        let mut temporary_receivers = self.shared.temporary_receivers.lock().unwrap();
+        // Remove the receivers of the calls abandoned by the callers, e.g.,
+        // because their futures have been dropped.
+        temporary_receivers.retain(|_, sender| !sender.is_canceled());
+        temporary_receivers.insert(channel_id, sender);
+    }
+
+    /// Stop waiting for the return event on a given channel.
+    pub fn remove_temporary_receiver(&self, channel_id: u64) {
+        self.shared.temporary_receivers.lock().unwrap().remove(&channel_id);
+    }
+
+    /// Return the number of calls waiting for a return event.
+    pub fn num_temporary_receivers(&self) -> usize {
+        self.shared.temporary_receivers.lock().unwrap().len()
+    }
+}
+
+impl Drop for InboundReceiver {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        // Wake up the senders blocked on a full queue.
+        self.shared.writable.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cast(source_id: edgeless_api::function_instance::InstanceId, payload: &str) -> DataplaneEvent {
+        DataplaneEvent {
+            source_id,
+            channel_id: 0,
+            message: Message::Cast(payload.as_bytes().to_vec()),
+            created: edgeless_api::function_instance::EventTimestamp::default(),
+            metadata: edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf0002cu128, 0x42a42bdecaf0002du64),
+        }
+    }
+
+    fn call(source_id: edgeless_api::function_instance::InstanceId, payload: &str) -> DataplaneEvent {
+        DataplaneEvent {
+            message: Message::Call(payload.as_bytes().to_vec()),
+            ..cast(source_id, payload)
+        }
+    }
+
+    fn payload(event: DataplaneEvent) -> String {
+        match event.message {
+            Message::Cast(payload) | Message::Call(payload) => String::from_utf8(payload).unwrap(),
+            _ => panic!("wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_overflow_policy_from_str() {
+        assert_eq!(OverflowPolicy::BlockLocal, "block-local".parse::<OverflowPolicy>().unwrap());
+        assert_eq!(OverflowPolicy::DropOldest, "drop-oldest".parse::<OverflowPolicy>().unwrap());
+        assert_eq!(OverflowPolicy::DropNewest, "drop-newest".parse::<OverflowPolicy>().unwrap());
+        assert_eq!(OverflowPolicy::DeadLetter, "dead-letter".parse::<OverflowPolicy>().unwrap());
+        assert!("drop".parse::<OverflowPolicy>().is_err());
+
+        let settings = InboundQueueSettings {
+            capacity: 10,
+            overflow: OverflowPolicy::BlockLocal,
+        };
+        assert_eq!(settings, settings.with_annotations(&std::collections::HashMap::new()));
+        assert_eq!(
+            InboundQueueSettings {
+                capacity: 5,
+                overflow: OverflowPolicy::DropOldest
+            },
+            settings.with_annotations(&std::collections::HashMap::from([
+                (CAPACITY_ANNOTATION.to_string(), "5".to_string()),
+                (OVERFLOW_ANNOTATION.to_string(), "drop-oldest".to_string()),
+            ]))
+        );
+        assert_eq!(
+            settings,
+            settings.with_annotations(&std::collections::HashMap::from([
+                (CAPACITY_ANNOTATION.to_string(), "-1".to_string()),
+                (OVERFLOW_ANNOTATION.to_string(), "invalid".to_string()),
+            ]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inbound_queue_block() {
+        let source_id = edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4());
+        let settings = InboundQueueSettings {
+            capacity: 2,
+            overflow: OverflowPolicy::BlockLocal,
+        };
+        let (sender, receiver) = inbound_queue(edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4()), settings, None);
+
+        sender.send(cast(source_id, "1")).await.unwrap();
+        sender.send(cast(source_id, "2")).await.unwrap();
+        assert_eq!(2, receiver.len());
+
+        // The sender waits until there is room in the queue.
+        let blocked_sender = sender.clone();
+        let mut blocked = tokio::spawn(async move { blocked_sender.send(cast(source_id, "3")).await });
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(100), &mut blocked).await.is_err());
+        assert_eq!("1", payload(receiver.recv().await));
+        assert!(blocked.await.unwrap().is_ok());
+        assert_eq!("2", payload(receiver.recv().await));
+        assert_eq!("3", payload(receiver.recv().await));
+        assert!(receiver.is_empty());
+
+        // The events are rejected without waiting, if requested.
+        sender.try_send(cast(source_id, "4")).unwrap();
+        sender.try_send(cast(source_id, "5")).unwrap();
+        match sender.try_send(cast(source_id, "6")) {
+            Err(TrySendError::Full(event)) => assert_eq!("6", payload(event)),
+            _ => panic!("event not rejected"),
+        }
+        assert_eq!("4", payload(receiver.recv().await));
+        assert_eq!("5", payload(receiver.recv().await));
+
+        // The sender gives up after the maximum blocking time, and the event
+        // is diverted, so that it reaches the dead-letter target, if any,
+        // and the caller is replied to.
+        let receiver_id = edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4());
+        let (diverter, mut diverted) = futures::channel::mpsc::unbounded();
+        let (timed_sender, timed_receiver) = inbound_queue(receiver_id, settings, Some(diverter));
+        timed_sender.send(cast(source_id, "1")).await.unwrap();
+        timed_sender.send(cast(source_id, "2")).await.unwrap();
+        let start = std::time::Instant::now();
+        timed_sender.send(call(source_id, "3")).await.unwrap();
+        assert!(start.elapsed() >= MAX_BLOCKING_TIME);
+        assert_eq!(2, timed_receiver.len());
+        let (_, diverted_event, overflow) = diverted.try_next().unwrap().unwrap();
+        assert_eq!("3", payload(diverted_event));
+        assert_eq!(OverflowPolicy::BlockLocal, overflow);
+
+        // The events sent by the receiving instance to itself are diverted
+        // without waiting.
+        let start = std::time::Instant::now();
+        timed_sender.send(cast(receiver_id, "4")).await.unwrap();
+        assert!(start.elapsed() < MAX_BLOCKING_TIME);
+        let (_, diverted_event, _) = diverted.try_next().unwrap().unwrap();
+        assert_eq!("4", payload(diverted_event));
+        assert_eq!("1", payload(timed_receiver.recv().await));
+        assert_eq!("2", payload(timed_receiver.recv().await));
+        assert!(timed_receiver.is_empty());
+
+        // The blocked senders are released when the queue is closed.
+        sender.send(cast(source_id, "4")).await.unwrap();
+        sender.send(cast(source_id, "5")).await.unwrap();
+        let blocked_sender = sender.clone();
+        let blocked = tokio::spawn(async move { blocked_sender.send(cast(source_id, "6")).await });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        drop(receiver);
+        assert!(blocked.await.unwrap().is_err());
+        assert!(sender.is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_inbound_queue_drop() {
+        let source_id = edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4());
+        let receiver_id = edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4());
+
+        let settings = InboundQueueSettings {
+            capacity: 2,
+            overflow: OverflowPolicy::DropOldest,
+        };
+        let (sender, receiver) = inbound_queue(receiver_id, settings, None);
+        for payload in ["1", "2", "3"] {
+            sender.send(cast(source_id, payload)).await.unwrap();
+        }
+        assert_eq!(2, receiver.len());
+        assert_eq!("2", payload(receiver.recv().await));
+        assert_eq!("3", payload(receiver.recv().await));
+
+        // The calls dropped are diverted, so that the callers are replied to,
+        // while the casts are not.
+        let (diverter, mut diverted) = futures::channel::mpsc::unbounded();
+        let (sender, receiver) = inbound_queue(receiver_id, settings, Some(diverter));
+        sender.send(call(source_id, "1")).await.unwrap();
+        for payload in ["2", "3", "4"] {
+            sender.send(cast(source_id, payload)).await.unwrap();
+        }
+        assert_eq!(2, receiver.len());
+        let (diverted_receiver_id, diverted_event, overflow) = diverted.try_next().unwrap().unwrap();
+        assert_eq!(receiver_id, diverted_receiver_id);
+        assert_eq!(Message::Call("1".as_bytes().to_vec()), diverted_event.message);
+        assert_eq!(OverflowPolicy::DropOldest, overflow);
+        assert!(diverted.try_next().is_err());
+
+        let settings = InboundQueueSettings {
+            capacity: 2,
+            overflow: OverflowPolicy::DropNewest,
+        };
+        let (diverter, mut diverted) = futures::channel::mpsc::unbounded();
+        let (sender, receiver) = inbound_queue(receiver_id, settings, Some(diverter));
+        for payload in ["1", "2", "3"] {
+            sender.send(cast(source_id, payload)).await.unwrap();
+        }
+        sender.send(call(source_id, "4")).await.unwrap();
+        assert_eq!(2, receiver.len());
+        assert_eq!("1", payload(receiver.recv().await));
+        assert_eq!("2", payload(receiver.recv().await));
+        let (_, diverted_event, overflow) = diverted.try_next().unwrap().unwrap();
+        assert_eq!("4", payload(diverted_event));
+        assert_eq!(OverflowPolicy::DropNewest, overflow);
+        assert!(diverted.try_next().is_err());
+
+        let (diverter, mut diverted) = futures::channel::mpsc::unbounded();
+        let settings = InboundQueueSettings {
+            capacity: 2,
+            overflow: OverflowPolicy::DeadLetter,
+        };
+        let (sender, receiver) = inbound_queue(receiver_id, settings, Some(diverter));
+        for payload in ["1", "2", "3"] {
+            sender.send(cast(source_id, payload)).await.unwrap();
+        }
+        assert_eq!(2, receiver.len());
+        let (diverted_receiver_id, diverted_event, overflow) = diverted.try_next().unwrap().unwrap();
+        assert_eq!(receiver_id, diverted_receiver_id);
+        assert_eq!("3", payload(diverted_event));
+        assert_eq!(OverflowPolicy::DeadLetter, overflow);
+    }
+
+    #[tokio::test]
+    async fn test_inbound_queue_return_events() {
+        let source_id = edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4());
+        let settings = InboundQueueSettings {
+            capacity: 1,
+            overflow: OverflowPolicy::BlockLocal,
+        };
+        let (sender, receiver) = inbound_queue(edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4()), settings, None);
+
+        // The return events are not subject to the capacity of the queue.
+        sender.send(cast(source_id, "1")).await.unwrap();
+        let (return_sender, return_receiver) = futures::channel::oneshot::channel();
+        receiver.add_temporary_receiver(42, return_sender);
+        assert_eq!(1, receiver.num_temporary_receivers());
+        sender
+            .send(DataplaneEvent {
+                source_id,
+                channel_id: 42,
+                message: Message::CallRet("ret".as_bytes().to_vec()),
+                created: edgeless_api::function_instance::EventTimestamp::default(),
+                metadata: edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf0002cu128, 0x42a42bdecaf0002du64),
+            })
+            .await
+            .unwrap();
+        assert_eq!((source_id, Message::CallRet("ret".as_bytes().to_vec())), return_receiver.await.unwrap());
+        assert_eq!(0, receiver.num_temporary_receivers());
+        assert_eq!(1, receiver.len());
+    }
+}
@@ -1,7 +1,11 @@
 // SPDX-FileCopyrightText: © 2023 Technical University of Munich, Chair of Connected Mobility
 // SPDX-FileCopyrightText: © 2023 Siemens AG
 // SPDX-License-Identifier: MIT
+pub mod at_least_once;
 pub mod core;
+pub mod dead_letter;
 pub mod handle;
+pub mod inbound_queue;
 pub mod node_local;
+pub mod output_split;
 pub mod remote_node;
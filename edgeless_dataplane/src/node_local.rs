@@ -3,8 +3,7 @@
 // SPDX-FileCopyrightText: © 2023 Siemens AG
 // SPDX-License-Identifier: MIT
 use crate::core::*;
-use edgeless_api::invocation::InvocationAPI;
-use futures::SinkExt;
+use crate::inbound_queue::InboundSender;
 
 // Link representing a component on the local node.
 // Internally uses a table if link instances (NodeLocalRouter) that enqueues events based on the targeted function_id.
@@ -23,13 +22,12 @@ impl DataPlaneLink for NodeLocalLink {
         created: &edgeless_api::function_instance::EventTimestamp,
         stream_id: u64,
         metadata: &edgeless_api::function_instance::EventMetadata,
+        _delivery: Delivery,
     ) -> LinkProcessingResult {
         if target.node_id == self.node_id {
-            return self
-                .router
-                .lock()
-                .await
-                .handle(edgeless_api::invocation::Event {
+            return deliver_local(
+                &self.router,
+                edgeless_api::invocation::Event {
                     target: *target,
                     source: *src,
                     stream_id,
@@ -42,9 +40,10 @@ impl DataPlaneLink for NodeLocalLink {
                     },
                     created: *created,
                     metadata: metadata.clone(),
-                })
-                .await
-                .unwrap();
+                    event_id: 0,
+                },
+            )
+            .await;
         } else {
             return LinkProcessingResult::PASSED;
         }
@@ -52,40 +51,73 @@ impl DataPlaneLink for NodeLocalLink {
 }
 
 pub struct NodeLocalRouter {
-    pub receivers: std::collections::HashMap<uuid::Uuid, futures::channel::mpsc::UnboundedSender<DataplaneEvent>>,
+    pub receivers: std::collections::HashMap<uuid::Uuid, InboundSender>,
 }
 
-// This is used by the remote node that is currently borrowing the `NodeLocalRouter`
-#[async_trait::async_trait]
-impl edgeless_api::invocation::InvocationAPI for NodeLocalRouter {
-    async fn handle(&mut self, event: edgeless_api::invocation::Event) -> anyhow::Result<edgeless_api::invocation::LinkProcessingResult> {
-        if let Some(sender) = self.receivers.get_mut(&event.target.function_id) {
-            let msg = match event.data {
-                edgeless_api::invocation::EventData::Call(data) => Message::Call(data),
-                edgeless_api::invocation::EventData::Cast(data) => Message::Cast(data),
-                edgeless_api::invocation::EventData::CallRet(data) => Message::CallRet(data),
-                edgeless_api::invocation::EventData::CallNoRet => Message::CallNoRet,
-                edgeless_api::invocation::EventData::Err => Message::Err,
-            };
-            match sender
-                .send(DataplaneEvent {
-                    source_id: event.source,
-                    channel_id: event.stream_id,
-                    message: msg,
-                    created: event.created,
-                    metadata: event.metadata,
-                })
-                .await
-            {
-                Ok(_) => {}
-                Err(_) => {
-                    log::debug!("Remove old receiver.");
-                    self.receivers.remove(&event.target.function_id);
-                }
-            }
-            return Ok(LinkProcessingResult::FINAL);
-        }
-        Ok(LinkProcessingResult::PASSED)
+/// Deliver an event to the inbound queue of a component hosted by this node.
+///
+/// The lock on the router is not held while waiting for room in the queue,
+/// so that the events towards the other components are not blocked.
+pub async fn deliver_local(
+    router: &std::sync::Arc<tokio::sync::Mutex<NodeLocalRouter>>,
+    event: edgeless_api::invocation::Event,
+) -> LinkProcessingResult {
+    let component_id = event.target.function_id;
+    let sender = match router.lock().await.receivers.get(&component_id) {
+        Some(sender) => sender.clone(),
+        None => return LinkProcessingResult::PASSED,
+    };
+    if sender.send(dataplane_event(event)).await.is_err() {
+        remove_closed(router, &component_id).await;
+    }
+    LinkProcessingResult::FINAL
+}
+
+/// Deliver an event to the inbound queue of a component hosted by this node,
+/// without waiting for room in the queue.
+///
+/// Return an error if the queue is full and its overflow policy is `BlockLocal`,
+/// e.g., so that a remote sender retries later instead of blocking the
+/// events received from the other nodes.
+pub async fn try_deliver_local(
+    router: &std::sync::Arc<tokio::sync::Mutex<NodeLocalRouter>>,
+    event: edgeless_api::invocation::Event,
+) -> anyhow::Result<LinkProcessingResult> {
+    let component_id = event.target.function_id;
+    let sender = match router.lock().await.receivers.get(&component_id) {
+        Some(sender) => sender.clone(),
+        None => return Ok(LinkProcessingResult::PASSED),
+    };
+    match sender.try_send(dataplane_event(event)) {
+        Ok(()) => {}
+        Err(crate::inbound_queue::TrySendError::Full(_)) => anyhow::bail!("Inbound queue of the target component full"),
+        Err(crate::inbound_queue::TrySendError::Closed(_)) => remove_closed(router, &component_id).await,
+    }
+    Ok(LinkProcessingResult::FINAL)
+}
+
+fn dataplane_event(event: edgeless_api::invocation::Event) -> DataplaneEvent {
+    DataplaneEvent {
+        source_id: event.source,
+        channel_id: event.stream_id,
+        message: match event.data {
+            edgeless_api::invocation::EventData::Call(data) => Message::Call(data),
+            edgeless_api::invocation::EventData::Cast(data) => Message::Cast(data),
+            edgeless_api::invocation::EventData::CallRet(data) => Message::CallRet(data),
+            edgeless_api::invocation::EventData::CallNoRet => Message::CallNoRet,
+            edgeless_api::invocation::EventData::Err => Message::Err,
+        },
+        created: event.created,
+        metadata: event.metadata,
+    }
+}
+
+// Remove the sender of a component whose inbound queue has been closed.
+async fn remove_closed(router: &std::sync::Arc<tokio::sync::Mutex<NodeLocalRouter>>, component_id: &uuid::Uuid) {
+    log::debug!("Remove old receiver.");
+    let mut router = router.lock().await;
+    if router.receivers.get(component_id).is_some_and(|sender| sender.is_closed()) {
+        router.receivers.remove(component_id);
     }
 }
 
@@ -103,16 +135,12 @@ impl NodeLocalLinkProvider {
     pub fn new() -> Self {
         Self {
             router: std::sync::Arc::new(tokio::sync::Mutex::new(NodeLocalRouter {
-                receivers: std::collections::HashMap::<uuid::Uuid, futures::channel::mpsc::UnboundedSender<DataplaneEvent>>::new(),
+                receivers: std::collections::HashMap::<uuid::Uuid, InboundSender>::new(),
             })),
         }
     }
 
-    pub async fn new_link(
-        &self,
-        target: edgeless_api::function_instance::InstanceId,
-        sender: futures::channel::mpsc::UnboundedSender<DataplaneEvent>,
-    ) -> Box<dyn DataPlaneLink> {
+    pub async fn new_link(&self, target: edgeless_api::function_instance::InstanceId, sender: InboundSender) -> Box<dyn DataPlaneLink> {
         self.router.lock().await.receivers.insert(target.function_id, sender);
         Box::new(NodeLocalLink {
             node_id: target.node_id,
@@ -124,6 +152,7 @@ impl NodeLocalLinkProvider {
 #[cfg(test)]
 mod test {
     use super::NodeLocalLinkProvider;
+    use crate::core::Delivery;
 
     #[tokio::test]
     async fn basic_forwarding() {
@@ -136,37 +165,49 @@ mod test {
 
         let provider = NodeLocalLinkProvider::new();
 
-        let (sender_1, mut receiver_1) = futures::channel::mpsc::unbounded::<crate::core::DataplaneEvent>();
+        let (sender_1, receiver_1) = crate::inbound_queue::inbound_queue(fid_1, crate::inbound_queue::InboundQueueSettings::default(), None);
         let mut handle_1 = provider.new_link(fid_1, sender_1).await;
 
-        let (sender_2, mut receiver_2) = futures::channel::mpsc::unbounded::<crate::core::DataplaneEvent>();
+        let (sender_2, receiver_2) = crate::inbound_queue::inbound_queue(fid_2, crate::inbound_queue::InboundQueueSettings::default(), None);
         let _handle_2 = provider.new_link(fid_2, sender_2).await;
 
-        assert!(receiver_1.try_next().is_err());
-        assert!(receiver_2.try_next().is_err());
+        assert!(receiver_1.is_empty());
+        assert!(receiver_2.is_empty());
 
         let ret_1 = handle_1
-            .handle_send(&fid_3, crate::core::Message::Cast("".to_string()), &fid_1, &ts, 0, &metad_1)
+            .handle_send(
+                &fid_3,
+                crate::core::Message::Cast(Vec::new()),
+                &fid_1,
+                &ts,
+                0,
+                &metad_1,
+                Delivery::AtMostOnce,
+            )
             .as_mut()
             .await;
 
         assert_eq!(ret_1, crate::core::LinkProcessingResult::PASSED);
-        assert!(receiver_1.try_next().is_err());
-        assert!(receiver_2.try_next().is_err());
+        assert!(receiver_1.is_empty());
+        assert!(receiver_2.is_empty());
 
         let ret_2 = handle_1
-            .handle_send(&fid_2, crate::core::Message::Cast("".to_string()), &fid_1, &ts, 0, &metad_1)
+            .handle_send(
+                &fid_2,
+                crate::core::Message::Cast(Vec::new()),
+                &fid_1,
+                &ts,
+                0,
+                &metad_1,
+                Delivery::AtMostOnce,
+            )
             .as_mut()
             .await;
 
         assert_eq!(ret_2, crate::core::LinkProcessingResult::FINAL);
-        assert!(receiver_1.try_next().is_err());
-        let result = {
-            let tmp = receiver_2.try_next();
-            assert!(tmp.is_ok());
-            assert!(tmp.as_ref().unwrap().is_some());
-            tmp.unwrap().unwrap()
-        };
+        assert!(receiver_1.is_empty());
+        assert_eq!(1, receiver_2.len());
+        let result = receiver_2.recv().await;
         assert_eq!(metad_1, result.metadata)
     }
 }
@@ -0,0 +1,112 @@
+// SPDX-FileCopyrightText: © 2024 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Output splits of the components hosted by a node.
+///
+/// key:   component identifier of the sender
+/// value: map of:
+///        key:   output channel
+///        value: split of the events sent on that channel
+pub type OutputSplits = std::collections::HashMap<edgeless_api::function_instance::ComponentId, std::collections::HashMap<String, OutputSplit>>;
+
+/// Selection of the target of the events sent on an output channel whose
+/// events are split among multiple instances, each receiving a fraction of
+/// the events proportional to its weight.
+///
+/// The selection is done with a smooth weighted round-robin, so that the
+/// events towards a given target are evenly spaced, e.g., with weights 9 and
+/// 1 the second target is selected once every 10 events.
+#[derive(Debug)]
+pub struct OutputSplit {
+    targets: Vec<SplitTarget>,
+    total_weight: i64,
+}
+
+#[derive(Debug)]
+struct SplitTarget {
+    instance_id: edgeless_api::function_instance::InstanceId,
+    weight: i64,
+    current: i64,
+}
+
+impl OutputSplit {
+    /// Create a split among the given targets.
+    ///
+    /// Return None if there are no targets with a positive weight.
+    pub fn new(targets: &[edgeless_api::common::WeightedTarget]) -> Option<Self> {
+        let targets = targets
+            .iter()
+            .filter(|target| target.weight > 0)
+            .map(|target| SplitTarget {
+                instance_id: target.instance_id,
+                weight: target.weight as i64,
+                current: 0,
+            })
+            .collect::<Vec<SplitTarget>>();
+        if targets.is_empty() {
+            return None;
+        }
+        let total_weight = targets.iter().map(|target| target.weight).sum();
+        Some(Self { targets, total_weight })
+    }
+
+    /// Return the target of the next event.
+    pub fn next_target(&mut self) -> edgeless_api::function_instance::InstanceId {
+        for target in &mut self.targets {
+            target.current += target.weight;
+        }
+        // Ties are broken in favor of the first target.
+        let selected = self
+            .targets
+            .iter_mut()
+            .enumerate()
+            .max_by_key(|(i, target)| (target.current, std::cmp::Reverse(*i)))
+            .map(|(_, target)| target)
+            .unwrap();
+        selected.current -= self.total_weight;
+        selected.instance_id
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn weighted_target(weight: u32) -> edgeless_api::common::WeightedTarget {
+        edgeless_api::common::WeightedTarget {
+            instance_id: edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4()),
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_output_split_weights() {
+        let targets = vec![weighted_target(90), weighted_target(10), weighted_target(0)];
+        let mut split = OutputSplit::new(&targets).unwrap();
+
+        let mut counts = std::collections::HashMap::new();
+        for _ in 0..1000 {
+            *counts.entry(split.next_target()).or_insert(0) += 1;
+        }
+        assert_eq!(Some(&900), counts.get(&targets[0].instance_id));
+        assert_eq!(Some(&100), counts.get(&targets[1].instance_id));
+        assert_eq!(None, counts.get(&targets[2].instance_id));
+
+        // The events to the target with a smaller weight are evenly spaced.
+        for _ in 0..10 {
+            let selected = (0..10).map(|_| split.next_target()).collect::<Vec<_>>();
+            assert_eq!(1, selected.iter().filter(|x| **x == targets[1].instance_id).count());
+        }
+    }
+
+    #[test]
+    fn test_output_split_no_targets() {
+        assert!(OutputSplit::new(&[]).is_none());
+        assert!(OutputSplit::new(&[weighted_target(0), weighted_target(0)]).is_none());
+        let target = weighted_target(1);
+        let mut split = OutputSplit::new(std::slice::from_ref(&target)).unwrap();
+        for _ in 0..10 {
+            assert_eq!(target.instance_id, split.next_target());
+        }
+    }
+}
@@ -2,12 +2,20 @@
 // SPDX-FileCopyrightText: © 2023 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
 // SPDX-FileCopyrightText: © 2023 Siemens AG
 // SPDX-License-Identifier: MIT
+use crate::at_least_once::*;
 use crate::core::*;
-use crate::node_local::NodeLocalRouter;
+use crate::inbound_queue::InboundSender;
+use crate::node_local::{NodeLocalRouter, try_deliver_local};
 use edgeless_api::function_instance::{ComponentId, NodeId};
-use edgeless_api::invocation::InvocationAPI;
+
+/// Sink of the events that could not be delivered to a peer node, with the
+/// reason why, e.g., to forward them to the dead-letter functions/resources
+/// of their senders.
+pub type UndeliveredSender = tokio::sync::mpsc::UnboundedSender<(edgeless_api::invocation::Event, String)>;
+pub type UndeliveredReceiver = tokio::sync::mpsc::UnboundedReceiver<(edgeless_api::invocation::Event, String)>;
 
 // Link allowing to send messages to a remote node using the InvocationAPI.
+// The casts sent with `Delivery::AtLeastOnce` are delivered at least once.
 struct RemoteLink {
     remotes: std::sync::Arc<tokio::sync::Mutex<RemoteRouter>>,
 }
@@ -22,66 +30,140 @@ impl DataPlaneLink for RemoteLink {
         created: &edgeless_api::function_instance::EventTimestamp,
         stream_id: u64,
         metadata: &edgeless_api::function_instance::EventMetadata,
+        delivery: Delivery,
     ) -> LinkProcessingResult {
-        return self
-            .remotes
-            .lock()
-            .await
-            .handle(edgeless_api::invocation::Event {
-                target: *target,
-                source: *src,
-                stream_id,
-                data: match msg {
-                    Message::Call(data) => edgeless_api::invocation::EventData::Call(data),
-                    Message::Cast(data) => edgeless_api::invocation::EventData::Cast(data),
-                    Message::CallRet(data) => edgeless_api::invocation::EventData::CallRet(data),
-                    Message::CallNoRet => edgeless_api::invocation::EventData::CallNoRet,
-                    Message::Err => edgeless_api::invocation::EventData::Err,
-                },
-                created: *created,
-                metadata: metadata.clone(),
-            })
-            .await
-            .unwrap();
+        let at_least_once = matches!(msg, Message::Cast(_)) && delivery == Delivery::AtLeastOnce;
+        let event = edgeless_api::invocation::Event {
+            target: *target,
+            source: *src,
+            stream_id,
+            data: match msg {
+                Message::Call(data) => edgeless_api::invocation::EventData::Call(data),
+                Message::Cast(data) => edgeless_api::invocation::EventData::Cast(data),
+                Message::CallRet(data) => edgeless_api::invocation::EventData::CallRet(data),
+                Message::CallNoRet => edgeless_api::invocation::EventData::CallNoRet,
+                Message::Err => edgeless_api::invocation::EventData::Err,
+            },
+            created: *created,
+            metadata: metadata.clone(),
+            event_id: 0,
+        };
+        if at_least_once {
+            return self.remotes.lock().await.send_at_least_once(event);
+        }
+
+        // The lock on the router is released before sending the event, so
+        // that a slow peer does not delay the events towards the others.
+        let (node_client, undelivered) = {
+            let remotes = self.remotes.lock().await;
+            match remotes.receivers.get(&event.target.node_id) {
+                Some(node_client) => (node_client.clone(), remotes.undelivered.clone()),
+                None => return LinkProcessingResult::PASSED,
+            }
+        };
+        RemoteRouter::send(&node_client, &undelivered, event).await
     }
 }
 
 pub struct RemoteRouter {
-    receivers: std::collections::HashMap<NodeId, Box<dyn edgeless_api::invocation::InvocationAPI>>,
+    receivers: std::collections::HashMap<NodeId, std::sync::Arc<tokio::sync::Mutex<Box<dyn edgeless_api::invocation::InvocationAPI>>>>,
+    // Connectors of the clients dedicated to the events delivered at least
+    // once, until the outbox of the peer is created.
+    at_least_once_connectors: std::collections::HashMap<NodeId, PeerConnector>,
+    // Created upon sending the first event delivered at least once to a peer.
+    outboxes: std::collections::HashMap<NodeId, PeerOutbox>,
+    retry_policy: RetryPolicy,
+    telemetry: SharedTelemetryHandle,
+    undelivered: UndeliveredSender,
+}
+
+impl RemoteRouter {
+    // Send an event delivered at most once to a peer node, forwarding the
+    // casts and calls rejected to the undelivered events.
+    async fn send(
+        node_client: &std::sync::Arc<tokio::sync::Mutex<Box<dyn edgeless_api::invocation::InvocationAPI>>>,
+        undelivered: &UndeliveredSender,
+        event: edgeless_api::invocation::Event,
+    ) -> LinkProcessingResult {
+        // The event is kept only if it may be forwarded to a dead letter.
+        let rejected = match event.data {
+            edgeless_api::invocation::EventData::Cast(_) | edgeless_api::invocation::EventData::Call(_) => Some(event.clone()),
+            _ => None,
+        };
+        if let Err(err) = node_client.lock().await.handle(event).await {
+            log::warn!("Error in handling event: {}", err);
+            if let Some(rejected) = rejected {
+                let _ = undelivered.send((rejected, "rejected by the remote node".to_string()));
+            }
+        }
+        LinkProcessingResult::FINAL
+    }
+
+    // Enqueue an event to be delivered at least once to the target node.
+    // The events towards a node that is not (yet) a peer are buffered until
+    // the peer is added.
+    fn send_at_least_once(&mut self, event: edgeless_api::invocation::Event) -> LinkProcessingResult {
+        let node_id = event.target.node_id;
+        let outbox = match self.outboxes.entry(node_id) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let outbox = PeerOutbox::new(self.retry_policy.clone(), self.telemetry.clone(), self.undelivered.clone());
+                if let Some(connector) = self.at_least_once_connectors.remove(&node_id) {
+                    outbox.connect(connector);
+                }
+                entry.insert(outbox)
+            }
+        };
+        outbox.push(edgeless_api::invocation::Event {
+            event_id: new_event_id(),
+            ..event
+        });
+        LinkProcessingResult::FINAL
+    }
 }
 
 pub struct RemoteLinkProvider {
     own_node_id: edgeless_api::function_instance::NodeId,
     remotes: std::sync::Arc<tokio::sync::Mutex<RemoteRouter>>,
     locals: std::sync::Arc<tokio::sync::Mutex<NodeLocalRouter>>,
+    delivered: std::sync::Arc<tokio::sync::Mutex<DeliveredEvents>>,
+    undelivered: Option<UndeliveredReceiver>,
 }
 
 struct InvocationEventHandler {
     node_id: edgeless_api::function_instance::NodeId,
     locals: std::sync::Arc<tokio::sync::Mutex<NodeLocalRouter>>,
+    delivered: std::sync::Arc<tokio::sync::Mutex<DeliveredEvents>>,
 }
 
 #[async_trait::async_trait]
 impl edgeless_api::invocation::InvocationAPI for InvocationEventHandler {
     async fn handle(&mut self, event: edgeless_api::invocation::Event) -> anyhow::Result<edgeless_api::invocation::LinkProcessingResult> {
-        if event.target.node_id == self.node_id {
-            self.locals.lock().await.handle(event).await
-        } else {
-            Err(anyhow::anyhow!("Wrong Node ID"))
+        if event.target.node_id != self.node_id {
+            return Err(anyhow::anyhow!("Wrong Node ID"));
+        }
+        // The events are rejected, instead of waiting, if the inbound queue
+        // of the target is full, so that the events towards the other
+        // components are not blocked.
+        if event.event_id == 0 {
+            return try_deliver_local(&self.locals, event).await;
         }
-    }
-}
 
-#[async_trait::async_trait]
-impl edgeless_api::invocation::InvocationAPI for RemoteRouter {
-    async fn handle(&mut self, event: edgeless_api::invocation::Event) -> anyhow::Result<edgeless_api::invocation::LinkProcessingResult> {
-        if let Some(node_client) = self.receivers.get_mut(&event.target.node_id) {
-            if let Err(err) = node_client.handle(event).await {
-                log::warn!("Error in handling event: {}", err);
+        // The event is delivered at least once: acknowledge the duplicates
+        // without delivering them and let the sender retry if the target
+        // component is not (yet) hosted by this node or its queue is full.
+        let (source, event_id) = (event.source, event.event_id);
+        let mut delivered = self.delivered.lock().await;
+        if delivered.contains(&source, event_id) {
+            log::debug!("Discarded duplicate event {} from {:?}", event_id, source);
+            return Ok(edgeless_api::invocation::LinkProcessingResult::FINAL);
+        }
+        match try_deliver_local(&self.locals, event).await? {
+            edgeless_api::invocation::LinkProcessingResult::FINAL => {
+                delivered.insert(source, event_id);
+                Ok(edgeless_api::invocation::LinkProcessingResult::FINAL)
             }
-            Ok(edgeless_api::invocation::LinkProcessingResult::FINAL)
-        } else {
-            Ok(edgeless_api::invocation::LinkProcessingResult::PASSED)
+            _ => Err(anyhow::anyhow!("Target component not found")),
         }
     }
 }
@@ -89,25 +171,55 @@ impl edgeless_api::invocation::InvocationAPI for RemoteRouter {
 impl RemoteLinkProvider {
     pub async fn new(own_node_id: edgeless_api::function_instance::NodeId) -> Self {
         let locals = std::sync::Arc::new(tokio::sync::Mutex::new(NodeLocalRouter {
-            receivers: std::collections::HashMap::<ComponentId, futures::channel::mpsc::UnboundedSender<DataplaneEvent>>::new(),
+            receivers: std::collections::HashMap::<ComponentId, InboundSender>::new(),
         }));
 
+        let (undelivered, undelivered_receiver) = tokio::sync::mpsc::unbounded_channel();
         let remotes = std::sync::Arc::new(tokio::sync::Mutex::new(RemoteRouter {
             receivers: std::collections::HashMap::new(),
+            at_least_once_connectors: std::collections::HashMap::new(),
+            outboxes: std::collections::HashMap::new(),
+            retry_policy: RetryPolicy::default(),
+            telemetry: std::sync::Arc::new(std::sync::Mutex::new(None)),
+            undelivered,
         }));
 
         Self {
             own_node_id,
             remotes,
             locals,
+            delivered: std::sync::Arc::new(tokio::sync::Mutex::new(DeliveredEvents::new(DEDUP_WINDOW))),
+            undelivered: Some(undelivered_receiver),
         }
     }
 
-    pub async fn new_link(
-        &self,
-        target: edgeless_api::function_instance::InstanceId,
-        sender: futures::channel::mpsc::UnboundedSender<DataplaneEvent>,
-    ) -> Box<dyn DataPlaneLink> {
+    /// Take the receiver of the events that could not be delivered to the
+    /// peers: those rejected by the peer and those delivered at least once
+    /// that have been dropped.
+    /// Return `None` if already taken.
+    pub fn take_undelivered(&mut self) -> Option<UndeliveredReceiver> {
+        self.undelivered.take()
+    }
+
+    /// Return the sink of the events that could not be delivered to the
+    /// peers, e.g., for the clients that detect the rejections
+    /// asynchronously.
+    pub async fn undelivered_sender(&self) -> UndeliveredSender {
+        self.remotes.lock().await.undelivered.clone()
+    }
+
+    /// Set the parameters of the retransmissions of the events delivered at
+    /// least once, used for the peers without any such event sent so far.
+    pub async fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.remotes.lock().await.retry_policy = retry_policy;
+    }
+
+    /// Set the telemetry handle used to report delivery failures.
+    pub async fn set_telemetry_handle(&mut self, telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>) {
+        *self.remotes.lock().await.telemetry.lock().unwrap() = Some(telemetry_handle);
+    }
+
+    pub async fn new_link(&self, target: edgeless_api::function_instance::InstanceId, sender: InboundSender) -> Box<dyn DataPlaneLink> {
         self.locals.lock().await.receivers.insert(target.function_id, sender);
         Box::new(RemoteLink {
             remotes: self.remotes.clone(),
@@ -118,15 +230,46 @@ impl RemoteLinkProvider {
         Box::new(InvocationEventHandler {
             node_id: self.own_node_id,
             locals: self.locals.clone(),
+            delivered: self.delivered.clone(),
         })
     }
 
-    pub async fn add_peer(&mut self, peer_id: NodeId, peer_api: Box<dyn edgeless_api::invocation::InvocationAPI>) {
-        self.remotes.lock().await.receivers.insert(peer_id, peer_api);
+    /// Add a peer node, or replace the clients of an existing one.
+    ///
+    /// * `peer_api` - The client used for the events delivered at most once.
+    /// * `at_least_once_connector` - Creates the client used for the events
+    ///   delivered at least once, so that waiting for their acknowledgements
+    ///   does not delay the other events, upon sending the first of them.
+    pub async fn add_peer(
+        &mut self,
+        peer_id: NodeId,
+        peer_api: Box<dyn edgeless_api::invocation::InvocationAPI>,
+        at_least_once_connector: PeerConnector,
+    ) {
+        let mut remotes = self.remotes.lock().await;
+        match remotes.receivers.get(&peer_id) {
+            Some(node_client) => *node_client.lock().await = peer_api,
+            None => {
+                remotes.receivers.insert(peer_id, std::sync::Arc::new(tokio::sync::Mutex::new(peer_api)));
+            }
+        }
+        // The events pending delivery at least once, if any, are sent via the
+        // new client.
+        match remotes.outboxes.get(&peer_id) {
+            Some(outbox) => outbox.connect(at_least_once_connector),
+            None => {
+                remotes.at_least_once_connectors.insert(peer_id, at_least_once_connector);
+            }
+        }
     }
 
+    /// Remove a peer node, dropping the events pending delivery at least
+    /// once to it, which are reported as delivery failures.
     pub async fn del_peer(&mut self, peer_id: NodeId) {
-        self.remotes.lock().await.receivers.remove(&peer_id);
+        let mut remotes = self.remotes.lock().await;
+        remotes.receivers.remove(&peer_id);
+        remotes.at_least_once_connectors.remove(&peer_id);
+        remotes.outboxes.remove(&peer_id);
     }
 }
 
@@ -153,52 +296,54 @@ mod test {
         let mut provider = RemoteLinkProvider::new(node_id).await;
         let mut api = provider.incomming_api().await;
 
-        let (sender_1, mut receiver_1) = futures::channel::mpsc::unbounded::<crate::core::DataplaneEvent>();
+        let (sender_1, receiver_1) = crate::inbound_queue::inbound_queue(fid_target, crate::inbound_queue::InboundQueueSettings::default(), None);
         provider.new_link(fid_target, sender_1).await;
 
         api.handle(edgeless_api::invocation::Event {
             target: fid_wrong_component_id,
             source: fid_source,
             stream_id: 0,
-            data: edgeless_api::invocation::EventData::Cast("Test".to_string()),
+            data: edgeless_api::invocation::EventData::Cast(b"Test".to_vec()),
             created,
             metadata: edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00015u128, 0x42a42bdecaf00016u64),
+            event_id: 0,
         })
         .await
         .unwrap();
 
-        assert!(receiver_1.try_next().is_err());
+        assert!(receiver_1.is_empty());
 
         assert!(
             api.handle(edgeless_api::invocation::Event {
                 target: fid_wrong_node_id,
                 source: fid_source,
                 stream_id: 0,
-                data: edgeless_api::invocation::EventData::Cast("Test".to_string()),
+                data: edgeless_api::invocation::EventData::Cast(b"Test".to_vec()),
                 created,
                 metadata: edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00013u128, 0x42a42bdecaf00014u64),
+                event_id: 0,
             })
             .await
             .is_err()
         );
 
-        assert!(receiver_1.try_next().is_err());
+        assert!(receiver_1.is_empty());
 
         let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00011u128, 0x42a42bdecaf00012u64);
         api.handle(edgeless_api::invocation::Event {
             target: fid_target,
             source: fid_source,
             stream_id: 0,
-            data: edgeless_api::invocation::EventData::Cast("Test".to_string()),
+            data: edgeless_api::invocation::EventData::Cast(b"Test".to_vec()),
             created,
             metadata: metad_1.clone(),
+            event_id: 0,
         })
         .await
         .unwrap();
 
-        let result = receiver_1.try_next();
-        assert!(result.as_ref().is_ok_and(|o| o.is_some()));
-        let result = result.unwrap().unwrap();
+        assert_eq!(1, receiver_1.len());
+        let result = receiver_1.recv().await;
         assert_eq!(&metad_1, &result.metadata)
     }
 
@@ -219,6 +364,18 @@ mod test {
         }
     }
 
+    // Return a connector of a client already created, counting the times
+    // that it is called.
+    fn connector(
+        client: Box<dyn edgeless_api::invocation::InvocationAPI>,
+        connected: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    ) -> PeerConnector {
+        Box::new(move || {
+            connected.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Box::pin(async move { client })
+        })
+    }
+
     #[tokio::test]
     async fn outgoing_message() {
         let node_id = uuid::Uuid::new_v4();
@@ -237,19 +394,34 @@ mod test {
 
         let (api_sender_node_2, mut api_receiver_node_2) = futures::channel::mpsc::unbounded::<edgeless_api::invocation::Event>();
         let node_2_api: Box<dyn edgeless_api::invocation::InvocationAPI> = Box::new(MockInvocationAPI {
+            own_node_id: node_id_2,
+            events: api_sender_node_2.clone(),
+        });
+        let node_2_at_least_once_api: Box<dyn edgeless_api::invocation::InvocationAPI> = Box::new(MockInvocationAPI {
             own_node_id: node_id_2,
             events: api_sender_node_2,
         });
+        let connected = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
         let mut provider = RemoteLinkProvider::new(node_id).await;
-        provider.add_peer(node_id_2, node_2_api).await;
+        provider
+            .add_peer(node_id_2, node_2_api, connector(node_2_at_least_once_api, connected.clone()))
+            .await;
         // let mut api = provider.incomming_api().await;
         let created = edgeless_api::function_instance::EventTimestamp::default();
 
-        let (sender_1, _receiver_1) = futures::channel::mpsc::unbounded::<crate::core::DataplaneEvent>();
+        let (sender_1, _receiver_1) = crate::inbound_queue::inbound_queue(fid_source, crate::inbound_queue::InboundQueueSettings::default(), None);
         let mut link = provider.new_link(fid_source, sender_1).await;
 
         let res = link
-            .handle_send(&fid_target, Message::Cast("Test".to_string()), &fid_source, &created, 0, &metad_source)
+            .handle_send(
+                &fid_target,
+                Message::Cast(b"Test".to_vec()),
+                &fid_source,
+                &created,
+                0,
+                &metad_source,
+                Delivery::AtMostOnce,
+            )
             .await;
         assert_eq!(res, LinkProcessingResult::FINAL);
         assert!(api_receiver_node_2.try_next().unwrap().is_some());
@@ -257,11 +429,12 @@ mod test {
         let res = link
             .handle_send(
                 &fid_wrong_component_id,
-                Message::Cast("Test".to_string()),
+                Message::Cast(b"Test".to_vec()),
                 &fid_source,
                 &created,
                 0,
                 &metad_source,
+                Delivery::AtMostOnce,
             )
             .await;
         assert_eq!(res, LinkProcessingResult::FINAL);
@@ -270,20 +443,500 @@ mod test {
         let res = link
             .handle_send(
                 &fid_wrong_node_id,
-                Message::Cast("Test".to_string()),
+                Message::Cast(b"Test".to_vec()),
                 &fid_source,
                 &created,
                 0,
                 &metad_source,
+                Delivery::AtMostOnce,
             )
             .await;
         assert_eq!(res, LinkProcessingResult::PASSED);
         assert!(api_receiver_node_2.try_next().is_err());
 
         let res = link
-            .handle_send(&fid_target, Message::Cast("Test".to_string()), &fid_source, &created, 0, &metad_source)
+            .handle_send(
+                &fid_target,
+                Message::Cast(b"Test".to_vec()),
+                &fid_source,
+                &created,
+                0,
+                &metad_source,
+                Delivery::AtMostOnce,
+            )
             .await;
         assert_eq!(res, LinkProcessingResult::FINAL);
         assert!(api_receiver_node_2.try_next().unwrap().is_some());
+
+        // The client for the events delivered at least once is created only
+        // when the first of them is sent.
+        assert_eq!(0, connected.load(std::sync::atomic::Ordering::SeqCst));
+        let res = link
+            .handle_send(
+                &fid_target,
+                Message::Cast(b"Test".to_vec()),
+                &fid_source,
+                &created,
+                0,
+                &metad_source,
+                Delivery::AtLeastOnce,
+            )
+            .await;
+        assert_eq!(res, LinkProcessingResult::FINAL);
+        use futures::StreamExt;
+        let event = tokio::time::timeout(std::time::Duration::from_secs(1), api_receiver_node_2.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(0, event.event_id);
+        assert_eq!(1, connected.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    struct FlakyInvocationAPI {
+        failures: u32,
+        events: futures::channel::mpsc::UnboundedSender<edgeless_api::invocation::Event>,
+    }
+
+    #[async_trait::async_trait]
+    impl edgeless_api::invocation::InvocationAPI for FlakyInvocationAPI {
+        async fn handle(&mut self, event: edgeless_api::invocation::Event) -> anyhow::Result<LinkProcessingResult> {
+            self.events.send(event).await.unwrap();
+            if self.failures > 0 {
+                self.failures -= 1;
+                Err(anyhow::anyhow!("Remote Event Request Failed"))
+            } else {
+                Ok(LinkProcessingResult::FINAL)
+            }
+        }
+    }
+
+    struct MockTelemetryHandle {
+        sender: std::sync::mpsc::Sender<edgeless_telemetry::telemetry_events::TelemetryEvent>,
+    }
+
+    impl edgeless_telemetry::telemetry_events::TelemetryHandleAPI for MockTelemetryHandle {
+        fn observe(&mut self, event: edgeless_telemetry::telemetry_events::TelemetryEvent, _event_tags: std::collections::BTreeMap<String, String>) {
+            self.sender.send(event).unwrap();
+        }
+
+        fn fork(
+            &mut self,
+            _child_tags: std::collections::BTreeMap<String, String>,
+        ) -> Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI> {
+            Box::new(MockTelemetryHandle { sender: self.sender.clone() })
+        }
+    }
+
+    #[tokio::test]
+    async fn outgoing_message_at_least_once() {
+        use futures::StreamExt;
+        let node_id = uuid::Uuid::new_v4();
+        let node_id_2 = uuid::Uuid::new_v4();
+        let node_id_3 = uuid::Uuid::new_v4();
+        let fid_source = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_target = edgeless_api::function_instance::InstanceId::new(node_id_2);
+        let fid_other = edgeless_api::function_instance::InstanceId::new(node_id_2);
+        let fid_unreachable = edgeless_api::function_instance::InstanceId::new(node_id_3);
+        let metad_source = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf0003au128, 0x42a42bdecaf0003bu64);
+        let created = edgeless_api::function_instance::EventTimestamp::default();
+
+        let mut provider = RemoteLinkProvider::new(node_id).await;
+        provider
+            .set_retry_policy(RetryPolicy {
+                max_attempts: 3,
+                initial_backoff: std::time::Duration::from_millis(10),
+                max_backoff: std::time::Duration::from_millis(20),
+                max_buffered: 10,
+            })
+            .await;
+        let (telemetry_sender, telemetry_receiver) = std::sync::mpsc::channel();
+        provider
+            .set_telemetry_handle(Box::new(MockTelemetryHandle { sender: telemetry_sender }))
+            .await;
+        let mut undelivered = provider.take_undelivered().unwrap();
+
+        // Node 2 fails twice the events delivered at least once, node 3
+        // always fails.
+        let (api_sender_node_2, mut api_receiver_node_2) = futures::channel::mpsc::unbounded::<edgeless_api::invocation::Event>();
+        provider
+            .add_peer(
+                node_id_2,
+                Box::new(FlakyInvocationAPI {
+                    failures: 0,
+                    events: api_sender_node_2.clone(),
+                }),
+                connector(
+                    Box::new(FlakyInvocationAPI {
+                        failures: 2,
+                        events: api_sender_node_2,
+                    }),
+                    std::sync::Arc::default(),
+                ),
+            )
+            .await;
+        let (api_sender_node_3, mut api_receiver_node_3) = futures::channel::mpsc::unbounded::<edgeless_api::invocation::Event>();
+        provider
+            .add_peer(
+                node_id_3,
+                Box::new(FlakyInvocationAPI {
+                    failures: u32::MAX,
+                    events: api_sender_node_3.clone(),
+                }),
+                connector(
+                    Box::new(FlakyInvocationAPI {
+                        failures: u32::MAX,
+                        events: api_sender_node_3,
+                    }),
+                    std::sync::Arc::default(),
+                ),
+            )
+            .await;
+
+        let (sender_1, _receiver_1) = crate::inbound_queue::inbound_queue(fid_source, crate::inbound_queue::InboundQueueSettings::default(), None);
+        let mut link = provider.new_link(fid_source, sender_1).await;
+
+        // The cast is retried until acknowledged, with the same identifier.
+        let res = link
+            .handle_send(
+                &fid_target,
+                Message::Cast(b"Test".to_vec()),
+                &fid_source,
+                &created,
+                0,
+                &metad_source,
+                Delivery::AtLeastOnce,
+            )
+            .await;
+        assert_eq!(res, LinkProcessingResult::FINAL);
+        let mut event_ids = vec![];
+        for _ in 0..3 {
+            let event = tokio::time::timeout(std::time::Duration::from_secs(1), api_receiver_node_2.next())
+                .await
+                .unwrap()
+                .unwrap();
+            event_ids.push(event.event_id);
+        }
+        assert_ne!(0, event_ids[0]);
+        assert!(event_ids.iter().all(|event_id| *event_id == event_ids[0]));
+        for attempts in 1..=2 {
+            assert_eq!(
+                edgeless_telemetry::telemetry_events::TelemetryEvent::DeliveryRetry(attempts),
+                telemetry_receiver.recv_timeout(std::time::Duration::from_secs(1)).unwrap()
+            );
+        }
+
+        // The cast is dropped after the maximum number of attempts.
+        let res = link
+            .handle_send(
+                &fid_unreachable,
+                Message::Cast(b"Test".to_vec()),
+                &fid_source,
+                &created,
+                0,
+                &metad_source,
+                Delivery::AtLeastOnce,
+            )
+            .await;
+        assert_eq!(res, LinkProcessingResult::FINAL);
+        for _ in 0..3 {
+            assert!(
+                tokio::time::timeout(std::time::Duration::from_secs(1), api_receiver_node_3.next())
+                    .await
+                    .is_ok()
+            );
+        }
+        for attempts in 1..=2 {
+            assert_eq!(
+                edgeless_telemetry::telemetry_events::TelemetryEvent::DeliveryRetry(attempts),
+                telemetry_receiver.recv_timeout(std::time::Duration::from_secs(1)).unwrap()
+            );
+        }
+        assert_eq!(
+            edgeless_telemetry::telemetry_events::TelemetryEvent::DeliveryFailure(3),
+            telemetry_receiver.recv_timeout(std::time::Duration::from_secs(1)).unwrap()
+        );
+        let (event, reason) = tokio::time::timeout(std::time::Duration::from_secs(1), undelivered.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fid_unreachable, event.target);
+        assert_eq!("maximum delivery attempts reached", reason);
+
+        // The casts on the other output channels are delivered at most once.
+        let res = link
+            .handle_send(
+                &fid_other,
+                Message::Cast(b"Test".to_vec()),
+                &fid_source,
+                &created,
+                0,
+                &metad_source,
+                Delivery::AtMostOnce,
+            )
+            .await;
+        assert_eq!(res, LinkProcessingResult::FINAL);
+        assert_eq!(0, api_receiver_node_2.try_next().unwrap().unwrap().event_id);
+    }
+
+    struct FailingTargetInvocationAPI {
+        failing_target: edgeless_api::function_instance::InstanceId,
+        events: futures::channel::mpsc::UnboundedSender<edgeless_api::invocation::Event>,
+    }
+
+    #[async_trait::async_trait]
+    impl edgeless_api::invocation::InvocationAPI for FailingTargetInvocationAPI {
+        async fn handle(&mut self, event: edgeless_api::invocation::Event) -> anyhow::Result<LinkProcessingResult> {
+            let failed = event.target == self.failing_target;
+            self.events.send(event).await.unwrap();
+            if failed {
+                Err(anyhow::anyhow!("Target component not found"))
+            } else {
+                Ok(LinkProcessingResult::FINAL)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn outgoing_message_at_least_once_per_target() {
+        use futures::StreamExt;
+        let node_id = uuid::Uuid::new_v4();
+        let node_id_2 = uuid::Uuid::new_v4();
+        let fid_source = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_target = edgeless_api::function_instance::InstanceId::new(node_id_2);
+        let fid_failing = edgeless_api::function_instance::InstanceId::new(node_id_2);
+        let metad_source = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf0003eu128, 0x42a42bdecaf0003fu64);
+        let created = edgeless_api::function_instance::EventTimestamp::default();
+
+        let mut provider = RemoteLinkProvider::new(node_id).await;
+        provider
+            .set_retry_policy(RetryPolicy {
+                max_attempts: 1000,
+                initial_backoff: std::time::Duration::from_millis(10),
+                max_backoff: std::time::Duration::from_millis(20),
+                max_buffered: 10,
+            })
+            .await;
+        let (telemetry_sender, telemetry_receiver) = std::sync::mpsc::channel();
+        provider
+            .set_telemetry_handle(Box::new(MockTelemetryHandle { sender: telemetry_sender }))
+            .await;
+        let mut undelivered = provider.take_undelivered().unwrap();
+
+        let (sender_1, _receiver_1) = crate::inbound_queue::inbound_queue(fid_source, crate::inbound_queue::InboundQueueSettings::default(), None);
+        let mut link = provider.new_link(fid_source, sender_1).await;
+
+        // The cast towards a node that is not a peer yet is buffered.
+        let res = link
+            .handle_send(
+                &fid_target,
+                Message::Cast(b"1".to_vec()),
+                &fid_source,
+                &created,
+                0,
+                &metad_source,
+                Delivery::AtLeastOnce,
+            )
+            .await;
+        assert_eq!(res, LinkProcessingResult::FINAL);
+
+        let (api_sender_node_2, mut api_receiver_node_2) = futures::channel::mpsc::unbounded::<edgeless_api::invocation::Event>();
+        provider
+            .add_peer(
+                node_id_2,
+                Box::new(MockInvocationAPI {
+                    own_node_id: node_id_2,
+                    events: api_sender_node_2.clone(),
+                }),
+                connector(
+                    Box::new(FailingTargetInvocationAPI {
+                        failing_target: fid_failing,
+                        events: api_sender_node_2,
+                    }),
+                    std::sync::Arc::default(),
+                ),
+            )
+            .await;
+
+        // The retransmissions towards a target do not delay the casts
+        // towards the other targets.
+        for (target, data) in [(fid_failing, b"2"), (fid_target, b"3")] {
+            let res = link
+                .handle_send(
+                    &target,
+                    Message::Cast(data.to_vec()),
+                    &fid_source,
+                    &created,
+                    0,
+                    &metad_source,
+                    Delivery::AtLeastOnce,
+                )
+                .await;
+            assert_eq!(res, LinkProcessingResult::FINAL);
+        }
+        let mut delivered = vec![];
+        while delivered.len() < 2 {
+            let event = tokio::time::timeout(std::time::Duration::from_secs(1), api_receiver_node_2.next())
+                .await
+                .unwrap()
+                .unwrap();
+            if event.target == fid_target {
+                delivered.push(event.data);
+            }
+        }
+        assert_eq!(
+            vec![
+                edgeless_api::invocation::EventData::Cast(b"1".to_vec()),
+                edgeless_api::invocation::EventData::Cast(b"3".to_vec())
+            ],
+            delivered
+        );
+
+        // The cast still pending is dropped when the peer is removed.
+        provider.del_peer(node_id_2).await;
+        let mut failure = None;
+        for _ in 0..100 {
+            match telemetry_receiver.try_recv() {
+                Ok(edgeless_telemetry::telemetry_events::TelemetryEvent::DeliveryFailure(attempts)) => {
+                    failure = Some(attempts);
+                    break;
+                }
+                Ok(_) => {}
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(10)).await,
+            }
+        }
+        assert!(failure.is_some_and(|attempts| attempts > 0));
+        let (event, reason) = tokio::time::timeout(std::time::Duration::from_secs(1), undelivered.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fid_failing, event.target);
+        assert_eq!(edgeless_api::invocation::EventData::Cast(b"2".to_vec()), event.data);
+        assert_eq!("peer node removed", reason);
+    }
+
+    #[tokio::test]
+    async fn outgoing_message_rejected() {
+        let node_id = uuid::Uuid::new_v4();
+        let node_id_2 = uuid::Uuid::new_v4();
+        let fid_source = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_target = edgeless_api::function_instance::InstanceId::new(node_id_2);
+        let fid_failing = edgeless_api::function_instance::InstanceId::new(node_id_2);
+        let metad_source = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00040u128, 0x42a42bdecaf00041u64);
+        let created = edgeless_api::function_instance::EventTimestamp::default();
+
+        let (api_sender_node_2, mut api_receiver_node_2) = futures::channel::mpsc::unbounded::<edgeless_api::invocation::Event>();
+        let mut provider = RemoteLinkProvider::new(node_id).await;
+        let mut undelivered = provider.take_undelivered().unwrap();
+        assert!(provider.take_undelivered().is_none());
+        provider
+            .add_peer(
+                node_id_2,
+                Box::new(FailingTargetInvocationAPI {
+                    failing_target: fid_failing,
+                    events: api_sender_node_2.clone(),
+                }),
+                connector(
+                    Box::new(MockInvocationAPI {
+                        own_node_id: node_id_2,
+                        events: api_sender_node_2,
+                    }),
+                    std::sync::Arc::default(),
+                ),
+            )
+            .await;
+
+        let (sender_1, _receiver_1) = crate::inbound_queue::inbound_queue(fid_source, crate::inbound_queue::InboundQueueSettings::default(), None);
+        let mut link = provider.new_link(fid_source, sender_1).await;
+
+        // The events accepted by the peer are not reported.
+        let res = link
+            .handle_send(
+                &fid_target,
+                Message::Cast(b"Test".to_vec()),
+                &fid_source,
+                &created,
+                0,
+                &metad_source,
+                Delivery::AtMostOnce,
+            )
+            .await;
+        assert_eq!(res, LinkProcessingResult::FINAL);
+        assert!(api_receiver_node_2.try_next().unwrap().is_some());
+        assert!(undelivered.try_recv().is_err());
+
+        // The casts and calls rejected by the peer are reported, the other
+        // events are not.
+        for msg in [Message::Cast(b"Cast".to_vec()), Message::Call(b"Call".to_vec()), Message::CallNoRet] {
+            let res = link
+                .handle_send(&fid_failing, msg, &fid_source, &created, 1, &metad_source, Delivery::AtMostOnce)
+                .await;
+            assert_eq!(res, LinkProcessingResult::FINAL);
+            assert!(api_receiver_node_2.try_next().unwrap().is_some());
+        }
+        for data in [
+            edgeless_api::invocation::EventData::Cast(b"Cast".to_vec()),
+            edgeless_api::invocation::EventData::Call(b"Call".to_vec()),
+        ] {
+            let (event, reason) = undelivered.try_recv().unwrap();
+            assert_eq!(fid_failing, event.target);
+            assert_eq!(1, event.stream_id);
+            assert_eq!(data, event.data);
+            assert_eq!("rejected by the remote node", reason);
+        }
+        assert!(undelivered.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn incomming_message_at_least_once() {
+        let node_id = uuid::Uuid::new_v4();
+        let fid_target = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_missing = edgeless_api::function_instance::InstanceId::new(node_id);
+        let fid_source = edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4());
+        let event = |target: edgeless_api::function_instance::InstanceId, event_id: u64| edgeless_api::invocation::Event {
+            target,
+            source: fid_source,
+            stream_id: 0,
+            data: edgeless_api::invocation::EventData::Cast(b"Test".to_vec()),
+            created: edgeless_api::function_instance::EventTimestamp::default(),
+            metadata: edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf0003cu128, 0x42a42bdecaf0003du64),
+            event_id,
+        };
+
+        let mut provider = RemoteLinkProvider::new(node_id).await;
+        let mut api = provider.incomming_api().await;
+        let (sender_1, receiver_1) = crate::inbound_queue::inbound_queue(fid_target, crate::inbound_queue::InboundQueueSettings::default(), None);
+        provider.new_link(fid_target, sender_1).await;
+
+        // Duplicates are acknowledged but not delivered.
+        for _ in 0..2 {
+            assert_eq!(LinkProcessingResult::FINAL, api.handle(event(fid_target, 42)).await.unwrap());
+        }
+        assert_eq!(1, receiver_1.len());
+        receiver_1.recv().await;
+        assert!(receiver_1.is_empty());
+
+        // Events towards components not hosted are not acknowledged.
+        assert!(api.handle(event(fid_missing, 43)).await.is_err());
+
+        // Events towards a component whose inbound queue is full are
+        // rejected without waiting, and accepted when retransmitted after
+        // the queue has room again.
+        let fid_slow = edgeless_api::function_instance::InstanceId::new(node_id);
+        let (sender_2, receiver_2) = crate::inbound_queue::inbound_queue(
+            fid_slow,
+            crate::inbound_queue::InboundQueueSettings {
+                capacity: 1,
+                overflow: crate::inbound_queue::OverflowPolicy::BlockLocal,
+            },
+            None,
+        );
+        provider.new_link(fid_slow, sender_2).await;
+        assert_eq!(LinkProcessingResult::FINAL, api.handle(event(fid_slow, 44)).await.unwrap());
+        assert!(api.handle(event(fid_slow, 45)).await.is_err());
+        assert!(api.handle(event(fid_slow, 0)).await.is_err());
+        assert_eq!(1, receiver_2.len());
+        receiver_2.recv().await;
+        assert_eq!(LinkProcessingResult::FINAL, api.handle(event(fid_slow, 45)).await.unwrap());
+        assert_eq!(1, receiver_2.len());
     }
 }
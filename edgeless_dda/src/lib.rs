@@ -107,6 +107,7 @@ where
     // available by the dda resource
     match call("dda", encode(event).as_slice()) {
         CallRet::Err => Err(format!("{:?}: did not work", event_name)),
+        CallRet::Timeout => Err(format!("{:?}: timed out", event_name)),
         CallRet::NoReply => Err(format!("{:?}: TODO timeout", event_name)),
         CallRet::Reply(reply) => match transform {
             Some(f) => f(decode(reply)),
@@ -125,6 +126,7 @@ pub fn publish_event(pub_alias: &str, data: Vec<u8>) -> Result<(), &'static str>
     log::info!("publish_event on {:?}", pub_alias);
     match call("dda", encode(message).as_slice()) {
         CallRet::Err => Err("publish_event: did not succeed"),
+        CallRet::Timeout => Err("publish_event: timed out"),
         CallRet::NoReply => Err("publish_event: TODO timeout"),
         CallRet::Reply(_) => Ok(()),
     }
@@ -135,6 +137,7 @@ pub fn publish_action(pub_alias: &str, data: Vec<u8>) -> Result<Vec<u8>, &'stati
     log::info!("publish_action on {:?}", pub_alias);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("publish_action: did not succeed"),
+        CallRet::Timeout => Err("publish_action: timed out"),
         CallRet::NoReply => Err("publish_action: TODO timeout"),
         CallRet::Reply(reply) => match decode(reply) {
             DDA::ComSubscribeActionResult(res) => Ok(res),
@@ -147,6 +150,7 @@ pub fn publish_action_result(correlation_id: String, result_data: Vec<u8>) -> Re
     let msg = DDA::ComPublishActionResult(correlation_id, result_data);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("publish_action_result: did not work"),
+        CallRet::Timeout => Err("publish_action_result: timed out"),
         CallRet::NoReply => Err("publish_action_result: TODO timeout"),
         // empty reply means success
         CallRet::Reply(_) => Ok(()),
@@ -157,6 +161,7 @@ pub fn publish_query(pub_alias: &str, data: Vec<u8>) -> Result<Vec<u8>, &'static
     let msg = DDA::ComPublishQuery(String::from(pub_alias), data);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("publish_query: did not succeed"),
+        CallRet::Timeout => Err("publish_query: timed out"),
         CallRet::NoReply => Err("publish_query: TODO timeout"),
         CallRet::Reply(reply) => match decode(reply) {
             DDA::ComSubscribeQueryResult(res) => Ok(res),
@@ -169,6 +174,7 @@ pub fn publish_query_result(correlation_id: String, result_data: Vec<u8>) -> Res
     let msg = DDA::ComPublishQueryResult(correlation_id, result_data);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("publish_query_result: did not work"),
+        CallRet::Timeout => Err("publish_query_result: timed out"),
         CallRet::NoReply => Err("publish_query_result: TODO timeout"),
         // empty reply means success
         CallRet::Reply(_) => Ok(()),
@@ -183,6 +189,7 @@ pub fn state_propose_set(key: String, value: Vec<u8>) -> Result<(), &'static str
     let msg = DDA::StatePublishSet(key, value);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("state_propose_set: did not work"),
+        CallRet::Timeout => Err("state_propose_set: timed out"),
         CallRet::NoReply => Err("state_propose_set: TODO timeout"),
         // empty reply means success
         CallRet::Reply(_) => Ok(()),
@@ -193,6 +200,7 @@ pub fn state_propose_delete(key: String) -> Result<(), &'static str> {
     let msg = DDA::StatePublishDelete(key);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("state_propose_delete: did not work"),
+        CallRet::Timeout => Err("state_propose_delete: timed out"),
         CallRet::NoReply => Err("state_propose_delete: TODO timeout"),
         // empty reply means success
         CallRet::Reply(_) => Ok(()),
@@ -206,6 +214,7 @@ pub fn store_get(key: String) -> Result<Vec<u8>, &'static str> {
     let msg = DDA::StoreGet(key);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("store_get: did not work"),
+        CallRet::Timeout => Err("store_get: timed out"),
         CallRet::NoReply => Err("store_get: TODO timeout"),
         CallRet::Reply(reply) => match decode(reply) {
             DDA::StoreGetResult(data) => Ok(data),
@@ -218,6 +227,7 @@ pub fn store_set(key: String, value: Vec<u8>) -> Result<(), &'static str> {
     let msg = DDA::StoreSet(key, value);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("store_set: did not work"),
+        CallRet::Timeout => Err("store_set: timed out"),
         CallRet::NoReply => Err("store_set: TODO timeout"),
         // empty reply means success
         CallRet::Reply(_) => Ok(()),
@@ -228,6 +238,7 @@ pub fn store_delete(key: String) -> Result<(), &'static str> {
     let msg = DDA::StoreDelete(key);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("store_delete: did not work"),
+        CallRet::Timeout => Err("store_delete: timed out"),
         CallRet::NoReply => Err("store_delete: TODO timeout"),
         // empty reply means success
         CallRet::Reply(_) => Ok(()),
@@ -238,6 +249,7 @@ pub fn store_delete_all() -> Result<(), &'static str> {
     let msg = DDA::StoreDeleteAll();
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("store_delete_all: did not work"),
+        CallRet::Timeout => Err("store_delete_all: timed out"),
         CallRet::NoReply => Err("store_delete_all: TODO timeout"),
         // empty reply means success
         CallRet::Reply(_) => Ok(()),
@@ -248,6 +260,7 @@ pub fn store_delete_prefix(prefix: String) -> Result<(), &'static str> {
     let msg = DDA::StoreDeletePrefix(prefix);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("store_delete_prefix: did not work"),
+        CallRet::Timeout => Err("store_delete_prefix: timed out"),
         CallRet::NoReply => Err("store_delete_prefix: TODO timeout"),
         // empty reply means success
         CallRet::Reply(_) => Ok(()),
@@ -258,6 +271,7 @@ pub fn store_delete_range(start: String, end: String) -> Result<(), &'static str
     let msg = DDA::StoreDeleteRange(start, end);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("store_delete_range: did not work"),
+        CallRet::Timeout => Err("store_delete_range: timed out"),
         CallRet::NoReply => Err("store_delete_range: TODO timeout"),
         // empty reply means success
         CallRet::Reply(_) => Ok(()),
@@ -268,6 +282,7 @@ pub fn store_scan_prefix(prefix: String) -> Result<(String, Vec<u8>), &'static s
     let msg = DDA::StoreScanPrefix(prefix);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("store_scan_prefix: did not work"),
+        CallRet::Timeout => Err("store_scan_prefix: timed out"),
         CallRet::NoReply => Err("store_scan_prefix: TODO timeout"),
         CallRet::Reply(reply) => match decode(reply) {
             DDA::StoreScanPrefixResult(key, data) => Ok((key, data)),
@@ -280,6 +295,7 @@ pub fn store_scan_range(start: String, end: String) -> Result<(String, Vec<u8>),
     let msg = DDA::StoreScanRange(start, end);
     match call("dda", encode(msg).as_slice()) {
         CallRet::Err => Err("store_scan_range: did not work"),
+        CallRet::Timeout => Err("store_scan_range: timed out"),
         CallRet::NoReply => Err("store_scan_range: TODO timeout"),
         CallRet::Reply(reply) => match decode(reply) {
             DDA::StoreScanRangeResult(key, data) => Ok((key, data)),
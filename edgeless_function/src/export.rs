@@ -38,6 +38,7 @@ macro_rules! export {
                 CallRet::NoReply => (0, None),
                 CallRet::Reply(reply) => (1, Some(reply.consume())),
                 CallRet::Err => (2, None),
+                CallRet::Timeout => (3, None),
             };
             if let (Some((output_ptr, output_len))) = output_params {
                 *out_ptr_ptr = output_ptr;
@@ -72,5 +73,10 @@ macro_rules! export {
         pub extern "C" fn handle_stop_asm() {
             $fun::handle_stop()
         }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn handle_sync_asm() {
+            $fun::handle_sync()
+        }
     };
 }
@@ -37,6 +37,15 @@ pub enum CallRet {
     NoReply,
     Reply(owned_data::OwnedByteBuff),
     Err,
+    /// The call was not answered before its deadline.
+    Timeout,
+}
+
+/// The state has been modified by another instance since it was last read
+/// or written by this one.
+pub struct StateConflict {
+    /// Current state, if any.
+    pub current_state: Option<owned_data::OwnedByteBuff>,
 }
 
 pub struct InstanceId {
@@ -51,6 +60,9 @@ pub trait EdgeFunction {
     fn handle_call(src: InstanceId, encoded_message: &[u8]) -> CallRet;
     fn handle_init(payload: Option<&[u8]>, _serialized_state: Option<&[u8]>);
     fn handle_stop();
+    /// Called when the host needs the latest state of the function instance,
+    /// e.g., before migrating it: the function should call `sync()`.
+    fn handle_sync() {}
 }
 
 #[cfg(feature = "std")]
@@ -36,6 +36,7 @@ pub fn call_raw(target: crate::InstanceId, msg: &[u8]) -> crate::CallRet {
         match call_ret_type {
             0 => crate::CallRet::NoReply,
             1 => crate::CallRet::Reply(crate::owned_data::OwnedByteBuff::new(out_ptr_ptr, out_len_ptr)),
+            3 => crate::CallRet::Timeout,
             _ => crate::CallRet::Err,
         }
     }
@@ -57,6 +58,7 @@ pub fn call(name: &str, msg: &[u8]) -> crate::CallRet {
         match call_ret_type {
             0 => crate::CallRet::NoReply,
             1 => crate::CallRet::Reply(crate::owned_data::OwnedByteBuff::new(out_ptr_ptr, out_len_ptr)),
+            3 => crate::CallRet::Timeout,
             _ => crate::CallRet::Err,
         }
     }
@@ -79,8 +81,41 @@ pub fn slf() -> crate::InstanceId {
     }
 }
 
+/// Synchronize the state of the function instance.
+///
+/// If the state has been modified by another instance since this one last
+/// read or wrote it, then it is not overwritten: use `try_sync()` to be
+/// notified of the conflict.
 pub fn sync(state: &[u8]) {
+    if try_sync(state).is_err() {
+        log::warn!("state not synchronized because of a conflict with another instance");
+    }
+}
+
+/// Synchronize the state of the function instance, like `sync()`.
+///
+/// If the state has been modified by another instance since this one last
+/// read or wrote it, then it is not overwritten and the current state is
+/// returned, so that the function can apply its changes on top of it
+/// before synchronizing again.
+pub fn try_sync(state: &[u8]) -> Result<(), crate::StateConflict> {
     unsafe {
-        crate::imports::sync_asm(state.as_ptr(), state.len() as u32);
+        let mut out_ptr_ptr: *mut u8 = core::ptr::null_mut();
+        let mut out_len_ptr: usize = 0;
+
+        let sync_ret_type = crate::imports::sync_asm(
+            state.as_ptr(),
+            state.len() as u32,
+            &mut out_ptr_ptr as *mut *mut u8,
+            &mut out_len_ptr as *mut usize,
+        );
+
+        match sync_ret_type {
+            0 => Ok(()),
+            1 => Err(crate::StateConflict {
+                current_state: Some(crate::owned_data::OwnedByteBuff::new(out_ptr_ptr, out_len_ptr)),
+            }),
+            _ => Err(crate::StateConflict { current_state: None }),
+        }
     }
 }
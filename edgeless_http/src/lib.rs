@@ -79,14 +79,14 @@ pub fn response_from_string(response_str: &str) -> anyhow::Result<EdgelessHTTPRe
     Ok(serde_json::from_str(response_str)?)
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
 pub enum EdgelessHTTPProtocol {
     Unknown,
     HTTP,
     HTTPS,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
 pub struct EdgelessHTTPRequest {
     pub method: EdgelessHTTPMethod,
     pub protocol: EdgelessHTTPProtocol,
@@ -102,3 +102,113 @@ pub struct EdgelessHTTPResponse {
     pub status: u16,
     pub headers: std::collections::HashMap<String, String>,
 }
+
+/// Default maximum size of the body of an HTTP request, in bytes.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Return true if the size of a body exceeds the maximum, 0 meaning no limit.
+pub fn exceeds(size: usize, max_body_bytes: usize) -> bool {
+    max_body_bytes != 0 && size > max_body_bytes
+}
+
+/// Return the maximum size of a body, 0 meaning no limit.
+pub fn max_body_limit(max_body_bytes: usize) -> usize {
+    if max_body_bytes == 0 { usize::MAX } else { max_body_bytes }
+}
+
+/// Return the maximum size of a body from the `max_body_bytes` entry of a
+/// resource configuration, if any, or the default value.
+pub fn max_body_bytes(configuration: &std::collections::HashMap<String, String>) -> usize {
+    configuration
+        .get("max_body_bytes")
+        .and_then(|val| val.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_BODY_BYTES)
+}
+
+/// Prefix of the serialized chunks, used to tell them from other events.
+const CHUNK_MAGIC: &[u8; 4] = b"EHC1";
+
+/// Size of the header of a serialized chunk: magic, stream identifier,
+/// sequence number, and flags.
+const CHUNK_HEADER_LEN: usize = 4 + 8 + 8 + 1;
+
+/// Chunk of an HTTP request streamed as a sequence of events.
+///
+/// The first chunk of a stream, with sequence number 0, contains the
+/// request serialized with `request_to_string` without body, and the
+/// following ones contain the body, split in pieces. The last chunk of the
+/// stream is flagged as such, while the stream is interrupted by a chunk
+/// flagged as aborted, e.g., because the body exceeds the maximum size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgelessHTTPChunk {
+    pub stream_id: u64,
+    pub seq: u64,
+    pub last: bool,
+    pub aborted: bool,
+    pub data: Vec<u8>,
+}
+
+impl EdgelessHTTPChunk {
+    /// Serialize the chunk into the payload of an event.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut ret = Vec::with_capacity(CHUNK_HEADER_LEN + self.data.len());
+        ret.extend_from_slice(CHUNK_MAGIC);
+        ret.extend_from_slice(&self.stream_id.to_be_bytes());
+        ret.extend_from_slice(&self.seq.to_be_bytes());
+        ret.push((self.last as u8) | ((self.aborted as u8) << 1));
+        ret.extend_from_slice(&self.data);
+        ret
+    }
+
+    /// Deserialize the chunk from the payload of an event.
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        if !Self::is_chunk(data) {
+            anyhow::bail!("Not an HTTP chunk");
+        }
+        let flags = data[20];
+        Ok(Self {
+            stream_id: u64::from_be_bytes(data[4..12].try_into()?),
+            seq: u64::from_be_bytes(data[12..20].try_into()?),
+            last: flags & 1 != 0,
+            aborted: flags & 2 != 0,
+            data: data[CHUNK_HEADER_LEN..].to_vec(),
+        })
+    }
+
+    /// Return true if the payload of an event is a serialized chunk.
+    pub fn is_chunk(data: &[u8]) -> bool {
+        data.len() >= CHUNK_HEADER_LEN && &data[..4] == CHUNK_MAGIC
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chunk_encode_decode() {
+        let chunk = EdgelessHTTPChunk {
+            stream_id: 42,
+            seq: 7,
+            last: true,
+            aborted: false,
+            data: vec![0, 1, 2, 255],
+        };
+        let encoded = chunk.encode();
+        assert!(EdgelessHTTPChunk::is_chunk(&encoded));
+        assert_eq!(chunk, EdgelessHTTPChunk::decode(&encoded).unwrap());
+
+        let chunk = EdgelessHTTPChunk {
+            stream_id: u64::MAX,
+            seq: 0,
+            last: false,
+            aborted: true,
+            data: vec![],
+        };
+        assert_eq!(chunk, EdgelessHTTPChunk::decode(&chunk.encode()).unwrap());
+
+        assert!(!EdgelessHTTPChunk::is_chunk(b"hello world, not a chunk"));
+        assert!(EdgelessHTTPChunk::decode(b"hello world, not a chunk").is_err());
+        assert!(EdgelessHTTPChunk::decode(b"EHC1").is_err());
+    }
+}
@@ -146,6 +146,11 @@ fn generate_configs(config_path: String, number_of_nodes: u32, initial_port: u16
                 invocation_url_announced_coap: None,
                 node_register_url: orc_conf.general.node_register_url.clone(),
                 subscription_refresh_interval_sec: 2,
+                call_timeout_ms: Some(60000),
+                inbound_queue_capacity: 10000,
+                inbound_queue_overflow: edgeless_dataplane::inbound_queue::OverflowPolicy::BlockLocal,
+                invocation_transport: edgeless_dataplane::core::InvocationTransport::default(),
+                invocation_max_batch_size: None,
             },
             telemetry: EdgelessNodeTelemetrySettings {
                 metrics_url: next_url(false),
@@ -156,7 +161,11 @@ fn generate_configs(config_path: String, number_of_nodes: u32, initial_port: u16
             resources: Some(EdgelessNodeResourceSettings {
                 prepend_hostname: true,
                 http_ingress_url: Some(next_url(false)),
+                http_ingress_tls_cert: None,
+                http_ingress_tls_key: None,
                 http_ingress_provider: Some(format!("http-ingress-{}", counter + 1)),
+                ws_ingress_url: Some(next_url(false)),
+                ws_ingress_provider: Some(format!("ws-ingress-{}", counter + 1)),
                 http_egress_provider: Some(format!("http-egress-{}", counter + 1)),
                 http_poster_provider: Some(format!("http-poster-{}", counter + 1)),
                 file_pusher_provider: Some(FilePusherSettings::default()),
@@ -166,10 +175,17 @@ fn generate_configs(config_path: String, number_of_nodes: u32, initial_port: u16
                 ollama_provider: Some(OllamaProviderSettings::default()),
                 serverless_provider: Some(vec![ServerlessProviderSettings::default()]),
                 kafka_egress_provider: Some(String::default()),
+                kafka_ingress_provider: Some(String::default()),
+                mqtt_ingress_provider: Some(String::default()),
+                mqtt_egress_provider: Some(String::default()),
+                object_store_provider: Some(format!("object-store-{}", counter + 1)),
+                grpc_egress_provider: Some(String::default()),
+                timer_provider: Some(format!("timer-{}", counter + 1)),
                 sqlx_provider: Some(format!("sqlx-{}", counter + 1)),
             }),
             user_node_capabilities: Some(edgeless_node::NodeCapabilitiesUser::default()),
             power_info: None,
+            state_management: None,
         });
     }
 
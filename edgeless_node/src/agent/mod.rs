@@ -31,6 +31,22 @@ enum AgentRequest {
     // Node management API.
     UpdatePeers(edgeless_api::node_management::UpdatePeersRequest),
     Reset(),
+    SyncState(
+        edgeless_api::function_instance::InstanceId,
+        futures::channel::oneshot::Sender<anyhow::Result<()>>,
+    ),
+    HandOver(
+        edgeless_api::node_management::HandOverRequest,
+        futures::channel::oneshot::Sender<anyhow::Result<()>>,
+    ),
+    GetState(
+        edgeless_api::function_instance::StateSpecification,
+        futures::channel::oneshot::Sender<anyhow::Result<Option<edgeless_api::node_management::SerializedState>>>,
+    ),
+    SetState(
+        edgeless_api::node_management::SetStateRequest,
+        futures::channel::oneshot::Sender<anyhow::Result<()>>,
+    ),
 }
 
 pub struct Agent {
@@ -48,6 +64,7 @@ impl Agent {
         resources: std::collections::HashMap<String, ResourceDesc>,
         node_id: uuid::Uuid,
         data_plane_provider: edgeless_dataplane::handle::DataplaneProvider,
+        state_manager: Option<Box<dyn crate::state_management::StateManagerAPI>>,
     ) -> (Self, std::pin::Pin<Box<dyn Future<Output = ()> + Send>>) {
         let (sender, receiver) = futures::channel::mpsc::unbounded();
 
@@ -56,7 +73,7 @@ impl Agent {
         }
 
         let main_task = Box::pin(async move {
-            Self::main_task(node_id, receiver, runners, resources, data_plane_provider).await;
+            Self::main_task(node_id, receiver, runners, resources, data_plane_provider, state_manager).await;
         });
 
         (Agent { sender }, main_task)
@@ -68,9 +85,11 @@ impl Agent {
         function_runtimes: std::collections::HashMap<String, Box<dyn crate::base_runtime::RuntimeAPI + Send>>,
         resources: std::collections::HashMap<String, ResourceDesc>,
         data_plane_provider: edgeless_dataplane::handle::DataplaneProvider,
+        state_manager: Option<Box<dyn crate::state_management::StateManagerAPI>>,
     ) {
         let mut receiver = std::pin::pin!(receiver);
         let mut data_plane_provider = data_plane_provider;
+        let mut state_manager = state_manager;
 
         // key:   function class
         // value: function run-time API
@@ -97,8 +116,23 @@ impl Agent {
                 AgentRequest::SpawnFunction(spawn_req, responder) => {
                     log::debug!("Agent SpawnFunction {:?}", spawn_req.spec.to_short_string());
 
+                    // Check that the state policy of the function is supported.
+                    let state_check = match &mut state_manager {
+                        Some(state_manager) => state_manager
+                            .get_handle(spawn_req.state_specification.state_policy.clone(), spawn_req.state_specification.state_id)
+                            .await
+                            .map(|_| ()),
+                        None => Ok(()),
+                    };
+
                     // Get runner for function_class of spawn_req
                     let res = match function_runtimes.get_mut(&spawn_req.spec.function_type) {
+                        Some(_) if state_check.is_err() => {
+                            edgeless_api::common::StartComponentResponse::ResponseError(edgeless_api::common::ResponseError {
+                                summary: "Could not start function".to_string(),
+                                detail: state_check.err().map(|err| format!("{}", err)),
+                            })
+                        }
                         Some(runner) => {
                             // Assign a new physical identifier to the function instance being created.
                             let instance_id = edgeless_api::function_instance::InstanceId::new(node_id);
@@ -106,6 +140,11 @@ impl Agent {
                             // Save function_class for further interaction.
                             function_instances.insert(instance_id.function_id, spawn_req.spec.function_type.clone());
 
+                            // Select the output channels delivered at least once.
+                            data_plane_provider
+                                .set_at_least_once_outputs(instance_id.function_id, &spawn_req.annotations)
+                                .await;
+
                             // Forward the start request to the matching runtime.
                             match runner.start(instance_id, spawn_req).await {
                                 Ok(_) => edgeless_api::common::StartComponentResponse::InstanceId(instance_id),
@@ -128,12 +167,18 @@ impl Agent {
                     log::debug!("Agent StopFunction {:?}", stop_function_id);
 
                     Self::stop_function(&mut function_runtimes, &mut function_instances, stop_function_id).await;
+                    data_plane_provider.remove_output_splits(&stop_function_id.function_id).await;
+                    data_plane_provider.remove_at_least_once_outputs(&stop_function_id.function_id).await;
+                    data_plane_provider.remove_dead_letter(&stop_function_id.function_id).await;
                 }
 
                 // PatchRequest contains function_id: ComponentId
                 AgentRequest::PatchFunction(update) => {
                     log::debug!("Agent PatchFunction {:?}", update);
 
+                    data_plane_provider.patch_output_splits(&update).await;
+                    data_plane_provider.patch_dead_letter(&update).await;
+
                     // Get function class by looking it up in the instanceId->functionClass map
                     // and then orward the patch request to the correct runner.
                     match function_instances.get(&update.function_id) {
@@ -206,6 +251,8 @@ impl Agent {
                 AgentRequest::StopResource(resource_id, responder) => {
                     log::debug!("Agent StopResource {:?}", resource_id);
 
+                    data_plane_provider.remove_output_splits(&resource_id.function_id).await;
+                    data_plane_provider.remove_dead_letter(&resource_id.function_id).await;
                     responder
                         .send(Self::stop_resource(&mut resource_providers, &mut resource_instances, resource_id).await)
                         .unwrap_or_else(|_| log::warn!("Agent StopResource: responder send error"));
@@ -213,6 +260,9 @@ impl Agent {
                 AgentRequest::PatchResource(update, responder) => {
                     log::debug!("Agent PatchResource {:?}", update);
 
+                    data_plane_provider.patch_output_splits(&update).await;
+                    data_plane_provider.patch_dead_letter(&update).await;
+
                     let res = if let Some(provider_id) = resource_instances.get(&update.function_id) {
                         if let Some(resource_desc) = resource_providers.get_mut(provider_id) {
                             resource_desc.client.patch(update).await
@@ -267,6 +317,103 @@ impl Agent {
                         }
                     }
                     resource_instances.clear();
+
+                    data_plane_provider.clear_output_splits().await;
+                    data_plane_provider.clear_at_least_once_outputs().await;
+                    data_plane_provider.clear_dead_letters().await;
+                }
+                AgentRequest::SyncState(instance_id, responder) => {
+                    log::debug!("Agent SyncState {:?}", instance_id);
+
+                    let runner = function_instances
+                        .get(&instance_id.function_id)
+                        .and_then(|function_class| function_runtimes.get_mut(function_class));
+                    let synced = match runner {
+                        Some(runner) => runner.sync(instance_id).await,
+                        None => Err(anyhow::anyhow!("Could not find the runner of function instance {}", instance_id)),
+                    };
+                    // Wait for the function instance in a separate task, so
+                    // that the agent keeps handling the other requests.
+                    match synced {
+                        Ok(synced) => {
+                            tokio::spawn(async move {
+                                let res = synced.await.unwrap_or_else(|_| Err(anyhow::anyhow!("Runner Channel Error")));
+                                responder
+                                    .send(res)
+                                    .unwrap_or_else(|_| log::warn!("Agent SyncState: responder send error"));
+                            });
+                        }
+                        Err(err) => responder
+                            .send(Err(err))
+                            .unwrap_or_else(|_| log::warn!("Agent SyncState: responder send error")),
+                    }
+                }
+                AgentRequest::HandOver(request, responder) => {
+                    log::debug!("Agent HandOver {:?}", request);
+
+                    let runner = function_instances
+                        .get(&request.origin.function_id)
+                        .and_then(|function_class| function_runtimes.get_mut(function_class));
+                    let handed_over = match runner {
+                        Some(runner) => runner.hand_over(request.origin, request.target).await,
+                        None => Err(anyhow::anyhow!("Could not find the runner of function instance {}", request.origin)),
+                    };
+                    match handed_over {
+                        Ok(handed_over) => {
+                            tokio::spawn(async move {
+                                let res = handed_over.await.unwrap_or_else(|_| Err(anyhow::anyhow!("Runner Channel Error")));
+                                responder.send(res).unwrap_or_else(|_| log::warn!("Agent HandOver: responder send error"));
+                            });
+                        }
+                        Err(err) => responder
+                            .send(Err(err))
+                            .unwrap_or_else(|_| log::warn!("Agent HandOver: responder send error")),
+                    }
+                }
+                AgentRequest::GetState(state_specification, responder) => {
+                    log::debug!("Agent GetState {:?}", state_specification);
+
+                    // The state returned is the one last synchronized by the
+                    // function instance, since synchronizations are written
+                    // through to the state provider.
+                    let res = match &mut state_manager {
+                        Some(state_manager) => match state_manager
+                            .get_handle(state_specification.state_policy, state_specification.state_id)
+                            .await
+                        {
+                            Ok(mut state_handle) => {
+                                Ok(state_handle
+                                    .get()
+                                    .await
+                                    .map(|serialized_state| edgeless_api::node_management::SerializedState {
+                                        serialized_state,
+                                        version: state_handle.version(),
+                                    }))
+                            }
+                            Err(err) => Err(err),
+                        },
+                        None => Err(anyhow::anyhow!("No state manager at node {}", node_id)),
+                    };
+                    responder.send(res).unwrap_or_else(|_| log::warn!("Agent GetState: responder send error"));
+                }
+                AgentRequest::SetState(request, responder) => {
+                    log::debug!("Agent SetState {:?}", request.state_specification);
+
+                    let res = match &mut state_manager {
+                        Some(state_manager) => {
+                            match state_manager
+                                .get_handle(request.state_specification.state_policy, request.state_specification.state_id)
+                                .await
+                            {
+                                // The state is not saved if it has been
+                                // modified since the sender read its version.
+                                Ok(mut state_handle) => state_handle.set_on(request.serialized_state, request.expected_version).await,
+                                Err(err) => Err(err),
+                            }
+                        }
+                        None => Err(anyhow::anyhow!("No state manager at node {}", node_id)),
+                    };
+                    responder.send(res).unwrap_or_else(|_| log::warn!("Agent SetState: responder send error"));
                 }
             }
         }
@@ -408,6 +555,50 @@ impl edgeless_api::node_management::NodeManagementAPI for NodeManagementClient {
             .await
             .map_err(|err| anyhow::anyhow!("Agent channel error when resetting a node: {}", err))
     }
+    async fn sync_state(&mut self, instance_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
+        let (rsp_sender, rsp_receiver) = futures::channel::oneshot::channel::<anyhow::Result<()>>();
+        self.sender
+            .send(AgentRequest::SyncState(instance_id, rsp_sender))
+            .await
+            .map_err(|err| anyhow::anyhow!("Agent channel error when synchronizing a state: {}", err))?;
+        rsp_receiver
+            .await
+            .map_err(|err| anyhow::anyhow!("Agent channel error when synchronizing a state: {}", err))?
+    }
+    async fn hand_over(&mut self, request: edgeless_api::node_management::HandOverRequest) -> anyhow::Result<()> {
+        let (rsp_sender, rsp_receiver) = futures::channel::oneshot::channel::<anyhow::Result<()>>();
+        self.sender
+            .send(AgentRequest::HandOver(request, rsp_sender))
+            .await
+            .map_err(|err| anyhow::anyhow!("Agent channel error when handing over events: {}", err))?;
+        rsp_receiver
+            .await
+            .map_err(|err| anyhow::anyhow!("Agent channel error when handing over events: {}", err))?
+    }
+    async fn get_state(
+        &mut self,
+        state_specification: edgeless_api::function_instance::StateSpecification,
+    ) -> anyhow::Result<Option<edgeless_api::node_management::SerializedState>> {
+        let (rsp_sender, rsp_receiver) =
+            futures::channel::oneshot::channel::<anyhow::Result<Option<edgeless_api::node_management::SerializedState>>>();
+        self.sender
+            .send(AgentRequest::GetState(state_specification, rsp_sender))
+            .await
+            .map_err(|err| anyhow::anyhow!("Agent channel error when getting a state: {}", err))?;
+        rsp_receiver
+            .await
+            .map_err(|err| anyhow::anyhow!("Agent channel error when getting a state: {}", err))?
+    }
+    async fn set_state(&mut self, request: edgeless_api::node_management::SetStateRequest) -> anyhow::Result<()> {
+        let (rsp_sender, rsp_receiver) = futures::channel::oneshot::channel::<anyhow::Result<()>>();
+        self.sender
+            .send(AgentRequest::SetState(request, rsp_sender))
+            .await
+            .map_err(|err| anyhow::anyhow!("Agent channel error when setting a state: {}", err))?;
+        rsp_receiver
+            .await
+            .map_err(|err| anyhow::anyhow!("Agent channel error when setting a state: {}", err))?
+    }
 }
 
 #[async_trait::async_trait]
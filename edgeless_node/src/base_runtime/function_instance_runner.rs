@@ -17,13 +17,25 @@ pub struct FunctionInstanceRunner<FunctionInstanceType: FunctionInstance> {
     task_handle: Option<tokio::task::JoinHandle<()>>,
     alias_mapping: super::alias_mapping::AliasMapping,
     poison_pill_sender: tokio::sync::broadcast::Sender<()>,
+    sync_sender: tokio::sync::mpsc::UnboundedSender<SyncResponder>,
+    hand_over_sender: tokio::sync::mpsc::UnboundedSender<HandOverRequest>,
     _instance: PhantomData<FunctionInstanceType>,
 }
 
+/// Channel to notify the completion of a synchronization of the state.
+type SyncResponder = tokio::sync::oneshot::Sender<Result<(), FunctionInstanceError>>;
+
+/// Request to forward the events received after the synchronization of the
+/// state to the instance replacing this one, with the channel to notify
+/// when the events pending have been forwarded.
+type HandOverRequest = (edgeless_api::function_instance::InstanceId, tokio::sync::oneshot::Sender<()>);
+
 /// This is a runnable object (with all required state) actually executing a function.
 /// It is managed/owned by a FunctionInstanceRunner, which also runs it using a tokio task.
 struct FunctionInstanceTask<FunctionInstanceType: FunctionInstance> {
     poison_pill_receiver: tokio::sync::broadcast::Receiver<()>,
+    sync_receiver: tokio::sync::mpsc::UnboundedReceiver<SyncResponder>,
+    hand_over_receiver: tokio::sync::mpsc::UnboundedReceiver<HandOverRequest>,
     function_instance: Option<Box<FunctionInstanceType>>,
     guest_api_host: Option<super::guest_api::GuestAPIHost>,
     telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
@@ -36,6 +48,8 @@ struct FunctionInstanceTask<FunctionInstanceType: FunctionInstance> {
     runtime_api: futures::channel::mpsc::UnboundedSender<super::runtime::RuntimeRequest>,
     instance_id: edgeless_api::function_instance::InstanceId,
     event_metadata: Arc<Mutex<Option<edgeless_api::function_instance::EventMetadata>>>,
+    // Depth of the inbound queue last reported via telemetry.
+    queue_depth: usize,
 }
 
 impl<FunctionInstanceType: FunctionInstance> FunctionInstanceRunner<FunctionInstanceType> {
@@ -53,6 +67,8 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceRunner<FunctionInst
 
         let alias_mapping = super::alias_mapping::AliasMapping::new();
         let (poison_pill_sender, poison_pill_receiver) = tokio::sync::broadcast::channel::<()>(1);
+        let (sync_sender, sync_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let (hand_over_sender, hand_over_receiver) = tokio::sync::mpsc::unbounded_channel();
         let serialized_state = state_handle.get().await;
 
         let shared_ev_mt = Arc::new(Mutex::new(None));
@@ -70,6 +86,8 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceRunner<FunctionInst
         let task = Box::new(
             FunctionInstanceTask::<FunctionInstanceType>::new(
                 poison_pill_receiver,
+                sync_receiver,
+                hand_over_receiver,
                 telemetry_handle,
                 guest_api_host_register,
                 guest_api_host,
@@ -96,6 +114,8 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceRunner<FunctionInst
             task_handle: Some(task_handle),
             alias_mapping,
             poison_pill_sender,
+            sync_sender,
+            hand_over_sender,
             _instance: PhantomData {},
         }
     }
@@ -111,12 +131,49 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceRunner<FunctionInst
     pub async fn patch(&mut self, update_request: edgeless_api::common::PatchRequest) {
         self.alias_mapping.update(update_request.output_mapping).await;
     }
+
+    /// Ask the function instance to synchronize its state, in between the
+    /// processing of two events, e.g., before it is migrated.
+    /// The future returned completes when the state has been synchronized,
+    /// after which the function instance does not process events anymore.
+    pub fn sync(&mut self) -> impl std::future::Future<Output = anyhow::Result<()>> + Send + 'static {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let sent = self.sync_sender.send(sender).is_ok();
+        async move {
+            anyhow::ensure!(sent, "function instance terminated");
+            match receiver.await {
+                Ok(res) => res.map_err(|err| anyhow::anyhow!("sync failed: {:?}", err)),
+                Err(_) => anyhow::bail!("function instance terminated"),
+            }
+        }
+    }
+
+    /// Ask the function instance, after it has synchronized its state, to
+    /// forward the events it receives to the instance replacing it, e.g.,
+    /// after a migration, instead of leaving them in its inbound queue.
+    /// The future returned completes when the events pending have been
+    /// forwarded.
+    pub fn hand_over(
+        &mut self,
+        target: edgeless_api::function_instance::InstanceId,
+    ) -> impl std::future::Future<Output = anyhow::Result<()>> + Send + 'static {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let sent = self.hand_over_sender.send((target, sender)).is_ok();
+        async move {
+            anyhow::ensure!(sent, "function instance terminated");
+            receiver
+                .await
+                .map_err(|_| anyhow::anyhow!("function instance terminated or not synchronized"))
+        }
+    }
 }
 
 impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstanceType> {
     #[allow(clippy::too_many_arguments)]
     pub async fn new(
         poison_pill_receiver: tokio::sync::broadcast::Receiver<()>,
+        sync_receiver: tokio::sync::mpsc::UnboundedReceiver<SyncResponder>,
+        hand_over_receiver: tokio::sync::mpsc::UnboundedReceiver<HandOverRequest>,
         telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
         guest_api_host_register: std::sync::Arc<tokio::sync::Mutex<Box<dyn super::runtime::GuestAPIHostRegister + Send>>>,
         guest_api_host: super::guest_api::GuestAPIHost,
@@ -131,6 +188,8 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
     ) -> Self {
         Self {
             poison_pill_receiver,
+            sync_receiver,
+            hand_over_receiver,
             function_instance: None,
             guest_api_host: Some(guest_api_host),
             telemetry_handle,
@@ -143,6 +202,7 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
             runtime_api,
             instance_id,
             event_metadata,
+            queue_depth: 0,
         }
     }
 
@@ -218,6 +278,17 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
                 _ = Box::pin(self.poison_pill_receiver.recv()).fuse() => {
                     return self.stop().await;
                 },
+                // The state is synchronized on request, e.g., before a migration
+                responder = Box::pin(self.sync_receiver.recv()).fuse() => {
+                    if let Some(responder) = responder {
+                        let res = self.sync().await;
+                        let synced = res.is_ok();
+                        let _ = responder.send(res);
+                        if synced {
+                            return self.quiesced_loop().await;
+                        }
+                    }
+                },
                 // Receive a normal event from the dataplane and invoke the function instance
                 edgeless_dataplane::core::DataplaneEvent{source_id, channel_id, message, created, metadata} =  Box::pin(self.data_plane.receive_next()).fuse() => {
                     self.process_message(
@@ -232,6 +303,91 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
         }
     }
 
+    /// Wait for the function instance to be stopped without processing
+    /// events, so that its state does not change after the last
+    /// synchronization.
+    ///
+    /// The events received in the meanwhile are kept in the inbound queue
+    /// until they are forwarded to the instance replacing this one, once
+    /// known, see `hand_over()`. If the instance is stopped before, then
+    /// the casts pending are forwarded to the dead-letter function/resource,
+    /// if any, while the callers are replied to with an error.
+    async fn quiesced_loop(&mut self) -> Result<(), super::FunctionInstanceError> {
+        loop {
+            futures::select! {
+                _ = Box::pin(self.poison_pill_receiver.recv()).fuse() => {
+                    self.drain_quiesced(None).await;
+                    return self.stop().await;
+                },
+                responder = Box::pin(self.sync_receiver.recv()).fuse() => {
+                    if let Some(responder) = responder {
+                        let _ = responder.send(Ok(()));
+                    }
+                },
+                request = Box::pin(self.hand_over_receiver.recv()).fuse() => {
+                    if let Some((target, responder)) = request {
+                        log::info!("Function instance {:?} handing over its events to {:?}", self.instance_id, target);
+                        self.drain_quiesced(Some(target)).await;
+                        let _ = responder.send(());
+                        return self.handed_over_loop(target).await;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Forward the events received by a quiesced function instance to the
+    /// instance replacing it until it is stopped.
+    async fn handed_over_loop(&mut self, target: edgeless_api::function_instance::InstanceId) -> Result<(), super::FunctionInstanceError> {
+        loop {
+            futures::select! {
+                _ = Box::pin(self.poison_pill_receiver.recv()).fuse() => {
+                    self.drain_quiesced(Some(target)).await;
+                    return self.stop().await;
+                },
+                responder = Box::pin(self.sync_receiver.recv()).fuse() => {
+                    if let Some(responder) = responder {
+                        let _ = responder.send(Ok(()));
+                    }
+                },
+                request = Box::pin(self.hand_over_receiver.recv()).fuse() => {
+                    if let Some((_, responder)) = request {
+                        let _ = responder.send(());
+                    }
+                },
+                event = Box::pin(self.data_plane.receive_next()).fuse() => {
+                    self.data_plane.redirect(target, event).await;
+                },
+            }
+        }
+    }
+
+    /// Forward the events pending in the inbound queue of a quiesced
+    /// function instance to the instance replacing it, if any, otherwise
+    /// to the dead-letter function/resource, replying to the callers with
+    /// an error.
+    async fn drain_quiesced(&mut self, successor: Option<edgeless_api::function_instance::InstanceId>) {
+        while let Some(event) = self.data_plane.try_receive_next() {
+            if let Some(target) = successor {
+                self.data_plane.redirect(target, event).await;
+                continue;
+            }
+            match &event.message {
+                edgeless_dataplane::core::Message::Cast(payload) => {
+                    self.data_plane
+                        .send_dead_letter("function instance stopped", &event.source_id, &self.instance_id, payload, &event.metadata)
+                        .await;
+                }
+                edgeless_dataplane::core::Message::Call(_) => {
+                    self.data_plane
+                        .reply(event.source_id, event.channel_id, edgeless_dataplane::core::CallRet::Err, &event.metadata)
+                        .await;
+                }
+                _ => {}
+            }
+        }
+    }
+
     async fn process_message(
         &mut self,
         source_id: edgeless_api::function_instance::InstanceId,
@@ -247,6 +403,16 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
             edgeless_telemetry::telemetry_events::TelemetryEvent::FunctionTransfer(elapsed),
             std::collections::BTreeMap::new(),
         );
+        // The queue depth is only reported when it changes, to limit the
+        // telemetry overhead when the function keeps up with its inputs.
+        let queue_depth = self.data_plane.queue_depth();
+        if queue_depth != self.queue_depth {
+            self.queue_depth = queue_depth;
+            self.telemetry_handle.observe(
+                edgeless_telemetry::telemetry_events::TelemetryEvent::FunctionQueueDepth(queue_depth),
+                std::collections::BTreeMap::new(),
+            );
+        }
 
         match message {
             edgeless_dataplane::core::Message::Cast(payload) => self.process_cast_message(source_id, payload, metadata).await,
@@ -261,7 +427,7 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
     async fn process_cast_message(
         &mut self,
         source_id: edgeless_api::function_instance::InstanceId,
-        payload: String,
+        payload: Vec<u8>,
         metadata: &edgeless_api::function_instance::EventMetadata,
     ) -> Result<(), super::FunctionInstanceError> {
         let start = tokio::time::Instant::now();
@@ -271,11 +437,18 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
             *locked_shared_metadata = Some(metadata.clone())
         }
 
-        self.function_instance
+        let res = self
+            .function_instance
             .as_mut()
             .ok_or(super::FunctionInstanceError::InternalError)?
             .cast(&source_id, &payload)
-            .await?;
+            .await;
+        if let Err(err) = &res {
+            self.data_plane
+                .send_dead_letter(&format!("cast failed: {:?}", err), &source_id, &self.instance_id, &payload, metadata)
+                .await;
+        }
+        res?;
 
         self.telemetry_handle.observe(
             edgeless_telemetry::telemetry_events::TelemetryEvent::FunctionInvocationCompleted(start.elapsed()),
@@ -287,7 +460,7 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
     async fn process_call_message(
         &mut self,
         source_id: edgeless_api::function_instance::InstanceId,
-        payload: String,
+        payload: Vec<u8>,
         channel_id: u64,
         metadata: &edgeless_api::function_instance::EventMetadata,
     ) -> Result<(), super::FunctionInstanceError> {
@@ -303,7 +476,20 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
             .as_mut()
             .ok_or(super::FunctionInstanceError::InternalError)?
             .call(&source_id, &payload)
-            .await?;
+            .await;
+
+        // Forward the calls that failed to the dead-letter function/resource.
+        let reason = match &res {
+            Ok(edgeless_dataplane::core::CallRet::Err) => Some(String::from("call returned an error")),
+            Err(err) => Some(format!("call failed: {:?}", err)),
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            self.data_plane
+                .send_dead_letter(&reason, &source_id, &self.instance_id, &payload, metadata)
+                .await;
+        }
+        let res = res?;
 
         self.telemetry_handle.observe(
             edgeless_telemetry::telemetry_events::TelemetryEvent::FunctionInvocationCompleted(start.elapsed()),
@@ -332,6 +518,14 @@ impl<FunctionInstanceType: FunctionInstance> FunctionInstanceTask<FunctionInstan
         Ok(())
     }
 
+    async fn sync(&mut self) -> Result<(), super::FunctionInstanceError> {
+        self.function_instance
+            .as_mut()
+            .ok_or(super::FunctionInstanceError::InternalError)?
+            .sync()
+            .await
+    }
+
     async fn exit(&mut self, exit_status: Result<(), super::FunctionInstanceError>) {
         self.runtime_api
             .send(super::runtime::RuntimeRequest::FunctionExit(self.instance_id, exit_status.clone()))
@@ -26,17 +26,21 @@ pub struct GuestAPIHost {
 #[derive(Debug)]
 pub enum GuestAPIError {
     UnknownAlias,
+    /// The state has been modified by someone else since it was last read:
+    /// the current state, if any, is reloaded and handed back to the
+    /// function, which must apply its changes on top of it.
+    StateConflict(Option<String>),
 }
 
 impl GuestAPIHost {
-    pub async fn cast_alias(&mut self, alias: &str, msg: &str) -> Result<(), GuestAPIError> {
+    pub async fn cast_alias(&mut self, alias: &str, msg: &[u8]) -> Result<(), GuestAPIError> {
         let shared_metadata = { self.event_metadata.lock().await.clone() };
         let metadata = shared_metadata.unwrap_or(edgeless_api::function_instance::EventMetadata::empty_dangling_root(0x42a42bdecaf00022u64));
         if alias == "self" {
-            self.data_plane.send(self.instance_id, msg.to_string(), &metadata).await;
+            self.data_plane.send(self.instance_id, msg, &metadata).await;
             Ok(())
         } else if let Some(target) = self.callback_table.get_mapping(alias).await {
-            self.data_plane.send(target, msg.to_string(), &metadata).await;
+            self.data_plane.send_output(alias, target, msg, &metadata).await;
             Ok(())
         } else {
             log::warn!("Unknown alias at {} for cast: {}", self.instance_id, alias);
@@ -44,18 +48,19 @@ impl GuestAPIHost {
         }
     }
 
-    pub async fn cast_raw(&mut self, target: edgeless_api::function_instance::InstanceId, msg: &str) -> Result<(), GuestAPIError> {
+    pub async fn cast_raw(&mut self, target: edgeless_api::function_instance::InstanceId, msg: &[u8]) -> Result<(), GuestAPIError> {
         let shared_metadata = { self.event_metadata.lock().await.clone() };
         let metadata = shared_metadata.unwrap_or(edgeless_api::function_instance::EventMetadata::empty_dangling_root(0x42a42bdecaf00023u64));
-        self.data_plane.send(target, msg.to_string(), &metadata).await;
+        self.data_plane.send(target, msg, &metadata).await;
         Ok(())
     }
 
-    pub async fn call_alias(&mut self, alias: &str, msg: &str) -> Result<edgeless_dataplane::core::CallRet, GuestAPIError> {
+    pub async fn call_alias(&mut self, alias: &str, msg: &[u8]) -> Result<edgeless_dataplane::core::CallRet, GuestAPIError> {
         if alias == "self" {
             self.call_raw(self.instance_id, msg).await
             // return Ok(self.data_plane.call(self.instance_id.clone(), msg.to_string()).await);
         } else if let Some(target) = self.callback_table.get_mapping(alias).await {
+            let target = self.data_plane.select_target(alias, target).await;
             return self.call_raw(target, msg).await;
             // return Ok(self.data_plane.call(target.clone(), msg.to_string()).await);
         } else {
@@ -67,7 +72,7 @@ impl GuestAPIHost {
     pub async fn call_raw(
         &mut self,
         target: edgeless_api::function_instance::InstanceId,
-        msg: &str,
+        msg: &[u8],
     ) -> Result<edgeless_dataplane::core::CallRet, GuestAPIError> {
         let shared_metadata = { self.event_metadata.lock().await.clone() };
         let metadata = shared_metadata.unwrap_or(edgeless_api::function_instance::EventMetadata::empty_dangling_root(0x42a42bdecaf00024u64));
@@ -76,7 +81,7 @@ impl GuestAPIHost {
             _ = Box::pin(self.poison_pill_receiver.recv()).fuse() => {
                 Ok(edgeless_dataplane::core::CallRet::Err)
             },
-            call_res = Box::pin(self.data_plane.call(target, msg.to_string(), &metadata)).fuse() => {
+            call_res = Box::pin(self.data_plane.call(target, msg, &metadata)).fuse() => {
                 Ok(call_res)
             }
         }
@@ -93,9 +98,9 @@ impl GuestAPIHost {
         self.instance_id
     }
 
-    pub async fn delayed_cast(&mut self, delay: u64, target_alias: &str, payload: &str) -> Result<(), GuestAPIError> {
+    pub async fn delayed_cast(&mut self, delay: u64, target_alias: &str, payload: &[u8]) -> Result<(), GuestAPIError> {
         let mut cloned_plane = self.data_plane.clone();
-        let cloned_msg = payload.to_string();
+        let cloned_msg = payload.to_vec();
 
         let shared_metadata = { self.event_metadata.lock().await.clone() };
         let metadata = shared_metadata.unwrap_or(edgeless_api::function_instance::EventMetadata::empty_dangling_root(0x42a42bdecaf00025u64));
@@ -103,7 +108,7 @@ impl GuestAPIHost {
         let target_instance_id = if target_alias == "self" {
             self.instance_id
         } else if let Some(targted_id) = self.callback_table.get_mapping(target_alias).await {
-            targted_id
+            self.data_plane.select_target(target_alias, targted_id).await
         } else {
             log::warn!("Unknown alias at {} for delayed cast ({} ms): {}", self.instance_id, delay, target_alias);
             return Err(GuestAPIError::UnknownAlias);
@@ -118,8 +123,19 @@ impl GuestAPIHost {
     }
 
     pub async fn sync(&mut self, serialized_state: &str) -> Result<(), GuestAPIError> {
-        self.state_handle.set(serialized_state.to_string()).await;
-        log::info!("Function State Sync: {}", serialized_state);
+        // If someone else has updated the same state in the meanwhile, the
+        // state is not overwritten and the conflict is reported to the
+        // function, together with the state reloaded, so that a retry does
+        // not overwrite the changes of the other writer.
+        // Other failures are not fatal for the function instance.
+        match self.state_handle.set(serialized_state.to_string()).await {
+            Ok(_) => log::info!("Function State Sync: {}", serialized_state),
+            Err(err) if err.is::<crate::state_management::StateConflict>() => {
+                log::warn!("Function State Sync rejected at {}: {}", self.instance_id, err);
+                return Err(GuestAPIError::StateConflict(self.state_handle.get().await));
+            }
+            Err(err) => log::warn!("Function State Sync failed at {}: {}", self.instance_id, err),
+        }
         Ok(())
     }
 }
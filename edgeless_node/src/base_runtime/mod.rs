@@ -18,6 +18,20 @@ pub trait RuntimeAPI {
     ) -> anyhow::Result<()>;
     async fn stop(&mut self, instance_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()>;
     async fn patch(&mut self, update: edgeless_api::common::PatchRequest) -> anyhow::Result<()>;
+    /// Ask a function instance to synchronize its state: the receiver
+    /// returned is notified when done.
+    async fn sync(
+        &mut self,
+        instance_id: edgeless_api::function_instance::InstanceId,
+    ) -> anyhow::Result<futures::channel::oneshot::Receiver<anyhow::Result<()>>>;
+    /// Ask a function instance that has synchronized its state to forward
+    /// the events it receives to the instance replacing it: the receiver
+    /// returned is notified when the events pending have been forwarded.
+    async fn hand_over(
+        &mut self,
+        instance_id: edgeless_api::function_instance::InstanceId,
+        target: edgeless_api::function_instance::InstanceId,
+    ) -> anyhow::Result<futures::channel::oneshot::Receiver<anyhow::Result<()>>>;
 }
 
 /// This must be implemented for each virtualization technology.
@@ -32,17 +46,20 @@ pub trait FunctionInstance: Send + 'static {
         code: &str,
     ) -> Result<Box<Self>, FunctionInstanceError>;
     async fn init(&mut self, init_payload: Option<&str>, serialized_state: Option<&str>) -> Result<(), FunctionInstanceError>;
-    async fn cast(&mut self, src: &edgeless_api::function_instance::InstanceId, msg: &str) -> Result<(), FunctionInstanceError>;
+    async fn cast(&mut self, src: &edgeless_api::function_instance::InstanceId, msg: &[u8]) -> Result<(), FunctionInstanceError>;
     async fn call(
         &mut self,
         src: &edgeless_api::function_instance::InstanceId,
-        msg: &str,
+        msg: &[u8],
     ) -> Result<edgeless_dataplane::core::CallRet, FunctionInstanceError>;
     async fn stop(&mut self) -> Result<(), FunctionInstanceError>;
+    /// Ask the function instance to synchronize its current state.
+    async fn sync(&mut self) -> Result<(), FunctionInstanceError>;
 }
 
 #[derive(Clone, Debug)]
 pub enum FunctionInstanceError {
     BadCode(String),
     InternalError,
+    NotSupported(String),
 }
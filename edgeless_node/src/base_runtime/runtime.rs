@@ -48,6 +48,15 @@ pub enum RuntimeRequest {
     ),
     Stop(edgeless_api::function_instance::InstanceId),
     Patch(edgeless_api::common::PatchRequest),
+    Sync(
+        edgeless_api::function_instance::InstanceId,
+        futures::channel::oneshot::Sender<anyhow::Result<()>>,
+    ),
+    HandOver(
+        edgeless_api::function_instance::InstanceId,
+        edgeless_api::function_instance::InstanceId,
+        futures::channel::oneshot::Sender<anyhow::Result<()>>,
+    ),
     FunctionExit(edgeless_api::function_instance::InstanceId, Result<(), super::FunctionInstanceError>),
 }
 
@@ -106,6 +115,12 @@ impl<FunctionInstanceType: super::FunctionInstance> RuntimeTask<FunctionInstance
                 RuntimeRequest::Patch(update_request) => {
                     self.patch_function_links(update_request).await;
                 }
+                RuntimeRequest::Sync(instance_id, responder) => {
+                    self.sync_function(instance_id, responder);
+                }
+                RuntimeRequest::HandOver(instance_id, target, responder) => {
+                    self.hand_over_function(instance_id, target, responder);
+                }
                 RuntimeRequest::FunctionExit(id, status) => {
                     self.function_exit(id, status).await;
                 }
@@ -120,15 +135,28 @@ impl<FunctionInstanceType: super::FunctionInstance> RuntimeTask<FunctionInstance
     ) {
         log::info!("Start Function {:?}", instance_id);
         let cloned_req = spawn_request.clone();
-        let data_plane = self.data_plane_provider.get_handle_for(instance_id).await;
+        let inbound_queue = self
+            .data_plane_provider
+            .inbound_queue_settings()
+            .with_annotations(&spawn_request.annotations);
+        let state_handle = match self
+            .state_manager
+            .get_handle(spawn_request.state_specification.state_policy, spawn_request.state_specification.state_id)
+            .await
+        {
+            Ok(state_handle) => state_handle,
+            Err(err) => {
+                log::error!("Could not start function {:?}: {}", instance_id, err);
+                return;
+            }
+        };
+        let data_plane = self.data_plane_provider.get_handle_with_inbound_queue(instance_id, inbound_queue).await;
         let instance = super::function_instance_runner::FunctionInstanceRunner::new(
             instance_id,
             cloned_req,
             data_plane,
             self.slf_channel.clone(),
-            self.state_manager
-                .get_handle(spawn_request.state_specification.state_policy, spawn_request.state_specification.state_id)
-                .await,
+            state_handle,
             self.telemetry_handle.fork(std::collections::BTreeMap::from([(
                 "FUNCTION_ID".to_string(),
                 instance_id.function_id.to_string(),
@@ -153,6 +181,45 @@ impl<FunctionInstanceType: super::FunctionInstance> RuntimeTask<FunctionInstance
         }
     }
 
+    fn sync_function(
+        &mut self,
+        instance_id: edgeless_api::function_instance::InstanceId,
+        responder: futures::channel::oneshot::Sender<anyhow::Result<()>>,
+    ) {
+        log::info!("Sync Function {:?}", instance_id);
+        let synced = match self.functions.get_mut(&instance_id.function_id) {
+            Some(instance) => instance.sync(),
+            None => {
+                let _ = responder.send(Err(anyhow::anyhow!("function instance not found: {:?}", instance_id)));
+                return;
+            }
+        };
+        // Do not block the other requests while the function instance is
+        // busy processing events.
+        tokio::spawn(async move {
+            let _ = responder.send(synced.await);
+        });
+    }
+
+    fn hand_over_function(
+        &mut self,
+        instance_id: edgeless_api::function_instance::InstanceId,
+        target: edgeless_api::function_instance::InstanceId,
+        responder: futures::channel::oneshot::Sender<anyhow::Result<()>>,
+    ) {
+        log::info!("Hand Over Function {:?} -> {:?}", instance_id, target);
+        let handed_over = match self.functions.get_mut(&instance_id.function_id) {
+            Some(instance) => instance.hand_over(target),
+            None => {
+                let _ = responder.send(Err(anyhow::anyhow!("function instance not found: {:?}", instance_id)));
+                return;
+            }
+        };
+        tokio::spawn(async move {
+            let _ = responder.send(handed_over.await);
+        });
+    }
+
     async fn function_exit(&mut self, instance_id: edgeless_api::function_instance::InstanceId, status: Result<(), super::FunctionInstanceError>) {
         log::info!("Function Exit Event: {:?} {:?}", instance_id, status);
         self.functions.remove(&instance_id.function_id);
@@ -193,4 +260,27 @@ impl super::RuntimeAPI for RuntimeClient {
             Err(_) => Err(anyhow::anyhow!("Runner Channel Error")),
         }
     }
+
+    async fn sync(
+        &mut self,
+        instance_id: edgeless_api::function_instance::InstanceId,
+    ) -> anyhow::Result<futures::channel::oneshot::Receiver<anyhow::Result<()>>> {
+        let (rsp_sender, rsp_receiver) = futures::channel::oneshot::channel();
+        match self.sender.send(RuntimeRequest::Sync(instance_id, rsp_sender)).await {
+            Ok(_) => Ok(rsp_receiver),
+            Err(_) => Err(anyhow::anyhow!("Runner Channel Error")),
+        }
+    }
+
+    async fn hand_over(
+        &mut self,
+        instance_id: edgeless_api::function_instance::InstanceId,
+        target: edgeless_api::function_instance::InstanceId,
+    ) -> anyhow::Result<futures::channel::oneshot::Receiver<anyhow::Result<()>>> {
+        let (rsp_sender, rsp_receiver) = futures::channel::oneshot::channel();
+        match self.sender.send(RuntimeRequest::HandOver(instance_id, target, rsp_sender)).await {
+            Ok(_) => Ok(rsp_receiver),
+            Err(_) => Err(anyhow::anyhow!("Runner Channel Error")),
+        }
+    }
 }
@@ -6,17 +6,25 @@ use clap::Parser;
 use edgeless_node::resources::dda::DdaResourceSpec;
 use edgeless_node::resources::file_log::FileLogResourceSpec;
 use edgeless_node::resources::file_pusher::FilePusherResourceSpec;
+use edgeless_node::resources::grpc_egress::GrpcEgressResourceSpec;
 use edgeless_node::resources::http_egress::HttpEgressResourceSpec;
 use edgeless_node::resources::http_ingress::HttpIngressResourceSpec;
 use edgeless_node::resources::http_poster::HttpPosterResourceSpec;
 #[cfg(feature = "rdkafka")]
 use edgeless_node::resources::kafka_egress::KafkaEgressResourceSpec;
+#[cfg(feature = "rdkafka")]
+use edgeless_node::resources::kafka_ingress::KafkaIngressResourceSpec;
+use edgeless_node::resources::mqtt_egress::MqttEgressResourceSpec;
+use edgeless_node::resources::mqtt_ingress::MqttIngressResourceSpec;
+use edgeless_node::resources::object_store::ObjectStoreResourceSpec;
 use edgeless_node::resources::ollama::OllamaResourceSpec;
 use edgeless_node::resources::redis::RedisResourceSpec;
 use edgeless_node::resources::resource_provider_specs::ResourceProviderSpecOutput;
 use edgeless_node::resources::resource_provider_specs::ResourceProviderSpecs;
 use edgeless_node::resources::serverless::ServerlessResourceProviderSpec;
 use edgeless_node::resources::sqlx::SqlxResourceSpec;
+use edgeless_node::resources::timer::TimerResourceSpec;
+use edgeless_node::resources::ws_ingress::WsIngressResourceSpec;
 
 #[derive(Debug, clap::Parser)]
 #[command(long_about = None)]
@@ -73,16 +81,24 @@ fn main() -> anyhow::Result<()> {
         let mut specs: Vec<Box<dyn ResourceProviderSpecs>> = vec![
             Box::new(DdaResourceSpec {}),
             Box::new(FileLogResourceSpec {}),
+            Box::new(GrpcEgressResourceSpec {}),
             Box::new(HttpEgressResourceSpec {}),
             Box::new(HttpIngressResourceSpec {}),
             Box::new(HttpPosterResourceSpec {}),
             Box::new(FilePusherResourceSpec {}),
+            Box::new(MqttEgressResourceSpec {}),
+            Box::new(MqttIngressResourceSpec {}),
+            Box::new(ObjectStoreResourceSpec {}),
             Box::new(OllamaResourceSpec {}),
             Box::new(RedisResourceSpec {}),
             Box::new(SqlxResourceSpec {}),
+            Box::new(TimerResourceSpec {}),
+            Box::new(WsIngressResourceSpec {}),
         ];
         #[cfg(feature = "rdkafka")]
         specs.push(Box::new(KafkaEgressResourceSpec {}));
+        #[cfg(feature = "rdkafka")]
+        specs.push(Box::new(KafkaIngressResourceSpec {}));
         if let Ok(conf) = &conf
             && let Some(resources) = &conf.resources
             && let Some(serverless_providers) = &resources.serverless_provider
@@ -22,7 +22,7 @@ enum ContainerRuntimeRequest {
     TelemetryLog(edgeless_api::guest_api_host::TelemetryLogEvent),
     Slf(tokio::sync::oneshot::Sender<anyhow::Result<edgeless_api::function_instance::InstanceId>>),
     DelayedCast(edgeless_api::guest_api_host::DelayedEventData),
-    Sync(edgeless_api::guest_api_host::SyncData),
+    Sync(edgeless_api::guest_api_host::SyncData, tokio::sync::oneshot::Sender<anyhow::Result<()>>),
 }
 
 impl crate::base_runtime::runtime::GuestAPIHostRegister for ContainerRuntime {
@@ -101,11 +101,7 @@ impl ContainerRuntime {
                 ContainerRuntimeRequest::Cast(event) => {
                     log::debug!("cast, alias {}, msg {} bytes", event.alias, event.msg.len());
                     if let Some(runtime) = container_runtime.lock().await.guest_api_host(&event.originator) {
-                        if runtime
-                            .cast_alias(&event.alias, String::from_utf8(event.msg).unwrap().as_str())
-                            .await
-                            .is_err()
-                        {
+                        if runtime.cast_alias(&event.alias, &event.msg).await.is_err() {
                             log::error!("error occurred when casting an event towards alias {}: dropped", event.alias);
                         }
                     } else {
@@ -119,7 +115,7 @@ impl ContainerRuntime {
                 ContainerRuntimeRequest::CastRaw(event) => {
                     log::debug!("cast-raw, dst {}, msg {} bytes", event.dst, event.msg.len());
                     if let Some(runtime) = container_runtime.lock().await.guest_api_host(&event.originator) {
-                        if runtime.cast_raw(event.dst, String::from_utf8(event.msg).unwrap().as_str()).await.is_err() {
+                        if runtime.cast_raw(event.dst, &event.msg).await.is_err() {
                             log::error!("error occurred when raw-casting an event towards {}", event.dst);
                         }
                     } else {
@@ -134,14 +130,13 @@ impl ContainerRuntime {
                     log::debug!("call, alias {}, msg {} bytes", event.alias, event.msg.len());
                     let mut res = edgeless_api::guest_api_function::CallReturn::Err;
                     if let Some(runtime) = container_runtime.lock().await.guest_api_host(&event.originator) {
-                        match runtime.call_alias(&event.alias, String::from_utf8(event.msg).unwrap().as_str()).await {
+                        match runtime.call_alias(&event.alias, &event.msg).await {
                             Ok(ret) => {
                                 res = match ret {
                                     edgeless_dataplane::core::CallRet::NoReply => edgeless_api::guest_api_function::CallReturn::NoRet,
-                                    edgeless_dataplane::core::CallRet::Reply(msg) => {
-                                        edgeless_api::guest_api_function::CallReturn::Reply(msg.as_bytes().to_vec())
-                                    }
+                                    edgeless_dataplane::core::CallRet::Reply(msg) => edgeless_api::guest_api_function::CallReturn::Reply(msg),
                                     edgeless_dataplane::core::CallRet::Err => edgeless_api::guest_api_function::CallReturn::Err,
+                                    edgeless_dataplane::core::CallRet::Timeout => edgeless_api::guest_api_function::CallReturn::Timeout,
                                 }
                             }
                             Err(_) => {
@@ -166,14 +161,13 @@ impl ContainerRuntime {
                     log::debug!("call-raw, dst {}, msg {} bytes", event.dst, event.msg.len());
                     let mut res = edgeless_api::guest_api_function::CallReturn::Err;
                     if let Some(runtime) = container_runtime.lock().await.guest_api_host(&event.originator) {
-                        match runtime.call_raw(event.dst, String::from_utf8(event.msg).unwrap().as_str()).await {
+                        match runtime.call_raw(event.dst, &event.msg).await {
                             Ok(ret) => {
                                 res = match ret {
                                     edgeless_dataplane::core::CallRet::NoReply => edgeless_api::guest_api_function::CallReturn::NoRet,
-                                    edgeless_dataplane::core::CallRet::Reply(msg) => {
-                                        edgeless_api::guest_api_function::CallReturn::Reply(msg.as_bytes().to_vec())
-                                    }
+                                    edgeless_dataplane::core::CallRet::Reply(msg) => edgeless_api::guest_api_function::CallReturn::Reply(msg),
                                     edgeless_dataplane::core::CallRet::Err => edgeless_api::guest_api_function::CallReturn::Err,
+                                    edgeless_dataplane::core::CallRet::Timeout => edgeless_api::guest_api_function::CallReturn::Timeout,
                                 }
                             }
                             Err(_) => {
@@ -254,11 +248,7 @@ impl ContainerRuntime {
                         event.msg.len()
                     );
                     if let Some(runtime) = container_runtime.lock().await.guest_api_host(&event.originator) {
-                        if runtime
-                            .delayed_cast(event.delay, &event.alias, String::from_utf8(event.msg).unwrap().as_str())
-                            .await
-                            .is_err()
-                        {
+                        if runtime.delayed_cast(event.delay, &event.alias, &event.msg).await.is_err() {
                             log::error!(
                                 "error occurred when casting an event with delay {} towards alias {}: dropped",
                                 event.delay,
@@ -274,21 +264,27 @@ impl ContainerRuntime {
                         );
                     }
                 }
-                ContainerRuntimeRequest::Sync(sync_data) => {
+                ContainerRuntimeRequest::Sync(sync_data, reply_sender) => {
                     log::debug!("sync, serialized-data {} bytes", sync_data.serialized_data.len());
-                    if let Some(runtime) = container_runtime.lock().await.guest_api_host(&sync_data.originator) {
-                        if runtime
-                            .sync(String::from_utf8(sync_data.serialized_data).unwrap().as_str())
+                    let res = if let Some(runtime) = container_runtime.lock().await.guest_api_host(&sync_data.originator) {
+                        // The error is returned to the function, which can
+                        // retry after a conflict.
+                        runtime
+                            .sync(String::from_utf8_lossy(&sync_data.serialized_data).as_ref())
                             .await
-                            .is_err()
-                        {
-                            log::error!("error occurred when synchronizing state of {}: ignored", sync_data.originator);
-                        }
+                            .map_err(|err| anyhow::anyhow!("error occurred when synchronizing state of {}: {:?}", sync_data.originator, err))
                     } else {
                         log::warn!(
                             "no function instance with matching ID {} when synchronizing state: ignored",
                             sync_data.originator
                         );
+                        Ok(())
+                    };
+                    match reply_sender.send(res) {
+                        Ok(_) => {}
+                        Err(err) => {
+                            log::error!("Unhandled: {:?}", err);
+                        }
                     }
                 }
             }
@@ -371,8 +367,12 @@ impl edgeless_api::guest_api_host::GuestAPIHost for GuestAPIRuntimeClient {
         }
     }
     async fn sync(&mut self, sync_data: edgeless_api::guest_api_host::SyncData) -> anyhow::Result<()> {
-        match self.sender.send(ContainerRuntimeRequest::Sync(sync_data.clone())).await {
-            Ok(_) => Ok(()),
+        let (reply_sender, reply_receiver) = tokio::sync::oneshot::channel::<anyhow::Result<()>>();
+        match self.sender.send(ContainerRuntimeRequest::Sync(sync_data.clone(), reply_sender)).await {
+            Ok(_) => match reply_receiver.await {
+                Ok(ret) => ret,
+                Err(err) => Err(anyhow::anyhow!("GuestAPIRuntime::sync error: {}", err)),
+            },
             Err(err) => return Err(anyhow::anyhow!("GuestAPIRuntime::sync channel error: {}", err)),
         }
     }
@@ -139,7 +139,11 @@ impl crate::base_runtime::FunctionInstance for ContainerFunctionInstance {
             .or(Err(crate::base_runtime::FunctionInstanceError::InternalError))
     }
 
-    async fn cast(&mut self, src: &edgeless_api::function_instance::InstanceId, msg: &str) -> Result<(), crate::base_runtime::FunctionInstanceError> {
+    async fn cast(
+        &mut self,
+        src: &edgeless_api::function_instance::InstanceId,
+        msg: &[u8],
+    ) -> Result<(), crate::base_runtime::FunctionInstanceError> {
         log::debug!("container run-time: cast, src {}, msg {} bytes", src, msg.len());
         self.function_client_api
             .cast(edgeless_api::guest_api_function::InputEventData { src: *src, msg: msg.into() })
@@ -150,7 +154,7 @@ impl crate::base_runtime::FunctionInstance for ContainerFunctionInstance {
     async fn call(
         &mut self,
         src: &edgeless_api::function_instance::InstanceId,
-        msg: &str,
+        msg: &[u8],
     ) -> Result<edgeless_dataplane::core::CallRet, crate::base_runtime::FunctionInstanceError> {
         log::debug!("container run-time: call, src {}, msg {} bytes", src, msg.len());
         match self
@@ -160,10 +164,9 @@ impl crate::base_runtime::FunctionInstance for ContainerFunctionInstance {
         {
             Ok(ret) => match ret {
                 edgeless_api::guest_api_function::CallReturn::NoRet => Ok(edgeless_dataplane::core::CallRet::NoReply),
-                edgeless_api::guest_api_function::CallReturn::Reply(msg) => {
-                    Ok(edgeless_dataplane::core::CallRet::Reply(String::from_utf8(msg).unwrap_or_default()))
-                }
+                edgeless_api::guest_api_function::CallReturn::Reply(msg) => Ok(edgeless_dataplane::core::CallRet::Reply(msg)),
                 edgeless_api::guest_api_function::CallReturn::Err => Ok(edgeless_dataplane::core::CallRet::Err),
+                edgeless_api::guest_api_function::CallReturn::Timeout => Ok(edgeless_dataplane::core::CallRet::Timeout),
             },
             Err(_) => Err(crate::base_runtime::FunctionInstanceError::InternalError),
         }
@@ -194,4 +197,13 @@ impl crate::base_runtime::FunctionInstance for ContainerFunctionInstance {
 
         Ok(())
     }
+
+    async fn sync(&mut self) -> Result<(), crate::base_runtime::FunctionInstanceError> {
+        // Container functions have no hook to be asked for their state,
+        // hence they cannot be migrated without losing the updates since
+        // their last synchronization.
+        Err(crate::base_runtime::FunctionInstanceError::NotSupported(
+            "container functions cannot be asked to synchronize their state".to_string(),
+        ))
+    }
 }
@@ -34,6 +34,8 @@ pub struct EdgelessNodeSettings {
     pub user_node_capabilities: Option<NodeCapabilitiesUser>,
     /// Power information settings.
     pub power_info: Option<EdgelessNodePowerInfoSettings>,
+    /// State management settings. Only node-local state is available if not present.
+    pub state_management: Option<EdgelessNodeStateSettings>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
@@ -100,6 +102,33 @@ pub struct EdgelessNodeGeneralSettings {
     pub node_register_url: String,
     /// The interval at which the node refreshes subscription, s.
     pub subscription_refresh_interval_sec: u64,
+    /// The timeout of the calls made by functions and resources, ms.
+    /// If not specified, `edgeless_dataplane::handle::DEFAULT_CALL_TIMEOUT`.
+    #[serde(default)]
+    pub call_timeout_ms: Option<u64>,
+    /// The maximum number of events waiting to be processed by a function
+    /// or resource instance, unbounded if 0.
+    /// If not specified, `edgeless_dataplane::inbound_queue::DEFAULT_CAPACITY`.
+    /// It can be overridden by functions via annotations.
+    #[serde(default = "default_inbound_queue_capacity")]
+    pub inbound_queue_capacity: usize,
+    /// What to do with the events received by a function or resource
+    /// instance whose inbound queue is full.
+    /// It can be overridden by functions via annotations.
+    #[serde(default)]
+    pub inbound_queue_overflow: edgeless_dataplane::inbound_queue::OverflowPolicy,
+    /// The transport used to send events to the other nodes via gRPC.
+    #[serde(default)]
+    pub invocation_transport: edgeless_dataplane::core::InvocationTransport,
+    /// The maximum number of events sent in a batch with the `stream`
+    /// transport.
+    /// If not specified, `edgeless_api::grpc_impl::outer::invocation_stream::DEFAULT_MAX_BATCH_SIZE`.
+    #[serde(default)]
+    pub invocation_max_batch_size: Option<usize>,
+}
+
+fn default_inbound_queue_capacity() -> usize {
+    edgeless_dataplane::inbound_queue::DEFAULT_CAPACITY
 }
 
 impl Default for EdgelessNodeGeneralSettings {
@@ -114,6 +143,11 @@ impl Default for EdgelessNodeGeneralSettings {
             invocation_url_announced_coap: None,
             node_register_url: String::from("http://127.0.0.1:7004"),
             subscription_refresh_interval_sec: 2,
+            call_timeout_ms: Some(60000),
+            inbound_queue_capacity: default_inbound_queue_capacity(),
+            inbound_queue_overflow: edgeless_dataplane::inbound_queue::OverflowPolicy::BlockLocal,
+            invocation_transport: edgeless_dataplane::core::InvocationTransport::Unary,
+            invocation_max_batch_size: None,
         }
     }
 }
@@ -125,8 +159,21 @@ pub struct EdgelessNodeResourceSettings {
     /// If `http_ingress_provider` is not empty, this is the URL of the
     /// HTTP web server exposed by the http-ingress resource for this node.
     pub http_ingress_url: Option<String>,
+    /// If `http_ingress_url` uses the https protocol, this is the path of
+    /// the PEM file with the TLS certificate chain of the web server.
+    pub http_ingress_tls_cert: Option<String>,
+    /// If `http_ingress_url` uses the https protocol, this is the path of
+    /// the PEM file with the TLS private key of the web server.
+    pub http_ingress_tls_key: Option<String>,
     /// If not empty, a http-ingress resource provider with that name is created.
     pub http_ingress_provider: Option<String>,
+    /// If `ws_ingress_provider` is not empty, this is the URL of the
+    /// web server exposed by the ws-ingress resource for this node.
+    pub ws_ingress_url: Option<String>,
+    /// If not empty, a ws-ingress resource provider with that name is created.
+    /// The resource will keep sessions open with WebSocket and Server-Sent
+    /// Events clients.
+    pub ws_ingress_provider: Option<String>,
     /// If not empty, a http-egress resource provider with that name is created.
     pub http_egress_provider: Option<String>,
     /// If not empty, a http-poster resource provider with that name is created.
@@ -151,6 +198,28 @@ pub struct EdgelessNodeResourceSettings {
     /// The resource will connect to a remote Kafka server to stream the
     /// messages received on a given topic.
     pub kafka_egress_provider: Option<String>,
+    /// If not empty, a kafka-ingress resource provider with that name is created.
+    /// The resource will connect to a remote Kafka server to consume the
+    /// messages on given topics.
+    pub kafka_ingress_provider: Option<String>,
+    /// If not empty, an mqtt-ingress resource provider with that name is created.
+    /// The resource will connect to a remote MQTT broker to receive the
+    /// messages published on given topic filters.
+    pub mqtt_ingress_provider: Option<String>,
+    /// If not empty, an mqtt-egress resource provider with that name is created.
+    /// The resource will connect to a remote MQTT broker to publish the
+    /// messages received.
+    pub mqtt_egress_provider: Option<String>,
+    /// If not empty, an object-store resource provider with that name is created.
+    /// The resource will read and write objects on an S3-compatible object
+    /// storage or a local directory.
+    pub object_store_provider: Option<String>,
+    /// If not empty, a grpc-egress resource provider with that name is created.
+    /// The resource will invoke unary methods of external gRPC services.
+    pub grpc_egress_provider: Option<String>,
+    /// If not empty, a timer resource provider with that name is created.
+    /// The resource will emit events periodically.
+    pub timer_provider: Option<String>,
     /// The sqlx resource provider.
     pub sqlx_provider: Option<String>,
 }
@@ -198,6 +267,18 @@ pub struct FilePusherSettings {
     pub provider: String,
 }
 
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct EdgelessNodeStateSettings {
+    /// If not empty, the state of the function instances with a global
+    /// state policy is stored on the Redis server at this URL.
+    pub global_redis_url: Option<String>,
+    /// If not empty, the state of the function instances with a global
+    /// state policy is stored in this directory, which should be shared
+    /// by all the nodes, e.g., via NFS.
+    /// Ignored if `global_redis_url` is set.
+    pub global_shared_dir: Option<String>,
+}
+
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct EdgelessNodePowerInfoSettings {
     /// The endpoint IP:port of the Modbus server.
@@ -351,14 +432,48 @@ async fn fill_resources(
             let class_type = resources::http_ingress::HttpIngressResourceSpec {}.class_type();
             let provider_id = make_provider_id(provider_id);
             log::info!("Creating {} resource provider '{}' at {}", class_type, provider_id, http_ingress_url);
+            match resources::http_ingress::ingress_task(
+                data_plane.clone(),
+                edgeless_api::function_instance::InstanceId::new(node_id),
+                http_ingress_url.clone(),
+                settings.http_ingress_tls_cert.clone(),
+                settings.http_ingress_tls_key.clone(),
+            )
+            .await
+            {
+                Ok(client) => {
+                    ret.insert(
+                        provider_id.clone(),
+                        agent::ResourceDesc {
+                            class_type: class_type.clone(),
+                            client,
+                        },
+                    );
+                    provider_specifications.push(edgeless_api::node_registration::ResourceProviderSpecification {
+                        provider_id,
+                        class_type,
+                        outputs: resources::http_ingress::HttpIngressResourceSpec {}.outputs(),
+                    });
+                }
+                Err(err) => log::error!("Could not create {} resource provider '{}': {}", class_type, provider_id, err),
+            }
+        }
+
+        if let (Some(ws_ingress_url), Some(provider_id)) = (&settings.ws_ingress_url, &settings.ws_ingress_provider)
+            && !ws_ingress_url.is_empty()
+            && !provider_id.is_empty()
+        {
+            let class_type = resources::ws_ingress::WsIngressResourceSpec {}.class_type();
+            let provider_id = make_provider_id(provider_id);
+            log::info!("Creating {} resource provider '{}' at {}", class_type, provider_id, ws_ingress_url);
             ret.insert(
                 provider_id.clone(),
                 agent::ResourceDesc {
                     class_type: class_type.clone(),
-                    client: resources::http_ingress::ingress_task(
+                    client: resources::ws_ingress::ws_ingress_task(
                         data_plane.clone(),
                         edgeless_api::function_instance::InstanceId::new(node_id),
-                        http_ingress_url.clone(),
+                        ws_ingress_url.clone(),
                     )
                     .await,
                 },
@@ -366,7 +481,7 @@ async fn fill_resources(
             provider_specifications.push(edgeless_api::node_registration::ResourceProviderSpecification {
                 provider_id,
                 class_type,
-                outputs: resources::http_ingress::HttpIngressResourceSpec {}.outputs(),
+                outputs: resources::ws_ingress::WsIngressResourceSpec {}.outputs(),
             });
         }
 
@@ -688,6 +803,201 @@ async fn fill_resources(
             );
         }
 
+        if let Some(provider_id) = &settings.kafka_ingress_provider
+            && !provider_id.is_empty()
+        {
+            #[cfg(feature = "rdkafka")]
+            {
+                let class_type = resources::kafka_ingress::KafkaIngressResourceSpec {}.class_type();
+                let provider_id = make_provider_id(provider_id);
+                log::info!("Creating {} resource provider '{}'", class_type, provider_id);
+                ret.insert(
+                    provider_id.clone(),
+                    agent::ResourceDesc {
+                        class_type: class_type.clone(),
+                        client: Box::new(
+                            resources::kafka_ingress::KafkaIngressResourceProvider::new(
+                                data_plane.clone(),
+                                Box::new(telemetry_provider.get_handle(std::collections::BTreeMap::from([
+                                    ("RESOURCE_CLASS_TYPE".to_string(), class_type.clone()),
+                                    ("RESOURCE_PROVIDER_ID".to_string(), provider_id.clone()),
+                                    ("NODE_ID".to_string(), node_id.to_string()),
+                                ]))),
+                                edgeless_api::function_instance::InstanceId::new(node_id),
+                                Box::new(resources::kafka_ingress::rdkafka_consumer),
+                            )
+                            .await,
+                        ),
+                    },
+                );
+                provider_specifications.push(edgeless_api::node_registration::ResourceProviderSpecification {
+                    provider_id,
+                    class_type,
+                    outputs: resources::kafka_ingress::KafkaIngressResourceSpec {}.outputs(),
+                });
+            }
+            #[cfg(not(feature = "rdkafka"))]
+            log::error!(
+                "Could not create resource provider '{}' because rdkafka was disabled at compile time",
+                provider_id
+            );
+        }
+
+        if let Some(provider_id) = &settings.mqtt_ingress_provider
+            && !provider_id.is_empty()
+        {
+            let class_type = resources::mqtt_ingress::MqttIngressResourceSpec {}.class_type();
+            let provider_id = make_provider_id(provider_id);
+            log::info!("Creating {} resource provider '{}'", class_type, provider_id);
+            ret.insert(
+                provider_id.clone(),
+                agent::ResourceDesc {
+                    class_type: class_type.clone(),
+                    client: Box::new(
+                        resources::mqtt_ingress::MqttIngressResourceProvider::new(
+                            data_plane.clone(),
+                            Box::new(telemetry_provider.get_handle(std::collections::BTreeMap::from([
+                                ("RESOURCE_CLASS_TYPE".to_string(), class_type.clone()),
+                                ("RESOURCE_PROVIDER_ID".to_string(), provider_id.clone()),
+                                ("NODE_ID".to_string(), node_id.to_string()),
+                            ]))),
+                            edgeless_api::function_instance::InstanceId::new(node_id),
+                        )
+                        .await,
+                    ),
+                },
+            );
+            provider_specifications.push(edgeless_api::node_registration::ResourceProviderSpecification {
+                provider_id,
+                class_type,
+                outputs: resources::mqtt_ingress::MqttIngressResourceSpec {}.outputs(),
+            });
+        }
+
+        if let Some(provider_id) = &settings.mqtt_egress_provider
+            && !provider_id.is_empty()
+        {
+            let class_type = resources::mqtt_egress::MqttEgressResourceSpec {}.class_type();
+            let provider_id = make_provider_id(provider_id);
+            log::info!("Creating {} resource provider '{}'", class_type, provider_id);
+            ret.insert(
+                provider_id.clone(),
+                agent::ResourceDesc {
+                    class_type: class_type.clone(),
+                    client: Box::new(
+                        resources::mqtt_egress::MqttEgressResourceProvider::new(
+                            data_plane.clone(),
+                            Box::new(telemetry_provider.get_handle(std::collections::BTreeMap::from([
+                                ("RESOURCE_CLASS_TYPE".to_string(), class_type.clone()),
+                                ("RESOURCE_PROVIDER_ID".to_string(), provider_id.clone()),
+                                ("NODE_ID".to_string(), node_id.to_string()),
+                            ]))),
+                            edgeless_api::function_instance::InstanceId::new(node_id),
+                        )
+                        .await,
+                    ),
+                },
+            );
+            provider_specifications.push(edgeless_api::node_registration::ResourceProviderSpecification {
+                provider_id,
+                class_type,
+                outputs: resources::mqtt_egress::MqttEgressResourceSpec {}.outputs(),
+            });
+        }
+
+        if let Some(provider_id) = &settings.object_store_provider
+            && !provider_id.is_empty()
+        {
+            let class_type = resources::object_store::ObjectStoreResourceSpec {}.class_type();
+            let provider_id = make_provider_id(provider_id);
+            log::info!("Creating {} resource provider '{}'", class_type, provider_id);
+            ret.insert(
+                provider_id.clone(),
+                agent::ResourceDesc {
+                    class_type: class_type.clone(),
+                    client: Box::new(
+                        resources::object_store::ObjectStoreResourceProvider::new(
+                            data_plane.clone(),
+                            Box::new(telemetry_provider.get_handle(std::collections::BTreeMap::from([
+                                ("RESOURCE_CLASS_TYPE".to_string(), class_type.clone()),
+                                ("RESOURCE_PROVIDER_ID".to_string(), provider_id.clone()),
+                                ("NODE_ID".to_string(), node_id.to_string()),
+                            ]))),
+                            edgeless_api::function_instance::InstanceId::new(node_id),
+                        )
+                        .await,
+                    ),
+                },
+            );
+            provider_specifications.push(edgeless_api::node_registration::ResourceProviderSpecification {
+                provider_id,
+                class_type,
+                outputs: resources::object_store::ObjectStoreResourceSpec {}.outputs(),
+            });
+        }
+
+        if let Some(provider_id) = &settings.grpc_egress_provider
+            && !provider_id.is_empty()
+        {
+            let class_type = resources::grpc_egress::GrpcEgressResourceSpec {}.class_type();
+            let provider_id = make_provider_id(provider_id);
+            log::info!("Creating {} resource provider '{}'", class_type, provider_id);
+            ret.insert(
+                provider_id.clone(),
+                agent::ResourceDesc {
+                    class_type: class_type.clone(),
+                    client: Box::new(
+                        resources::grpc_egress::GrpcEgressResourceProvider::new(
+                            data_plane.clone(),
+                            Box::new(telemetry_provider.get_handle(std::collections::BTreeMap::from([
+                                ("RESOURCE_CLASS_TYPE".to_string(), class_type.clone()),
+                                ("RESOURCE_PROVIDER_ID".to_string(), provider_id.clone()),
+                                ("NODE_ID".to_string(), node_id.to_string()),
+                            ]))),
+                            edgeless_api::function_instance::InstanceId::new(node_id),
+                        )
+                        .await,
+                    ),
+                },
+            );
+            provider_specifications.push(edgeless_api::node_registration::ResourceProviderSpecification {
+                provider_id,
+                class_type,
+                outputs: resources::grpc_egress::GrpcEgressResourceSpec {}.outputs(),
+            });
+        }
+
+        if let Some(provider_id) = &settings.timer_provider
+            && !provider_id.is_empty()
+        {
+            let class_type = resources::timer::TimerResourceSpec {}.class_type();
+            let provider_id = make_provider_id(provider_id);
+            log::info!("Creating {} resource provider '{}'", class_type, provider_id);
+            ret.insert(
+                provider_id.clone(),
+                agent::ResourceDesc {
+                    class_type: class_type.clone(),
+                    client: Box::new(
+                        resources::timer::TimerResourceProvider::new(
+                            data_plane.clone(),
+                            Box::new(telemetry_provider.get_handle(std::collections::BTreeMap::from([
+                                ("RESOURCE_CLASS_TYPE".to_string(), class_type.clone()),
+                                ("RESOURCE_PROVIDER_ID".to_string(), provider_id.clone()),
+                                ("NODE_ID".to_string(), node_id.to_string()),
+                            ]))),
+                            edgeless_api::function_instance::InstanceId::new(node_id),
+                        )
+                        .await,
+                    ),
+                },
+            );
+            provider_specifications.push(edgeless_api::node_registration::ResourceProviderSpecification {
+                provider_id,
+                class_type,
+                outputs: resources::timer::TimerResourceSpec {}.outputs(),
+            });
+        }
+
         if let Some(provider_id) = &settings.sqlx_provider
             && !provider_id.is_empty()
         {
@@ -728,15 +1038,29 @@ pub async fn edgeless_node_main(settings: EdgelessNodeSettings) {
     log::debug!("Settings: {:?}", settings);
 
     // Create the state manager.
-    let state_manager = Box::new(state_management::StateManager::new().await);
+    let state_manager = Box::new(state_management::StateManager::new(&settings.state_management).await);
 
     // Create the data plane.
-    let data_plane = edgeless_dataplane::handle::DataplaneProvider::new(
+    let mut data_plane = edgeless_dataplane::handle::DataplaneProvider::new(
         settings.general.node_id,
         settings.general.invocation_url.clone(),
         settings.general.invocation_url_coap.clone(),
     )
     .await;
+    if let Some(call_timeout_ms) = settings.general.call_timeout_ms {
+        data_plane = data_plane.with_call_timeout(std::time::Duration::from_millis(call_timeout_ms));
+    }
+    data_plane = data_plane.with_inbound_queue(edgeless_dataplane::inbound_queue::InboundQueueSettings {
+        capacity: settings.general.inbound_queue_capacity,
+        overflow: settings.general.inbound_queue_overflow,
+    });
+    data_plane = data_plane.with_invocation_transport(
+        settings.general.invocation_transport,
+        settings
+            .general
+            .invocation_max_batch_size
+            .unwrap_or(edgeless_api::grpc_impl::outer::invocation_stream::DEFAULT_MAX_BATCH_SIZE),
+    );
 
     // Create the performance target.
     let telemetry_performance_target = edgeless_telemetry::performance_target::PerformanceTargetInner::new();
@@ -755,6 +1079,12 @@ pub async fn edgeless_node_main(settings: EdgelessNodeSettings) {
         Ok(telemetry_provider) => telemetry_provider,
         Err(err) => panic!("could not build the telemetry provider: {}", err),
     };
+    data_plane
+        .set_telemetry_handle(Box::new(telemetry_provider.get_handle(std::collections::BTreeMap::from([(
+            "NODE_ID".to_string(),
+            settings.general.node_id.to_string(),
+        )]))))
+        .await;
 
     // List of runners supported by this node to be filled below depending on
     // the node's configuration.
@@ -858,7 +1188,13 @@ pub async fn edgeless_node_main(settings: EdgelessNodeSettings) {
 
     // Create the agent.
     let runtimes = runners.keys().map(|x| x.to_string()).collect::<Vec<String>>();
-    let (mut agent, agent_task) = agent::Agent::new(runners, resources, settings.general.node_id, data_plane.clone());
+    let (mut agent, agent_task) = agent::Agent::new(
+        runners,
+        resources,
+        settings.general.node_id,
+        data_plane.clone(),
+        Some(state_manager as Box<dyn state_management::StateManagerAPI>),
+    );
     let agent_api_server = edgeless_api::grpc_impl::outer::agent::AgentAPIServer::run(
         agent.get_api_client(),
         settings.general.agent_url.clone(),
@@ -896,7 +1232,11 @@ pub fn edgeless_node_default_conf() -> String {
         resources: Some(EdgelessNodeResourceSettings {
             prepend_hostname: true,
             http_ingress_url: Some(String::from("http://127.0.0.1:7008")),
+            http_ingress_tls_cert: Some(String::default()),
+            http_ingress_tls_key: Some(String::default()),
             http_ingress_provider: Some("http-ingress-1".to_string()),
+            ws_ingress_url: Some(String::from("http://127.0.0.1:7009")),
+            ws_ingress_provider: Some("ws-ingress-1".to_string()),
             http_egress_provider: Some("http-egress-1".to_string()),
             http_poster_provider: Some("http-poster-1".to_string()),
             file_pusher_provider: Some(FilePusherSettings::default()),
@@ -906,10 +1246,17 @@ pub fn edgeless_node_default_conf() -> String {
             ollama_provider: Some(OllamaProviderSettings::default()),
             serverless_provider: Some(vec![ServerlessProviderSettings::default()]),
             kafka_egress_provider: Some(String::default()),
+            kafka_ingress_provider: Some(String::default()),
+            mqtt_ingress_provider: Some(String::default()),
+            mqtt_egress_provider: Some(String::default()),
+            object_store_provider: Some("object-store-1".to_string()),
+            grpc_egress_provider: Some(String::default()),
+            timer_provider: Some("timer-1".to_string()),
             sqlx_provider: Some("sqlx-1".to_string()),
         }),
         user_node_capabilities: Some(NodeCapabilitiesUser::default()),
         power_info: None,
+        state_management: Some(EdgelessNodeStateSettings::default()),
     };
     toml::to_string(&node_conf).expect("Wrong")
 }
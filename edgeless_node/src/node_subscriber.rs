@@ -147,6 +147,8 @@ impl NodeSubscriber {
                         std::mem::take(&mut metrics.function_times[edgeless_telemetry::performance_target::FunctionTime::Stop as usize]);
                     let function_transfer_times =
                         std::mem::take(&mut metrics.function_times[edgeless_telemetry::performance_target::FunctionTime::Transfer as usize]);
+                    let function_queue_depths =
+                        std::mem::take(&mut metrics.function_times[edgeless_telemetry::performance_target::FunctionTime::QueueDepth as usize]);
                     let update_node_request = edgeless_api::node_registration::UpdateNodeRequest {
                         node_id,
                         invocation_url: invocation_url.clone(),
@@ -162,6 +164,7 @@ impl NodeSubscriber {
                             function_execution_times,
                             function_stop_times,
                             function_transfer_times,
+                            function_queue_depths,
                             function_log_entries: metrics.function_log_entries,
                         },
                     };
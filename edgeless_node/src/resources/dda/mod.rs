@@ -316,7 +316,8 @@ impl DDAResource {
                     Some(target_function_id) => match dataplane_event_type.as_str() {
                         "cast" => {
                             let _ = handle
-                                .send(
+                                .send_output(
+                                    &dda_sub.target,
                                     target_function_id,
                                     encoded_event,
                                     &edgeless_api::function_instance::EventMetadata::empty_new_root(),
@@ -507,7 +508,7 @@ impl DDAResource {
                     edgeless_dataplane::core::Message::Call(data) => {
                         // all calls to DDA resource must be Calls with
                         // DataplaneDDA as serialized data
-                        serde_json::from_str::<dda::DDA>(&data).expect("wrong incoming dataplane event from a function")
+                        serde_json::from_slice::<dda::DDA>(&data).expect("wrong incoming dataplane event from a function")
                     }
                     _ => {
                         // disregard anything but Calls
@@ -545,7 +546,7 @@ impl DDAResource {
                             ..Default::default()
                         };
                         let _ = dda_com_client.publish_event(event).await;
-                        respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await;
+                        respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await;
                     }
                     dda::DDA::ComPublishAction(alias, data) => {
                         let p = match dda_pub_map.get(&alias) {
@@ -578,7 +579,7 @@ impl DDAResource {
                                     Ok(response) => {
                                         let action_result = response.expect("expected an action result!").data;
                                         let res = dda::DDA::ComSubscribeActionResult(action_result);
-                                        let r = serde_json::to_string(&res).expect("wrong");
+                                        let r = serde_json::to_vec(&res).expect("wrong");
                                         respond(edgeless_dataplane::core::CallRet::Reply(r)).await;
                                     }
                                     Err(status) => {
@@ -626,7 +627,7 @@ impl DDAResource {
                                     Ok(response) => {
                                         let query_result = response.expect("expected a query result!").data;
                                         let res = dda::DDA::ComSubscribeQueryResult(query_result);
-                                        let r = serde_json::to_string(&res).expect("should never happen");
+                                        let r = serde_json::to_vec(&res).expect("should never happen");
                                         respond(edgeless_dataplane::core::CallRet::Reply(r)).await;
                                     }
                                     Err(status) => {
@@ -652,7 +653,7 @@ impl DDAResource {
                             correlation_id,
                         };
                         match dda_com_client.publish_action_result(action_result_correlated).await {
-                            Ok(_) => respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await,
+                            Ok(_) => respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await,
                             Err(status) => {
                                 log::error!("publishing action result failed: {:?}", status);
                                 respond(edgeless_dataplane::core::CallRet::Err).await;
@@ -670,7 +671,7 @@ impl DDAResource {
                             correlation_id,
                         };
                         match dda_com_client.publish_query_result(query_result_correlated).await {
-                            Ok(_) => respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await,
+                            Ok(_) => respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await,
                             Err(status) => {
                                 log::error!("publishing query result failed: {:?}", status.message());
                                 respond(edgeless_dataplane::core::CallRet::Err).await;
@@ -685,7 +686,7 @@ impl DDAResource {
                         };
                         match dda_state_client.propose_input(set_input).await {
                             Ok(_) => {
-                                respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await;
+                                respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await;
                             }
                             Err(e) => {
                                 log::error!("DDA: StatePublishSet: {:?}", e.message());
@@ -701,7 +702,7 @@ impl DDAResource {
                         };
                         match dda_state_client.propose_input(delete_input).await {
                             Ok(_) => {
-                                respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await;
+                                respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await;
                             }
                             Err(e) => {
                                 log::error!("DDA: StatePublishDelete: {:?}", e.message());
@@ -714,11 +715,10 @@ impl DDAResource {
                         match dda_store_client.get(get).await {
                             Ok(val) => match val.into_inner().value {
                                 Some(v) => {
-                                    let v_as_str = String::from_utf8(v).expect("should never happen");
-                                    respond(edgeless_dataplane::core::CallRet::Reply(v_as_str)).await;
+                                    respond(edgeless_dataplane::core::CallRet::Reply(v)).await;
                                 }
                                 None => {
-                                    respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await;
+                                    respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await;
                                 }
                             },
                             Err(e) => {
@@ -731,7 +731,7 @@ impl DDAResource {
                         let set = dda_store::KeyValue { key, value };
                         match dda_store_client.set(set).await {
                             Ok(_) => {
-                                respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await;
+                                respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await;
                             }
                             Err(e) => {
                                 log::error!("DDA: StoreSet: {:?}", e.message());
@@ -743,7 +743,7 @@ impl DDAResource {
                         let delete = dda_store::Key { key };
                         match dda_store_client.delete(delete).await {
                             Ok(_) => {
-                                respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await;
+                                respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await;
                             }
                             Err(e) => {
                                 log::error!("DDA: StoreDelete: {:?}", e.message());
@@ -755,7 +755,7 @@ impl DDAResource {
                         let delete_all = dda_store::DeleteAllParams {};
                         match dda_store_client.delete_all(delete_all).await {
                             Ok(_) => {
-                                respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await;
+                                respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await;
                             }
                             Err(e) => {
                                 log::error!("DDA: StoreDeleteAll: {:?}", e.message());
@@ -767,7 +767,7 @@ impl DDAResource {
                         let delete_prefix = dda_store::Key { key };
                         match dda_store_client.delete_prefix(delete_prefix).await {
                             Ok(_) => {
-                                respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await;
+                                respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await;
                             }
                             Err(e) => {
                                 log::error!("DDA: StoreDeletePrefix: {:?}", e.message());
@@ -779,7 +779,7 @@ impl DDAResource {
                         let delete_range = dda_store::Range { start, end };
                         match dda_store_client.delete_range(delete_range).await {
                             Ok(_) => {
-                                respond(edgeless_dataplane::core::CallRet::Reply("".to_string())).await;
+                                respond(edgeless_dataplane::core::CallRet::Reply(Vec::new())).await;
                             }
                             Err(e) => {
                                 log::error!("DDA: StoreDeleteRange: {:?}", e.message());
@@ -802,7 +802,7 @@ impl DDAResource {
                                             value: vec![],
                                         });
                                         let key_value = dda::DDA::StoreScanPrefixResult(res.key, res.value);
-                                        let r = serde_json::to_string(&key_value).expect("should never happen");
+                                        let r = serde_json::to_vec(&key_value).expect("should never happen");
                                         respond(edgeless_dataplane::core::CallRet::Reply(r)).await;
                                     }
                                     Err(status) => {
@@ -830,7 +830,7 @@ impl DDAResource {
                                             value: vec![],
                                         });
                                         let key_value = dda::DDA::StoreScanRangeResult(res.key, res.value);
-                                        let r = serde_json::to_string(&key_value).expect("should never happen");
+                                        let r = serde_json::to_vec(&key_value).expect("should never happen");
                                         respond(edgeless_dataplane::core::CallRet::Reply(r)).await;
                                     }
                                     Err(status) => {
@@ -103,7 +103,7 @@ impl FileLogResource {
                 if add_source_id {
                     line.push_str(format!("{} ", source_id).as_str());
                 }
-                line.push_str(&message_data);
+                line.push_str(&String::from_utf8_lossy(&message_data));
 
                 // Dump the line to the output file.
                 log::debug!("{}", line);
@@ -114,7 +114,7 @@ impl FileLogResource {
                 // Reply to the caller if the resource instance was called.
                 if need_reply {
                     dataplane_handle
-                        .reply(source_id, channel_id, edgeless_dataplane::core::CallRet::Reply("".to_string()), &metadata)
+                        .reply(source_id, channel_id, edgeless_dataplane::core::CallRet::Reply(Vec::new()), &metadata)
                         .await;
                 }
 
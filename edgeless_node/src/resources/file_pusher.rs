@@ -75,18 +75,18 @@ impl FilePusherResource {
                 let inner = inner.lock().await;
 
                 let msg = if num_files == 0 {
-                    String::default()
+                    Vec::new()
                 } else if encode_base64 {
-                    base64::engine::general_purpose::STANDARD.encode(inner.files[cur].clone())
+                    base64::engine::general_purpose::STANDARD.encode(&inner.files[cur]).into_bytes()
                 } else {
-                    String::from_utf8(inner.files[cur].to_vec()).unwrap_or_default()
+                    inner.files[cur].clone()
                 };
 
                 if let Some(instance) = inner.instances.get(&self_function_id)
                     && let Some(instance_id) = instance.target
                 {
                     dataplane_handle
-                        .send(instance_id, msg, &edgeless_api::function_instance::EventMetadata::empty_new_root())
+                        .send_output("out", instance_id, msg, &edgeless_api::function_instance::EventMetadata::empty_new_root())
                         .await;
                 }
 
@@ -0,0 +1,570 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use base64::Engine;
+
+/// Default timeout of the calls to the gRPC service, in ms.
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+pub struct GrpcEgressResourceSpec {}
+
+impl super::resource_provider_specs::ResourceProviderSpecs for GrpcEgressResourceSpec {
+    fn class_type(&self) -> String {
+        String::from("grpc-egress")
+    }
+
+    fn description(&self) -> String {
+        r"Invoke a unary method of an external gRPC service
+
+The message body is the request message encoded in JSON, which is converted to protobuf using the descriptor set in the configuration.
+With call(), the reply contains the response message encoded in JSON."
+            .to_string()
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn configurations(&self) -> std::collections::HashMap<String, String> {
+        std::collections::HashMap::from([
+            (String::from("url"), String::from("URL of the gRPC server, e.g., http://127.0.0.1:50051")),
+            (
+                String::from("method"),
+                String::from("Path of the method invoked, e.g., /package.Service/Method"),
+            ),
+            (
+                String::from("descriptor_set"),
+                String::from(
+                    "Base64-encoded FileDescriptorSet containing the service, e.g., generated with protoc --include_imports --descriptor_set_out",
+                ),
+            ),
+            (
+                String::from("descriptor_set_file"),
+                String::from("Path of a file on the node containing the FileDescriptorSet, if 'descriptor_set' is not specified"),
+            ),
+            (String::from("timeout_ms"), String::from("Timeout of the calls, in ms. Default: 5000")),
+        ])
+    }
+
+    fn version(&self) -> String {
+        String::from("1.0")
+    }
+}
+
+/// Return the descriptor of a method, in the form /package.Service/Method,
+/// from a descriptor pool.
+fn find_method(pool: &prost_reflect::DescriptorPool, path: &str) -> anyhow::Result<prost_reflect::MethodDescriptor> {
+    let (service_name, method_name) = path
+        .trim_start_matches('/')
+        .split_once('/')
+        .ok_or(anyhow::anyhow!("Invalid method path: {}", path))?;
+    let service = pool
+        .get_service_by_name(service_name)
+        .ok_or(anyhow::anyhow!("Service not found in the descriptor set: {}", service_name))?;
+    let method = service.methods().find(|method| method.name() == method_name).ok_or(anyhow::anyhow!(
+        "Method not found in service {}: {}",
+        service_name,
+        method_name
+    ))?;
+    if method.is_client_streaming() || method.is_server_streaming() {
+        anyhow::bail!("Streaming methods are not supported: {}", path);
+    }
+    Ok(method)
+}
+
+/// Return the descriptor of the method in a resource configuration.
+fn method_from_configuration(configuration: &std::collections::HashMap<String, String>) -> anyhow::Result<prost_reflect::MethodDescriptor> {
+    let descriptor_set = match (configuration.get("descriptor_set"), configuration.get("descriptor_set_file")) {
+        (Some(encoded), _) => base64::engine::general_purpose::STANDARD.decode(encoded)?,
+        (None, Some(path)) => std::fs::read(path)?,
+        (None, None) => anyhow::bail!("Missing field 'descriptor_set' or 'descriptor_set_file'"),
+    };
+    let pool = prost_reflect::DescriptorPool::decode(descriptor_set.as_slice())?;
+    find_method(&pool, configuration.get("method").ok_or(anyhow::anyhow!("Missing field 'method'"))?)
+}
+
+/// Codec of the messages built at run-time from their descriptors.
+#[derive(Clone)]
+pub(crate) struct DynamicCodec {
+    /// Descriptor of the messages decoded.
+    decode_descriptor: prost_reflect::MessageDescriptor,
+}
+
+pub(crate) struct DynamicEncoder {}
+
+pub(crate) struct DynamicDecoder {
+    descriptor: prost_reflect::MessageDescriptor,
+}
+
+impl tonic::codec::Codec for DynamicCodec {
+    type Encode = prost_reflect::DynamicMessage;
+    type Decode = prost_reflect::DynamicMessage;
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder {}
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder {
+            descriptor: self.decode_descriptor.clone(),
+        }
+    }
+}
+
+impl tonic::codec::Encoder for DynamicEncoder {
+    type Item = prost_reflect::DynamicMessage;
+    type Error = tonic::Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut tonic::codec::EncodeBuf<'_>) -> Result<(), Self::Error> {
+        prost::Message::encode(&item, dst).map_err(|err| tonic::Status::internal(err.to_string()))
+    }
+}
+
+impl tonic::codec::Decoder for DynamicDecoder {
+    type Item = prost_reflect::DynamicMessage;
+    type Error = tonic::Status;
+
+    fn decode(&mut self, src: &mut tonic::codec::DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        prost_reflect::DynamicMessage::decode(self.descriptor.clone(), src)
+            .map(Some)
+            .map_err(|err| tonic::Status::internal(err.to_string()))
+    }
+}
+
+#[derive(Clone)]
+pub struct GrpcEgressResourceProvider {
+    inner: std::sync::Arc<tokio::sync::Mutex<GrpcEgressResourceProviderInner>>,
+}
+
+pub struct GrpcEgressResourceProviderInner {
+    resource_provider_id: edgeless_api::function_instance::InstanceId,
+    dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+    telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+    instances: std::collections::HashMap<edgeless_api::function_instance::InstanceId, GrpcEgressResource>,
+}
+
+pub struct GrpcEgressResource {
+    join_handle: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for GrpcEgressResource {
+    fn drop(&mut self) {
+        self.join_handle.abort();
+    }
+}
+
+/// The grpc-egress resource invokes a unary method of an external gRPC
+/// service for every event received, converting the JSON payload into the
+/// request message and the response message into JSON, based on the
+/// descriptors in the resource configuration.
+/// The connection with the server is established upon the first event.
+impl GrpcEgressResource {
+    async fn new(
+        dataplane_handle: edgeless_dataplane::handle::DataplaneHandle,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        url: &str,
+        method: prost_reflect::MethodDescriptor,
+        timeout: std::time::Duration,
+    ) -> anyhow::Result<Self> {
+        let mut dataplane_handle = dataplane_handle;
+        let mut telemetry_handle = telemetry_handle;
+
+        let channel = tonic::transport::Channel::from_shared(url.to_string())?.connect_lazy();
+        let path = tonic::codegen::http::uri::PathAndQuery::try_from(format!("/{}/{}", method.parent_service().full_name(), method.name()))?;
+
+        let handle = tokio::spawn(async move {
+            loop {
+                let edgeless_dataplane::core::DataplaneEvent {
+                    source_id,
+                    channel_id,
+                    message,
+                    created,
+                    metadata,
+                } = dataplane_handle.receive_next().await;
+                let started = crate::resources::observe_transfer(created, &mut telemetry_handle);
+
+                let (need_reply, message_data) = match message {
+                    edgeless_dataplane::core::Message::Call(data) => (true, data),
+                    edgeless_dataplane::core::Message::Cast(data) => (false, data),
+                    _ => {
+                        continue;
+                    }
+                };
+
+                // Invoke the method in a separate task, so that a slow
+                // server does not delay the events that follow.
+                let mut cloned_dataplane = dataplane_handle.clone();
+                let channel = channel.clone();
+                let path = path.clone();
+                let method = method.clone();
+                tokio::spawn(async move {
+                    let ret = match invoke(channel, path.clone(), &method, timeout, &message_data).await {
+                        Ok(reply) => edgeless_dataplane::core::CallRet::Reply(reply),
+                        Err(err) => {
+                            log::error!("Error when invoking gRPC method {}: {}", path, err);
+                            edgeless_dataplane::core::CallRet::Err
+                        }
+                    };
+                    if need_reply {
+                        cloned_dataplane.reply(source_id, channel_id, ret, &metadata).await;
+                    }
+                });
+
+                crate::resources::observe_execution(started, &mut telemetry_handle, need_reply);
+            }
+        });
+
+        Ok(Self { join_handle: handle })
+    }
+}
+
+/// Invoke a unary method with the request in JSON and return the response
+/// in JSON.
+async fn invoke(
+    channel: tonic::transport::Channel,
+    path: tonic::codegen::http::uri::PathAndQuery,
+    method: &prost_reflect::MethodDescriptor,
+    timeout: std::time::Duration,
+    data: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let mut deserializer = serde_json::Deserializer::from_slice(data);
+    let request = prost_reflect::DynamicMessage::deserialize(method.input(), &mut deserializer)?;
+    deserializer.end()?;
+
+    let mut request = tonic::Request::new(request);
+    request.set_timeout(timeout);
+
+    let mut client = tonic::client::Grpc::new(channel);
+    client.ready().await?;
+    let response = client
+        .unary(
+            request,
+            path,
+            DynamicCodec {
+                decode_descriptor: method.output(),
+            },
+        )
+        .await?;
+
+    Ok(serde_json::to_vec(response.get_ref())?)
+}
+
+impl GrpcEgressResourceProvider {
+    pub async fn new(
+        dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        resource_provider_id: edgeless_api::function_instance::InstanceId,
+    ) -> Self {
+        Self {
+            inner: std::sync::Arc::new(tokio::sync::Mutex::new(GrpcEgressResourceProviderInner {
+                resource_provider_id,
+                dataplane_provider,
+                telemetry_handle,
+                instances: std::collections::HashMap::<edgeless_api::function_instance::InstanceId, GrpcEgressResource>::new(),
+            })),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId> for GrpcEgressResourceProvider {
+    async fn start(
+        &mut self,
+        instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
+    ) -> anyhow::Result<edgeless_api::common::StartComponentResponse<edgeless_api::function_instance::InstanceId>> {
+        let mut lck = self.inner.lock().await;
+        let new_id = edgeless_api::function_instance::InstanceId::new(lck.resource_provider_id.node_id);
+        let dataplane_handle = lck.dataplane_provider.get_handle_for(new_id).await;
+        let telemetry_handle = lck.telemetry_handle.fork(std::collections::BTreeMap::from([(
+            "FUNCTION_ID".to_string(),
+            new_id.function_id.to_string(),
+        )]));
+
+        let configuration = &instance_specification.configuration;
+        let res = async {
+            let url = configuration.get("url").ok_or(anyhow::anyhow!("Missing field 'url'"))?;
+            let method = method_from_configuration(configuration)?;
+            let timeout = match configuration.get("timeout_ms") {
+                Some(val) => val.parse::<u64>()?,
+                None => DEFAULT_TIMEOUT_MS,
+            };
+            log::info!("GrpcEgressResource created, url {}, method {}", url, method.full_name());
+            GrpcEgressResource::new(dataplane_handle, telemetry_handle, url, method, std::time::Duration::from_millis(timeout)).await
+        }
+        .await;
+
+        match res {
+            Ok(resource) => {
+                lck.instances.insert(new_id, resource);
+                Ok(edgeless_api::common::StartComponentResponse::InstanceId(new_id))
+            }
+            Err(err) => Ok(edgeless_api::common::StartComponentResponse::ResponseError(
+                edgeless_api::common::ResponseError {
+                    summary: "Invalid resource configuration".to_string(),
+                    detail: Some(err.to_string()),
+                },
+            )),
+        }
+    }
+
+    async fn stop(&mut self, resource_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
+        self.inner.lock().await.instances.remove(&resource_id);
+        Ok(())
+    }
+
+    async fn patch(&mut self, _update: edgeless_api::common::PatchRequest) -> anyhow::Result<()> {
+        // the resource has no channels: nothing to be patched
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edgeless_api::resource_configuration::ResourceConfigurationAPI;
+    use prost_reflect::prost_types;
+
+    struct MockTelemetryHandle {}
+
+    impl edgeless_telemetry::telemetry_events::TelemetryHandleAPI for MockTelemetryHandle {
+        fn observe(&mut self, _event: edgeless_telemetry::telemetry_events::TelemetryEvent, _event_tags: std::collections::BTreeMap<String, String>) {
+        }
+        fn fork(
+            &mut self,
+            _child_tags: std::collections::BTreeMap<String, String>,
+        ) -> Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI> {
+            Box::new(MockTelemetryHandle {})
+        }
+    }
+
+    /// Return the descriptor set of the following service:
+    ///
+    /// package test;
+    /// message EchoRequest { string text = 1; int32 times = 2; }
+    /// message EchoReply { string text = 1; }
+    /// service Echo {
+    ///   rpc Say(EchoRequest) returns (EchoReply);
+    ///   rpc Listen(EchoRequest) returns (stream EchoReply);
+    /// }
+    fn descriptor_set() -> Vec<u8> {
+        let field = |name: &str, number: i32, r#type: prost_types::field_descriptor_proto::Type| prost_types::FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(prost_types::field_descriptor_proto::Label::Optional as i32),
+            r#type: Some(r#type as i32),
+            json_name: Some(name.to_string()),
+            ..Default::default()
+        };
+        let method = |name: &str, server_streaming: bool| prost_types::MethodDescriptorProto {
+            name: Some(name.to_string()),
+            input_type: Some(String::from(".test.EchoRequest")),
+            output_type: Some(String::from(".test.EchoReply")),
+            server_streaming: Some(server_streaming),
+            ..Default::default()
+        };
+        let file = prost_types::FileDescriptorProto {
+            name: Some(String::from("test.proto")),
+            package: Some(String::from("test")),
+            syntax: Some(String::from("proto3")),
+            message_type: vec![
+                prost_types::DescriptorProto {
+                    name: Some(String::from("EchoRequest")),
+                    field: vec![
+                        field("text", 1, prost_types::field_descriptor_proto::Type::String),
+                        field("times", 2, prost_types::field_descriptor_proto::Type::Int32),
+                    ],
+                    ..Default::default()
+                },
+                prost_types::DescriptorProto {
+                    name: Some(String::from("EchoReply")),
+                    field: vec![field("text", 1, prost_types::field_descriptor_proto::Type::String)],
+                    ..Default::default()
+                },
+            ],
+            service: vec![prost_types::ServiceDescriptorProto {
+                name: Some(String::from("Echo")),
+                method: vec![method("Say", false), method("Listen", true)],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        prost::Message::encode_to_vec(&prost_types::FileDescriptorSet { file: vec![file] })
+    }
+
+    /// gRPC server implementing test.Echo/Say with the dynamic codec.
+    /// The reply to the text "slow" is delayed by one second.
+    #[derive(Clone)]
+    struct EchoServer {
+        method: prost_reflect::MethodDescriptor,
+    }
+
+    struct EchoService {
+        method: prost_reflect::MethodDescriptor,
+    }
+
+    impl tonic::server::UnaryService<prost_reflect::DynamicMessage> for EchoService {
+        type Response = prost_reflect::DynamicMessage;
+        type Future = tonic::codegen::BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+
+        fn call(&mut self, request: tonic::Request<prost_reflect::DynamicMessage>) -> Self::Future {
+            let output = self.method.output();
+            Box::pin(async move {
+                let request = request.into_inner();
+                let text = request
+                    .get_field_by_name("text")
+                    .and_then(|x| x.as_str().map(|x| x.to_string()))
+                    .unwrap_or_default();
+                let times = request.get_field_by_name("times").and_then(|x| x.as_i32()).unwrap_or_default();
+                if text == "slow" {
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                }
+                let mut reply = prost_reflect::DynamicMessage::new(output);
+                reply.set_field_by_name("text", prost_reflect::Value::String(text.repeat(times as usize)));
+                Ok(tonic::Response::new(reply))
+            })
+        }
+    }
+
+    impl<B> tonic::codegen::Service<tonic::codegen::http::Request<B>> for EchoServer
+    where
+        B: tonic::codegen::Body + std::marker::Send + 'static,
+        B::Error: Into<tonic::codegen::StdError> + std::marker::Send + 'static,
+    {
+        type Response = tonic::codegen::http::Response<tonic::body::Body>;
+        type Error = std::convert::Infallible;
+        type Future = tonic::codegen::BoxFuture<Self::Response, Self::Error>;
+
+        fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: tonic::codegen::http::Request<B>) -> Self::Future {
+            let method = self.method.clone();
+            Box::pin(async move {
+                let codec = DynamicCodec {
+                    decode_descriptor: method.input(),
+                };
+                let mut grpc = tonic::server::Grpc::new(codec);
+                Ok(grpc.unary(EchoService { method }, req).await)
+            })
+        }
+    }
+
+    impl tonic::server::NamedService for EchoServer {
+        const NAME: &'static str = "test.Echo";
+    }
+
+    #[test]
+    fn test_grpc_egress_method_from_configuration() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(descriptor_set());
+        let configuration = |entries: Vec<(&str, &str)>| -> std::collections::HashMap<String, String> {
+            entries.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+
+        let method = method_from_configuration(&configuration(vec![("descriptor_set", &encoded), ("method", "/test.Echo/Say")])).unwrap();
+        assert_eq!("test.Echo.Say", method.full_name());
+        assert_eq!("test.EchoRequest", method.input().full_name());
+        assert_eq!("test.EchoReply", method.output().full_name());
+
+        assert!(method_from_configuration(&configuration(vec![("method", "/test.Echo/Say")])).is_err());
+        assert!(method_from_configuration(&configuration(vec![("descriptor_set", &encoded)])).is_err());
+        assert!(method_from_configuration(&configuration(vec![("descriptor_set", "not base64!"), ("method", "/test.Echo/Say")])).is_err());
+        assert!(method_from_configuration(&configuration(vec![("descriptor_set", &encoded), ("method", "/test.Echo/Shout")])).is_err());
+        assert!(method_from_configuration(&configuration(vec![("descriptor_set", &encoded), ("method", "/test.Other/Say")])).is_err());
+        assert!(method_from_configuration(&configuration(vec![("descriptor_set", &encoded), ("method", "/test.Echo/Listen")])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_grpc_egress_call() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(descriptor_set());
+        let pool = prost_reflect::DescriptorPool::decode(descriptor_set().as_slice()).unwrap();
+        let server = EchoServer {
+            method: find_method(&pool, "/test.Echo/Say").unwrap(),
+        };
+        tokio::spawn(async move {
+            tonic::transport::Server::builder()
+                .add_service(server)
+                .serve("127.0.0.1:50151".parse().unwrap())
+                .await
+                .unwrap();
+        });
+
+        let node_id = uuid::Uuid::new_v4();
+        let mut dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7089".to_string(), None).await;
+        let mut provider = GrpcEgressResourceProvider::new(
+            dataplane_provider.clone(),
+            Box::new(MockTelemetryHandle {}),
+            edgeless_api::function_instance::InstanceId::new(node_id),
+        )
+        .await;
+
+        let res = provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("grpc-egress"),
+                configuration: std::collections::HashMap::from([
+                    (String::from("url"), String::from("http://127.0.0.1:50151")),
+                    (String::from("method"), String::from("/test.Echo/Say")),
+                    (String::from("descriptor_set"), encoded),
+                ]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        let resource_id = match res {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+
+        let mut client = dataplane_provider
+            .get_handle_for(edgeless_api::function_instance::InstanceId::new(node_id))
+            .await;
+        let metadata = edgeless_api::function_instance::EventMetadata::empty_new_root();
+
+        // Wait for the server to be ready.
+        let mut ret = edgeless_dataplane::core::CallRet::Err;
+        for _ in 0..50 {
+            ret = client.call(resource_id, br#"{"text":"ab","times":3}"#.to_vec(), &metadata).await;
+            if ret != edgeless_dataplane::core::CallRet::Err {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(edgeless_dataplane::core::CallRet::Reply(br#"{"text":"ababab"}"#.to_vec()), ret);
+
+        // A slow invocation does not delay the following ones.
+        let mut slow_client = dataplane_provider
+            .get_handle_for(edgeless_api::function_instance::InstanceId::new(node_id))
+            .await;
+        let slow_metadata = metadata.clone();
+        let slow = tokio::spawn(async move {
+            slow_client
+                .call(resource_id, br#"{"text":"slow","times":1}"#.to_vec(), &slow_metadata)
+                .await
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let started = std::time::Instant::now();
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Reply(br#"{"text":"a"}"#.to_vec()),
+            client.call(resource_id, br#"{"text":"a","times":1}"#.to_vec(), &metadata).await
+        );
+        assert!(started.elapsed() < std::time::Duration::from_millis(500));
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Reply(br#"{"text":"slow"}"#.to_vec()),
+            slow.await.unwrap()
+        );
+
+        // Fields not in the request message and invalid JSON are errors.
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Err,
+            client.call(resource_id, br#"{"unknown":"ab"}"#.to_vec(), &metadata).await
+        );
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Err,
+            client.call(resource_id, b"not json".to_vec(), &metadata).await
+        );
+
+        provider.stop(resource_id).await.unwrap();
+    }
+}
@@ -20,11 +20,16 @@ impl super::resource_provider_specs::ResourceProviderSpecs for HttpEgressResourc
     }
 
     fn configurations(&self) -> std::collections::HashMap<String, String> {
-        std::collections::HashMap::new()
+        std::collections::HashMap::from([(
+            String::from("max_body_bytes"),
+            String::from(
+                "Maximum size, in bytes, of the body of the responses received and of the requests reassembled from chunks streamed by an http-ingress resource. 0 means no limit. Default: 16 MiB.",
+            ),
+        )])
     }
 
     fn version(&self) -> String {
-        String::from("1.1")
+        String::from("1.2")
     }
 }
 
@@ -40,6 +45,293 @@ struct EgressResourceProviderInner {
     egress_instances: std::collections::HashMap<edgeless_api::function_instance::InstanceId, EgressResource>,
 }
 
+/// Maximum time between two consecutive chunks of a streamed request,
+/// after which the stream is aborted.
+const STREAM_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Interval between two checks of the streamed requests timed out.
+const EXPIRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Maximum number of chunks of a streamed request received out of order,
+/// above which the stream is aborted.
+const MAX_PENDING_CHUNKS: usize = 1024;
+
+/// Identifier of a streamed request: component sending the chunks and
+/// stream identifier.
+type StreamKey = (edgeless_api::function_instance::InstanceId, u64);
+
+/// Sender of the body of a streamed request to the task performing it.
+///
+/// The channel is unbounded so that a slow web server does not stall the
+/// other requests, since the data queued cannot exceed the maximum body
+/// size anyway.
+type BodySender = tokio::sync::mpsc::UnboundedSender<Result<Vec<u8>, std::io::Error>>;
+
+/// Call carrying the last chunk of a streamed request, which is replied to
+/// with the response: source, channel and metadata of the call.
+type Responder = (
+    edgeless_api::function_instance::InstanceId,
+    u64,
+    edgeless_api::function_instance::EventMetadata,
+);
+
+/// Streamed request being performed.
+struct StreamBody {
+    /// Sender of the body to the task performing the request.
+    sender: BodySender,
+    /// Sender of the call to be replied to by the task performing the
+    /// request, once the body is complete.
+    responder: tokio::sync::oneshot::Sender<Responder>,
+}
+
+/// Action to be taken on a streamed request upon receiving a chunk.
+#[derive(Debug, PartialEq)]
+enum StreamAction {
+    /// Start the request, whose body will follow.
+    Start(Box<edgeless_http::EdgelessHTTPRequest>),
+    /// Append data to the body of the request.
+    Data(Vec<u8>),
+    /// The body of the request is complete.
+    End,
+    /// Interrupt the request.
+    Abort,
+}
+
+/// Stream whose chunks are being received.
+struct PartialStream {
+    /// Sequence number of the next chunk expected.
+    next_seq: u64,
+    /// Chunks received out of order.
+    pending: std::collections::BTreeMap<u64, edgeless_http::EdgelessHTTPChunk>,
+    /// Number of bytes of the body received so far.
+    received: usize,
+    /// True if the stream has been aborted, but further chunks may arrive.
+    discarded: bool,
+    /// Time when the last chunk has been received.
+    last_update: std::time::Instant,
+}
+
+/// Reassembles the requests streamed as sequences of chunks, which may be
+/// received out of order, within a maximum body size and a maximum number
+/// of chunks kept out of order.
+struct ChunkReassembler {
+    max_body_bytes: usize,
+    streams: std::collections::HashMap<StreamKey, PartialStream>,
+}
+
+impl ChunkReassembler {
+    fn new(max_body_bytes: usize) -> Self {
+        Self {
+            max_body_bytes,
+            streams: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Process a chunk and return the actions to be taken on its stream, in
+    /// order.
+    fn push(&mut self, key: StreamKey, chunk: edgeless_http::EdgelessHTTPChunk, now: std::time::Instant) -> Vec<StreamAction> {
+        let stream = self.streams.entry(key).or_insert_with(|| PartialStream {
+            next_seq: 0,
+            pending: std::collections::BTreeMap::new(),
+            received: 0,
+            discarded: false,
+            last_update: now,
+        });
+        stream.last_update = now;
+
+        if chunk.aborted || (stream.discarded && chunk.last) {
+            let discarded = stream.discarded;
+            self.streams.remove(&key);
+            return if discarded { vec![] } else { vec![StreamAction::Abort] };
+        }
+        // Ignore the chunks already received.
+        if stream.discarded || chunk.seq < stream.next_seq || stream.pending.contains_key(&chunk.seq) {
+            return vec![];
+        }
+
+        if chunk.seq > 0 {
+            stream.received += chunk.data.len();
+            if edgeless_http::exceeds(stream.received, self.max_body_bytes) {
+                stream.discarded = true;
+                stream.pending.clear();
+                return vec![StreamAction::Abort];
+            }
+        }
+        if chunk.seq != stream.next_seq && stream.pending.len() >= MAX_PENDING_CHUNKS {
+            log::warn!(
+                "Too many chunks of streamed HTTP request {} from {:?} received out of order",
+                key.1,
+                key.0
+            );
+            stream.discarded = true;
+            stream.pending.clear();
+            return vec![StreamAction::Abort];
+        }
+        stream.pending.insert(chunk.seq, chunk);
+
+        let mut actions = vec![];
+        while let Some(chunk) = stream.pending.remove(&stream.next_seq) {
+            stream.next_seq += 1;
+            if chunk.seq == 0 {
+                match std::str::from_utf8(&chunk.data)
+                    .map_err(anyhow::Error::from)
+                    .and_then(edgeless_http::request_from_string)
+                {
+                    Ok(req) => actions.push(StreamAction::Start(Box::new(req))),
+                    Err(err) => {
+                        log::warn!("Invalid head of streamed HTTP request: {}", err);
+                        stream.discarded = true;
+                        stream.pending.clear();
+                        return vec![];
+                    }
+                }
+            } else if !chunk.data.is_empty() {
+                actions.push(StreamAction::Data(chunk.data));
+            }
+            if chunk.last {
+                actions.push(StreamAction::End);
+                self.streams.remove(&key);
+                break;
+            }
+        }
+        actions
+    }
+
+    /// Remove the streams that have not received chunks for too long.
+    fn expire(&mut self, now: std::time::Instant) -> Vec<StreamKey> {
+        let expired = self
+            .streams
+            .iter()
+            .filter(|(_key, stream)| now.duration_since(stream.last_update) > STREAM_TIMEOUT)
+            .map(|(key, _stream)| *key)
+            .collect::<Vec<_>>();
+        for key in &expired {
+            self.streams.remove(key);
+        }
+        expired
+    }
+}
+
+/// Requests streamed to an egress resource, which are performed while their
+/// chunks are received.
+///
+/// The last chunk of a stream may be received with a call, which is replied
+/// to with the response once the request is completed, or with an error if
+/// the request cannot be performed.
+struct StreamedRequests {
+    reassembler: ChunkReassembler,
+    bodies: std::collections::HashMap<StreamKey, StreamBody>,
+    /// Calls received with the last chunk of a stream not yet complete.
+    responders: std::collections::HashMap<StreamKey, Responder>,
+    max_body_bytes: usize,
+}
+
+impl StreamedRequests {
+    fn new(max_body_bytes: usize) -> Self {
+        Self {
+            reassembler: ChunkReassembler::new(max_body_bytes),
+            bodies: std::collections::HashMap::new(),
+            responders: std::collections::HashMap::new(),
+            max_body_bytes,
+        }
+    }
+
+    /// Process a chunk, received with a call if `responder` is set.
+    async fn push(
+        &mut self,
+        dataplane_handle: &mut edgeless_dataplane::handle::DataplaneHandle,
+        key: StreamKey,
+        chunk: edgeless_http::EdgelessHTTPChunk,
+        responder: Option<Responder>,
+    ) {
+        if let Some(responder) = responder {
+            self.responders.insert(key, responder);
+        }
+        for action in self.reassembler.push(key, chunk, std::time::Instant::now()) {
+            match action {
+                StreamAction::Start(req) => {
+                    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+                    let (responder_sender, responder_receiver) = tokio::sync::oneshot::channel::<Responder>();
+                    self.bodies.insert(
+                        key,
+                        StreamBody {
+                            sender,
+                            responder: responder_sender,
+                        },
+                    );
+                    let mut dataplane_handle = dataplane_handle.clone();
+                    let max_body_bytes = self.max_body_bytes;
+                    tokio::spawn(async move {
+                        let res = EgressResource::perform_streamed_request(*req, receiver, max_body_bytes).await;
+                        match &res {
+                            Ok(resp) => log::debug!("Streamed HTTP request completed with status {}", resp.status),
+                            Err(err) => log::warn!("Streamed HTTP request failed: {}", err),
+                        }
+                        if let Ok((source_id, channel_id, metadata)) = responder_receiver.await {
+                            let ret = match res {
+                                Ok(resp) => edgeless_dataplane::core::CallRet::Reply(edgeless_http::response_to_string(&resp).into_bytes()),
+                                Err(_) => edgeless_dataplane::core::CallRet::Err,
+                            };
+                            dataplane_handle.reply(source_id, channel_id, ret, &metadata).await;
+                        }
+                    });
+                }
+                StreamAction::Data(data) => {
+                    if let Some(body) = self.bodies.get(&key)
+                        && body.sender.send(Ok(data)).is_err()
+                    {
+                        self.bodies.remove(&key);
+                    }
+                }
+                StreamAction::End => {
+                    if let Some(body) = self.bodies.remove(&key)
+                        && let Some(responder) = self.responders.remove(&key)
+                    {
+                        let _ = body.responder.send(responder);
+                    }
+                }
+                StreamAction::Abort => {
+                    log::warn!("Streamed HTTP request {} from {:?} aborted", key.1, key.0);
+                    self.abort(dataplane_handle, &key).await;
+                }
+            }
+        }
+
+        // The call received with the last chunk of a stream that has been
+        // discarded, or whose request has not been performed, is replied to
+        // with an error.
+        if !self.reassembler.streams.contains_key(&key)
+            && let Some((source_id, channel_id, metadata)) = self.responders.remove(&key)
+        {
+            dataplane_handle
+                .reply(source_id, channel_id, edgeless_dataplane::core::CallRet::Err, &metadata)
+                .await;
+        }
+    }
+
+    /// Abort the streams that have not received chunks for too long.
+    async fn expire(&mut self, dataplane_handle: &mut edgeless_dataplane::handle::DataplaneHandle) {
+        for key in self.reassembler.expire(std::time::Instant::now()) {
+            log::warn!("Streamed HTTP request {} from {:?} timed out", key.1, key.0);
+            self.abort(dataplane_handle, &key).await;
+        }
+    }
+
+    /// Interrupt the body of a streamed request, if still being sent, so
+    /// that the web server does not receive an incomplete body as complete,
+    /// and reply with an error to the call waiting for its response, if any.
+    async fn abort(&mut self, dataplane_handle: &mut edgeless_dataplane::handle::DataplaneHandle, key: &StreamKey) {
+        if let Some(body) = self.bodies.remove(key) {
+            let _ = body.sender.send(Err(std::io::Error::other("stream aborted")));
+        }
+        if let Some((source_id, channel_id, metadata)) = self.responders.remove(key) {
+            dataplane_handle
+                .reply(source_id, channel_id, edgeless_dataplane::core::CallRet::Err, &metadata)
+                .await;
+        }
+    }
+}
+
 pub struct EgressResource {
     join_handle: tokio::task::JoinHandle<()>,
 }
@@ -54,28 +346,66 @@ impl EgressResource {
     async fn new(
         dataplane_handle: edgeless_dataplane::handle::DataplaneHandle,
         telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        max_body_bytes: usize,
     ) -> Self {
         let mut dataplane_handle = dataplane_handle;
         let mut telemetry_handle = telemetry_handle;
 
         let handle = tokio::spawn(async move {
+            let mut streams = StreamedRequests::new(max_body_bytes);
+            let mut expiry = tokio::time::interval(EXPIRY_INTERVAL);
+            expiry.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
             loop {
+                let event = tokio::select! {
+                    event = dataplane_handle.receive_next() => event,
+                    _ = expiry.tick() => {
+                        streams.expire(&mut dataplane_handle).await;
+                        continue;
+                    }
+                };
                 let edgeless_dataplane::core::DataplaneEvent {
                     source_id,
                     channel_id,
                     message,
                     created,
                     metadata,
-                } = dataplane_handle.receive_next().await;
+                } = event;
                 let started = crate::resources::observe_transfer(created, &mut telemetry_handle);
-                let message_data = match message {
-                    Message::Call(data) => data,
+                let (message_data, need_reply) = match message {
+                    Message::Call(data) => (data, true),
+                    Message::Cast(data) => (data, false),
                     _ => {
                         continue;
                     }
                 };
 
-                let req = match edgeless_http::request_from_string(&message_data) {
+                // The chunks of a streamed request are received via casts,
+                // except possibly the last one, which is replied to when the
+                // request is completed.
+                if edgeless_http::EdgelessHTTPChunk::is_chunk(&message_data) {
+                    match edgeless_http::EdgelessHTTPChunk::decode(&message_data) {
+                        Ok(chunk) => {
+                            let responder = need_reply.then(|| (source_id, channel_id, metadata));
+                            streams.push(&mut dataplane_handle, (source_id, chunk.stream_id), chunk, responder).await;
+                        }
+                        Err(_) if need_reply => {
+                            dataplane_handle
+                                .reply(source_id, channel_id, edgeless_dataplane::core::CallRet::Err, &metadata)
+                                .await;
+                        }
+                        Err(_) => {}
+                    }
+                    crate::resources::observe_execution(started, &mut telemetry_handle, need_reply);
+                    continue;
+                }
+                if !need_reply {
+                    continue;
+                }
+
+                let req = match std::str::from_utf8(&message_data)
+                    .map_err(anyhow::Error::from)
+                    .and_then(edgeless_http::request_from_string)
+                {
                     Ok(val) => val,
                     Err(_) => {
                         dataplane_handle
@@ -86,14 +416,14 @@ impl EgressResource {
                 };
                 let mut cloned_dataplane = dataplane_handle.clone();
                 tokio::spawn(async move {
-                    match Self::perform_request(req).await {
+                    match Self::perform_request(req, max_body_bytes).await {
                         Ok(resp) => {
                             let serialized_resp = edgeless_http::response_to_string(&resp);
                             cloned_dataplane
                                 .reply(
                                     source_id,
                                     channel_id,
-                                    edgeless_dataplane::core::CallRet::Reply(serialized_resp),
+                                    edgeless_dataplane::core::CallRet::Reply(serialized_resp.into_bytes()),
                                     &metadata,
                                 )
                                 .await;
@@ -112,8 +442,8 @@ impl EgressResource {
         Self { join_handle: handle }
     }
 
-    async fn perform_request(req: edgeless_http::EdgelessHTTPRequest) -> anyhow::Result<edgeless_http::EdgelessHTTPResponse> {
-        let method = reqwest::Method::from_bytes(edgeless_http::edgeless_method_to_string(req.method).as_bytes())?;
+    fn build_request(req: &edgeless_http::EdgelessHTTPRequest) -> anyhow::Result<reqwest::RequestBuilder> {
+        let method = reqwest::Method::from_bytes(edgeless_http::edgeless_method_to_string(req.method.clone()).as_bytes())?;
 
         let protocol_string = match req.protocol {
             edgeless_http::EdgelessHTTPProtocol::HTTPS => "HTTPS",
@@ -126,16 +456,26 @@ impl EgressResource {
 
         let mut client_r = client.request(method, url);
 
-        if let Some(b) = req.body {
-            client_r = client_r.body(b);
+        for (header_key, header_val) in &req.headers {
+            client_r = client_r.header(header_key, header_val);
         }
 
-        for (header_key, header_val) in req.headers {
-            client_r = client_r.header(header_key, header_val);
+        Ok(client_r)
+    }
+
+    async fn perform_request(req: edgeless_http::EdgelessHTTPRequest, max_body_bytes: usize) -> anyhow::Result<edgeless_http::EdgelessHTTPResponse> {
+        let mut client_r = Self::build_request(&req)?;
+
+        if let Some(b) = req.body {
+            client_r = client_r.body(b);
         }
 
-        let ret = client_r.send().await?;
+        Self::read_response(client_r.send().await?, max_body_bytes).await
+    }
 
+    /// Return the response to an HTTP request, whose body is dropped if it
+    /// exceeds the maximum size.
+    async fn read_response(mut ret: reqwest::Response, max_body_bytes: usize) -> anyhow::Result<edgeless_http::EdgelessHTTPResponse> {
         let headers = ret
             .headers()
             .iter()
@@ -147,15 +487,39 @@ impl EgressResource {
                 }
             })
             .collect();
+        let status = ret.status().as_u16();
 
-        Ok(edgeless_http::EdgelessHTTPResponse {
-            status: ret.status().as_u16(),
-            headers,
-            body: match ret.bytes().await {
-                Ok(btes) => Some(btes.to_vec()),
-                _ => None,
-            },
-        })
+        // Receive the body chunk by chunk, so that a response exceeding the
+        // maximum size is dropped without being buffered entirely.
+        let mut body = Some(vec![]);
+        while let Some(buffer) = body.as_mut() {
+            match ret.chunk().await {
+                Ok(Some(chunk)) => {
+                    if edgeless_http::exceeds(buffer.len() + chunk.len(), max_body_bytes) {
+                        anyhow::bail!("The body of the HTTP response exceeds {} bytes", max_body_bytes);
+                    }
+                    buffer.extend_from_slice(&chunk);
+                }
+                Ok(None) => break,
+                Err(_) => body = None,
+            }
+        }
+
+        Ok(edgeless_http::EdgelessHTTPResponse { status, headers, body })
+    }
+
+    /// Perform an HTTP request whose body is streamed from a channel and
+    /// return the response.
+    async fn perform_streamed_request(
+        req: edgeless_http::EdgelessHTTPRequest,
+        body: tokio::sync::mpsc::UnboundedReceiver<Result<Vec<u8>, std::io::Error>>,
+        max_body_bytes: usize,
+    ) -> anyhow::Result<edgeless_http::EdgelessHTTPResponse> {
+        let ret = Self::build_request(&req)?
+            .body(reqwest::Body::wrap_stream(tokio_stream::wrappers::UnboundedReceiverStream::new(body)))
+            .send()
+            .await?;
+        Self::read_response(ret, max_body_bytes).await
     }
 }
 
@@ -180,7 +544,7 @@ impl EgressResourceProvider {
 impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId> for EgressResourceProvider {
     async fn start(
         &mut self,
-        _instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
+        instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
     ) -> anyhow::Result<edgeless_api::common::StartComponentResponse<edgeless_api::function_instance::InstanceId>> {
         let mut lck = self.inner.lock().await;
 
@@ -191,8 +555,9 @@ impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api
             "FUNCTION_ID".to_string(),
             new_id.function_id.to_string(),
         )]));
+        let max_body_bytes = edgeless_http::max_body_bytes(&instance_specification.configuration);
         lck.egress_instances
-            .insert(new_id, EgressResource::new(dataplane_handle, telemetry_handle).await);
+            .insert(new_id, EgressResource::new(dataplane_handle, telemetry_handle, max_body_bytes).await);
 
         Ok(edgeless_api::common::StartComponentResponse::InstanceId(new_id))
     }
@@ -207,3 +572,143 @@ impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chunk(seq: u64, last: bool, data: &[u8]) -> edgeless_http::EdgelessHTTPChunk {
+        edgeless_http::EdgelessHTTPChunk {
+            stream_id: 1,
+            seq,
+            last,
+            aborted: false,
+            data: data.to_vec(),
+        }
+    }
+
+    fn head() -> (edgeless_http::EdgelessHTTPRequest, edgeless_http::EdgelessHTTPChunk) {
+        let req = edgeless_http::EdgelessHTTPRequest {
+            method: edgeless_http::EdgelessHTTPMethod::Post,
+            protocol: edgeless_http::EdgelessHTTPProtocol::HTTP,
+            host: "localhost:8080".to_string(),
+            path: "/upload".to_string(),
+            body: None,
+            headers: std::collections::HashMap::new(),
+        };
+        let head = chunk(0, false, edgeless_http::request_to_string(&req).as_bytes());
+        (req, head)
+    }
+
+    #[test]
+    fn test_http_egress_reassembler_in_order() {
+        let key = (edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4()), 1);
+        let now = std::time::Instant::now();
+        let mut reassembler = ChunkReassembler::new(0);
+        let (req, head) = head();
+
+        assert_eq!(vec![StreamAction::Start(Box::new(req))], reassembler.push(key, head, now));
+        assert_eq!(
+            vec![StreamAction::Data(b"abc".to_vec())],
+            reassembler.push(key, chunk(1, false, b"abc"), now)
+        );
+        assert_eq!(
+            vec![StreamAction::Data(b"def".to_vec()), StreamAction::End],
+            reassembler.push(key, chunk(2, true, b"def"), now)
+        );
+        assert!(reassembler.streams.is_empty());
+    }
+
+    #[test]
+    fn test_http_egress_reassembler_out_of_order() {
+        let key = (edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4()), 1);
+        let now = std::time::Instant::now();
+        let mut reassembler = ChunkReassembler::new(0);
+        let (req, head) = head();
+
+        assert!(reassembler.push(key, chunk(3, true, b""), now).is_empty());
+        assert!(reassembler.push(key, chunk(2, false, b"def"), now).is_empty());
+        assert_eq!(vec![StreamAction::Start(Box::new(req))], reassembler.push(key, head, now));
+        assert_eq!(
+            vec![
+                StreamAction::Data(b"abc".to_vec()),
+                StreamAction::Data(b"def".to_vec()),
+                StreamAction::End
+            ],
+            reassembler.push(key, chunk(1, false, b"abc"), now)
+        );
+        assert!(reassembler.streams.is_empty());
+    }
+
+    #[test]
+    fn test_http_egress_reassembler_duplicates() {
+        let key = (edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4()), 1);
+        let now = std::time::Instant::now();
+        let mut reassembler = ChunkReassembler::new(6);
+        let (req, head) = head();
+
+        // The duplicates of a chunk received out of order do not count
+        // towards the maximum body size.
+        assert!(reassembler.push(key, chunk(2, false, b"def"), now).is_empty());
+        assert!(reassembler.push(key, chunk(2, false, b"def"), now).is_empty());
+        assert_eq!(vec![StreamAction::Start(Box::new(req))], reassembler.push(key, head.clone(), now));
+        assert!(reassembler.push(key, head, now).is_empty());
+        assert_eq!(
+            vec![StreamAction::Data(b"abc".to_vec()), StreamAction::Data(b"def".to_vec())],
+            reassembler.push(key, chunk(1, false, b"abc"), now)
+        );
+        assert!(reassembler.push(key, chunk(1, false, b"abc"), now).is_empty());
+        assert_eq!(vec![StreamAction::End], reassembler.push(key, chunk(3, true, b""), now));
+        assert!(reassembler.streams.is_empty());
+    }
+
+    #[test]
+    fn test_http_egress_reassembler_abort() {
+        let key = (edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4()), 1);
+        let now = std::time::Instant::now();
+
+        // Body too large: the following chunks are ignored.
+        let mut reassembler = ChunkReassembler::new(5);
+        let (_req, head) = head();
+        assert_eq!(1, reassembler.push(key, head.clone(), now).len());
+        assert_eq!(
+            vec![StreamAction::Data(b"abc".to_vec())],
+            reassembler.push(key, chunk(1, false, b"abc"), now)
+        );
+        assert_eq!(vec![StreamAction::Abort], reassembler.push(key, chunk(2, false, b"def"), now));
+        assert!(reassembler.push(key, chunk(3, false, b"ghi"), now).is_empty());
+        assert!(reassembler.push(key, chunk(4, true, b""), now).is_empty());
+        assert!(reassembler.streams.is_empty());
+
+        // Stream aborted by the sender.
+        let mut reassembler = ChunkReassembler::new(0);
+        assert_eq!(1, reassembler.push(key, head.clone(), now).len());
+        let mut aborted = chunk(1, false, b"");
+        aborted.aborted = true;
+        assert_eq!(vec![StreamAction::Abort], reassembler.push(key, aborted, now));
+        assert!(reassembler.streams.is_empty());
+
+        // Invalid head.
+        assert!(reassembler.push(key, chunk(0, false, b"not a request"), now).is_empty());
+        assert!(reassembler.push(key, chunk(1, true, b"abc"), now).is_empty());
+        assert!(reassembler.streams.is_empty());
+
+        // Stream timed out.
+        assert_eq!(1, reassembler.push(key, head, now).len());
+        assert!(reassembler.expire(now + STREAM_TIMEOUT).is_empty());
+        assert_eq!(vec![key], reassembler.expire(now + STREAM_TIMEOUT * 2));
+        assert!(reassembler.streams.is_empty());
+
+        // Too many chunks received out of order: the following chunks are
+        // ignored.
+        let (_req, head) = head();
+        let max_seq = MAX_PENDING_CHUNKS as u64 + 1;
+        for seq in 2..=max_seq {
+            assert!(reassembler.push(key, chunk(seq, false, b"x"), now).is_empty());
+        }
+        assert_eq!(vec![StreamAction::Abort], reassembler.push(key, chunk(max_seq + 1, false, b"x"), now));
+        assert!(reassembler.push(key, head, now).is_empty());
+        assert!(reassembler.push(key, chunk(max_seq + 2, true, b""), now).is_empty());
+        assert!(reassembler.streams.is_empty());
+    }
+}
@@ -6,8 +6,9 @@
 use base64::Engine;
 use edgeless_api::function_instance::ComponentId;
 use http_body_util::BodyExt;
-use rand::{SeedableRng, seq::SliceRandom};
+use rand::{RngCore, SeedableRng};
 use std::str::FromStr;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 pub struct HttpIngressResourceSpec {}
 
@@ -36,6 +37,12 @@ impl super::resource_provider_specs::ResourceProviderSpecs for HttpIngressResour
                 String::from("method"),
                 String::from("Comma-separated list of HTTP methods allowed. Default: accept any method"),
             ),
+            (
+                String::from("path_prefix"),
+                String::from(
+                    "If not empty, requires the path in the URL to begin with the given prefix, e.g., /api/v1. If multiple resources match, the one with the longest prefix is selected. Default: any path accepted",
+                ),
+            ),
             (
                 String::from("wf_id"),
                 String::from(
@@ -50,25 +57,149 @@ impl super::resource_provider_specs::ResourceProviderSpecs for HttpIngressResour
             ),
             (
                 String::from("encode_base64"),
-                String::from("Encode the input with base64 (only with async=true)."),
+                String::from(
+                    "Encode the input with base64 (only with async=true). Not needed for binary bodies, which are forwarded as they are. Default: false.",
+                ),
+            ),
+            (
+                String::from("max_body_bytes"),
+                String::from(
+                    "Maximum size of the body of the HTTP commands, in bytes, above which the external client receives 413 Content Too Large. 0 means no limit. Default: 16 MiB.",
+                ),
+            ),
+            (
+                String::from("chunk_size"),
+                String::from(
+                    "If greater than 0, the body is streamed to the target on the output channel via asynchronous casts of chunks of at most the given size, in bytes, except the last chunk, which is sent via a call whose return value is the HTTP response. The chunks can be reassembled by an http-egress resource. Default: 0, i.e., the body is not split.",
+                ),
             ),
         ])
     }
 
     fn version(&self) -> String {
-        String::from("2.1")
+        String::from("2.4")
     }
 }
 
+#[derive(Clone)]
 struct ResourceDesc {
     host: Option<String>,
+    path_prefix: String,
     allow: std::collections::HashSet<edgeless_http::EdgelessHTTPMethod>,
     wf_id: Option<String>,
     async_out: bool,
     encode_base64: bool,
+    max_body_bytes: usize,
+    chunk_size: usize,
     target: Option<edgeless_api::function_instance::InstanceId>,
 }
 
+/// HTTP methods supported.
+const ALL_METHODS: [edgeless_http::EdgelessHTTPMethod; 6] = [
+    edgeless_http::EdgelessHTTPMethod::Get,
+    edgeless_http::EdgelessHTTPMethod::Head,
+    edgeless_http::EdgelessHTTPMethod::Post,
+    edgeless_http::EdgelessHTTPMethod::Put,
+    edgeless_http::EdgelessHTTPMethod::Delete,
+    edgeless_http::EdgelessHTTPMethod::Patch,
+];
+
+/// Outcome of the matching of an HTTP request against the active resources.
+#[derive(Debug, PartialEq)]
+enum Route {
+    /// Resource that serves the request: the one with the longest prefix,
+    /// then with a host and a workflow identifier specified, then with the
+    /// smallest identifier.
+    Found(ComponentId),
+    /// Some resources match the request, but none allows its method: the
+    /// methods allowed are returned, sorted.
+    MethodNotAllowed(Vec<String>),
+    /// No resource matches the request.
+    NotFound,
+}
+
+/// Return the normalized path prefix, i.e., beginning with `/` and without
+/// trailing `/`, which means that the empty string matches any path.
+fn normalize_path_prefix(path_prefix: &str) -> String {
+    let path_prefix = path_prefix.trim().trim_end_matches('/');
+    if path_prefix.is_empty() || path_prefix.starts_with('/') {
+        path_prefix.to_string()
+    } else {
+        format!("/{}", path_prefix)
+    }
+}
+
+/// Return true if the path begins with the given normalized prefix, which
+/// must be followed by a path separator unless the two are identical.
+fn path_matches(path: &str, path_prefix: &str) -> bool {
+    match path.strip_prefix(path_prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Find the resources that can serve an HTTP request.
+///
+/// A resource matches the request if the host, path prefix, and workflow
+/// identifier match, as configured, and the resource has been patched.
+/// Among the matching resources, only those allowing the HTTP method of
+/// the request are considered and those with the longest path prefix
+/// are returned.
+///
+/// * `resources` - The active resources.
+/// * `host` - The host in the request.
+/// * `path` - The path in the request.
+/// * `method` - The method in the request, None if not supported, in which
+///   case no resource can serve the request.
+/// * `wf_id` - The workflow identifier in the request query, if any.
+fn route(
+    resources: &std::collections::HashMap<ComponentId, ResourceDesc>,
+    host: &str,
+    path: &str,
+    method: Option<&edgeless_http::EdgelessHTTPMethod>,
+    wf_id: &Option<String>,
+) -> Route {
+    let matching = resources
+        .iter()
+        .filter(|(_id, desc)| {
+            (desc.host.is_none() || desc.host.as_deref() == Some(host))
+                && (desc.wf_id.is_none() || desc.wf_id == *wf_id)
+                && desc.target.is_some()
+                && path_matches(path, &desc.path_prefix)
+        })
+        .collect::<Vec<_>>();
+    if matching.is_empty() {
+        return Route::NotFound;
+    }
+
+    let allowed = matching
+        .iter()
+        .filter(|(_id, desc)| method.is_some_and(|method| desc.allow.is_empty() || desc.allow.contains(method)))
+        .collect::<Vec<_>>();
+    match allowed
+        .iter()
+        .max_by_key(|(id, desc)| (desc.path_prefix.len(), desc.host.is_some(), desc.wf_id.is_some(), std::cmp::Reverse(**id)))
+    {
+        Some((id, _desc)) => Route::Found(**id),
+        None => {
+            let mut methods = matching
+                .iter()
+                .flat_map(|(_id, desc)| {
+                    if desc.allow.is_empty() {
+                        ALL_METHODS.to_vec()
+                    } else {
+                        desc.allow.iter().cloned().collect()
+                    }
+                })
+                .map(edgeless_http::edgeless_method_to_string)
+                .collect::<Vec<_>>();
+            methods.sort();
+            methods.dedup();
+            Route::MethodNotAllowed(methods)
+        }
+    }
+}
+
 struct IngressState {
     active_resources: std::collections::HashMap<ComponentId, ResourceDesc>,
     dataplane: edgeless_dataplane::handle::DataplaneHandle,
@@ -92,7 +223,7 @@ impl hyper::service::Service<hyper::Request<hyper::body::Incoming>> for IngressS
         let cloned_addr = self.listen_addr.clone();
         let mut rng = rand::rngs::StdRng::from_entropy();
         Box::pin(async move {
-            let mut lck = cloned.lock().await;
+            let lck = cloned.lock().await;
 
             let query = req.uri().query().unwrap_or_default().to_string();
             let (parts, body) = req.into_parts();
@@ -101,7 +232,7 @@ impl hyper::service::Service<hyper::Request<hyper::body::Incoming>> for IngressS
                 Some(val) => val.to_str()?,
                 None => &cloned_addr,
             };
-            let method = edgeless_http::hyper_method_to_edgeless(&parts.method)?;
+            let method = edgeless_http::hyper_method_to_edgeless(&parts.method).ok();
 
             let mut wf_id = None;
             for param in query.split("&") {
@@ -113,99 +244,308 @@ impl hyper::service::Service<hyper::Request<hyper::body::Incoming>> for IngressS
                 }
             }
 
-            let data = body.collect().await?.to_bytes();
-
-            // Find the set of matching resources for this HTTP request.
-            let mut matching = vec![];
-            for desc in lck.active_resources.values() {
-                if (desc.host.is_none() || desc.host == Some(host.to_string()))
-                    && (desc.allow.is_empty() || desc.allow.contains(&method))
-                    && (desc.wf_id.is_none() || desc.wf_id == wf_id)
-                    && desc.target.is_some()
-                {
-                    matching.push(desc);
+            // Find the resource matching this HTTP request.
+            let id = match route(&lck.active_resources, host, parts.uri.path(), method.as_ref(), &wf_id) {
+                Route::Found(id) => id,
+                Route::MethodNotAllowed(methods) => {
+                    let mut not_allowed = plain_response(hyper::StatusCode::METHOD_NOT_ALLOWED, "Method Not Allowed");
+                    not_allowed
+                        .headers_mut()
+                        .insert(hyper::header::ALLOW, hyper::header::HeaderValue::from_str(&methods.join(", "))?);
+                    return Ok(not_allowed);
                 }
+                Route::NotFound => return Ok(plain_response(hyper::StatusCode::NOT_FOUND, "Not Found")),
+            };
+
+            // Release the lock so that the body of the request does not
+            // block other requests while being received.
+            let Some(desc) = lck.active_resources.get(&id).cloned() else {
+                return Ok(plain_response(hyper::StatusCode::NOT_FOUND, "Not Found"));
+            };
+            let Some(target) = desc.target else {
+                return Ok(plain_response(hyper::StatusCode::NOT_FOUND, "Not Found"));
+            };
+            let mut dataplane = lck.dataplane.clone();
+            drop(lck);
+
+            if let Some(content_length) = parts
+                .headers
+                .get(hyper::header::CONTENT_LENGTH)
+                .and_then(|val| val.to_str().ok())
+                .and_then(|val| val.parse::<usize>().ok())
+                && edgeless_http::exceeds(content_length, desc.max_body_bytes)
+            {
+                return Ok(plain_response(hyper::StatusCode::PAYLOAD_TOO_LARGE, "Content Too Large"));
             }
 
-            // Choone one resource at random, if any.
-            if let Some(desc) = matching.choose(&mut rng) {
-                let target = desc.target.unwrap();
+            let request_head = edgeless_http::EdgelessHTTPRequest {
+                host: host.to_string(),
+                protocol: edgeless_http::EdgelessHTTPProtocol::Unknown,
+                method: method.clone().unwrap(),
+                path: parts.uri.to_string(),
+                body: None,
+                headers: parts
+                    .headers
+                    .iter()
+                    .filter_map(|(k, v)| match v.to_str() {
+                        Ok(header_value) => Some((k.to_string(), header_value.to_string())),
+                        Err(_) => {
+                            log::warn!("Bad Header Value.");
+                            None
+                        }
+                    })
+                    .collect(),
+            };
 
-                if desc.async_out {
-                    // Invoke the next component via cast().
-                    let msg = if desc.encode_base64 {
-                        base64::engine::general_purpose::STANDARD.encode(&data)
-                    } else {
-                        String::from_utf8(data.to_vec())?
-                    };
-                    lck.dataplane
-                        .send(target, msg, &edgeless_api::function_instance::EventMetadata::empty_new_root())
-                        .await;
-                    let mut ok_res = hyper::Response::new(http_body_util::Full::new(hyper::body::Bytes::from("OK")));
-                    *ok_res.status_mut() = hyper::StatusCode::OK;
-                    return Ok(ok_res);
-                } else {
-                    // Invoke the next component via call().
-                    let msg = edgeless_http::EdgelessHTTPRequest {
-                        host: host.to_string(),
-                        protocol: edgeless_http::EdgelessHTTPProtocol::Unknown,
-                        method: method.clone(),
-                        path: parts.uri.to_string(),
-                        body: Some(Vec::from(data)),
-                        headers: parts
-                            .headers
-                            .iter()
-                            .filter_map(|(k, v)| match v.to_str() {
-                                Ok(header_value) => Some((k.to_string(), header_value.to_string())),
-                                Err(_) => {
-                                    log::warn!("Bad Header Value.");
-                                    None
-                                }
-                            })
-                            .collect(),
-                    };
-                    let serialized_msg = serde_json::to_string(&msg)?;
-                    let res = lck
-                        .dataplane
-                        .call(target, serialized_msg, &edgeless_api::function_instance::EventMetadata::empty_new_root())
-                        .await;
-                    if let edgeless_dataplane::core::CallRet::Reply(data) = res {
-                        let processor_response: edgeless_http::EdgelessHTTPResponse = serde_json::from_str(&data)?;
-                        let mut response_builder = hyper::Response::new(http_body_util::Full::new(hyper::body::Bytes::from(
-                            processor_response.body.unwrap_or_default(),
-                        )));
-                        *response_builder.status_mut() = hyper::StatusCode::from_u16(processor_response.status)?;
-                        {
-                            let headers = response_builder.headers_mut();
-                            for (header_key, header_val) in processor_response.headers {
-                                if let (Ok(key), Ok(value)) = (
-                                    hyper::header::HeaderName::from_bytes(header_key.as_bytes()),
-                                    hyper::header::HeaderValue::from_str(&header_val),
-                                ) {
-                                    headers.append(key, value);
-                                }
-                            }
+            if desc.chunk_size > 0 {
+                // Stream the request to the next component via cast(), as
+                // a sequence of chunks, all to the same instance.
+                let target = dataplane.select_target("new_request", target).await;
+                let mut stream = ChunkSender {
+                    dataplane: &mut dataplane,
+                    target,
+                    stream_id: rng.next_u64(),
+                    seq: 0,
+                };
+                stream.send(edgeless_http::request_to_string(&request_head).into_bytes()).await;
+
+                let mut body = body;
+                let mut buffer = Vec::with_capacity(desc.chunk_size);
+                let mut received = 0;
+                while let Some(frame) = body.frame().await {
+                    let data = match frame {
+                        Ok(frame) => match frame.into_data() {
+                            Ok(data) => data,
+                            Err(_) => continue,
+                        },
+                        Err(err) => {
+                            stream.abort().await;
+                            return Err(err.into());
                         }
-                        return Ok(response_builder);
+                    };
+                    received += data.len();
+                    if edgeless_http::exceeds(received, desc.max_body_bytes) {
+                        stream.abort().await;
+                        return Ok(plain_response(hyper::StatusCode::PAYLOAD_TOO_LARGE, "Content Too Large"));
+                    }
+                    buffer.extend_from_slice(&data);
+                    while buffer.len() >= desc.chunk_size {
+                        let rest = buffer.split_off(desc.chunk_size);
+                        stream.send(std::mem::replace(&mut buffer, rest)).await;
                     }
                 }
+                // The response is returned by the next component when
+                // the last chunk has been processed.
+                let res = stream.finish(buffer).await;
+                return call_response(res);
             }
 
-            let mut not_found = hyper::Response::new(http_body_util::Full::new(hyper::body::Bytes::from("Not Found")));
-            *not_found.status_mut() = hyper::StatusCode::NOT_FOUND;
-            Ok(not_found)
+            let data = match http_body_util::Limited::new(body, edgeless_http::max_body_limit(desc.max_body_bytes))
+                .collect()
+                .await
+            {
+                Ok(collected) => collected.to_bytes(),
+                Err(err) => {
+                    if err.is::<http_body_util::LengthLimitError>() {
+                        return Ok(plain_response(hyper::StatusCode::PAYLOAD_TOO_LARGE, "Content Too Large"));
+                    }
+                    return Err(anyhow::anyhow!(err));
+                }
+            };
+
+            if desc.async_out {
+                // Invoke the next component via cast().
+                let msg = if desc.encode_base64 {
+                    base64::engine::general_purpose::STANDARD.encode(&data).into_bytes()
+                } else {
+                    data.to_vec()
+                };
+                dataplane
+                    .send_output(
+                        "new_request",
+                        target,
+                        msg,
+                        &edgeless_api::function_instance::EventMetadata::empty_new_root(),
+                    )
+                    .await;
+                return Ok(plain_response(hyper::StatusCode::OK, "OK"));
+            }
+
+            // Invoke the next component via call().
+            let msg = edgeless_http::EdgelessHTTPRequest {
+                body: Some(Vec::from(data)),
+                ..request_head
+            };
+            let serialized_msg = serde_json::to_string(&msg)?;
+            let res = dataplane
+                .call_output(
+                    "new_request",
+                    target,
+                    serialized_msg,
+                    &edgeless_api::function_instance::EventMetadata::empty_new_root(),
+                )
+                .await;
+            call_response(res)
         })
     }
 }
 
+/// Return the HTTP response contained in the return value of a call.
+fn call_response(res: edgeless_dataplane::core::CallRet) -> anyhow::Result<hyper::Response<http_body_util::Full<hyper::body::Bytes>>> {
+    if let edgeless_dataplane::core::CallRet::Reply(data) = res {
+        let processor_response: edgeless_http::EdgelessHTTPResponse = serde_json::from_slice(&data)?;
+        let mut response_builder = hyper::Response::new(http_body_util::Full::new(hyper::body::Bytes::from(
+            processor_response.body.unwrap_or_default(),
+        )));
+        *response_builder.status_mut() = hyper::StatusCode::from_u16(processor_response.status)?;
+        {
+            let headers = response_builder.headers_mut();
+            for (header_key, header_val) in processor_response.headers {
+                if let (Ok(key), Ok(value)) = (
+                    hyper::header::HeaderName::from_bytes(header_key.as_bytes()),
+                    hyper::header::HeaderValue::from_str(&header_val),
+                ) {
+                    headers.append(key, value);
+                }
+            }
+        }
+        return Ok(response_builder);
+    }
+
+    match res {
+        edgeless_dataplane::core::CallRet::Err => Ok(plain_response(hyper::StatusCode::BAD_GATEWAY, "Bad Gateway")),
+        edgeless_dataplane::core::CallRet::Timeout => Ok(plain_response(hyper::StatusCode::GATEWAY_TIMEOUT, "Gateway Timeout")),
+        _ => Ok(plain_response(hyper::StatusCode::NOT_FOUND, "Not Found")),
+    }
+}
+
+/// Sender of the chunks of an HTTP request streamed to a component.
+///
+/// All the chunks are sent via casts, except the last one, which is sent
+/// via a call whose return value is the HTTP response.
+struct ChunkSender<'a> {
+    dataplane: &'a mut edgeless_dataplane::handle::DataplaneHandle,
+    target: edgeless_api::function_instance::InstanceId,
+    stream_id: u64,
+    seq: u64,
+}
+
+impl ChunkSender<'_> {
+    async fn send(&mut self, data: Vec<u8>) {
+        let chunk = self.next_chunk(data, false, false);
+        self.dataplane
+            .send(
+                self.target,
+                chunk.encode(),
+                &edgeless_api::function_instance::EventMetadata::empty_new_root(),
+            )
+            .await;
+    }
+
+    async fn abort(&mut self) {
+        let chunk = self.next_chunk(vec![], false, true);
+        self.dataplane
+            .send(
+                self.target,
+                chunk.encode(),
+                &edgeless_api::function_instance::EventMetadata::empty_new_root(),
+            )
+            .await;
+    }
+
+    /// Send the last chunk and wait for the response.
+    async fn finish(&mut self, data: Vec<u8>) -> edgeless_dataplane::core::CallRet {
+        let chunk = self.next_chunk(data, true, false);
+        self.dataplane
+            .call(
+                self.target,
+                chunk.encode(),
+                &edgeless_api::function_instance::EventMetadata::empty_new_root(),
+            )
+            .await
+    }
+
+    fn next_chunk(&mut self, data: Vec<u8>, last: bool, aborted: bool) -> edgeless_http::EdgelessHTTPChunk {
+        let chunk = edgeless_http::EdgelessHTTPChunk {
+            stream_id: self.stream_id,
+            seq: self.seq,
+            last,
+            aborted,
+            data,
+        };
+        self.seq += 1;
+        chunk
+    }
+}
+
+/// Return a response with the given status and plain text body.
+fn plain_response(status: hyper::StatusCode, body: &'static str) -> hyper::Response<http_body_util::Full<hyper::body::Bytes>> {
+    let mut response = hyper::Response::new(http_body_util::Full::new(hyper::body::Bytes::from(body)));
+    *response.status_mut() = status;
+    response
+}
+
+/// Create the TLS acceptor from the PEM files of the certificate chain and
+/// private key.
+fn tls_acceptor(tls_cert: &str, tls_key: &str) -> anyhow::Result<openssl::ssl::SslAcceptor> {
+    let mut builder = openssl::ssl::SslAcceptor::mozilla_intermediate_v5(openssl::ssl::SslMethod::tls_server())?;
+    builder.set_certificate_chain_file(tls_cert)?;
+    builder.set_private_key_file(tls_key, openssl::ssl::SslFiletype::PEM)?;
+    builder.check_private_key()?;
+    Ok(builder.build())
+}
+
+/// Serve the HTTP requests received on a connection, either plain or TLS.
+async fn serve_connection<S>(stream: S, service: IngressService)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    if let Err(err) = hyper::server::conn::http1::Builder::new()
+        .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+        .await
+    {
+        log::warn!("Error serving connection: {:?}", err);
+    }
+}
+
+/// Start the web server of the http-ingress resource provider.
+///
+/// * `dataplane_provider` - The data plane provider.
+/// * `ingress_id` - The identifier of the resource provider.
+/// * `ingress_url` - The URL of the web server. If the protocol is https,
+///   then the TLS certificate chain and private key must be provided.
+/// * `tls_cert` - The path of the PEM file with the TLS certificate chain.
+/// * `tls_key` - The path of the PEM file with the TLS private key.
+///
+/// Return an error if the URL is invalid or TLS cannot be enabled.
 pub async fn ingress_task(
     dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
     ingress_id: edgeless_api::function_instance::InstanceId,
     ingress_url: String,
-) -> Box<dyn edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId>> {
+    tls_cert: Option<String>,
+    tls_key: Option<String>,
+) -> anyhow::Result<Box<dyn edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId>>> {
     let mut provider = dataplane_provider;
-    let (_, host, port) = edgeless_api::util::parse_http_host(&ingress_url).unwrap();
-    let addr = std::net::SocketAddr::from((std::net::IpAddr::from_str(&host).unwrap(), port));
+    let (proto, host, port) = edgeless_api::util::parse_http_host(&ingress_url)?;
+    let addr = std::net::SocketAddr::from((std::net::IpAddr::from_str(&host)?, port));
+
+    let acceptor = match proto {
+        edgeless_api::util::Proto::HTTPS => {
+            let (Some(tls_cert), Some(tls_key)) = (tls_cert.filter(|x| !x.is_empty()), tls_key.filter(|x| !x.is_empty())) else {
+                anyhow::bail!("missing TLS certificate or private key for the http-ingress at {}", ingress_url);
+            };
+            match tls_acceptor(&tls_cert, &tls_key) {
+                Ok(acceptor) => Some(std::sync::Arc::new(acceptor)),
+                Err(err) => anyhow::bail!("could not enable TLS for the http-ingress at {}: {}", ingress_url, err),
+            }
+        }
+        _ => {
+            if tls_cert.is_some_and(|x| !x.is_empty()) || tls_key.is_some_and(|x| !x.is_empty()) {
+                log::warn!("TLS certificate and private key ignored for the http-ingress at {}", ingress_url);
+            }
+            None
+        }
+    };
 
     let dataplane = provider.get_handle_for(ingress_id).await;
 
@@ -226,31 +566,37 @@ pub async fn ingress_task(
                     continue;
                 }
             };
-            let io = hyper_util::rt::TokioIo::new(stream);
-            let cloned_interests = cloned_interests.clone();
-            let cloned_host = host.clone();
-            let cloned_port = port;
+            let service = IngressService {
+                interests: cloned_interests.clone(),
+                listen_addr: format!("{}:{}", host, port),
+            };
+            let acceptor = acceptor.clone();
             tokio::task::spawn(async move {
-                if let Err(err) = hyper::server::conn::http1::Builder::new()
-                    .serve_connection(
-                        io,
-                        IngressService {
-                            interests: cloned_interests,
-                            listen_addr: format!("{}:{}", cloned_host, cloned_port).to_string(),
-                        },
-                    )
-                    .await
-                {
-                    println!("Error serving connection: {:?}", err);
+                match acceptor {
+                    Some(acceptor) => {
+                        let mut stream = match openssl::ssl::Ssl::new(acceptor.context()).and_then(|ssl| tokio_openssl::SslStream::new(ssl, stream)) {
+                            Ok(stream) => stream,
+                            Err(err) => {
+                                log::warn!("Error creating TLS stream: {}", err);
+                                return;
+                            }
+                        };
+                        if let Err(err) = std::pin::Pin::new(&mut stream).accept().await {
+                            log::warn!("TLS handshake error: {}", err);
+                            return;
+                        }
+                        serve_connection(stream, service).await;
+                    }
+                    None => serve_connection(stream, service).await,
                 }
             });
         }
     });
 
-    Box::new(IngressResource {
+    Ok(Box::new(IngressResource {
         own_node_id: ingress_id.node_id,
         configuration_state: ingress_state,
-    })
+    }))
 }
 
 #[derive(Clone)]
@@ -268,6 +614,7 @@ impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api
         let mut lck = self.configuration_state.lock().await;
 
         let host = instance_specification.configuration.get("host").cloned();
+        let path_prefix = normalize_path_prefix(instance_specification.configuration.get("path_prefix").unwrap_or(&String::default()));
         let mut allow = std::collections::HashSet::new();
         for method in instance_specification
             .configuration
@@ -308,24 +655,41 @@ impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api
             .get("encode_base64")
             .unwrap_or(&String::from("false"))
             .eq_ignore_ascii_case("true");
+        let max_body_bytes = edgeless_http::max_body_bytes(&instance_specification.configuration);
+        let chunk_size = instance_specification
+            .configuration
+            .get("chunk_size")
+            .and_then(|val| val.parse::<usize>().ok())
+            .unwrap_or_default();
 
         // Assign a new component identifier to the newly-created  resource.
         log::info!(
-            "created a new http-ingress resource: host {:?}, methods allowed {:?}, wf_id {:?}, {}",
+            "created a new http-ingress resource: host {:?}, path prefix '{}', methods allowed {:?}, wf_id {:?}, {}, max body {} bytes",
             host,
+            path_prefix,
             allow,
             wf_id,
-            if async_out { "cast" } else { "call" }
+            if chunk_size > 0 {
+                format!("stream of {} bytes chunks", chunk_size)
+            } else if async_out {
+                "cast".to_string()
+            } else {
+                "call".to_string()
+            },
+            max_body_bytes
         );
         let resource_id = edgeless_api::function_instance::InstanceId::new(self.own_node_id);
         lck.active_resources.insert(
             resource_id.function_id,
             ResourceDesc {
                 host,
+                path_prefix,
                 allow,
                 wf_id,
                 async_out,
                 encode_base64,
+                max_body_bytes,
+                chunk_size,
                 target: None, // will be set by patch()
             },
         );
@@ -351,3 +715,117 @@ impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn desc(host: Option<&str>, path_prefix: &str, allow: Vec<edgeless_http::EdgelessHTTPMethod>) -> ResourceDesc {
+        ResourceDesc {
+            host: host.map(|x| x.to_string()),
+            path_prefix: normalize_path_prefix(path_prefix),
+            allow: allow.into_iter().collect(),
+            wf_id: None,
+            async_out: false,
+            encode_base64: false,
+            max_body_bytes: edgeless_http::DEFAULT_MAX_BODY_BYTES,
+            chunk_size: 0,
+            target: Some(edgeless_api::function_instance::InstanceId::new(uuid::Uuid::new_v4())),
+        }
+    }
+
+    #[test]
+    fn test_http_ingress_path_prefix() {
+        assert_eq!("", normalize_path_prefix(""));
+        assert_eq!("", normalize_path_prefix("/"));
+        assert_eq!("/api", normalize_path_prefix("api"));
+        assert_eq!("/api/v1", normalize_path_prefix("/api/v1/"));
+
+        assert!(path_matches("/", ""));
+        assert!(path_matches("/api", ""));
+        assert!(path_matches("/api", "/api"));
+        assert!(path_matches("/api/", "/api"));
+        assert!(path_matches("/api/v1/items", "/api/v1"));
+        assert!(!path_matches("/apis", "/api"));
+        assert!(!path_matches("/", "/api"));
+    }
+
+    #[test]
+    fn test_http_ingress_route() {
+        let get = edgeless_http::EdgelessHTTPMethod::Get;
+        let post = edgeless_http::EdgelessHTTPMethod::Post;
+        let [root, api, api_v1, api_v1_other, host_only, unpatched] = [0; 6].map(|_| uuid::Uuid::new_v4());
+        let mut resources = std::collections::HashMap::from([
+            (root, desc(None, "", vec![])),
+            (api, desc(None, "/api", vec![])),
+            (api_v1, desc(None, "/api/v1", vec![post.clone()])),
+            (api_v1_other, desc(None, "/api/v1/", vec![post.clone()])),
+            (host_only, desc(Some("example.com"), "/web", vec![get.clone()])),
+            (unpatched, desc(None, "/unpatched", vec![])),
+        ]);
+        resources.get_mut(&unpatched).unwrap().target = None;
+
+        // Longest prefix matching.
+        assert_eq!(Route::Found(root), route(&resources, "localhost", "/", Some(&get), &None));
+        assert_eq!(Route::Found(root), route(&resources, "localhost", "/apis", Some(&get), &None));
+        assert_eq!(Route::Found(api), route(&resources, "localhost", "/api/v2", Some(&get), &None));
+        assert_eq!(
+            Route::Found(std::cmp::min(api_v1, api_v1_other)),
+            route(&resources, "localhost", "/api/v1/x", Some(&post), &None)
+        );
+
+        // Resources not allowing the method are skipped.
+        assert_eq!(Route::Found(api), route(&resources, "localhost", "/api/v1/x", Some(&get), &None));
+
+        // Resources not patched are skipped.
+        assert_eq!(Route::Found(root), route(&resources, "localhost", "/unpatched", Some(&get), &None));
+
+        // Host matching.
+        assert_eq!(Route::Found(host_only), route(&resources, "example.com", "/web", Some(&get), &None));
+        assert_eq!(Route::Found(root), route(&resources, "localhost", "/web", Some(&get), &None));
+
+        // No catch-all resource.
+        resources.remove(&root);
+        assert_eq!(Route::NotFound, route(&resources, "localhost", "/", Some(&get), &None));
+        assert_eq!(Route::NotFound, route(&resources, "localhost", "/unpatched", Some(&get), &None));
+        assert_eq!(
+            Route::MethodNotAllowed(vec!["GET".to_string()]),
+            route(&resources, "example.com", "/web", Some(&post), &None)
+        );
+        resources.remove(&api);
+        assert_eq!(
+            Route::MethodNotAllowed(vec!["POST".to_string()]),
+            route(&resources, "localhost", "/api/v1", Some(&get), &None)
+        );
+        assert_eq!(
+            Route::MethodNotAllowed(vec!["POST".to_string()]),
+            route(&resources, "localhost", "/api/v1", None, &None)
+        );
+
+        // Ties are broken by preferring a host, then a workflow identifier.
+        let any_host = uuid::Uuid::new_v4();
+        resources.insert(any_host, desc(None, "/web", vec![get.clone()]));
+        assert_eq!(Route::Found(host_only), route(&resources, "example.com", "/web", Some(&get), &None));
+        let with_wf_id = uuid::Uuid::new_v4();
+        resources.insert(with_wf_id, desc(None, "/web", vec![get.clone()]));
+        resources.get_mut(&with_wf_id).unwrap().wf_id = Some("wf".to_string());
+        assert_eq!(
+            Route::Found(with_wf_id),
+            route(&resources, "localhost", "/web", Some(&get), &Some("wf".to_string()))
+        );
+        resources.remove(&any_host);
+        resources.remove(&with_wf_id);
+
+        // Workflow identifier matching.
+        resources.get_mut(&host_only).unwrap().wf_id = Some("wf".to_string());
+        assert_eq!(Route::NotFound, route(&resources, "example.com", "/web", Some(&get), &None));
+        assert_eq!(
+            Route::NotFound,
+            route(&resources, "example.com", "/web", Some(&get), &Some("other".to_string()))
+        );
+        assert_eq!(
+            Route::Found(host_only),
+            route(&resources, "example.com", "/web", Some(&get), &Some("wf".to_string()))
+        );
+    }
+}
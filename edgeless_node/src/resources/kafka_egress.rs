@@ -76,6 +76,7 @@ impl KafkaEgressResource {
                     channel_id,
                     message,
                     created,
+                    metadata,
                 } = dataplane_handle.receive_next().await;
                 let started = crate::resources::observe_transfer(created, &mut telemetry_handle);
 
@@ -97,11 +98,11 @@ impl KafkaEgressResource {
 
                 if need_reply {
                     dataplane_handle
-                        .reply(source_id, channel_id, edgeless_dataplane::core::CallRet::Reply("".to_string()))
+                        .reply(source_id, channel_id, edgeless_dataplane::core::CallRet::Reply(Vec::new()), &metadata)
                         .await;
                 }
 
-                crate::resources::observe_execution(started, &mut telemetry_handle);
+                crate::resources::observe_execution(started, &mut telemetry_handle, need_reply);
             }
         });
 
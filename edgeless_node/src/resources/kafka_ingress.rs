@@ -0,0 +1,646 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Default interval between two attempts to deliver a record, in ms.
+const DEFAULT_RETRY_INTERVAL_MS: u64 = 1000;
+
+/// Default maximum number of attempts to deliver a record.
+const DEFAULT_MAX_ATTEMPTS: u32 = 10;
+
+pub struct KafkaIngressResourceSpec {}
+
+impl super::resource_provider_specs::ResourceProviderSpecs for KafkaIngressResourceSpec {
+    fn class_type(&self) -> String {
+        String::from("kafka-ingress")
+    }
+
+    fn description(&self) -> String {
+        r"Receive messages from an external Apache Kafka server -- see https://kafka.apache.org/".to_string()
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("new_message")]
+    }
+
+    fn configurations(&self) -> std::collections::HashMap<String, String> {
+        std::collections::HashMap::from([
+            (
+                String::from("brokers"),
+                String::from("Comma-separated list of initial brokers to access the cluster"),
+            ),
+            (String::from("topics"), String::from("Comma-separated list of topics to subscribe to")),
+            (String::from("group_id"), String::from("Consumer group of the resource")),
+            (
+                String::from("async"),
+                String::from(
+                    "Boolean specifying if the target on the output channel should be invoked via an asynchronous cast, in which case the offset is committed as soon as the record is sent. One of: true, false. Default: use a synchronous call and commit the offset only after it succeeds.",
+                ),
+            ),
+            (
+                String::from("retry_interval_ms"),
+                String::from("Interval between two attempts to deliver a record whose call failed, in ms. Default: 1000"),
+            ),
+            (
+                String::from("max_attempts"),
+                String::from(
+                    "Maximum number of failed calls to deliver a record, after which the record is forwarded to the dead-letter function/resource of the workflow, if any, and its offset is committed. 0 means no limit. Default: 10",
+                ),
+            ),
+        ])
+    }
+
+    fn version(&self) -> String {
+        String::from("1.1")
+    }
+}
+
+/// Record received from a Kafka topic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KafkaRecord {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub payload: Vec<u8>,
+}
+
+/// Configuration of a Kafka consumer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KafkaConsumerConfig {
+    pub brokers: String,
+    pub topics: Vec<String>,
+    pub group_id: String,
+}
+
+/// Consumer of the records from Kafka topics.
+#[async_trait::async_trait]
+pub trait KafkaConsumer: Send {
+    /// Wait for the next record.
+    async fn recv(&mut self) -> anyhow::Result<KafkaRecord>;
+
+    /// Commit the offset of a record, i.e., mark it and the previous ones
+    /// in the same partition as consumed.
+    fn commit(&mut self, record: &KafkaRecord) -> anyhow::Result<()>;
+}
+
+/// Function creating the consumer of a resource instance.
+pub type KafkaConsumerFactory = Box<dyn Fn(&KafkaConsumerConfig) -> anyhow::Result<Box<dyn KafkaConsumer>> + Send + Sync>;
+
+/// Kafka consumer based on rdkafka, with automatic commits disabled.
+struct RdKafkaConsumer {
+    consumer: rdkafka::consumer::StreamConsumer,
+}
+
+#[async_trait::async_trait]
+impl KafkaConsumer for RdKafkaConsumer {
+    async fn recv(&mut self) -> anyhow::Result<KafkaRecord> {
+        use rdkafka::Message;
+        let message = self.consumer.recv().await?;
+        Ok(KafkaRecord {
+            topic: message.topic().to_string(),
+            partition: message.partition(),
+            offset: message.offset(),
+            payload: message.payload().unwrap_or_default().to_vec(),
+        })
+    }
+
+    fn commit(&mut self, record: &KafkaRecord) -> anyhow::Result<()> {
+        use rdkafka::consumer::Consumer;
+        let mut offsets = rdkafka::TopicPartitionList::new();
+        offsets.add_partition_offset(&record.topic, record.partition, rdkafka::Offset::Offset(record.offset + 1))?;
+        self.consumer.commit(&offsets, rdkafka::consumer::CommitMode::Async)?;
+        Ok(())
+    }
+}
+
+/// Create a Kafka consumer based on rdkafka.
+pub fn rdkafka_consumer(config: &KafkaConsumerConfig) -> anyhow::Result<Box<dyn KafkaConsumer>> {
+    use rdkafka::consumer::Consumer;
+    let consumer: rdkafka::consumer::StreamConsumer = rdkafka::config::ClientConfig::new()
+        .set("bootstrap.servers", &config.brokers)
+        .set("group.id", &config.group_id)
+        .set("enable.auto.commit", "false")
+        .create()?;
+    consumer.subscribe(&config.topics.iter().map(|x| x.as_str()).collect::<Vec<&str>>())?;
+    Ok(Box::new(RdKafkaConsumer { consumer }))
+}
+
+#[derive(Clone)]
+pub struct KafkaIngressResourceProvider {
+    inner: std::sync::Arc<tokio::sync::Mutex<KafkaIngressResourceProviderInner>>,
+}
+
+pub struct KafkaIngressResourceProviderInner {
+    resource_provider_id: edgeless_api::function_instance::InstanceId,
+    dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+    telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+    consumer_factory: KafkaConsumerFactory,
+    instances: std::collections::HashMap<edgeless_api::function_instance::ComponentId, KafkaIngressResource>,
+}
+
+pub struct KafkaIngressResource {
+    join_handle: tokio::task::JoinHandle<()>,
+    target: Option<edgeless_api::function_instance::InstanceId>,
+}
+
+impl Drop for KafkaIngressResource {
+    fn drop(&mut self) {
+        self.join_handle.abort();
+    }
+}
+
+impl KafkaIngressResource {
+    #[allow(clippy::too_many_arguments)]
+    async fn new(
+        consumer: Box<dyn KafkaConsumer>,
+        async_out: bool,
+        retry_interval: std::time::Duration,
+        max_attempts: u32,
+        self_id: edgeless_api::function_instance::InstanceId,
+        dataplane_handle: edgeless_dataplane::handle::DataplaneHandle,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        inner: std::sync::Arc<tokio::sync::Mutex<KafkaIngressResourceProviderInner>>,
+    ) -> Self {
+        let mut consumer = consumer;
+        let mut dataplane_handle = dataplane_handle;
+        let mut telemetry_handle = telemetry_handle;
+
+        let handle = tokio::spawn(async move {
+            loop {
+                let record = match consumer.recv().await {
+                    Ok(record) => record,
+                    Err(err) => {
+                        log::warn!("Error when receiving from Kafka: {}", err);
+                        tokio::time::sleep(retry_interval).await;
+                        continue;
+                    }
+                };
+                let started = chrono::Utc::now();
+
+                // Deliver the record until successful, so that the offset
+                // is not committed for records that have not been consumed,
+                // or until the maximum number of attempts is reached, so that
+                // a record that cannot be processed does not block the
+                // following ones.
+                let mut attempts = 0;
+                loop {
+                    let target = inner
+                        .lock()
+                        .await
+                        .instances
+                        .get(&self_id.function_id)
+                        .and_then(|instance| instance.target);
+                    if let Some(target) = target {
+                        let metadata = edgeless_api::function_instance::EventMetadata::empty_new_root();
+                        if async_out {
+                            dataplane_handle
+                                .send_output("new_message", target, record.payload.clone(), &metadata)
+                                .await;
+                            break;
+                        }
+                        match dataplane_handle
+                            .call_output("new_message", target, record.payload.clone(), &metadata)
+                            .await
+                        {
+                            edgeless_dataplane::core::CallRet::Reply(_) | edgeless_dataplane::core::CallRet::NoReply => break,
+                            edgeless_dataplane::core::CallRet::Err | edgeless_dataplane::core::CallRet::Timeout => {
+                                attempts += 1;
+                                log::warn!(
+                                    "Failed to deliver the record at offset {} of partition {} of topic '{}', attempt {}",
+                                    record.offset,
+                                    record.partition,
+                                    record.topic,
+                                    attempts
+                                );
+                                if max_attempts > 0 && attempts >= max_attempts {
+                                    dataplane_handle
+                                        .send_dead_letter(
+                                            &format!("maximum delivery attempts reached: {}", attempts),
+                                            &self_id,
+                                            &target,
+                                            &record.payload,
+                                            &metadata,
+                                        )
+                                        .await;
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    tokio::time::sleep(retry_interval).await;
+                }
+
+                if let Err(err) = consumer.commit(&record) {
+                    log::error!(
+                        "Failed to commit the offset {} of partition {} of topic '{}': {}",
+                        record.offset,
+                        record.partition,
+                        record.topic,
+                        err
+                    );
+                }
+
+                crate::resources::observe_execution(started, &mut telemetry_handle, !async_out);
+            }
+        });
+
+        Self {
+            join_handle: handle,
+            target: None,
+        }
+    }
+}
+
+impl KafkaIngressResourceProvider {
+    pub async fn new(
+        dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        resource_provider_id: edgeless_api::function_instance::InstanceId,
+        consumer_factory: KafkaConsumerFactory,
+    ) -> Self {
+        Self {
+            inner: std::sync::Arc::new(tokio::sync::Mutex::new(KafkaIngressResourceProviderInner {
+                resource_provider_id,
+                dataplane_provider,
+                telemetry_handle,
+                consumer_factory,
+                instances: std::collections::HashMap::new(),
+            })),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId> for KafkaIngressResourceProvider {
+    async fn start(
+        &mut self,
+        instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
+    ) -> anyhow::Result<edgeless_api::common::StartComponentResponse<edgeless_api::function_instance::InstanceId>> {
+        let (Some(brokers), Some(topics), Some(group_id)) = (
+            instance_specification.configuration.get("brokers"),
+            instance_specification.configuration.get("topics"),
+            instance_specification.configuration.get("group_id"),
+        ) else {
+            return Ok(edgeless_api::common::StartComponentResponse::ResponseError(
+                edgeless_api::common::ResponseError {
+                    summary: "Invalid resource configuration".to_string(),
+                    detail: Some("One of the fields 'brokers', 'topics', or 'group_id' is missing".to_string()),
+                },
+            ));
+        };
+        let config = KafkaConsumerConfig {
+            brokers: brokers.clone(),
+            topics: topics.split(",").filter(|x| !x.is_empty()).map(|x| x.to_string()).collect(),
+            group_id: group_id.clone(),
+        };
+        let async_out = instance_specification
+            .configuration
+            .get("async")
+            .unwrap_or(&String::from("false"))
+            .eq_ignore_ascii_case("true");
+        let retry_interval_ms = instance_specification
+            .configuration
+            .get("retry_interval_ms")
+            .and_then(|val| val.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_RETRY_INTERVAL_MS);
+        let max_attempts = instance_specification
+            .configuration
+            .get("max_attempts")
+            .and_then(|val| val.parse::<u32>().ok())
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+
+        let mut lck = self.inner.lock().await;
+
+        let consumer = match (lck.consumer_factory)(&config) {
+            Ok(consumer) => consumer,
+            Err(err) => {
+                return Ok(edgeless_api::common::StartComponentResponse::ResponseError(
+                    edgeless_api::common::ResponseError {
+                        summary: "Invalid resource configuration".to_string(),
+                        detail: Some(err.to_string()),
+                    },
+                ));
+            }
+        };
+
+        let new_id = edgeless_api::function_instance::InstanceId::new(lck.resource_provider_id.node_id);
+        let dataplane_handle = lck.dataplane_provider.get_handle_for(new_id).await;
+        let telemetry_handle = lck.telemetry_handle.fork(std::collections::BTreeMap::from([(
+            "FUNCTION_ID".to_string(),
+            new_id.function_id.to_string(),
+        )]));
+
+        log::info!(
+            "KafkaIngressResource created, brokers: {}, topics: {:?}, group_id: {}",
+            config.brokers,
+            config.topics,
+            config.group_id
+        );
+        lck.instances.insert(
+            new_id.function_id,
+            KafkaIngressResource::new(
+                consumer,
+                async_out,
+                std::time::Duration::from_millis(retry_interval_ms),
+                max_attempts,
+                new_id,
+                dataplane_handle,
+                telemetry_handle,
+                self.inner.clone(),
+            )
+            .await,
+        );
+
+        Ok(edgeless_api::common::StartComponentResponse::InstanceId(new_id))
+    }
+
+    async fn stop(&mut self, resource_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
+        self.inner.lock().await.instances.remove(&resource_id.function_id);
+        Ok(())
+    }
+
+    async fn patch(&mut self, update: edgeless_api::common::PatchRequest) -> anyhow::Result<()> {
+        let mut lck = self.inner.lock().await;
+        if let Some(instance) = lck.instances.get_mut(&update.function_id) {
+            instance.target = update.output_mapping.get("new_message").copied();
+        } else {
+            anyhow::bail!("Patching a non-existing resource: {}", update.function_id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edgeless_api::resource_configuration::ResourceConfigurationAPI;
+
+    struct MockTelemetryHandle {}
+
+    impl edgeless_telemetry::telemetry_events::TelemetryHandleAPI for MockTelemetryHandle {
+        fn observe(&mut self, _event: edgeless_telemetry::telemetry_events::TelemetryEvent, _event_tags: std::collections::BTreeMap<String, String>) {
+        }
+        fn fork(
+            &mut self,
+            _child_tags: std::collections::BTreeMap<String, String>,
+        ) -> Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI> {
+            Box::new(MockTelemetryHandle {})
+        }
+    }
+
+    struct MockConsumer {
+        records: std::sync::Arc<std::sync::Mutex<std::collections::VecDeque<KafkaRecord>>>,
+        commits: std::sync::Arc<std::sync::Mutex<Vec<i64>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl KafkaConsumer for MockConsumer {
+        async fn recv(&mut self) -> anyhow::Result<KafkaRecord> {
+            loop {
+                if let Some(record) = self.records.lock().unwrap().pop_front() {
+                    return Ok(record);
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        }
+
+        fn commit(&mut self, record: &KafkaRecord) -> anyhow::Result<()> {
+            self.commits.lock().unwrap().push(record.offset);
+            Ok(())
+        }
+    }
+
+    fn record(offset: i64) -> KafkaRecord {
+        KafkaRecord {
+            topic: String::from("sensors"),
+            partition: 0,
+            offset,
+            payload: format!("record-{}", offset).into_bytes(),
+        }
+    }
+
+    async fn wait_for_commits(commits: &std::sync::Arc<std::sync::Mutex<Vec<i64>>>, expected: Vec<i64>) {
+        for _ in 0..100 {
+            if *commits.lock().unwrap() == expected {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(expected, *commits.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_kafka_ingress_commit_after_call() {
+        let node_id = uuid::Uuid::new_v4();
+        let mut dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7093".to_string(), None).await;
+
+        let records = std::sync::Arc::new(std::sync::Mutex::new(std::collections::VecDeque::from([record(10), record(11)])));
+        let commits = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let configs = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let factory: KafkaConsumerFactory = {
+            let records = records.clone();
+            let commits = commits.clone();
+            let configs = configs.clone();
+            Box::new(move |config: &KafkaConsumerConfig| -> anyhow::Result<Box<dyn KafkaConsumer>> {
+                configs.lock().unwrap().push(config.clone());
+                Ok(Box::new(MockConsumer {
+                    records: records.clone(),
+                    commits: commits.clone(),
+                }))
+            })
+        };
+
+        let mut provider = KafkaIngressResourceProvider::new(
+            dataplane_provider.clone(),
+            Box::new(MockTelemetryHandle {}),
+            edgeless_api::function_instance::InstanceId::new(node_id),
+            factory,
+        )
+        .await;
+
+        // Missing configuration.
+        let res = provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("kafka-ingress"),
+                configuration: std::collections::HashMap::from([(String::from("brokers"), String::from("localhost:9092"))]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(res, edgeless_api::common::StartComponentResponse::ResponseError(_)));
+
+        let res = provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("kafka-ingress"),
+                configuration: std::collections::HashMap::from([
+                    (String::from("brokers"), String::from("localhost:9092")),
+                    (String::from("topics"), String::from("sensors,other")),
+                    (String::from("group_id"), String::from("group_1")),
+                    (String::from("retry_interval_ms"), String::from("10")),
+                ]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        let resource_id = match res {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+        assert_eq!(
+            vec![KafkaConsumerConfig {
+                brokers: String::from("localhost:9092"),
+                topics: vec![String::from("sensors"), String::from("other")],
+                group_id: String::from("group_1"),
+            }],
+            *configs.lock().unwrap()
+        );
+
+        // Records are not consumed until the output channel is patched.
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(commits.lock().unwrap().is_empty());
+
+        let target_id = edgeless_api::function_instance::InstanceId::new(node_id);
+        let mut target = dataplane_provider.get_handle_for(target_id).await;
+        provider
+            .patch(edgeless_api::common::PatchRequest {
+                function_id: resource_id.function_id,
+                output_mapping: std::collections::HashMap::from([(String::from("new_message"), target_id)]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await
+            .unwrap();
+
+        // The first call fails: the offset is not committed and the record
+        // is delivered again.
+        let event = target.receive_next().await;
+        assert_eq!(edgeless_dataplane::core::Message::Call(b"record-10".to_vec()), event.message);
+        target
+            .reply(event.source_id, event.channel_id, edgeless_dataplane::core::CallRet::Err, &event.metadata)
+            .await;
+        let event = target.receive_next().await;
+        assert_eq!(edgeless_dataplane::core::Message::Call(b"record-10".to_vec()), event.message);
+        assert!(commits.lock().unwrap().is_empty());
+        target
+            .reply(
+                event.source_id,
+                event.channel_id,
+                edgeless_dataplane::core::CallRet::Reply(vec![]),
+                &event.metadata,
+            )
+            .await;
+        wait_for_commits(&commits, vec![10]).await;
+
+        let event = target.receive_next().await;
+        assert_eq!(edgeless_dataplane::core::Message::Call(b"record-11".to_vec()), event.message);
+        target
+            .reply(
+                event.source_id,
+                event.channel_id,
+                edgeless_dataplane::core::CallRet::NoReply,
+                &event.metadata,
+            )
+            .await;
+        wait_for_commits(&commits, vec![10, 11]).await;
+
+        provider.stop(resource_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_kafka_ingress_max_attempts() {
+        let node_id = uuid::Uuid::new_v4();
+        let mut dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7085".to_string(), None).await;
+
+        let records = std::sync::Arc::new(std::sync::Mutex::new(std::collections::VecDeque::from([record(20), record(21)])));
+        let commits = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let factory: KafkaConsumerFactory = {
+            let records = records.clone();
+            let commits = commits.clone();
+            Box::new(move |_config: &KafkaConsumerConfig| -> anyhow::Result<Box<dyn KafkaConsumer>> {
+                Ok(Box::new(MockConsumer {
+                    records: records.clone(),
+                    commits: commits.clone(),
+                }))
+            })
+        };
+
+        let mut provider = KafkaIngressResourceProvider::new(
+            dataplane_provider.clone(),
+            Box::new(MockTelemetryHandle {}),
+            edgeless_api::function_instance::InstanceId::new(node_id),
+            factory,
+        )
+        .await;
+
+        let res = provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("kafka-ingress"),
+                configuration: std::collections::HashMap::from([
+                    (String::from("brokers"), String::from("localhost:9092")),
+                    (String::from("topics"), String::from("sensors")),
+                    (String::from("group_id"), String::from("group_1")),
+                    (String::from("retry_interval_ms"), String::from("10")),
+                    (String::from("max_attempts"), String::from("2")),
+                ]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        let resource_id = match res {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+
+        let target_id = edgeless_api::function_instance::InstanceId::new(node_id);
+        let mut target = dataplane_provider.get_handle_for(target_id).await;
+        let dead_letter_id = edgeless_api::function_instance::InstanceId::new(node_id);
+        let mut dead_letter = dataplane_provider.get_handle_for(dead_letter_id).await;
+        dataplane_provider
+            .patch_dead_letter(&edgeless_api::common::PatchRequest {
+                function_id: resource_id.function_id,
+                output_mapping: std::collections::HashMap::from([(edgeless_api::workflow_instance::DEAD_LETTER_CHANNEL.to_string(), dead_letter_id)]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await;
+        provider
+            .patch(edgeless_api::common::PatchRequest {
+                function_id: resource_id.function_id,
+                output_mapping: std::collections::HashMap::from([(String::from("new_message"), target_id)]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await
+            .unwrap();
+
+        // The record fails as many times as the maximum number of attempts:
+        // it is forwarded to the dead letter and its offset is committed.
+        for _ in 0..2 {
+            let event = target.receive_next().await;
+            assert_eq!(edgeless_dataplane::core::Message::Call(b"record-20".to_vec()), event.message);
+            target
+                .reply(event.source_id, event.channel_id, edgeless_dataplane::core::CallRet::Err, &event.metadata)
+                .await;
+        }
+        let event = dead_letter.receive_next().await;
+        let edgeless_dataplane::core::Message::Cast(data) = event.message else {
+            panic!("dead letter not received as a cast");
+        };
+        let letter: edgeless_dataplane::dead_letter::DeadLetter = serde_json::from_slice(&data).unwrap();
+        assert_eq!(resource_id.function_id, letter.source.function_id);
+        assert_eq!(target_id.function_id, letter.target.function_id);
+        wait_for_commits(&commits, vec![20]).await;
+
+        // The following record is delivered.
+        let event = target.receive_next().await;
+        assert_eq!(edgeless_dataplane::core::Message::Call(b"record-21".to_vec()), event.message);
+        target
+            .reply(
+                event.source_id,
+                event.channel_id,
+                edgeless_dataplane::core::CallRet::NoReply,
+                &event.metadata,
+            )
+            .await;
+        wait_for_commits(&commits, vec![20, 21]).await;
+
+        provider.stop(resource_id).await.unwrap();
+    }
+}
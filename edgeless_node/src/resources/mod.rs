@@ -4,16 +4,24 @@
 pub mod dda;
 pub mod file_log;
 pub mod file_pusher;
+pub mod grpc_egress;
 pub mod http_egress;
 pub mod http_ingress;
 pub mod http_poster;
 #[cfg(feature = "rdkafka")]
 pub mod kafka_egress;
+#[cfg(feature = "rdkafka")]
+pub mod kafka_ingress;
+pub mod mqtt_egress;
+pub mod mqtt_ingress;
+pub mod object_store;
 pub mod ollama;
 pub mod redis;
 pub mod resource_provider_specs;
 pub mod serverless;
 pub mod sqlx;
+pub mod timer;
+pub mod ws_ingress;
 
 pub fn observe_transfer(
     created: edgeless_api::function_instance::EventTimestamp,
@@ -42,3 +50,38 @@ pub fn observe_execution(
         std::collections::BTreeMap::from([("EVENT_TYPE".to_string(), event_type.to_string())]),
     );
 }
+
+/// Return the payload of an event containing both a key, e.g., the topic of
+/// an MQTT message, and data, separated by a NUL character.
+pub fn encode_message(key: &str, data: &[u8]) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(key.len() + 1 + data.len());
+    ret.extend_from_slice(key.as_bytes());
+    ret.push(0);
+    ret.extend_from_slice(data);
+    ret
+}
+
+/// Return the key and data from the payload of an event, if it contains
+/// both, with a non-empty key.
+pub fn decode_message(data: &[u8]) -> Option<(String, Vec<u8>)> {
+    let pos = data.iter().position(|x| *x == 0)?;
+    let key = std::str::from_utf8(&data[..pos]).ok()?;
+    if key.is_empty() {
+        return None;
+    }
+    Some((key.to_string(), data[pos + 1..].to_vec()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_message() {
+        let encoded = encode_message("sensors/1", b"\x00\x01hello");
+        assert_eq!(Some(("sensors/1".to_string(), b"\x00\x01hello".to_vec())), decode_message(&encoded));
+        assert_eq!(Some(("sensors/1".to_string(), vec![])), decode_message(b"sensors/1\x00"));
+        assert_eq!(None, decode_message(b"no key"));
+        assert_eq!(None, decode_message(b"\x00data"));
+    }
+}
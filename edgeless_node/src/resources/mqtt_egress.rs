@@ -0,0 +1,437 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use super::mqtt_ingress::{MQTT_REQUEST_QUEUE_LEN, RECONNECT_INTERVAL, client_configurations, mqtt_options, parse_bool, parse_qos};
+
+pub struct MqttEgressResourceSpec {}
+
+impl super::resource_provider_specs::ResourceProviderSpecs for MqttEgressResourceSpec {
+    fn class_type(&self) -> String {
+        String::from("mqtt-egress")
+    }
+
+    fn description(&self) -> String {
+        r"Publish messages on an external MQTT broker".to_string()
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn configurations(&self) -> std::collections::HashMap<String, String> {
+        let mut configurations = client_configurations();
+        configurations.extend([
+            (
+                String::from("topic"),
+                String::from(
+                    "Topic on which messages are published. If not specified, the event must contain the topic, followed by a NUL character and the payload",
+                ),
+            ),
+            (
+                String::from("qos"),
+                String::from("QoS of the messages published. One of: 0, 1, 2. Default: 0"),
+            ),
+            (
+                String::from("retain"),
+                String::from("Boolean specifying if the messages published are retained by the broker. One of: true, false. Default: false"),
+            ),
+        ]);
+        configurations
+    }
+
+    fn version(&self) -> String {
+        String::from("1.0")
+    }
+}
+
+#[derive(Clone)]
+pub struct MqttEgressResourceProvider {
+    inner: std::sync::Arc<tokio::sync::Mutex<MqttEgressResourceProviderInner>>,
+}
+
+pub struct MqttEgressResourceProviderInner {
+    resource_provider_id: edgeless_api::function_instance::InstanceId,
+    dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+    telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+    instances: std::collections::HashMap<edgeless_api::function_instance::InstanceId, MqttEgressResource>,
+}
+
+pub struct MqttEgressResource {
+    join_handle: tokio::task::JoinHandle<()>,
+    eventloop_join_handle: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for MqttEgressResource {
+    fn drop(&mut self) {
+        self.join_handle.abort();
+        self.eventloop_join_handle.abort();
+    }
+}
+
+/// The mqtt-egress resource publishes the payload of the events received on
+/// an MQTT broker, either on the topic in the configuration or on the one
+/// contained in the event.
+/// Calls are replied to as soon as the message is queued for publication,
+/// with an error if this is not possible, e.g., because the event does not
+/// contain the topic.
+impl MqttEgressResource {
+    async fn new(
+        options: rumqttc::MqttOptions,
+        topic: Option<String>,
+        qos: rumqttc::QoS,
+        retain: bool,
+        dataplane_handle: edgeless_dataplane::handle::DataplaneHandle,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+    ) -> Self {
+        let mut dataplane_handle = dataplane_handle;
+        let mut telemetry_handle = telemetry_handle;
+
+        let (client, mut eventloop) = rumqttc::AsyncClient::new(options, MQTT_REQUEST_QUEUE_LEN);
+
+        // The event loop must be polled for the messages to be published.
+        let eventloop_join_handle = tokio::spawn(async move {
+            loop {
+                if let Err(err) = eventloop.poll().await {
+                    log::warn!("Error on the connection with the MQTT broker: {}", err);
+                    tokio::time::sleep(RECONNECT_INTERVAL).await;
+                }
+            }
+        });
+
+        let join_handle = tokio::spawn(async move {
+            loop {
+                let edgeless_dataplane::core::DataplaneEvent {
+                    source_id,
+                    channel_id,
+                    message,
+                    created,
+                    metadata,
+                } = dataplane_handle.receive_next().await;
+                let started = crate::resources::observe_transfer(created, &mut telemetry_handle);
+
+                let mut need_reply = false;
+                let message_data = match message {
+                    edgeless_dataplane::core::Message::Call(data) => {
+                        need_reply = true;
+                        data
+                    }
+                    edgeless_dataplane::core::Message::Cast(data) => data,
+                    _ => {
+                        continue;
+                    }
+                };
+
+                let message = match &topic {
+                    Some(topic) => Some((topic.clone(), message_data)),
+                    None => crate::resources::decode_message(&message_data),
+                };
+                let res = match message {
+                    Some((topic, payload)) => match client.publish(&topic, qos, retain, payload).await {
+                        Ok(_) => true,
+                        Err(err) => {
+                            log::error!("Failed to publish message on MQTT topic '{}': {}", topic, err);
+                            false
+                        }
+                    },
+                    None => {
+                        log::warn!("Event received without an MQTT topic, ignored");
+                        false
+                    }
+                };
+
+                if need_reply {
+                    dataplane_handle
+                        .reply(
+                            source_id,
+                            channel_id,
+                            if res {
+                                edgeless_dataplane::core::CallRet::Reply(Vec::new())
+                            } else {
+                                edgeless_dataplane::core::CallRet::Err
+                            },
+                            &metadata,
+                        )
+                        .await;
+                }
+
+                crate::resources::observe_execution(started, &mut telemetry_handle, need_reply);
+            }
+        });
+
+        Self {
+            join_handle,
+            eventloop_join_handle,
+        }
+    }
+}
+
+impl MqttEgressResourceProvider {
+    pub async fn new(
+        dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        resource_provider_id: edgeless_api::function_instance::InstanceId,
+    ) -> Self {
+        Self {
+            inner: std::sync::Arc::new(tokio::sync::Mutex::new(MqttEgressResourceProviderInner {
+                resource_provider_id,
+                dataplane_provider,
+                telemetry_handle,
+                instances: std::collections::HashMap::new(),
+            })),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId> for MqttEgressResourceProvider {
+    async fn start(
+        &mut self,
+        instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
+    ) -> anyhow::Result<edgeless_api::common::StartComponentResponse<edgeless_api::function_instance::InstanceId>> {
+        let mut lck = self.inner.lock().await;
+        let new_id = edgeless_api::function_instance::InstanceId::new(lck.resource_provider_id.node_id);
+
+        let configuration = &instance_specification.configuration;
+        let (options, qos) = match (
+            mqtt_options(&format!("edgeless-{}", new_id.function_id), configuration),
+            parse_qos(configuration, "qos"),
+        ) {
+            (Ok(options), Ok(qos)) => (options, qos),
+            (Err(err), _) | (_, Err(err)) => {
+                return Ok(edgeless_api::common::StartComponentResponse::ResponseError(
+                    edgeless_api::common::ResponseError {
+                        summary: "Invalid resource configuration".to_string(),
+                        detail: Some(err.to_string()),
+                    },
+                ));
+            }
+        };
+        let topic = configuration.get("topic").filter(|x| !x.is_empty()).cloned();
+        let retain = parse_bool(configuration, "retain");
+
+        let dataplane_handle = lck.dataplane_provider.get_handle_for(new_id).await;
+        let telemetry_handle = lck.telemetry_handle.fork(std::collections::BTreeMap::from([(
+            "FUNCTION_ID".to_string(),
+            new_id.function_id.to_string(),
+        )]));
+
+        log::info!(
+            "MqttEgressResource created, broker {:?}, topic {:?}, qos {:?}, retain {}",
+            options.broker_address(),
+            topic,
+            qos,
+            retain
+        );
+        lck.instances.insert(
+            new_id,
+            MqttEgressResource::new(options, topic, qos, retain, dataplane_handle, telemetry_handle).await,
+        );
+
+        Ok(edgeless_api::common::StartComponentResponse::InstanceId(new_id))
+    }
+
+    async fn stop(&mut self, resource_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
+        self.inner.lock().await.instances.remove(&resource_id);
+        Ok(())
+    }
+
+    async fn patch(&mut self, _update: edgeless_api::common::PatchRequest) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edgeless_api::resource_configuration::ResourceConfigurationAPI;
+
+    struct MockTelemetryHandle {}
+
+    impl edgeless_telemetry::telemetry_events::TelemetryHandleAPI for MockTelemetryHandle {
+        fn observe(&mut self, _event: edgeless_telemetry::telemetry_events::TelemetryEvent, _event_tags: std::collections::BTreeMap<String, String>) {
+        }
+        fn fork(
+            &mut self,
+            _child_tags: std::collections::BTreeMap<String, String>,
+        ) -> Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI> {
+            Box::new(MockTelemetryHandle {})
+        }
+    }
+
+    /// Start an in-process MQTT broker listening on the given port.
+    fn start_broker(port: u16) {
+        let config = toml::from_str::<rumqttd::Config>(&format!(
+            r#"
+id = 0
+
+[router]
+max_connections = 10
+max_outgoing_packet_count = 200
+max_segment_size = 104857600
+max_segment_count = 10
+
+[v4.1]
+name = "v4-1"
+listen = "127.0.0.1:{}"
+next_connection_delay_ms = 1
+
+[v4.1.connections]
+connection_timeout_ms = 60000
+max_payload_size = 20480
+max_inflight_count = 100
+dynamic_filters = true
+"#,
+            port
+        ))
+        .unwrap();
+        std::thread::spawn(move || {
+            let mut broker = rumqttd::Broker::new(config);
+            broker.start().unwrap();
+        });
+    }
+
+    #[tokio::test]
+    async fn test_mqtt_egress_ingress() {
+        start_broker(1884);
+
+        let node_id = uuid::Uuid::new_v4();
+        let mut dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7092".to_string(), None).await;
+
+        let mut egress_provider = MqttEgressResourceProvider::new(
+            dataplane_provider.clone(),
+            Box::new(MockTelemetryHandle {}),
+            edgeless_api::function_instance::InstanceId::new(node_id),
+        )
+        .await;
+        let mut ingress_provider = crate::resources::mqtt_ingress::MqttIngressResourceProvider::new(
+            dataplane_provider.clone(),
+            Box::new(MockTelemetryHandle {}),
+            edgeless_api::function_instance::InstanceId::new(node_id),
+        )
+        .await;
+
+        // Invalid configurations.
+        for configuration in [
+            vec![("topic", "sensors/1")],
+            vec![("broker_url", "mqtt://127.0.0.1:port")],
+            vec![("broker_url", "mqtt://127.0.0.1:1884"), ("qos", "3")],
+        ] {
+            let res = egress_provider
+                .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                    class_type: String::from("mqtt-egress"),
+                    configuration: configuration.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+                    workflow_id: String::from("workflow_1"),
+                })
+                .await
+                .unwrap();
+            assert!(matches!(res, edgeless_api::common::StartComponentResponse::ResponseError(_)));
+        }
+        let res = ingress_provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("mqtt-ingress"),
+                configuration: std::collections::HashMap::from([(String::from("broker_url"), String::from("mqtt://127.0.0.1:1884"))]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(res, edgeless_api::common::StartComponentResponse::ResponseError(_)));
+
+        // Publish a retained message, without a topic in the configuration.
+        let res = egress_provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("mqtt-egress"),
+                configuration: std::collections::HashMap::from([
+                    (String::from("broker_url"), String::from("mqtt://127.0.0.1:1884")),
+                    (String::from("qos"), String::from("1")),
+                    (String::from("retain"), String::from("true")),
+                ]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        let egress_id = match res {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+
+        let client_id = edgeless_api::function_instance::InstanceId::new(node_id);
+        let mut client = dataplane_provider.get_handle_for(client_id).await;
+        let metadata = edgeless_api::function_instance::EventMetadata::empty_new_root();
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Err,
+            client.call(egress_id, b"no topic".to_vec(), &metadata).await
+        );
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Reply(vec![]),
+            client
+                .call(egress_id, crate::resources::encode_message("sensors/1", b"21.5"), &metadata)
+                .await
+        );
+
+        // Subscribe after the message has been published: the retained
+        // message is received with its topic.
+        let res = ingress_provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("mqtt-ingress"),
+                configuration: std::collections::HashMap::from([
+                    (String::from("broker_url"), String::from("mqtt://127.0.0.1:1884")),
+                    (String::from("topics"), String::from("sensors/#")),
+                    (String::from("qos"), String::from("1")),
+                    (String::from("with_topic"), String::from("true")),
+                ]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        let ingress_id = match res {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+        ingress_provider
+            .patch(edgeless_api::common::PatchRequest {
+                function_id: ingress_id.function_id,
+                output_mapping: std::collections::HashMap::from([(String::from("new_message"), client_id)]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await
+            .unwrap();
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), client.receive_next())
+            .await
+            .expect("retained message not received");
+        assert_eq!(ingress_id, event.source_id);
+        assert_eq!(
+            edgeless_dataplane::core::Message::Cast(crate::resources::encode_message("sensors/1", b"21.5")),
+            event.message
+        );
+
+        // Messages published afterwards are received, too.
+        let res = egress_provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("mqtt-egress"),
+                configuration: std::collections::HashMap::from([
+                    (String::from("broker_url"), String::from("mqtt://127.0.0.1:1884")),
+                    (String::from("topic"), String::from("sensors/2")),
+                ]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        let egress_id = match res {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+        client.send(egress_id, b"22.0".to_vec(), &metadata).await;
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), client.receive_next())
+            .await
+            .expect("message not received");
+        assert_eq!(
+            edgeless_dataplane::core::Message::Cast(crate::resources::encode_message("sensors/2", b"22.0")),
+            event.message
+        );
+
+        egress_provider.stop(egress_id).await.unwrap();
+        ingress_provider.stop(ingress_id).await.unwrap();
+    }
+}
@@ -0,0 +1,417 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Default port of the MQTT broker.
+const DEFAULT_MQTT_PORT: u16 = 1883;
+
+/// Default keep-alive interval of the connection with the MQTT broker, in s.
+const DEFAULT_KEEP_ALIVE_SEC: u64 = 30;
+
+/// Interval between two attempts to reconnect to the MQTT broker.
+pub(crate) const RECONNECT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Capacity of the queue of requests towards the MQTT event loop.
+pub(crate) const MQTT_REQUEST_QUEUE_LEN: usize = 100;
+
+pub struct MqttIngressResourceSpec {}
+
+impl super::resource_provider_specs::ResourceProviderSpecs for MqttIngressResourceSpec {
+    fn class_type(&self) -> String {
+        String::from("mqtt-ingress")
+    }
+
+    fn description(&self) -> String {
+        r"Receive messages published on an external MQTT broker".to_string()
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("new_message")]
+    }
+
+    fn configurations(&self) -> std::collections::HashMap<String, String> {
+        let mut configurations = client_configurations();
+        configurations.extend([
+            (
+                String::from("topics"),
+                String::from("Comma-separated list of topic filters to subscribe to, which may contain the + and # wildcards"),
+            ),
+            (
+                String::from("qos"),
+                String::from("Maximum QoS of the messages received. One of: 0, 1, 2. Default: 0"),
+            ),
+            (
+                String::from("with_topic"),
+                String::from(
+                    "Boolean specifying if the topic is added to the event, followed by a NUL character and the payload. One of: true, false. Default: false, i.e., the event only contains the payload",
+                ),
+            ),
+        ]);
+        configurations
+    }
+
+    fn version(&self) -> String {
+        String::from("1.0")
+    }
+}
+
+/// Configurations of the connection with the MQTT broker, common to the
+/// mqtt-ingress and mqtt-egress resources.
+pub(crate) fn client_configurations() -> std::collections::HashMap<String, String> {
+    std::collections::HashMap::from([
+        (
+            String::from("broker_url"),
+            String::from("URL of the MQTT broker, e.g., mqtt://127.0.0.1:1883"),
+        ),
+        (String::from("username"), String::from("Username to authenticate with the MQTT broker")),
+        (String::from("password"), String::from("Password to authenticate with the MQTT broker")),
+        (
+            String::from("keep_alive_sec"),
+            String::from("Keep-alive interval of the connection with the MQTT broker, in s. Default: 30"),
+        ),
+        (
+            String::from("last_will_topic"),
+            String::from("If not empty, topic of the message published by the broker if the connection is lost"),
+        ),
+        (String::from("last_will_payload"), String::from("Payload of the last-will message")),
+        (
+            String::from("last_will_qos"),
+            String::from("QoS of the last-will message. One of: 0, 1, 2. Default: 0"),
+        ),
+        (
+            String::from("last_will_retain"),
+            String::from("Boolean specifying if the last-will message is retained. One of: true, false. Default: false"),
+        ),
+    ])
+}
+
+/// Return the host and port of an MQTT broker URL: mqtt://host[:port].
+pub(crate) fn parse_broker_url(broker_url: &str) -> anyhow::Result<(String, u16)> {
+    let address = broker_url
+        .strip_prefix("mqtt://")
+        .or_else(|| broker_url.strip_prefix("tcp://"))
+        .unwrap_or(broker_url)
+        .trim_end_matches('/');
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse::<u16>()?),
+        None => (address, DEFAULT_MQTT_PORT),
+    };
+    if host.is_empty() {
+        anyhow::bail!("Invalid MQTT broker URL: {}", broker_url);
+    }
+    Ok((host.to_string(), port))
+}
+
+/// Return the QoS from a resource configuration, 0 if not specified.
+pub(crate) fn parse_qos(configuration: &std::collections::HashMap<String, String>, key: &str) -> anyhow::Result<rumqttc::QoS> {
+    match configuration.get(key).map(|x| x.as_str()) {
+        None | Some("") | Some("0") => Ok(rumqttc::QoS::AtMostOnce),
+        Some("1") => Ok(rumqttc::QoS::AtLeastOnce),
+        Some("2") => Ok(rumqttc::QoS::ExactlyOnce),
+        Some(val) => anyhow::bail!("Invalid {}: {}", key, val),
+    }
+}
+
+/// Return the value of a boolean field in a resource configuration, false
+/// if not specified.
+pub(crate) fn parse_bool(configuration: &std::collections::HashMap<String, String>, key: &str) -> bool {
+    configuration.get(key).is_some_and(|x| x.eq_ignore_ascii_case("true"))
+}
+
+/// Return the options of the connection with the MQTT broker from a
+/// resource configuration.
+pub(crate) fn mqtt_options(client_id: &str, configuration: &std::collections::HashMap<String, String>) -> anyhow::Result<rumqttc::MqttOptions> {
+    let broker_url = configuration.get("broker_url").ok_or(anyhow::anyhow!("Missing field 'broker_url'"))?;
+    let (host, port) = parse_broker_url(broker_url)?;
+    let keep_alive_sec = match configuration.get("keep_alive_sec") {
+        Some(val) => val.parse::<u64>()?,
+        None => DEFAULT_KEEP_ALIVE_SEC,
+    };
+
+    let mut options = rumqttc::MqttOptions::new(client_id, host, port);
+    options.set_keep_alive(std::time::Duration::from_secs(keep_alive_sec));
+    if let Some(username) = configuration.get("username")
+        && !username.is_empty()
+    {
+        options.set_credentials(username, configuration.get("password").cloned().unwrap_or_default());
+    }
+    if let Some(last_will_topic) = configuration.get("last_will_topic")
+        && !last_will_topic.is_empty()
+    {
+        options.set_last_will(rumqttc::LastWill::new(
+            last_will_topic,
+            configuration.get("last_will_payload").cloned().unwrap_or_default(),
+            parse_qos(configuration, "last_will_qos")?,
+            parse_bool(configuration, "last_will_retain"),
+        ));
+    }
+    Ok(options)
+}
+
+#[derive(Clone)]
+pub struct MqttIngressResourceProvider {
+    inner: std::sync::Arc<tokio::sync::Mutex<MqttIngressResourceProviderInner>>,
+}
+
+pub struct MqttIngressResourceProviderInner {
+    resource_provider_id: edgeless_api::function_instance::InstanceId,
+    dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+    telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+    instances: std::collections::HashMap<edgeless_api::function_instance::ComponentId, MqttIngressResource>,
+}
+
+pub struct MqttIngressResource {
+    join_handle: tokio::task::JoinHandle<()>,
+    eventloop_join_handle: tokio::task::JoinHandle<()>,
+    target: Option<edgeless_api::function_instance::InstanceId>,
+}
+
+impl Drop for MqttIngressResource {
+    fn drop(&mut self) {
+        self.join_handle.abort();
+        self.eventloop_join_handle.abort();
+    }
+}
+
+/// The mqtt-ingress resource subscribes to the given topic filters on an
+/// MQTT broker and casts the messages received to the output channel.
+/// Each resource instance has its own connection with the broker, which is
+/// re-established if lost, subscribing again to the topic filters.
+impl MqttIngressResource {
+    #[allow(clippy::too_many_arguments)]
+    async fn new(
+        options: rumqttc::MqttOptions,
+        topics: Vec<String>,
+        qos: rumqttc::QoS,
+        with_topic: bool,
+        self_function_id: edgeless_api::function_instance::ComponentId,
+        dataplane_handle: edgeless_dataplane::handle::DataplaneHandle,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        inner: std::sync::Arc<tokio::sync::Mutex<MqttIngressResourceProviderInner>>,
+    ) -> Self {
+        let mut dataplane_handle = dataplane_handle;
+        let mut telemetry_handle = telemetry_handle;
+
+        let (client, mut eventloop) = rumqttc::AsyncClient::new(options, MQTT_REQUEST_QUEUE_LEN);
+        let (publish_sender, mut publish_receiver) = futures::channel::mpsc::unbounded::<rumqttc::Publish>();
+
+        // The event loop only queues the subscriptions and forwards the
+        // messages received, so that it keeps being polled, e.g., to send
+        // the keep-alive pings, while the messages are sent on the output.
+        let eventloop_join_handle = tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(rumqttc::Event::Incoming(rumqttc::Packet::ConnAck(_))) => {
+                        for topic in &topics {
+                            if let Err(err) = client.try_subscribe(topic, qos) {
+                                log::error!("Could not subscribe to MQTT topic filter '{}': {}", topic, err);
+                            }
+                        }
+                    }
+                    Ok(rumqttc::Event::Incoming(rumqttc::Packet::Publish(publish))) => {
+                        if publish_sender.unbounded_send(publish).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        log::warn!("Error on the connection with the MQTT broker: {}", err);
+                        tokio::time::sleep(RECONNECT_INTERVAL).await;
+                    }
+                }
+            }
+        });
+
+        let join_handle = tokio::spawn(async move {
+            while let Some(publish) = futures::StreamExt::next(&mut publish_receiver).await {
+                let started = chrono::Utc::now();
+
+                let target = inner.lock().await.instances.get(&self_function_id).and_then(|instance| instance.target);
+                if let Some(target) = target {
+                    let msg = if with_topic {
+                        crate::resources::encode_message(&publish.topic, &publish.payload)
+                    } else {
+                        publish.payload.to_vec()
+                    };
+                    dataplane_handle
+                        .send_output(
+                            "new_message",
+                            target,
+                            msg,
+                            &edgeless_api::function_instance::EventMetadata::empty_new_root(),
+                        )
+                        .await;
+                }
+
+                crate::resources::observe_execution(started, &mut telemetry_handle, false);
+            }
+        });
+
+        Self {
+            join_handle,
+            eventloop_join_handle,
+            target: None,
+        }
+    }
+}
+
+impl MqttIngressResourceProvider {
+    pub async fn new(
+        dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        resource_provider_id: edgeless_api::function_instance::InstanceId,
+    ) -> Self {
+        Self {
+            inner: std::sync::Arc::new(tokio::sync::Mutex::new(MqttIngressResourceProviderInner {
+                resource_provider_id,
+                dataplane_provider,
+                telemetry_handle,
+                instances: std::collections::HashMap::new(),
+            })),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId> for MqttIngressResourceProvider {
+    async fn start(
+        &mut self,
+        instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
+    ) -> anyhow::Result<edgeless_api::common::StartComponentResponse<edgeless_api::function_instance::InstanceId>> {
+        let mut lck = self.inner.lock().await;
+        let new_id = edgeless_api::function_instance::InstanceId::new(lck.resource_provider_id.node_id);
+
+        let configuration = &instance_specification.configuration;
+        let parse_configuration = || -> anyhow::Result<(rumqttc::MqttOptions, Vec<String>, rumqttc::QoS)> {
+            let topics = configuration
+                .get("topics")
+                .ok_or(anyhow::anyhow!("Missing field 'topics'"))?
+                .split(",")
+                .map(|x| x.trim())
+                .filter(|x| !x.is_empty())
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>();
+            if topics.is_empty() {
+                anyhow::bail!("Empty field 'topics'");
+            }
+            let options = mqtt_options(&format!("edgeless-{}", new_id.function_id), configuration)?;
+            let qos = parse_qos(configuration, "qos")?;
+            Ok((options, topics, qos))
+        };
+        let (options, topics, qos) = match parse_configuration() {
+            Ok(val) => val,
+            Err(err) => {
+                return Ok(edgeless_api::common::StartComponentResponse::ResponseError(
+                    edgeless_api::common::ResponseError {
+                        summary: "Invalid resource configuration".to_string(),
+                        detail: Some(err.to_string()),
+                    },
+                ));
+            }
+        };
+        let with_topic = parse_bool(configuration, "with_topic");
+
+        let dataplane_handle = lck.dataplane_provider.get_handle_for(new_id).await;
+        let telemetry_handle = lck.telemetry_handle.fork(std::collections::BTreeMap::from([(
+            "FUNCTION_ID".to_string(),
+            new_id.function_id.to_string(),
+        )]));
+
+        log::info!(
+            "MqttIngressResource created, broker {:?}, topics {:?}, qos {:?}, with_topic {}",
+            options.broker_address(),
+            topics,
+            qos,
+            with_topic
+        );
+        lck.instances.insert(
+            new_id.function_id,
+            MqttIngressResource::new(
+                options,
+                topics,
+                qos,
+                with_topic,
+                new_id.function_id,
+                dataplane_handle,
+                telemetry_handle,
+                self.inner.clone(),
+            )
+            .await,
+        );
+
+        Ok(edgeless_api::common::StartComponentResponse::InstanceId(new_id))
+    }
+
+    async fn stop(&mut self, resource_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
+        self.inner.lock().await.instances.remove(&resource_id.function_id);
+        Ok(())
+    }
+
+    async fn patch(&mut self, update: edgeless_api::common::PatchRequest) -> anyhow::Result<()> {
+        let mut lck = self.inner.lock().await;
+        if let Some(instance) = lck.instances.get_mut(&update.function_id) {
+            instance.target = update.output_mapping.get("new_message").copied();
+        } else {
+            anyhow::bail!("Patching a non-existing resource: {}", update.function_id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mqtt_parse_broker_url() {
+        assert_eq!(("127.0.0.1".to_string(), 1884), parse_broker_url("mqtt://127.0.0.1:1884").unwrap());
+        assert_eq!(("broker".to_string(), 1883), parse_broker_url("tcp://broker/").unwrap());
+        assert_eq!(("broker".to_string(), 1883), parse_broker_url("broker").unwrap());
+        assert!(parse_broker_url("mqtt://broker:port").is_err());
+        assert!(parse_broker_url("mqtt://:1883").is_err());
+    }
+
+    #[test]
+    fn test_mqtt_options() {
+        let configuration = |entries: Vec<(&str, &str)>| -> std::collections::HashMap<String, String> {
+            entries.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+
+        assert!(mqtt_options("client", &configuration(vec![])).is_err());
+        assert!(mqtt_options("client", &configuration(vec![("broker_url", "mqtt://broker"), ("keep_alive_sec", "x")])).is_err());
+        assert!(
+            mqtt_options(
+                "client",
+                &configuration(vec![
+                    ("broker_url", "mqtt://broker"),
+                    ("last_will_topic", "status"),
+                    ("last_will_qos", "3")
+                ])
+            )
+            .is_err()
+        );
+
+        let options = mqtt_options(
+            "client",
+            &configuration(vec![
+                ("broker_url", "mqtt://broker:1884"),
+                ("keep_alive_sec", "10"),
+                ("last_will_topic", "status"),
+                ("last_will_payload", "offline"),
+                ("last_will_qos", "1"),
+                ("last_will_retain", "true"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(("broker".to_string(), 1884), options.broker_address());
+        assert_eq!(std::time::Duration::from_secs(10), options.keep_alive());
+        let last_will = options.last_will().unwrap();
+        assert_eq!("status", last_will.topic);
+        assert_eq!(b"offline".to_vec(), last_will.message.to_vec());
+        assert_eq!(rumqttc::QoS::AtLeastOnce, last_will.qos);
+        assert!(last_will.retain);
+
+        assert_eq!(rumqttc::QoS::AtMostOnce, parse_qos(&configuration(vec![]), "qos").unwrap());
+        assert_eq!(rumqttc::QoS::ExactlyOnce, parse_qos(&configuration(vec![("qos", "2")]), "qos").unwrap());
+    }
+}
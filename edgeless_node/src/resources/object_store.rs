@@ -0,0 +1,548 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Default interval between two listings of the objects under the watched
+/// prefix, in ms.
+const DEFAULT_WATCH_INTERVAL_MS: u64 = 1000;
+
+pub struct ObjectStoreResourceSpec {}
+
+impl super::resource_provider_specs::ResourceProviderSpecs for ObjectStoreResourceSpec {
+    fn class_type(&self) -> String {
+        String::from("object-store")
+    }
+
+    fn description(&self) -> String {
+        r"Read and write objects on an S3-compatible object storage or a local directory
+
+The first line of the message body contains the operation and its argument, i.e., one of:
+put <key>, followed by a newline and the content of the object
+get <key>, which returns the content of the object, if it exists
+list <prefix>, which returns the keys, sizes, and modification times of the objects in JSON
+delete <key>
+If 'watch_prefix' is specified, the key of every object that is created or modified under that prefix is cast on the 'new_object' output."
+            .to_string()
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("new_object")]
+    }
+
+    fn configurations(&self) -> std::collections::HashMap<String, String> {
+        std::collections::HashMap::from([
+            (
+                String::from("url"),
+                String::from("URL of the bucket or directory containing the objects, e.g., s3://bucket/path or file:///tmp/objects"),
+            ),
+            (
+                String::from("aws_*"),
+                String::from("Options of the S3 client, e.g., aws_endpoint, aws_region, aws_access_key_id, aws_secret_access_key, aws_allow_http"),
+            ),
+            (
+                String::from("watch_prefix"),
+                String::from("If present, the keys of the new objects with this prefix are cast on the output channel"),
+            ),
+            (
+                String::from("watch_interval_ms"),
+                String::from("Interval between two checks of the new objects, in ms. Default: 1000"),
+            ),
+        ])
+    }
+
+    fn version(&self) -> String {
+        String::from("1.0")
+    }
+}
+
+/// Operation requested to the object-store resource.
+#[derive(Debug, PartialEq)]
+pub enum ObjectStoreCommand<'a> {
+    Put { key: &'a str, data: &'a [u8] },
+    Get { key: &'a str },
+    List { prefix: &'a str },
+    Delete { key: &'a str },
+}
+
+impl<'a> ObjectStoreCommand<'a> {
+    pub fn parse(data: &'a [u8]) -> anyhow::Result<Self> {
+        let (header, body) = match data.iter().position(|x| *x == b'\n') {
+            Some(pos) => (&data[..pos], &data[pos + 1..]),
+            None => (data, &data[data.len()..]),
+        };
+        let header = std::str::from_utf8(header)?.trim_end_matches('\r');
+        let (op, arg) = header.split_once(' ').unwrap_or((header, ""));
+        let arg = arg.trim();
+        match op.to_lowercase().as_str() {
+            "put" if !arg.is_empty() => Ok(ObjectStoreCommand::Put { key: arg, data: body }),
+            "get" if !arg.is_empty() => Ok(ObjectStoreCommand::Get { key: arg }),
+            "delete" if !arg.is_empty() => Ok(ObjectStoreCommand::Delete { key: arg }),
+            "list" => Ok(ObjectStoreCommand::List { prefix: arg }),
+            "put" | "get" | "delete" => anyhow::bail!("Missing key in operation: {}", op),
+            _ => anyhow::bail!("Invalid operation: {}", op),
+        }
+    }
+}
+
+/// Return the path of an object from the base path and its key, which
+/// cannot contain empty, "." or ".." segments.
+fn object_path(base: &object_store::path::Path, key: &str) -> anyhow::Result<object_store::path::Path> {
+    let key = key.trim_matches('/');
+    if base.as_ref().is_empty() {
+        Ok(object_store::path::Path::parse(key)?)
+    } else if key.is_empty() {
+        Ok(base.clone())
+    } else {
+        Ok(object_store::path::Path::parse(format!("{}/{}", base, key))?)
+    }
+}
+
+/// Return the key of an object from its path and the base path.
+fn object_key(base: &object_store::path::Path, location: &object_store::path::Path) -> String {
+    location
+        .as_ref()
+        .strip_prefix(base.as_ref())
+        .unwrap_or(location.as_ref())
+        .trim_start_matches('/')
+        .to_string()
+}
+
+#[derive(Clone)]
+pub struct ObjectStoreResourceProvider {
+    inner: std::sync::Arc<tokio::sync::Mutex<ObjectStoreResourceProviderInner>>,
+}
+
+pub struct ObjectStoreResourceProviderInner {
+    resource_provider_id: edgeless_api::function_instance::InstanceId,
+    dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+    telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+    instances: std::collections::HashMap<edgeless_api::function_instance::ComponentId, ObjectStoreResource>,
+}
+
+pub struct ObjectStoreResource {
+    join_handle: tokio::task::JoinHandle<()>,
+    watch_join_handle: Option<tokio::task::JoinHandle<()>>,
+    target: Option<edgeless_api::function_instance::InstanceId>,
+}
+
+impl Drop for ObjectStoreResource {
+    fn drop(&mut self) {
+        self.join_handle.abort();
+        if let Some(watch_join_handle) = &self.watch_join_handle {
+            watch_join_handle.abort();
+        }
+    }
+}
+
+/// The object-store resource reads and writes objects, identified by keys
+/// relative to the URL in the resource configuration, on an S3-compatible
+/// object storage or on a local directory.
+///
+/// Optionally, the objects under a prefix are periodically listed and the
+/// keys of those created or modified since the previous listing are cast on
+/// the output channel.
+/// The objects that already exist when the resource is created are ignored,
+/// while those created or modified before the output channel is patched are
+/// notified as soon as it is.
+impl ObjectStoreResource {
+    #[allow(clippy::too_many_arguments)]
+    async fn new(
+        dataplane_handle: edgeless_dataplane::handle::DataplaneHandle,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        store: std::sync::Arc<dyn object_store::ObjectStore>,
+        base: object_store::path::Path,
+        watch: Option<(String, std::time::Duration)>,
+        self_function_id: edgeless_api::function_instance::ComponentId,
+        inner: std::sync::Arc<tokio::sync::Mutex<ObjectStoreResourceProviderInner>>,
+    ) -> anyhow::Result<Self> {
+        let mut dataplane_handle = dataplane_handle;
+        let mut telemetry_handle = telemetry_handle;
+
+        let watch_join_handle = match watch {
+            Some((prefix, interval)) => {
+                let prefix = object_path(&base, &prefix)?;
+                let mut watch_dataplane_handle = dataplane_handle.clone();
+                let store = store.clone();
+                let base = base.clone();
+                let mut last = list_objects(&store, &prefix).await?;
+                Some(tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(interval);
+                    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                    loop {
+                        interval.tick().await;
+                        let cur = match list_objects(&store, &prefix).await {
+                            Ok(cur) => cur,
+                            Err(err) => {
+                                log::warn!("Could not list the objects under '{}': {}", prefix, err);
+                                continue;
+                            }
+                        };
+                        // Without an output target, the changes are kept until
+                        // they can be notified.
+                        let target = inner.lock().await.instances.get(&self_function_id).and_then(|instance| instance.target);
+                        if let Some(target) = target {
+                            for (location, last_modified) in &cur {
+                                if last.get(location) != Some(last_modified) {
+                                    watch_dataplane_handle
+                                        .send_output(
+                                            "new_object",
+                                            target,
+                                            object_key(&base, location),
+                                            &edgeless_api::function_instance::EventMetadata::empty_new_root(),
+                                        )
+                                        .await;
+                                }
+                            }
+                            last = cur;
+                        }
+                    }
+                }))
+            }
+            None => None,
+        };
+
+        let handle = tokio::spawn(async move {
+            loop {
+                let edgeless_dataplane::core::DataplaneEvent {
+                    source_id,
+                    channel_id,
+                    message,
+                    created,
+                    metadata,
+                } = dataplane_handle.receive_next().await;
+                let started = crate::resources::observe_transfer(created, &mut telemetry_handle);
+
+                let (need_reply, message_data) = match message {
+                    edgeless_dataplane::core::Message::Call(data) => (true, data),
+                    edgeless_dataplane::core::Message::Cast(data) => (false, data),
+                    _ => {
+                        continue;
+                    }
+                };
+
+                let ret = match execute(&store, &base, &message_data).await {
+                    Ok(Some(reply)) => edgeless_dataplane::core::CallRet::Reply(reply),
+                    Ok(None) => edgeless_dataplane::core::CallRet::NoReply,
+                    Err(err) => {
+                        log::error!("Error on object-store resource: {}", err);
+                        edgeless_dataplane::core::CallRet::Err
+                    }
+                };
+                if need_reply {
+                    dataplane_handle.reply(source_id, channel_id, ret, &metadata).await;
+                }
+
+                crate::resources::observe_execution(started, &mut telemetry_handle, need_reply);
+            }
+        });
+
+        Ok(Self {
+            join_handle: handle,
+            watch_join_handle,
+            target: None,
+        })
+    }
+}
+
+/// Execute an operation and return the body of the reply, if any.
+async fn execute(
+    store: &std::sync::Arc<dyn object_store::ObjectStore>,
+    base: &object_store::path::Path,
+    data: &[u8],
+) -> anyhow::Result<Option<Vec<u8>>> {
+    match ObjectStoreCommand::parse(data)? {
+        ObjectStoreCommand::Put { key, data } => {
+            store.put(&object_path(base, key)?, data.to_vec().into()).await?;
+            Ok(Some(vec![]))
+        }
+        ObjectStoreCommand::Get { key } => match store.get(&object_path(base, key)?).await {
+            Ok(res) => Ok(Some(res.bytes().await?.to_vec())),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(err) => Err(err.into()),
+        },
+        ObjectStoreCommand::List { prefix } => {
+            let prefix = object_path(base, prefix)?;
+            let objects = futures::TryStreamExt::try_collect::<Vec<_>>(store.list(Some(&prefix))).await?;
+            let objects = objects
+                .into_iter()
+                .map(|meta| {
+                    serde_json::json!({
+                        "key": object_key(base, &meta.location),
+                        "size": meta.size,
+                        "last_modified": meta.last_modified.to_rfc3339(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(Some(serde_json::to_vec(&objects)?))
+        }
+        ObjectStoreCommand::Delete { key } => match store.delete(&object_path(base, key)?).await {
+            Ok(_) | Err(object_store::Error::NotFound { .. }) => Ok(Some(vec![])),
+            Err(err) => Err(err.into()),
+        },
+    }
+}
+
+/// Return the location and modification time of all the objects under a prefix.
+async fn list_objects(
+    store: &std::sync::Arc<dyn object_store::ObjectStore>,
+    prefix: &object_store::path::Path,
+) -> anyhow::Result<std::collections::HashMap<object_store::path::Path, chrono::DateTime<chrono::Utc>>> {
+    let objects = futures::TryStreamExt::try_collect::<Vec<_>>(store.list(Some(prefix))).await?;
+    Ok(objects.into_iter().map(|meta| (meta.location, meta.last_modified)).collect())
+}
+
+impl ObjectStoreResourceProvider {
+    pub async fn new(
+        dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        resource_provider_id: edgeless_api::function_instance::InstanceId,
+    ) -> Self {
+        Self {
+            inner: std::sync::Arc::new(tokio::sync::Mutex::new(ObjectStoreResourceProviderInner {
+                resource_provider_id,
+                dataplane_provider,
+                telemetry_handle,
+                instances: std::collections::HashMap::new(),
+            })),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId> for ObjectStoreResourceProvider {
+    async fn start(
+        &mut self,
+        instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
+    ) -> anyhow::Result<edgeless_api::common::StartComponentResponse<edgeless_api::function_instance::InstanceId>> {
+        let mut lck = self.inner.lock().await;
+        let new_id = edgeless_api::function_instance::InstanceId::new(lck.resource_provider_id.node_id);
+        let dataplane_handle = lck.dataplane_provider.get_handle_for(new_id).await;
+        let telemetry_handle = lck.telemetry_handle.fork(std::collections::BTreeMap::from([(
+            "FUNCTION_ID".to_string(),
+            new_id.function_id.to_string(),
+        )]));
+
+        let configuration = &instance_specification.configuration;
+        let res = async {
+            let url = configuration.get("url").ok_or(anyhow::anyhow!("Missing field 'url'"))?;
+            let options = configuration.iter().filter(|(k, _)| k.starts_with("aws_"));
+            let (store, base) = object_store::parse_url_opts(&url::Url::parse(url)?, options)?;
+            let watch = match configuration.get("watch_prefix") {
+                Some(prefix) => {
+                    let interval = match configuration.get("watch_interval_ms") {
+                        Some(val) => val.parse::<u64>()?,
+                        None => DEFAULT_WATCH_INTERVAL_MS,
+                    };
+                    Some((prefix.clone(), std::time::Duration::from_millis(interval.max(1))))
+                }
+                None => None,
+            };
+            log::info!("ObjectStoreResource created, url {}, watch {:?}", url, watch);
+            ObjectStoreResource::new(
+                dataplane_handle,
+                telemetry_handle,
+                std::sync::Arc::from(store),
+                base,
+                watch,
+                new_id.function_id,
+                self.inner.clone(),
+            )
+            .await
+        }
+        .await;
+
+        match res {
+            Ok(resource) => {
+                lck.instances.insert(new_id.function_id, resource);
+                Ok(edgeless_api::common::StartComponentResponse::InstanceId(new_id))
+            }
+            Err(err) => Ok(edgeless_api::common::StartComponentResponse::ResponseError(
+                edgeless_api::common::ResponseError {
+                    summary: "Invalid resource configuration".to_string(),
+                    detail: Some(err.to_string()),
+                },
+            )),
+        }
+    }
+
+    async fn stop(&mut self, resource_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
+        self.inner.lock().await.instances.remove(&resource_id.function_id);
+        Ok(())
+    }
+
+    async fn patch(&mut self, update: edgeless_api::common::PatchRequest) -> anyhow::Result<()> {
+        let mut lck = self.inner.lock().await;
+        if let Some(instance) = lck.instances.get_mut(&update.function_id) {
+            instance.target = update.output_mapping.get("new_object").copied();
+        } else {
+            anyhow::bail!("Patching a non-existing resource: {}", update.function_id);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edgeless_api::resource_configuration::ResourceConfigurationAPI;
+
+    struct MockTelemetryHandle {}
+
+    impl edgeless_telemetry::telemetry_events::TelemetryHandleAPI for MockTelemetryHandle {
+        fn observe(&mut self, _event: edgeless_telemetry::telemetry_events::TelemetryEvent, _event_tags: std::collections::BTreeMap<String, String>) {
+        }
+        fn fork(
+            &mut self,
+            _child_tags: std::collections::BTreeMap<String, String>,
+        ) -> Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI> {
+            Box::new(MockTelemetryHandle {})
+        }
+    }
+
+    #[test]
+    fn test_object_store_command() {
+        assert_eq!(
+            ObjectStoreCommand::Put {
+                key: "images/1.jpg",
+                data: b"\x00\x01\n"
+            },
+            ObjectStoreCommand::parse(b"put images/1.jpg\n\x00\x01\n").unwrap()
+        );
+        assert_eq!(
+            ObjectStoreCommand::Put { key: "empty", data: b"" },
+            ObjectStoreCommand::parse(b"PUT empty").unwrap()
+        );
+        assert_eq!(ObjectStoreCommand::Get { key: "a/b" }, ObjectStoreCommand::parse(b"get a/b").unwrap());
+        assert_eq!(
+            ObjectStoreCommand::Delete { key: "a/b" },
+            ObjectStoreCommand::parse(b"delete a/b\r\n").unwrap()
+        );
+        assert_eq!(ObjectStoreCommand::List { prefix: "" }, ObjectStoreCommand::parse(b"list").unwrap());
+        assert_eq!(ObjectStoreCommand::List { prefix: "a" }, ObjectStoreCommand::parse(b"list a").unwrap());
+        assert!(ObjectStoreCommand::parse(b"get").is_err());
+        assert!(ObjectStoreCommand::parse(b"copy a b").is_err());
+    }
+
+    #[test]
+    fn test_object_store_path() {
+        let base = object_store::path::Path::from("data/objects");
+        assert_eq!("data/objects/a/b", object_path(&base, "a/b").unwrap().as_ref());
+        assert_eq!("data/objects/a", object_path(&base, "/a/").unwrap().as_ref());
+        assert_eq!("data/objects", object_path(&base, "").unwrap().as_ref());
+        assert!(object_path(&base, "../secret").is_err());
+        assert!(object_path(&base, "a//b").is_err());
+        assert_eq!("a", object_path(&object_store::path::Path::default(), "a").unwrap().as_ref());
+        assert_eq!("a/b", object_key(&base, &object_path(&base, "a/b").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_object_store_local() {
+        let node_id = uuid::Uuid::new_v4();
+        let mut dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7090".to_string(), None).await;
+        let mut provider = ObjectStoreResourceProvider::new(
+            dataplane_provider.clone(),
+            Box::new(MockTelemetryHandle {}),
+            edgeless_api::function_instance::InstanceId::new(node_id),
+        )
+        .await;
+
+        let dir = std::env::temp_dir().join(format!("edgeless-object-store-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("inbox")).unwrap();
+        std::fs::write(dir.join("inbox/old.txt"), b"old").unwrap();
+        let url = format!("file://{}", dir.display());
+
+        let res = provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("object-store"),
+                configuration: std::collections::HashMap::from([(String::from("url"), String::from("not a url"))]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        assert!(matches!(res, edgeless_api::common::StartComponentResponse::ResponseError(_)));
+
+        let res = provider
+            .start(edgeless_api::resource_configuration::ResourceInstanceSpecification {
+                class_type: String::from("object-store"),
+                configuration: std::collections::HashMap::from([
+                    (String::from("url"), url),
+                    (String::from("watch_prefix"), String::from("inbox")),
+                    (String::from("watch_interval_ms"), String::from("50")),
+                ]),
+                workflow_id: String::from("workflow_1"),
+            })
+            .await
+            .unwrap();
+        let resource_id = match res {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+
+        // An object created before the output is patched is notified
+        // afterwards.
+        std::fs::write(dir.join("inbox/early.txt"), b"early").unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        let client_id = edgeless_api::function_instance::InstanceId::new(node_id);
+        let mut client = dataplane_provider.get_handle_for(client_id).await;
+        provider
+            .patch(edgeless_api::common::PatchRequest {
+                function_id: resource_id.function_id,
+                output_mapping: std::collections::HashMap::from([(String::from("new_object"), client_id)]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await
+            .unwrap();
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), client.receive_next())
+            .await
+            .expect("object created before patching not notified");
+        assert_eq!(edgeless_dataplane::core::Message::Cast(b"inbox/early.txt".to_vec()), event.message);
+
+        let metadata = edgeless_api::function_instance::EventMetadata::empty_new_root();
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Reply(vec![]),
+            client.call(resource_id, b"put inbox/new.bin\n\x00\x01\x02".to_vec(), &metadata).await
+        );
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Reply(b"\x00\x01\x02".to_vec()),
+            client.call(resource_id, b"get inbox/new.bin".to_vec(), &metadata).await
+        );
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::NoReply,
+            client.call(resource_id, b"get inbox/missing".to_vec(), &metadata).await
+        );
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Err,
+            client.call(resource_id, b"get ../outside".to_vec(), &metadata).await
+        );
+
+        // Only the new object is notified, not the one that existed before.
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), client.receive_next())
+            .await
+            .expect("new object not notified");
+        assert_eq!(resource_id, event.source_id);
+        assert_eq!(edgeless_dataplane::core::Message::Cast(b"inbox/new.bin".to_vec()), event.message);
+
+        match client.call(resource_id, b"list inbox".to_vec(), &metadata).await {
+            edgeless_dataplane::core::CallRet::Reply(reply) => {
+                let objects = serde_json::from_slice::<Vec<serde_json::Value>>(&reply).unwrap();
+                let mut keys = objects.iter().map(|x| x["key"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+                keys.sort();
+                assert_eq!(vec!["inbox/early.txt", "inbox/new.bin", "inbox/old.txt"], keys);
+            }
+            ret => panic!("unexpected reply to list: {:?}", ret),
+        }
+
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Reply(vec![]),
+            client.call(resource_id, b"delete inbox/new.bin".to_vec(), &metadata).await
+        );
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::NoReply,
+            client.call(resource_id, b"get inbox/new.bin".to_vec(), &metadata).await
+        );
+
+        provider.stop(resource_id).await.unwrap();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
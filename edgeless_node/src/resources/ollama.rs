@@ -114,7 +114,7 @@ impl OllamaResource {
 
                 // Ignore any non-cast messages.
                 let prompt = match message {
-                    edgeless_dataplane::core::Message::Cast(data) => data,
+                    edgeless_dataplane::core::Message::Cast(data) => String::from_utf8_lossy(&data).to_string(),
                     _ => {
                         continue;
                     }
@@ -135,7 +135,7 @@ impl OllamaResource {
                 match reply_receiver.await {
                     Ok(response) => match response {
                         Ok((target, response)) => {
-                            let _ = dataplane_handle.send(target, response, &metadata).await;
+                            let _ = dataplane_handle.send_output("out", target, response, &metadata).await;
                         }
                         Err(err) => {
                             log::warn!("Error from ollama: {}", err)
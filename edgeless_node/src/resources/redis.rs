@@ -4,7 +4,7 @@
 // SPDX-License-Identifier: MIT
 use edgeless_dataplane::core::Message;
 extern crate redis;
-use redis::Commands;
+use redis::AsyncCommands;
 
 pub struct RedisResourceSpec {}
 
@@ -14,15 +14,23 @@ impl super::resource_provider_specs::ResourceProviderSpecs for RedisResourceSpec
     }
 
     fn description(&self) -> String {
-        r"Perform SET and GET operations on a Redis server -- https://redis.io/
+        r#"Perform operations on a Redis server -- https://redis.io/
 
+With mode 'kv' (default):
 A SET operation is performed with a cast() on the key specified in the 'key' configuration parameter of the resource.
-A GET operation is performed with a call(), with the key specified in the message body."
+A GET operation is performed with a call(), with the key specified in the message body.
+
+With mode 'command':
+The message body is a JSON object with the command, the key, and the other arguments, e.g., {"cmd":"HSET","key":"sensors","args":["temp","21.5"]}.
+The command is executed with both cast() and call(), in the latter case the reply contains its result.
+
+With mode 'subscribe':
+The messages published on the channels specified in the 'channels' configuration parameter are cast on the 'new_message' output."#
             .to_string()
     }
 
     fn outputs(&self) -> Vec<String> {
-        vec![]
+        vec![String::from("new_message")]
     }
 
     fn configurations(&self) -> std::collections::HashMap<String, String> {
@@ -31,13 +39,151 @@ A GET operation is performed with a call(), with the key specified in the messag
             (String::from("key"), String::from("Key for SET operations (optional)")),
             (
                 String::from("add-workflow-id"),
-                String::from("If present, add the workflow identifier to the key"),
+                String::from("If present, add the workflow identifier to the keys and channels"),
+            ),
+            (
+                String::from("mode"),
+                String::from("Mode of operation of the resource. One of: kv, command, subscribe. Default: kv"),
+            ),
+            (
+                String::from("channels"),
+                String::from("Comma-separated list of channels to subscribe to in subscribe mode, which may contain glob-style patterns"),
             ),
         ])
     }
 
     fn version(&self) -> String {
-        String::from("1.2")
+        String::from("1.3")
+    }
+}
+
+/// Commands that can be executed in the command mode.
+/// The first argument of all of them is a key, or a channel for PUBLISH.
+const SUPPORTED_COMMANDS: [&str; 24] = [
+    "GET", "SET", "DEL", "EXISTS", "INCR", "INCRBY", "DECR", "EXPIRE", "TTL", "HSET", "HGET", "HDEL", "HGETALL", "LPUSH", "RPUSH", "LPOP", "RPOP",
+    "LLEN", "LRANGE", "XADD", "XLEN", "XRANGE", "XTRIM", "PUBLISH",
+];
+
+/// Interval between two attempts to subscribe again to the channels when the
+/// connection with the Redis server is lost.
+const RECONNECT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq)]
+enum RedisMode {
+    /// SET with cast() on the configured key, GET with call().
+    KeyValue,
+    /// Structured commands in the message body.
+    Command,
+    /// Subscription to the given channels.
+    Subscribe(Vec<String>),
+}
+
+impl RedisMode {
+    fn from_configuration(configuration: &std::collections::HashMap<String, String>) -> anyhow::Result<Self> {
+        match configuration.get("mode").map(|x| x.to_lowercase()).as_deref() {
+            None | Some("") | Some("kv") => Ok(RedisMode::KeyValue),
+            Some("command") => Ok(RedisMode::Command),
+            Some("subscribe") => {
+                let channels = configuration
+                    .get("channels")
+                    .unwrap_or(&String::default())
+                    .split(",")
+                    .map(|x| x.trim())
+                    .filter(|x| !x.is_empty())
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>();
+                if channels.is_empty() {
+                    anyhow::bail!("Missing field 'channels' in subscribe mode");
+                }
+                Ok(RedisMode::Subscribe(channels))
+            }
+            Some(mode) => anyhow::bail!("Invalid mode: {}", mode),
+        }
+    }
+}
+
+/// Structured command received in the command mode, e.g.:
+/// {"cmd":"XADD","key":"sensors","args":["*","temp","21.5"]}
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct RedisCommand {
+    pub cmd: String,
+    pub key: String,
+    #[serde(default)]
+    pub args: Vec<serde_json::Value>,
+}
+
+impl RedisCommand {
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let mut command: RedisCommand = serde_json::from_slice(data)?;
+        command.cmd = command.cmd.to_uppercase();
+        if !SUPPORTED_COMMANDS.contains(&command.cmd.as_str()) {
+            anyhow::bail!("Unsupported command: {}", command.cmd);
+        }
+        if command.key.is_empty() {
+            anyhow::bail!("Empty key in command {}", command.cmd);
+        }
+        Ok(command)
+    }
+
+    /// Execute the command on a connection, with the key prepended by the
+    /// given header.
+    async fn execute(&self, connection: &mut redis::aio::MultiplexedConnection, key_header: &str) -> anyhow::Result<redis::Value> {
+        let value: redis::Value = self
+            .to_cmd(key_header)?
+            .query_async(connection)
+            .await
+            .map_err(|err| anyhow::anyhow!("Could not execute command {} on key '{}': {}", self.cmd, self.key, err))?;
+        Ok(value)
+    }
+
+    /// Return the Redis command, with the key prepended by the given header.
+    fn to_cmd(&self, key_header: &str) -> anyhow::Result<redis::Cmd> {
+        let mut cmd = redis::cmd(&self.cmd);
+        cmd.arg(format!("{}{}", key_header, self.key));
+        for arg in &self.args {
+            match arg {
+                serde_json::Value::String(val) => cmd.arg(val),
+                serde_json::Value::Number(val) => cmd.arg(val.to_string()),
+                serde_json::Value::Bool(val) => cmd.arg(val.to_string()),
+                _ => anyhow::bail!("Invalid argument in command {}: {}", self.cmd, arg),
+            };
+        }
+        Ok(cmd)
+    }
+}
+
+/// Return the body of the reply to a command from the value returned by
+/// Redis: bulk strings are returned as raw bytes, integers and status
+/// messages as strings, aggregates as JSON.
+/// Return None if there is no value, e.g., with GET on a non-existing key.
+fn reply_body(value: redis::Value) -> Option<Vec<u8>> {
+    match value {
+        redis::Value::Nil => None,
+        redis::Value::Int(val) => Some(val.to_string().into_bytes()),
+        redis::Value::BulkString(val) => Some(val),
+        redis::Value::SimpleString(val) => Some(val.into_bytes()),
+        redis::Value::Okay => Some(b"OK".to_vec()),
+        value => Some(value_to_json(value).to_string().into_bytes()),
+    }
+}
+
+fn value_to_json(value: redis::Value) -> serde_json::Value {
+    match value {
+        redis::Value::Nil => serde_json::Value::Null,
+        redis::Value::Int(val) => serde_json::Value::from(val),
+        redis::Value::BulkString(val) => serde_json::Value::from(String::from_utf8_lossy(&val).to_string()),
+        redis::Value::SimpleString(val) => serde_json::Value::from(val),
+        redis::Value::Okay => serde_json::Value::from("OK"),
+        redis::Value::Boolean(val) => serde_json::Value::from(val),
+        redis::Value::Double(val) => serde_json::Value::from(val),
+        redis::Value::Array(values) | redis::Value::Set(values) => serde_json::Value::Array(values.into_iter().map(value_to_json).collect()),
+        redis::Value::Map(entries) => serde_json::Value::Array(
+            entries
+                .into_iter()
+                .map(|(k, v)| serde_json::Value::Array(vec![value_to_json(k), value_to_json(v)]))
+                .collect(),
+        ),
+        value => serde_json::Value::from(format!("{:?}", value)),
     }
 }
 
@@ -50,11 +196,12 @@ pub struct RedisResourceProviderInner {
     resource_provider_id: edgeless_api::function_instance::InstanceId,
     dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
     telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
-    instances: std::collections::HashMap<edgeless_api::function_instance::InstanceId, RedisResource>,
+    instances: std::collections::HashMap<edgeless_api::function_instance::ComponentId, RedisResource>,
 }
 
 pub struct RedisResource {
     join_handle: tokio::task::JoinHandle<()>,
+    target: Option<edgeless_api::function_instance::InstanceId>,
 }
 
 impl Drop for RedisResource {
@@ -65,33 +212,45 @@ impl Drop for RedisResource {
 
 /// The redis resource can be used to access a Redis KVS.
 /// Each resource instance has its own connection at the Redis URL specified
-/// in the resource configuration.
-/// The same resource can be used to GET or SET keys.
+/// in the resource configuration, which is multiplexed so that the commands
+/// do not block the runtime.
 ///
+/// In the kv mode, the same resource can be used to GET or SET keys.
 /// The GET operation is done on an arbitrary key that is specified as the
-/// message of the call() operation.
-///
+/// message of the call() operation. The value is returned as raw bytes.
 /// The SET operation is done via a cast() on the key, if specified in the
 /// resource configuration.
 ///
-/// Optionally, the key can be prepended by the workflow identifier and a
-/// semicolon.
+/// In the command mode, the message contains a `RedisCommand` serialized
+/// in JSON, which is executed both with cast() and call().
+///
+/// In the subscribe mode, the resource subscribes to the channels in the
+/// resource configuration and casts the payload of every message published
+/// to the output channel.
+///
+/// Optionally, the keys and channels can be prepended by the workflow
+/// identifier and a semicolon.
 impl RedisResource {
+    #[allow(clippy::too_many_arguments)]
     async fn new(
         dataplane_handle: edgeless_dataplane::handle::DataplaneHandle,
         telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
         redis_url: &str,
         redis_key: Option<&String>,
         workflow_id: Option<String>,
+        mode: RedisMode,
+        self_function_id: edgeless_api::function_instance::ComponentId,
+        inner: std::sync::Arc<tokio::sync::Mutex<RedisResourceProviderInner>>,
     ) -> anyhow::Result<Self> {
         let mut dataplane_handle = dataplane_handle;
         let mut telemetry_handle = telemetry_handle;
 
         log::info!(
-            "RedisResource created, url {}, key {:?}, workflow_id {:?}",
+            "RedisResource created, url {}, key {:?}, workflow_id {:?}, mode {:?}",
             redis_url,
             redis_key,
-            workflow_id
+            workflow_id,
+            mode
         );
 
         let workflow_id_header = if let Some(workflow_id) = workflow_id {
@@ -101,7 +260,60 @@ impl RedisResource {
         };
         let redis_key = redis_key.cloned().map(|k| format!("{}{}", workflow_id_header, k));
 
-        let mut connection = redis::Client::open(redis_url)?.get_connection()?;
+        let client = redis::Client::open(redis_url)?;
+
+        if let RedisMode::Subscribe(channels) = &mode {
+            let channels = channels
+                .iter()
+                .map(|channel| format!("{}{}", workflow_id_header, channel))
+                .collect::<Vec<String>>();
+            let mut pubsub = Some(subscribe(&client, &channels).await?);
+
+            let handle = tokio::spawn(async move {
+                loop {
+                    let mut cur_pubsub = match pubsub.take() {
+                        Some(pubsub) => pubsub,
+                        None => match subscribe(&client, &channels).await {
+                            Ok(pubsub) => pubsub,
+                            Err(err) => {
+                                log::warn!("Could not subscribe to Redis channels {:?}: {}", channels, err);
+                                tokio::time::sleep(RECONNECT_INTERVAL).await;
+                                continue;
+                            }
+                        },
+                    };
+
+                    let mut messages = cur_pubsub.on_message();
+                    while let Some(msg) = futures::StreamExt::next(&mut messages).await {
+                        let started = chrono::Utc::now();
+
+                        let target = inner.lock().await.instances.get(&self_function_id).and_then(|instance| instance.target);
+                        if let Some(target) = target {
+                            dataplane_handle
+                                .send_output(
+                                    "new_message",
+                                    target,
+                                    msg.get_payload_bytes().to_vec(),
+                                    &edgeless_api::function_instance::EventMetadata::empty_new_root(),
+                                )
+                                .await;
+                        }
+
+                        crate::resources::observe_execution(started, &mut telemetry_handle, false);
+                    }
+
+                    log::warn!("Connection lost with the Redis server, subscribing again to channels {:?}", channels);
+                    tokio::time::sleep(RECONNECT_INTERVAL).await;
+                }
+            });
+
+            return Ok(Self {
+                join_handle: handle,
+                target: None,
+            });
+        }
+
+        let mut connection = client.get_multiplexed_async_connection().await?;
 
         let handle = tokio::spawn(async move {
             loop {
@@ -122,10 +334,28 @@ impl RedisResource {
                     }
                 };
 
-                if get_operation {
+                if mode == RedisMode::Command {
+                    let res = match RedisCommand::parse(&message_data) {
+                        Ok(command) => command.execute(&mut connection, &workflow_id_header).await,
+                        Err(err) => Err(err),
+                    };
+                    let ret = match res {
+                        Ok(value) => match reply_body(value) {
+                            Some(body) => edgeless_dataplane::core::CallRet::Reply(body),
+                            None => edgeless_dataplane::core::CallRet::NoReply,
+                        },
+                        Err(err) => {
+                            log::error!("Invalid command on redis resource: {}", err);
+                            edgeless_dataplane::core::CallRet::Err
+                        }
+                    };
+                    if get_operation {
+                        dataplane_handle.reply(source_id, channel_id, ret, &metadata).await;
+                    }
+                } else if get_operation {
                     // GET
-                    let redis_key = format!("{}{}", workflow_id_header, message_data);
-                    match connection.get::<&str, std::string::String>(&redis_key) {
+                    let redis_key = format!("{}{}", workflow_id_header, String::from_utf8_lossy(&message_data));
+                    match connection.get::<&str, Vec<u8>>(&redis_key).await {
                         Ok(res) => {
                             dataplane_handle
                                 .reply(source_id, channel_id, edgeless_dataplane::core::CallRet::Reply(res), &metadata)
@@ -141,11 +371,11 @@ impl RedisResource {
                 } else {
                     // SET
                     if let Some(redis_key) = &redis_key {
-                        if let Err(err) = connection.set::<&str, &str, std::string::String>(redis_key, &message_data) {
+                        if let Err(err) = connection.set::<&str, &[u8], std::string::String>(redis_key, &message_data).await {
                             log::error!(
-                                "Could not set key '{}' to value '{}' via redis resource: {}",
+                                "Could not set key '{}' to a value of {} bytes via redis resource: {}",
                                 redis_key,
-                                &message_data,
+                                message_data.len(),
                                 err
                             );
                         }
@@ -158,10 +388,27 @@ impl RedisResource {
             }
         });
 
-        Ok(Self { join_handle: handle })
+        Ok(Self {
+            join_handle: handle,
+            target: None,
+        })
     }
 }
 
+/// Subscribe to the given channels, using pattern subscriptions for those
+/// that contain glob-style special characters.
+async fn subscribe(client: &redis::Client, channels: &[String]) -> anyhow::Result<redis::aio::PubSub> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    for channel in channels {
+        if channel.contains(['*', '?', '[']) {
+            pubsub.psubscribe(channel).await?;
+        } else {
+            pubsub.subscribe(channel).await?;
+        }
+    }
+    Ok(pubsub)
+}
+
 impl RedisResourceProvider {
     pub async fn new(
         dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
@@ -173,7 +420,7 @@ impl RedisResourceProvider {
                 resource_provider_id,
                 dataplane_provider,
                 telemetry_handle,
-                instances: std::collections::HashMap::<edgeless_api::function_instance::InstanceId, RedisResource>::new(),
+                instances: std::collections::HashMap::<edgeless_api::function_instance::ComponentId, RedisResource>::new(),
             })),
         }
     }
@@ -186,6 +433,18 @@ impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api
         instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
     ) -> anyhow::Result<edgeless_api::common::StartComponentResponse<edgeless_api::function_instance::InstanceId>> {
         if let Some(url) = instance_specification.configuration.get("url") {
+            let mode = match RedisMode::from_configuration(&instance_specification.configuration) {
+                Ok(mode) => mode,
+                Err(err) => {
+                    return Ok(edgeless_api::common::StartComponentResponse::ResponseError(
+                        edgeless_api::common::ResponseError {
+                            summary: "Invalid resource configuration".to_string(),
+                            detail: Some(err.to_string()),
+                        },
+                    ));
+                }
+            };
+
             let mut lck = self.inner.lock().await;
             let new_id = edgeless_api::function_instance::InstanceId::new(lck.resource_provider_id.node_id);
             let dataplane_handle = lck.dataplane_provider.get_handle_for(new_id).await;
@@ -203,11 +462,14 @@ impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api
                     .configuration
                     .contains_key("add-workflow-id")
                     .then_some(instance_specification.workflow_id),
+                mode,
+                new_id.function_id,
+                self.inner.clone(),
             )
             .await
             {
                 Ok(resource) => {
-                    lck.instances.insert(new_id, resource);
+                    lck.instances.insert(new_id.function_id, resource);
                     return Ok(edgeless_api::common::StartComponentResponse::InstanceId(new_id));
                 }
                 Err(err) => {
@@ -230,12 +492,96 @@ impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api
     }
 
     async fn stop(&mut self, resource_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
-        self.inner.lock().await.instances.remove(&resource_id);
+        self.inner.lock().await.instances.remove(&resource_id.function_id);
         Ok(())
     }
 
-    async fn patch(&mut self, _update: edgeless_api::common::PatchRequest) -> anyhow::Result<()> {
-        // the resource has no channels: nothing to be patched
+    async fn patch(&mut self, update: edgeless_api::common::PatchRequest) -> anyhow::Result<()> {
+        // only the subscribe mode uses the output channel
+        let mut lck = self.inner.lock().await;
+        if let Some(instance) = lck.instances.get_mut(&update.function_id) {
+            instance.target = update.output_mapping.get("new_message").copied();
+        } else {
+            anyhow::bail!("Patching a non-existing resource: {}", update.function_id);
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_redis_mode_from_configuration() {
+        let configuration = |entries: Vec<(&str, &str)>| -> std::collections::HashMap<String, String> {
+            entries.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+        };
+        assert_eq!(RedisMode::KeyValue, RedisMode::from_configuration(&configuration(vec![])).unwrap());
+        assert_eq!(
+            RedisMode::KeyValue,
+            RedisMode::from_configuration(&configuration(vec![("mode", "kv")])).unwrap()
+        );
+        assert_eq!(
+            RedisMode::Command,
+            RedisMode::from_configuration(&configuration(vec![("mode", "Command")])).unwrap()
+        );
+        assert_eq!(
+            RedisMode::Subscribe(vec!["alerts".to_string(), "sensors.*".to_string()]),
+            RedisMode::from_configuration(&configuration(vec![("mode", "subscribe"), ("channels", "alerts, sensors.*,")])).unwrap()
+        );
+        assert!(RedisMode::from_configuration(&configuration(vec![("mode", "subscribe")])).is_err());
+        assert!(RedisMode::from_configuration(&configuration(vec![("mode", "unknown")])).is_err());
+    }
+
+    #[test]
+    fn test_redis_command() {
+        let command = RedisCommand::parse(br#"{"cmd":"xadd","key":"sensors","args":["*","temp",21.5,"valid",true]}"#).unwrap();
+        assert_eq!("XADD", command.cmd);
+        assert_eq!(
+            redis::cmd("XADD")
+                .arg("wf:sensors")
+                .arg("*")
+                .arg("temp")
+                .arg("21.5")
+                .arg("valid")
+                .arg("true")
+                .get_packed_command(),
+            command.to_cmd("wf:").unwrap().get_packed_command()
+        );
+
+        let command = RedisCommand::parse(br#"{"cmd":"INCR","key":"counter"}"#).unwrap();
+        assert_eq!(
+            redis::cmd("INCR").arg("counter").get_packed_command(),
+            command.to_cmd("").unwrap().get_packed_command()
+        );
+
+        assert!(RedisCommand::parse(br#"{"cmd":"FLUSHALL","key":"counter"}"#).is_err());
+        assert!(RedisCommand::parse(br#"{"cmd":"GET","key":""}"#).is_err());
+        assert!(RedisCommand::parse(b"counter").is_err());
+        assert!(
+            RedisCommand::parse(br#"{"cmd":"HSET","key":"h","args":[["f","v"]]}"#)
+                .unwrap()
+                .to_cmd("")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_redis_reply_body() {
+        assert_eq!(None, reply_body(redis::Value::Nil));
+        assert_eq!(Some(b"42".to_vec()), reply_body(redis::Value::Int(42)));
+        assert_eq!(Some(b"\x00\x01".to_vec()), reply_body(redis::Value::BulkString(vec![0, 1])));
+        assert_eq!(Some(b"OK".to_vec()), reply_body(redis::Value::Okay));
+        assert_eq!(
+            Some(br#"["f1","v1",null,3]"#.to_vec()),
+            reply_body(redis::Value::Array(vec![
+                redis::Value::BulkString(b"f1".to_vec()),
+                redis::Value::BulkString(b"v1".to_vec()),
+                redis::Value::Nil,
+                redis::Value::Int(3)
+            ]))
+        );
+    }
+}
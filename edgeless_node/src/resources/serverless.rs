@@ -44,11 +44,14 @@ pub struct ServerlessResourceProvider {
     inner: std::sync::Arc<tokio::sync::Mutex<ServerlessResourceProviderInner>>,
 }
 
+// output channel and target of the response, if any, and the response
+type CallResponse = anyhow::Result<(Option<(&'static str, edgeless_api::function_instance::InstanceId)>, Vec<u8>)>;
+
 #[derive(Debug)]
 struct CallCommand {
-    msg: String,
+    msg: Vec<u8>,
     resource_id: edgeless_api::function_instance::ComponentId,
-    reply_sender: tokio::sync::oneshot::Sender<anyhow::Result<(Option<edgeless_api::function_instance::InstanceId>, String)>>,
+    reply_sender: tokio::sync::oneshot::Sender<CallResponse>,
 }
 
 enum ServerlessCommand {
@@ -113,8 +116,7 @@ impl ServerlessResource {
                     }
                 };
 
-                let (reply_sender, reply_receiver) =
-                    tokio::sync::oneshot::channel::<anyhow::Result<(Option<edgeless_api::function_instance::InstanceId>, String)>>();
+                let (reply_sender, reply_receiver) = tokio::sync::oneshot::channel::<CallResponse>();
                 let _ = sender
                     .send(ServerlessCommand::Call(CallCommand {
                         msg,
@@ -126,8 +128,8 @@ impl ServerlessResource {
                 match reply_receiver.await {
                     Ok(response) => match response {
                         Ok((target, response)) => {
-                            if let Some(target) = target {
-                                let _ = dataplane_handle.send(target, response, &metadata).await;
+                            if let Some((channel, target)) = target {
+                                let _ = dataplane_handle.send_output(channel, target, response, &metadata).await;
                             }
                         }
                         Err(err) => {
@@ -175,20 +177,21 @@ impl ServerlessResourceProvider {
             while let Some(command) = receiver.next().await {
                 match command {
                     ServerlessCommand::Call(cmd) => {
-                        let target_out = targets_out.get(&cmd.resource_id).cloned();
-                        let target_err = targets_err.get(&cmd.resource_id).cloned();
+                        let target_out = targets_out.get(&cmd.resource_id).copied().map(|target| ("out", target));
+                        let target_err = targets_err.get(&cmd.resource_id).copied().map(|target| ("err", target));
                         let client = client.request(reqwest::Method::POST, function_url.clone()).body(cmd.msg);
                         let response = match client.send().await {
                             Ok(ret) => {
                                 if ret.status() == reqwest::StatusCode::OK {
-                                    match ret.text().await {
-                                        Ok(body) => Ok((target_out, body)),
+                                    match ret.bytes().await {
+                                        Ok(body) => Ok((target_out, body.to_vec())),
                                         Err(err) => Ok((
                                             target_err,
                                             format!(
                                                 "error when calling serverless function at {} for resource {}: {}",
                                                 function_url, cmd.resource_id, err,
-                                            ),
+                                            )
+                                            .into_bytes(),
                                         )),
                                     }
                                 } else {
@@ -199,7 +202,8 @@ impl ServerlessResourceProvider {
                                             function_url,
                                             cmd.resource_id,
                                             ret.status()
-                                        ),
+                                        )
+                                        .into_bytes(),
                                     ))
                                 }
                             }
@@ -208,7 +212,8 @@ impl ServerlessResourceProvider {
                                 format!(
                                     "error when calling serverless function at {} for resource {}: {}",
                                     function_url, cmd.resource_id, err,
-                                ),
+                                )
+                                .into_bytes(),
                             )),
                         };
 
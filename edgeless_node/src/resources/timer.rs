@@ -0,0 +1,378 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use rand::Rng;
+use std::str::FromStr;
+
+pub struct TimerResourceSpec {}
+
+impl super::resource_provider_specs::ResourceProviderSpecs for TimerResourceSpec {
+    fn class_type(&self) -> String {
+        String::from("timer")
+    }
+
+    fn description(&self) -> String {
+        r"Emit events periodically, at fixed intervals or according to a cron expression
+
+The firing times do not depend on when the resource is started, so that the schedule is not altered if the resource is migrated."
+            .to_string()
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("out")]
+    }
+
+    fn configurations(&self) -> std::collections::HashMap<String, String> {
+        std::collections::HashMap::from([
+            (
+                String::from("interval_ms"),
+                String::from("Interval between consecutive events, in ms, aligned to the UNIX epoch"),
+            ),
+            (
+                String::from("cron"),
+                String::from(
+                    "Cron expression in UTC, with optional seconds as first field, e.g., */5 * * * *, used if 'interval_ms' is not specified",
+                ),
+            ),
+            (
+                String::from("jitter_ms"),
+                String::from("Maximum random delay added to each firing time, in ms. Default: 0"),
+            ),
+            (String::from("payload"), String::from("Payload of the events emitted. Default: empty")),
+        ])
+    }
+
+    fn version(&self) -> String {
+        String::from("1.0")
+    }
+}
+
+/// Schedule of the firing times of a timer.
+#[derive(Debug)]
+pub enum TimerSchedule {
+    /// Fixed interval, with firing times multiple of the interval since the UNIX epoch.
+    Interval(chrono::TimeDelta),
+    /// Firing times matching a cron expression.
+    Cron(Box<cron::Schedule>),
+}
+
+impl TimerSchedule {
+    /// Parse the schedule from the resource configuration.
+    pub fn from_configuration(configuration: &std::collections::HashMap<String, String>) -> anyhow::Result<Self> {
+        if let Some(interval_ms) = configuration.get("interval_ms") {
+            let interval_ms = interval_ms.parse::<u64>()?;
+            anyhow::ensure!(interval_ms > 0, "The interval must be positive");
+            Ok(Self::Interval(chrono::TimeDelta::milliseconds(i64::try_from(interval_ms)?)))
+        } else if let Some(expression) = configuration.get("cron") {
+            // Cron expressions without seconds fire at the start of the minute.
+            let expression = match expression.split_whitespace().count() {
+                5 => format!("0 {}", expression),
+                _ => expression.clone(),
+            };
+            Ok(Self::Cron(Box::new(cron::Schedule::from_str(&expression)?)))
+        } else {
+            anyhow::bail!("Missing field 'interval_ms' or 'cron'")
+        }
+    }
+
+    /// Return the first firing time strictly after the given time.
+    pub fn next_after(&self, time: chrono::DateTime<chrono::Utc>) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            Self::Interval(interval) => {
+                let interval = interval.num_milliseconds();
+                let elapsed = time.timestamp_millis();
+                chrono::DateTime::from_timestamp_millis((elapsed.div_euclid(interval) + 1) * interval)
+            }
+            Self::Cron(schedule) => schedule.after(&time).next(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct TimerResourceProvider {
+    inner: std::sync::Arc<tokio::sync::Mutex<TimerResourceProviderInner>>,
+}
+
+pub struct TimerResourceProviderInner {
+    resource_provider_id: edgeless_api::function_instance::InstanceId,
+    dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+    telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+    instances: std::collections::HashMap<edgeless_api::function_instance::ComponentId, TimerResource>,
+}
+
+pub struct TimerResource {
+    join_handle: tokio::task::JoinHandle<()>,
+    target: Option<edgeless_api::function_instance::InstanceId>,
+}
+
+impl Drop for TimerResource {
+    fn drop(&mut self) {
+        self.join_handle.abort();
+    }
+}
+
+/// The timer resource casts an event with a fixed payload to its output at
+/// the firing times of its schedule, each delayed by a random jitter.
+/// The next firing time is computed from the wall clock when the resource
+/// starts, thus a resource migrated to another node continues with the same
+/// schedule, without firing immediately.
+impl TimerResource {
+    async fn new(
+        dataplane_handle: edgeless_dataplane::handle::DataplaneHandle,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        schedule: TimerSchedule,
+        jitter: std::time::Duration,
+        payload: Vec<u8>,
+        self_function_id: edgeless_api::function_instance::ComponentId,
+        inner: std::sync::Arc<tokio::sync::Mutex<TimerResourceProviderInner>>,
+    ) -> Self {
+        let mut dataplane_handle = dataplane_handle;
+        let mut telemetry_handle = telemetry_handle;
+
+        let handle = tokio::spawn(async move {
+            let mut last = chrono::Utc::now();
+            loop {
+                let next = match schedule.next_after(last) {
+                    Some(next) => next,
+                    None => {
+                        log::info!("No more firing times for timer {}", self_function_id);
+                        break;
+                    }
+                };
+                last = next;
+
+                let jitter = match jitter.is_zero() {
+                    true => std::time::Duration::ZERO,
+                    false => rand::thread_rng().gen_range(std::time::Duration::ZERO..=jitter),
+                };
+                let delay = (next - chrono::Utc::now()).to_std().unwrap_or(std::time::Duration::ZERO);
+                tokio::time::sleep(delay + jitter).await;
+
+                let started = chrono::Utc::now();
+                let target = inner.lock().await.instances.get(&self_function_id).and_then(|instance| instance.target);
+                if let Some(target) = target {
+                    dataplane_handle
+                        .send_output(
+                            "out",
+                            target,
+                            payload.clone(),
+                            &edgeless_api::function_instance::EventMetadata::empty_new_root(),
+                        )
+                        .await;
+                }
+                crate::resources::observe_execution(started, &mut telemetry_handle, false);
+            }
+        });
+
+        Self {
+            join_handle: handle,
+            target: None,
+        }
+    }
+}
+
+impl TimerResourceProvider {
+    pub async fn new(
+        dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+        telemetry_handle: Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI>,
+        resource_provider_id: edgeless_api::function_instance::InstanceId,
+    ) -> Self {
+        Self {
+            inner: std::sync::Arc::new(tokio::sync::Mutex::new(TimerResourceProviderInner {
+                resource_provider_id,
+                dataplane_provider,
+                telemetry_handle,
+                instances: std::collections::HashMap::new(),
+            })),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId> for TimerResourceProvider {
+    async fn start(
+        &mut self,
+        instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
+    ) -> anyhow::Result<edgeless_api::common::StartComponentResponse<edgeless_api::function_instance::InstanceId>> {
+        let configuration = &instance_specification.configuration;
+        let res = (|| {
+            let schedule = TimerSchedule::from_configuration(configuration)?;
+            let jitter_ms = match configuration.get("jitter_ms") {
+                Some(val) => val.parse::<u64>()?,
+                None => 0,
+            };
+            Ok::<_, anyhow::Error>((schedule, std::time::Duration::from_millis(jitter_ms)))
+        })();
+        let (schedule, jitter) = match res {
+            Ok(res) => res,
+            Err(err) => {
+                return Ok(edgeless_api::common::StartComponentResponse::ResponseError(
+                    edgeless_api::common::ResponseError {
+                        summary: "Invalid resource configuration".to_string(),
+                        detail: Some(err.to_string()),
+                    },
+                ));
+            }
+        };
+        let payload = configuration.get("payload").cloned().unwrap_or_default().into_bytes();
+
+        let mut lck = self.inner.lock().await;
+        let new_id = edgeless_api::function_instance::InstanceId::new(lck.resource_provider_id.node_id);
+        let dataplane_handle = lck.dataplane_provider.get_handle_for(new_id).await;
+        let telemetry_handle = lck.telemetry_handle.fork(std::collections::BTreeMap::from([(
+            "FUNCTION_ID".to_string(),
+            new_id.function_id.to_string(),
+        )]));
+
+        log::info!("TimerResource created, schedule {:?}, jitter {:?}", schedule, jitter);
+        lck.instances.insert(
+            new_id.function_id,
+            TimerResource::new(
+                dataplane_handle,
+                telemetry_handle,
+                schedule,
+                jitter,
+                payload,
+                new_id.function_id,
+                self.inner.clone(),
+            )
+            .await,
+        );
+
+        Ok(edgeless_api::common::StartComponentResponse::InstanceId(new_id))
+    }
+
+    async fn stop(&mut self, resource_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
+        self.inner.lock().await.instances.remove(&resource_id.function_id);
+        Ok(())
+    }
+
+    async fn patch(&mut self, update: edgeless_api::common::PatchRequest) -> anyhow::Result<()> {
+        let mut lck = self.inner.lock().await;
+        if let Some(instance) = lck.instances.get_mut(&update.function_id) {
+            instance.target = update.output_mapping.get("out").copied();
+        } else {
+            anyhow::bail!("Patching a non-existing resource: {}", update.function_id);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use edgeless_api::resource_configuration::ResourceConfigurationAPI;
+
+    struct MockTelemetryHandle {}
+
+    impl edgeless_telemetry::telemetry_events::TelemetryHandleAPI for MockTelemetryHandle {
+        fn observe(&mut self, _event: edgeless_telemetry::telemetry_events::TelemetryEvent, _event_tags: std::collections::BTreeMap<String, String>) {
+        }
+        fn fork(
+            &mut self,
+            _child_tags: std::collections::BTreeMap<String, String>,
+        ) -> Box<dyn edgeless_telemetry::telemetry_events::TelemetryHandleAPI> {
+            Box::new(MockTelemetryHandle {})
+        }
+    }
+
+    fn configuration(entries: Vec<(&str, &str)>) -> std::collections::HashMap<String, String> {
+        entries.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn utc(timestamp: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(timestamp).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_timer_schedule() {
+        // Intervals are aligned to the UNIX epoch.
+        let schedule = TimerSchedule::from_configuration(&configuration(vec![("interval_ms", "60000")])).unwrap();
+        assert_eq!(Some(utc("2025-01-01T10:01:00Z")), schedule.next_after(utc("2025-01-01T10:00:00Z")));
+        assert_eq!(Some(utc("2025-01-01T10:01:00Z")), schedule.next_after(utc("2025-01-01T10:00:59.999Z")));
+
+        // The interval has priority over the cron expression.
+        let schedule = TimerSchedule::from_configuration(&configuration(vec![("interval_ms", "1000"), ("cron", "0 0 * * *")])).unwrap();
+        assert_eq!(Some(utc("2025-01-01T10:00:01Z")), schedule.next_after(utc("2025-01-01T10:00:00.5Z")));
+
+        // Cron expressions with and without seconds.
+        let schedule = TimerSchedule::from_configuration(&configuration(vec![("cron", "*/15 * * * *")])).unwrap();
+        assert_eq!(Some(utc("2025-01-01T10:15:00Z")), schedule.next_after(utc("2025-01-01T10:00:00Z")));
+        let schedule = TimerSchedule::from_configuration(&configuration(vec![("cron", "30 0 12 * * *")])).unwrap();
+        assert_eq!(Some(utc("2025-01-02T12:00:30Z")), schedule.next_after(utc("2025-01-01T13:00:00Z")));
+
+        for entries in [
+            vec![],
+            vec![("interval_ms", "0")],
+            vec![("interval_ms", "-1")],
+            vec![("interval_ms", "one")],
+            vec![("cron", "* * *")],
+            vec![("cron", "61 * * * *")],
+        ] {
+            assert!(
+                TimerSchedule::from_configuration(&configuration(entries.clone())).is_err(),
+                "{:?}",
+                entries
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timer_resource() {
+        let node_id = uuid::Uuid::new_v4();
+        let mut dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7088".to_string(), None).await;
+        let mut provider = TimerResourceProvider::new(
+            dataplane_provider.clone(),
+            Box::new(MockTelemetryHandle {}),
+            edgeless_api::function_instance::InstanceId::new(node_id),
+        )
+        .await;
+
+        let start = |entries: Vec<(&str, &str)>| edgeless_api::resource_configuration::ResourceInstanceSpecification {
+            class_type: String::from("timer"),
+            configuration: configuration(entries),
+            workflow_id: String::from("workflow_1"),
+        };
+
+        match provider.start(start(vec![("payload", "tick")])).await.unwrap() {
+            edgeless_api::common::StartComponentResponse::InstanceId(_) => panic!("timer started without a schedule"),
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => assert_eq!("Invalid resource configuration", err.summary),
+        }
+
+        let resource_id = match provider
+            .start(start(vec![("interval_ms", "100"), ("jitter_ms", "10"), ("payload", "tick")]))
+            .await
+            .unwrap()
+        {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+
+        let target = edgeless_api::function_instance::InstanceId::new(node_id);
+        let mut receiver = dataplane_provider.get_handle_for(target).await;
+        provider
+            .patch(edgeless_api::common::PatchRequest {
+                function_id: resource_id.function_id,
+                output_mapping: std::collections::HashMap::from([(String::from("out"), target)]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await
+            .unwrap();
+
+        for _ in 0..3 {
+            let event = tokio::time::timeout(std::time::Duration::from_secs(1), receiver.receive_next())
+                .await
+                .expect("no event received from the timer");
+            assert_eq!(resource_id, event.source_id);
+            assert_eq!(edgeless_dataplane::core::Message::Cast(b"tick".to_vec()), event.message);
+        }
+
+        // No more events after the resource is stopped.
+        provider.stop(resource_id).await.unwrap();
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(300), receiver.receive_next())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,688 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use edgeless_api::function_instance::ComponentId;
+use futures::{SinkExt, StreamExt};
+use std::str::FromStr;
+
+/// Maximum number of events queued for a session, above which the events
+/// are dropped for that session.
+const SESSION_QUEUE_SIZE: usize = 256;
+
+pub struct WsIngressResourceSpec {}
+
+impl super::resource_provider_specs::ResourceProviderSpecs for WsIngressResourceSpec {
+    fn class_type(&self) -> String {
+        String::from("ws-ingress")
+    }
+
+    fn description(&self) -> String {
+        r"Keep sessions open with external WebSocket and Server-Sent Events (SSE) clients
+
+The messages received from WebSocket clients are cast to the output channel.
+The events received by the resource are pushed to all the sessions, unless with_key is true: in this case the events must be in the form <key>\0<data> and data is pushed only to the session with that identifier or to the sessions subscribed to the topic key.
+Clients subscribe to topics in the URL query (?topic=<topic>, can be repeated).
+SSE clients are served with GET requests that accept text/event-stream."
+            .to_string()
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        vec![String::from("new_message")]
+    }
+
+    fn configurations(&self) -> std::collections::HashMap<String, String> {
+        std::collections::HashMap::from([
+            (
+                String::from("path"),
+                String::from("Path of the URL to which the clients connect, e.g., /dashboard. Default: /"),
+            ),
+            (
+                String::from("with_session_id"),
+                String::from(
+                    "If true, the messages received are cast to the output channel as <session_id>\\0<data>, so that they can be answered by pushing events with the session identifier as key. Default: false",
+                ),
+            ),
+            (
+                String::from("with_key"),
+                String::from(
+                    "If true, the events received must be in the form <key>\\0<data> and data is pushed only to the session with identifier key or to the sessions subscribed to the topic key, otherwise the events are pushed to all the sessions as they are. Default: false",
+                ),
+            ),
+        ])
+    }
+
+    fn version(&self) -> String {
+        String::from("1.0")
+    }
+}
+
+/// Event pushed to a session.
+#[derive(Debug, PartialEq)]
+struct Push {
+    /// Topic of the event, if any.
+    topic: Option<String>,
+    /// Content of the event.
+    data: Vec<u8>,
+}
+
+/// Session of a client connected to a resource.
+struct Session {
+    /// Topics to which the session is subscribed.
+    topics: std::collections::HashSet<String>,
+    /// Queue of the events to be pushed to the client.
+    sender: tokio::sync::mpsc::Sender<Push>,
+}
+
+impl Session {
+    /// Return true if an event with the given key must be pushed to the
+    /// session with the given identifier.
+    fn matches(&self, session_id: &str, key: Option<&str>) -> bool {
+        match key {
+            Some(key) => key == session_id || self.topics.contains(key),
+            None => true,
+        }
+    }
+}
+
+struct ResourceDesc {
+    path: String,
+    with_session_id: bool,
+    target: Option<edgeless_api::function_instance::InstanceId>,
+    dataplane_handle: edgeless_dataplane::handle::DataplaneHandle,
+    sessions: std::collections::HashMap<String, Session>,
+    join_handle: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for ResourceDesc {
+    fn drop(&mut self) {
+        self.join_handle.abort();
+    }
+}
+
+struct WsIngressState {
+    active_resources: std::collections::HashMap<ComponentId, ResourceDesc>,
+}
+
+/// Return the normalized path, i.e., beginning with `/` and without trailing `/`.
+fn normalize_path(path: &str) -> String {
+    let path = path.trim().trim_matches('/');
+    format!("/{}", path)
+}
+
+/// Return the topics in the query of a URL, i.e., the values of the
+/// `topic` parameters.
+fn query_topics(query: Option<&str>) -> std::collections::HashSet<String> {
+    query
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .filter(|(k, v)| *k == "topic" && !v.is_empty())
+        .map(|(_k, v)| v.to_string())
+        .collect()
+}
+
+/// Return an event encoded in the SSE format, with the topic as event type.
+fn sse_event(push: &Push) -> Vec<u8> {
+    let mut ret = String::new();
+    if let Some(topic) = &push.topic {
+        ret.push_str(&format!("event: {}\n", topic));
+    }
+    for line in String::from_utf8_lossy(&push.data).split('\n') {
+        ret.push_str(&format!("data: {}\n", line.trim_end_matches('\r')));
+    }
+    ret.push('\n');
+    ret.into_bytes()
+}
+
+type ResponseBody = http_body_util::combinators::BoxBody<hyper::body::Bytes, std::convert::Infallible>;
+
+/// Return a response with the given status and plain text body.
+fn plain_response(status: hyper::StatusCode, body: &'static str) -> hyper::Response<ResponseBody> {
+    let mut response = hyper::Response::new(http_body_util::BodyExt::boxed(http_body_util::Full::new(hyper::body::Bytes::from(body))));
+    *response.status_mut() = status;
+    response
+}
+
+#[derive(Clone)]
+struct WsIngressService {
+    state: std::sync::Arc<tokio::sync::Mutex<WsIngressState>>,
+}
+
+impl hyper::service::Service<hyper::Request<hyper::body::Incoming>> for WsIngressService {
+    type Response = hyper::Response<ResponseBody>;
+
+    type Error = anyhow::Error;
+
+    type Future = std::pin::Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn call(&self, req: hyper::Request<hyper::body::Incoming>) -> Self::Future {
+        let state = self.state.clone();
+        Box::pin(async move {
+            if req.method() != hyper::Method::GET {
+                return Ok(plain_response(hyper::StatusCode::METHOD_NOT_ALLOWED, "Method Not Allowed"));
+            }
+
+            let path = normalize_path(req.uri().path());
+            let topics = query_topics(req.uri().query());
+            let is_websocket = req
+                .headers()
+                .get(hyper::header::UPGRADE)
+                .and_then(|val| val.to_str().ok())
+                .is_some_and(|val| val.eq_ignore_ascii_case("websocket"));
+            let is_sse = req
+                .headers()
+                .get(hyper::header::ACCEPT)
+                .and_then(|val| val.to_str().ok())
+                .is_some_and(|val| val.contains("text/event-stream"));
+            if !is_websocket && !is_sse {
+                return Ok(plain_response(hyper::StatusCode::BAD_REQUEST, "Bad Request"));
+            }
+
+            // Register a new session with the resource serving the path.
+            let session_id = uuid::Uuid::new_v4().to_string();
+            let (sender, receiver) = tokio::sync::mpsc::channel(SESSION_QUEUE_SIZE);
+            let Some(resource_id) = state
+                .lock()
+                .await
+                .active_resources
+                .iter_mut()
+                .find(|(_id, desc)| desc.path == path)
+                .map(|(id, desc)| {
+                    desc.sessions.insert(session_id.clone(), Session { topics, sender });
+                    *id
+                })
+            else {
+                return Ok(plain_response(hyper::StatusCode::NOT_FOUND, "Not Found"));
+            };
+            log::info!(
+                "New {} session {} with ws-ingress {}",
+                if is_websocket { "WebSocket" } else { "SSE" },
+                session_id,
+                resource_id
+            );
+
+            if is_websocket {
+                let Some(key) = req.headers().get(hyper::header::SEC_WEBSOCKET_KEY) else {
+                    if let Some(desc) = state.lock().await.active_resources.get_mut(&resource_id) {
+                        desc.sessions.remove(&session_id);
+                    }
+                    return Ok(plain_response(hyper::StatusCode::BAD_REQUEST, "Bad Request"));
+                };
+                let accept = tokio_tungstenite::tungstenite::handshake::derive_accept_key(key.as_bytes());
+                tokio::spawn(websocket_session(req, state, resource_id, session_id, receiver));
+
+                let mut response = plain_response(hyper::StatusCode::SWITCHING_PROTOCOLS, "");
+                let headers = response.headers_mut();
+                headers.insert(hyper::header::UPGRADE, hyper::header::HeaderValue::from_static("websocket"));
+                headers.insert(hyper::header::CONNECTION, hyper::header::HeaderValue::from_static("Upgrade"));
+                headers.insert(hyper::header::SEC_WEBSOCKET_ACCEPT, hyper::header::HeaderValue::from_str(&accept)?);
+                return Ok(response);
+            }
+
+            // The SSE session ends when the stream is dropped by the
+            // server, upon client disconnection, after which the session
+            // is removed when the next event is pushed.
+            let stream = tokio_stream::wrappers::ReceiverStream::new(receiver)
+                .map(|push| Ok::<_, std::convert::Infallible>(hyper::body::Frame::data(hyper::body::Bytes::from(sse_event(&push)))));
+            let mut response = hyper::Response::new(http_body_util::BodyExt::boxed(http_body_util::StreamBody::new(stream)));
+            let headers = response.headers_mut();
+            headers.insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static("text/event-stream"));
+            headers.insert(hyper::header::CACHE_CONTROL, hyper::header::HeaderValue::from_static("no-cache"));
+            Ok(response)
+        })
+    }
+}
+
+/// Serve a WebSocket session, once the connection has been upgraded: the
+/// messages received are cast to the target of the resource and the events
+/// queued are sent to the client.
+async fn websocket_session(
+    req: hyper::Request<hyper::body::Incoming>,
+    state: std::sync::Arc<tokio::sync::Mutex<WsIngressState>>,
+    resource_id: ComponentId,
+    session_id: String,
+    mut receiver: tokio::sync::mpsc::Receiver<Push>,
+) {
+    let upgraded = match hyper::upgrade::on(req).await {
+        Ok(upgraded) => upgraded,
+        Err(err) => {
+            log::warn!("Error upgrading session {} to WebSocket: {}", session_id, err);
+            return;
+        }
+    };
+    let ws = tokio_tungstenite::WebSocketStream::from_raw_socket(
+        hyper_util::rt::TokioIo::new(upgraded),
+        tokio_tungstenite::tungstenite::protocol::Role::Server,
+        None,
+    )
+    .await;
+    let (mut ws_sender, mut ws_receiver) = ws.split();
+
+    loop {
+        tokio::select! {
+            push = receiver.recv() => {
+                let Some(push) = push else {
+                    break;
+                };
+                let msg = match String::from_utf8(push.data) {
+                    Ok(text) => tokio_tungstenite::tungstenite::Message::text(text),
+                    Err(err) => tokio_tungstenite::tungstenite::Message::binary(err.into_bytes()),
+                };
+                if ws_sender.send(msg).await.is_err() {
+                    break;
+                }
+            }
+            msg = ws_receiver.next() => {
+                let data = match msg {
+                    Some(Ok(tokio_tungstenite::tungstenite::Message::Text(text))) => text.as_bytes().to_vec(),
+                    Some(Ok(tokio_tungstenite::tungstenite::Message::Binary(data))) => data.to_vec(),
+                    Some(Ok(tokio_tungstenite::tungstenite::Message::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => {
+                        log::warn!("Error receiving from WebSocket session {}: {}", session_id, err);
+                        break;
+                    }
+                };
+
+                let mut lck = state.lock().await;
+                let Some(desc) = lck.active_resources.get_mut(&resource_id) else {
+                    break;
+                };
+                if let Some(target) = desc.target {
+                    let msg = match desc.with_session_id {
+                        true => crate::resources::encode_message(&session_id, &data),
+                        false => data,
+                    };
+                    let mut dataplane_handle = desc.dataplane_handle.clone();
+                    drop(lck);
+                    dataplane_handle
+                        .send_output("new_message", target, msg, &edgeless_api::function_instance::EventMetadata::empty_new_root())
+                        .await;
+                }
+            }
+        }
+    }
+
+    log::info!("WebSocket session {} with ws-ingress {} closed", session_id, resource_id);
+    if let Some(desc) = state.lock().await.active_resources.get_mut(&resource_id) {
+        desc.sessions.remove(&session_id);
+    }
+}
+
+/// Return the key, if any, and the data to be pushed from the payload of an
+/// event received by a resource, which must be in the form <key>\0<data>
+/// if `with_key` is true.
+fn split_event(with_key: bool, data: Vec<u8>) -> anyhow::Result<(Option<String>, Vec<u8>)> {
+    match with_key {
+        true => match crate::resources::decode_message(&data) {
+            Some((key, data)) => Ok((Some(key), data)),
+            None => anyhow::bail!("Event without a key"),
+        },
+        false => Ok((None, data)),
+    }
+}
+
+/// Push an event to the sessions of a resource, removing those closed.
+/// If a key is given, the event is pushed only to the session with that
+/// identifier or to the sessions subscribed to the topic key, otherwise it
+/// is pushed to all the sessions.
+/// Return the number of sessions to which the event has been pushed.
+fn push_event(desc: &mut ResourceDesc, key: Option<String>, data: Vec<u8>) -> usize {
+    let mut closed = vec![];
+    let mut pushed = 0;
+    for (session_id, session) in &desc.sessions {
+        if !session.matches(session_id, key.as_deref()) {
+            continue;
+        }
+        let push = Push {
+            topic: key.clone().filter(|key| session.topics.contains(key)),
+            data: data.clone(),
+        };
+        match session.sender.try_send(push) {
+            Ok(_) => pushed += 1,
+            Err(tokio::sync::mpsc::error::TrySendError::Full(_)) => log::warn!("Event dropped for slow session {}", session_id),
+            Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => closed.push(session_id.clone()),
+        }
+    }
+    for session_id in closed {
+        desc.sessions.remove(&session_id);
+    }
+    pushed
+}
+
+/// Start the web server of the ws-ingress resource provider.
+///
+/// * `dataplane_provider` - The data plane provider.
+/// * `ingress_id` - The identifier of the resource provider.
+/// * `ingress_url` - The URL of the web server.
+pub async fn ws_ingress_task(
+    dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+    ingress_id: edgeless_api::function_instance::InstanceId,
+    ingress_url: String,
+) -> Box<dyn edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId>> {
+    let (_proto, host, port) = edgeless_api::util::parse_http_host(&ingress_url).unwrap();
+    let addr = std::net::SocketAddr::from((std::net::IpAddr::from_str(&host).unwrap(), port));
+
+    let state = std::sync::Arc::new(tokio::sync::Mutex::new(WsIngressState {
+        active_resources: std::collections::HashMap::new(),
+    }));
+
+    let cloned_state = state.clone();
+    let _web_task: tokio::task::JoinHandle<anyhow::Result<()>> = tokio::spawn(async move {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(val) => val,
+                Err(_) => {
+                    log::error!("Accept Error");
+                    continue;
+                }
+            };
+            let service = WsIngressService { state: cloned_state.clone() };
+            tokio::task::spawn(async move {
+                if let Err(err) = hyper::server::conn::http1::Builder::new()
+                    .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                    .with_upgrades()
+                    .await
+                {
+                    log::warn!("Error serving connection: {:?}", err);
+                }
+            });
+        }
+    });
+
+    Box::new(WsIngressResourceProvider {
+        own_node_id: ingress_id.node_id,
+        dataplane_provider,
+        state,
+    })
+}
+
+#[derive(Clone)]
+struct WsIngressResourceProvider {
+    own_node_id: uuid::Uuid,
+    dataplane_provider: edgeless_dataplane::handle::DataplaneProvider,
+    state: std::sync::Arc<tokio::sync::Mutex<WsIngressState>>,
+}
+
+#[async_trait::async_trait]
+impl edgeless_api::resource_configuration::ResourceConfigurationAPI<edgeless_api::function_instance::InstanceId> for WsIngressResourceProvider {
+    async fn start(
+        &mut self,
+        instance_specification: edgeless_api::resource_configuration::ResourceInstanceSpecification,
+    ) -> anyhow::Result<edgeless_api::common::StartComponentResponse<edgeless_api::function_instance::InstanceId>> {
+        let path = normalize_path(instance_specification.configuration.get("path").unwrap_or(&String::default()));
+        let with_session_id = instance_specification
+            .configuration
+            .get("with_session_id")
+            .unwrap_or(&String::from("false"))
+            .eq_ignore_ascii_case("true");
+        let with_key = instance_specification
+            .configuration
+            .get("with_key")
+            .unwrap_or(&String::from("false"))
+            .eq_ignore_ascii_case("true");
+
+        let mut lck = self.state.lock().await;
+        if lck.active_resources.values().any(|desc| desc.path == path) {
+            return Ok(edgeless_api::common::StartComponentResponse::ResponseError(
+                edgeless_api::common::ResponseError {
+                    summary: "Invalid resource configuration".to_string(),
+                    detail: Some(format!("Path already in use by another ws-ingress resource: {}", path)),
+                },
+            ));
+        }
+
+        let resource_id = edgeless_api::function_instance::InstanceId::new(self.own_node_id);
+        let dataplane_handle = self.dataplane_provider.get_handle_for(resource_id).await;
+
+        // Push the events received to the sessions.
+        let mut receiver_handle = dataplane_handle.clone();
+        let state = self.state.clone();
+        let join_handle = tokio::spawn(async move {
+            loop {
+                let edgeless_dataplane::core::DataplaneEvent {
+                    source_id,
+                    channel_id,
+                    message,
+                    metadata,
+                    ..
+                } = receiver_handle.receive_next().await;
+                let (need_reply, data) = match message {
+                    edgeless_dataplane::core::Message::Call(data) => (true, data),
+                    edgeless_dataplane::core::Message::Cast(data) => (false, data),
+                    _ => continue,
+                };
+                let ret = match split_event(with_key, data) {
+                    Ok((key, data)) => {
+                        if let Some(desc) = state.lock().await.active_resources.get_mut(&resource_id.function_id) {
+                            let pushed = push_event(desc, key, data);
+                            log::debug!("Event pushed to {} sessions of ws-ingress {}", pushed, resource_id.function_id);
+                        }
+                        edgeless_dataplane::core::CallRet::Reply(vec![])
+                    }
+                    Err(err) => {
+                        log::warn!("Event not pushed to the sessions of ws-ingress {}: {}", resource_id.function_id, err);
+                        edgeless_dataplane::core::CallRet::Err
+                    }
+                };
+                if need_reply {
+                    receiver_handle.reply(source_id, channel_id, ret, &metadata).await;
+                }
+            }
+        });
+
+        log::info!(
+            "created a new ws-ingress resource: path '{}', with session id {}, with key {}",
+            path,
+            with_session_id,
+            with_key
+        );
+        lck.active_resources.insert(
+            resource_id.function_id,
+            ResourceDesc {
+                path,
+                with_session_id,
+                target: None, // will be set by patch()
+                dataplane_handle,
+                sessions: std::collections::HashMap::new(),
+                join_handle,
+            },
+        );
+        Ok(edgeless_api::common::StartComponentResponse::InstanceId(resource_id))
+    }
+
+    async fn stop(&mut self, resource_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
+        // Dropping the sessions closes the connections with the clients.
+        self.state.lock().await.active_resources.remove(&resource_id.function_id);
+        Ok(())
+    }
+
+    async fn patch(&mut self, update: edgeless_api::common::PatchRequest) -> anyhow::Result<()> {
+        let mut lck = self.state.lock().await;
+        let desc = lck.active_resources.get_mut(&update.function_id).ok_or(anyhow::anyhow!(
+            "Trying to patch a non-existing resource with PID '{}'",
+            update.function_id
+        ))?;
+        desc.target = update.output_mapping.get("new_message").copied();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_ws_ingress_helpers() {
+        assert_eq!("/", normalize_path(""));
+        assert_eq!("/", normalize_path("/"));
+        assert_eq!("/dashboard", normalize_path("dashboard/"));
+        assert_eq!("/a/b", normalize_path("/a/b"));
+
+        assert!(query_topics(None).is_empty());
+        assert_eq!(
+            std::collections::HashSet::from([String::from("a"), String::from("b")]),
+            query_topics(Some("topic=a&other=c&topic=b&topic="))
+        );
+
+        assert_eq!(
+            b"data: hello\n\n".to_vec(),
+            sse_event(&Push {
+                topic: None,
+                data: b"hello".to_vec()
+            })
+        );
+        assert_eq!(
+            b"event: temp\ndata: 1\ndata: 2\n\n".to_vec(),
+            sse_event(&Push {
+                topic: Some(String::from("temp")),
+                data: b"1\r\n2".to_vec()
+            })
+        );
+
+        let (sender, _receiver) = tokio::sync::mpsc::channel(1);
+        let session = Session {
+            topics: std::collections::HashSet::from([String::from("temp")]),
+            sender,
+        };
+        assert!(session.matches("s1", None));
+        assert!(session.matches("s1", Some("s1")));
+        assert!(session.matches("s1", Some("temp")));
+        assert!(!session.matches("s1", Some("s2")));
+        assert!(!session.matches("s1", Some("humidity")));
+
+        // NUL characters are not interpreted unless with_key is true.
+        assert_eq!((None, b"temp\x0021".to_vec()), split_event(false, b"temp\x0021".to_vec()).unwrap());
+        assert_eq!(
+            (Some(String::from("temp")), b"21".to_vec()),
+            split_event(true, b"temp\x0021".to_vec()).unwrap()
+        );
+        assert!(split_event(true, b"21".to_vec()).is_err());
+        assert!(split_event(true, b"\x0021".to_vec()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ws_ingress_sessions() {
+        let node_id = uuid::Uuid::new_v4();
+        let mut dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7087".to_string(), None).await;
+        let mut provider = ws_ingress_task(
+            dataplane_provider.clone(),
+            edgeless_api::function_instance::InstanceId::new(node_id),
+            String::from("http://127.0.0.1:7086"),
+        )
+        .await;
+
+        let start = |path: &str| edgeless_api::resource_configuration::ResourceInstanceSpecification {
+            class_type: String::from("ws-ingress"),
+            configuration: std::collections::HashMap::from([
+                (String::from("path"), path.to_string()),
+                (String::from("with_session_id"), String::from("true")),
+                (String::from("with_key"), String::from("true")),
+            ]),
+            workflow_id: String::from("workflow_1"),
+        };
+        let resource_id = match provider.start(start("/dashboard")).await.unwrap() {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+        assert!(matches!(
+            provider.start(start("dashboard")).await.unwrap(),
+            edgeless_api::common::StartComponentResponse::ResponseError(_)
+        ));
+
+        let client_id = edgeless_api::function_instance::InstanceId::new(node_id);
+        let mut client = dataplane_provider.get_handle_for(client_id).await;
+        provider
+            .patch(edgeless_api::common::PatchRequest {
+                function_id: resource_id.function_id,
+                output_mapping: std::collections::HashMap::from([(String::from("new_message"), client_id)]),
+                output_splits: std::collections::HashMap::new(),
+            })
+            .await
+            .unwrap();
+
+        // Wait for the web server to be ready, then open a WebSocket session
+        // subscribed to topics temp and all.
+        let mut ws = None;
+        for _ in 0..50 {
+            if let Ok((stream, _)) = tokio_tungstenite::connect_async("ws://127.0.0.1:7086/dashboard?topic=temp&topic=all").await {
+                ws = Some(stream);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        let mut ws = ws.expect("could not connect to the ws-ingress");
+
+        // Unknown paths and plain requests are rejected.
+        assert!(tokio_tungstenite::connect_async("ws://127.0.0.1:7086/other").await.is_err());
+
+        // Open an SSE session, subscribed to topic all.
+        let mut sse = tokio::net::TcpStream::connect("127.0.0.1:7086").await.unwrap();
+        sse.write_all(b"GET /dashboard?topic=all HTTP/1.1\r\nHost: 127.0.0.1\r\nAccept: text/event-stream\r\n\r\n")
+            .await
+            .unwrap();
+        let mut sse = tokio::io::BufReader::new(sse);
+        let mut sse_next_line = async || {
+            let mut line = String::new();
+            tokio::time::timeout(std::time::Duration::from_secs(1), sse.read_line(&mut line))
+                .await
+                .unwrap()
+                .unwrap();
+            line
+        };
+        assert!(sse_next_line().await.starts_with("HTTP/1.1 200"));
+        while sse_next_line().await != "\r\n" {}
+
+        // The messages from the WebSocket client are cast with the session identifier.
+        ws.send(tokio_tungstenite::tungstenite::Message::text("hello")).await.unwrap();
+        let event = tokio::time::timeout(std::time::Duration::from_secs(1), client.receive_next())
+            .await
+            .unwrap();
+        assert_eq!(resource_id, event.source_id);
+        let edgeless_dataplane::core::Message::Cast(data) = event.message else {
+            panic!("unexpected message: {:?}", event.message);
+        };
+        let (session_id, data) = crate::resources::decode_message(&data).unwrap();
+        assert_eq!(b"hello".to_vec(), data);
+
+        let mut ws_next = async || match tokio::time::timeout(std::time::Duration::from_secs(1), ws.next()).await.unwrap() {
+            Some(Ok(tokio_tungstenite::tungstenite::Message::Text(text))) => text.to_string(),
+            msg => panic!("unexpected message: {:?}", msg),
+        };
+        let metadata = edgeless_api::function_instance::EventMetadata::empty_new_root();
+
+        // Events addressed to the session are pushed only to it.
+        client
+            .send(resource_id, crate::resources::encode_message(&session_id, b"reply"), &metadata)
+            .await;
+        assert_eq!("reply", ws_next().await);
+
+        // Events with a topic are pushed only to the subscribers.
+        client
+            .send(resource_id, crate::resources::encode_message("humidity", b"60"), &metadata)
+            .await;
+        client.send(resource_id, crate::resources::encode_message("temp", b"21"), &metadata).await;
+        assert_eq!("21", ws_next().await);
+
+        // Events without a key are rejected.
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Err,
+            client.call(resource_id, b"nokey".to_vec(), &metadata).await
+        );
+        assert_eq!(
+            edgeless_dataplane::core::CallRet::Reply(vec![]),
+            client.call(resource_id, crate::resources::encode_message("all", b"all"), &metadata).await
+        );
+        assert_eq!("all", ws_next().await);
+        let mut sse_lines: Vec<String> = vec![];
+        while sse_lines.last().is_none_or(|line| !line.starts_with("data: ")) {
+            sse_lines.push(sse_next_line().await.trim_end().to_string());
+        }
+        assert!(sse_lines.contains(&String::from("event: all")));
+        assert_eq!(Some(&String::from("data: all")), sse_lines.last());
+
+        provider.stop(resource_id).await.unwrap();
+    }
+}
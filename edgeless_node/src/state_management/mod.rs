@@ -3,120 +3,664 @@
 // SPDX-License-Identifier: MIT
 #[derive(Clone)]
 pub struct StateManager {
-    handlers: std::sync::Arc<tokio::sync::Mutex<StateProviders>>,
+    handlers: std::sync::Arc<StateProviders>,
 }
 
+/// Serialized state of a function instance, with the version at which it
+/// was written.
+/// Versions are incremented by one at every write, starting from 1, while
+/// version 0 denotes a state that has never been written.
+#[derive(Debug, Clone, PartialEq)]
+struct VersionedState {
+    serialized_state: String,
+    version: u64,
+}
+
+/// Error returned when writing a state that has been modified by someone
+/// else since it was last read.
+#[derive(Debug)]
+pub struct StateConflict {
+    pub state_id: uuid::Uuid,
+    pub expected_version: u64,
+    pub found_version: u64,
+}
+
+impl std::fmt::Display for StateConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "state {} modified concurrently: expected version {}, found {}",
+            self.state_id, self.expected_version, self.found_version
+        )
+    }
+}
+
+impl std::error::Error for StateConflict {}
+
+/// The providers are shared by all the state handles of a node, hence they
+/// must not serialize the accesses to different states.
 #[async_trait::async_trait]
 trait StateProvider: Sync + Send {
-    fn get(&mut self, state_id: uuid::Uuid) -> Option<String>;
-    fn set(&mut self, state_id: uuid::Uuid, serialized_state: String);
+    /// Return the current state, if any.
+    async fn get(&self, state_id: uuid::Uuid) -> anyhow::Result<Option<VersionedState>>;
+
+    /// Write the state only if its current version is `expected_version`,
+    /// which prevents concurrent writers from overwriting each other,
+    /// otherwise fail with `StateConflict`.
+    /// Return the new version.
+    async fn set(&self, state_id: uuid::Uuid, serialized_state: String, expected_version: u64) -> anyhow::Result<u64>;
 }
 
+/// State provider that saves the state of every function instance in a
+/// separate file, whose first line contains the version.
+///
+/// The directory can be shared among multiple nodes (e.g., via NFS),
+/// since the read-modify-write cycles are serialized with a lock file.
+///
+/// The file operations are blocking, hence they are run on the threads
+/// dedicated to blocking tasks.
+#[derive(Clone)]
 struct FileStateProvider {
     base_path: std::path::PathBuf,
 }
 
+/// Lock on the state of a function instance, released when dropped.
+struct FileStateLock {
+    lock_file: std::path::PathBuf,
+    /// Unique content of the lock file, which identifies its owner.
+    token: String,
+}
+
+impl FileStateLock {
+    /// Age after which a lock file is considered left behind by a node that
+    /// crashed while holding it, and is removed.
+    const STALE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+    /// Maximum time waiting for a lock, which is longer than
+    /// `STALE_TIMEOUT` so that a lock left behind by a node that crashed is
+    /// eventually broken, rather than failing the writes in the meanwhile.
+    const ACQUIRE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(15);
+
+    fn acquire(lock_file: std::path::PathBuf) -> anyhow::Result<Self> {
+        let deadline = std::time::Instant::now() + Self::ACQUIRE_TIMEOUT;
+        while std::time::Instant::now() < deadline {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&lock_file) {
+                Ok(mut file) => {
+                    let token = uuid::Uuid::new_v4().to_string();
+                    std::io::Write::write_all(&mut file, token.as_bytes())?;
+                    return Ok(Self { lock_file, token });
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if Self::is_stale(&lock_file) && Self::break_stale(&lock_file)? {
+                        continue;
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        anyhow::bail!("could not acquire lock {}", lock_file.display())
+    }
+
+    /// Remove a stale lock, returning true if it was removed.
+    ///
+    /// The nodes breaking the same lock are serialized by a second lock
+    /// file, created exclusively.
+    /// The lock is renamed to a unique name before being removed and it is
+    /// restored if it is no longer stale after the rename, i.e., if it has
+    /// been released and acquired again by another node in the meanwhile.
+    fn break_stale(lock_file: &std::path::Path) -> anyhow::Result<bool> {
+        let breaker_file = Self::with_suffix(lock_file, ".break");
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&breaker_file) {
+            Ok(_) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                // A breaker file is held only for a few file operations,
+                // hence one that is stale has been left behind by a node
+                // that crashed while breaking the lock.
+                if Self::is_stale(&breaker_file) {
+                    let _ = std::fs::remove_file(&breaker_file);
+                }
+                return Ok(false);
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        let renamed_file = Self::with_suffix(lock_file, &format!(".{}", uuid::Uuid::new_v4()));
+        let broken = match std::fs::rename(lock_file, &renamed_file) {
+            Ok(_) if Self::is_stale(&renamed_file) => {
+                log::warn!("removing stale lock {}", lock_file.display());
+                let _ = std::fs::remove_file(&renamed_file);
+                true
+            }
+            Ok(_) => {
+                // Hard links never replace an existing file.
+                let _ = std::fs::hard_link(&renamed_file, lock_file);
+                let _ = std::fs::remove_file(&renamed_file);
+                false
+            }
+            Err(_) => false,
+        };
+        let _ = std::fs::remove_file(&breaker_file);
+        Ok(broken)
+    }
+
+    fn with_suffix(path: &std::path::Path, suffix: &str) -> std::path::PathBuf {
+        let mut path = path.as_os_str().to_owned();
+        path.push(suffix);
+        std::path::PathBuf::from(path)
+    }
+
+    fn is_stale(lock_file: &std::path::Path) -> bool {
+        std::fs::metadata(lock_file)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > Self::STALE_TIMEOUT)
+    }
+}
+
+impl Drop for FileStateLock {
+    fn drop(&mut self) {
+        // The lock file is not removed if it has been broken in the
+        // meanwhile and now belongs to another node.
+        if std::fs::read_to_string(&self.lock_file).is_ok_and(|token| token == self.token) {
+            let _ = std::fs::remove_file(&self.lock_file);
+        }
+    }
+}
+
 impl FileStateProvider {
-    fn new() -> Self {
-        std::fs::DirBuilder::new().recursive(true).create("./function_state/").unwrap();
-        Self {
-            base_path: std::path::PathBuf::from("./function_state/"),
+    fn new(base_path: &str) -> anyhow::Result<Self> {
+        std::fs::DirBuilder::new().recursive(true).create(base_path)?;
+        Ok(Self {
+            base_path: std::path::PathBuf::from(base_path),
+        })
+    }
+
+    fn state_file(&self, state_id: &uuid::Uuid) -> std::path::PathBuf {
+        self.base_path.join(state_id.to_string())
+    }
+
+    fn lock(&self, state_id: &uuid::Uuid) -> anyhow::Result<FileStateLock> {
+        FileStateLock::acquire(self.base_path.join(format!("{}.lock", state_id)))
+    }
+
+    /// Read the state and its version, which are written together so that
+    /// readers never need the lock.
+    ///
+    /// The files written before the states were versioned have no version
+    /// header: their content is the state, at version 0.
+    fn read(&self, state_id: &uuid::Uuid) -> anyhow::Result<Option<VersionedState>> {
+        let content = match std::fs::read_to_string(self.state_file(state_id)) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Some(
+            match content
+                .split_once('\n')
+                .and_then(|(version, serialized_state)| version.parse::<u64>().ok().map(|version| (version, serialized_state)))
+            {
+                Some((version, serialized_state)) => VersionedState {
+                    serialized_state: serialized_state.to_string(),
+                    version,
+                },
+                None => VersionedState {
+                    serialized_state: content,
+                    version: 0,
+                },
+            },
+        ))
+    }
+
+    fn write(&self, state_id: uuid::Uuid, serialized_state: String, expected_version: u64) -> anyhow::Result<u64> {
+        let _lock = self.lock(&state_id)?;
+        let current_version = self.read(&state_id)?.map(|state| state.version).unwrap_or_default();
+        if current_version != expected_version {
+            return Err(StateConflict {
+                state_id,
+                expected_version,
+                found_version: current_version,
+            }
+            .into());
         }
+        let new_version = current_version + 1;
+        Self::write_atomically(&self.state_file(&state_id), &format!("{}\n{}", new_version, serialized_state))?;
+        Ok(new_version)
+    }
+
+    /// Write to a temporary file first, so that readers never see a
+    /// partially-written file.
+    fn write_atomically(path: &std::path::Path, content: &str) -> anyhow::Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, content)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
     }
 }
 
+#[async_trait::async_trait]
 impl StateProvider for FileStateProvider {
-    fn get(&mut self, state_id: uuid::Uuid) -> Option<String> {
-        let state_file = self.base_path.join(state_id.to_string());
-        if state_file.exists() {
-            return Some(std::fs::read_to_string(state_file).unwrap());
-        }
-        None
+    async fn get(&self, state_id: uuid::Uuid) -> anyhow::Result<Option<VersionedState>> {
+        let provider = self.clone();
+        tokio::task::spawn_blocking(move || provider.read(&state_id)).await?
+    }
+
+    async fn set(&self, state_id: uuid::Uuid, serialized_state: String, expected_version: u64) -> anyhow::Result<u64> {
+        let provider = self.clone();
+        tokio::task::spawn_blocking(move || provider.write(state_id, serialized_state, expected_version)).await?
+    }
+}
+
+/// State provider that saves the state of every function instance in a
+/// Redis hash, with fields `state` and `version`.
+///
+/// The conditional update is performed atomically by the Redis server
+/// through a Lua script.
+///
+/// The connection is multiplexed: every request uses a clone of it.
+struct RedisStateProvider {
+    connection: redis::aio::ConnectionManager,
+}
+
+impl RedisStateProvider {
+    const KEY_PREFIX: &'static str = "edgeless-state:";
+
+    const SET_SCRIPT: &'static str = r"
+local current = tonumber(redis.call('HGET', KEYS[1], 'version') or '0')
+if current ~= tonumber(ARGV[1]) then
+    return -1 - current
+end
+redis.call('HSET', KEYS[1], 'state', ARGV[2], 'version', current + 1)
+return current + 1
+";
+
+    async fn new(redis_url: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            connection: redis::aio::ConnectionManager::new(redis::Client::open(redis_url)?).await?,
+        })
     }
-    fn set(&mut self, state_id: uuid::Uuid, serialized_state: String) {
-        let state_file = self.base_path.join(state_id.to_string());
-        std::fs::write(state_file, serialized_state).unwrap();
+
+    fn key(state_id: &uuid::Uuid) -> String {
+        format!("{}{}", Self::KEY_PREFIX, state_id)
     }
 }
 
+#[async_trait::async_trait]
+impl StateProvider for RedisStateProvider {
+    async fn get(&self, state_id: uuid::Uuid) -> anyhow::Result<Option<VersionedState>> {
+        let (serialized_state, version): (Option<String>, Option<u64>) = redis::cmd("HMGET")
+            .arg(Self::key(&state_id))
+            .arg("state")
+            .arg("version")
+            .query_async(&mut self.connection.clone())
+            .await?;
+        Ok(serialized_state.map(|serialized_state| VersionedState {
+            serialized_state,
+            version: version.unwrap_or_default(),
+        }))
+    }
+
+    async fn set(&self, state_id: uuid::Uuid, serialized_state: String, expected_version: u64) -> anyhow::Result<u64> {
+        let res: i64 = redis::Script::new(Self::SET_SCRIPT)
+            .key(Self::key(&state_id))
+            .arg(expected_version)
+            .arg(serialized_state)
+            .invoke_async(&mut self.connection.clone())
+            .await?;
+        if res < 0 {
+            return Err(StateConflict {
+                state_id,
+                expected_version,
+                found_version: (-1 - res) as u64,
+            }
+            .into());
+        }
+        Ok(res as u64)
+    }
+}
+
+/// Providers of the node, which never change after creation, hence they
+/// are shared without locks.
 struct StateProviders {
-    node_local: Option<Box<dyn StateProvider>>,
-    global: Option<Box<dyn StateProvider>>,
+    node_local: Option<std::sync::Arc<dyn StateProvider>>,
+    global: Option<std::sync::Arc<dyn StateProvider>>,
+}
+
+impl StateProviders {
+    fn provider(&self, state_policy: &edgeless_api::function_instance::StatePolicy) -> Option<std::sync::Arc<dyn StateProvider>> {
+        match state_policy {
+            edgeless_api::function_instance::StatePolicy::NodeLocal => self.node_local.clone(),
+            edgeless_api::function_instance::StatePolicy::Global => self.global.clone(),
+            edgeless_api::function_instance::StatePolicy::Transient => None,
+        }
+    }
 }
 
 #[async_trait::async_trait]
 pub trait StateManagerAPI: Send {
-    async fn get_handle(&mut self, state_policy: edgeless_api::function_instance::StatePolicy, state_id: uuid::Uuid) -> Box<dyn StateHandleAPI>;
+    /// Return a handle to the state with the given policy and identifier.
+    /// Fail if there is no provider for the policy requested.
+    async fn get_handle(
+        &mut self,
+        state_policy: edgeless_api::function_instance::StatePolicy,
+        state_id: uuid::Uuid,
+    ) -> anyhow::Result<Box<dyn StateHandleAPI>>;
 }
 
 impl StateManager {
-    pub async fn new() -> Self {
+    /// Create a state manager with a node-local provider, always, and a
+    /// global provider if enabled in the settings.
+    /// The Redis server takes precedence over the shared directory if both
+    /// are specified.
+    pub async fn new(settings: &Option<crate::EdgelessNodeStateSettings>) -> Self {
+        let node_local: Option<std::sync::Arc<dyn StateProvider>> = match FileStateProvider::new("./function_state/") {
+            Ok(provider) => Some(std::sync::Arc::new(provider)),
+            Err(err) => {
+                log::error!("could not create the node-local state provider: {}", err);
+                None
+            }
+        };
+
+        let mut global: Option<std::sync::Arc<dyn StateProvider>> = None;
+        if let Some(settings) = settings {
+            if let Some(redis_url) = &settings.global_redis_url
+                && !redis_url.is_empty()
+            {
+                match RedisStateProvider::new(redis_url).await {
+                    Ok(provider) => {
+                        log::info!("Global state stored on Redis at {}", redis_url);
+                        global = Some(std::sync::Arc::new(provider));
+                    }
+                    Err(err) => log::error!("could not connect to Redis at {} for global state: {}", redis_url, err),
+                }
+            } else if let Some(shared_dir) = &settings.global_shared_dir
+                && !shared_dir.is_empty()
+            {
+                match FileStateProvider::new(shared_dir) {
+                    Ok(provider) => {
+                        log::info!("Global state stored in directory {}", shared_dir);
+                        global = Some(std::sync::Arc::new(provider));
+                    }
+                    Err(err) => log::error!("could not use directory {} for global state: {}", shared_dir, err),
+                }
+            }
+        }
+
         Self {
-            handlers: std::sync::Arc::new(tokio::sync::Mutex::new(StateProviders {
-                node_local: Some(Box::new(FileStateProvider::new())),
-                global: None,
-            })),
+            handlers: std::sync::Arc::new(StateProviders { node_local, global }),
         }
     }
 }
 
 #[async_trait::async_trait]
 impl StateManagerAPI for StateManager {
-    async fn get_handle(&mut self, state_policy: edgeless_api::function_instance::StatePolicy, state_id: uuid::Uuid) -> Box<dyn StateHandleAPI> {
-        Box::new(StateHandle {
+    async fn get_handle(
+        &mut self,
+        state_policy: edgeless_api::function_instance::StatePolicy,
+        state_id: uuid::Uuid,
+    ) -> anyhow::Result<Box<dyn StateHandleAPI>> {
+        anyhow::ensure!(
+            state_policy != edgeless_api::function_instance::StatePolicy::Global || self.handlers.global.is_some(),
+            "global state requested for {} but no global state provider is configured",
+            state_id
+        );
+        Ok(Box::new(StateHandle {
             state_policy,
             state_id,
+            version: 0,
             handlers: self.handlers.clone(),
-        })
+        }))
     }
 }
 
+/// Access to the state of a function instance, independent of its policy.
+///
+/// A handle can only write on top of the version it last read or wrote:
+/// if someone else has written the state in the meanwhile, then `set()`
+/// fails with `StateConflict` and `get()` must be called again before
+/// writing.
 #[async_trait::async_trait]
 pub trait StateHandleAPI: Send {
     async fn get(&mut self) -> Option<String>;
-    async fn set(&mut self, serialized_state: String);
+    async fn set(&mut self, serialized_state: String) -> anyhow::Result<()>;
+
+    /// Version of the state last read or written, 0 if there is none.
+    fn version(&self) -> u64;
+
+    /// Write the state on top of `expected_version`, rather than on top of
+    /// the version last read or written.
+    async fn set_on(&mut self, serialized_state: String, expected_version: u64) -> anyhow::Result<()>;
 }
 
 pub struct StateHandle {
-    handlers: std::sync::Arc<tokio::sync::Mutex<StateProviders>>,
+    handlers: std::sync::Arc<StateProviders>,
     state_id: uuid::Uuid,
     state_policy: edgeless_api::function_instance::StatePolicy,
+    version: u64,
 }
 
 #[async_trait::async_trait]
 impl StateHandleAPI for StateHandle {
     async fn get(&mut self) -> Option<String> {
-        let mut handles = self.handlers.lock().await;
-        match self.state_policy {
-            edgeless_api::function_instance::StatePolicy::NodeLocal => {
-                if let Some(provider) = &mut handles.node_local {
-                    return provider.get(self.state_id);
-                }
+        let provider = self.handlers.provider(&self.state_policy)?;
+        match provider.get(self.state_id).await {
+            Ok(Some(state)) => {
+                self.version = state.version;
+                Some(state.serialized_state)
             }
-            edgeless_api::function_instance::StatePolicy::Global => {
-                if let Some(provider) = &mut handles.global {
-                    return provider.get(self.state_id);
-                }
+            Ok(None) => {
+                self.version = 0;
+                None
+            }
+            Err(err) => {
+                log::error!("could not retrieve state {}: {}", self.state_id, err);
+                None
             }
-            _ => {}
         }
-        None
     }
 
-    async fn set(&mut self, serialized_state: String) {
-        let mut handles = self.handlers.lock().await;
-        match self.state_policy {
-            edgeless_api::function_instance::StatePolicy::NodeLocal => {
-                if let Some(provider) = &mut handles.node_local {
-                    return provider.set(self.state_id, serialized_state);
-                }
-            }
-            edgeless_api::function_instance::StatePolicy::Global => {
-                if let Some(provider) = &mut handles.global {
-                    return provider.set(self.state_id, serialized_state);
-                }
-            }
-            _ => {}
+    async fn set(&mut self, serialized_state: String) -> anyhow::Result<()> {
+        self.set_on(serialized_state, self.version).await
+    }
+
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    async fn set_on(&mut self, serialized_state: String, expected_version: u64) -> anyhow::Result<()> {
+        if let Some(provider) = self.handlers.provider(&self.state_policy) {
+            self.version = provider.set(self.state_id, serialized_state, expected_version).await?;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_dir() -> String {
+        std::env::temp_dir()
+            .join(format!("edgeless-state-{}", uuid::Uuid::new_v4()))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_file_state_provider_versioning() {
+        let base_path = temp_dir();
+        let provider = FileStateProvider::new(&base_path).unwrap();
+        let state_id = uuid::Uuid::new_v4();
+
+        assert!(provider.get(state_id).await.unwrap().is_none());
+        assert_eq!(1, provider.set(state_id, "first".to_string(), 0).await.unwrap());
+        assert_eq!(2, provider.set(state_id, "second".to_string(), 1).await.unwrap());
+        assert_eq!(
+            Some(VersionedState {
+                serialized_state: "second".to_string(),
+                version: 2
+            }),
+            provider.get(state_id).await.unwrap()
+        );
+
+        // Stale writers are rejected.
+        assert!(provider.set(state_id, "stale".to_string(), 1).await.is_err());
+        assert_eq!("second", provider.get(state_id).await.unwrap().unwrap().serialized_state);
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[tokio::test]
+    async fn test_file_state_provider_stale_lock() {
+        let base_path = temp_dir();
+        let provider = FileStateProvider::new(&base_path).unwrap();
+        let state_id = uuid::Uuid::new_v4();
+
+        // A lock left behind by a crashed node is eventually broken.
+        let lock_file = std::fs::File::create(std::path::Path::new(&base_path).join(format!("{}.lock", state_id))).unwrap();
+        lock_file
+            .set_modified(std::time::SystemTime::now() - 2 * FileStateLock::STALE_TIMEOUT)
+            .unwrap();
+        assert_eq!(1, provider.set(state_id, "first".to_string(), 0).await.unwrap());
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[tokio::test]
+    async fn test_file_state_provider_unversioned() {
+        let base_path = temp_dir();
+        let provider = FileStateProvider::new(&base_path).unwrap();
+        let state_id = uuid::Uuid::new_v4();
+
+        // A state file written before the states were versioned is read at
+        // version 0 and can be overwritten on top of it.
+        std::fs::write(provider.state_file(&state_id), "{\"count\":42}").unwrap();
+        assert_eq!(
+            Some(VersionedState {
+                serialized_state: "{\"count\":42}".to_string(),
+                version: 0
+            }),
+            provider.get(state_id).await.unwrap()
+        );
+        assert_eq!(1, provider.set(state_id, "first".to_string(), 0).await.unwrap());
+        assert_eq!("first", provider.get(state_id).await.unwrap().unwrap().serialized_state);
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[test]
+    fn test_file_state_lock_ownership() {
+        let base_path = temp_dir();
+        std::fs::DirBuilder::new().recursive(true).create(&base_path).unwrap();
+        let lock_file = std::path::Path::new(&base_path).join("state.lock");
+
+        // A lock that is not stale is never broken.
+        let lock_1 = FileStateLock::acquire(lock_file.clone()).unwrap();
+        assert!(!FileStateLock::break_stale(&lock_file).unwrap());
+        assert!(lock_file.exists());
+
+        // A lock broken while its owner was still holding it is not
+        // released by the former owner.
+        std::fs::File::options()
+            .write(true)
+            .open(&lock_file)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - 2 * FileStateLock::STALE_TIMEOUT)
+            .unwrap();
+        let lock_2 = FileStateLock::acquire(lock_file.clone()).unwrap();
+        drop(lock_1);
+        assert_eq!(lock_2.token, std::fs::read_to_string(&lock_file).unwrap());
+        drop(lock_2);
+        assert!(!lock_file.exists());
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[tokio::test]
+    async fn test_state_handles_shared_directory() {
+        let base_path = temp_dir();
+        let mut mgr = StateManager::new(&Some(crate::EdgelessNodeStateSettings {
+            global_redis_url: None,
+            global_shared_dir: Some(base_path.clone()),
+        }))
+        .await;
+        let state_id = uuid::Uuid::new_v4();
+
+        let mut handle_1 = mgr
+            .get_handle(edgeless_api::function_instance::StatePolicy::Global, state_id)
+            .await
+            .unwrap();
+        let mut handle_2 = mgr
+            .get_handle(edgeless_api::function_instance::StatePolicy::Global, state_id)
+            .await
+            .unwrap();
+        assert!(handle_1.get().await.is_none());
+        assert!(handle_2.get().await.is_none());
+
+        // Only the first of two writers on the same version wins.
+        assert!(handle_1.set("one".to_string()).await.is_ok());
+        assert!(handle_2.set("two".to_string()).await.unwrap_err().is::<StateConflict>());
+        assert!(handle_1.set("three".to_string()).await.is_ok());
+
+        // After reading again, the second writer can go ahead.
+        assert_eq!(Some("three".to_string()), handle_2.get().await);
+        assert!(handle_2.set("four".to_string()).await.is_ok());
+        assert_eq!(Some("four".to_string()), handle_1.get().await);
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[tokio::test]
+    async fn test_state_handles_set_on() {
+        let base_path = temp_dir();
+        let mut mgr = StateManager::new(&Some(crate::EdgelessNodeStateSettings {
+            global_redis_url: None,
+            global_shared_dir: Some(base_path.clone()),
+        }))
+        .await;
+        let state_id = uuid::Uuid::new_v4();
+
+        let mut handle_1 = mgr
+            .get_handle(edgeless_api::function_instance::StatePolicy::Global, state_id)
+            .await
+            .unwrap();
+        let mut handle_2 = mgr
+            .get_handle(edgeless_api::function_instance::StatePolicy::Global, state_id)
+            .await
+            .unwrap();
+        assert!(handle_1.set("one".to_string()).await.is_ok());
+        assert_eq!(1, handle_1.version());
+
+        // The state is only written on top of the version expected.
+        assert!(handle_2.set_on("two".to_string(), 0).await.unwrap_err().is::<StateConflict>());
+        assert!(handle_2.set_on("two".to_string(), 1).await.is_ok());
+        assert_eq!(2, handle_2.version());
+        assert_eq!(Some("two".to_string()), handle_1.get().await);
+
+        let _ = std::fs::remove_dir_all(base_path);
+    }
+
+    #[tokio::test]
+    async fn test_state_handles_transient() {
+        let mut mgr = StateManager::new(&None).await;
+        let mut handle = mgr
+            .get_handle(edgeless_api::function_instance::StatePolicy::Transient, uuid::Uuid::new_v4())
+            .await
+            .unwrap();
+        assert!(handle.set("state".to_string()).await.is_ok());
+        assert!(handle.get().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_state_handles_global_without_provider() {
+        let mut mgr = StateManager::new(&None).await;
+        assert!(
+            mgr.get_handle(edgeless_api::function_instance::StatePolicy::Global, uuid::Uuid::new_v4())
+                .await
+                .is_err()
+        );
     }
 }
@@ -40,6 +40,8 @@ pub struct WASMFunctionInstance {
         (),
     >,
     edgefunctione_handle_stop: wasmtime::TypedFunc<(), ()>,
+    // Not exported by functions built before handle_sync was introduced.
+    edgefunctione_handle_sync: Option<wasmtime::TypedFunc<(), ()>>,
     memory: wasmtime::Memory,
     store: wasmtime::Store<super::guest_api_binding::GuestAPI>,
 }
@@ -151,11 +153,24 @@ impl crate::base_runtime::FunctionInstance for WASMFunctionInstance {
                 },
             )
             .map_err(|_| crate::base_runtime::FunctionInstanceError::InternalError)?;
-        linker
-            .func_wrap2_async("env", "sync_asm", |store, state_ptr, state_len| {
-                Box::new(super::guest_api_binding::sync(store, state_ptr, state_len))
-            })
-            .map_err(|_| crate::base_runtime::FunctionInstanceError::InternalError)?;
+        // Functions built before sync_asm returned a status import it
+        // without the output pointers.
+        let legacy_sync = module.imports().any(|import| {
+            import.name() == "sync_asm" && matches!(import.ty(), wasmtime::ExternType::Func(ty) if ty.params().len() == 2)
+        });
+        if legacy_sync {
+            linker
+                .func_wrap2_async("env", "sync_asm", |store, state_ptr, state_len| {
+                    Box::new(super::guest_api_binding::sync_legacy(store, state_ptr, state_len))
+                })
+                .map_err(|_| crate::base_runtime::FunctionInstanceError::InternalError)?;
+        } else {
+            linker
+                .func_wrap4_async("env", "sync_asm", |store, state_ptr, state_len, out_ptr_ptr, out_len_ptr| {
+                    Box::new(super::guest_api_binding::sync(store, state_ptr, state_len, out_ptr_ptr, out_len_ptr))
+                })
+                .map_err(|_| crate::base_runtime::FunctionInstanceError::InternalError)?;
+        }
 
         let instance = linker
             .instantiate_async(&mut store, &module)
@@ -184,6 +199,7 @@ impl crate::base_runtime::FunctionInstance for WASMFunctionInstance {
             edgefunctione_handle_stop: instance
                 .get_typed_func::<(), ()>(&mut store, "handle_stop_asm")
                 .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("handle_stop_asm not available: {}", e)))?,
+            edgefunctione_handle_sync: instance.get_typed_func::<(), ()>(&mut store, "handle_sync_asm").ok(),
             memory: instance
                 .get_memory(&mut store, "memory")
                 .ok_or_else(|| crate::base_runtime::FunctionInstanceError::BadCode("memory not available".to_string()))?,
@@ -247,7 +263,11 @@ impl crate::base_runtime::FunctionInstance for WASMFunctionInstance {
         ret
     }
 
-    async fn cast(&mut self, src: &edgeless_api::function_instance::InstanceId, msg: &str) -> Result<(), crate::base_runtime::FunctionInstanceError> {
+    async fn cast(
+        &mut self,
+        src: &edgeless_api::function_instance::InstanceId,
+        msg: &[u8],
+    ) -> Result<(), crate::base_runtime::FunctionInstanceError> {
         // Depending on the Function, we might employ a basic arena/bump allocator that we must reset at the end of a transaction.
         // This might be a noop if the function defines a working version of `edgeless_mem_free`.
         self.edgeless_mem_clear
@@ -273,7 +293,7 @@ impl crate::base_runtime::FunctionInstance for WASMFunctionInstance {
         .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("cast failed: copy_to_vm2 {}", e)))?;
 
         let payload_len = msg.len();
-        let payload_ptr = super::helpers::copy_to_vm(&mut self.store.as_context_mut(), &self.memory, &self.edgeless_mem_alloc, msg.as_bytes())
+        let payload_ptr = super::helpers::copy_to_vm(&mut self.store.as_context_mut(), &self.memory, &self.edgeless_mem_alloc, msg)
             .await
             .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("cast failed: copy_to_vm3 {}", e)))?;
 
@@ -305,7 +325,7 @@ impl crate::base_runtime::FunctionInstance for WASMFunctionInstance {
     async fn call(
         &mut self,
         src: &edgeless_api::function_instance::InstanceId,
-        msg: &str,
+        msg: &[u8],
     ) -> Result<edgeless_dataplane::core::CallRet, crate::base_runtime::FunctionInstanceError> {
         self.edgeless_mem_clear
             .call_async(&mut self.store, ())
@@ -331,7 +351,7 @@ impl crate::base_runtime::FunctionInstance for WASMFunctionInstance {
         .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("call failed: {}", e)))?;
 
         let payload_len = msg.len();
-        let payload_ptr = super::helpers::copy_to_vm(&mut self.store.as_context_mut(), &self.memory, &self.edgeless_mem_alloc, msg.as_bytes())
+        let payload_ptr = super::helpers::copy_to_vm(&mut self.store.as_context_mut(), &self.memory, &self.edgeless_mem_alloc, msg)
             .await
             .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("call failed: {}", e)))?;
 
@@ -373,10 +393,9 @@ impl crate::base_runtime::FunctionInstance for WASMFunctionInstance {
 
                 // load the atual output param
                 let out_raw = self.memory.data_mut(&mut self.store)[out_ptr as usize..(out_ptr as usize) + out_len as usize].to_vec();
-                // TODO(raphaelhetzel) This unwrap can be removed after we migrate the dataplane to use string slices.
-                let out = unsafe { std::string::String::from_utf8_unchecked(out_raw) };
-                Ok(edgeless_dataplane::core::CallRet::Reply(out))
+                Ok(edgeless_dataplane::core::CallRet::Reply(out_raw))
             }
+            3 => Ok(edgeless_dataplane::core::CallRet::Timeout),
             _ => Ok(edgeless_dataplane::core::CallRet::Err),
         };
 
@@ -419,4 +438,14 @@ impl crate::base_runtime::FunctionInstance for WASMFunctionInstance {
             .await
             .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("stop failed: {}", e)))
     }
+
+    async fn sync(&mut self) -> Result<(), crate::base_runtime::FunctionInstanceError> {
+        match &self.edgefunctione_handle_sync {
+            Some(handle_sync) => handle_sync
+                .call_async(&mut self.store, ())
+                .await
+                .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("sync failed: {}", e))),
+            None => Ok(()),
+        }
+    }
 }
@@ -44,7 +44,7 @@ pub async fn cast_raw(
         node_id: uuid::Uuid::from_bytes(node_id.try_into().map_err(|_| wasmtime::Error::msg("uuid error"))?),
         function_id: uuid::Uuid::from_bytes(component_id.try_into().map_err(|_| wasmtime::Error::msg("uuid error"))?),
     };
-    let payload = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = super::helpers::load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     caller
         .data_mut()
@@ -72,7 +72,7 @@ pub async fn call_raw(
         node_id: uuid::Uuid::from_bytes(node_id.try_into().map_err(|_| wasmtime::Error::msg("uuid error"))?),
         function_id: uuid::Uuid::from_bytes(component_id.try_into().map_err(|_| wasmtime::Error::msg("uuid error"))?),
     };
-    let payload = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = super::helpers::load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     let call_ret = caller
         .data_mut()
@@ -85,13 +85,14 @@ pub async fn call_raw(
         edgeless_dataplane::core::CallRet::Reply(data) => {
             let len = data.len();
 
-            let data_ptr = super::helpers::copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, data.as_bytes()).await?;
+            let data_ptr = super::helpers::copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, &data).await?;
             super::helpers::copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_ptr_ptr, &data_ptr.to_le_bytes())?;
             super::helpers::copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_len_ptr, &len.to_le_bytes())?;
 
             Ok(1)
         }
         edgeless_dataplane::core::CallRet::Err => Ok(2),
+        edgeless_dataplane::core::CallRet::Timeout => Ok(3),
     }
 }
 
@@ -105,7 +106,7 @@ pub async fn cast(
     let mem = get_memory(&mut caller)?;
 
     let target = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, target_ptr, target_len)?;
-    let payload = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = super::helpers::load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     match caller.data_mut().host.cast_alias(&target, &payload).await {
         Ok(_) => {}
@@ -131,7 +132,7 @@ pub async fn call(
     let alloc = get_alloc(&mut caller)?;
 
     let target = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, target_ptr, target_len)?;
-    let payload = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = super::helpers::load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     let call_ret = caller
         .data_mut()
@@ -144,13 +145,14 @@ pub async fn call(
         edgeless_dataplane::core::CallRet::Reply(data) => {
             let len = data.len();
 
-            let data_ptr = super::helpers::copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, data.as_bytes()).await?;
+            let data_ptr = super::helpers::copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, &data).await?;
             super::helpers::copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_ptr_ptr, &data_ptr.to_le_bytes())?;
             super::helpers::copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_len_ptr, &len.to_le_bytes())?;
 
             Ok(1)
         }
         edgeless_dataplane::core::CallRet::Err => Ok(2),
+        edgeless_dataplane::core::CallRet::Timeout => Ok(3),
     }
 }
 
@@ -164,7 +166,7 @@ pub async fn delayed_cast(
 ) -> wasmtime::Result<()> {
     let mem = get_memory(&mut caller)?;
     let target = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, target_ptr, target_len)?;
-    let payload = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = super::helpers::load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     caller
         .data_mut()
@@ -175,17 +177,47 @@ pub async fn delayed_cast(
     Ok(())
 }
 
-pub async fn sync(mut caller: wasmtime::Caller<'_, GuestAPI>, state_ptr: i32, state_len: i32) -> wasmtime::Result<()> {
+/// Return 0 if the state has been synchronized, 1 if it has been rejected
+/// because of a conflict, in which case the current state is copied into
+/// the guest memory, or 2 if it has been rejected and there is no current
+/// state to hand back.
+pub async fn sync(
+    mut caller: wasmtime::Caller<'_, GuestAPI>,
+    state_ptr: i32,
+    state_len: i32,
+    out_ptr_ptr: i32,
+    out_len_ptr: i32,
+) -> wasmtime::Result<i32> {
     let mem = get_memory(&mut caller)?;
+    let alloc = get_alloc(&mut caller)?;
     let state = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, state_ptr, state_len)?;
 
-    caller
-        .data_mut()
-        .host
-        .sync(&state)
-        .await
-        .map_err(|_| wasmtime::Error::msg("sync error"))?;
-    Ok(())
+    match caller.data_mut().host.sync(&state).await {
+        Ok(_) => Ok(0),
+        Err(crate::base_runtime::guest_api::GuestAPIError::StateConflict(Some(current_state))) => {
+            let len = current_state.len();
+
+            let data_ptr = super::helpers::copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, current_state.as_bytes()).await?;
+            super::helpers::copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_ptr_ptr, &data_ptr.to_le_bytes())?;
+            super::helpers::copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_len_ptr, &len.to_le_bytes())?;
+
+            Ok(1)
+        }
+        Err(crate::base_runtime::guest_api::GuestAPIError::StateConflict(None)) => Ok(2),
+        Err(_) => Err(wasmtime::Error::msg("sync error")),
+    }
+}
+
+/// Binding of `sync_asm` for the functions built before it returned a
+/// status, which are not told about conflicts.
+pub async fn sync_legacy(mut caller: wasmtime::Caller<'_, GuestAPI>, state_ptr: i32, state_len: i32) -> wasmtime::Result<()> {
+    let mem = get_memory(&mut caller)?;
+    let state = super::helpers::load_string_from_vm(&mut caller.as_context_mut(), &mem, state_ptr, state_len)?;
+
+    match caller.data_mut().host.sync(&state).await {
+        Ok(_) | Err(crate::base_runtime::guest_api::GuestAPIError::StateConflict(_)) => Ok(()),
+        Err(_) => Err(wasmtime::Error::msg("sync error")),
+    }
 }
 
 pub async fn slf(mut caller: wasmtime::Caller<'_, GuestAPI>, out_node_id_ptr: i32, out_component_id_ptr: i32) -> wasmtime::Result<()> {
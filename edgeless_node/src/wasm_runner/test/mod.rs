@@ -39,12 +39,12 @@ impl crate::state_management::StateManagerAPI for MockStateMananger {
         &mut self,
         _state_policy: edgeless_api::function_instance::StatePolicy,
         state_id: uuid::Uuid,
-    ) -> Box<dyn crate::state_management::StateHandleAPI> {
-        Box::new(MockStateHandle {
+    ) -> anyhow::Result<Box<dyn crate::state_management::StateHandleAPI>> {
+        Ok(Box::new(MockStateHandle {
             state_id,
             output_mocks: self.output_mocks.clone(),
             state_set_sender: self.state_set_sender.clone(),
-        })
+        }))
     }
 }
 
@@ -60,8 +60,17 @@ impl crate::state_management::StateHandleAPI for MockStateHandle {
         self.output_mocks.lock().await.get(&self.state_id).cloned()
     }
 
-    async fn set(&mut self, serialized_state: String) {
+    async fn set(&mut self, serialized_state: String) -> anyhow::Result<()> {
         self.state_set_sender.send((self.state_id, serialized_state)).await.unwrap();
+        Ok(())
+    }
+
+    fn version(&self) -> u64 {
+        0
+    }
+
+    async fn set_on(&mut self, serialized_state: String, _expected_version: u64) -> anyhow::Result<()> {
+        self.set(serialized_state).await
     }
 }
 
@@ -74,7 +83,7 @@ async fn basic_lifecycle() {
     let node_id = uuid::Uuid::new_v4();
     let instance_id = edgeless_api::function_instance::InstanceId::new(node_id);
 
-    let state_manager = Box::new(crate::state_management::StateManager::new().await);
+    let state_manager = Box::new(crate::state_management::StateManager::new(&None).await);
     let dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7002".to_string(), None).await;
 
     let (telemetry_mock_sender, telemetry_mock_receiver) = std::sync::mpsc::channel::<(
@@ -232,7 +241,7 @@ async fn messaging_test_setup() -> (
     let node_id = uuid::Uuid::new_v4();
     let instance_id = edgeless_api::function_instance::InstanceId::new(node_id);
 
-    let state_manager = Box::new(crate::state_management::StateManager::new().await);
+    let state_manager = Box::new(crate::state_management::StateManager::new(&None).await);
     let mut dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7002".to_string(), None).await;
 
     // shared insert
@@ -286,6 +295,7 @@ async fn messaging_test_setup() -> (
         .patch(PatchRequest {
             function_id: instance_id.function_id,
             output_mapping: std::collections::HashMap::from([("test".to_string(), next_fid)]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await;
 
@@ -350,10 +360,7 @@ async fn messaging_cast_raw_output() {
 
     let test_message = test_peer_handle.receive_next().await;
     assert_eq!(test_message.source_id, instance_id);
-    assert_eq!(
-        test_message.message,
-        edgeless_dataplane::core::Message::Cast("cast_raw_output".to_string())
-    );
+    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Cast(b"cast_raw_output".to_vec()));
     assert_eq!(metad_1, test_message.metadata);
 }
 
@@ -373,11 +380,8 @@ async fn messaging_call_raw_output() {
 
     let test_message = test_peer_handle.receive_next().await;
     assert_eq!(test_message.source_id, instance_id);
-    assert_eq!(
-        test_message.message,
-        edgeless_dataplane::core::Message::Call("call_raw_output".to_string())
-    );
-    assert_eq!(&metad_1, &test_message.metadata);
+    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Call(b"call_raw_output".to_vec()));
+    assert_eq!(&metad_1, &test_message.metadata.clone().with_deadline(None));
 
     test_peer_handle
         .reply(test_message.source_id, test_message.channel_id, CallRet::NoReply, &test_message.metadata)
@@ -404,7 +408,7 @@ async fn messaging_delayed_cast_output() {
     assert_eq!(test_message.source_id, instance_id);
     assert_eq!(
         test_message.message,
-        edgeless_dataplane::core::Message::Cast("delayed_cast_output".to_string())
+        edgeless_dataplane::core::Message::Cast(b"delayed_cast_output".to_vec())
     );
     assert_eq!(&metad_1, &test_message.metadata);
 
@@ -431,7 +435,7 @@ async fn messaging_cast_output() {
 
     let test_message = next_handle.receive_next().await;
     assert_eq!(test_message.source_id, instance_id);
-    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Cast("cast_output".to_string()));
+    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Cast(b"cast_output".to_vec()));
     assert_eq!(metad_1, test_message.metadata);
 }
 
@@ -452,8 +456,8 @@ async fn messaging_call_output() {
 
     let test_message = next_handle.receive_next().await;
     assert_eq!(test_message.source_id, instance_id);
-    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Call("call_output".to_string()));
-    assert_eq!(&metad_1, &test_message.metadata);
+    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Call(b"call_output".to_vec()));
+    assert_eq!(&metad_1, &test_message.metadata.clone().with_deadline(None));
 
     next_handle
         .reply(test_message.source_id, test_message.channel_id, CallRet::NoReply, &test_message.metadata)
@@ -481,8 +485,8 @@ async fn function_in_call_can_be_stopped() {
 
     let test_message = next_handle.receive_next().await;
     assert_eq!(test_message.source_id, instance_id);
-    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Call("call_output".to_string()));
-    assert_eq!(&metad_1, &test_message.metadata);
+    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Call(b"call_output".to_vec()));
+    assert_eq!(&metad_1, &test_message.metadata.clone().with_deadline(None));
 
     assert!(telemetry_mock_receiver.try_recv().is_err());
 
@@ -513,7 +517,7 @@ async fn messaging_call_raw_input_reply() {
     let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00036u128, 0x42a42bdecaf00037u64);
 
     let ret = test_peer_handle.call(instance_id, "test_ret".to_string(), &metad_1).await;
-    assert_eq!(ret, CallRet::Reply("test_reply".to_string()));
+    assert_eq!(ret, CallRet::Reply(b"test_reply".to_vec()));
 
     assert!(is_telemetry_event_transfer(&mut telemetry_mock_receiver).await);
     assert!(is_telemetry_event_invocation_complete(&mut telemetry_mock_receiver).await);
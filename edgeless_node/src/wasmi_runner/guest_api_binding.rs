@@ -38,7 +38,7 @@ pub fn cast_raw(
         node_id: uuid::Uuid::from_bytes(node_id.try_into().map_err(|_| wasmi::core::Trap::new("uuid error"))?),
         function_id: uuid::Uuid::from_bytes(component_id.try_into().map_err(|_| wasmi::core::Trap::new("uuid error"))?),
     };
-    let payload = load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     tokio::runtime::Handle::current()
         .block_on(caller.data_mut().host.cast_raw(instance_id, &payload))
@@ -63,7 +63,7 @@ pub fn call_raw(
         node_id: uuid::Uuid::from_bytes(node_id.try_into().map_err(|_| wasmi::core::Trap::new("uuid error"))?),
         function_id: uuid::Uuid::from_bytes(component_id.try_into().map_err(|_| wasmi::core::Trap::new("uuid error"))?),
     };
-    let payload = load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     let call_ret = tokio::runtime::Handle::current()
         .block_on(caller.data_mut().host.call_raw(instance_id, &payload))
@@ -73,13 +73,14 @@ pub fn call_raw(
         edgeless_dataplane::core::CallRet::Reply(data) => {
             let len = data.len();
 
-            let data_ptr = copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, data.as_bytes())?;
+            let data_ptr = copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, &data)?;
             copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_ptr_ptr, &data_ptr.to_le_bytes())?;
             copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_len_ptr, &len.to_le_bytes())?;
 
             Ok(1)
         }
         edgeless_dataplane::core::CallRet::Err => Ok(2),
+        edgeless_dataplane::core::CallRet::Timeout => Ok(3),
     }
 }
 
@@ -93,7 +94,7 @@ pub fn cast(
     let mem = get_memory(&mut caller)?;
 
     let target = load_string_from_vm(&mut caller.as_context_mut(), &mem, target_ptr, target_len)?;
-    let payload = load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     match tokio::runtime::Handle::current().block_on(caller.data_mut().host.cast_alias(&target, &payload)) {
         Ok(_) => {}
@@ -119,7 +120,7 @@ pub fn call(
     let alloc = get_alloc(&mut caller)?;
 
     let target = load_string_from_vm(&mut caller.as_context_mut(), &mem, target_ptr, target_len)?;
-    let payload = load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     let call_ret = tokio::runtime::Handle::current()
         .block_on(caller.data_mut().host.call_alias(&target, &payload))
@@ -129,13 +130,14 @@ pub fn call(
         edgeless_dataplane::core::CallRet::Reply(data) => {
             let len = data.len();
 
-            let data_ptr = copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, data.as_bytes())?;
+            let data_ptr = copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, &data)?;
             copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_ptr_ptr, &data_ptr.to_le_bytes())?;
             copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_len_ptr, &len.to_le_bytes())?;
 
             Ok(1)
         }
         edgeless_dataplane::core::CallRet::Err => Ok(2),
+        edgeless_dataplane::core::CallRet::Timeout => Ok(3),
     }
 }
 
@@ -149,7 +151,7 @@ pub fn delayed_cast(
 ) -> Result<(), wasmi::core::Trap> {
     let mem = get_memory(&mut caller)?;
     let target = load_string_from_vm(&mut caller.as_context_mut(), &mem, target_ptr, target_len)?;
-    let payload = load_string_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
+    let payload = load_bytes_from_vm(&mut caller.as_context_mut(), &mem, payload_ptr, payload_len)?;
 
     tokio::runtime::Handle::current()
         .block_on(caller.data_mut().host.delayed_cast(delay_ms as u64, &target, &payload))
@@ -157,14 +159,47 @@ pub fn delayed_cast(
     Ok(())
 }
 
-pub fn sync(mut caller: wasmi::Caller<'_, GuestAPI>, state_ptr: i32, state_len: i32) -> Result<(), wasmi::core::Trap> {
+/// Return 0 if the state has been synchronized, 1 if it has been rejected
+/// because of a conflict, in which case the current state is copied into
+/// the guest memory, or 2 if it has been rejected and there is no current
+/// state to hand back.
+pub fn sync(
+    mut caller: wasmi::Caller<'_, GuestAPI>,
+    state_ptr: i32,
+    state_len: i32,
+    out_ptr_ptr: i32,
+    out_len_ptr: i32,
+) -> Result<i32, wasmi::core::Trap> {
     let mem = get_memory(&mut caller)?;
+    let alloc = get_alloc(&mut caller)?;
     let state = load_string_from_vm(&mut caller.as_context_mut(), &mem, state_ptr, state_len)?;
 
-    tokio::runtime::Handle::current()
-        .block_on(caller.data_mut().host.sync(&state))
-        .map_err(|_| wasmi::core::Trap::new("sync error"))?;
-    Ok(())
+    match tokio::runtime::Handle::current().block_on(caller.data_mut().host.sync(&state)) {
+        Ok(_) => Ok(0),
+        Err(crate::base_runtime::guest_api::GuestAPIError::StateConflict(Some(current_state))) => {
+            let len = current_state.len();
+
+            let data_ptr = copy_to_vm(&mut caller.as_context_mut(), &mem, &alloc, current_state.as_bytes())?;
+            copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_ptr_ptr, &data_ptr.to_le_bytes())?;
+            copy_to_vm_ptr(&mut caller.as_context_mut(), &mem, out_len_ptr, &len.to_le_bytes())?;
+
+            Ok(1)
+        }
+        Err(crate::base_runtime::guest_api::GuestAPIError::StateConflict(None)) => Ok(2),
+        Err(_) => Err(wasmi::core::Trap::new("sync error")),
+    }
+}
+
+/// Binding of `sync_asm` for the functions built before it returned a
+/// status, which are not told about conflicts.
+pub fn sync_legacy(mut caller: wasmi::Caller<'_, GuestAPI>, state_ptr: i32, state_len: i32) -> Result<(), wasmi::core::Trap> {
+    let mem = get_memory(&mut caller)?;
+    let state = load_string_from_vm(&mut caller.as_context_mut(), &mem, state_ptr, state_len)?;
+
+    match tokio::runtime::Handle::current().block_on(caller.data_mut().host.sync(&state)) {
+        Ok(_) | Err(crate::base_runtime::guest_api::GuestAPIError::StateConflict(_)) => Ok(()),
+        Err(_) => Err(wasmi::core::Trap::new("sync error")),
+    }
 }
 
 pub fn slf(mut caller: wasmi::Caller<'_, GuestAPI>, out_node_id_ptr: i32, out_component_id_ptr: i32) -> Result<(), wasmi::core::Trap> {
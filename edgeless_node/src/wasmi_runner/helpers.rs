@@ -37,6 +37,19 @@ pub(crate) fn load_string_from_vm(
         .map_err(|_| wasmi::core::Trap::new("string error"))
 }
 
+pub(crate) fn load_bytes_from_vm(
+    ctx: &mut wasmi::StoreContextMut<'_, super::guest_api_binding::GuestAPI>,
+    memory: &wasmi::Memory,
+    data_ptr: i32,
+    data_len: i32,
+) -> Result<Vec<u8>, wasmi::core::Trap> {
+    memory
+        .data_mut(ctx)
+        .get(data_ptr as usize..(data_ptr as usize) + data_len as usize)
+        .map(|data| data.to_vec())
+        .ok_or(wasmi::core::Trap::new("memory error"))
+}
+
 pub(crate) fn level_from_i32(lvl: i32) -> edgeless_telemetry::telemetry_events::TelemetryLogLevel {
     match lvl {
         1 => edgeless_telemetry::telemetry_events::TelemetryLogLevel::Error,
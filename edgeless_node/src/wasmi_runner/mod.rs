@@ -45,6 +45,8 @@ pub struct WASMIFunctionInstance {
         (),
     >,
     edgefunctione_handle_stop: wasmi::TypedFunc<(), ()>,
+    // Not exported by functions built before handle_sync was introduced.
+    edgefunctione_handle_sync: Option<wasmi::TypedFunc<(), ()>>,
     memory: wasmi::Memory,
     store: wasmi::Store<guest_api_binding::GuestAPI>,
 }
@@ -95,8 +97,18 @@ impl crate::base_runtime::FunctionInstance for WASMIFunctionInstance {
         linker
             .define("env", "delayed_cast_asm", wasmi::Func::wrap(&mut store, guest_api_binding::delayed_cast))
             .map_err(|_| crate::base_runtime::FunctionInstanceError::InternalError)?;
+        // Functions built before sync_asm returned a status import it
+        // without the output pointers.
+        let legacy_sync = module
+            .imports()
+            .any(|import| import.name() == "sync_asm" && matches!(import.ty(), wasmi::ExternType::Func(ty) if ty.params().len() == 2));
+        let sync = if legacy_sync {
+            wasmi::Func::wrap(&mut store, guest_api_binding::sync_legacy)
+        } else {
+            wasmi::Func::wrap(&mut store, guest_api_binding::sync)
+        };
         linker
-            .define("env", "sync_asm", wasmi::Func::wrap(&mut store, guest_api_binding::sync))
+            .define("env", "sync_asm", sync)
             .map_err(|_| crate::base_runtime::FunctionInstanceError::InternalError)?;
 
         let instance = linker
@@ -127,6 +139,7 @@ impl crate::base_runtime::FunctionInstance for WASMIFunctionInstance {
             edgefunctione_handle_stop: instance
                 .get_typed_func::<(), ()>(&mut store, "handle_stop_asm")
                 .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("handle_stop_asm not available: {}", e)))?,
+            edgefunctione_handle_sync: instance.get_typed_func::<(), ()>(&mut store, "handle_sync_asm").ok(),
             memory: instance
                 .get_memory(&mut store, "memory")
                 .ok_or_else(|| crate::base_runtime::FunctionInstanceError::BadCode("memory not available".to_string()))?,
@@ -185,7 +198,11 @@ impl crate::base_runtime::FunctionInstance for WASMIFunctionInstance {
         ret
     }
 
-    async fn cast(&mut self, src: &edgeless_api::function_instance::InstanceId, msg: &str) -> Result<(), crate::base_runtime::FunctionInstanceError> {
+    async fn cast(
+        &mut self,
+        src: &edgeless_api::function_instance::InstanceId,
+        msg: &[u8],
+    ) -> Result<(), crate::base_runtime::FunctionInstanceError> {
         // Depending on the Function, we might employ a basic arena/bump allocator that we must reset at the end of a transaction.
         // This might be a noop if the function defines a working version of `edgeless_mem_free`.
         self.edgeless_mem_clear
@@ -208,7 +225,7 @@ impl crate::base_runtime::FunctionInstance for WASMIFunctionInstance {
         .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("cast failed: {}", e)))?;
 
         let payload_len = msg.len();
-        let payload_ptr = helpers::copy_to_vm(&mut self.store.as_context_mut(), &self.memory, &self.edgeless_mem_alloc, msg.as_bytes())
+        let payload_ptr = helpers::copy_to_vm(&mut self.store.as_context_mut(), &self.memory, &self.edgeless_mem_alloc, msg)
             .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("cast failed: {}", e)))?;
 
         let ret = tokio::task::block_in_place(|| {
@@ -235,7 +252,7 @@ impl crate::base_runtime::FunctionInstance for WASMIFunctionInstance {
     async fn call(
         &mut self,
         src: &edgeless_api::function_instance::InstanceId,
-        msg: &str,
+        msg: &[u8],
     ) -> Result<edgeless_dataplane::core::CallRet, crate::base_runtime::FunctionInstanceError> {
         self.edgeless_mem_clear
             .call(&mut self.store, ())
@@ -258,7 +275,7 @@ impl crate::base_runtime::FunctionInstance for WASMIFunctionInstance {
         .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("call failed: {}", e)))?;
 
         let payload_len = msg.len();
-        let payload_ptr = helpers::copy_to_vm(&mut self.store.as_context_mut(), &self.memory, &self.edgeless_mem_alloc, msg.as_bytes())
+        let payload_ptr = helpers::copy_to_vm(&mut self.store.as_context_mut(), &self.memory, &self.edgeless_mem_alloc, msg)
             .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("call failed: {}", e)))?;
 
         let out_ptr_ptr = self
@@ -297,10 +314,9 @@ impl crate::base_runtime::FunctionInstance for WASMIFunctionInstance {
 
                 // load the atual output param
                 let out_raw = self.memory.data_mut(&mut self.store)[out_ptr as usize..(out_ptr as usize) + out_len as usize].to_vec();
-                // TODO(raphaelhetzel) This unwrap can be removed after we migrate the dataplane to use string slices.
-                let out = std::string::String::from_utf8(out_raw).unwrap();
-                Ok(edgeless_dataplane::core::CallRet::Reply(out))
+                Ok(edgeless_dataplane::core::CallRet::Reply(out_raw))
             }
+            3 => Ok(edgeless_dataplane::core::CallRet::Timeout),
             _ => Ok(edgeless_dataplane::core::CallRet::Err),
         };
 
@@ -338,4 +354,15 @@ impl crate::base_runtime::FunctionInstance for WASMIFunctionInstance {
                 .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("stop failed: {}", e)))
         })
     }
+
+    async fn sync(&mut self) -> Result<(), crate::base_runtime::FunctionInstanceError> {
+        match &self.edgefunctione_handle_sync {
+            Some(handle_sync) => tokio::task::block_in_place(|| {
+                handle_sync
+                    .call(&mut self.store, ())
+                    .map_err(|e| crate::base_runtime::FunctionInstanceError::BadCode(format!("sync failed: {}", e)))
+            }),
+            None => Ok(()),
+        }
+    }
 }
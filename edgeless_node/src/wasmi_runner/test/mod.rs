@@ -39,12 +39,12 @@ impl crate::state_management::StateManagerAPI for MockStateMananger {
         &mut self,
         _state_policy: edgeless_api::function_instance::StatePolicy,
         state_id: uuid::Uuid,
-    ) -> Box<dyn crate::state_management::StateHandleAPI> {
-        Box::new(MockStateHandle {
+    ) -> anyhow::Result<Box<dyn crate::state_management::StateHandleAPI>> {
+        Ok(Box::new(MockStateHandle {
             state_id: state_id,
             output_mocks: self.output_mocks.clone(),
             state_set_sender: self.state_set_sender.clone(),
-        })
+        }))
     }
 }
 
@@ -60,8 +60,17 @@ impl crate::state_management::StateHandleAPI for MockStateHandle {
         self.output_mocks.lock().await.get(&self.state_id).cloned()
     }
 
-    async fn set(&mut self, serialized_state: String) {
+    async fn set(&mut self, serialized_state: String) -> anyhow::Result<()> {
         self.state_set_sender.send((self.state_id.clone(), serialized_state)).await.unwrap();
+        Ok(())
+    }
+
+    fn version(&self) -> u64 {
+        0
+    }
+
+    async fn set_on(&mut self, serialized_state: String, _expected_version: u64) -> anyhow::Result<()> {
+        self.set(serialized_state).await
     }
 }
 
@@ -74,7 +83,7 @@ async fn basic_lifecycle() {
     let node_id = uuid::Uuid::new_v4();
     let instance_id = edgeless_api::function_instance::InstanceId::new(node_id);
 
-    let state_manager = Box::new(crate::state_management::StateManager::new().await);
+    let state_manager = Box::new(crate::state_management::StateManager::new(&None).await);
     let dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7002".to_string(), None).await;
 
     let (telemetry_mock_sender, telemetry_mock_receiver) = std::sync::mpsc::channel::<(
@@ -197,7 +206,7 @@ async fn messaging_test_setup() -> (InstanceId, DataplaneHandle, InstanceId, Dat
     let node_id = uuid::Uuid::new_v4();
     let instance_id = edgeless_api::function_instance::InstanceId::new(node_id);
 
-    let state_manager = Box::new(crate::state_management::StateManager::new().await);
+    let state_manager = Box::new(crate::state_management::StateManager::new(&None).await);
     let mut dataplane_provider = edgeless_dataplane::handle::DataplaneProvider::new(node_id, "http://127.0.0.1:7002".to_string(), None).await;
 
     // shared insert
@@ -247,6 +256,7 @@ async fn messaging_test_setup() -> (InstanceId, DataplaneHandle, InstanceId, Dat
         .patch(PatchRequest {
             function_id: instance_id.function_id.clone(),
             output_mapping: std::collections::HashMap::from([("test".to_string(), next_fid.clone())]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await;
 
@@ -320,10 +330,7 @@ async fn messaging_cast_raw_output() {
     println!("Wait");
     let test_message = test_peer_handle.receive_next().await;
     assert_eq!(test_message.source_id, instance_id);
-    assert_eq!(
-        test_message.message,
-        edgeless_dataplane::core::Message::Cast("cast_raw_output".to_string())
-    );
+    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Cast(b"cast_raw_output".to_vec()));
     assert_eq!(test_message.metadata, metad_1);
 }
 
@@ -345,11 +352,8 @@ async fn messaging_call_raw_output() {
 
     let test_message = test_peer_handle.receive_next().await;
     assert_eq!(test_message.source_id, instance_id);
-    assert_eq!(
-        test_message.message,
-        edgeless_dataplane::core::Message::Call("call_raw_output".to_string())
-    );
-    assert_eq!(&test_message.metadata, &metad_1);
+    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Call(b"call_raw_output".to_vec()));
+    assert_eq!(&test_message.metadata.clone().with_deadline(None), &metad_1);
 
     test_peer_handle
         .reply(test_message.source_id, test_message.channel_id, CallRet::NoReply, &test_message.metadata)
@@ -377,7 +381,7 @@ async fn messaging_delayed_cast_output() {
     assert_eq!(test_message.source_id, instance_id);
     assert_eq!(
         test_message.message,
-        edgeless_dataplane::core::Message::Cast("delayed_cast_output".to_string())
+        edgeless_dataplane::core::Message::Cast(b"delayed_cast_output".to_vec())
     );
     assert_eq!(&test_message.metadata, &metad_1);
 
@@ -403,7 +407,7 @@ async fn messaging_cast_output() {
 
     let test_message = next_handle.receive_next().await;
     assert_eq!(test_message.source_id, instance_id);
-    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Cast("cast_output".to_string()));
+    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Cast(b"cast_output".to_vec()));
     assert_eq!(&test_message.metadata, &metad_1);
 }
 
@@ -423,8 +427,8 @@ async fn messaging_call_output() {
 
     let test_message = next_handle.receive_next().await;
     assert_eq!(test_message.source_id, instance_id);
-    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Call("call_output".to_string()));
-    assert_eq!(&test_message.metadata, &metad_1);
+    assert_eq!(test_message.message, edgeless_dataplane::core::Message::Call(b"call_output".to_vec()));
+    assert_eq!(&test_message.metadata.clone().with_deadline(None), &metad_1);
 
     next_handle
         .reply(test_message.source_id, test_message.channel_id, CallRet::NoReply, &metad_1)
@@ -456,7 +460,7 @@ async fn messaging_call_raw_input_reply() {
     let metad_1 = edgeless_api::function_instance::EventMetadata::from_uints(0x42a42bdecaf00049u128, 0x42a42bdecaf0004au64);
 
     let ret = test_peer_handle.call(instance_id.clone(), "test_ret".to_string(), &metad_1).await;
-    assert_eq!(ret, CallRet::Reply("test_reply".to_string()));
+    assert_eq!(ret, CallRet::Reply(b"test_reply".to_vec()));
 
     assert!(is_telemetry_event_transfer(&mut telemetry_mock_receiver).await);
     assert!(is_telemetry_event_invocation_complete(&mut telemetry_mock_receiver).await);
@@ -0,0 +1,44 @@
+// SPDX-FileCopyrightText: © 2024 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum AntiAffinity {
+    /// Function instances can share the same node.
+    None,
+    /// Two instances of the same function cannot share the same node.
+    Replicas,
+    /// An instance of the function cannot share the same node with any other
+    /// function instance of the same workflow.
+    Workflow,
+}
+
+impl std::fmt::Display for AntiAffinity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AntiAffinity::None => "none",
+                AntiAffinity::Replicas => "replicas",
+                AntiAffinity::Workflow => "workflow",
+            }
+        )
+    }
+}
+
+impl AntiAffinity {
+    /// Parse the anti-affinity from a string, case-insensitive.
+    ///
+    /// An unknown value is reported and treated as `none`.
+    pub fn from_string(val: &str) -> Self {
+        match val.to_lowercase().as_str() {
+            "none" | "" => AntiAffinity::None,
+            "replicas" => AntiAffinity::Replicas,
+            "workflow" => AntiAffinity::Workflow,
+            _ => {
+                log::warn!("unknown anti_affinity '{}', ignored: the valid values are none, replicas, workflow", val);
+                AntiAffinity::None
+            }
+        }
+    }
+}
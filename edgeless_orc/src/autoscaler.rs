@@ -0,0 +1,283 @@
+// SPDX-FileCopyrightText: © 2024 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Default cooldown period between two consecutive scaling actions of the
+/// same function, in seconds.
+pub const DEFAULT_SCALE_COOLDOWN_SEC: u64 = 30;
+
+/// Fraction of the target latency below which an instance is removed.
+const SCALE_DOWN_THRESHOLD: f64 = 0.5;
+
+/// Minimum window over which the latency is averaged.
+const MIN_LATENCY_WINDOW: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(PartialEq, Debug, Clone)]
+/// Horizontal scaling policy of a function, as specified by annotations.
+pub struct ScalingPolicy {
+    /// Minimum number of function instances.
+    pub min_instances: usize,
+    /// Maximum number of function instances. 0 means unlimited.
+    pub max_instances: usize,
+    /// Target latency, in ms, i.e., the average time to handle an event,
+    /// including the transfer time. If not specified, then the number of
+    /// instances is only kept between the minimum and the maximum.
+    pub target_latency_ms: Option<f64>,
+    /// Minimum time between two consecutive scaling actions.
+    pub scale_cooldown: std::time::Duration,
+}
+
+impl std::fmt::Display for ScalingPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "min_instances {}, max_instances {}, target_latency_ms {}, scale_cooldown_sec {}",
+            self.min_instances,
+            self.max_instances,
+            self.target_latency_ms.map(|x| x.to_string()).unwrap_or(String::from("none")),
+            self.scale_cooldown.as_secs()
+        )
+    }
+}
+
+impl ScalingPolicy {
+    /// Scaling policy from the annotations in the function's spawn request.
+    pub fn from_annotations(annotations: &std::collections::HashMap<String, String>) -> Self {
+        let mut min_instances = 1;
+        if let Some(val) = annotations.get("min_instances") {
+            min_instances = val.parse::<usize>().unwrap_or(1).max(1);
+        }
+
+        let max_instances = crate::deployment_requirements::DeploymentRequirements::from_annotations(annotations).max_instances;
+
+        let mut target_latency_ms = None;
+        if let Some(val) = annotations.get("target_latency_ms") {
+            target_latency_ms = val.parse::<f64>().ok().filter(|x| *x > 0.0);
+        }
+
+        let mut scale_cooldown_sec = DEFAULT_SCALE_COOLDOWN_SEC;
+        if let Some(val) = annotations.get("scale_cooldown_sec") {
+            scale_cooldown_sec = val.parse::<u64>().unwrap_or(DEFAULT_SCALE_COOLDOWN_SEC);
+        }
+
+        Self {
+            min_instances,
+            max_instances,
+            target_latency_ms,
+            scale_cooldown: std::time::Duration::from_secs(scale_cooldown_sec),
+        }
+    }
+
+    /// Return true if the function may run more than one instance.
+    pub fn is_scalable(&self) -> bool {
+        self.min_instances > 1 || (self.target_latency_ms.is_some() && self.max_instances != 1)
+    }
+
+    /// Return the window over which the latency is averaged, which is the
+    /// cooldown period, so that only the samples taken after the last
+    /// scaling action are considered, but not shorter than 5 s.
+    pub fn latency_window(&self) -> std::time::Duration {
+        self.scale_cooldown.max(MIN_LATENCY_WINDOW)
+    }
+
+    /// Return the number of instances closest to the given one that is
+    /// within the minimum and the maximum.
+    fn bounded(&self, num_instances: usize) -> usize {
+        let num_instances = num_instances.max(self.min_instances);
+        if self.max_instances == 0 {
+            num_instances
+        } else {
+            num_instances.min(self.max_instances.max(self.min_instances))
+        }
+    }
+}
+
+/// Decides when to add or remove instances of the functions with a scaling
+/// policy, based on their latency.
+///
+/// The number of instances is changed by one at a time:
+/// - an instance is added if the latency is above the target;
+/// - an instance is removed if the latency is below a fraction of the target.
+///
+/// After a scaling action, the function is not scaled again until the
+/// cooldown period expires, so that the latency with the new number of
+/// instances can be measured. The number of instances is brought within the
+/// minimum and the maximum immediately, instead.
+#[derive(Default)]
+pub struct Autoscaler {
+    // key: lid
+    // value: time of the last scaling action
+    last_scaled: std::collections::HashMap<edgeless_api::function_instance::ComponentId, std::time::Instant>,
+}
+
+impl Autoscaler {
+    /// Return the target number of instances of a function.
+    ///
+    /// * `lid` - The logical identifier of the function.
+    /// * `policy` - The scaling policy of the function.
+    /// * `num_instances` - The current number of instances.
+    /// * `latency` - The average latency, in s, if measured.
+    /// * `now` - The current time.
+    pub fn target_instances(
+        &mut self,
+        lid: &edgeless_api::function_instance::ComponentId,
+        policy: &ScalingPolicy,
+        num_instances: usize,
+        latency: Option<f64>,
+        now: std::time::Instant,
+    ) -> usize {
+        let bounded = policy.bounded(num_instances);
+        if bounded != num_instances {
+            self.last_scaled.insert(*lid, now);
+            return bounded;
+        }
+
+        if let Some(last_scaled) = self.last_scaled.get(lid)
+            && now.duration_since(*last_scaled) < policy.scale_cooldown
+        {
+            return num_instances;
+        }
+
+        let target = match (policy.target_latency_ms, latency) {
+            (Some(target_latency_ms), Some(latency)) => {
+                let latency_ms = latency * 1000.0;
+                if latency_ms > target_latency_ms {
+                    policy.bounded(num_instances + 1)
+                } else if latency_ms < target_latency_ms * SCALE_DOWN_THRESHOLD {
+                    policy.bounded(num_instances - 1)
+                } else {
+                    num_instances
+                }
+            }
+            _ => num_instances,
+        };
+        if target != num_instances {
+            self.last_scaled.insert(*lid, now);
+        }
+        target
+    }
+
+    /// Forget about a function that is not active anymore.
+    pub fn remove(&mut self, lid: &edgeless_api::function_instance::ComponentId) {
+        self.last_scaled.remove(lid);
+    }
+}
+
+/// Return the average of the samples in a performance series taken after
+/// a given time, if any.
+pub fn average_since(series: &[(chrono::DateTime<chrono::Utc>, String)], since: &chrono::DateTime<chrono::Utc>) -> Option<f64> {
+    let samples = series
+        .iter()
+        .filter(|(timestamp, _value)| timestamp > since)
+        .filter_map(|(_timestamp, value)| value.parse::<f64>().ok())
+        .collect::<Vec<f64>>();
+    if samples.is_empty() {
+        None
+    } else {
+        Some(samples.iter().sum::<f64>() / samples.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn policy(annotations: Vec<(&str, &str)>) -> ScalingPolicy {
+        ScalingPolicy::from_annotations(&annotations.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+    }
+
+    #[test]
+    fn test_scaling_policy_from_annotations() {
+        let default_policy = policy(vec![]);
+        assert_eq!(
+            ScalingPolicy {
+                min_instances: 1,
+                max_instances: 0,
+                target_latency_ms: None,
+                scale_cooldown: std::time::Duration::from_secs(DEFAULT_SCALE_COOLDOWN_SEC),
+            },
+            default_policy
+        );
+        assert!(!default_policy.is_scalable());
+
+        let full_policy = policy(vec![
+            ("min_instances", "2"),
+            ("max_instances", "5"),
+            ("target_latency_ms", "100"),
+            ("scale_cooldown_sec", "10"),
+        ]);
+        assert_eq!(
+            ScalingPolicy {
+                min_instances: 2,
+                max_instances: 5,
+                target_latency_ms: Some(100.0),
+                scale_cooldown: std::time::Duration::from_secs(10),
+            },
+            full_policy
+        );
+        assert!(full_policy.is_scalable());
+
+        assert!(!policy(vec![("target_latency_ms", "100"), ("max_instances", "1")]).is_scalable());
+        assert!(policy(vec![("min_instances", "3")]).is_scalable());
+
+        let invalid_policy = policy(vec![("min_instances", "0"), ("target_latency_ms", "-1"), ("scale_cooldown_sec", "x")]);
+        assert_eq!(1, invalid_policy.min_instances);
+        assert_eq!(None, invalid_policy.target_latency_ms);
+        assert_eq!(std::time::Duration::from_secs(DEFAULT_SCALE_COOLDOWN_SEC), invalid_policy.scale_cooldown);
+    }
+
+    #[test]
+    fn test_autoscaler_target_instances() {
+        let lid = uuid::Uuid::new_v4();
+        let policy = policy(vec![
+            ("min_instances", "2"),
+            ("max_instances", "4"),
+            ("target_latency_ms", "100"),
+            ("scale_cooldown_sec", "10"),
+        ]);
+        let mut autoscaler = Autoscaler::default();
+        let t0 = std::time::Instant::now();
+        let secs = |x| t0 + std::time::Duration::from_secs(x);
+
+        // Below the minimum: scale up irrespective of the latency.
+        assert_eq!(2, autoscaler.target_instances(&lid, &policy, 1, Some(0.01), t0));
+
+        // Cooldown.
+        assert_eq!(2, autoscaler.target_instances(&lid, &policy, 2, Some(0.2), secs(5)));
+
+        // Latency above the target: add one instance.
+        assert_eq!(3, autoscaler.target_instances(&lid, &policy, 2, Some(0.2), secs(10)));
+        assert_eq!(4, autoscaler.target_instances(&lid, &policy, 3, Some(0.2), secs(20)));
+
+        // Maximum reached.
+        assert_eq!(4, autoscaler.target_instances(&lid, &policy, 4, Some(0.2), secs(30)));
+
+        // Latency close to the target or unknown: no change.
+        assert_eq!(4, autoscaler.target_instances(&lid, &policy, 4, Some(0.08), secs(40)));
+        assert_eq!(4, autoscaler.target_instances(&lid, &policy, 4, None, secs(40)));
+
+        // Latency well below the target: remove one instance, down to the minimum.
+        assert_eq!(3, autoscaler.target_instances(&lid, &policy, 4, Some(0.01), secs(40)));
+        assert_eq!(3, autoscaler.target_instances(&lid, &policy, 3, Some(0.01), secs(45)));
+        assert_eq!(2, autoscaler.target_instances(&lid, &policy, 3, Some(0.01), secs(50)));
+        assert_eq!(2, autoscaler.target_instances(&lid, &policy, 2, Some(0.01), secs(60)));
+
+        // Above the maximum: scale down immediately.
+        assert_eq!(4, autoscaler.target_instances(&lid, &policy, 6, None, secs(61)));
+    }
+
+    #[test]
+    fn test_average_since() {
+        let t0 = chrono::Utc::now();
+        let secs = |x| t0 + chrono::Duration::seconds(x);
+        let series = vec![
+            (secs(1), "1.0".to_string()),
+            (secs(2), "2.0".to_string()),
+            (secs(3), "invalid".to_string()),
+            (secs(4), "6.0".to_string()),
+        ];
+        assert_eq!(Some(3.0), average_since(&series, &t0));
+        assert_eq!(Some(4.0), average_since(&series, &secs(1)));
+        assert_eq!(None, average_since(&series, &secs(4)));
+        assert_eq!(None, average_since(&[], &t0));
+    }
+}
@@ -72,6 +72,7 @@ enum ShowCommands {
     Resources {},
     LogicalToPhysical {},
     LogicalToWorkflow {},
+    Splits {},
     Node {
         #[command(subcommand)]
         node_command: NodeCommands,
@@ -338,6 +339,36 @@ async fn main() -> anyhow::Result<()> {
                     );
                 }
             }
+            ShowCommands::Splits {} => {
+                let lid_to_pid = proxy.fetch_instances_to_physical_ids();
+                for (logical, splits) in proxy.fetch_output_splits().iter().sorted_by_key(|x| x.0.to_string()) {
+                    for (channel, targets) in splits.iter().sorted_by_key(|x| x.0.to_string()) {
+                        let total_weight = targets.iter().map(|(_target, weight)| *weight as f64).sum::<f64>();
+                        println!(
+                            "{} {} -> {}",
+                            logical,
+                            channel,
+                            targets
+                                .iter()
+                                .map(|(target, weight)| format!(
+                                    "{} ({:.1}%) [{}]",
+                                    target,
+                                    if total_weight > 0.0 {
+                                        100.0 * *weight as f64 / total_weight
+                                    } else {
+                                        0.0
+                                    },
+                                    lid_to_pid
+                                        .get(target)
+                                        .map(|pids| pids.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(","))
+                                        .unwrap_or_default()
+                                ))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        );
+                    }
+                }
+            }
             ShowCommands::LogicalToWorkflow {} => {
                 for (logical, workflow_id) in proxy.fetch_logical_id_to_workflow_id().iter().sorted_by_key(|x| x.0.to_string()) {
                     println!("{} -> {}", logical, workflow_id);
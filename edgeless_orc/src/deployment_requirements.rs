@@ -20,19 +20,50 @@ pub struct DeploymentRequirements {
     pub tee: crate::affinity_level::AffinityLevel,
     /// Function instance's node affinity with Trusted Platform Module.
     pub tpm: crate::affinity_level::AffinityLevel,
+    /// Function instances with which the function instance cannot share
+    /// the same node.
+    pub anti_affinity: crate::anti_affinity::AntiAffinity,
+    /// The function instances must be spread evenly across the values of the
+    /// given label, if any is given, i.e., they must be created on nodes with
+    /// labels `<key>=<value>` for the value that hosts the fewest instances of
+    /// the same function, or of the functions in the same workflow with the
+    /// same label key.
+    pub spread_by_label: Option<String>,
+}
+
+/// Constraints for the creation of a function instance that depend on the
+/// current placement of the other function instances.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct PlacementConstraints {
+    /// Nodes where the function instance cannot be created, due to anti-affinity.
+    pub excluded_nodes: std::collections::HashSet<uuid::Uuid>,
+    /// Values of the spread label of the nodes where the function instance
+    /// can be created, if the function has a spread requirement.
+    pub spread_values: Option<std::collections::HashSet<String>>,
+}
+
+/// Return the value of the label with given key of a node, i.e., `<value>`
+/// if the node has a label `<key>=<value>`.
+pub fn label_value<'a>(capabilities: &'a edgeless_api::node_registration::NodeCapabilities, key: &str) -> Option<&'a str> {
+    capabilities
+        .labels
+        .iter()
+        .find_map(|label| label.strip_prefix(key).and_then(|rest| rest.strip_prefix('=')))
 }
 
 impl std::fmt::Display for DeploymentRequirements {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "max_instances {}, node_id_match_any {}, label_match_all {}, resource_match_all {}, tee {}, tpm {}",
+            "max_instances {}, node_id_match_any {}, label_match_all {}, resource_match_all {}, tee {}, tpm {}, anti_affinity {}, spread_by_label {}",
             self.max_instances,
             self.node_id_match_any.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(","),
             self.label_match_all.join(","),
             self.resource_match_all.join(","),
             self.tee,
-            self.tpm
+            self.tpm,
+            self.anti_affinity,
+            self.spread_by_label.as_deref().unwrap_or_default()
         )
     }
 }
@@ -48,6 +79,8 @@ impl DeploymentRequirements {
             resource_match_all: vec![],
             tee: crate::affinity_level::AffinityLevel::NotRequired,
             tpm: crate::affinity_level::AffinityLevel::NotRequired,
+            anti_affinity: crate::anti_affinity::AntiAffinity::None,
+            spread_by_label: None,
         }
     }
     /// Deployment requirements from the annotations in the function's spawn request.
@@ -82,6 +115,18 @@ impl DeploymentRequirements {
             tpm = crate::affinity_level::AffinityLevel::from_string(val);
         }
 
+        let mut anti_affinity = crate::anti_affinity::AntiAffinity::None;
+        if let Some(val) = annotations.get("anti_affinity") {
+            anti_affinity = crate::anti_affinity::AntiAffinity::from_string(val);
+        }
+
+        let mut spread_by_label = None;
+        if let Some(val) = annotations.get("spread_by_label")
+            && !val.is_empty()
+        {
+            spread_by_label = Some(val.clone());
+        }
+
         Self {
             max_instances,
             node_id_match_any,
@@ -89,6 +134,8 @@ impl DeploymentRequirements {
             resource_match_all,
             tee,
             tpm,
+            anti_affinity,
+            spread_by_label,
         }
     }
 
@@ -99,12 +146,29 @@ impl DeploymentRequirements {
     /// - `node_id`: ID of this node.
     /// - `capabilities`: capabilities of this node.
     /// - `resource_providers`: resource providers offered by this node.
+    /// - `constraints`: constraints due to the placement of other instances.
     pub fn is_feasible(
         &self,
         node_id: &uuid::Uuid,
         capabilities: &edgeless_api::node_registration::NodeCapabilities,
         resource_providers: &std::collections::HashSet<String>,
+        constraints: &PlacementConstraints,
     ) -> bool {
+        if constraints.excluded_nodes.contains(node_id) {
+            return false;
+        }
+        if let Some(key) = &self.spread_by_label {
+            match label_value(capabilities, key) {
+                Some(value) => {
+                    if let Some(spread_values) = &constraints.spread_values
+                        && !spread_values.contains(value)
+                    {
+                        return false;
+                    }
+                }
+                None => return false,
+            }
+        }
         if !self.node_id_match_any.is_empty() && !self.node_id_match_any.contains(node_id) {
             return false;
         }
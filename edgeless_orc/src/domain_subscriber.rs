@@ -13,6 +13,8 @@ pub struct DomainSubscriber {
 #[derive(Clone)]
 pub enum DomainSubscriberRequest {
     Update(Box<edgeless_api::domain_registration::DomainCapabilities>),
+    // Logical identifiers of the active functions and resources.
+    UpdateActiveInstances(std::collections::HashSet<uuid::Uuid>, std::collections::HashSet<uuid::Uuid>),
     RegisterOrcSender(futures::channel::mpsc::UnboundedSender<crate::orchestrator::OrchestratorRequest>),
     Refresh(),
 }
@@ -79,6 +81,8 @@ impl DomainSubscriber {
         .domain_registration_api();
         let mut last_caps = edgeless_api::domain_registration::DomainCapabilities::default();
         let mut counter = 0;
+        let mut active_functions = std::collections::HashSet::new();
+        let mut active_resources = std::collections::HashSet::new();
         let mut orc_sender = None;
 
         while let Some(req) = receiver.next().await {
@@ -88,6 +92,15 @@ impl DomainSubscriber {
                     counter += 1;
                     last_caps = *new_caps;
                 }
+                DomainSubscriberRequest::UpdateActiveInstances(new_active_functions, new_active_resources) => {
+                    log::debug!(
+                        "Subscriber UpdateActiveInstances: {} functions, {} resources",
+                        new_active_functions.len(),
+                        new_active_resources.len()
+                    );
+                    active_functions = new_active_functions;
+                    active_resources = new_active_resources;
+                }
                 DomainSubscriberRequest::RegisterOrcSender(new_orc_sender) => {
                     log::info!("Orchestrator registered with domain subscriber");
                     orc_sender = Some(new_orc_sender);
@@ -104,6 +117,8 @@ impl DomainSubscriber {
                         refresh_deadline: std::time::SystemTime::now() + std::time::Duration::from_secs(subscription_refresh_interval_sec * 2),
                         counter,
                         nonce,
+                        active_functions: active_functions.clone(),
+                        active_resources: active_resources.clone(),
                     };
                     match domain_registration_api.update_domain(update_domain_request).await {
                         Ok(response) => {
@@ -5,6 +5,8 @@
 
 pub mod active_instance;
 pub mod affinity_level;
+pub mod anti_affinity;
+pub mod autoscaler;
 pub mod client_desc;
 pub mod deploy_intent;
 pub mod deployment_requirements;
@@ -15,6 +17,7 @@ pub mod node_register_client;
 pub mod orchestration_logic;
 pub mod orchestrator;
 pub mod orchestrator_task;
+pub mod placement;
 pub mod proxy;
 pub mod proxy_none;
 pub mod proxy_redis;
@@ -98,6 +101,11 @@ pub enum OrchestrationStrategy {
     /// RoundRobin traverses the list of available worker nodes in a fixed order
     /// and places new function instances according to this fixed order.
     RoundRobin,
+    /// LeastLoaded selects the worker node with the smallest load, which
+    /// accounts for the CPU load and memory usage reported by the node in its
+    /// health status, as well as for the number of function instances that
+    /// are already assigned to it, relative to its number of cores.
+    LeastLoaded,
 }
 
 pub fn make_proxy(settings: EdgelessOrcProxySettings) -> std::sync::Arc<tokio::sync::Mutex<dyn proxy::Proxy>> {
@@ -170,6 +170,12 @@ impl NodeRegister {
                         }
                         }
 
+                        // Notify the orchestrator of the node's health status.
+                        let _ = orchestrator_sender.send(crate::orchestrator::OrchestratorRequest::UpdateNodeHealth(
+                            request.node_id,
+                            request.health_status.clone()
+                        )).await;
+
                         // Push the dynamic data to the proxy.
                         let mut proxy = proxy.lock().await;
                         proxy.push_node_health(&request.node_id, request.health_status);
@@ -23,6 +23,13 @@ pub struct OrchestrationLogic {
     resource_providers: Vec<std::collections::HashSet<String>>,
     /// Used by Random, pair of (weight, node_id).
     weights: Vec<f32>,
+    /// Used by LeastLoaded. Last health status received from each node.
+    health_status: std::collections::HashMap<uuid::Uuid, edgeless_api::node_registration::NodeHealthStatus>,
+    /// Used by LeastLoaded. Number of function instances assigned to each node.
+    num_instances: std::collections::HashMap<uuid::Uuid, usize>,
+    /// Used by the locality-aware placement. Last transfer latency, in s,
+    /// measured from the first node to the second one.
+    transfer_latencies: std::collections::HashMap<(uuid::Uuid, uuid::Uuid), f64>,
 }
 
 impl OrchestrationLogic {
@@ -30,6 +37,7 @@ impl OrchestrationLogic {
         match orchestration_strategy {
             crate::OrchestrationStrategy::Random => log::info!("Orchestration logic strategy: random"),
             crate::OrchestrationStrategy::RoundRobin => log::info!("Orchestration logic strategy: round-robin"),
+            crate::OrchestrationStrategy::LeastLoaded => log::info!("Orchestration logic strategy: least-loaded"),
         };
 
         Self {
@@ -40,6 +48,9 @@ impl OrchestrationLogic {
             capabilities: vec![],
             resource_providers: vec![],
             weights: vec![],
+            health_status: std::collections::HashMap::new(),
+            num_instances: std::collections::HashMap::new(),
+            transfer_latencies: std::collections::HashMap::new(),
         }
     }
 
@@ -78,10 +89,119 @@ impl OrchestrationLogic {
         assert!(self.nodes.len() == self.resource_providers.len());
         assert!(self.nodes.len() == self.weights.len());
         assert!(self.nodes.len() <= clients.len());
+
+        // Forget about the health status and transfer latencies of the nodes that are gone.
+        self.health_status.retain(|node_id, _| clients.contains_key(node_id));
+        self.transfer_latencies
+            .retain(|(origin, target), _| clients.contains_key(origin) && clients.contains_key(target));
+    }
+
+    /// Update the last health status received from a node.
+    pub fn update_node_health(&mut self, node_id: &uuid::Uuid, health_status: edgeless_api::node_registration::NodeHealthStatus) {
+        self.health_status.insert(*node_id, health_status);
+    }
+
+    /// Update the number of function instances currently assigned to each
+    /// node. The nodes that are not included are assumed to host none.
+    pub fn update_placement(&mut self, num_instances: std::collections::HashMap<uuid::Uuid, usize>) {
+        self.num_instances = num_instances;
+    }
+
+    /// Update the transfer latency, in s, measured from a node to another.
+    pub fn update_transfer_latency(&mut self, origin: &uuid::Uuid, target: &uuid::Uuid, latency: f64) {
+        self.transfer_latencies.insert((*origin, *target), latency);
+    }
+
+    /// Return the transfer latency, in s, from a node to another, which is
+    /// the last one measured, if any, or otherwise zero if the nodes are
+    /// the same and `DEFAULT_TRANSFER_LATENCY` if they are different.
+    pub fn transfer_latency(&self, origin: &uuid::Uuid, target: &uuid::Uuid) -> f64 {
+        match self.transfer_latencies.get(&(*origin, *target)) {
+            Some(latency) => *latency,
+            None if origin == target => 0.0,
+            None => crate::placement::DEFAULT_TRANSFER_LATENCY,
+        }
+    }
+
+    /// Return the cost of placing a function instance on a given node, which
+    /// is the sum of the transfer latencies from the nodes hosting the
+    /// components sending events to it and to the nodes hosting the
+    /// components receiving events from it.
+    ///
+    /// * `node_id` - The candidate node.
+    /// * `upstream` - The nodes hosting the components sending events, with repetitions.
+    /// * `downstream` - The nodes hosting the components receiving events, with repetitions.
+    pub fn locality_cost(&self, node_id: &uuid::Uuid, upstream: &[uuid::Uuid], downstream: &[uuid::Uuid]) -> f64 {
+        upstream.iter().map(|origin| self.transfer_latency(origin, node_id)).sum::<f64>()
+            + downstream.iter().map(|target| self.transfer_latency(node_id, target)).sum::<f64>()
+    }
+
+    /// Select the node on which a function instance should be spawned so as
+    /// to minimize its locality cost, breaking ties by selecting the least
+    /// loaded node. If there are no upstream and downstream nodes, then the
+    /// orchestration strategy in the settings is used.
+    /// Always match the deployment requirements specified with the nodes'
+    /// capabilities.
+    ///
+    /// * `spawn_req` - The specifications of the function.
+    /// * `constraints` - The constraints due to the placement of other instances.
+    /// * `upstream` - The nodes hosting the components sending events, with repetitions.
+    /// * `downstream` - The nodes hosting the components receiving events, with repetitions.
+    pub fn next_local(
+        &mut self,
+        spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest,
+        constraints: &crate::deployment_requirements::PlacementConstraints,
+        upstream: &[uuid::Uuid],
+        downstream: &[uuid::Uuid],
+    ) -> Option<uuid::Uuid> {
+        if upstream.is_empty() && downstream.is_empty() {
+            return self.next(spawn_req, constraints);
+        }
+        let reqs = crate::deployment_requirements::DeploymentRequirements::from_annotations(&spawn_req.annotations);
+        (0..self.nodes.len())
+            .filter(|i| {
+                Self::is_node_feasible(
+                    &spawn_req.spec.function_type,
+                    &reqs,
+                    &self.nodes[*i],
+                    &self.capabilities[*i],
+                    &self.resource_providers[*i],
+                    constraints,
+                )
+            })
+            .map(|i| (self.locality_cost(&self.nodes[i], upstream, downstream), self.load(i), self.nodes[i]))
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)).then(a.2.cmp(&b.2)))
+            .map(|(_cost, _load, node_id)| node_id)
+    }
+
+    /// Return the load of the node at the given index, which is the sum of:
+    /// - the 1-minute load average divided by the number of cores;
+    /// - the fraction of memory not available;
+    /// - the number of function instances divided by the number of cores.
+    ///
+    /// The first two terms are zero if the health status of the node is unknown.
+    fn load(&self, ndx: usize) -> f64 {
+        let capabilities = &self.capabilities[ndx];
+        let cores = std::cmp::max(capabilities.num_cores, capabilities.num_cpus).max(1) as f64;
+
+        let mut load = *self.num_instances.get(&self.nodes[ndx]).unwrap_or(&0) as f64 / cores;
+        if let Some(health_status) = self.health_status.get(&self.nodes[ndx]) {
+            load += (health_status.load_avg_1 as f64 / 100.0 / cores).max(0.0);
+            if capabilities.mem_size > 0 {
+                // mem_size is in MiB, mem_available in KiB.
+                load += (1.0 - health_status.mem_available as f64 / (capabilities.mem_size as f64 * 1024.0)).clamp(0.0, 1.0);
+            }
+        }
+        load
     }
 
     /// Filter only the nodes on which the given function can be deployed.
-    pub fn feasible_nodes(&self, spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest, nodes: &Vec<uuid::Uuid>) -> Vec<uuid::Uuid> {
+    pub fn feasible_nodes(
+        &self,
+        spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest,
+        nodes: &Vec<uuid::Uuid>,
+        constraints: &crate::deployment_requirements::PlacementConstraints,
+    ) -> Vec<uuid::Uuid> {
         let mut candidates = vec![];
 
         for candidate in nodes {
@@ -92,6 +212,7 @@ impl OrchestrationLogic {
                     &self.nodes[ndx],
                     &self.capabilities[ndx],
                     &self.resource_providers[ndx],
+                    constraints,
                 )
             {
                 candidates.push(self.nodes[ndx]);
@@ -103,22 +224,86 @@ impl OrchestrationLogic {
 
     /// Return true if it is possible to assign a function requesting a given
     /// run-time and with given deployment requirements to a node with
-    /// given UUID and capabilities.
+    /// given UUID and capabilities, under the given placement constraints.
     pub fn is_node_feasible(
         runtime: &str,
         reqs: &crate::deployment_requirements::DeploymentRequirements,
         node_id: &uuid::Uuid,
         capabilities: &edgeless_api::node_registration::NodeCapabilities,
         resource_providers: &std::collections::HashSet<String>,
+        constraints: &crate::deployment_requirements::PlacementConstraints,
     ) -> bool {
-        capabilities.runtimes.contains(&runtime.to_string()) && reqs.is_feasible(node_id, capabilities, resource_providers)
+        capabilities.runtimes.contains(&runtime.to_string()) && reqs.is_feasible(node_id, capabilities, resource_providers, constraints)
+    }
+
+    /// Return the placement constraints of a function instance.
+    ///
+    /// If the function has a spread requirement, then the values of the
+    /// spread label allowed are those that host the fewest of the given peer
+    /// instances among the nodes where the function instance could be
+    /// created.
+    ///
+    /// * `spawn_req` - The specifications of the function.
+    /// * `excluded_nodes` - The nodes excluded due to anti-affinity.
+    /// * `spread_peers` - The nodes hosting the instances across which the
+    ///   function instances are spread, with repetitions.
+    pub fn placement_constraints(
+        &self,
+        spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest,
+        excluded_nodes: std::collections::HashSet<uuid::Uuid>,
+        spread_peers: &[uuid::Uuid],
+    ) -> crate::deployment_requirements::PlacementConstraints {
+        let mut constraints = crate::deployment_requirements::PlacementConstraints {
+            excluded_nodes,
+            spread_values: None,
+        };
+        let reqs = crate::deployment_requirements::DeploymentRequirements::from_annotations(&spawn_req.annotations);
+        if let Some(key) = &reqs.spread_by_label {
+            // key:   value of the spread label
+            // value: number of peer instances on nodes with that value
+            let mut num_peers = std::collections::HashMap::new();
+            for i in 0..self.nodes.len() {
+                if Self::is_node_feasible(
+                    &spawn_req.spec.function_type,
+                    &reqs,
+                    &self.nodes[i],
+                    &self.capabilities[i],
+                    &self.resource_providers[i],
+                    &constraints,
+                ) && let Some(value) = crate::deployment_requirements::label_value(&self.capabilities[i], key)
+                {
+                    num_peers.insert(value, 0);
+                }
+            }
+            for peer in spread_peers {
+                if let Some(ndx) = self.nodes.iter().position(|x| x == peer)
+                    && let Some(value) = crate::deployment_requirements::label_value(&self.capabilities[ndx], key)
+                    && let Some(cnt) = num_peers.get_mut(value)
+                {
+                    *cnt += 1;
+                }
+            }
+            let min_peers = num_peers.values().min().cloned().unwrap_or_default();
+            constraints.spread_values = Some(
+                num_peers
+                    .into_iter()
+                    .filter(|(_value, cnt)| *cnt == min_peers)
+                    .map(|(value, _cnt)| value.to_string())
+                    .collect(),
+            );
+        }
+        constraints
     }
 
     /// Select the next node on which a function instance should be spawned,
     /// based on a general orchestration strategy as defined in the settings.
     /// Always match the deployment requirements specified with the nodes'
-    /// capabilities.
-    pub fn next(&mut self, spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest) -> Option<uuid::Uuid> {
+    /// capabilities and the placement constraints.
+    pub fn next(
+        &mut self,
+        spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest,
+        constraints: &crate::deployment_requirements::PlacementConstraints,
+    ) -> Option<uuid::Uuid> {
         if self.nodes.is_empty() {
             return None;
         }
@@ -135,6 +320,7 @@ impl OrchestrationLogic {
                         &self.nodes[i],
                         &self.capabilities[i],
                         &self.resource_providers[i],
+                        constraints,
                     ) {
                         candidates.push((i, self.weights[i]));
                         high += self.weights[i];
@@ -170,12 +356,31 @@ impl OrchestrationLogic {
                         &self.nodes[cand_ndx],
                         &self.capabilities[cand_ndx],
                         &self.resource_providers[cand_ndx],
+                        constraints,
                     ) {
                         return Some(self.nodes[cand_ndx]);
                     }
                 }
                 None
             }
+            crate::OrchestrationStrategy::LeastLoaded => {
+                // Select the feasible node with the smallest load, breaking
+                // ties by the node identifier to make the choice deterministic.
+                (0..self.nodes.len())
+                    .filter(|i| {
+                        Self::is_node_feasible(
+                            &spawn_req.spec.function_type,
+                            &reqs,
+                            &self.nodes[*i],
+                            &self.capabilities[*i],
+                            &self.resource_providers[*i],
+                            constraints,
+                        )
+                    })
+                    .map(|i| (self.load(i), self.nodes[i]))
+                    .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+                    .map(|(_load, node_id)| node_id)
+            }
         }
     }
 }
@@ -194,27 +399,48 @@ mod tests {
         let mut caps = edgeless_api::node_registration::NodeCapabilities::minimum();
         let mut providers = std::collections::HashSet::new();
         let mut runtime = "RUST_WASM".to_string();
+        let mut constraints = crate::deployment_requirements::PlacementConstraints::default();
 
         // Empty requirements
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         // Match any node_id
         reqs.node_id_match_any.push(node_id);
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         reqs.node_id_match_any.push(uuid::Uuid::new_v4());
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         reqs.node_id_match_any.clear();
         reqs.node_id_match_any.push(uuid::Uuid::new_v4());
         assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
         reqs.node_id_match_any.clear();
 
@@ -222,82 +448,221 @@ mod tests {
         reqs.label_match_all.push("red".to_string());
         caps.labels.push("green".to_string());
         assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         caps.labels.push("red".to_string());
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         reqs.label_match_all.push("blue".to_string());
         assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         caps.labels.push("blue".to_string());
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         // Match all providers
         reqs.resource_match_all.push("file-1".to_string());
         assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         providers.insert("file-1".to_string());
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         providers.insert("file-2".to_string());
         providers.insert("file-3".to_string());
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         reqs.resource_match_all.push("file-9".to_string());
         assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         providers.insert("file-9".to_string());
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         // Match TEE and TPM
         reqs.tee = AffinityLevel::Required;
         assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
         caps.is_tee_running = true;
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         reqs.tpm = AffinityLevel::Required;
         assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
         caps.has_tpm = true;
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
 
         // Match runtime
         runtime = "CONTAINER".to_string();
         assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
         runtime = "".to_string();
         assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
         runtime = "RUST_WASM".to_string();
         assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
-            &runtime, &reqs, &node_id, &caps, &providers
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
+        ));
+
+        // Anti-affinity
+        constraints.excluded_nodes.insert(uuid::Uuid::new_v4());
+        assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
+        ));
+        constraints.excluded_nodes.insert(node_id);
+        assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
+        ));
+        constraints.excluded_nodes.clear();
+
+        // Spread by label
+        reqs.spread_by_label = Some("zone".to_string());
+        assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
+        ));
+        caps.labels.push("zone=z1".to_string());
+        assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
+        ));
+        constraints.spread_values = Some(std::collections::HashSet::from(["z2".to_string()]));
+        assert!(!crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
+        ));
+        constraints.spread_values = Some(std::collections::HashSet::from(["z1".to_string(), "z2".to_string()]));
+        assert!(crate::orchestration_logic::OrchestrationLogic::is_node_feasible(
+            &runtime,
+            &reqs,
+            &node_id,
+            &caps,
+            &providers,
+            &constraints
         ));
     }
 }
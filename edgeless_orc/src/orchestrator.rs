@@ -53,6 +53,7 @@ pub enum OrchestratorRequest {
         Vec<edgeless_api::node_registration::ResourceProviderSpecification>,
     ),
     DelNode(uuid::Uuid),
+    UpdateNodeHealth(uuid::Uuid, edgeless_api::node_registration::NodeHealthStatus),
     Refresh(
         // Reply Channel
         tokio::sync::oneshot::Sender<()>,
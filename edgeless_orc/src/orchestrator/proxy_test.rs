@@ -5,6 +5,8 @@
 #[derive(Default)]
 pub struct ProxyTest {
     intents: Vec<crate::deploy_intent::DeployIntent>,
+    // key: (node, series)
+    pub performance_series: std::collections::HashMap<(String, String), crate::proxy::PerformanceSeries>,
 }
 
 impl crate::proxy::Proxy for ProxyTest {
@@ -19,6 +21,7 @@ impl crate::proxy::Proxy for ProxyTest {
     fn update_resource_providers(&mut self, _resource_providers: &std::collections::HashMap<String, crate::resource_provider::ResourceProvider>) {}
     fn update_active_instances(&mut self, _active_instances: &std::collections::HashMap<uuid::Uuid, crate::active_instance::ActiveInstance>) {}
     fn update_dependency_graph(&mut self, _dependency_graph: &std::collections::HashMap<uuid::Uuid, std::collections::HashMap<String, uuid::Uuid>>) {}
+    fn update_output_splits(&mut self, _output_splits: &crate::proxy::OutputSplits) {}
     fn update_domain_info(&mut self, _domain_info: &crate::domain_info::DomainInfo) {}
     fn push_node_health(&mut self, _node_id: &uuid::Uuid, _node_health: edgeless_api::node_registration::NodeHealthStatus) {}
     fn push_performance_samples(&mut self, _node_id: &uuid::Uuid, _performance_samples: edgeless_api::node_registration::NodePerformanceSamples) {}
@@ -44,8 +47,11 @@ impl crate::proxy::Proxy for ProxyTest {
     fn fetch_performance_samples(&mut self) -> std::collections::HashMap<String, crate::proxy::PerformanceSamples> {
         std::collections::HashMap::new()
     }
-    fn fetch_performance_series(&mut self, _node: &str, _series: &str) -> crate::proxy::PerformanceSeries {
-        vec![]
+    fn fetch_performance_series(&mut self, node: &str, series: &str) -> crate::proxy::PerformanceSeries {
+        self.performance_series
+            .get(&(node.to_string(), series.to_string()))
+            .cloned()
+            .unwrap_or_default()
     }
     fn fetch_function_instance_requests(
         &mut self,
@@ -79,6 +85,9 @@ impl crate::proxy::Proxy for ProxyTest {
     fn fetch_dependency_graph(&mut self) -> std::collections::HashMap<uuid::Uuid, std::collections::HashMap<String, uuid::Uuid>> {
         std::collections::HashMap::new()
     }
+    fn fetch_output_splits(&mut self) -> crate::proxy::OutputSplits {
+        std::collections::HashMap::new()
+    }
     fn fetch_logical_id_to_workflow_id(&mut self) -> std::collections::HashMap<edgeless_api::function_instance::ComponentId, String> {
         std::collections::HashMap::new()
     }
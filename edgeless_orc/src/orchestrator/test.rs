@@ -32,6 +32,10 @@ pub enum MockAgentEvent {
     StopResource(edgeless_api::function_instance::InstanceId),
     PatchResource(edgeless_api::common::PatchRequest),
     Reset(),
+    SyncState(edgeless_api::function_instance::InstanceId),
+    GetState(edgeless_api::function_instance::StateSpecification),
+    SetState(edgeless_api::node_management::SetStateRequest),
+    HandOver(edgeless_api::node_management::HandOverRequest),
 }
 
 pub struct MockNode {
@@ -103,6 +107,29 @@ impl edgeless_api::node_management::NodeManagementAPI for MockAgentAPI {
         self.sender.send(MockAgentEvent::Reset()).await.unwrap();
         Ok(())
     }
+    async fn sync_state(&mut self, instance_id: edgeless_api::function_instance::InstanceId) -> anyhow::Result<()> {
+        self.sender.send(MockAgentEvent::SyncState(instance_id)).await.unwrap();
+        Ok(())
+    }
+    async fn get_state(
+        &mut self,
+        state_specification: edgeless_api::function_instance::StateSpecification,
+    ) -> anyhow::Result<Option<edgeless_api::node_management::SerializedState>> {
+        let serialized_state = format!("state-{}", state_specification.state_id);
+        self.sender.send(MockAgentEvent::GetState(state_specification)).await.unwrap();
+        Ok(Some(edgeless_api::node_management::SerializedState {
+            serialized_state,
+            version: 1,
+        }))
+    }
+    async fn set_state(&mut self, request: edgeless_api::node_management::SetStateRequest) -> anyhow::Result<()> {
+        self.sender.send(MockAgentEvent::SetState(request)).await.unwrap();
+        Ok(())
+    }
+    async fn hand_over(&mut self, request: edgeless_api::node_management::HandOverRequest) -> anyhow::Result<()> {
+        self.sender.send(MockAgentEvent::HandOver(request)).await.unwrap();
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -199,14 +226,16 @@ struct SetupResult {
 }
 
 async fn setup(num_nodes: u32, num_resources_per_node: u32) -> SetupResult {
+    setup_with_strategy(num_nodes, num_resources_per_node, crate::OrchestrationStrategy::Random).await
+}
+
+async fn setup_with_strategy(num_nodes: u32, num_resources_per_node: u32, orchestration_strategy: crate::OrchestrationStrategy) -> SetupResult {
     let (mut nodes, client_descs_resources, stable_node_id) = create_clients_resources(num_nodes, num_resources_per_node);
     let (subscriber_sender, subscriber_receiver) = futures::channel::mpsc::unbounded();
 
     let proxy = std::sync::Arc::new(tokio::sync::Mutex::new(proxy_test::ProxyTest::default()));
     let (mut orchestrator, orchestrator_task, _refresh_task) = Orchestrator::new(
-        crate::EdgelessOrcBaselineSettings {
-            orchestration_strategy: crate::OrchestrationStrategy::Random,
-        },
+        crate::EdgelessOrcBaselineSettings { orchestration_strategy },
         proxy.clone(),
         subscriber_sender,
     )
@@ -244,6 +273,10 @@ fn event_to_string(event: &MockAgentEvent) -> &'static str {
         MockAgentEvent::PatchResource(_) => "patch-resource",
         MockAgentEvent::UpdatePeers(_) => "update-peers",
         MockAgentEvent::Reset() => "reset",
+        MockAgentEvent::SyncState(_) => "sync-state",
+        MockAgentEvent::GetState(_) => "get-state",
+        MockAgentEvent::SetState(_) => "set-state",
+        MockAgentEvent::HandOver(_) => "hand-over",
     }
 }
 
@@ -591,6 +624,7 @@ async fn test_orc_patch() {
                     function_id: ext_resource_id,
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await
     {
@@ -622,6 +656,7 @@ async fn test_orc_patch() {
                     function_id: ext_function_id,
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await
     {
@@ -734,6 +769,7 @@ async fn test_orc_node_with_fun_disconnects() {
                     function_id: lid_2,
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await
     {
@@ -758,6 +794,7 @@ async fn test_orc_node_with_fun_disconnects() {
                     function_id: lid_3,
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await
     {
@@ -782,6 +819,7 @@ async fn test_orc_node_with_fun_disconnects() {
                     function_id: lid_4,
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await
     {
@@ -941,6 +979,7 @@ async fn test_orc_node_with_res_disconnects() {
                     function_id: lid_res,
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await
     {
@@ -1073,6 +1112,7 @@ async fn test_orc_patch_after_fun_stop() {
             .patch(edgeless_api::common::PatchRequest {
                 function_id: lid_pair.0,
                 output_mapping,
+                output_splits: std::collections::HashMap::new(),
             })
             .await
         {
@@ -1134,6 +1174,310 @@ async fn test_orc_patch_after_fun_stop() {
     no_function_event(&mut setup.nodes).await;
 }
 
+#[tokio::test]
+async fn test_orc_patch_output_splits() {
+    let mut setup = setup(10, 0).await;
+
+    // Start this workflow
+    //
+    // f1 -> f2 (90%)
+    //    \
+    //     -> f3 (10%)
+    //
+    // then stop f3
+
+    // Start functions
+    let mut lids = vec![];
+    let mut pids = vec![];
+    for i in 1..=3 {
+        let spawn_req = make_spawn_function_request(format!("f{}", i).as_str());
+        lids.push(match setup.fun_client.start(spawn_req.clone()).await.unwrap() {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        });
+        if let (_node_id, MockAgentEvent::StartFunction((new_instance_id, spawn_req_rcvd))) = wait_for_event_multiple(&mut setup.nodes).await {
+            pids.push(new_instance_id.function_id);
+            assert_eq!(spawn_req, spawn_req_rcvd);
+        }
+    }
+    assert_eq!(3, pids.len());
+
+    let logical_target = |lid: uuid::Uuid| edgeless_api::function_instance::InstanceId {
+        node_id: uuid::Uuid::nil(),
+        function_id: lid,
+    };
+    let physical_splits = |patch_request: &edgeless_api::common::PatchRequest| {
+        patch_request
+            .output_splits
+            .get("out")
+            .unwrap()
+            .iter()
+            .map(|target| (target.instance_id.function_id, target.weight))
+            .collect::<Vec<(uuid::Uuid, u32)>>()
+    };
+
+    // Patch f1 so that its events are split between f2 and f3.
+    match setup
+        .fun_client
+        .patch(edgeless_api::common::PatchRequest {
+            function_id: lids[0],
+            output_mapping: std::collections::HashMap::from([("out".to_string(), logical_target(lids[1]))]),
+            output_splits: std::collections::HashMap::from([(
+                "out".to_string(),
+                vec![
+                    edgeless_api::common::WeightedTarget {
+                        instance_id: logical_target(lids[1]),
+                        weight: 90,
+                    },
+                    edgeless_api::common::WeightedTarget {
+                        instance_id: logical_target(lids[2]),
+                        weight: 10,
+                    },
+                ],
+            )]),
+        })
+        .await
+    {
+        Ok(_) => {}
+        Err(err) => panic!("{}", err),
+    };
+    if let (_node_id, MockAgentEvent::PatchFunction(patch_request)) = wait_for_event_multiple(&mut setup.nodes).await {
+        assert_eq!(pids[0], patch_request.function_id);
+        assert_eq!(pids[1], patch_request.output_mapping.get("out").unwrap().function_id);
+        assert_eq!(vec![(pids[1], 90), (pids[2], 10)], physical_splits(&patch_request));
+    } else {
+        panic!("wrong event received");
+    }
+
+    // Make sure there are no pending events around.
+    no_function_event(&mut setup.nodes).await;
+
+    // Stop function f3: f1 is repatched without it.
+    match setup.fun_client.stop(lids[2]).await {
+        Ok(_) => {}
+        Err(err) => panic!("{}", err),
+    }
+
+    let mut num_events = std::collections::HashMap::new();
+    while let Some((_node_id, event)) = wait_for_events_if_any(&mut setup.nodes).await {
+        *num_events.entry(event_to_string(&event)).or_insert(0) += 1;
+        match event {
+            MockAgentEvent::StopFunction(instance_id) => {
+                assert_eq!(pids[2], instance_id.function_id);
+            }
+            MockAgentEvent::PatchFunction(patch_request) => {
+                assert_eq!(pids[0], patch_request.function_id);
+                assert_eq!(vec![(pids[1], 90)], physical_splits(&patch_request));
+            }
+            _ => panic!("unexpected event type: {}", event_to_string(&event)),
+        };
+    }
+    assert_eq!(Some(&1), num_events.get("stop-function"));
+    assert_eq!(Some(&1), num_events.get("patch-function"));
+
+    // Patch f1 again without splits.
+    match setup
+        .fun_client
+        .patch(edgeless_api::common::PatchRequest {
+            function_id: lids[0],
+            output_mapping: std::collections::HashMap::from([("out".to_string(), logical_target(lids[1]))]),
+            output_splits: std::collections::HashMap::new(),
+        })
+        .await
+    {
+        Ok(_) => {}
+        Err(err) => panic!("{}", err),
+    };
+    if let (_node_id, MockAgentEvent::PatchFunction(patch_request)) = wait_for_event_multiple(&mut setup.nodes).await {
+        assert_eq!(pids[0], patch_request.function_id);
+        assert!(patch_request.output_splits.is_empty());
+    } else {
+        panic!("wrong event received");
+    }
+
+    // Make sure there are no pending events around.
+    no_function_event(&mut setup.nodes).await;
+}
+
+#[tokio::test]
+async fn test_orc_autoscale_function() {
+    let mut setup = setup(4, 0).await;
+
+    // Start this workflow
+    //
+    // f1 -> f2
+    //
+    // where f2 is scaled between 2 and 3 instances.
+
+    // Start functions
+    let mut lids = vec![];
+    let mut f1_pid = edgeless_api::function_instance::InstanceId::none();
+    let mut f2_pids = vec![];
+    for i in 1..=2 {
+        let mut spawn_req = make_spawn_function_request(format!("f{}", i).as_str());
+        if i == 2 {
+            spawn_req.annotations = std::collections::HashMap::from([
+                ("min_instances".to_string(), "2".to_string()),
+                ("max_instances".to_string(), "3".to_string()),
+                ("target_latency_ms".to_string(), "100".to_string()),
+                ("scale_cooldown_sec".to_string(), "0".to_string()),
+            ]);
+        }
+        lids.push(match setup.fun_client.start(spawn_req.clone()).await.unwrap() {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        });
+        if let (_node_id, MockAgentEvent::StartFunction((new_instance_id, _spawn_req_rcvd))) = wait_for_event_multiple(&mut setup.nodes).await {
+            if i == 1 {
+                f1_pid = new_instance_id;
+            } else {
+                f2_pids.push(new_instance_id);
+            }
+        } else {
+            panic!("wrong event received");
+        }
+    }
+
+    // Patch f1 so that its events go to f2.
+    match setup
+        .fun_client
+        .patch(edgeless_api::common::PatchRequest {
+            function_id: lids[0],
+            output_mapping: std::collections::HashMap::from([(
+                "out".to_string(),
+                edgeless_api::function_instance::InstanceId {
+                    node_id: uuid::Uuid::nil(),
+                    function_id: lids[1],
+                },
+            )]),
+            output_splits: std::collections::HashMap::new(),
+        })
+        .await
+    {
+        Ok(_) => {}
+        Err(err) => panic!("{}", err),
+    };
+    if let (_node_id, MockAgentEvent::PatchFunction(patch_request)) = wait_for_event_multiple(&mut setup.nodes).await {
+        assert_eq!(f1_pid.function_id, patch_request.function_id);
+        assert_eq!(f2_pids[0], *patch_request.output_mapping.get("out").unwrap());
+        assert!(patch_request.output_splits.is_empty());
+    } else {
+        panic!("wrong event received");
+    }
+
+    // Refresh and return the start, patch, and stop events received.
+    #[derive(Default)]
+    struct Events {
+        started: Vec<(uuid::Uuid, edgeless_api::function_instance::InstanceId)>,
+        patched: Vec<edgeless_api::common::PatchRequest>,
+        stopped: Vec<edgeless_api::function_instance::InstanceId>,
+    }
+    async fn refresh(setup: &mut SetupResult) -> Events {
+        let (reply_sender, reply_receiver) = tokio::sync::oneshot::channel::<()>();
+        let _ = setup.orc_sender.send(OrchestratorRequest::Refresh(reply_sender)).await;
+        let _ = reply_receiver.await;
+        let mut events = Events::default();
+        while let Some((node_id, event)) = wait_for_events_if_any(&mut setup.nodes).await {
+            match event {
+                MockAgentEvent::StartFunction((new_instance_id, spawn_req_rcvd)) => {
+                    assert_eq!("f2", spawn_req_rcvd.spec.id);
+                    events.started.push((node_id, new_instance_id));
+                }
+                MockAgentEvent::PatchFunction(patch_request) => events.patched.push(patch_request),
+                MockAgentEvent::StopFunction(instance_id) => events.stopped.push(instance_id),
+                _ => panic!("unexpected event type: {}", event_to_string(&event)),
+            }
+        }
+        events
+    }
+    async fn set_latency(setup: &mut SetupResult, pids: &[edgeless_api::function_instance::InstanceId], latency: &str) {
+        let mut proxy = setup.proxy.lock().await;
+        proxy.performance_series.clear();
+        for pid in pids {
+            proxy.performance_series.insert(
+                (pid.function_id.to_string(), "function_execution_time".to_string()),
+                vec![(chrono::Utc::now(), latency.to_string())],
+            );
+        }
+    }
+    let split_targets = |patch_request: &edgeless_api::common::PatchRequest| {
+        patch_request
+            .output_splits
+            .get("out")
+            .unwrap()
+            .iter()
+            .map(|target| (target.instance_id, target.weight))
+            .collect::<Vec<(edgeless_api::function_instance::InstanceId, u32)>>()
+    };
+
+    // Below the minimum number of instances: a new instance of f2 is
+    // started on another node, then f1 is patched to split its events
+    // between the two instances.
+    let events = refresh(&mut setup).await;
+    assert_eq!(1, events.started.len());
+    assert_ne!(f2_pids[0].node_id, events.started[0].0);
+    f2_pids.push(events.started[0].1);
+    assert_eq!(1, events.patched.len());
+    assert_eq!(f1_pid.function_id, events.patched[0].function_id);
+    assert_eq!(f2_pids[0], *events.patched[0].output_mapping.get("out").unwrap());
+    assert_eq!(vec![(f2_pids[0], 1), (f2_pids[1], 1)], split_targets(&events.patched[0]));
+    assert!(events.stopped.is_empty());
+
+    // Latency above the target: scale up.
+    set_latency(&mut setup, &f2_pids, "0.2").await;
+    let events = refresh(&mut setup).await;
+    assert_eq!(1, events.started.len());
+    f2_pids.push(events.started[0].1);
+    assert_eq!(1, events.patched.len());
+    assert_eq!(vec![(f2_pids[0], 1), (f2_pids[1], 1), (f2_pids[2], 1)], split_targets(&events.patched[0]));
+    assert!(events.stopped.is_empty());
+    assert_eq!(
+        3,
+        f2_pids
+            .iter()
+            .map(|pid| pid.node_id)
+            .collect::<std::collections::HashSet<uuid::Uuid>>()
+            .len()
+    );
+
+    // Maximum number of instances reached.
+    set_latency(&mut setup, &f2_pids, "0.2").await;
+    let events = refresh(&mut setup).await;
+    assert!(events.started.is_empty() && events.patched.is_empty() && events.stopped.is_empty());
+
+    // Latency well below the target: scale down by stopping the most
+    // recent instance of f2, after f1 is repatched.
+    set_latency(&mut setup, &f2_pids, "0.01").await;
+    let events = refresh(&mut setup).await;
+    assert!(events.started.is_empty());
+    assert_eq!(1, events.patched.len());
+    assert_eq!(vec![(f2_pids[0], 1), (f2_pids[1], 1)], split_targets(&events.patched[0]));
+    assert_eq!(vec![f2_pids[2]], events.stopped);
+
+    // Minimum number of instances reached.
+    let events = refresh(&mut setup).await;
+    assert!(events.started.is_empty() && events.patched.is_empty() && events.stopped.is_empty());
+
+    // Stop f2: all its instances are stopped.
+    match setup.fun_client.stop(lids[1]).await {
+        Ok(_) => {}
+        Err(err) => panic!("{}", err),
+    }
+    let mut stopped = std::collections::HashSet::new();
+    while let Some((_node_id, event)) = wait_for_events_if_any(&mut setup.nodes).await {
+        match event {
+            MockAgentEvent::StopFunction(instance_id) => {
+                stopped.insert(instance_id);
+            }
+            MockAgentEvent::PatchFunction(patch_request) => {
+                assert!(patch_request.output_mapping.is_empty());
+            }
+            _ => panic!("unexpected event type: {}", event_to_string(&event)),
+        }
+    }
+    assert_eq!(std::collections::HashSet::from([f2_pids[0], f2_pids[1]]), stopped);
+}
+
 #[tokio::test]
 #[serial_test::serial]
 async fn test_orc_recreate_fun_after_disconnect() {
@@ -1203,6 +1547,7 @@ async fn test_orc_recreate_fun_after_disconnect() {
                     function_id: lid_2,
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await
     {
@@ -1227,6 +1572,7 @@ async fn test_orc_recreate_fun_after_disconnect() {
                     function_id: lid_3,
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         })
         .await
     {
@@ -1415,6 +1761,7 @@ async fn test_orc_migrate_function() {
                     function_id: lids[1],
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         },
         edgeless_api::common::PatchRequest {
             function_id: lids[1],
@@ -1434,6 +1781,7 @@ async fn test_orc_migrate_function() {
                     },
                 ),
             ]),
+            output_splits: std::collections::HashMap::new(),
         },
         edgeless_api::common::PatchRequest {
             function_id: lids[2],
@@ -1444,6 +1792,7 @@ async fn test_orc_migrate_function() {
                     function_id: lids[1],
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         },
     ];
 
@@ -1483,16 +1832,60 @@ async fn test_orc_migrate_function() {
     let _ = setup.orc_sender.send(OrchestratorRequest::Refresh(reply_sender)).await;
     let _ = reply_receiver.await;
 
+    // The old instance is asked for a final synchronization of the state,
+    // which is then retrieved from the old node and saved on the new one,
+    // on top of the version found there, before the new instance is
+    // started, and only then the old instance hands over the events it has
+    // received in the meantime to the new one and it is stopped.
     let mut num_patches = 0;
-    for _ in 0..5 {
+    let mut state_synced = false;
+    let mut state_retrieved = false;
+    let mut target_version_retrieved = false;
+    let mut state_saved = false;
+    let mut started = false;
+    let mut handed_over = false;
+    for _ in 0..10 {
         let (node_id, event) = wait_for_event_multiple(&mut setup.nodes).await;
         match event {
+            MockAgentEvent::SyncState(instance_id) => {
+                assert_eq!(old_node, node_id);
+                assert_eq!(pids[1], instance_id.function_id);
+                state_synced = true;
+            }
+            MockAgentEvent::GetState(state_specification) if node_id == old_node => {
+                assert!(state_synced);
+                assert_eq!(StatePolicy::NodeLocal, state_specification.state_policy);
+                state_retrieved = true;
+            }
+            MockAgentEvent::GetState(state_specification) => {
+                assert_eq!(another_node, node_id);
+                assert!(state_retrieved);
+                assert_eq!(StatePolicy::NodeLocal, state_specification.state_policy);
+                target_version_retrieved = true;
+            }
+            MockAgentEvent::SetState(request) => {
+                assert_eq!(another_node, node_id);
+                assert!(target_version_retrieved);
+                assert_eq!(format!("state-{}", request.state_specification.state_id), request.serialized_state);
+                assert_eq!(1, request.expected_version);
+                state_saved = true;
+            }
             MockAgentEvent::StartFunction((_new_instance_id, spawn_req_rcvd)) => {
                 assert_eq!("fc-1", spawn_req_rcvd.spec.id);
                 assert_eq!(another_node, node_id);
+                assert!(state_saved);
+                started = true;
+            }
+            MockAgentEvent::HandOver(request) => {
+                assert_eq!(old_node, node_id);
+                assert_eq!(pids[1], request.origin.function_id);
+                assert_eq!(another_node, request.target.node_id);
+                assert!(started);
+                handed_over = true;
             }
             MockAgentEvent::StopFunction(_new_instance_id) => {
                 assert_eq!(old_node, node_id);
+                assert!(handed_over);
             }
             MockAgentEvent::PatchFunction(_patch_request) => {
                 num_patches += 1;
@@ -1500,6 +1893,8 @@ async fn test_orc_migrate_function() {
             _ => panic!("unexpected event"),
         }
     }
+    assert!(started);
+    assert!(handed_over);
     assert_eq!(3, num_patches);
 
     no_function_event(&mut setup.nodes).await;
@@ -1544,6 +1939,7 @@ async fn test_orc_migrate_resource() {
                     function_id: lids[1],
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         },
         edgeless_api::common::PatchRequest {
             function_id: lids[1],
@@ -1563,6 +1959,7 @@ async fn test_orc_migrate_resource() {
                     },
                 ),
             ]),
+            output_splits: std::collections::HashMap::new(),
         },
         edgeless_api::common::PatchRequest {
             function_id: lids[2],
@@ -1573,6 +1970,7 @@ async fn test_orc_migrate_resource() {
                     function_id: lids[1],
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         },
     ];
 
@@ -1772,6 +2170,7 @@ async fn test_orc_invalid_migration() {
                     function_id: function_lids[1],
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         },
         edgeless_api::common::PatchRequest {
             function_id: function_lids[1],
@@ -1791,6 +2190,7 @@ async fn test_orc_invalid_migration() {
                     },
                 ),
             ]),
+            output_splits: std::collections::HashMap::new(),
         },
         edgeless_api::common::PatchRequest {
             function_id: function_lids[2],
@@ -1810,6 +2210,7 @@ async fn test_orc_invalid_migration() {
                     },
                 ),
             ]),
+            output_splits: std::collections::HashMap::new(),
         },
         edgeless_api::common::PatchRequest {
             function_id: resource_lids[0],
@@ -1829,6 +2230,7 @@ async fn test_orc_invalid_migration() {
                     },
                 ),
             ]),
+            output_splits: std::collections::HashMap::new(),
         },
         edgeless_api::common::PatchRequest {
             function_id: resource_lids[1],
@@ -1848,6 +2250,7 @@ async fn test_orc_invalid_migration() {
                     },
                 ),
             ]),
+            output_splits: std::collections::HashMap::new(),
         },
         edgeless_api::common::PatchRequest {
             function_id: resource_lids[2],
@@ -1858,6 +2261,7 @@ async fn test_orc_invalid_migration() {
                     function_id: resource_lids[1],
                 },
             )]),
+            output_splits: std::collections::HashMap::new(),
         },
     ];
     assert_eq!(6, patch_requests.len());
@@ -1994,6 +2398,7 @@ async fn test_orc_reset() {
             .patch(edgeless_api::common::PatchRequest {
                 function_id: lid_1,
                 output_mapping,
+                output_splits: std::collections::HashMap::new(),
             })
             .await
             .expect("Could not patch");
@@ -2012,6 +2417,7 @@ async fn test_orc_reset() {
             .patch(edgeless_api::common::PatchRequest {
                 function_id: lid_2,
                 output_mapping,
+                output_splits: std::collections::HashMap::new(),
             })
             .await
             .expect("Could not patch");
@@ -2073,6 +2479,9 @@ async fn test_orc_update_domain_capabilities() {
                     last_caps = *actual_caps;
                     num_events += 1;
                 }
+                DomainSubscriberRequest::UpdateActiveInstances(..) => {
+                    panic!("unexpected update of the active instances received");
+                }
                 DomainSubscriberRequest::RegisterOrcSender(_) => {}
                 DomainSubscriberRequest::Refresh() => {
                     panic!("unexpected refresh event received");
@@ -2086,6 +2495,55 @@ async fn test_orc_update_domain_capabilities() {
     assert_eq!(11, num_events);
 }
 
+#[tokio::test]
+#[serial_test::serial]
+async fn test_orc_update_domain_active_instances() {
+    let mut setup = setup(1, 1).await;
+
+    let mut last_active_instances = || {
+        let mut last = None;
+        while let Ok(Some(event)) = setup.subscriber_receiver.try_next() {
+            if let DomainSubscriberRequest::UpdateActiveInstances(functions, resources) = event {
+                last = Some((functions, resources));
+            }
+        }
+        last
+    };
+
+    let lid_fun = match setup.fun_client.start(make_spawn_function_request("fc")).await.unwrap() {
+        edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+        edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+    };
+    let lid_res = match setup.res_client.start(make_start_resource_request("rc-1")).await.unwrap() {
+        edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+        edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+    };
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(
+        Some((std::collections::HashSet::from([lid_fun]), std::collections::HashSet::from([lid_res]))),
+        last_active_instances()
+    );
+
+    setup.fun_client.stop(lid_fun).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(
+        Some((std::collections::HashSet::new(), std::collections::HashSet::from([lid_res]))),
+        last_active_instances()
+    );
+
+    // No notification if nothing has changed.
+    let _ = setup
+        .orc_sender
+        .send(OrchestratorRequest::Patch(edgeless_api::common::PatchRequest {
+            function_id: lid_res,
+            output_mapping: std::collections::HashMap::new(),
+            output_splits: std::collections::HashMap::new(),
+        }))
+        .await;
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(None, last_active_instances());
+}
+
 #[test]
 fn test_orc_deployment_requirements() {
     let no_reqs = DeploymentRequirements::none();
@@ -2106,6 +2564,8 @@ fn test_orc_deployment_requirements() {
         ("resource_match_all".to_string(), "file,redis".to_string()),
         ("tee".to_string(), "REQuired".to_string()),
         ("tpm".to_string(), "required".to_string()),
+        ("anti_affinity".to_string(), "Replicas".to_string()),
+        ("spread_by_label".to_string(), "zone".to_string()),
     ]);
     let reqs = DeploymentRequirements::from_annotations(&valid_annotations);
     assert_eq!(42, reqs.max_instances);
@@ -2114,19 +2574,26 @@ fn test_orc_deployment_requirements() {
     assert_eq!(vec!["file".to_string(), "redis".to_string()], reqs.resource_match_all);
     assert!(std::mem::discriminant(&AffinityLevel::Required) == std::mem::discriminant(&reqs.tee));
     assert!(std::mem::discriminant(&AffinityLevel::Required) == std::mem::discriminant(&reqs.tpm));
+    assert_eq!(crate::anti_affinity::AntiAffinity::Replicas, reqs.anti_affinity);
+    assert_eq!(Some("zone".to_string()), reqs.spread_by_label);
 }
 
 #[test]
 fn test_orc_feasible_nodes() {
+    let no_constraints = crate::deployment_requirements::PlacementConstraints::default();
     let mut logic = crate::orchestration_logic::OrchestrationLogic::new(crate::OrchestrationStrategy::Random);
 
     // No nodes
     let mut fun1_req = make_spawn_function_request("fun");
 
-    assert!(logic.feasible_nodes(&fun1_req, &vec![]).is_empty());
+    assert!(logic.feasible_nodes(&fun1_req, &vec![], &no_constraints).is_empty());
     assert!(
         logic
-            .feasible_nodes(&fun1_req, &vec![uuid::Uuid::new_v4(), uuid::Uuid::new_v4(), uuid::Uuid::new_v4()])
+            .feasible_nodes(
+                &fun1_req,
+                &vec![uuid::Uuid::new_v4(), uuid::Uuid::new_v4(), uuid::Uuid::new_v4()],
+                &no_constraints
+            )
             .is_empty()
     );
 
@@ -2144,7 +2611,7 @@ fn test_orc_feasible_nodes() {
     assert!(client_descs.len() == 5);
 
     // No annotations, all nodes are good
-    assert_eq!(5, logic.feasible_nodes(&fun1_req, &all_nodes).len());
+    assert_eq!(5, logic.feasible_nodes(&fun1_req, &all_nodes, &no_constraints).len());
 
     // Pin-point to a node.
     fun1_req
@@ -2153,5 +2620,416 @@ fn test_orc_feasible_nodes() {
 
     // Wrong run-time
     fun1_req.spec.function_type = "non-existing-runtime".to_string();
-    assert!(logic.feasible_nodes(&fun1_req, &all_nodes).is_empty());
+    assert!(logic.feasible_nodes(&fun1_req, &all_nodes, &no_constraints).is_empty());
+}
+
+#[test]
+fn test_orc_least_loaded_logic() {
+    let no_constraints = crate::deployment_requirements::PlacementConstraints::default();
+    let mut logic = crate::orchestration_logic::OrchestrationLogic::new(crate::OrchestrationStrategy::LeastLoaded);
+    let fun1_req = make_spawn_function_request("fun");
+
+    // No nodes
+    assert!(logic.next(&fun1_req, &no_constraints).is_none());
+
+    // Add nodes, sorted by their identifiers.
+    let (nodes, mut client_descs_resources, _stable_node_id) = create_clients_resources(3, 0);
+    let mut client_descs = std::collections::HashMap::new();
+    for node_id in nodes.keys() {
+        let mut client_desc = client_descs_resources.remove(node_id).unwrap().0;
+        client_desc.capabilities.num_cores = 4;
+        client_desc.capabilities.mem_size = 1024;
+        client_descs.insert(*node_id, client_desc);
+    }
+    logic.update_nodes(&client_descs, &std::collections::HashMap::new());
+    let mut all_nodes = client_descs.keys().cloned().collect::<Vec<uuid::Uuid>>();
+    all_nodes.sort();
+
+    // Same load everywhere: the choice is deterministic.
+    for _ in 0..10 {
+        assert_eq!(Some(all_nodes[0]), logic.next(&fun1_req, &no_constraints));
+    }
+
+    // Placement only: select the node with fewest instances.
+    logic.update_placement(std::collections::HashMap::from([(all_nodes[0], 2), (all_nodes[1], 1)]));
+    assert_eq!(Some(all_nodes[2]), logic.next(&fun1_req, &no_constraints));
+
+    // Placement and health: a busy node is avoided even if it is empty.
+    let health = |load_avg_1, mem_available| edgeless_api::node_registration::NodeHealthStatus {
+        load_avg_1,
+        mem_available,
+        ..Default::default()
+    };
+    logic.update_node_health(&all_nodes[0], health(0, 1024 * 1024));
+    logic.update_node_health(&all_nodes[1], health(0, 1024 * 1024));
+    logic.update_node_health(&all_nodes[2], health(400, 1024 * 1024));
+    assert_eq!(Some(all_nodes[1]), logic.next(&fun1_req, &no_constraints));
+
+    // Memory pressure is also considered.
+    logic.update_node_health(&all_nodes[1], health(0, 0));
+    assert_eq!(Some(all_nodes[0]), logic.next(&fun1_req, &no_constraints));
+
+    // Unfeasible nodes are never selected, no matter their load.
+    let mut fun2_req = make_spawn_function_request("fun");
+    fun2_req
+        .annotations
+        .insert("node_id_match_any".to_string(), format!("{},{}", all_nodes[1], all_nodes[2]));
+    assert_eq!(Some(all_nodes[2]), logic.next(&fun2_req, &no_constraints));
+
+    // The health status of removed nodes is forgotten.
+    logic.update_placement(std::collections::HashMap::new());
+    logic.update_node_health(&all_nodes[0], health(400, 1024 * 1024));
+    let client_desc = client_descs.remove(&all_nodes[2]).unwrap();
+    logic.update_nodes(&client_descs, &std::collections::HashMap::new());
+    client_descs.insert(all_nodes[2], client_desc);
+    logic.update_nodes(&client_descs, &std::collections::HashMap::new());
+    assert_eq!(Some(all_nodes[2]), logic.next(&fun1_req, &no_constraints));
+}
+
+#[tokio::test]
+async fn test_orc_least_loaded_placement() {
+    let mut setup = setup_with_strategy(4, 0, crate::OrchestrationStrategy::LeastLoaded).await;
+
+    // Start functions: they are spread evenly across the nodes.
+    let mut num_instances = std::collections::HashMap::new();
+    for i in 0..8 {
+        let spawn_req = make_spawn_function_request(format!("fc-{}", i).as_str());
+        match setup.fun_client.start(spawn_req).await.unwrap() {
+            edgeless_api::common::StartComponentResponse::InstanceId(_) => {}
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+        let (node_id, event) = wait_for_event_multiple(&mut setup.nodes).await;
+        assert!(matches!(event, MockAgentEvent::StartFunction(_)));
+        *num_instances.entry(node_id).or_insert(0) += 1;
+    }
+    assert_eq!(4, num_instances.len());
+    assert!(num_instances.values().all(|x| *x == 2));
+
+    // Overload all the nodes but one: the next functions go there.
+    let mut node_ids = setup.nodes.keys().cloned().collect::<Vec<uuid::Uuid>>();
+    node_ids.sort();
+    let idle_node_id = node_ids[2];
+    for node_id in &node_ids {
+        let load_avg_1 = if *node_id == idle_node_id { 0 } else { 1000 };
+        let _ = setup
+            .orc_sender
+            .send(OrchestratorRequest::UpdateNodeHealth(
+                *node_id,
+                edgeless_api::node_registration::NodeHealthStatus {
+                    load_avg_1,
+                    ..Default::default()
+                },
+            ))
+            .await;
+    }
+    for i in 0..3 {
+        let spawn_req = make_spawn_function_request(format!("fc-{}", 8 + i).as_str());
+        match setup.fun_client.start(spawn_req).await.unwrap() {
+            edgeless_api::common::StartComponentResponse::InstanceId(_) => {}
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        };
+        let (node_id, event) = wait_for_event_multiple(&mut setup.nodes).await;
+        assert!(matches!(event, MockAgentEvent::StartFunction(_)));
+        assert_eq!(idle_node_id, node_id);
+    }
+}
+
+#[test]
+fn test_orc_locality_logic() {
+    let no_constraints = crate::deployment_requirements::PlacementConstraints::default();
+    let mut logic = crate::orchestration_logic::OrchestrationLogic::new(crate::OrchestrationStrategy::RoundRobin);
+    let (nodes, mut client_descs_resources, _stable_node_id) = create_clients_resources(3, 0);
+    let mut client_descs = std::collections::HashMap::new();
+    for node_id in nodes.keys() {
+        client_descs.insert(*node_id, client_descs_resources.remove(node_id).unwrap().0);
+    }
+    logic.update_nodes(&client_descs, &std::collections::HashMap::new());
+    let mut all_nodes = client_descs.keys().cloned().collect::<Vec<uuid::Uuid>>();
+    all_nodes.sort();
+    let (a, b, c) = (all_nodes[0], all_nodes[1], all_nodes[2]);
+
+    // Default transfer latencies.
+    assert_eq!(0.0, logic.transfer_latency(&a, &a));
+    assert_eq!(crate::placement::DEFAULT_TRANSFER_LATENCY, logic.transfer_latency(&a, &b));
+
+    // Co-locate with the neighbors.
+    let fun1_req = make_spawn_function_request("fun");
+    assert_eq!(Some(b), logic.next_local(&fun1_req, &no_constraints, &[b], &[]));
+    assert_eq!(Some(c), logic.next_local(&fun1_req, &no_constraints, &[], &[c, c]));
+    assert_eq!(Some(c), logic.next_local(&fun1_req, &no_constraints, &[b], &[c, c]));
+
+    // Measured latencies are taken into account.
+    logic.update_transfer_latency(&b, &c, 0.5);
+    assert_eq!(0.5, logic.locality_cost(&c, &[b], &[]));
+    assert_eq!(Some(a), logic.next_local(&fun1_req, &no_constraints, &[b], &[c, c]));
+    logic.update_transfer_latency(&a, &a, 0.001);
+    logic.update_transfer_latency(&a, &b, 0.001);
+    assert_eq!(Some(b), logic.next_local(&fun1_req, &no_constraints, &[a, a, b], &[]));
+
+    // Only feasible nodes are selected.
+    let mut fun2_req = make_spawn_function_request("fun");
+    fun2_req.annotations.insert("node_id_match_any".to_string(), format!("{},{}", a, c));
+    assert_eq!(Some(a), logic.next_local(&fun2_req, &no_constraints, &[b, b], &[]));
+
+    // No neighbors: use the orchestration strategy in the settings.
+    let mut selected = std::collections::HashSet::new();
+    for _ in 0..3 {
+        selected.insert(logic.next_local(&fun1_req, &no_constraints, &[], &[]).unwrap());
+    }
+    assert_eq!(3, selected.len());
+
+    // The latencies of removed nodes are forgotten.
+    client_descs.remove(&c);
+    logic.update_nodes(&client_descs, &std::collections::HashMap::new());
+    assert_eq!(crate::placement::DEFAULT_TRANSFER_LATENCY, logic.transfer_latency(&b, &c));
+    assert_eq!(0.001, logic.transfer_latency(&a, &b));
+}
+
+#[tokio::test]
+async fn test_orc_locality_placement() {
+    let mut setup = setup(4, 0).await;
+    let mut node_ids = setup.nodes.keys().cloned().collect::<Vec<uuid::Uuid>>();
+    node_ids.sort();
+    let (a, b, c) = (node_ids[0], node_ids[1], node_ids[2]);
+
+    // Start this workflow
+    //
+    // f1 -> f3
+    // f2
+    //
+    // where f1 is pinned to node a, f2 can be placed anywhere, and f3 on
+    // nodes b and c only. f2 and f3 have a locality-aware placement.
+    let mut lids = vec![];
+    let mut pids = vec![];
+    for (name, node_id_match_any, expected_node_id) in [
+        ("f1", format!("{}", a), a),
+        ("f2", String::default(), a),
+        ("f3", format!("{},{}", b, c), b),
+    ] {
+        let mut spawn_req = make_spawn_function_request(name);
+        spawn_req.state_specification.state_policy = StatePolicy::Transient;
+        if !node_id_match_any.is_empty() {
+            spawn_req.annotations.insert("node_id_match_any".to_string(), node_id_match_any);
+        }
+        if name != "f1" {
+            spawn_req.annotations.insert("placement".to_string(), "locality".to_string());
+        }
+        lids.push(match setup.fun_client.start(spawn_req).await.unwrap() {
+            edgeless_api::common::StartComponentResponse::InstanceId(id) => id,
+            edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+        });
+        if let (node_id, MockAgentEvent::StartFunction((new_instance_id, _spawn_req_rcvd))) = wait_for_event_multiple(&mut setup.nodes).await {
+            assert_eq!(expected_node_id, node_id, "wrong node for {}", name);
+            pids.push(new_instance_id);
+        } else {
+            panic!("wrong event received");
+        }
+    }
+
+    // Patch f1 so that its events go to f3.
+    match setup
+        .fun_client
+        .patch(edgeless_api::common::PatchRequest {
+            function_id: lids[0],
+            output_mapping: std::collections::HashMap::from([(
+                "out".to_string(),
+                edgeless_api::function_instance::InstanceId {
+                    node_id: uuid::Uuid::nil(),
+                    function_id: lids[2],
+                },
+            )]),
+            output_splits: std::collections::HashMap::new(),
+        })
+        .await
+    {
+        Ok(_) => {}
+        Err(err) => panic!("{}", err),
+    };
+    if let (node_id, MockAgentEvent::PatchFunction(patch_request)) = wait_for_event_multiple(&mut setup.nodes).await {
+        assert_eq!(a, node_id);
+        assert_eq!(pids[0].function_id, patch_request.function_id);
+        assert_eq!(pids[2], *patch_request.output_mapping.get("out").unwrap());
+    } else {
+        panic!("wrong event received");
+    }
+
+    async fn refresh(setup: &mut SetupResult) {
+        let (reply_sender, reply_receiver) = tokio::sync::oneshot::channel::<()>();
+        let _ = setup.orc_sender.send(OrchestratorRequest::Refresh(reply_sender)).await;
+        let _ = reply_receiver.await;
+    }
+
+    // Nodes b and c are equally far from a: nothing happens.
+    refresh(&mut setup).await;
+    no_function_event(&mut setup.nodes).await;
+
+    // The transfer latency measured from a to b is high: f3 is moved to c
+    // and f1 is repatched.
+    setup.proxy.lock().await.performance_series.insert(
+        (pids[2].function_id.to_string(), "function_transfer_time".to_string()),
+        vec![(chrono::Utc::now(), "0.5".to_string())],
+    );
+    refresh(&mut setup).await;
+    let mut new_f3_pid = None;
+    let mut f3_stopped = false;
+    let mut f1_patch = None;
+    while let Some((node_id, event)) = wait_for_events_if_any(&mut setup.nodes).await {
+        match event {
+            MockAgentEvent::StartFunction((new_instance_id, spawn_req_rcvd)) => {
+                assert_eq!(c, node_id);
+                assert_eq!("f3", spawn_req_rcvd.spec.id);
+                new_f3_pid = Some(new_instance_id);
+            }
+            MockAgentEvent::StopFunction(instance_id) => {
+                assert_eq!(pids[2], instance_id);
+                f3_stopped = true;
+            }
+            MockAgentEvent::PatchFunction(patch_request) => {
+                assert_eq!(pids[0].function_id, patch_request.function_id);
+                f1_patch = Some(patch_request);
+            }
+            _ => panic!("unexpected event type: {}", event_to_string(&event)),
+        }
+    }
+    let new_f3_pid = new_f3_pid.expect("f3 not relocated");
+    assert!(f3_stopped);
+    assert_eq!(new_f3_pid, *f1_patch.expect("f1 not repatched").output_mapping.get("out").unwrap());
+
+    // The relocation is stable.
+    refresh(&mut setup).await;
+    no_function_event(&mut setup.nodes).await;
+}
+
+#[tokio::test]
+async fn test_orc_anti_affinity_replicas() {
+    let mut setup = setup(3, 0).await;
+
+    // Start a function that requires more instances than nodes, which
+    // cannot share the same node.
+    let mut spawn_req = make_spawn_function_request("f1");
+    spawn_req.annotations = std::collections::HashMap::from([
+        ("min_instances".to_string(), "4".to_string()),
+        ("anti_affinity".to_string(), "replicas".to_string()),
+    ]);
+    match setup.fun_client.start(spawn_req).await.unwrap() {
+        edgeless_api::common::StartComponentResponse::InstanceId(_) => {}
+        edgeless_api::common::StartComponentResponse::ResponseError(err) => panic!("{}", err),
+    };
+    let mut nodes_used = std::collections::HashSet::new();
+    if let (node_id, MockAgentEvent::StartFunction(_)) = wait_for_event_multiple(&mut setup.nodes).await {
+        nodes_used.insert(node_id);
+    } else {
+        panic!("wrong event received");
+    }
+
+    // Only one instance per node is created.
+    for _ in 0..2 {
+        let (reply_sender, reply_receiver) = tokio::sync::oneshot::channel::<()>();
+        let _ = setup.orc_sender.send(OrchestratorRequest::Refresh(reply_sender)).await;
+        let _ = reply_receiver.await;
+        while let Some((node_id, event)) = wait_for_events_if_any(&mut setup.nodes).await {
+            match event {
+                MockAgentEvent::StartFunction(_) => assert!(nodes_used.insert(node_id)),
+                _ => panic!("unexpected event type: {}", event_to_string(&event)),
+            }
+        }
+    }
+    assert_eq!(3, nodes_used.len());
+}
+
+#[tokio::test]
+async fn test_orc_anti_affinity_workflow() {
+    let mut setup = setup(3, 0).await;
+
+    // The functions of the same workflow with anti-affinity are started on
+    // different nodes, until there are nodes available.
+    let mut nodes_used = std::collections::HashSet::new();
+    for i in 0..4 {
+        let mut spawn_req = make_spawn_function_request(format!("f{}", i).as_str());
+        spawn_req.workflow_id = "workflow_2".to_string();
+        spawn_req.annotations = std::collections::HashMap::from([("anti_affinity".to_string(), "workflow".to_string())]);
+        match setup.fun_client.start(spawn_req).await.unwrap() {
+            edgeless_api::common::StartComponentResponse::InstanceId(_) => {
+                assert!(i < 3);
+                if let (node_id, MockAgentEvent::StartFunction(_)) = wait_for_event_multiple(&mut setup.nodes).await {
+                    assert!(nodes_used.insert(node_id));
+                } else {
+                    panic!("wrong event received");
+                }
+            }
+            edgeless_api::common::StartComponentResponse::ResponseError(_) => assert_eq!(3, i),
+        };
+    }
+    no_function_event(&mut setup.nodes).await;
+
+    // Other functions in the same workflow or in other workflows are not affected.
+    let mut spawn_req = make_spawn_function_request("f4");
+    spawn_req.workflow_id = "workflow_2".to_string();
+    assert!(matches!(
+        setup.fun_client.start(spawn_req).await.unwrap(),
+        edgeless_api::common::StartComponentResponse::InstanceId(_)
+    ));
+    let mut spawn_req = make_spawn_function_request("f5");
+    spawn_req.annotations = std::collections::HashMap::from([("anti_affinity".to_string(), "workflow".to_string())]);
+    assert!(matches!(
+        setup.fun_client.start(spawn_req).await.unwrap(),
+        edgeless_api::common::StartComponentResponse::InstanceId(_)
+    ));
+}
+
+#[test]
+fn test_orc_spread_by_label() {
+    let mut logic = crate::orchestration_logic::OrchestrationLogic::new(crate::OrchestrationStrategy::Random);
+
+    // Nodes a and b are in zone z1, c in zone z2, while d has no zone.
+    let (nodes, mut client_descs_resources, _stable_node_id) = create_clients_resources(4, 0);
+    let mut all_nodes = nodes.keys().cloned().collect::<Vec<uuid::Uuid>>();
+    all_nodes.sort();
+    let (a, b, c, d) = (all_nodes[0], all_nodes[1], all_nodes[2], all_nodes[3]);
+    let mut client_descs = std::collections::HashMap::new();
+    for (node_id, zone) in [(a, Some("z1")), (b, Some("z1")), (c, Some("z2")), (d, None)] {
+        let mut client_desc = client_descs_resources.remove(&node_id).unwrap().0;
+        if let Some(zone) = zone {
+            client_desc.capabilities.labels.push(format!("zone={}", zone));
+        }
+        client_descs.insert(node_id, client_desc);
+    }
+    logic.update_nodes(&client_descs, &std::collections::HashMap::new());
+
+    let mut fun1_req = make_spawn_function_request("fun");
+    fun1_req.annotations.insert("spread_by_label".to_string(), "zone".to_string());
+    let spread_values = |values: Vec<&str>| Some(values.into_iter().map(|x| x.to_string()).collect::<std::collections::HashSet<String>>());
+    let feasible_nodes = |logic: &crate::orchestration_logic::OrchestrationLogic,
+                          constraints: &crate::deployment_requirements::PlacementConstraints| {
+        let mut nodes = logic.feasible_nodes(&fun1_req, &all_nodes, constraints);
+        nodes.sort();
+        nodes
+    };
+
+    // No other instances: all the zones are good, but the node must have one.
+    let constraints = logic.placement_constraints(&fun1_req, std::collections::HashSet::new(), &[]);
+    assert_eq!(spread_values(vec!["z1", "z2"]), constraints.spread_values);
+    assert_eq!(vec![a, b, c], feasible_nodes(&logic, &constraints));
+
+    // Fill the zone with fewer instances first.
+    let constraints = logic.placement_constraints(&fun1_req, std::collections::HashSet::new(), &[a]);
+    assert_eq!(spread_values(vec!["z2"]), constraints.spread_values);
+    assert_eq!(Some(c), logic.next(&fun1_req, &constraints));
+
+    let constraints = logic.placement_constraints(&fun1_req, std::collections::HashSet::new(), &[a, c, d]);
+    assert_eq!(spread_values(vec!["z1", "z2"]), constraints.spread_values);
+
+    let constraints = logic.placement_constraints(&fun1_req, std::collections::HashSet::new(), &[a, c, b]);
+    assert_eq!(spread_values(vec!["z2"]), constraints.spread_values);
+
+    // The zones without nodes available are not considered.
+    let constraints = logic.placement_constraints(&fun1_req, std::collections::HashSet::from([c]), &[a]);
+    assert_eq!(spread_values(vec!["z1"]), constraints.spread_values);
+    assert_eq!(vec![a, b], feasible_nodes(&logic, &constraints));
+
+    // No zone at all.
+    fun1_req.annotations.insert("spread_by_label".to_string(), "region".to_string());
+    let constraints = logic.placement_constraints(&fun1_req, std::collections::HashSet::new(), &[]);
+    assert_eq!(spread_values(vec![]), constraints.spread_values);
+    assert!(logic.next(&fun1_req, &constraints).is_none());
 }
@@ -4,7 +4,6 @@
 // SPDX-License-Identifier: MIT
 
 use futures::{SinkExt, StreamExt};
-use itertools::Itertools;
 use rand::SeedableRng;
 use rand::seq::SliceRandom;
 
@@ -41,13 +40,21 @@ pub(crate) struct OrchestratorTask {
     // key: lid
     active_instances: std::collections::HashMap<uuid::Uuid, crate::active_instance::ActiveInstance>,
     active_instances_changed: bool,
+    // LIDs of the active functions and resources last notified to the
+    // domain register
+    reported_functions: std::collections::HashSet<uuid::Uuid>,
+    reported_resources: std::collections::HashSet<uuid::Uuid>,
     // active patches to which the orchestrator commits
     // key:   lid (origin function)
     // value: map of:
     //        key:   channel output name
     //        value: lid (target function)
     dependency_graph: std::collections::HashMap<uuid::Uuid, std::collections::HashMap<String, uuid::Uuid>>,
+    // output channels of the active patches whose events are split among
+    // multiple targets, which are also part of the dependency graph
+    output_splits: crate::proxy::OutputSplits,
     dependency_graph_changed: bool,
+    autoscaler: crate::autoscaler::Autoscaler,
 }
 
 impl OrchestratorTask {
@@ -67,8 +74,12 @@ impl OrchestratorTask {
             rng: rand::rngs::StdRng::from_entropy(),
             active_instances: std::collections::HashMap::new(),
             active_instances_changed: false,
+            reported_functions: std::collections::HashSet::new(),
+            reported_resources: std::collections::HashSet::new(),
             dependency_graph: std::collections::HashMap::new(),
+            output_splits: std::collections::HashMap::new(),
             dependency_graph_changed: false,
+            autoscaler: crate::autoscaler::Autoscaler::default(),
         }
     }
 
@@ -116,6 +127,10 @@ impl OrchestratorTask {
                     self.update_domain().await;
                     self.refresh().await;
                 }
+                crate::orchestrator::OrchestratorRequest::UpdateNodeHealth(node_id, health_status) => {
+                    log::debug!("Orchestrator UpdateNodeHealth {:?}", node_id);
+                    self.orchestration_logic.update_node_health(&node_id, health_status);
+                }
                 crate::orchestrator::OrchestratorRequest::Refresh(reply_sender) => {
                     log::debug!("Orchestrator Refresh");
                     self.refresh().await;
@@ -126,6 +141,7 @@ impl OrchestratorTask {
                     self.reset().await;
                 }
             }
+            self.update_active_instances().await;
         }
     }
 
@@ -198,50 +214,89 @@ impl OrchestratorTask {
         // Do the migration of the function or resource.
         if let Some(spawn_req) = spawn_req {
             // Filter out the unfeasible targets.
-            let target_node_ids = self.orchestration_logic.feasible_nodes(&spawn_req, targets);
+            // The origin instances are not considered for the placement
+            // constraints since they are going to be replaced.
+            let constraints = self.placement_constraints(lid, &spawn_req, true);
+            let target_node_ids = self.orchestration_logic.feasible_nodes(&spawn_req, targets, &constraints);
 
             // Select one feasible target as the candidate one.
-            let target = target_node_ids.first();
-            let mut to_be_started = vec![];
-            if let Some(target) = target {
-                if target_node_ids.len() > 1 {
-                    log::warn!(
-                        "Currently supporting only a single target node per component: choosing {}, the others will be ignored",
-                        target
-                    );
-                }
-                to_be_started.push((spawn_req.clone(), *target));
-            } else {
-                anyhow::bail!("No (valid) target found for the migration of function LID {}", lid);
+            let target = match target_node_ids.first() {
+                Some(target) => *target,
+                None => anyhow::bail!("No (valid) target found for the migration of function LID {}", lid),
+            };
+            if target_node_ids.len() > 1 {
+                log::warn!(
+                    "Currently supporting only a single target node per component: choosing {}, the others will be ignored",
+                    target
+                );
             }
 
-            // Stop all the function instances associated with this LID.
-            for origin_instance in &origin_instances {
-                self.stop_function(origin_instance).await;
+            // Ask the origin instances for a final synchronization of their
+            // state, then hand over the node-local state from the origin
+            // node to the target one, before the new function instance is
+            // started so that it can be initialized with it.
+            // A transient state is not preserved, while a global state is
+            // accessible from any node once synchronized.
+            // After the final synchronization the origin instances are
+            // quiesced, i.e., they do not process events anymore, so that
+            // no state update is lost.
+            let state_policy = &spawn_req.state_specification.state_policy;
+            let mut quiesced = vec![];
+            let mut res = Ok(());
+            if *state_policy != edgeless_api::function_instance::StatePolicy::Transient {
+                res = self
+                    .sync_state(&origin_instances, &mut quiesced)
+                    .await
+                    .map_err(|err| anyhow::anyhow!("Error when migrating the state of function LID {}: {}", lid, err));
+            }
+            if res.is_ok() && *state_policy == edgeless_api::function_instance::StatePolicy::NodeLocal {
+                res = self
+                    .transfer_state(&spawn_req.state_specification, &origin_instances, &target)
+                    .await
+                    .map_err(|err| anyhow::anyhow!("Error when migrating the state of function LID {}: {}", lid, err));
+            }
+
+            // Start the new function instance while the origin ones are still
+            // running.
+            // TODO: if migration to multiple instances is supported, then we
+            // should choose how to consider the case of a function start
+            // failing while others succeed.
+            if res.is_ok() {
+                res = self
+                    .start_function_in_node(&spawn_req, lid, &target)
+                    .await
+                    .map(|_| ())
+                    .map_err(|err| anyhow::anyhow!("Error when migrating function LID {} to node_id {}: {}", lid, target, err));
             }
 
-            // Remove the association of the component with origin instances.
-            // If the start below fails, then the function instance will remain
-            // associated with no instances.
-            if let Some(crate::active_instance::ActiveInstance::Function(_spawn_req, origin_instances)) = self.active_instances.get_mut(lid) {
-                origin_instances.clear();
+            // If the migration fails, then the function remains associated
+            // with the origin instances, unless they have been quiesced.
+            if let Err(err) = res {
+                if !quiesced.is_empty() {
+                    self.restart_quiesced(&spawn_req, lid, &origin_instances).await;
+                }
+                return Err(err);
             }
-            self.active_instances_changed = true;
-
-            // Start the new function instances.
-            assert_eq!(1, to_be_started.len());
-            for (spawn_request, node_id) in to_be_started {
-                if let Err(err) = self.start_function_in_node(&spawn_request, lid, &node_id).await {
-                    // TODO: if migration to multiple instances is supported,
-                    // then we should choose how to consider the case of a
-                    // function start failing while others succeed:
-                    // - if this is considered a failure, then the function
-                    // instances already started should be stopped (rollback)
-                    // - otherwise, an Ok must be returned instead of an Err
-                    anyhow::bail!("Error when migrating function LID {} to node_id {}: {}", lid, node_id, err);
+
+            // Hand over to the new instance the events that have reached the
+            // quiesced instances after the final synchronization, so that
+            // they are not lost when the latter are stopped.
+            if let Some(crate::active_instance::ActiveInstance::Function(_, instances)) = self.active_instances.get(lid) {
+                if let Some(new_instance) = instances.first().cloned() {
+                    self.hand_over(&quiesced, &new_instance).await;
                 }
             }
-            Ok(*target.expect("impossible: the target node must have a value"))
+
+            // Stop all the function instances previously associated with
+            // this LID, which have been replaced by the new one.
+            // The events still queued by the quiesced instances are sent to
+            // the dead letter or replied with an error.
+            // The dependency graph is repatched by the caller.
+            for origin_instance in &origin_instances {
+                self.stop_function(origin_instance).await;
+            }
+
+            Ok(target)
         } else if let Some(resource_req) = resource_req {
             assert!(origin_instances.len() <= 1);
 
@@ -283,6 +338,190 @@ impl OrchestratorTask {
         }
     }
 
+    /// Replace the instances of a function, some of which have been quiesced
+    /// during a migration that has failed, with a new instance on the node
+    /// of the first of them, which is initialized with the state synchronized.
+    ///
+    /// * `spawn_req` - The specifications of the function.
+    /// * `lid` - The logical identifier of the function.
+    /// * `origin_instances` - The current instances of the function.
+    async fn restart_quiesced(
+        &mut self,
+        spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest,
+        lid: &edgeless_api::function_instance::ComponentId,
+        origin_instances: &[edgeless_api::function_instance::InstanceId],
+    ) {
+        for origin_instance in origin_instances {
+            self.stop_function(origin_instance).await;
+        }
+        let origin_node_id = match origin_instances.first() {
+            Some(origin_instance) => origin_instance.node_id,
+            None => return,
+        };
+        match self.start_function_in_node(spawn_req, lid, &origin_node_id).await {
+            Ok(_) => {
+                log::info!("Function LID {} restarted on node_id {} after a failed migration", lid, origin_node_id);
+                let mut to_be_repatched = vec![*lid];
+                to_be_repatched.append(&mut self.dependencies(lid));
+                self.apply_patches(to_be_repatched).await;
+            }
+            Err(err) => {
+                log::error!(
+                    "Could not restart function LID {} on node_id {} after a failed migration: {}",
+                    lid,
+                    origin_node_id,
+                    err
+                );
+                // A function without instances is started again when
+                // the active instances are next checked.
+                if let Some(crate::active_instance::ActiveInstance::Function(_, instances)) = self.active_instances.get_mut(lid) {
+                    instances.clear();
+                    self.active_instances_changed = true;
+                }
+            }
+        }
+    }
+
+    /// Ask the quiesced instances of a function to forward the events they
+    /// receive to the instance that replaces them.
+    ///
+    /// A failure is only logged: the events are then handled when the
+    /// quiesced instances are stopped.
+    ///
+    /// * `quiesced` - The instances that have been quiesced.
+    /// * `target` - The instance that replaces them.
+    async fn hand_over(&mut self, quiesced: &[edgeless_api::function_instance::InstanceId], target: &edgeless_api::function_instance::InstanceId) {
+        for origin_instance in quiesced {
+            match self.nodes.get_mut(&origin_instance.node_id) {
+                Some(client_desc) => {
+                    if let Err(err) = client_desc
+                        .api
+                        .node_management_api()
+                        .hand_over(edgeless_api::node_management::HandOverRequest {
+                            origin: *origin_instance,
+                            target: *target,
+                        })
+                        .await
+                    {
+                        log::warn!("Could not hand over the events of {} to {}: {}", origin_instance, target, err);
+                    }
+                }
+                None => log::warn!(
+                    "Cannot hand over the events of {} on unknown node_id {}",
+                    origin_instance,
+                    origin_instance.node_id
+                ),
+            }
+        }
+    }
+
+    /// Ask the current instances of a function to synchronize their state,
+    /// so that the updates since their last synchronization are not lost
+    /// when migrating the function.
+    /// The instances stop processing events once synchronized.
+    ///
+    /// * `origin_instances` - The current instances of the function.
+    /// * `quiesced` - Filled with the instances synchronized.
+    async fn sync_state(
+        &mut self,
+        origin_instances: &[edgeless_api::function_instance::InstanceId],
+        quiesced: &mut Vec<edgeless_api::function_instance::InstanceId>,
+    ) -> anyhow::Result<()> {
+        for origin_instance in origin_instances {
+            match self.nodes.get_mut(&origin_instance.node_id) {
+                Some(client_desc) => {
+                    client_desc
+                        .api
+                        .node_management_api()
+                        .sync_state(*origin_instance)
+                        .await
+                        .map_err(|err| anyhow::anyhow!("could not synchronize the state of {}: {}", origin_instance, err))?;
+                    quiesced.push(*origin_instance);
+                }
+                None => log::warn!(
+                    "Cannot synchronize the state of {} on unknown node_id {}",
+                    origin_instance,
+                    origin_instance.node_id
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy the node-local state of a function from the node of its
+    /// current instance to the target node of a migration.
+    ///
+    /// The origin node is asked for the state last synchronized by the
+    /// function instance, i.e., upon the request of `sync_state()`, which
+    /// is then saved by the state manager of the target node.
+    /// The state is saved on top of the version found on the target node,
+    /// hence the transfer fails if that state is modified in the meanwhile.
+    /// Nothing is done if there is no state yet.
+    ///
+    /// * `state_specification` - The state specification of the function.
+    /// * `origin_instances` - The current instances of the function.
+    /// * `target` - The node where the new function instance will be started.
+    async fn transfer_state(
+        &mut self,
+        state_specification: &edgeless_api::function_instance::StateSpecification,
+        origin_instances: &[edgeless_api::function_instance::InstanceId],
+        target: &edgeless_api::function_instance::NodeId,
+    ) -> anyhow::Result<()> {
+        let origin_node_id = match origin_instances.first() {
+            Some(origin_instance) => origin_instance.node_id,
+            None => return Ok(()),
+        };
+        if origin_node_id == *target {
+            return Ok(());
+        }
+
+        let serialized_state = match self.nodes.get_mut(&origin_node_id) {
+            Some(client_desc) => client_desc
+                .api
+                .node_management_api()
+                .get_state(state_specification.clone())
+                .await
+                .map_err(|err| anyhow::anyhow!("could not retrieve the state from node_id {}: {}", origin_node_id, err))?,
+            None => {
+                log::warn!(
+                    "Cannot retrieve state {} from unknown node_id {}",
+                    state_specification.state_id,
+                    origin_node_id
+                );
+                None
+            }
+        };
+
+        if let Some(serialized_state) = serialized_state {
+            let mut node_management_api = match self.nodes.get_mut(target) {
+                Some(client_desc) => client_desc.api.node_management_api(),
+                None => anyhow::bail!("Cannot save state {} on unknown node_id {}", state_specification.state_id, target),
+            };
+            let expected_version = node_management_api
+                .get_state(state_specification.clone())
+                .await
+                .map_err(|err| anyhow::anyhow!("could not retrieve the state from node_id {}: {}", target, err))?
+                .map(|state| state.version)
+                .unwrap_or_default();
+            node_management_api
+                .set_state(edgeless_api::node_management::SetStateRequest {
+                    state_specification: state_specification.clone(),
+                    serialized_state: serialized_state.serialized_state,
+                    expected_version,
+                })
+                .await
+                .map_err(|err| anyhow::anyhow!("could not save the state on node_id {}: {}", target, err))?;
+            log::info!(
+                "State {} transferred from node_id {} to node_id {}",
+                state_specification.state_id,
+                origin_node_id,
+                target
+            );
+        }
+
+        Ok(())
+    }
+
     /// Apply patches on node's run-time agents.
     ///
     /// * `origin_lids` - The logical resource identifiers for which patches
@@ -296,17 +535,51 @@ impl OrchestratorTask {
 
             // Transform logical identifiers (LIDs) into internal ones (PIDs).
             for source in self.lid_to_pid(origin_lid) {
+                // The events towards a logical function with multiple
+                // instances are split evenly among them.
                 let mut physical_output_mapping = std::collections::HashMap::new();
+                let mut physical_output_splits = std::collections::HashMap::new();
                 for (channel, target_lid) in logical_output_mapping {
-                    for target in self.lid_to_pid(target_lid) {
-                        // [TODO] Issue#96 The output_mapping structure
-                        // should be changed so that multiple
-                        // values are possible (with weights), and
-                        // this change must be applied to runners,
-                        // as well. For now, we just keep
-                        // overwriting the same entry.
+                    let targets = self.lid_to_pid(target_lid);
+                    if let Some(target) = targets.first() {
                         physical_output_mapping.insert(channel.clone(), target.instance_id());
                     }
+                    if targets.len() > 1 {
+                        physical_output_splits.insert(
+                            channel.clone(),
+                            targets
+                                .iter()
+                                .map(|target| edgeless_api::common::WeightedTarget {
+                                    instance_id: target.instance_id(),
+                                    weight: 1,
+                                })
+                                .collect::<Vec<edgeless_api::common::WeightedTarget>>(),
+                        );
+                    }
+                }
+
+                // The instances of a target in a split output channel
+                // receive an equal share of the events for the logical
+                // function/resource, as given by its weight.
+                for (channel, weighted_lids) in self.output_splits.get(origin_lid).into_iter().flatten() {
+                    let weighted_pids = weighted_lids
+                        .iter()
+                        .map(|(target_lid, weight)| (self.lid_to_pid(target_lid), *weight))
+                        .collect::<Vec<(Vec<Pid>, u32)>>();
+                    let num_shares = weighted_pids.iter().map(|(pids, _weight)| pids.len()).filter(|x| *x > 0).fold(1, lcm);
+                    let weighted_targets = weighted_pids
+                        .iter()
+                        .flat_map(|(pids, weight)| {
+                            let weight = weight * (num_shares / pids.len().max(1)) as u32;
+                            pids.iter().map(move |target| edgeless_api::common::WeightedTarget {
+                                instance_id: target.instance_id(),
+                                weight,
+                            })
+                        })
+                        .collect::<Vec<edgeless_api::common::WeightedTarget>>();
+                    if !weighted_targets.is_empty() {
+                        physical_output_splits.insert(channel.clone(), weighted_targets);
+                    }
                 }
 
                 // Notify the new mapping to the node / resource.
@@ -318,6 +591,7 @@ impl OrchestratorTask {
                             .patch(edgeless_api::common::PatchRequest {
                                 function_id: instance_id.function_id,
                                 output_mapping: physical_output_mapping,
+                                output_splits: physical_output_splits,
                             })
                             .await
                         {
@@ -344,6 +618,7 @@ impl OrchestratorTask {
                             .patch(edgeless_api::common::PatchRequest {
                                 function_id: instance_id.function_id,
                                 output_mapping: physical_output_mapping,
+                                output_splits: physical_output_splits,
                             })
                             .await
                         {
@@ -427,7 +702,12 @@ impl OrchestratorTask {
                 let deployment_requirements = crate::deployment_requirements::DeploymentRequirements::from_annotations(&resource_req.configuration);
                 if provider.class_type == resource_req.class_type
                     && !cordoned_nodes.contains(&provider.node_id)
-                    && deployment_requirements.is_feasible(&provider.node_id, capabilities, &std::collections::HashSet::default())
+                    && deployment_requirements.is_feasible(
+                        &provider.node_id,
+                        capabilities,
+                        &std::collections::HashSet::default(),
+                        &crate::deployment_requirements::PlacementConstraints::default(),
+                    )
                 {
                     Some(provider_id.clone())
                 } else {
@@ -453,6 +733,7 @@ impl OrchestratorTask {
             node_id,
             capabilities,
             &std::collections::HashSet::default(),
+            &crate::deployment_requirements::PlacementConstraints::default(),
         ) {
             return false;
         }
@@ -471,18 +752,141 @@ impl OrchestratorTask {
     /// Orchestration strategy can also be changed during
     /// runtime.
     ///
+    /// If the function has a locality-aware placement, then the node is
+    /// selected based on the nodes hosting the components exchanging events
+    /// with it or, if there are none yet, the other components of the same
+    /// workflow.
+    ///
+    /// * `lid` - The logical identifier of the function.
     /// * `spawn_req` - The specifications of the function.
-    /// * `orchestration_logic` - The orchestration logic configured at run-time.
     fn select_node(
         &mut self,
+        lid: &edgeless_api::function_instance::ComponentId,
         spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest,
     ) -> anyhow::Result<edgeless_api::function_instance::NodeId> {
-        match self.orchestration_logic.next(spawn_req) {
+        self.update_placement();
+        let constraints = self.placement_constraints(lid, spawn_req, false);
+
+        let node_id = match crate::placement::Placement::from_annotations(&spawn_req.annotations) {
+            crate::placement::Placement::Default => self.orchestration_logic.next(spawn_req, &constraints),
+            crate::placement::Placement::Locality => {
+                let (upstream, mut downstream) = self.neighbor_nodes(lid);
+                if upstream.is_empty() && downstream.is_empty() {
+                    downstream = self.workflow_nodes(lid, &spawn_req.workflow_id);
+                }
+                self.orchestration_logic.next_local(spawn_req, &constraints, &upstream, &downstream)
+            }
+        };
+
+        match node_id {
             Some(node_id) => Ok(node_id),
             None => Err(anyhow::anyhow!("no valid node found")),
         }
     }
 
+    /// Return the constraints for the placement of a function instance due
+    /// to its anti-affinity and spread requirements, based on the nodes
+    /// hosting the other function instances.
+    ///
+    /// * `lid` - The logical identifier of the function.
+    /// * `spawn_req` - The specifications of the function.
+    /// * `replace` - True if the current instances of the function are
+    ///   going to be replaced, in which case they are not considered.
+    fn placement_constraints(
+        &self,
+        lid: &edgeless_api::function_instance::ComponentId,
+        spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest,
+        replace: bool,
+    ) -> crate::deployment_requirements::PlacementConstraints {
+        let reqs = crate::deployment_requirements::DeploymentRequirements::from_annotations(&spawn_req.annotations);
+        let mut excluded_nodes = std::collections::HashSet::new();
+        let mut spread_peers = vec![];
+        for (other_lid, active_instance) in &self.active_instances {
+            let (other_spawn_req, instances) = match active_instance {
+                crate::active_instance::ActiveInstance::Function(other_spawn_req, instances) => (other_spawn_req, instances),
+                crate::active_instance::ActiveInstance::Resource(_, _) => continue,
+            };
+            let same_function = other_lid == lid;
+            if same_function && replace {
+                continue;
+            }
+            let same_workflow = other_spawn_req.workflow_id == spawn_req.workflow_id;
+            let anti_affinity = match reqs.anti_affinity {
+                crate::anti_affinity::AntiAffinity::None => false,
+                crate::anti_affinity::AntiAffinity::Replicas => same_function,
+                crate::anti_affinity::AntiAffinity::Workflow => same_function || same_workflow,
+            };
+            let spread = reqs.spread_by_label.is_some()
+                && (same_function
+                    || (same_workflow
+                        && crate::deployment_requirements::DeploymentRequirements::from_annotations(&other_spawn_req.annotations).spread_by_label
+                            == reqs.spread_by_label));
+            for instance_id in instances {
+                if anti_affinity {
+                    excluded_nodes.insert(instance_id.node_id);
+                }
+                if spread {
+                    spread_peers.push(instance_id.node_id);
+                }
+            }
+        }
+        self.orchestration_logic.placement_constraints(spawn_req, excluded_nodes, &spread_peers)
+    }
+
+    /// Update the number of function instances assigned to each node in
+    /// the orchestration logic.
+    fn update_placement(&mut self) {
+        let mut num_instances = std::collections::HashMap::new();
+        for active_instance in self.active_instances.values() {
+            if let crate::active_instance::ActiveInstance::Function(_spawn_req, instances) = active_instance {
+                for instance_id in instances {
+                    *num_instances.entry(instance_id.node_id).or_insert(0) += 1;
+                }
+            }
+        }
+        self.orchestration_logic.update_placement(num_instances);
+    }
+
+    /// Return the nodes hosting the instances of the components sending
+    /// events to the given one and those of the components receiving events
+    /// from it, according to the active patches, with repetitions.
+    ///
+    /// * `lid` - The logical identifier of the function.
+    fn neighbor_nodes(
+        &self,
+        lid: &edgeless_api::function_instance::ComponentId,
+    ) -> (Vec<edgeless_api::function_instance::NodeId>, Vec<edgeless_api::function_instance::NodeId>) {
+        let nodes = |lids: Vec<uuid::Uuid>| {
+            lids.iter()
+                .filter(|other_lid| *other_lid != lid)
+                .flat_map(|other_lid| self.lid_to_pid(other_lid))
+                .map(|pid| pid.instance_id().node_id)
+                .filter(|node_id| self.nodes.contains_key(node_id))
+                .collect::<Vec<edgeless_api::function_instance::NodeId>>()
+        };
+        (nodes(self.dependencies(lid)), nodes(self.output_targets(lid).into_iter().collect()))
+    }
+
+    /// Return the nodes hosting the instances of the other components of a
+    /// workflow, with repetitions.
+    ///
+    /// * `lid` - The logical identifier of the function, which is excluded.
+    /// * `workflow_id` - The identifier of the workflow.
+    fn workflow_nodes(&self, lid: &edgeless_api::function_instance::ComponentId, workflow_id: &str) -> Vec<edgeless_api::function_instance::NodeId> {
+        let mut nodes = vec![];
+        for (other_lid, active_instance) in &self.active_instances {
+            if other_lid == lid || active_instance.workflow_id() != workflow_id {
+                continue;
+            }
+            for instance_id in active_instance.instance_ids() {
+                if self.nodes.contains_key(&instance_id.node_id) {
+                    nodes.push(instance_id.node_id);
+                }
+            }
+        }
+        nodes
+    }
+
     /// Start a new function instance on node assigned by orchestration's logic.
     async fn start_function(
         &mut self,
@@ -492,7 +896,7 @@ impl OrchestratorTask {
         let lid = uuid::Uuid::new_v4();
 
         // Select the target node.
-        match self.select_node(spawn_req) {
+        match self.select_node(&lid, spawn_req) {
             Ok(node_id) => {
                 // Start the function instance.
                 self.start_function_in_node(spawn_req, &lid, &node_id).await
@@ -524,7 +928,9 @@ impl OrchestratorTask {
                 };
                 self.apply_patches(self.dependencies(&lid)).await;
                 self.dependency_graph.remove(&lid);
+                self.output_splits.remove(&lid);
                 self.dependency_graph_changed = true;
+                self.autoscaler.remove(&lid);
             }
             None => {
                 log::error!("Request to stop a function that is not known: lid {}", lid);
@@ -541,11 +947,29 @@ impl OrchestratorTask {
             .iter()
             .map(|x| (x.0.clone(), x.1.function_id))
             .collect::<std::collections::HashMap<String, edgeless_api::function_instance::ComponentId>>();
+        let output_splits = update
+            .output_splits
+            .iter()
+            .map(|(channel, targets)| {
+                (
+                    channel.clone(),
+                    targets
+                        .iter()
+                        .map(|target| (target.instance_id.function_id, target.weight))
+                        .collect::<Vec<(edgeless_api::function_instance::ComponentId, u32)>>(),
+                )
+            })
+            .collect::<std::collections::HashMap<String, Vec<(edgeless_api::function_instance::ComponentId, u32)>>>();
 
         // Save the patch request into an internal data structure,
         // keeping track only of the lid for both origin
         // and target (logical) functions.
         self.dependency_graph.insert(origin_lid, output_mapping);
+        if output_splits.is_empty() {
+            self.output_splits.remove(&origin_lid);
+        } else {
+            self.output_splits.insert(origin_lid, output_splits);
+        }
         self.dependency_graph_changed = true;
 
         // Apply the patch.
@@ -567,6 +991,30 @@ impl OrchestratorTask {
         lid: &uuid::Uuid,
         node_id: &edgeless_api::function_instance::NodeId,
     ) -> Result<edgeless_api::common::StartComponentResponse<uuid::Uuid>, anyhow::Error> {
+        // The function is associated with this instance only: additional
+        // instances are added by the autoscaler, if needed.
+        let instance_id = self.start_function_instance(spawn_req, lid, node_id).await?;
+        self.active_instances.insert(
+            *lid,
+            crate::active_instance::ActiveInstance::Function(spawn_req.clone(), vec![instance_id]),
+        );
+        self.active_instances_changed = true;
+
+        Ok(edgeless_api::common::StartComponentResponse::InstanceId(*lid))
+    }
+
+    /// Start a new instance of a function on a specific node, without
+    /// associating it with the logical function.
+    ///
+    /// * `spawn_req` - The specifications of the function.
+    /// * `lid` - The logical identifier of the function.
+    /// * `node_id` - The node where to deploy the function instance.
+    async fn start_function_instance(
+        &mut self,
+        spawn_req: &edgeless_api::function_instance::SpawnFunctionRequest,
+        lid: &uuid::Uuid,
+        node_id: &edgeless_api::function_instance::NodeId,
+    ) -> anyhow::Result<edgeless_api::function_instance::InstanceId> {
         let mut fn_client = match self.nodes.get_mut(node_id) {
             Some(c) => c,
             None => panic!(
@@ -586,7 +1034,6 @@ impl OrchestratorTask {
 
         // Finally try to spawn the function instance on the
         // selected client.
-        // [TODO] Issue#96 We assume that one instance is spawned.
         match fn_client.start(spawn_req.clone()).await {
             Ok(res) => match res {
                 edgeless_api::common::StartComponentResponse::ResponseError(err) => {
@@ -594,20 +1041,11 @@ impl OrchestratorTask {
                 }
                 edgeless_api::common::StartComponentResponse::InstanceId(id) => {
                     assert!(*node_id == id.node_id);
-                    self.active_instances.insert(
-                        *lid,
-                        crate::active_instance::ActiveInstance::Function(
-                            spawn_req.clone(),
-                            vec![edgeless_api::function_instance::InstanceId {
-                                node_id: *node_id,
-                                function_id: id.function_id,
-                            }],
-                        ),
-                    );
-                    self.active_instances_changed = true;
                     log::info!("Spawned at node_id {}, LID {}, pid {}", node_id, &lid, id.function_id);
-
-                    Ok(edgeless_api::common::StartComponentResponse::InstanceId(*lid))
+                    Ok(edgeless_api::function_instance::InstanceId {
+                        node_id: *node_id,
+                        function_id: id.function_id,
+                    })
                 }
             },
             Err(err) => {
@@ -709,6 +1147,7 @@ impl OrchestratorTask {
                 }
                 self.apply_patches(self.dependencies(&lid)).await;
                 self.dependency_graph.remove(&lid);
+                self.output_splits.remove(&lid);
                 self.dependency_graph_changed = true;
             }
             None => {
@@ -748,17 +1187,28 @@ impl OrchestratorTask {
     /// identified by `lid`.
     fn dependencies(&self, lid: &uuid::Uuid) -> Vec<uuid::Uuid> {
         let mut dependencies = vec![];
-        for (origin_lid, output_mapping) in self.dependency_graph.iter() {
-            for (_output, target_lid) in output_mapping.iter() {
-                if target_lid == lid {
-                    dependencies.push(*origin_lid);
-                    break;
-                }
+        for origin_lid in self.dependency_graph.keys() {
+            if self.output_targets(origin_lid).contains(lid) {
+                dependencies.push(*origin_lid);
             }
         }
         dependencies
     }
 
+    /// Return the lids of the targets of the output channels of the given
+    /// one, according to the active patches, including those receiving only
+    /// a fraction of the events of a split output channel.
+    fn output_targets(&self, origin_lid: &uuid::Uuid) -> std::collections::HashSet<uuid::Uuid> {
+        let mut targets = std::collections::HashSet::new();
+        if let Some(output_mapping) = self.dependency_graph.get(origin_lid) {
+            targets.extend(output_mapping.values().cloned());
+        }
+        if let Some(output_splits) = self.output_splits.get(origin_lid) {
+            targets.extend(output_splits.values().flatten().map(|(target_lid, _weight)| *target_lid));
+        }
+        targets
+    }
+
     /// Return the aggregated capabilities of the nodes in the domain.
     fn domain_capabilities(&self) -> edgeless_api::domain_registration::DomainCapabilities {
         let mut ret = edgeless_api::domain_registration::DomainCapabilities::default();
@@ -924,6 +1374,31 @@ impl OrchestratorTask {
         proxy.update_resource_providers(&self.resource_providers);
     }
 
+    /// Notify the domain register of the LIDs of the active functions and
+    /// resources, if they changed since the last notification.
+    async fn update_active_instances(&mut self) {
+        let mut functions = std::collections::HashSet::new();
+        let mut resources = std::collections::HashSet::new();
+        for (lid, active_instance) in &self.active_instances {
+            match active_instance {
+                crate::active_instance::ActiveInstance::Function(..) => functions.insert(*lid),
+                crate::active_instance::ActiveInstance::Resource(..) => resources.insert(*lid),
+            };
+        }
+        if functions == self.reported_functions && resources == self.reported_resources {
+            return;
+        }
+        let _ = self
+            .subscriber_sender
+            .send(super::domain_subscriber::DomainSubscriberRequest::UpdateActiveInstances(
+                functions.clone(),
+                resources.clone(),
+            ))
+            .await;
+        self.reported_functions = functions;
+        self.reported_resources = resources;
+    }
+
     async fn refresh(&mut self) {
         //
         // Make sure that all active logical functions are assigned
@@ -992,11 +1467,11 @@ impl OrchestratorTask {
 
         // Also schedule to repatch all the functions that
         // depend on the functions/resources modified.
-        for (origin_lid, output_mapping) in self.dependency_graph.iter() {
-            for (_output, target_lid) in output_mapping.iter() {
-                if active_instances_to_be_updated.contains(target_lid)
-                    || fun_to_be_created.contains_key(target_lid)
-                    || res_to_be_created.contains_key(target_lid)
+        for origin_lid in self.dependency_graph.keys() {
+            for target_lid in self.output_targets(origin_lid) {
+                if active_instances_to_be_updated.contains(&target_lid)
+                    || fun_to_be_created.contains_key(&target_lid)
+                    || res_to_be_created.contains_key(&target_lid)
                 {
                     to_be_repatched.push(*origin_lid);
                 }
@@ -1024,7 +1499,7 @@ impl OrchestratorTask {
         // function remains in the active_instances, but it is
         // assigned no function instance.
         for (lid, spawn_req) in fun_to_be_created.into_iter() {
-            let res = match self.select_node(&spawn_req) {
+            let res = match self.select_node(&lid, &spawn_req) {
                 Ok(node_id) => {
                     // Start the function instance.
                     match self.start_function_in_node(&spawn_req, &lid, &node_id).await {
@@ -1083,8 +1558,8 @@ impl OrchestratorTask {
                             to_be_repatched.push(lid);
 
                             // Repatch all the component that depend on it.
-                            for (origin_lid, output_mapping) in self.dependency_graph.iter() {
-                                if output_mapping.values().contains(&lid) {
+                            for origin_lid in self.dependency_graph.keys() {
+                                if self.output_targets(origin_lid).contains(&lid) {
                                     to_be_repatched.push(*origin_lid);
                                 }
                             }
@@ -1113,9 +1588,16 @@ impl OrchestratorTask {
             self.orchestration_logic.update_nodes(&self.nodes, &self.resource_providers);
         }
 
+        // Move the functions with a locality-aware placement closer to the
+        // components with which they exchange events, if convenient.
+        to_be_repatched.append(&mut self.relocate().await);
+
         // Repatch everything that needs to be repatched.
         self.apply_patches(to_be_repatched).await;
 
+        // Add or remove function instances based on their load.
+        self.autoscale().await;
+
         // Update the proxy.
         let mut proxy = self.proxy.lock().await;
         if self.active_instances_changed {
@@ -1124,10 +1606,246 @@ impl OrchestratorTask {
         }
         if self.dependency_graph_changed {
             proxy.update_dependency_graph(&self.dependency_graph);
+            proxy.update_output_splits(&self.output_splits);
             self.dependency_graph_changed = false;
         }
     }
 
+    /// Add or remove instances of the functions with a scaling policy, as
+    /// decided by the autoscaler, and repatch the functions/resources that
+    /// depend on them.
+    ///
+    /// The functions currently without instances are ignored, since they
+    /// are handled during the refresh.
+    async fn autoscale(&mut self) {
+        let now = std::time::Instant::now();
+        let scalable_functions = self
+            .active_instances
+            .iter()
+            .filter_map(|(lid, active_instance)| match active_instance {
+                crate::active_instance::ActiveInstance::Function(spawn_req, instances) if !instances.is_empty() => {
+                    let policy = crate::autoscaler::ScalingPolicy::from_annotations(&spawn_req.annotations);
+                    if policy.is_scalable() {
+                        Some((*lid, policy, instances.clone()))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            })
+            .collect::<Vec<(
+                uuid::Uuid,
+                crate::autoscaler::ScalingPolicy,
+                Vec<edgeless_api::function_instance::InstanceId>,
+            )>>();
+
+        for (lid, policy, instances) in scalable_functions {
+            let latency = match policy.target_latency_ms {
+                Some(_) => self.latency(&instances, &policy.latency_window()).await,
+                None => None,
+            };
+            let num_instances = instances.len();
+            let target = self.autoscaler.target_instances(&lid, &policy, num_instances, latency, now);
+            if target == num_instances {
+                continue;
+            }
+            log::info!(
+                "Scaling function LID {} from {} to {} instances, latency {} ms ({})",
+                lid,
+                num_instances,
+                target,
+                latency.map(|x| format!("{:.1}", x * 1000.0)).unwrap_or(String::from("unknown")),
+                policy
+            );
+
+            if target > num_instances {
+                // Start the new instances, then patch them and the
+                // functions/resources that depend on this function.
+                for _ in num_instances..target {
+                    if let Err(err) = self.add_function_instance(&lid).await {
+                        log::warn!("Could not add an instance of function LID {}: {}", lid, err);
+                        break;
+                    }
+                }
+                let mut to_be_repatched = self.dependencies(&lid);
+                to_be_repatched.push(lid);
+                self.apply_patches(to_be_repatched).await;
+            } else {
+                // Remove the most recent instances, then repatch the
+                // functions/resources that depend on this function, so
+                // that no more events are sent to them, and stop them.
+                let mut removed = vec![];
+                if let Some(crate::active_instance::ActiveInstance::Function(_spawn_req, instances)) = self.active_instances.get_mut(&lid) {
+                    for _ in target..num_instances {
+                        removed.extend(instances.pop());
+                    }
+                }
+                self.active_instances_changed = true;
+                self.apply_patches(self.dependencies(&lid)).await;
+                for instance_id in removed {
+                    self.stop_function(&instance_id).await;
+                }
+            }
+        }
+    }
+
+    /// Start a new instance of an active function, preferably on a node
+    /// that is not hosting any instance of the same function yet.
+    ///
+    /// * `lid` - The logical identifier of the function.
+    async fn add_function_instance(&mut self, lid: &uuid::Uuid) -> anyhow::Result<()> {
+        let (spawn_req, instances) = match self.active_instances.get(lid) {
+            Some(crate::active_instance::ActiveInstance::Function(spawn_req, instances)) => (spawn_req.clone(), instances.clone()),
+            _ => anyhow::bail!("no active function with LID {}", lid),
+        };
+
+        let hosting_nodes = instances
+            .iter()
+            .map(|instance_id| instance_id.node_id)
+            .collect::<std::collections::HashSet<edgeless_api::function_instance::NodeId>>();
+        let other_nodes = self
+            .nodes
+            .keys()
+            .filter(|node_id| !hosting_nodes.contains(node_id))
+            .cloned()
+            .collect::<Vec<edgeless_api::function_instance::NodeId>>();
+        let constraints = self.placement_constraints(lid, &spawn_req, false);
+        let node_id = match self
+            .orchestration_logic
+            .feasible_nodes(&spawn_req, &other_nodes, &constraints)
+            .choose(&mut self.rng)
+        {
+            Some(node_id) => *node_id,
+            None => self.select_node(lid, &spawn_req)?,
+        };
+
+        let instance_id = self.start_function_instance(&spawn_req, lid, &node_id).await?;
+        if let Some(crate::active_instance::ActiveInstance::Function(_spawn_req, instances)) = self.active_instances.get_mut(lid) {
+            instances.push(instance_id);
+        }
+        self.active_instances_changed = true;
+        Ok(())
+    }
+
+    /// Return the average latency, in s, of the given function instances,
+    /// i.e., the sum of the execution and transfer times, as measured by
+    /// the nodes during the given window of time.
+    /// Return None if there are no execution time samples.
+    async fn latency(&mut self, instances: &[edgeless_api::function_instance::InstanceId], window: &std::time::Duration) -> Option<f64> {
+        let since = chrono::Utc::now() - chrono::Duration::from_std(*window).unwrap_or(chrono::Duration::zero());
+        let mut execution_times = vec![];
+        let mut transfer_times = vec![];
+        let mut proxy = self.proxy.lock().await;
+        for instance_id in instances {
+            let pid = instance_id.function_id.to_string();
+            execution_times.append(&mut proxy.fetch_performance_series_since(&pid, "function_execution_time", &since));
+            transfer_times.append(&mut proxy.fetch_performance_series_since(&pid, "function_transfer_time", &since));
+        }
+        let execution_time = crate::autoscaler::average_since(&execution_times, &since)?;
+        Some(execution_time + crate::autoscaler::average_since(&transfer_times, &since).unwrap_or_default())
+    }
+
+    /// Migrate the functions with a locality-aware placement to the node
+    /// that minimizes their locality cost, based on the active patches and
+    /// the transfer latencies measured between the nodes.
+    ///
+    /// A function is migrated only if it has a single instance, it exchanges
+    /// events with other components, and its cost decreases at least by
+    /// `MIN_RELOCATION_GAIN`, to avoid oscillations.
+    ///
+    /// Return the LIDs of the functions migrated and of those that depend
+    /// on them, which have to be repatched.
+    async fn relocate(&mut self) -> Vec<edgeless_api::function_instance::ComponentId> {
+        let local_functions = self
+            .active_instances
+            .iter()
+            .filter_map(|(lid, active_instance)| match active_instance {
+                crate::active_instance::ActiveInstance::Function(spawn_req, instances)
+                    if instances.len() == 1
+                        && crate::placement::Placement::from_annotations(&spawn_req.annotations) == crate::placement::Placement::Locality =>
+                {
+                    Some((*lid, spawn_req.clone(), instances[0].node_id))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if local_functions.is_empty() {
+            return vec![];
+        }
+
+        self.update_transfer_latencies().await;
+
+        let mut to_be_repatched = vec![];
+        for (lid, spawn_req, current) in local_functions {
+            let (upstream, downstream) = self.neighbor_nodes(&lid);
+            if upstream.is_empty() && downstream.is_empty() {
+                continue;
+            }
+            self.update_placement();
+            let constraints = self.placement_constraints(&lid, &spawn_req, true);
+            let target = match self.orchestration_logic.next_local(&spawn_req, &constraints, &upstream, &downstream) {
+                Some(target) if target != current => target,
+                _ => continue,
+            };
+            let current_cost = self.orchestration_logic.locality_cost(&current, &upstream, &downstream);
+            let target_cost = self.orchestration_logic.locality_cost(&target, &upstream, &downstream);
+            if target_cost > current_cost * (1.0 - crate::placement::MIN_RELOCATION_GAIN) {
+                continue;
+            }
+
+            match self.migrate(&lid, &vec![target]).await {
+                Ok(target) => {
+                    log::info!(
+                        "Function LID {} relocated from node_id {} to {}, locality cost {} -> {}",
+                        lid,
+                        current,
+                        target,
+                        current_cost,
+                        target_cost
+                    );
+                    to_be_repatched.push(lid);
+                    to_be_repatched.append(&mut self.dependencies(&lid));
+                }
+                Err(err) => log::warn!("Could not relocate function LID {}: {}", lid, err),
+            }
+        }
+        to_be_repatched
+    }
+
+    /// Update the transfer latencies between the nodes in the orchestration
+    /// logic with the average transfer times measured by the function
+    /// instances whose events come from components hosted on a single node.
+    async fn update_transfer_latencies(&mut self) {
+        let since = chrono::Utc::now() - chrono::Duration::from_std(crate::placement::TRANSFER_LATENCY_WINDOW).unwrap_or(chrono::Duration::zero());
+
+        // key:   (origin node, target node)
+        // value: average transfer times of the target function instances
+        let mut latencies = std::collections::HashMap::<(uuid::Uuid, uuid::Uuid), Vec<f64>>::new();
+        for (lid, active_instance) in &self.active_instances {
+            let instances = match active_instance {
+                crate::active_instance::ActiveInstance::Function(_spawn_req, instances) => instances,
+                crate::active_instance::ActiveInstance::Resource(_, _) => continue,
+            };
+            let (upstream, _downstream) = self.neighbor_nodes(lid);
+            let origin = match upstream.first() {
+                Some(origin) if upstream.iter().all(|node_id| node_id == origin) => *origin,
+                _ => continue,
+            };
+            let mut proxy = self.proxy.lock().await;
+            for instance_id in instances {
+                let series = proxy.fetch_performance_series_since(&instance_id.function_id.to_string(), "function_transfer_time", &since);
+                if let Some(latency) = crate::autoscaler::average_since(&series, &since) {
+                    latencies.entry((origin, instance_id.node_id)).or_default().push(latency);
+                }
+            }
+        }
+
+        for ((origin, target), values) in latencies {
+            self.orchestration_logic
+                .update_transfer_latency(&origin, &target, values.iter().sum::<f64>() / values.len() as f64);
+        }
+    }
+
     async fn reset(&mut self) {
         log::info!("Resetting the orchestration domain to a clean state");
         let mut function_lids = vec![];
@@ -1146,3 +1864,12 @@ impl OrchestratorTask {
         }
     }
 }
+
+/// Return the least common multiple of two positive integers.
+fn lcm(a: usize, b: usize) -> usize {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    a / x * b
+}
@@ -0,0 +1,61 @@
+// SPDX-FileCopyrightText: © 2024 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Window over which the transfer latencies between nodes are averaged.
+pub const TRANSFER_LATENCY_WINDOW: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Transfer latency, in s, assumed between two different nodes when it has
+/// not been measured.
+pub const DEFAULT_TRANSFER_LATENCY: f64 = 0.01;
+
+/// Minimum fraction by which the cost of a function instance must decrease
+/// for the instance to be relocated to another node.
+pub const MIN_RELOCATION_GAIN: f64 = 0.2;
+
+#[derive(PartialEq, Debug, Clone)]
+/// Placement policy of a function, as specified by annotations.
+pub enum Placement {
+    /// The node is selected by the orchestration strategy in the settings.
+    Default,
+    /// The node is selected so as to minimize the latency of the transfers
+    /// with the components exchanging events with the function.
+    Locality,
+}
+
+impl std::fmt::Display for Placement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Placement::Default => "default",
+                Placement::Locality => "locality",
+            }
+        )
+    }
+}
+
+impl Placement {
+    /// Placement policy from the annotations in the function's spawn request.
+    pub fn from_annotations(annotations: &std::collections::HashMap<String, String>) -> Self {
+        match annotations.get("placement") {
+            Some(val) if val.to_lowercase() == "locality" => Placement::Locality,
+            _ => Placement::Default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_placement_from_annotations() {
+        let placement = |val: &str| Placement::from_annotations(&std::collections::HashMap::from([("placement".to_string(), val.to_string())]));
+        assert_eq!(Placement::Default, Placement::from_annotations(&std::collections::HashMap::new()));
+        assert_eq!(Placement::Locality, placement("locality"));
+        assert_eq!(Placement::Locality, placement("Locality"));
+        assert_eq!(Placement::Default, placement("default"));
+        assert_eq!(Placement::Default, placement("unknown"));
+    }
+}
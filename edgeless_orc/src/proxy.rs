@@ -15,6 +15,15 @@ pub enum Category {
     DependencyGraph,
 }
 
+/// Output channels whose events are split among multiple logical
+/// function/resource instances.
+///
+/// key:   lid (origin function/resource)
+/// value: map of:
+///        key:   channel output name
+///        value: list of lid (target function/resource) and weight
+pub type OutputSplits = std::collections::HashMap<uuid::Uuid, std::collections::HashMap<String, Vec<(uuid::Uuid, u32)>>>;
+
 pub type PerformanceSeries = Vec<(chrono::DateTime<chrono::Utc>, String)>;
 pub type PerformanceSamples = std::collections::HashMap<String, PerformanceSeries>;
 pub type NodeHealthStatuses = std::collections::HashMap<
@@ -36,6 +45,10 @@ pub trait Proxy: Sync + Send {
     /// Update the dependency graph.
     fn update_dependency_graph(&mut self, dependency_graph: &std::collections::HashMap<uuid::Uuid, std::collections::HashMap<String, uuid::Uuid>>);
 
+    /// Update the output channels whose events are split among multiple
+    /// targets.
+    fn update_output_splits(&mut self, output_splits: &OutputSplits);
+
     // Update the domain's info.
     fn update_domain_info(&mut self, domain_info: &crate::domain_info::DomainInfo);
 
@@ -76,6 +89,14 @@ pub trait Proxy: Sync + Send {
     /// Fetch the performance samples for a given component and series.
     fn fetch_performance_series(&mut self, node: &str, series: &str) -> PerformanceSeries;
 
+    /// Fetch the performance samples for a given component and series that
+    /// are more recent than `since`.
+    fn fetch_performance_series_since(&mut self, node: &str, series: &str, since: &chrono::DateTime<chrono::Utc>) -> PerformanceSeries {
+        let mut ret = self.fetch_performance_series(node, series);
+        ret.retain(|(timestamp, _value)| timestamp > since);
+        ret
+    }
+
     /// Fetch the spawn requests of active function instances.
     fn fetch_function_instance_requests(
         &mut self,
@@ -108,6 +129,10 @@ pub trait Proxy: Sync + Send {
     /// Fetch all the dependecies of logical function/resource instances.
     fn fetch_dependency_graph(&mut self) -> std::collections::HashMap<uuid::Uuid, std::collections::HashMap<String, uuid::Uuid>>;
 
+    /// Fetch the output channels of logical function/resource instances
+    /// whose events are split among multiple targets, with the weights.
+    fn fetch_output_splits(&mut self) -> OutputSplits;
+
     /// Fetch the mapping between logical function/resource identifiers and
     /// workflow identifiers.
     fn fetch_logical_id_to_workflow_id(&mut self) -> std::collections::HashMap<edgeless_api::function_instance::ComponentId, String>;
@@ -9,6 +9,7 @@ impl super::proxy::Proxy for ProxyNone {
     fn update_resource_providers(&mut self, _resource_providers: &std::collections::HashMap<String, crate::resource_provider::ResourceProvider>) {}
     fn update_active_instances(&mut self, _active_instances: &std::collections::HashMap<uuid::Uuid, crate::active_instance::ActiveInstance>) {}
     fn update_dependency_graph(&mut self, _dependency_graph: &std::collections::HashMap<uuid::Uuid, std::collections::HashMap<String, uuid::Uuid>>) {}
+    fn update_output_splits(&mut self, _output_splits: &crate::proxy::OutputSplits) {}
     fn update_domain_info(&mut self, _domain_info: &crate::domain_info::DomainInfo) {}
     fn push_node_health(&mut self, _node_id: &uuid::Uuid, _node_health: edgeless_api::node_registration::NodeHealthStatus) {}
     fn push_performance_samples(&mut self, _node_id: &uuid::Uuid, _performance_samples: edgeless_api::node_registration::NodePerformanceSamples) {}
@@ -73,6 +74,9 @@ impl super::proxy::Proxy for ProxyNone {
     fn fetch_dependency_graph(&mut self) -> std::collections::HashMap<uuid::Uuid, std::collections::HashMap<String, uuid::Uuid>> {
         std::collections::HashMap::new()
     }
+    fn fetch_output_splits(&mut self) -> crate::proxy::OutputSplits {
+        std::collections::HashMap::new()
+    }
     fn fetch_logical_id_to_workflow_id(&mut self) -> std::collections::HashMap<edgeless_api::function_instance::ComponentId, String> {
         std::collections::HashMap::new()
     }
@@ -26,6 +26,7 @@ struct DatasetDumping {
 /// - provider::ID, where ID is the resource provider identifier
 /// - instance::UUID, where UUID is the logical function/resource identifier
 /// - dependency::UUID, where UUID is the logical function/resource identifier
+/// - split::UUID, where UUID is the logical function/resource identifier
 ///
 /// All the values are JSON structures.
 ///
@@ -35,6 +36,7 @@ pub struct ProxyRedis {
     resource_provider_ids: std::collections::HashSet<String>,
     active_instance_uuids: std::collections::HashSet<uuid::Uuid>,
     dependency_uuids: std::collections::HashSet<uuid::Uuid>,
+    split_uuids: std::collections::HashSet<uuid::Uuid>,
 
     // last update timestamps
     last_update_timestamps: std::collections::HashMap<crate::proxy::Category, String>,
@@ -111,6 +113,7 @@ impl ProxyRedis {
             resource_provider_ids: std::collections::HashSet::new(),
             active_instance_uuids: std::collections::HashSet::new(),
             dependency_uuids: std::collections::HashSet::new(),
+            split_uuids: std::collections::HashSet::new(),
             last_update_timestamps: std::collections::HashMap::new(),
             mapping_to_instance_id: std::collections::HashMap::new(),
             node_capabilities: std::collections::HashMap::new(),
@@ -219,6 +222,32 @@ impl ProxyRedis {
         now.timestamp() as f64 + now.timestamp_subsec_nanos() as f64 / 1e9
     }
 
+    /// Fetch the performance samples for a given component and series, with
+    /// a timestamp, used as score in the sorted set, above `min`, which uses
+    /// the syntax of the Redis command `ZRANGEBYSCORE`.
+    fn fetch_performance_series_by_score(&mut self, node: &str, series: &str, min: &str) -> crate::proxy::PerformanceSeries {
+        let mut ret = vec![];
+
+        if let Ok(values) = self
+            .connection
+            .zrangebyscore::<String, &str, &str, Vec<String>>(format!("performance:{node}:{series}"), min, "+inf")
+        {
+            for value in values {
+                if let Some((timestamp, value)) = value.split_once(":")
+                    && let Ok(timestamp) = timestamp.parse::<f64>()
+                {
+                    let secs = timestamp as i64;
+                    let nsecs = (timestamp.fract() * 1e9) as u32;
+                    if let Some(date_time) = chrono::DateTime::from_timestamp(secs, nsecs) {
+                        ret.push((date_time, value.to_string()));
+                    }
+                }
+            }
+        }
+
+        ret
+    }
+
     fn fetch_instances(&mut self) -> std::collections::HashMap<edgeless_api::function_instance::ComponentId, crate::active_instance::ActiveInstance> {
         self.local_timestamp_update(&crate::proxy::Category::ActiveInstances);
         let mut instance_ids = vec![];
@@ -418,6 +447,21 @@ impl super::proxy::Proxy for ProxyRedis {
         self.dependency_uuids = new_dependency_uuids;
     }
 
+    fn update_output_splits(&mut self, output_splits: &crate::proxy::OutputSplits) {
+        // serialize the output splits
+        for (lid, splits) in output_splits {
+            let _ = redis::Cmd::set(format!("split:{}", lid), serde_json::to_string(&splits).unwrap_or_default()).exec(&mut self.connection);
+        }
+
+        // remove splits that do not exist anymore
+        let new_split_uuids = output_splits.keys().cloned().collect::<std::collections::HashSet<uuid::Uuid>>();
+        self.split_uuids.difference(&new_split_uuids).for_each(|lid| {
+            let _ = redis::Cmd::del(format!("split:{}", lid)).exec(&mut self.connection);
+        });
+
+        self.split_uuids = new_split_uuids;
+    }
+
     fn update_domain_info(&mut self, domain_info: &crate::domain_info::DomainInfo) {
         let _ = self.connection.set::<&str, &str, usize>("domain_info:domain_id", &domain_info.domain_id);
     }
@@ -458,6 +502,7 @@ impl super::proxy::Proxy for ProxyRedis {
             ("function_execution_time", &performance_samples.function_execution_times),
             ("function_stop_time", &performance_samples.function_stop_times),
             ("function_transfer_time", &performance_samples.function_transfer_times),
+            ("function_queue_depth", &performance_samples.function_queue_depths),
         ];
         for (name, series) in all_sample_series {
             for (function_id, values) in series {
@@ -673,26 +718,14 @@ impl super::proxy::Proxy for ProxyRedis {
     }
 
     fn fetch_performance_series(&mut self, node: &str, series: &str) -> crate::proxy::PerformanceSeries {
-        let mut ret = vec![];
-
-        if let Ok(values) =
-            self.connection
-                .zrangebyscore::<String, f64, f64, Vec<String>>(format!("performance:{node}:{series}"), f64::NEG_INFINITY, f64::INFINITY)
-        {
-            for value in values {
-                if let Some((timestamp, value)) = value.split_once(":")
-                    && let Ok(timestamp) = timestamp.parse::<f64>()
-                {
-                    let secs = timestamp as i64;
-                    let nsecs = (timestamp.fract() * 1e9) as u32;
-                    if let Some(date_time) = chrono::DateTime::from_timestamp(secs, nsecs) {
-                        ret.push((date_time, value.to_string()));
-                    }
-                }
-            }
-        }
+        self.fetch_performance_series_by_score(node, series, "-inf")
+    }
 
-        ret
+    fn fetch_performance_series_since(&mut self, node: &str, series: &str, since: &chrono::DateTime<chrono::Utc>) -> crate::proxy::PerformanceSeries {
+        // The samples are scored by their timestamp, hence only those in
+        // the window requested are returned by Redis.
+        let min = format!("({}.{:09}", since.timestamp(), since.timestamp_subsec_nanos());
+        self.fetch_performance_series_by_score(node, series, &min)
     }
 
     fn fetch_function_instance_requests(
@@ -797,6 +830,22 @@ impl super::proxy::Proxy for ProxyRedis {
         dependency_graph
     }
 
+    fn fetch_output_splits(&mut self) -> crate::proxy::OutputSplits {
+        let mut output_splits = std::collections::HashMap::new();
+        for split_key in self.connection.keys::<&str, Vec<String>>("split:*").unwrap_or(vec![]) {
+            let tokens: Vec<&str> = split_key.split(':').collect();
+            assert_eq!(tokens.len(), 2);
+            assert_eq!("split", tokens[0]);
+            if let Ok(lid) = uuid::Uuid::parse_str(tokens[1])
+                && let Ok(val) = self.connection.get::<&str, String>(&split_key)
+                && let Ok(val) = serde_json::from_str::<std::collections::HashMap<String, Vec<(uuid::Uuid, u32)>>>(&val)
+            {
+                output_splits.insert(lid, val);
+            }
+        }
+        output_splits
+    }
+
     fn fetch_logical_id_to_workflow_id(&mut self) -> std::collections::HashMap<edgeless_api::function_instance::ComponentId, String> {
         self.fetch_instances()
             .iter()
@@ -852,6 +901,7 @@ mod test {
             None => return,
         };
         assert!(redis_proxy.fetch_dependency_graph().is_empty());
+        assert!(redis_proxy.fetch_output_splits().is_empty());
         assert!(redis_proxy.fetch_function_instance_requests().is_empty());
         assert!(redis_proxy.fetch_function_instances_to_nodes().is_empty());
         assert!(redis_proxy.fetch_instances().is_empty());
@@ -1068,6 +1118,7 @@ mod test {
                 function_execution_times: std::collections::HashMap::from([(fid_perf_1, samples_1.clone()), (fid_perf_2, samples_2.clone())]),
                 function_stop_times: std::collections::HashMap::from([(fid_perf_1, samples_1.clone()), (fid_perf_2, samples_2.clone())]),
                 function_transfer_times: std::collections::HashMap::from([(fid_perf_1, samples_1.clone()), (fid_perf_2, samples_2.clone())]),
+                function_queue_depths: std::collections::HashMap::from([(fid_perf_1, samples_1.clone()), (fid_perf_2, samples_2.clone())]),
                 function_log_entries: std::collections::HashMap::from([(fid_perf_1, log_1.clone()), (fid_perf_2, log_2.clone())]),
             },
         );
@@ -1093,7 +1144,7 @@ mod test {
         let samples = redis_proxy.fetch_performance_samples();
 
         let entry = samples.get(&fid_perf_1.to_string()).unwrap();
-        assert_eq!(6, entry.len());
+        assert_eq!(7, entry.len());
         let actual_values = entry.get("function_instantiate_time").unwrap();
         assert_eq!(
             samples_1_values.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
@@ -1119,6 +1170,11 @@ mod test {
             samples_1_values.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
             actual_values.iter().map(|x| x.1.clone()).collect::<Vec<String>>()
         );
+        let actual_values = entry.get("function_queue_depth").unwrap();
+        assert_eq!(
+            samples_1_values.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
+            actual_values.iter().map(|x| x.1.clone()).collect::<Vec<String>>()
+        );
         let actual_values = entry.get("target").unwrap();
         assert_eq!(
             log_1_values.iter().map(|x| format!("value={}", x)).collect::<Vec<String>>(),
@@ -1126,7 +1182,7 @@ mod test {
         );
 
         let entry = samples.get(&fid_perf_2.to_string()).unwrap();
-        assert_eq!(6, entry.len());
+        assert_eq!(7, entry.len());
         let actual_values = entry.get("function_instantiate_time").unwrap();
         assert_eq!(
             samples_2_values.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
@@ -1152,6 +1208,11 @@ mod test {
             samples_2_values.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
             actual_values.iter().map(|x| x.1.clone()).collect::<Vec<String>>()
         );
+        let actual_values = entry.get("function_queue_depth").unwrap();
+        assert_eq!(
+            samples_2_values.iter().map(|x| x.to_string()).collect::<Vec<String>>(),
+            actual_values.iter().map(|x| x.1.clone()).collect::<Vec<String>>()
+        );
         let actual_values = entry.get("target").unwrap();
         assert_eq!(
             log_2_values.iter().map(|x| format!("value={}", x)).collect::<Vec<String>>(),
@@ -1252,6 +1313,35 @@ mod test {
         assert!(!redis_proxy.updated(crate::proxy::Category::DependencyGraph));
     }
 
+    #[serial_test::serial]
+    #[test]
+    fn test_redis_proxy_output_splits() {
+        let mut redis_proxy = match get_proxy() {
+            Some(redis_proxy) => redis_proxy,
+            None => return,
+        };
+
+        let mut output_splits = std::collections::HashMap::new();
+        for _ in 0..10 {
+            let mut splits = std::collections::HashMap::new();
+            for j in 0..2 {
+                splits.insert(format!("out-{}", j), vec![(uuid::Uuid::new_v4(), 90), (uuid::Uuid::new_v4(), 10)]);
+            }
+            output_splits.insert(uuid::Uuid::new_v4(), splits);
+        }
+
+        redis_proxy.update_output_splits(&output_splits);
+        assert_eq!(output_splits, redis_proxy.fetch_output_splits());
+
+        // Remove half of the splits.
+        let removed = output_splits.keys().take(5).cloned().collect::<Vec<uuid::Uuid>>();
+        for lid in removed {
+            output_splits.remove(&lid);
+        }
+        redis_proxy.update_output_splits(&output_splits);
+        assert_eq!(output_splits, redis_proxy.fetch_output_splits());
+    }
+
     #[serial_test::serial]
     #[test]
     fn test_redis_proxy_domain_info() {
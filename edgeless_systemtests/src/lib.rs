@@ -116,6 +116,11 @@ mod system_tests {
                         invocation_url_announced_coap: None,
                         node_register_url: node_register_url.clone(),
                         subscription_refresh_interval_sec: 5,
+                        call_timeout_ms: None,
+                        inbound_queue_capacity: 0,
+                        inbound_queue_overflow: edgeless_dataplane::inbound_queue::OverflowPolicy::default(),
+                        invocation_transport: edgeless_dataplane::core::InvocationTransport::default(),
+                        invocation_max_batch_size: None,
                     },
                     telemetry: edgeless_node::EdgelessNodeTelemetrySettings {
                         metrics_url: format!("http://{}:{}", address, next_port()),
@@ -126,6 +131,7 @@ mod system_tests {
                     resources,
                     user_node_capabilities: None,
                     power_info: None,
+                    state_management: None,
                 }));
                 tokio::spawn(task);
                 abort_handles_nodes.insert(node_id, handle);
@@ -218,6 +224,11 @@ mod system_tests {
                     invocation_url_announced_coap: None,
                     node_register_url: node_register_url_local,
                     subscription_refresh_interval_sec: 5,
+                    call_timeout_ms: None,
+                    inbound_queue_capacity: 0,
+                    inbound_queue_overflow: edgeless_dataplane::inbound_queue::OverflowPolicy::default(),
+                    invocation_transport: edgeless_dataplane::core::InvocationTransport::default(),
+                    invocation_max_batch_size: None,
                 },
                 portal: edgeless_node::EdgelessNodeGeneralSettings {
                     node_id: node_id_portal,
@@ -229,6 +240,11 @@ mod system_tests {
                     invocation_url_announced_coap: None,
                     node_register_url: node_register_url_portal.clone(),
                     subscription_refresh_interval_sec: 5,
+                    call_timeout_ms: None,
+                    inbound_queue_capacity: 0,
+                    inbound_queue_overflow: edgeless_dataplane::inbound_queue::OverflowPolicy::default(),
+                    invocation_transport: edgeless_dataplane::core::InvocationTransport::default(),
+                    invocation_max_batch_size: None,
                 },
                 telemetry: edgeless_node::EdgelessNodeTelemetrySettings {
                     metrics_url: format!("http://{}:{}", address, next_port()),
@@ -9,13 +9,14 @@ pub enum FunctionTime {
     Execution,
     Stop,
     Transfer,
+    QueueDepth,
 }
 
 pub type FunctionTimes = std::collections::HashMap<edgeless_api::function_instance::ComponentId, Vec<edgeless_api::node_registration::Sample>>;
 
 #[derive(Default)]
 pub struct Metrics {
-    pub function_times: [FunctionTimes; 5],
+    pub function_times: [FunctionTimes; 6],
     pub function_log_entries:
         std::collections::HashMap<edgeless_api::function_instance::ComponentId, Vec<edgeless_api::node_registration::FunctionLogEntry>>,
 }
@@ -42,14 +43,15 @@ impl crate::telemetry_events::EventProcessor for PerformanceTarget {
         event: &crate::telemetry_events::TelemetryEvent,
         event_tags: &std::collections::BTreeMap<String, String>,
     ) -> crate::telemetry_events::TelemetryProcessingResult {
-        let new_sample = |lat: &std::time::Duration| {
+        let new_value_sample = |value: f64| {
             let now = chrono::Utc::now();
             edgeless_api::node_registration::Sample {
                 timestamp_sec: now.timestamp(),
                 timestamp_ns: now.timestamp_subsec_nanos(),
-                sample: lat.as_secs_f64(),
+                sample: value,
             }
         };
+        let new_sample = |lat: &std::time::Duration| new_value_sample(lat.as_secs_f64());
 
         match event {
             crate::telemetry_events::TelemetryEvent::FunctionInstantiate(lat) => {
@@ -98,6 +100,16 @@ impl crate::telemetry_events::EventProcessor for PerformanceTarget {
                     res.push(new_sample(lat));
                 }
             }
+            crate::telemetry_events::TelemetryEvent::FunctionQueueDepth(depth) => {
+                if let Some(function_id) = event_tags.get("FUNCTION_ID")
+                    && let Ok(function_id) = uuid::Uuid::from_str(function_id)
+                {
+                    let res = self.metrics.function_times[FunctionTime::QueueDepth as usize]
+                        .entry(function_id)
+                        .or_default();
+                    res.push(new_value_sample(*depth as f64));
+                }
+            }
             crate::telemetry_events::TelemetryEvent::FunctionLogEntry(_lvl, target, message) => {
                 if let Some(function_id) = event_tags.get("FUNCTION_ID")
                     && let Ok(function_id) = uuid::Uuid::from_str(function_id)
@@ -186,6 +198,7 @@ mod tests {
         assert!(metrics.function_times[FunctionTime::Execution as usize].is_empty());
         assert!(metrics.function_times[FunctionTime::Stop as usize].is_empty());
         assert!(metrics.function_times[FunctionTime::Transfer as usize].is_empty());
+        assert!(metrics.function_times[FunctionTime::QueueDepth as usize].is_empty());
         assert!(metrics.function_log_entries.is_empty());
 
         let mut expected_instantiate = vec![];
@@ -193,6 +206,7 @@ mod tests {
         let mut expected_execution = vec![];
         let mut expected_stop = vec![];
         let mut expected_transfer = vec![];
+        let mut expected_queue_depth = vec![];
         let mut expected_log_entries = vec![];
         for i in 0..10 {
             expected_instantiate.push(i as f64 * 2.0);
@@ -200,6 +214,7 @@ mod tests {
             expected_execution.push(i as f64 * 4.0);
             expected_stop.push(i as f64 * 5.0);
             expected_transfer.push((1000 + i) as f64);
+            expected_queue_depth.push((i * 7) as f64);
             expected_log_entries.push((format!("target{}", i), format!("message{}", i)));
             target.handle(
                 &&crate::telemetry_events::TelemetryEvent::FunctionInstantiate(std::time::Duration::from_secs(
@@ -225,6 +240,10 @@ mod tests {
                 &crate::telemetry_events::TelemetryEvent::FunctionTransfer(std::time::Duration::from_secs(*expected_transfer.last().unwrap() as u64)),
                 &event_tags,
             );
+            target.handle(
+                &crate::telemetry_events::TelemetryEvent::FunctionQueueDepth(*expected_queue_depth.last().unwrap() as usize),
+                &event_tags,
+            );
             let log_target_message = expected_log_entries.last().cloned().unwrap();
             target.handle(
                 &crate::telemetry_events::TelemetryEvent::FunctionLogEntry(
@@ -253,6 +272,9 @@ mod tests {
         let samples = metrics.function_times[FunctionTime::Transfer as usize].get(&fid).cloned().unwrap();
         assert_eq!(expected_transfer, samples.iter().map(|x| x.sample).collect::<Vec<f64>>());
 
+        let samples = metrics.function_times[FunctionTime::QueueDepth as usize].get(&fid).cloned().unwrap();
+        assert_eq!(expected_queue_depth, samples.iter().map(|x| x.sample).collect::<Vec<f64>>());
+
         let log_entries = metrics.function_log_entries.get(&fid).cloned().unwrap();
         assert_eq!(
             expected_log_entries,
@@ -268,6 +290,7 @@ mod tests {
         assert!(metrics.function_times[FunctionTime::Execution as usize].is_empty());
         assert!(metrics.function_times[FunctionTime::Stop as usize].is_empty());
         assert!(metrics.function_times[FunctionTime::Transfer as usize].is_empty());
+        assert!(metrics.function_times[FunctionTime::QueueDepth as usize].is_empty());
         assert!(metrics.function_log_entries.is_empty());
     }
 }
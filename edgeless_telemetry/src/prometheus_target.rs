@@ -10,6 +10,9 @@ pub struct PrometheusEventTarget {
     function_count: prometheus_client::metrics::family::Family<RuntimeLabels, prometheus_client::metrics::gauge::Gauge>,
     execution_times: prometheus_client::metrics::family::Family<ExecutionLabels, prometheus_client::metrics::histogram::Histogram>,
     transfer_times: prometheus_client::metrics::family::Family<TransferLabels, prometheus_client::metrics::histogram::Histogram>,
+    queue_depths: prometheus_client::metrics::family::Family<TransferLabels, prometheus_client::metrics::gauge::Gauge>,
+    delivery_retries: prometheus_client::metrics::family::Family<TransferLabels, prometheus_client::metrics::counter::Counter>,
+    delivery_failures: prometheus_client::metrics::family::Family<TransferLabels, prometheus_client::metrics::counter::Counter>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, prometheus_client::encoding::EncodeLabelSet)]
@@ -69,9 +72,16 @@ impl PrometheusEventTarget {
                 },
             );
 
+        let queue_depths = prometheus_client::metrics::family::Family::<TransferLabels, prometheus_client::metrics::gauge::Gauge>::default();
+        let delivery_retries = prometheus_client::metrics::family::Family::<TransferLabels, prometheus_client::metrics::counter::Counter>::default();
+        let delivery_failures = prometheus_client::metrics::family::Family::<TransferLabels, prometheus_client::metrics::counter::Counter>::default();
+
         registry.lock().await.register("function_count", "", function_count.clone());
         registry.lock().await.register("execution_times", "", execution_times.clone());
         registry.lock().await.register("transfer_times", "", transfer_times.clone());
+        registry.lock().await.register("queue_depths", "", queue_depths.clone());
+        registry.lock().await.register("delivery_retries", "", delivery_retries.clone());
+        registry.lock().await.register("delivery_failures", "", delivery_failures.clone());
 
         let reg_clone = registry.clone();
         let socket_addr: std::net::SocketAddr = endpoint.parse().unwrap_or_else(|_| panic!("invalid endpoint: {}", &endpoint));
@@ -95,6 +105,9 @@ impl PrometheusEventTarget {
             function_count,
             execution_times,
             transfer_times,
+            queue_depths,
+            delivery_retries,
+            delivery_failures,
         }
     }
 }
@@ -160,6 +173,36 @@ impl crate::telemetry_events::EventProcessor for PrometheusEventTarget {
                         .observe(lat.as_secs_f64())
                 }
             }
+            crate::telemetry_events::TelemetryEvent::FunctionQueueDepth(depth) => {
+                if let (Some(node_id), Some(function_id)) = (event_tags.get("NODE_ID"), event_tags.get("FUNCTION_ID")) {
+                    self.queue_depths
+                        .get_or_create(&TransferLabels {
+                            node_id: node_id.to_string(),
+                            function_id: function_id.to_string(),
+                        })
+                        .set(*depth as i64);
+                }
+            }
+            crate::telemetry_events::TelemetryEvent::DeliveryRetry(_) => {
+                if let (Some(node_id), Some(function_id)) = (event_tags.get("NODE_ID"), event_tags.get("FUNCTION_ID")) {
+                    self.delivery_retries
+                        .get_or_create(&TransferLabels {
+                            node_id: node_id.to_string(),
+                            function_id: function_id.to_string(),
+                        })
+                        .inc();
+                }
+            }
+            crate::telemetry_events::TelemetryEvent::DeliveryFailure(_) => {
+                if let (Some(node_id), Some(function_id)) = (event_tags.get("NODE_ID"), event_tags.get("FUNCTION_ID")) {
+                    self.delivery_failures
+                        .get_or_create(&TransferLabels {
+                            node_id: node_id.to_string(),
+                            function_id: function_id.to_string(),
+                        })
+                        .inc();
+                }
+            }
             _ => {
                 return crate::telemetry_events::TelemetryProcessingResult::PASSED;
             }
@@ -58,6 +58,15 @@ pub enum TelemetryEvent {
     FunctionStop(std::time::Duration),
     FunctionExit(FunctionExitStatus),
     FunctionTransfer(std::time::Duration),
+    /// Number of events waiting in the inbound queue of a function instance
+    /// when it starts processing one.
+    FunctionQueueDepth(usize),
+    /// Failed attempt to deliver an event sent at least once, which will be
+    /// retried, with the number of attempts made so far.
+    DeliveryRetry(u32),
+    /// Event sent at least once that was dropped without being delivered,
+    /// with the number of attempts made.
+    DeliveryFailure(u32),
 }
 
 #[derive(Clone)]
@@ -37,6 +37,7 @@ impl EdgeFunction for CheckTemperatureFun {
                 CallRet::NoReply => panic!("should never happen"),
                 CallRet::Reply(res) => log::info!("all good!"),
                 CallRet::Err => log::error!("calling function to move_arm did not work"),
+                CallRet::Timeout => log::error!("calling function to move_arm timed out"),
             };
         } else if current_temperature < 40.0 {
             log::info!("check_temperature_fun: It's lower than 40 --> too cold! We need to take action -> forward event to move robotic arm function! Current temperature: {}", current_temperature);
@@ -45,6 +46,7 @@ impl EdgeFunction for CheckTemperatureFun {
                 CallRet::NoReply => panic!("should never happen"),
                 CallRet::Reply(res) => log::info!("all good!"),
                 CallRet::Err => log::error!("calling function to move_arm did not work"),
+                CallRet::Timeout => log::error!("calling function to move_arm timed out"),
             };
         } else {
             log::info!(
@@ -22,7 +22,20 @@ impl EdgeFunction for PingerFun {
             let id = STATE.get().unwrap().lock().unwrap().count;
 
             STATE.get().unwrap().lock().unwrap().count += 1;
-            sync(&serde_json::to_string(STATE.get().unwrap().lock().unwrap().deref()).unwrap().as_bytes());
+            // The state is serialized before the loop, so that the lock is
+            // not held while synchronizing it.
+            let mut serialized = serde_json::to_string(STATE.get().unwrap().lock().unwrap().deref()).unwrap();
+            while let Err(conflict) = try_sync(serialized.as_bytes()) {
+                // Another instance has written the state in the meanwhile:
+                // count this ping on top of it.
+                let current: PingerState = match conflict.current_state {
+                    Some(current_state) => serde_json::from_slice(&current_state).unwrap(),
+                    None => PingerState { count: 0 },
+                };
+                let mut state = STATE.get().unwrap().lock().unwrap();
+                state.count = current.count + 1;
+                serialized = serde_json::to_string(state.deref()).unwrap();
+            }
 
             let res = call("ponger", &format!("PING-{}", id).as_bytes());
             if let CallRet::Reply(msg) = res {
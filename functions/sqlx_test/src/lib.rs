@@ -5,25 +5,26 @@ use edgeless_function::*;
 
 struct SqlxTest;
 
-#[derive(serde::Serialize, serde::Deserialize, Default)]
-struct WorkflowState {
-    id: String,
-    metadata: MyState,
-}
-
 #[derive(serde::Serialize, serde::Deserialize, Default)]
 struct MyState {
     foo: f32,
     bar: String,
 }
 
-fn call_wrapper(msg: &str) -> Option<WorkflowState> {
-    match call("database", msg.as_bytes()) {
+#[derive(serde::Deserialize, Default)]
+struct SqlxResult {
+    rows: Vec<std::collections::HashMap<String, serde_json::Value>>,
+}
+
+/// Execute a prepared statement of the database resource and return the
+/// rows of the result, if successful.
+fn call_wrapper(statement: &str, params: serde_json::Value) -> Option<SqlxResult> {
+    let request = serde_json::json!({"statement": statement, "params": params});
+    match call("database", request.to_string().as_bytes()) {
         CallRet::Reply(msg) => {
             let reply = std::str::from_utf8(&msg).unwrap_or("not UTF8");
             log::info!("Got Reply: {}", reply);
-            let cur_state: WorkflowState = serde_json::from_str(reply).unwrap_or_default();
-            Some(cur_state)
+            Some(serde_json::from_str(reply).unwrap_or_default())
         }
         CallRet::NoReply => {
             log::warn!("Received empty reply from the database");
@@ -33,6 +34,10 @@ fn call_wrapper(msg: &str) -> Option<WorkflowState> {
             log::error!("Error when calling the database");
             None
         }
+        CallRet::Timeout => {
+            log::error!("Timeout when calling the database");
+            None
+        }
     }
 }
 
@@ -41,19 +46,17 @@ impl EdgeFunction for SqlxTest {
         let new_value = core::str::from_utf8(message).unwrap().parse::<f32>().unwrap_or_default();
 
         // Only update the state if an old one exists.
-        if let Some(old_state) = call_wrapper("SELECT id, metadata FROM WorkflowState WHERE id=$1") {
-            log::info!("wf_id {}, old value: {}, new value: {}", old_state.id, old_state.metadata.foo, new_value);
+        let old_state = call_wrapper("get_state", serde_json::json!([]))
+            .and_then(|result| result.rows.into_iter().next())
+            .and_then(|row| row.get("metadata").and_then(|x| x.as_str()).map(|x| x.to_string()))
+            .and_then(|metadata| serde_json::from_str::<MyState>(&metadata).ok());
+        if let Some(old_state) = old_state {
+            log::info!("old value: {}, new value: {}", old_state.foo, new_value);
             let new_state = MyState {
                 foo: new_value,
                 bar: String::from("subsequent"),
             };
-            call_wrapper(
-                format!(
-                    "UPDATE WorkflowState SET metadata='{}'  WHERE id = $1",
-                    serde_json::to_string(&new_state).unwrap_or_default()
-                )
-                .as_str(),
-            );
+            call_wrapper("update_state", serde_json::json!([serde_json::to_string(&new_state).unwrap_or_default()]));
         }
     }
 
@@ -77,11 +80,8 @@ impl EdgeFunction for SqlxTest {
         };
 
         call_wrapper(
-            format!(
-                "INSERT INTO WorkflowState (id, metadata) Values($1, '{}')",
-                serde_json::to_string(&initial_state).unwrap_or_default()
-            )
-            .as_str(),
+            "insert_state",
+            serde_json::json!([serde_json::to_string(&initial_state).unwrap_or_default()]),
         );
     }
 
@@ -92,7 +92,7 @@ impl EdgeFunction for SqlxTest {
         // no way to enforce a specific sequence when terminating instances
         // in a workflow).
         log::info!("Try to clean database");
-        call_wrapper("DELETE FROM WorkflowState WHERE id=$1");
+        call_wrapper("delete_state", serde_json::json!([]));
     }
 }
 
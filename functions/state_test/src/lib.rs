@@ -11,7 +11,9 @@ impl EdgeFunction for StateTest {
     fn handle_cast(_src: InstanceId, encoded_message: &[u8]) {
         match core::str::from_utf8(encoded_message).unwrap() {
             "test_cast_raw_output" => {
-                sync("new_state".as_bytes());
+                if try_sync("new_state".as_bytes()).is_err() {
+                    log::info!("state_conflict");
+                }
             }
             _ => {
                 log::info!("Unprocessed Message");
@@ -13,9 +13,9 @@ function echo_g(){ echo -e "${CLR_GREEN}$*${CLR_RST}"; }
 function echo_y(){ echo -e "${CLR_YELLOW}$*${CLR_RST}"; }
 # =========================================================== 
 
-artifacts="build.log build_functions.log edgeless_con.log edgeless_orc.log edgeless_node.log my-local-file.log out.log reading-errors.log skipped.log err.log metric.log sqlite.db"
+artifacts="build.log build_functions.log edgeless_con.log edgeless_orc.log edgeless_node.log my-local-file.log out.log reading-errors.log skipped.log err.log metric.log object-store.log timer.log ws-ingress.log sqlite.db"
 confs="controller.toml orchestrator.toml node.toml cli.toml"
-specialized_workflows="container dda_demo dda_test esp32_resources redis vector_mul matrix_mul ollama kafka_egress serverless"
+specialized_workflows="container dda_demo dda_test esp32_resources redis vector_mul matrix_mul ollama kafka_egress kafka_ingress mqtt grpc_egress serverless"
 
 echo_y "> Checking for existing files"
 existing_files=""